@@ -0,0 +1,24 @@
+# Messages used by the renderers, in German.
+
+button-copy-clipboard = In die Zwischenablage kopieren
+collapsible-open = + Block öffnen
+collapsible-hide = - Block ausblenden
+table-of-contents = Inhaltsverzeichnis
+footnote = Fußnote
+footnote-block-title = Fußnoten
+footnote-backlink = Zurück zum Verweis
+bibliography-reference = Referenz
+bibliography-block-title = Literaturverzeichnis
+bibliography-cite-not-found = Literaturangabe nicht gefunden
+image-context-bad = Keine Bilder in diesem Kontext
+media-context-bad = Keine Medien in diesem Kontext
+iframe-blocked = Eingebettete Inhalte von dieser Website sind nicht erlaubt
+element-blocked = Dieser Inhalt ist hier nicht erlaubt
+breadcrumbs = Brotkrümelnavigation
+rating = Bewertung
+rate-up = Gefällt mir
+rate-down = Gefällt mir nicht
+rate-cancel = Meine Stimme zurückziehen
+quote-wrote = schrieb:
+user-missing = Dieser Benutzer existiert nicht
+html-placeholder = HTML-Inhalt
@@ -0,0 +1,27 @@
+# Messages used by the renderers, in English.
+#
+# These are also used for any language without its own messages,
+# and for any messages missing from another language.
+
+button-copy-clipboard = Copy to Clipboard
+collapsible-open = + open block
+collapsible-hide = - hide block
+table-of-contents = Table of Contents
+footnote = Footnote
+footnote-block-title = Footnotes
+footnote-backlink = Back to reference
+bibliography-reference = Reference
+bibliography-block-title = Bibliography
+bibliography-cite-not-found = Bibliography item not found
+image-context-bad = No images in this context
+media-context-bad = No media in this context
+iframe-blocked = Embedded content from this site is not permitted
+element-blocked = This content is not permitted here
+breadcrumbs = Breadcrumbs
+rating = rating
+rate-up = I like it
+rate-down = I don't like it
+rate-cancel = Cancel my vote
+quote-wrote = wrote:
+user-missing = This user does not exist
+html-placeholder = HTML content
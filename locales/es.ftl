@@ -0,0 +1,24 @@
+# Messages used by the renderers, in Spanish.
+
+button-copy-clipboard = Copiar al portapapeles
+collapsible-open = + abrir bloque
+collapsible-hide = - ocultar bloque
+table-of-contents = Índice
+footnote = Nota al pie
+footnote-block-title = Notas al pie
+footnote-backlink = Volver a la referencia
+bibliography-reference = Referencia
+bibliography-block-title = Bibliografía
+bibliography-cite-not-found = Elemento de bibliografía no encontrado
+image-context-bad = No se permiten imágenes en este contexto
+media-context-bad = No se permiten medios en este contexto
+iframe-blocked = No se permite contenido incrustado de este sitio
+element-blocked = Este contenido no está permitido aquí
+breadcrumbs = Migas de pan
+rating = valoración
+rate-up = Me gusta
+rate-down = No me gusta
+rate-cancel = Cancelar mi voto
+quote-wrote = escribió:
+user-missing = Este usuario no existe
+html-placeholder = Contenido HTML
@@ -0,0 +1,24 @@
+# Messages used by the renderers, in French.
+
+button-copy-clipboard = Copier dans le presse-papiers
+collapsible-open = + afficher le bloc
+collapsible-hide = - masquer le bloc
+table-of-contents = Table des matières
+footnote = Note de bas de page
+footnote-block-title = Notes de bas de page
+footnote-backlink = Retour à la référence
+bibliography-reference = Référence
+bibliography-block-title = Bibliographie
+bibliography-cite-not-found = Élément de bibliographie introuvable
+image-context-bad = Aucune image dans ce contexte
+media-context-bad = Aucun média dans ce contexte
+iframe-blocked = Le contenu intégré provenant de ce site n'est pas autorisé
+element-blocked = Ce contenu n'est pas autorisé ici
+breadcrumbs = Fil d'Ariane
+rating = note
+rate-up = J'aime
+rate-down = Je n'aime pas
+rate-cancel = Annuler mon vote
+quote-wrote = a écrit :
+user-missing = Cet utilisateur n'existe pas
+html-placeholder = Contenu HTML
@@ -22,6 +22,8 @@ BLOCK_RULE_REGEX = re.compile(
     accepts_star: (true|false),
     accepts_score: (true|false),
     accepts_newlines: (true|false),
+    head: BlockHead::\w+,
+    body: BlockBody::\w+,
     parse_fn.*
 \};"""
 )
@@ -0,0 +1,421 @@
+/*
+ * audit.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Capturing an audit record of a parse and render run.
+//!
+//! When a user reports that a page renders incorrectly, it is helpful to have
+//! the full context of how that render happened: which settings were used,
+//! what limits were hit, which external lookups were made, and how long each
+//! step took. [`audit()`] runs the full pipeline and produces an [`AuditLog`]
+//! alongside the regular output, which can be serialized to JSON and attached
+//! to the report.
+
+use crate::data::PageInfo;
+use crate::info;
+use crate::parsing::{ParseError, ParseErrorKind};
+use crate::render::{metrics, Render};
+use crate::settings::WikitextSettings;
+use std::fmt::{self, Debug};
+use std::mem;
+use std::sync::{Arc, Mutex, PoisonError};
+use strum_macros::IntoStaticStr;
+
+/// A structured record of one parse and render run.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct AuditLog {
+    /// The version of ftml which performed this run.
+    pub version: String,
+
+    /// The site the page being rendered is on.
+    pub site: String,
+
+    /// The slug of the page being rendered.
+    pub page: String,
+
+    /// The settings used for parsing and rendering.
+    pub settings: WikitextSettings,
+
+    /// The length of the wikitext input, in bytes.
+    pub wikitext_len: usize,
+
+    /// All of the warnings produced by the parser.
+    pub warnings: Vec<ParseError>,
+
+    /// Which parser limits were hit during this run, if any.
+    ///
+    /// Each limit is listed once, in the order it was first hit.
+    pub limits_hit: Vec<ParseErrorKind>,
+
+    /// Each external lookup made via the renderer handle, in order.
+    pub lookups: Vec<AuditLookup>,
+
+    /// How long each step took.
+    pub timings: AuditTimings,
+}
+
+/// A lookup made to backend software during rendering.
+#[derive(Serialize, Deserialize, IntoStaticStr, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", tag = "type", content = "data")]
+#[strum(serialize_all = "kebab-case")]
+pub enum AuditLookup {
+    Module {
+        name: String,
+    },
+    PageTitle {
+        site: String,
+        page: String,
+    },
+    PageExists {
+        site: String,
+        page: String,
+    },
+    PageParents {
+        site: String,
+        page: String,
+    },
+    PageField {
+        site: String,
+        page: String,
+        field: String,
+    },
+    UserInfo {
+        name: String,
+    },
+    ImageLink {
+        source: String,
+    },
+    ImageDimensions {
+        source: String,
+    },
+    FileUrl {
+        site: String,
+        page: String,
+        file: String,
+    },
+    PostHtml {
+        length: usize,
+    },
+    PostCode {
+        index: usize,
+        length: usize,
+    },
+    PostCodeBlob {
+        index: usize,
+        blob: String,
+    },
+    LoadBlob {
+        blob: String,
+    },
+}
+
+impl AuditLookup {
+    /// The name of this kind of lookup, such as `page-exists`.
+    #[inline]
+    pub fn kind(&self) -> &'static str {
+        self.into()
+    }
+}
+
+/// Where the lookups made during an audited render are recorded.
+///
+/// [`audit()`] sets a new recorder in the settings it renders with,
+/// so lookups are recorded by the render itself, whichever thread it
+/// runs on. The default recorder does not record anything.
+///
+/// Recorders are not serialized, and all recorders compare as equal,
+/// since they do not affect the output of a render.
+#[derive(Clone, Default)]
+pub struct AuditRecorder(Option<Arc<Mutex<Vec<AuditLookup>>>>);
+
+impl AuditRecorder {
+    /// Creates a recorder which records lookups.
+    pub(crate) fn new() -> Self {
+        AuditRecorder(Some(Arc::default()))
+    }
+
+    #[inline]
+    pub fn is_recording(&self) -> bool {
+        self.0.is_some()
+    }
+
+    fn push(&self, lookup: AuditLookup) {
+        if let Some(ref lookups) = self.0 {
+            lookups
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(lookup);
+        }
+    }
+
+    /// Removes and returns all of the lookups recorded so far.
+    pub(crate) fn take(&self) -> Vec<AuditLookup> {
+        match self.0 {
+            Some(ref lookups) => {
+                mem::take(&mut *lookups.lock().unwrap_or_else(PoisonError::into_inner))
+            }
+            None => Vec::new(),
+        }
+    }
+}
+
+impl Debug for AuditRecorder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("AuditRecorder")
+            .field(&self.is_recording())
+            .finish()
+    }
+}
+
+impl PartialEq for AuditRecorder {
+    #[inline]
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for AuditRecorder {}
+
+/// The duration of each step of the run, in microseconds.
+///
+/// On WebAssembly there is no monotonic clock available,
+/// so these are always zero.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct AuditTimings {
+    pub preprocess: u64,
+    pub tokenize: u64,
+    pub parse: u64,
+    pub render: u64,
+}
+
+/// Runs the preprocessor, tokenizer, parser, and renderer, recording an audit log.
+///
+/// Any includes should already be substituted in `wikitext` before calling this.
+pub fn audit<R: Render>(
+    renderer: &R,
+    wikitext: &str,
+    page_info: &PageInfo,
+    settings: &WikitextSettings,
+) -> (R::Output, AuditLog) {
+    info!(
+        "Running audited render (site {}, page {})",
+        page_info.site.as_ref(),
+        page_info.page.as_ref(),
+    );
+
+    let mut text = str!(wikitext);
+    let (_, preprocess) = time(|| crate::preprocess_with_settings(&mut text, settings));
+    let (tokenization, tokenize) = time(|| crate::tokenize(&text));
+    let (outcome, parse) = time(|| crate::parse(&tokenization, page_info, settings));
+    let (tree, warnings) = outcome.into();
+
+    // Render with a recorder for handle calls
+    let recorder = AuditRecorder::new();
+    let render_settings = WikitextSettings {
+        audit: recorder.clone(),
+        ..settings.clone()
+    };
+
+    let (output, render) = time(|| renderer.render(&tree, page_info, &render_settings));
+    let lookups = recorder.take();
+    let timings = AuditTimings {
+        preprocess,
+        tokenize,
+        parse,
+        render,
+    };
+
+    // Build audit log
+    let limits_hit = limits_hit(&warnings);
+    let log = AuditLog {
+        version: info::VERSION.clone(),
+        site: str!(page_info.site),
+        page: str!(page_info.page),
+        settings: settings.clone(),
+        wikitext_len: wikitext.len(),
+        warnings,
+        limits_hit,
+        lookups,
+        timings,
+    };
+
+    (output, log)
+}
+
+/// Records an external lookup, if an audited render is running.
+///
+/// This is also counted in any [`RenderMetrics`](crate::render::RenderMetrics)
+/// being recorded. The closure is only invoked when recording, so building
+/// the lookup has no cost otherwise.
+pub(crate) fn record_lookup<F>(recorder: &AuditRecorder, f: F)
+where
+    F: FnOnce() -> AuditLookup,
+{
+    let auditing = recorder.is_recording();
+    let measuring = metrics::is_recording();
+
+    if !auditing && !measuring {
+        return;
+    }
+
+    let lookup = f();
+
+    if measuring {
+        metrics::record_handle_call(lookup.kind());
+    }
+
+    if auditing {
+        recorder.push(lookup);
+    }
+}
+
+/// Lists each limit hit by these warnings once, in the order first hit.
+fn limits_hit(warnings: &[ParseError]) -> Vec<ParseErrorKind> {
+    let mut limits_hit = Vec::new();
+
+    for kind in warnings.iter().map(|warning| warning.kind()) {
+        if is_limit(kind) && !limits_hit.contains(&kind) {
+            limits_hit.push(kind);
+        }
+    }
+
+    limits_hit
+}
+
+fn is_limit(kind: ParseErrorKind) -> bool {
+    matches!(
+        kind,
+        ParseErrorKind::RecursionDepthExceeded
+            | ParseErrorKind::LimitExceeded
+            | ParseErrorKind::TimeLimitExceeded
+            | ParseErrorKind::Cancelled
+            | ParseErrorKind::ListDepthExceeded
+            | ParseErrorKind::BlockquoteDepthExceeded,
+    )
+}
+
+cfg_if! {
+    if #[cfg(target_arch = "wasm32")] {
+        pub(crate) fn time<T, F: FnOnce() -> T>(f: F) -> (T, u64) {
+            (f(), 0)
+        }
+    } else {
+        pub(crate) fn time<T, F: FnOnce() -> T>(f: F) -> (T, u64) {
+            use std::time::Instant;
+
+            let start = Instant::now();
+            let value = f();
+            let elapsed = start.elapsed().as_micros().try_into().unwrap_or(u64::MAX);
+            (value, elapsed)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "html"))]
+#[test]
+fn audit_log() {
+    use crate::render::html::HtmlRender;
+    use crate::settings::WikitextMode;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let (output, log) = audit(
+        &HtmlRender,
+        "[[[missing]]] [[user aismallard]] [[module Backlinks]]",
+        &page_info,
+        &settings,
+    );
+
+    assert!(!output.body.is_empty());
+    assert_eq!(log.page, "some-page");
+    assert_eq!(log.settings, settings);
+    assert!(log.limits_hit.is_empty());
+    assert_eq!(
+        log.lookups,
+        vec![
+            AuditLookup::PageExists {
+                site: str!("sandbox"),
+                page: str!("missing"),
+            },
+            AuditLookup::UserInfo {
+                name: str!("aismallard"),
+            },
+            AuditLookup::Module {
+                name: str!("Backlinks"),
+            },
+        ],
+    );
+
+    // Nothing is recorded outside of an audited run
+    let recorder = AuditRecorder::default();
+    record_lookup(&recorder, || AuditLookup::PostHtml { length: 0 });
+    assert!(recorder.take().is_empty());
+
+    // Lookups are recorded by the render, even on another thread
+    let recorder = AuditRecorder::new();
+    let settings = WikitextSettings {
+        audit: recorder.clone(),
+        ..settings
+    };
+    let tokens = crate::tokenize("[[user aismallard]]");
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    std::thread::scope(|scope| {
+        scope.spawn(|| HtmlRender.render(&tree, &page_info, &settings));
+    });
+    assert_eq!(
+        recorder.take(),
+        vec![AuditLookup::UserInfo {
+            name: str!("aismallard"),
+        }],
+    );
+
+    let _ = serde_json::to_string(&log).expect("Unable to serialize audit log");
+}
+
+#[test]
+fn limits_hit_once() {
+    let warnings = [
+        "list-depth-exceeded",
+        "no-rules-match",
+        "limit-exceeded",
+        "list-depth-exceeded",
+        "limit-exceeded",
+    ]
+    .into_iter()
+    .map(|kind| {
+        serde_json::from_value(serde_json::json!({
+            "token": "other",
+            "rule": "text",
+            "span": { "start": 0, "end": 0 },
+            "kind": kind,
+        }))
+        .expect("Unable to deserialize parse error")
+    })
+    .collect::<Vec<ParseError>>();
+
+    assert_eq!(
+        limits_hit(&warnings),
+        vec![
+            ParseErrorKind::ListDepthExceeded,
+            ParseErrorKind::LimitExceeded,
+        ],
+    );
+}
@@ -0,0 +1,449 @@
+/*
+ * cache.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Caching of parse results, for repeated requests with the same source.
+//!
+//! Servers rendering previews will often receive the same wikitext many times
+//! in a row, and tokenizing and parsing it again each time is wasted work.
+//! [`ParseCache`] memoizes the owned result of a parse, keyed by a hash of the
+//! source, page information, and settings. The full input is stored alongside
+//! each result and compared on lookup, so hash collisions are never returned.
+//!
+//! Where results are kept is up to the [`CacheStorage`] implementation, so
+//! this can be backed by an LRU cache, an external store like Redis, or the
+//! simple bounded [`MemoryStorage`] provided here.
+//!
+//! This module is only available with the `cache` feature.
+
+use crate::data::PageInfo;
+use crate::info;
+use crate::parsing::{ParseErrorKind, ParseOutcome};
+use crate::settings::WikitextSettings;
+use crate::tree::SyntaxTree;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::{Mutex, PoisonError};
+
+/// The owned result of a parse, as stored in the cache.
+pub type CachedParse = ParseOutcome<SyntaxTree<'static>>;
+
+/// A parse result, along with the input it was produced from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct CacheEntry {
+    /// The wikitext which was parsed.
+    pub text: String,
+
+    /// The page information and settings used, see [`ParseCache::context()`].
+    pub context: String,
+
+    /// The result of parsing.
+    pub outcome: CachedParse,
+}
+
+/// Storage backend for [`ParseCache`].
+///
+/// Methods take `&self` so that one cache may be shared between threads,
+/// implementations should use interior mutability as needed.
+///
+/// Since entries are serializable, external stores may keep them as JSON.
+/// This includes the bodies of code blocks stored out-of-line.
+pub trait CacheStorage {
+    /// Fetches the entry stored for this key, if any.
+    fn get(&self, key: u64) -> Option<CacheEntry>;
+
+    /// Stores the entry for this key, replacing any existing entry.
+    fn insert(&self, key: u64, entry: &CacheEntry);
+}
+
+/// Memoizes parse results using the given storage.
+#[derive(Debug)]
+pub struct ParseCache<S> {
+    storage: S,
+}
+
+impl<S: CacheStorage> ParseCache<S> {
+    #[inline]
+    pub fn new(storage: S) -> Self {
+        ParseCache { storage }
+    }
+
+    #[inline]
+    pub fn storage(&self) -> &S {
+        &self.storage
+    }
+
+    /// Produces a canonical encoding of the page information and settings.
+    ///
+    /// This also includes the ftml version, so that a shared store is not
+    /// affected by results from other versions.
+    ///
+    /// Neither structure implements `Hash`, so their serialized forms are used.
+    /// These are converted to JSON values first, which sorts the keys of any maps,
+    /// so equal settings always have the same encoding.
+    pub fn context(page_info: &PageInfo, settings: &WikitextSettings) -> String {
+        let context = serde_json::json!({
+            "version": &*info::VERSION,
+            "page-info": page_info,
+            "settings": settings,
+        });
+
+        serde_json::to_string(&context).expect("Unable to serialize cache context")
+    }
+
+    /// Computes the cache key for the given input.
+    ///
+    /// The hash is not guaranteed to be stable across Rust versions.
+    pub fn key(text: &str, page_info: &PageInfo, settings: &WikitextSettings) -> u64 {
+        Self::key_with_context(text, &Self::context(page_info, settings))
+    }
+
+    fn key_with_context(text: &str, context: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        context.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Tokenizes and parses the given text, or returns the stored result
+    /// if this input has been seen before.
+    ///
+    /// The text is expected to already be preprocessed.
+    pub fn parse(
+        &self,
+        text: &str,
+        page_info: &PageInfo,
+        settings: &WikitextSettings,
+    ) -> CachedParse {
+        let context = Self::context(page_info, settings);
+        let key = Self::key_with_context(text, &context);
+
+        match self.storage.get(key) {
+            Some(entry) if entry.text == text && entry.context == context => {
+                debug!("Found cached parse result (key {key:016x})");
+                return entry.outcome;
+            }
+            Some(_) => warn!("Cache key collision, parsing (key {key:016x})"),
+            None => debug!("No cached parse result, parsing (key {key:016x})"),
+        }
+
+        let tokens = crate::tokenize(text);
+        let outcome = crate::parse(&tokens, page_info, settings);
+        let entry = CacheEntry {
+            text: str!(text),
+            context,
+            outcome: outcome.map(|tree| tree.to_owned()),
+        };
+
+        if is_cacheable(&entry.outcome) {
+            self.storage.insert(key, &entry);
+        } else {
+            debug!("Parse result was cut short, not caching (key {key:016x})");
+        }
+
+        entry.outcome
+    }
+}
+
+/// Determines if this parse result can be reused for later requests.
+///
+/// Cancelled parses, and those which ran out of time, are cut short for
+/// reasons unrelated to the input. Storing them would return the truncated
+/// tree for every later request, even once the host is less busy.
+fn is_cacheable(outcome: &CachedParse) -> bool {
+    !outcome.errors().iter().any(|error| {
+        matches!(
+            error.kind(),
+            ParseErrorKind::Cancelled | ParseErrorKind::TimeLimitExceeded,
+        )
+    })
+}
+
+/// A simple in-memory [`CacheStorage`], holding a bounded number of results.
+///
+/// Once full, the oldest entry is evicted to make room for new ones.
+/// A panic while the lock is held does not poison the storage, since
+/// at worst an entry is left that won't be evicted in order.
+#[derive(Debug)]
+pub struct MemoryStorage {
+    capacity: usize,
+    inner: Mutex<MemoryStorageInner>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryStorageInner {
+    entries: HashMap<u64, CacheEntry>,
+    order: VecDeque<u64>,
+}
+
+impl MemoryStorage {
+    pub fn new(capacity: usize) -> Self {
+        MemoryStorage {
+            capacity,
+            inner: Mutex::new(MemoryStorageInner::default()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entries
+            .len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        inner.entries.clear();
+        inner.order.clear();
+    }
+}
+
+impl CacheStorage for MemoryStorage {
+    fn get(&self, key: u64) -> Option<CacheEntry> {
+        self.inner
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entries
+            .get(&key)
+            .cloned()
+    }
+
+    fn insert(&self, key: u64, entry: &CacheEntry) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        if inner.entries.insert(key, entry.clone()).is_none() {
+            inner.order.push_back(key);
+        }
+
+        while inner.order.len() > self.capacity {
+            if let Some(oldest) = inner.order.pop_front() {
+                inner.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+#[test]
+fn cache() {
+    use crate::settings::WikitextMode;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let cache = ParseCache::new(MemoryStorage::new(2));
+
+    let first = cache.parse("**apple**", &page_info, &settings);
+    assert_eq!(cache.storage().len(), 1);
+
+    // Same input, returned from the cache
+    let second = cache.parse("**apple**", &page_info, &settings);
+    assert_eq!(first, second);
+    assert_eq!(cache.storage().len(), 1);
+
+    // Different settings are a different entry
+    let draft = WikitextSettings::from_mode(WikitextMode::Draft);
+    assert_ne!(
+        ParseCache::<MemoryStorage>::key("**apple**", &page_info, &settings),
+        ParseCache::<MemoryStorage>::key("**apple**", &page_info, &draft),
+    );
+    cache.parse("**apple**", &page_info, &draft);
+    assert_eq!(cache.storage().len(), 2);
+
+    // Oldest entry is evicted
+    cache.parse("//banana//", &page_info, &settings);
+    assert_eq!(cache.storage().len(), 2);
+    let key = ParseCache::<MemoryStorage>::key("**apple**", &page_info, &settings);
+    assert!(cache.storage().get(key).is_none());
+}
+
+#[test]
+fn cache_key_stable() {
+    use crate::settings::WikitextMode;
+
+    let page_info = PageInfo::dummy();
+    let key = |settings: &WikitextSettings| {
+        ParseCache::<MemoryStorage>::key("**apple**", &page_info, settings)
+    };
+
+    // Settings built separately have the same key, despite their hash maps
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    for _ in 0..20 {
+        assert_eq!(
+            key(&settings),
+            key(&WikitextSettings::from_mode(WikitextMode::Page))
+        );
+    }
+
+    let cache = ParseCache::new(MemoryStorage::new(4));
+    cache.parse("**apple**", &page_info, &settings);
+    cache.parse(
+        "**apple**",
+        &page_info,
+        &WikitextSettings::from_mode(WikitextMode::Page),
+    );
+    assert_eq!(cache.storage().len(), 1);
+}
+
+#[test]
+fn cache_collision() {
+    use crate::settings::WikitextMode;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let cache = ParseCache::new(MemoryStorage::new(4));
+    let expected = cache.parse("//banana//", &page_info, &settings);
+
+    // Store another input's result under this key, as a collision would
+    let key = ParseCache::<MemoryStorage>::key("//banana//", &page_info, &settings);
+    let mut entry = cache.storage().get(key).unwrap();
+    entry.text = str!("**apple**");
+    entry.outcome = cache.parse("**apple**", &page_info, &settings);
+    cache.storage().insert(key, &entry);
+
+    assert_eq!(cache.parse("//banana//", &page_info, &settings), expected);
+}
+
+#[test]
+fn cache_cancelled() {
+    use crate::settings::{CancellationToken, WikitextMode};
+
+    let page_info = PageInfo::dummy();
+    let token = CancellationToken::new();
+    let settings = WikitextSettings {
+        cancellation: token.clone(),
+        ..WikitextSettings::from_mode(WikitextMode::Page)
+    };
+    let cache = ParseCache::new(MemoryStorage::new(4));
+
+    token.cancel();
+    let cancelled = cache.parse("**apple**", &page_info, &settings);
+    assert!(!cancelled.errors().is_empty());
+    assert!(cache.storage().is_empty());
+
+    // Later requests parse in full
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let outcome = cache.parse("**apple**", &page_info, &settings);
+    assert!(outcome.errors().is_empty());
+    assert_eq!(cache.storage().len(), 1);
+}
+
+#[test]
+fn cache_time_limit() {
+    use crate::settings::{ParseLimits, WikitextMode};
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings {
+        parse_limits: ParseLimits {
+            max_time_ms: Some(0),
+            ..ParseLimits::default()
+        },
+        ..WikitextSettings::from_mode(WikitextMode::Page)
+    };
+    let cache = ParseCache::new(MemoryStorage::new(4));
+
+    let outcome = cache.parse("**apple** //banana//", &page_info, &settings);
+    assert!(outcome
+        .errors()
+        .iter()
+        .any(|error| error.kind() == ParseErrorKind::TimeLimitExceeded));
+    assert!(cache.storage().is_empty());
+
+    // Deterministic limits are still cached
+    let settings = WikitextSettings {
+        parse_limits: ParseLimits {
+            max_steps: Some(1),
+            ..ParseLimits::default()
+        },
+        ..WikitextSettings::from_mode(WikitextMode::Page)
+    };
+    let outcome = cache.parse("**apple** //banana//", &page_info, &settings);
+    assert!(outcome
+        .errors()
+        .iter()
+        .any(|error| error.kind() == ParseErrorKind::LimitExceeded));
+    assert_eq!(cache.storage().len(), 1);
+}
+
+#[test]
+fn cache_serialized() {
+    use crate::settings::WikitextMode;
+    use std::cell::RefCell;
+
+    /// Stores entries as JSON, as an external store would.
+    #[derive(Default)]
+    struct JsonStorage(RefCell<HashMap<u64, String>>);
+
+    impl CacheStorage for JsonStorage {
+        fn get(&self, key: u64) -> Option<CacheEntry> {
+            let entries = self.0.borrow();
+            let json = entries.get(&key)?;
+            Some(serde_json::from_str(json).expect("Unable to deserialize entry"))
+        }
+
+        fn insert(&self, key: u64, entry: &CacheEntry) {
+            let json = serde_json::to_string(entry).expect("Unable to serialize entry");
+            self.0.borrow_mut().insert(key, json);
+        }
+    }
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings {
+        code_blob_threshold: Some(10),
+        ..WikitextSettings::from_mode(WikitextMode::Page)
+    };
+    let cache = ParseCache::new(JsonStorage::default());
+    let input = "[[code]]\nA body which is stored out-of-line\n[[/code]]";
+
+    let first = cache.parse(input, &page_info, &settings);
+    assert_eq!(first.value().code_blobs.len(), 1);
+
+    // Code block bodies survive the round trip
+    let second = cache.parse(input, &page_info, &settings);
+    assert_eq!(first, second);
+}
+
+#[test]
+fn cache_poisoned() {
+    use std::panic;
+
+    let storage = MemoryStorage::new(2);
+
+    // Poison the lock by panicking while it's held
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        let _guard = storage.inner.lock().unwrap();
+        panic!("Panicking while holding the cache lock");
+    }));
+    assert!(result.is_err());
+    assert!(storage.inner.is_poisoned());
+
+    // The storage is still usable afterwards
+    assert!(storage.is_empty());
+    assert!(storage.get(0).is_none());
+    storage.clear();
+}
@@ -19,14 +19,43 @@
  */
 
 use crate::data::PageRef;
+use crate::tree::visit::{walk_element, walk_tree};
+use crate::tree::{Element, ImageSource, LinkLocation, LinkType, SyntaxTree, Visitor};
+use crate::url::is_url;
 use std::borrow::Cow;
+use std::ops::Range;
 
+/// The outgoing references from a page.
+///
+/// Each list has one entry per occurrence, so repeated references
+/// to the same target appear multiple times.
 #[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq, Default)]
 #[serde(rename_all = "kebab-case")]
 pub struct Backlinks<'a> {
     pub included_pages: Vec<PageRef<'a>>,
     pub internal_links: Vec<PageRef<'a>>,
     pub external_links: Vec<Cow<'a, str>>,
+
+    /// Links produced from interwiki prefixes, such as `[!wp:Apple]`.
+    #[serde(default)]
+    pub interwiki_links: Vec<Cow<'a, str>>,
+
+    /// Attached files which are embedded, such as with `[[image]]`,
+    /// `[[gallery]]`, `[[audio]]`, or `[[video]]`.
+    #[serde(default)]
+    pub file_references: Vec<FileReference<'a>>,
+
+    /// Users mentioned with `[[user]]` or `[[*user]]`.
+    #[serde(default)]
+    pub mentions: Vec<UserMention<'a>>,
+
+    /// Where in the source each reference occurs, if known.
+    ///
+    /// This is only populated by [`extract_backlinks()`], and only for
+    /// references within containers which have spans recorded.
+    /// See the `record_spans` setting.
+    #[serde(default)]
+    pub spans: Vec<BacklinkSpan>,
 }
 
 impl<'a> Backlinks<'a> {
@@ -34,4 +63,390 @@ impl<'a> Backlinks<'a> {
     pub fn new() -> Self {
         Backlinks::default()
     }
+
+    /// Returns how many references there are of the given type.
+    pub fn count(&self, btype: BacklinkType) -> usize {
+        match btype {
+            BacklinkType::Include => self.included_pages.len(),
+            BacklinkType::InternalLink => self.internal_links.len(),
+            BacklinkType::ExternalLink => self.external_links.len(),
+            BacklinkType::Interwiki => self.interwiki_links.len(),
+            BacklinkType::File => self.file_references.len(),
+            BacklinkType::Mention => self.mentions.len(),
+        }
+    }
+
+    /// Records an outgoing link, returning which list it was added to.
+    ///
+    /// Links within the same page, such as anchors, are not recorded.
+    /// This is shared by [`extract_backlinks()`] and the HTML renderer,
+    /// so that both classify links the same way.
+    pub(crate) fn add_link(
+        &mut self,
+        ltype: LinkType,
+        link: &LinkLocation<'a>,
+    ) -> Option<BacklinkType> {
+        // TODO: set to internal link if domain matches site
+        // See https://scuttle.atlassian.net/browse/WJ-24
+
+        match (ltype, link) {
+            (LinkType::Anchor | LinkType::TableOfContents, _) => None,
+            (LinkType::Interwiki, LinkLocation::Url(url)) => {
+                self.interwiki_links.push(url.clone());
+                Some(BacklinkType::Interwiki)
+            }
+            (_, LinkLocation::Page(page_ref)) => {
+                let page_ref = PageRef {
+                    site: page_ref.site.clone(),
+                    page: Cow::Borrowed(strip_anchor(page_ref.page())),
+                };
+
+                self.internal_links.push(page_ref.to_normalized());
+                Some(BacklinkType::InternalLink)
+            }
+            (_, LinkLocation::Url(url)) => {
+                if url.starts_with('#') || url == "javascript:;" {
+                    return None;
+                }
+
+                // Also support [ links pointing to local pages.
+                // e.g. [/scp-001 SCP-001] in addition to [[[SCP-001]]].
+                let local = url.strip_prefix('/').unwrap_or(url);
+                if is_url(local) {
+                    self.external_links.push(url.clone());
+                    Some(BacklinkType::ExternalLink)
+                } else {
+                    let page_ref =
+                        PageRef::page_only(strip_anchor(local)).to_normalized();
+                    self.internal_links.push(page_ref);
+                    Some(BacklinkType::InternalLink)
+                }
+            }
+        }
+    }
+
+    fn push_span(&mut self, btype: BacklinkType, span: Option<&Range<usize>>) {
+        if let Some(span) = span {
+            self.spans.push(BacklinkSpan {
+                btype,
+                index: self.count(btype) - 1,
+                span: span.clone(),
+            });
+        }
+    }
+}
+
+/// Removes any anchor from a page name, since it refers to a section of that page.
+fn strip_anchor(page: &str) -> &str {
+    match page.split_once('#') {
+        Some((page, _)) => page,
+        None => page,
+    }
+}
+
+/// A file attached to a page.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct FileReference<'a> {
+    /// The page the file is attached to, or `None` for the current page.
+    pub page: Option<PageRef<'a>>,
+    pub file: Cow<'a, str>,
+}
+
+/// A user mentioned on a page.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct UserMention<'a> {
+    pub name: Cow<'a, str>,
+
+    /// Whether this was an avatar-style mention, that is, `[[*user]]`.
+    pub show_avatar: bool,
+}
+
+/// The kind of outgoing reference.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum BacklinkType {
+    Include,
+    InternalLink,
+    ExternalLink,
+    Interwiki,
+    File,
+    Mention,
+}
+
+/// The source location of a reference in [`Backlinks`].
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct BacklinkSpan {
+    /// Which list the reference is in.
+    #[serde(rename = "type")]
+    pub btype: BacklinkType,
+
+    /// The index of the reference within that list.
+    pub index: usize,
+
+    /// The span of the innermost container enclosing the reference.
+    pub span: Range<usize>,
+}
+
+/// Gathers all outgoing references from a syntax tree, without rendering it.
+///
+/// Note that `[[include-messy]]` is substituted before parsing, and so those
+/// includes are not visible here. Only `[[include-elements]]` is recorded.
+pub fn extract_backlinks<'t>(tree: &SyntaxTree<'t>) -> Backlinks<'t> {
+    let mut extractor = Extractor {
+        backlinks: Backlinks::new(),
+        spans: Vec::new(),
+    };
+
+    walk_tree(&mut extractor, tree);
+    extractor.backlinks
+}
+
+/// Gathers all users mentioned in a syntax tree, without rendering it.
+///
+/// Each mention is returned alongside the span of its innermost container,
+/// if `record_spans` was enabled while parsing. This is intended for
+/// triggering notifications, such as for forum posts.
+pub fn extract_mentions<'t>(
+    tree: &SyntaxTree<'t>,
+) -> Vec<(UserMention<'t>, Option<Range<usize>>)> {
+    let Backlinks {
+        mentions, spans, ..
+    } = extract_backlinks(tree);
+
+    let mut mentions: Vec<_> = mentions.into_iter().map(|user| (user, None)).collect();
+    for BacklinkSpan { btype, index, span } in spans {
+        if btype == BacklinkType::Mention {
+            mentions[index].1 = Some(span);
+        }
+    }
+
+    mentions
+}
+
+#[derive(Debug)]
+struct Extractor<'t> {
+    backlinks: Backlinks<'t>,
+    spans: Vec<Range<usize>>,
+}
+
+impl<'t> Extractor<'t> {
+    fn add_link(&mut self, ltype: LinkType, link: &LinkLocation<'t>) {
+        if let Some(btype) = self.backlinks.add_link(ltype, link) {
+            self.backlinks.push_span(btype, self.spans.last());
+        }
+    }
+
+    fn add_image(&mut self, source: &ImageSource<'t>) {
+        let backlinks = &mut self.backlinks;
+        let btype = match source {
+            ImageSource::Url(url) => {
+                backlinks.external_links.push(url.clone());
+                BacklinkType::ExternalLink
+            }
+            ImageSource::File1 { file } => {
+                backlinks.file_references.push(FileReference {
+                    page: None,
+                    file: file.clone(),
+                });
+                BacklinkType::File
+            }
+            ImageSource::File2 { page, file } => {
+                backlinks.file_references.push(FileReference {
+                    page: Some(PageRef::page_only(page.clone())),
+                    file: file.clone(),
+                });
+                BacklinkType::File
+            }
+            ImageSource::File3 { site, page, file } => {
+                backlinks.file_references.push(FileReference {
+                    page: Some(PageRef::page_and_site(site.clone(), page.clone())),
+                    file: file.clone(),
+                });
+                BacklinkType::File
+            }
+        };
+
+        backlinks.push_span(btype, self.spans.last());
+    }
+}
+
+impl<'t> Visitor<'t> for Extractor<'t> {
+    fn visit_element(&mut self, element: &Element<'t>) {
+        match element {
+            Element::Container(container) => {
+                if let Some(span) = container.span() {
+                    self.spans.push(span.clone());
+                    walk_element(self, element);
+                    self.spans.pop();
+                    return;
+                }
+            }
+            Element::Link { ltype, link, .. } => self.add_link(*ltype, link),
+            Element::Image { source, .. } => self.add_image(source),
+            Element::Gallery { images, .. } => {
+                for image in images {
+                    self.add_image(&image.source);
+                }
+            }
+            Element::Media { sources, .. } => {
+                for source in sources {
+                    self.add_image(source);
+                }
+            }
+            Element::User { name, show_avatar } => {
+                self.backlinks.mentions.push(UserMention {
+                    name: name.clone(),
+                    show_avatar: *show_avatar,
+                });
+                self.backlinks
+                    .push_span(BacklinkType::Mention, self.spans.last());
+            }
+            Element::Include { location, .. } => {
+                self.backlinks.included_pages.push(location.clone());
+                self.backlinks
+                    .push_span(BacklinkType::Include, self.spans.last());
+            }
+            _ => (),
+        }
+
+        walk_element(self, element);
+    }
+}
+
+#[test]
+fn extract() {
+    use crate::data::PageInfo;
+    use crate::render::{html::HtmlRender, Render};
+    use crate::settings::{WikitextMode, WikitextSettings};
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings {
+        record_spans: true,
+        ..WikitextSettings::from_mode(WikitextMode::Page)
+    };
+
+    let text = "[[[Apple]]] [/banana#top Banana] [[[apple#section]]]\n\n\
+                [https://example.com/ Example] [[[!wp:Cherry]]] [#top Top]\n\n\
+                [[image durian.png]] [[image /fruit/eggplant.png]]";
+    let tokens = crate::tokenize(text);
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let backlinks = extract_backlinks(&tree);
+
+    assert_eq!(
+        backlinks.internal_links,
+        vec![
+            PageRef::page_only("apple"),
+            PageRef::page_only("banana"),
+            PageRef::page_only("apple"),
+        ],
+    );
+    assert_eq!(backlinks.external_links, vec![cow!("https://example.com/")]);
+    assert_eq!(backlinks.count(BacklinkType::Interwiki), 1);
+    assert_eq!(
+        backlinks.file_references,
+        vec![
+            FileReference {
+                page: None,
+                file: cow!("durian.png"),
+            },
+            FileReference {
+                page: Some(PageRef::page_only("fruit")),
+                file: cow!("eggplant.png"),
+            },
+        ],
+    );
+    assert!(backlinks.included_pages.is_empty());
+
+    // Each reference is in a paragraph
+    assert_eq!(backlinks.spans.len(), 7);
+    assert_eq!(
+        backlinks.spans[0],
+        BacklinkSpan {
+            btype: BacklinkType::InternalLink,
+            index: 0,
+            span: 0..52,
+        },
+    );
+
+    // Rendering classifies links the same way
+    let output = HtmlRender.render(&tree, &page_info, &settings);
+    assert_eq!(output.backlinks.internal_links, backlinks.internal_links);
+    assert_eq!(output.backlinks.external_links, backlinks.external_links);
+    assert_eq!(output.backlinks.interwiki_links, backlinks.interwiki_links);
+}
+
+#[test]
+fn extract_media() {
+    use crate::data::PageInfo;
+    use crate::settings::{WikitextMode, WikitextSettings};
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let text = "[[gallery]]\n: apple.png\n: /fruit/banana.png\n[[/gallery]]\n\n\
+                [[audio /music/song.mp3]]\n\n\
+                [[video https://example.com/clip.mp4]]";
+    let tokens = crate::tokenize(text);
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let backlinks = extract_backlinks(&tree);
+
+    assert_eq!(
+        backlinks.file_references,
+        vec![
+            FileReference {
+                page: None,
+                file: cow!("apple.png"),
+            },
+            FileReference {
+                page: Some(PageRef::page_only("fruit")),
+                file: cow!("banana.png"),
+            },
+            FileReference {
+                page: Some(PageRef::page_only("music")),
+                file: cow!("song.mp3"),
+            },
+        ],
+    );
+    assert_eq!(
+        backlinks.external_links,
+        vec![cow!("https://example.com/clip.mp4")],
+    );
+}
+
+#[test]
+fn mentions() {
+    use crate::data::PageInfo;
+    use crate::render::{html::HtmlRender, Render};
+    use crate::settings::{WikitextMode, WikitextSettings};
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings {
+        record_spans: true,
+        ..WikitextSettings::from_mode(WikitextMode::ForumPost)
+    };
+
+    let text = "Thanks [[user aismallard]]!\n\n[[*user Bluesoul]] [[user aismallard]]";
+    let tokens = crate::tokenize(text);
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let mentions = extract_mentions(&tree);
+
+    let mention = |name, show_avatar| UserMention {
+        name: cow!(name),
+        show_avatar,
+    };
+
+    assert_eq!(
+        mentions,
+        vec![
+            (mention("aismallard", false), Some(0..27)),
+            (mention("Bluesoul", true), Some(29..67)),
+            (mention("aismallard", false), Some(29..67)),
+        ],
+    );
+
+    // The same mentions are recorded when rendering
+    let output = HtmlRender.render(&tree, &page_info, &settings);
+    assert_eq!(output.backlinks.mentions, extract_backlinks(&tree).mentions);
 }
@@ -0,0 +1,49 @@
+/*
+ * data/breadcrumb.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+/// One step in the navigation trail leading to the current page.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct Breadcrumb {
+    /// What this breadcrumb refers to.
+    #[serde(rename = "type")]
+    pub btype: BreadcrumbType,
+
+    /// The slug this breadcrumb refers to.
+    ///
+    /// For categories this is the category name, otherwise it is the full page slug.
+    pub slug: String,
+
+    /// The display title of this breadcrumb.
+    pub title: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum BreadcrumbType {
+    /// The category the current page is in.
+    Category,
+
+    /// A parent page of the current page.
+    Parent,
+
+    /// The current page itself.
+    Current,
+}
@@ -0,0 +1,136 @@
+/*
+ * data/links.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Checking which pages linked from a syntax tree exist.
+//!
+//! Hosts usually know which pages exist in bulk, such as from a single
+//! database query, so this pass gathers every page link in the tree and
+//! resolves each once. The result is then passed to
+//! [`HtmlRender::render_validated()`](crate::render::html::HtmlRender::render_validated),
+//! which marks links to missing pages with the Wikidot `newpage` class.
+
+use super::PageRef;
+use crate::tree::{Element, LinkLocation, SyntaxTree};
+use std::collections::HashMap;
+
+/// Determines whether pages exist, for [`validate_links()`].
+pub trait PageExistenceResolver {
+    /// Whether the given page exists.
+    ///
+    /// References without a site are to the current site.
+    fn page_exists(&self, page_ref: &PageRef) -> bool;
+}
+
+impl<F> PageExistenceResolver for F
+where
+    F: Fn(&PageRef) -> bool,
+{
+    #[inline]
+    fn page_exists(&self, page_ref: &PageRef) -> bool {
+        self(page_ref)
+    }
+}
+
+/// Whether each page linked from a syntax tree exists.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkValidation {
+    pages: HashMap<PageRef<'static>, bool>,
+}
+
+impl LinkValidation {
+    /// Whether the given page exists, or `None` if it wasn't validated.
+    #[inline]
+    pub fn exists(&self, page_ref: &PageRef) -> Option<bool> {
+        self.pages.get(page_ref).copied()
+    }
+
+    /// Each linked page which does not exist, in no particular order.
+    pub fn missing(&self) -> impl Iterator<Item = &PageRef<'static>> {
+        self.pages
+            .iter()
+            .filter(|(_, exists)| !**exists)
+            .map(|(page_ref, _)| page_ref)
+    }
+}
+
+/// Checks whether the target of each page link in the tree exists.
+///
+/// Each distinct page is only resolved once. Only [`LinkLocation::Page`]
+/// links are checked, URLs are left alone.
+pub fn validate_links(
+    tree: &SyntaxTree,
+    resolver: &dyn PageExistenceResolver,
+) -> LinkValidation {
+    let mut validation = LinkValidation::default();
+
+    tree.walk(|element| {
+        if let Element::Link {
+            link: LinkLocation::Page(page_ref),
+            ..
+        } = element
+        {
+            if !validation.pages.contains_key(page_ref) {
+                let exists = resolver.page_exists(page_ref);
+                debug!("Validated page link '{page_ref}' (exists {exists})");
+                validation.pages.insert(page_ref.to_owned(), exists);
+            }
+        }
+    });
+
+    validation
+}
+
+#[test]
+fn validate() {
+    use crate::data::PageInfo;
+    use crate::settings::{WikitextMode, WikitextSettings};
+    use std::cell::RefCell;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let text = "[[[apple]]] [[[banana]]] [[[apple|again]]] [[[:other:banana]]] [https://example.com/ URL]";
+    let tokens = crate::tokenize(text);
+    let (tree, _) = crate::parse(&tokens, &page_info, &settings).into();
+
+    let resolved = RefCell::new(Vec::new());
+    let resolver = |page_ref: &PageRef| {
+        resolved.borrow_mut().push(page_ref.to_string());
+        page_ref.page() == "apple"
+    };
+
+    let validation = validate_links(&tree, &resolver);
+    assert_eq!(
+        resolved.into_inner(),
+        vec!["apple", "banana", ":other:banana"],
+        "Pages not resolved exactly once",
+    );
+
+    assert_eq!(validation.exists(&PageRef::page_only("apple")), Some(true));
+    assert_eq!(
+        validation.exists(&PageRef::page_only("banana")),
+        Some(false)
+    );
+    assert_eq!(
+        validation.exists(&PageRef::page_and_site("other", "banana")),
+        Some(false),
+    );
+    assert_eq!(validation.exists(&PageRef::page_only("cherry")), None);
+    assert_eq!(validation.missing().count(), 2);
+}
@@ -21,15 +21,24 @@
 //! This module defines POD (plain old data) structs.
 
 mod backlinks;
+mod breadcrumb;
 mod karma;
+mod links;
 mod page_info;
 mod page_ref;
 mod score;
+mod search;
 mod user_info;
 
-pub use self::backlinks::Backlinks;
+pub use self::backlinks::{
+    extract_backlinks, extract_mentions, BacklinkSpan, BacklinkType, Backlinks,
+    FileReference, UserMention,
+};
+pub use self::breadcrumb::{Breadcrumb, BreadcrumbType};
 pub use self::karma::KarmaLevel;
+pub use self::links::{validate_links, LinkValidation, PageExistenceResolver};
 pub use self::page_info::PageInfo;
-pub use self::page_ref::{PageRef, PageRefParseError};
+pub use self::page_ref::PageRef;
 pub use self::score::ScoreValue;
+pub use self::search::{extract_search_text, SearchField, SearchFieldKind, SearchText};
 pub use self::user_info::UserInfo;
@@ -18,9 +18,11 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+use crate::error::FtmlError;
 use ref_map::*;
 use std::borrow::Cow;
 use std::fmt::{self, Display};
+use wikidot_normalize::normalize;
 
 /// Represents a reference to a page on the wiki, as used by include notation.
 ///
@@ -85,10 +87,67 @@ impl<'t> PageRef<'t> {
         (self.site().unwrap_or(current_site), self.page())
     }
 
-    pub fn parse(s: &'t str) -> Result<PageRef<'t>, PageRefParseError> {
+    /// The category of this page, such as `component` in `component:theme`.
+    ///
+    /// As with Wikidot, if there are multiple colons, then everything
+    /// before the last one is the category.
+    pub fn category(&self) -> Option<&str> {
+        self.page
+            .rsplit_once(':')
+            .map(|(category, _)| category.trim())
+    }
+
+    /// The name of this page within its category, such as `theme` in `component:theme`.
+    pub fn slug(&self) -> &str {
+        match self.page.rsplit_once(':') {
+            Some((_, slug)) => slug.trim(),
+            None => self.page.trim(),
+        }
+    }
+
+    /// Whether each part of this reference is present and non-empty.
+    ///
+    /// For instance, `category:` and `:site:` are not valid page references.
+    pub fn is_valid(&self) -> bool {
+        fn nonempty(value: &str) -> bool {
+            let mut value = str!(value);
+            normalize(&mut value);
+            !value.is_empty()
+        }
+
+        self.site().map(nonempty).unwrap_or(true)
+            && self.category().map(nonempty).unwrap_or(true)
+            && nonempty(self.slug())
+    }
+
+    /// Returns this reference with the site and page names normalized.
+    ///
+    /// This follows Wikidot's slug rules, so `:SCP-Wiki:Component: Some Theme`
+    /// becomes `:scp-wiki:component:some-theme`, and the `_default` category
+    /// is removed. Use this when comparing or indexing page references.
+    pub fn to_normalized(&self) -> PageRef<'static> {
+        let site = self.site.ref_map(|site| {
+            let mut site = site.to_lowercase();
+            normalize(&mut site);
+            Cow::Owned(site)
+        });
+
+        let mut page = self.page.to_string();
+        normalize(&mut page);
+
+        PageRef {
+            site,
+            page: Cow::Owned(page),
+        }
+    }
+
+    /// Parses a page reference, such as `page` or `:wiki-name:page`.
+    ///
+    /// Fails with [`FtmlError::PageRefParse`] if the reference is empty or the site name is missing.
+    pub fn parse(s: &'t str) -> Result<PageRef<'t>, FtmlError> {
         let s = s.trim();
         if s.is_empty() {
-            return Err(PageRefParseError);
+            return Err(FtmlError::PageRefParse);
         }
 
         let result = match s.find(':') {
@@ -98,7 +157,7 @@ impl<'t> PageRef<'t> {
                 let idx = match s[1..].find(':') {
                     // Empty site name, e.g. "::something"
                     // or no second colon, e.g. ":something"
-                    Some(0) | None => return Err(PageRefParseError),
+                    Some(0) | None => return Err(FtmlError::PageRefParse),
 
                     // Slice off the rest
                     Some(idx) => idx + 1,
@@ -145,9 +204,6 @@ impl Display for PageRef<'_> {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-pub struct PageRefParseError;
-
 #[test]
 fn page_ref() {
     macro_rules! test {
@@ -160,8 +216,8 @@ fn page_ref() {
         };
 
         ($input:expr => $expected:expr) => {{
-            let actual = PageRef::parse($input);
-            let expected = $expected.ok_or(PageRefParseError);
+            let actual = PageRef::parse($input).ok();
+            let expected = $expected;
 
             println!("Input: {:?}", $input);
             println!("Output: {:?}", actual);
@@ -191,6 +247,52 @@ fn page_ref() {
     );
 }
 
+#[test]
+fn page_ref_parts() {
+    macro_rules! check {
+        ($input:expr, $category:expr, $slug:expr, $normalized:expr, $valid:expr $(,)?) => {{
+            let page_ref =
+                PageRef::parse($input).expect("Unable to parse page reference");
+
+            assert_eq!(page_ref.category(), $category, "Category doesn't match");
+            assert_eq!(page_ref.slug(), $slug, "Slug doesn't match");
+            assert_eq!(
+                page_ref.to_normalized().to_string(),
+                $normalized,
+                "Normalized page reference doesn't match",
+            );
+            assert_eq!(page_ref.is_valid(), $valid, "Validity doesn't match");
+        }};
+    }
+
+    check!("scp-001", None, "scp-001", "scp-001", true);
+    check!(
+        "Component:Some Theme",
+        Some("Component"),
+        "Some Theme",
+        "component:some-theme",
+        true
+    );
+    check!(
+        " :SCP-Wiki: Fragment:SCP 4447 (2) ",
+        Some("Fragment"),
+        "SCP 4447 (2)",
+        ":scp-wiki:fragment:scp-4447-2",
+        true,
+    );
+    check!("_default:page", Some("_default"), "page", "page", true);
+    check!(
+        "deleted:fragment:page",
+        Some("deleted:fragment"),
+        "page",
+        "deleted-fragment:page",
+        true,
+    );
+    check!("category:", Some("category"), "", "category", false);
+    check!(":site:", None, "", ":site:", false);
+    check!("!!!", None, "!!!", "", false);
+}
+
 #[cfg(test)]
 mod prop {
     use super::*;
@@ -40,6 +40,20 @@ impl ScoreValue {
             ScoreValue::Float(value) => value,
         }
     }
+
+    /// Formats this score as shown in a rating, such as `+12` or `-3`.
+    ///
+    /// Positive scores have a leading `+`, and whole floating-point
+    /// scores are shown without a fractional part.
+    pub fn to_rating_string(self) -> String {
+        match self {
+            ScoreValue::Integer(value) if value > 0 => format!("+{value}"),
+            ScoreValue::Integer(value) => value.to_string(),
+            ScoreValue::Float(value) if value > 0.0 => format!("+{value}"),
+            ScoreValue::Float(value) if value < 0.0 => value.to_string(),
+            ScoreValue::Float(_) => str!("0"),
+        }
+    }
 }
 
 impl From<i64> for ScoreValue {
@@ -55,3 +69,14 @@ impl From<f64> for ScoreValue {
         ScoreValue::Float(value)
     }
 }
+
+#[test]
+fn rating_string() {
+    assert_eq!(ScoreValue::Integer(12).to_rating_string(), "+12");
+    assert_eq!(ScoreValue::Integer(0).to_rating_string(), "0");
+    assert_eq!(ScoreValue::Integer(-3).to_rating_string(), "-3");
+    assert_eq!(ScoreValue::Float(69.0).to_rating_string(), "+69");
+    assert_eq!(ScoreValue::Float(4.5).to_rating_string(), "+4.5");
+    assert_eq!(ScoreValue::Float(-0.0).to_rating_string(), "0");
+    assert_eq!(ScoreValue::Float(-1.25).to_rating_string(), "-1.25");
+}
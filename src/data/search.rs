@@ -0,0 +1,321 @@
+/*
+ * data/search.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Extraction of a page's text for search indexing.
+//!
+//! Unlike [`TextRender`](crate::render::text::TextRender), which lays text
+//! out for display, this only produces the words a reader would search for.
+//! There are no list bullets, URLs, or interface labels such as those on
+//! collapsible buttons, and whitespace is normalized.
+
+use crate::entity::find_entity;
+use crate::tree::visit::{walk_element, walk_tree};
+use crate::tree::{ContainerType, Element, LinkLabel, ListItem, SyntaxTree, Visitor};
+
+/// The text of a page, prepared for a search index.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct SearchText {
+    /// The visible text of the page.
+    ///
+    /// Each block, such as a paragraph or list item, is on its own line,
+    /// and all other whitespace is collapsed into single spaces.
+    /// Code is not included, see [`SearchFieldKind::Code`].
+    pub text: String,
+
+    /// Text which may be weighted differently, in page order.
+    pub fields: Vec<SearchField>,
+}
+
+/// A piece of text with a particular role on the page.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct SearchField {
+    pub kind: SearchFieldKind,
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SearchFieldKind {
+    /// The text of a heading. This is also part of the main text.
+    Heading,
+
+    /// The label of a link. This is also part of the main text.
+    LinkLabel,
+
+    /// The alternative text or title of an image. This is not visible,
+    /// and so is not part of the main text.
+    AltText,
+
+    /// The contents of a code block. This is kept out of the main text,
+    /// since it is usually not prose.
+    Code,
+}
+
+/// Gathers the text of a syntax tree for search indexing.
+///
+/// This includes the footnotes and bibliographies, but not the generated
+/// table of contents, since it repeats the headings.
+pub fn extract_search_text(tree: &SyntaxTree) -> SearchText {
+    let mut extractor = Extractor::default();
+    walk_tree(&mut extractor, tree);
+
+    let Extractor { mut output, .. } = extractor;
+    let trimmed = output.text.trim_end().len();
+    output.text.truncate(trimmed);
+    output
+}
+
+#[derive(Debug, Default)]
+struct Extractor {
+    output: SearchText,
+
+    /// Text being gathered for fields, such as the contents of a heading.
+    captures: Vec<String>,
+
+    /// Whether whitespace was seen since the last word.
+    pending_space: bool,
+}
+
+impl Extractor {
+    fn add_text(&mut self, text: &str) {
+        for word in text.split_inclusive(char::is_whitespace) {
+            let trimmed = word.trim_end();
+            if !trimmed.is_empty() {
+                if self.pending_space {
+                    self.push_str(" ");
+                }
+
+                self.push_str(trimmed);
+                self.pending_space = false;
+            }
+
+            if trimmed.len() < word.len() {
+                self.pending_space = true;
+            }
+        }
+    }
+
+    fn push_str(&mut self, text: &str) {
+        // No leading spaces on a line
+        if text == " "
+            && (self.output.text.is_empty() || self.output.text.ends_with('\n'))
+        {
+            return;
+        }
+
+        self.output.text.push_str(text);
+        for capture in &mut self.captures {
+            capture.push_str(text);
+        }
+    }
+
+    /// Starts a new line in the main text, if one isn't already started.
+    fn end_block(&mut self) {
+        let text = &mut self.output.text;
+        text.truncate(text.trim_end_matches(' ').len());
+
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+
+        // Fields are a single line, so blocks are separated by spaces
+        for capture in &mut self.captures {
+            capture.push(' ');
+        }
+
+        self.pending_space = false;
+    }
+
+    fn add_field(&mut self, kind: SearchFieldKind, text: &str) {
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !text.is_empty() {
+            self.output.fields.push(SearchField { kind, text });
+        }
+    }
+
+    /// Visits the children of this element, adding their text as a field too.
+    fn capture(&mut self, kind: SearchFieldKind, element: &Element) {
+        self.captures.push(String::new());
+        walk_element(self, element);
+
+        let text = self.captures.pop().expect("Capture stack is empty");
+        self.add_field(kind, &text);
+    }
+}
+
+impl<'t> Visitor<'t> for Extractor {
+    fn visit_element(&mut self, element: &Element<'t>) {
+        // Block-level elements are placed on their own lines
+        let block = !element.paragraph_safe();
+        if block {
+            self.end_block();
+        }
+
+        match element {
+            Element::Text(text) | Element::Raw(text) | Element::Email(text) => {
+                self.add_text(text);
+            }
+            Element::HtmlEntity(name) => {
+                if let Some(value) = find_entity(name) {
+                    self.add_text(&value);
+                }
+            }
+            Element::Container(container) => match container.ctype() {
+                ContainerType::Hidden | ContainerType::Invisible => (),
+                ContainerType::Header(_) => {
+                    self.capture(SearchFieldKind::Heading, element);
+                }
+                _ => walk_element(self, element),
+            },
+            Element::Link { label, .. } => {
+                // The URL itself is not searchable text
+                if let LinkLabel::Text(text) | LinkLabel::Url(Some(text)) = label {
+                    self.add_text(text);
+                    self.add_field(SearchFieldKind::LinkLabel, text);
+                }
+            }
+            Element::Anchor { .. } => {
+                self.capture(SearchFieldKind::LinkLabel, element);
+            }
+            Element::Image { attributes, .. } => {
+                let attributes = attributes.get();
+                for key in ["alt", "title"] {
+                    if let Some(text) = attributes.get(key) {
+                        self.add_field(SearchFieldKind::AltText, text);
+                    }
+                }
+            }
+            Element::Gallery { images, .. } => {
+                for image in images {
+                    if let Some(title) = &image.title {
+                        self.add_field(SearchFieldKind::AltText, title);
+                    }
+                }
+            }
+            Element::Code { contents, .. } => {
+                self.add_field(SearchFieldKind::Code, contents);
+            }
+            Element::List { items, .. } => {
+                for item in items {
+                    self.end_block();
+                    match item {
+                        ListItem::Elements { elements, .. } => {
+                            self.visit_elements(elements)
+                        }
+                        ListItem::SubList { element } => self.visit_element(element),
+                    }
+                }
+            }
+            Element::DefinitionList(items) => {
+                for item in items {
+                    self.end_block();
+                    self.visit_elements(&item.key_elements);
+                    self.end_block();
+                    self.visit_elements(&item.value_elements);
+                }
+            }
+            Element::Table(table) => {
+                for row in &table.rows {
+                    self.end_block();
+                    for cell in &row.cells {
+                        self.pending_space = true;
+                        self.visit_elements(&cell.elements);
+                    }
+                }
+            }
+            Element::TabView(tabs) => {
+                for tab in tabs {
+                    self.end_block();
+                    self.add_text(&tab.label);
+                    self.end_block();
+                    self.visit_elements(&tab.elements);
+                }
+            }
+            Element::User { name, .. } => self.add_text(name),
+            Element::LineBreak | Element::LineBreaks(_) => self.pending_space = true,
+
+            // Not part of the page's own text
+            Element::TableOfContents { .. }
+            | Element::Module(_)
+            | Element::Style(_)
+            | Element::Html { .. }
+            | Element::Iframe { .. } => (),
+
+            // Collapsible labels are skipped, but not their contents.
+            // Other elements contribute only through their children.
+            _ => walk_element(self, element),
+        }
+
+        if block {
+            self.end_block();
+        }
+    }
+}
+
+#[test]
+fn search_text() {
+    use crate::data::PageInfo;
+    use crate::settings::{WikitextMode, WikitextSettings};
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let text = "+ Fruit  **salad**\n\n\
+                * Apple   banana\n\
+                * [https://example.com/ Cherry] https://example.org/\n\n\
+                [[collapsible show=\"Open me\" hide=\"Close me\"]]\n\
+                Durian\n\
+                [[/collapsible]]\n\n\
+                [[image eggplant.png alt=\"A purple eggplant\"]]\n\n\
+                [[code]]\nlet fig = 1;\n[[/code]]\n\n\
+                || Grape || Kiwi ||\n\n\
+                Lemon[[footnote]]Honeydew[[/footnote]]";
+
+    let tokens = crate::tokenize(text);
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let search = extract_search_text(&tree);
+
+    assert_eq!(
+        search.text,
+        "Fruit salad\nApple banana\nCherry\nDurian\nGrape Kiwi\nLemon\nHoneydew",
+    );
+
+    let fields = search
+        .fields
+        .iter()
+        .map(|field| (field.kind, field.text.as_str()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        fields,
+        vec![
+            (SearchFieldKind::Heading, "Fruit salad"),
+            (SearchFieldKind::LinkLabel, "Cherry"),
+            (SearchFieldKind::AltText, "A purple eggplant"),
+            (SearchFieldKind::Code, "let fig = 1;"),
+        ],
+    );
+
+    assert_eq!(
+        extract_search_text(&SyntaxTree::default()),
+        SearchText::default(),
+    );
+}
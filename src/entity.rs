@@ -0,0 +1,247 @@
+/*
+ * entity.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Lookup of HTML entities, such as `&amp;` or `&#x1f4af;`.
+
+use entities::ENTITIES;
+use once_cell::sync::Lazy;
+use std::borrow::Cow;
+use std::char;
+use std::collections::HashMap;
+
+static ENTITY_MAPPING: Lazy<HashMap<&'static str, &'static str>> = Lazy::new(|| {
+    let mut mapping = HashMap::new();
+
+    for entity in &ENTITIES {
+        let key = strip_entity(entity.entity);
+        let value = entity.characters;
+
+        mapping.insert(key, value);
+    }
+
+    mapping
+});
+
+/// Find the string corresponding to the passed entity, if any.
+pub fn find_entity(entity: &str) -> Option<Cow<'_, str>> {
+    // Named entity
+    if let Some(result) = ENTITY_MAPPING.get(entity) {
+        return Some(cow!(result));
+    }
+
+    // Hexadecimal entity
+    if let Some(value) = entity.strip_prefix("#x") {
+        if let Some(result) = get_char(value, 16) {
+            return Some(result);
+        }
+    }
+
+    // Decimal entity
+    if let Some(value) = entity.strip_prefix('#') {
+        if let Some(result) = get_char(value, 10) {
+            return Some(result);
+        }
+    }
+
+    // Not found
+    None
+}
+
+/// Gets the appropriate character from the number specified in the string.
+///
+/// Using the passed radix, it gets the integer value, then finds the appropriate
+/// character, if one exists.
+///
+/// Then converts the character into a string with only that value.
+fn get_char(value: &str, radix: u32) -> Option<Cow<'_, str>> {
+    let codepoint = match u32::from_str_radix(value, radix) {
+        Ok(codepoint) => codepoint,
+        Err(_) => return None,
+    };
+
+    let ch = char::from_u32(codepoint)?;
+
+    Some(Cow::Owned(ch.to_string()))
+}
+
+/// If a string starts with `&` or ends with `;`, those are removed.
+/// First trims the string of whitespace.
+pub fn strip_entity(mut s: &str) -> &str {
+    s = s.trim();
+
+    if let Some(stripped) = s.strip_prefix('&') {
+        s = stripped;
+    }
+
+    if let Some(stripped) = s.strip_suffix(';') {
+        s = stripped;
+    }
+
+    s
+}
+
+/// Splits text made up only of entity references, such as `&mdash;&nbsp;`.
+///
+/// Returns the name of each entity, without the `&` and `;`, or `None`
+/// if there is any other text or an entity isn't recognized.
+pub fn split_entities(text: &str) -> Option<Vec<&str>> {
+    let mut names = Vec::new();
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        let (name, next) = rest.strip_prefix('&')?.split_once(';')?;
+        find_entity(name)?;
+        names.push(name);
+        rest = next;
+    }
+
+    if names.is_empty() {
+        None
+    } else {
+        Some(names)
+    }
+}
+
+/* Tests */
+
+#[test]
+fn test_get_entity() {
+    macro_rules! check {
+        ($input:expr, $expected:expr $(,)?) => {{
+            let actual = find_entity($input);
+            let expected = $expected;
+
+            assert_eq!(
+                actual, expected,
+                "Actual entity string doesn't match expected",
+            );
+        }};
+    }
+
+    check!("", None);
+
+    // Names
+    check!("amp", Some(cow!("&")));
+    check!("lt", Some(cow!("<")));
+    check!("gt", Some(cow!(">")));
+    check!("copy", Some(cow!("©")));
+    check!("xxxzzz", None);
+
+    // Decimal
+    check!("#32", Some(cow!(" ")));
+    check!("#255", Some(cow!("\u{ff}")));
+    check!("#128175", Some(cow!("💯")));
+    check!("#2097151", None);
+
+    // Hex
+    check!("#x20", Some(cow!(" ")));
+    check!("#xff", Some(cow!("\u{ff}")));
+    check!("#x1f4af", Some(cow!("💯")));
+    check!("#x1fffff", None);
+}
+
+#[test]
+fn test_get_char() {
+    macro_rules! check {
+        ($value:expr, $radix:expr, $expected:expr $(,)?) => {{
+            let actual = get_char($value, $radix);
+            let expected = $expected;
+
+            assert_eq!(
+                actual, expected,
+                "Actual character value doesn't match expected",
+            );
+        }};
+    }
+
+    // Decimal
+    check!("32", 10, Some(Cow::Owned(str!(' '))));
+    check!("255", 10, Some(Cow::Owned(str!('\u{ff}'))));
+    check!("128175", 10, Some(Cow::Owned(str!('💯'))));
+    check!("2097151", 10, None);
+
+    // Hex
+    check!("20", 16, Some(Cow::Owned(str!(' '))));
+    check!("ff", 16, Some(Cow::Owned(str!('\u{ff}'))));
+    check!("1f4af", 16, Some(Cow::Owned(str!('💯'))));
+    check!("1fffff", 16, None);
+}
+
+#[test]
+fn test_strip_entity() {
+    macro_rules! check {
+        ($input:expr, $expected:expr $(,)?) => {{
+            let actual = strip_entity($input);
+            let expected = $expected;
+
+            assert_eq!(
+                actual, expected,
+                "Actual stripped entity value didn't match expected",
+            );
+        }};
+    }
+
+    check!("", "");
+    check!("abc", "abc");
+    check!("legumes1", "legumes1");
+    check!("&amp;", "amp");
+    check!("&#100;", "#100");
+    check!("&xdeadbeef;", "xdeadbeef");
+
+    check!("&amp", "amp");
+    check!("amp;", "amp");
+    check!("&#100", "#100");
+    check!("#100;", "#100");
+
+    check!(" ", "");
+    check!(" abc", "abc");
+    check!(" legumes1", "legumes1");
+    check!(" &amp;", "amp");
+    check!(" &#100;", "#100");
+    check!(" &xdeadbeef;", "xdeadbeef");
+}
+
+#[test]
+fn test_split_entities() {
+    macro_rules! check {
+        ($input:expr, $expected:expr $(,)?) => {{
+            let actual = split_entities($input);
+            let expected: Option<Vec<&str>> = $expected;
+
+            assert_eq!(
+                actual, expected,
+                "Actual entity names didn't match expected",
+            );
+        }};
+    }
+
+    check!("", None);
+    check!("&mdash;", Some(vec!["mdash"]));
+    check!(
+        "&#8212;&nbsp;&#x1f4af;",
+        Some(vec!["#8212", "nbsp", "#x1f4af"])
+    );
+
+    check!("mdash", None);
+    check!("&mdash", None);
+    check!("&mdash; ", None);
+    check!("&xxxzzz;", None);
+    check!("&mdash;&;", None);
+}
@@ -0,0 +1,142 @@
+/*
+ * error.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! The unified error type for the crate.
+//!
+//! Most processing in ftml is not fallible, since malformed wikitext
+//! is handled by fallback rules rather than errors. This type collects
+//! the cases where an operation can fail, so that consumers can
+//! propagate them with `?` uniformly.
+
+use crate::tree::query::SelectorParseError;
+use std::error::Error;
+use std::fmt::{self, Display};
+
+/// An error produced by some part of ftml.
+#[derive(Debug)]
+pub enum FtmlError {
+    /// A page reference could not be parsed.
+    PageRefParse,
+
+    /// Fetching included pages failed, or the fetched pages did not match the request.
+    Include(String),
+
+    /// Expanding includes would exceed the total number of includes or bytes
+    /// permitted by [`ParseLimits`](crate::settings::ParseLimits).
+    IncludeLimitExceeded,
+
+    /// No settings preset or mode exists with this name.
+    UnknownPreset(String),
+
+    /// A settings preset overrides a field which does not exist.
+    UnknownSetting(String),
+
+    /// A value could not be converted across a foreign function interface, such as WebAssembly.
+    Ffi(String),
+
+    /// A value could not be serialized or deserialized.
+    Serialization(serde_json::Error),
+
+    /// A stored syntax tree was produced by an incompatible version of ftml.
+    ///
+    /// The tree should be discarded and its source parsed again.
+    UnsupportedTreeVersion(u64),
+
+    /// A stored syntax tree is malformed.
+    ///
+    /// The path names the innermost node which could not be loaded,
+    /// such as `tree.elements[2].data.elements[0]`.
+    InvalidTree { path: String, message: String },
+
+    /// A selector for querying a syntax tree could not be parsed.
+    InvalidSelector(SelectorParseError),
+
+    /// Parsing or rendering was stopped using a
+    /// [`CancellationToken`](crate::settings::CancellationToken).
+    ///
+    /// Any output produced is incomplete, and should be discarded.
+    Cancelled,
+
+    /// Messages for a [`MessageCatalog`](crate::render::MessageCatalog) could not be loaded.
+    InvalidMessages(String),
+}
+
+impl Display for FtmlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FtmlError::PageRefParse => write!(f, "invalid page reference"),
+            FtmlError::Include(message) => write!(f, "include error: {message}"),
+            FtmlError::IncludeLimitExceeded => write!(f, "include limit exceeded"),
+            FtmlError::UnknownPreset(name) => {
+                write!(f, "unknown settings preset: {name}")
+            }
+            FtmlError::UnknownSetting(name) => write!(f, "unknown setting: {name}"),
+            FtmlError::Ffi(message) => write!(f, "ffi error: {message}"),
+            FtmlError::Serialization(error) => write!(f, "serialization error: {error}"),
+            FtmlError::UnsupportedTreeVersion(version) => {
+                write!(f, "unsupported syntax tree version: {version}")
+            }
+            FtmlError::InvalidTree { path, message } => {
+                write!(f, "invalid syntax tree at {path}: {message}")
+            }
+            FtmlError::InvalidSelector(error) => write!(f, "invalid selector: {error}"),
+            FtmlError::Cancelled => write!(f, "cancelled"),
+            FtmlError::InvalidMessages(message) => {
+                write!(f, "invalid messages: {message}")
+            }
+        }
+    }
+}
+
+impl Error for FtmlError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FtmlError::Serialization(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<SelectorParseError> for FtmlError {
+    #[inline]
+    fn from(error: SelectorParseError) -> Self {
+        FtmlError::InvalidSelector(error)
+    }
+}
+
+impl From<serde_json::Error> for FtmlError {
+    #[inline]
+    fn from(error: serde_json::Error) -> Self {
+        FtmlError::Serialization(error)
+    }
+}
+
+#[test]
+fn errors() {
+    use crate::data::PageRef;
+
+    let error = PageRef::parse(":page").unwrap_err();
+    assert!(matches!(error, FtmlError::PageRefParse));
+    assert_eq!(error.to_string(), "invalid page reference");
+
+    let error = FtmlError::from(serde_json::from_str::<u32>("[").unwrap_err());
+    assert!(matches!(error, FtmlError::Serialization(_)));
+    assert!(error.source().is_some());
+}
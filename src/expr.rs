@@ -0,0 +1,557 @@
+/*
+ * expr.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! A small, sandboxed expression engine.
+//!
+//! This evaluates simple arithmetic and string expressions, such as those
+//! found in `[[calc]]` blocks. It has no access to anything outside of the
+//! expression itself, and enforces strict limits on input length, nesting
+//! depth, and the size of produced strings so that evaluation is always cheap.
+//!
+//! Supported syntax:
+//! * Numbers (`12`, `3.5`) and double-quoted strings (`"Keter"`).
+//! * Arithmetic: `+`, `-`, `*`, `/`, `%`, and unary `-`.
+//!   Adding a string to anything concatenates them.
+//! * Parentheses for grouping.
+//! * Functions: `abs`, `ceil`, `floor`, `round`, `min`, `max`,
+//!   `len`, `lower`, `upper`.
+
+use std::fmt::{self, Display};
+
+/// The maximum length of an expression's source, in bytes.
+pub const MAX_EXPRESSION_LENGTH: usize = 256;
+
+/// The maximum nesting depth of an expression.
+pub const MAX_EXPRESSION_DEPTH: usize = 16;
+
+/// The maximum length of a string value produced during evaluation, in bytes.
+pub const MAX_STRING_LENGTH: usize = 1024;
+
+/// The maximum number of decimal places shown when formatting numbers.
+const MAX_DECIMAL_PLACES: usize = 6;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    String(String),
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Number(value) => write!(f, "{}", format_number(*value)),
+            Value::String(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExprError {
+    /// The expression source is longer than [`MAX_EXPRESSION_LENGTH`].
+    TooLong,
+
+    /// The expression is nested deeper than [`MAX_EXPRESSION_DEPTH`].
+    TooDeep,
+
+    /// A string value grew beyond [`MAX_STRING_LENGTH`].
+    StringTooLong,
+
+    /// The expression is not syntactically valid.
+    Syntax,
+
+    /// A function was called which does not exist.
+    UnknownFunction,
+
+    /// A function was called with the wrong number of arguments.
+    WrongArgumentCount,
+
+    /// An operation was applied to a value of the wrong type.
+    WrongType,
+
+    /// A division or remainder by zero was attempted.
+    DivideByZero,
+
+    /// A computation produced an infinite or undefined number.
+    OutOfRange,
+}
+
+impl ExprError {
+    pub fn description(self) -> &'static str {
+        match self {
+            ExprError::TooLong => "expression is too long",
+            ExprError::TooDeep => "expression is nested too deeply",
+            ExprError::StringTooLong => "string value is too long",
+            ExprError::Syntax => "invalid expression syntax",
+            ExprError::UnknownFunction => "unknown function",
+            ExprError::WrongArgumentCount => "wrong number of function arguments",
+            ExprError::WrongType => "wrong value type for operation",
+            ExprError::DivideByZero => "division by zero",
+            ExprError::OutOfRange => "number out of range",
+        }
+    }
+}
+
+impl Display for ExprError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.description())
+    }
+}
+
+/// Checks that the expression is syntactically valid, without evaluating it.
+pub fn validate(source: &str) -> Result<(), ExprError> {
+    parse(source).map(|_| ())
+}
+
+/// Parses and evaluates the expression, producing its value.
+pub fn evaluate(source: &str) -> Result<Value, ExprError> {
+    info!("Evaluating expression '{source}'");
+
+    let expr = parse(source)?;
+    let value = eval(&expr)?;
+    debug!("Expression evaluated to {value:?}");
+    Ok(value)
+}
+
+// Syntax tree
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Literal(Value),
+    Negate(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+// Lexing
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    String(String),
+    Identifier(String),
+    Operator(char),
+    LeftParen,
+    RightParen,
+    Comma,
+}
+
+fn lex(source: &str) -> Result<Vec<Token>, ExprError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some((start, ch)) = chars.next() {
+        let token = match ch {
+            _ if ch.is_whitespace() => continue,
+            '(' => Token::LeftParen,
+            ')' => Token::RightParen,
+            ',' => Token::Comma,
+            '+' | '-' | '*' | '/' | '%' => Token::Operator(ch),
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, ch)) => value.push(ch),
+                            None => return Err(ExprError::Syntax),
+                        },
+                        Some((_, ch)) => value.push(ch),
+                        None => return Err(ExprError::Syntax),
+                    }
+                }
+
+                Token::String(value)
+            }
+            '0'..='9' | '.' => {
+                let mut end = start + ch.len_utf8();
+                while let Some(&(idx, ch)) = chars.peek() {
+                    if !(ch.is_ascii_digit() || ch == '.') {
+                        break;
+                    }
+
+                    end = idx + ch.len_utf8();
+                    chars.next();
+                }
+
+                let value = source[start..end].parse().map_err(|_| ExprError::Syntax)?;
+
+                Token::Number(value)
+            }
+            _ if ch.is_ascii_alphabetic() || ch == '_' => {
+                let mut end = start + ch.len_utf8();
+                while let Some(&(idx, ch)) = chars.peek() {
+                    if !(ch.is_ascii_alphanumeric() || ch == '_') {
+                        break;
+                    }
+
+                    end = idx + ch.len_utf8();
+                    chars.next();
+                }
+
+                Token::Identifier(source[start..end].to_ascii_lowercase())
+            }
+            _ => return Err(ExprError::Syntax),
+        };
+
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+// Parsing
+
+fn parse(source: &str) -> Result<Expr, ExprError> {
+    if source.len() > MAX_EXPRESSION_LENGTH {
+        warn!(
+            "Expression too long ({} > {})",
+            source.len(),
+            MAX_EXPRESSION_LENGTH,
+        );
+        return Err(ExprError::TooLong);
+    }
+
+    let tokens = lex(source)?;
+    let mut parser = ExprParser {
+        tokens: &tokens,
+        position: 0,
+        depth: 0,
+    };
+
+    let expr = parser.parse_sum()?;
+    if parser.position != tokens.len() {
+        return Err(ExprError::Syntax);
+    }
+
+    Ok(expr)
+}
+
+#[derive(Debug)]
+struct ExprParser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    depth: usize,
+}
+
+impl ExprParser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: &Token) -> Result<(), ExprError> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            _ => Err(ExprError::Syntax),
+        }
+    }
+
+    fn descend(&mut self) -> Result<(), ExprError> {
+        self.depth += 1;
+        if self.depth > MAX_EXPRESSION_DEPTH {
+            warn!("Expression nested too deeply");
+            return Err(ExprError::TooDeep);
+        }
+
+        Ok(())
+    }
+
+    fn parse_sum(&mut self) -> Result<Expr, ExprError> {
+        let mut expr = self.parse_product()?;
+
+        loop {
+            let op = match self.peek() {
+                Some(Token::Operator('+')) => BinaryOp::Add,
+                Some(Token::Operator('-')) => BinaryOp::Subtract,
+                _ => return Ok(expr),
+            };
+
+            self.position += 1;
+            let rhs = self.parse_product()?;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(rhs));
+        }
+    }
+
+    fn parse_product(&mut self) -> Result<Expr, ExprError> {
+        let mut expr = self.parse_unary()?;
+
+        loop {
+            let op = match self.peek() {
+                Some(Token::Operator('*')) => BinaryOp::Multiply,
+                Some(Token::Operator('/')) => BinaryOp::Divide,
+                Some(Token::Operator('%')) => BinaryOp::Remainder,
+                _ => return Ok(expr),
+            };
+
+            self.position += 1;
+            let rhs = self.parse_unary()?;
+            expr = Expr::Binary(op, Box::new(expr), Box::new(rhs));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, ExprError> {
+        match self.peek() {
+            Some(Token::Operator('-')) => {
+                self.position += 1;
+                self.descend()?;
+                let expr = self.parse_unary()?;
+                self.depth -= 1;
+                Ok(Expr::Negate(Box::new(expr)))
+            }
+            _ => self.parse_atom(),
+        }
+    }
+
+    fn parse_atom(&mut self) -> Result<Expr, ExprError> {
+        match self.next().cloned() {
+            Some(Token::Number(value)) => Ok(Expr::Literal(Value::Number(value))),
+            Some(Token::String(value)) => Ok(Expr::Literal(Value::String(value))),
+            Some(Token::LeftParen) => {
+                self.descend()?;
+                let expr = self.parse_sum()?;
+                self.expect(&Token::RightParen)?;
+                self.depth -= 1;
+                Ok(expr)
+            }
+            Some(Token::Identifier(name)) => {
+                self.expect(&Token::LeftParen)?;
+                self.descend()?;
+
+                let mut arguments = Vec::new();
+                if self.peek() != Some(&Token::RightParen) {
+                    loop {
+                        arguments.push(self.parse_sum()?);
+
+                        match self.next() {
+                            Some(Token::Comma) => continue,
+                            Some(Token::RightParen) => break,
+                            _ => return Err(ExprError::Syntax),
+                        }
+                    }
+                } else {
+                    self.position += 1;
+                }
+
+                self.depth -= 1;
+                Ok(Expr::Call(name, arguments))
+            }
+            _ => Err(ExprError::Syntax),
+        }
+    }
+}
+
+// Evaluation
+
+fn eval(expr: &Expr) -> Result<Value, ExprError> {
+    match expr {
+        Expr::Literal(value) => Ok(value.clone()),
+        Expr::Negate(expr) => {
+            let value = as_number(eval(expr)?)?;
+            Ok(Value::Number(-value))
+        }
+        Expr::Binary(op, lhs, rhs) => {
+            let lhs = eval(lhs)?;
+            let rhs = eval(rhs)?;
+            eval_binary(*op, lhs, rhs)
+        }
+        Expr::Call(name, arguments) => {
+            let arguments = arguments.iter().map(eval).collect::<Result<Vec<_>, _>>()?;
+            eval_call(name, arguments)
+        }
+    }
+}
+
+fn eval_binary(op: BinaryOp, lhs: Value, rhs: Value) -> Result<Value, ExprError> {
+    // String concatenation
+    if op == BinaryOp::Add
+        && (matches!(lhs, Value::String(_)) || matches!(rhs, Value::String(_)))
+    {
+        return make_string(format!("{lhs}{rhs}"));
+    }
+
+    let lhs = as_number(lhs)?;
+    let rhs = as_number(rhs)?;
+    let result = match op {
+        BinaryOp::Add => lhs + rhs,
+        BinaryOp::Subtract => lhs - rhs,
+        BinaryOp::Multiply => lhs * rhs,
+        BinaryOp::Divide | BinaryOp::Remainder if rhs == 0.0 => {
+            return Err(ExprError::DivideByZero);
+        }
+        BinaryOp::Divide => lhs / rhs,
+        BinaryOp::Remainder => lhs % rhs,
+    };
+
+    make_number(result)
+}
+
+fn eval_call(name: &str, arguments: Vec<Value>) -> Result<Value, ExprError> {
+    macro_rules! unary {
+        () => {{
+            let [value]: [Value; 1] = arguments
+                .try_into()
+                .map_err(|_| ExprError::WrongArgumentCount)?;
+
+            value
+        }};
+    }
+
+    match name {
+        "abs" => make_number(as_number(unary!())?.abs()),
+        "ceil" => make_number(as_number(unary!())?.ceil()),
+        "floor" => make_number(as_number(unary!())?.floor()),
+        "round" => make_number(as_number(unary!())?.round()),
+        "min" | "max" => {
+            let mut numbers = arguments.into_iter().map(as_number);
+            let first = numbers.next().ok_or(ExprError::WrongArgumentCount)??;
+            numbers
+                .try_fold(first, |acc, value| {
+                    let value = value?;
+                    Ok(if name == "min" {
+                        acc.min(value)
+                    } else {
+                        acc.max(value)
+                    })
+                })
+                .map(Value::Number)
+        }
+        "len" => match unary!() {
+            Value::String(value) => make_number(value.chars().count() as f64),
+            Value::Number(_) => Err(ExprError::WrongType),
+        },
+        "lower" => make_string(as_string(unary!())?.to_lowercase()),
+        "upper" => make_string(as_string(unary!())?.to_uppercase()),
+        _ => {
+            warn!("Unknown expression function '{name}'");
+            Err(ExprError::UnknownFunction)
+        }
+    }
+}
+
+fn as_number(value: Value) -> Result<f64, ExprError> {
+    match value {
+        Value::Number(value) => Ok(value),
+        Value::String(_) => Err(ExprError::WrongType),
+    }
+}
+
+fn as_string(value: Value) -> Result<String, ExprError> {
+    match value {
+        Value::String(value) => Ok(value),
+        Value::Number(_) => Err(ExprError::WrongType),
+    }
+}
+
+fn make_number(value: f64) -> Result<Value, ExprError> {
+    if value.is_finite() {
+        Ok(Value::Number(value))
+    } else {
+        Err(ExprError::OutOfRange)
+    }
+}
+
+fn make_string(value: String) -> Result<Value, ExprError> {
+    if value.len() > MAX_STRING_LENGTH {
+        Err(ExprError::StringTooLong)
+    } else {
+        Ok(Value::String(value))
+    }
+}
+
+/// Formats a number deterministically.
+///
+/// Integral values are shown without a decimal point, and all others
+/// are rounded to a fixed number of places with trailing zeroes removed.
+fn format_number(value: f64) -> String {
+    let mut output = format!("{value:.MAX_DECIMAL_PLACES$}");
+    if output.contains('.') {
+        let trimmed = output.trim_end_matches('0').trim_end_matches('.').len();
+        output.truncate(trimmed);
+    }
+
+    if output == "-0" {
+        output.remove(0);
+    }
+
+    output
+}
+
+#[test]
+fn expressions() {
+    macro_rules! check {
+        ($source:expr, $expected:expr $(,)?) => {{
+            let actual = evaluate($source).map(|value| value.to_string());
+            let expected: Result<&str, ExprError> = $expected;
+
+            assert_eq!(
+                actual.as_deref().map_err(|error| *error),
+                expected,
+                "Actual expression result doesn't match expected (source {:?})",
+                $source,
+            );
+        }};
+    }
+
+    check!("1 + 2 * 3", Ok("7"));
+    check!("(1 + 2) * 3", Ok("9"));
+    check!("10 / 4", Ok("2.5"));
+    check!("1 / 3", Ok("0.333333"));
+    check!("-(2 - 5)", Ok("3"));
+    check!("7 % 3", Ok("1"));
+    check!("-0 * 1", Ok("0"));
+    check!("round(2.5) + floor(1.9) + ceil(0.1)", Ok("5"));
+    check!("max(3, 12, 7) - min(4, abs(-2))", Ok("10"));
+    check!("\"Keter: \" + 3", Ok("Keter: 3"));
+    check!("upper(\"euclid\") + lower(\"SAFE\")", Ok("EUCLIDsafe"));
+    check!("len(\"Thaumiel\")", Ok("8"));
+    check!("\"a \\\"quoted\\\" b\"", Ok("a \"quoted\" b"));
+
+    check!("", Err(ExprError::Syntax));
+    check!("1 +", Err(ExprError::Syntax));
+    check!("(1", Err(ExprError::Syntax));
+    check!("1 2", Err(ExprError::Syntax));
+    check!("1 / 0", Err(ExprError::DivideByZero));
+    check!("5 % 0", Err(ExprError::DivideByZero));
+    check!("\"a\" * 2", Err(ExprError::WrongType));
+    check!("len(5)", Err(ExprError::WrongType));
+    check!("abs(1, 2)", Err(ExprError::WrongArgumentCount));
+    check!("max()", Err(ExprError::WrongArgumentCount));
+    check!("exec(\"rm\")", Err(ExprError::UnknownFunction));
+    check!(
+        &"((((((((((((((((((1))))))))))))))))))",
+        Err(ExprError::TooDeep)
+    );
+    check!(&"1+".repeat(200), Err(ExprError::TooLong));
+}
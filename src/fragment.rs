@@ -0,0 +1,391 @@
+/*
+ * fragment.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Consistency checking for pages composed of multiple fragments.
+//!
+//! Some pages are split into several fragments which are parsed and rendered
+//! separately, but are displayed together. Each fragment only knows about
+//! itself, so issues which span fragments cannot be found by the parser.
+//!
+//! Each fragment produces a [`FragmentExport`] summarizing what it defines
+//! and references, and [`check_fragments()`] validates them together:
+//!
+//! * No anchor or element ID is defined more than once.
+//! * Footnote numbering continues from one fragment to the next.
+//! * Every footnote reference and footnote list entry link to each other.
+//! * Every in-page link (`#name`) resolves to an anchor in some fragment.
+//!
+//! Each fragment should be rendered with the `footnote_offset` setting
+//! equal to the number of footnotes in the fragments before it.
+
+use crate::settings::WikitextSettings;
+use crate::tree::{Element, LinkLocation, SyntaxTree};
+use std::collections::{HashMap, HashSet};
+
+/// The data exported from one fragment, for checking against the others.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct FragmentExport {
+    /// The name of this fragment, for reporting.
+    pub name: String,
+
+    /// The number the first footnote in this fragment was rendered with.
+    pub footnote_start: usize,
+
+    /// How many footnotes this fragment has.
+    pub footnote_count: usize,
+
+    /// Whether this fragment's footnotes are listed in a visible footnote block.
+    ///
+    /// Each entry in the list has a back-link to the references to it.
+    pub footnotes_listed: bool,
+
+    /// The numbers of all footnotes referenced in this fragment.
+    pub footnote_references: Vec<usize>,
+
+    /// All anchor names, heading IDs, and element IDs defined in this fragment.
+    pub anchors: Vec<String>,
+
+    /// All in-page anchors referenced by links in this fragment, without the `#`.
+    pub references: Vec<String>,
+}
+
+impl FragmentExport {
+    /// Gathers the exported data from a fragment's syntax tree.
+    ///
+    /// The `settings` should be the same as those the fragment is rendered with,
+    /// so that footnote numbering follows its `footnote_offset`.
+    pub fn from_tree(name: &str, tree: &SyntaxTree, settings: &WikitextSettings) -> Self {
+        let footnote_offset = settings.footnote_offset;
+        let mut footnotes_listed = false;
+        let mut footnote_references = Vec::new();
+        let mut footnote_index = 0;
+        let mut anchors: Vec<_> = tree
+            .table_of_contents_entries
+            .iter()
+            .map(|entry| str!(entry.id))
+            .collect();
+        let mut references = Vec::new();
+
+        tree.walk(|element| {
+            let footnote = match element {
+                Element::AnchorName(name) => {
+                    anchors.push(str!(name));
+                    None
+                }
+                Element::Footnote => {
+                    footnote_index += 1;
+                    Some(footnote_index)
+                }
+                Element::FootnoteRepeat(index) => Some(index.get()),
+                Element::FootnoteBlock { hide, .. } => {
+                    footnotes_listed |= !hide;
+                    None
+                }
+                _ => None,
+            };
+
+            if let Some(index) = footnote {
+                let number = footnote_offset + index;
+                if !footnote_references.contains(&number) {
+                    footnote_references.push(number);
+                }
+            }
+
+            if let Some(id) = element.attributes().and_then(|map| map.get().get("id")) {
+                anchors.push(str!(id));
+            }
+
+            let href = match element {
+                Element::Link {
+                    link: LinkLocation::Url(url),
+                    ..
+                } => Some(url.as_ref()),
+                Element::Anchor { attributes, .. } => {
+                    attributes.get().get("href").map(|href| href.as_ref())
+                }
+                _ => None,
+            };
+
+            if let Some(anchor) = href.and_then(|href| href.strip_prefix('#')) {
+                if !anchor.is_empty() {
+                    references.push(str!(anchor));
+                }
+            }
+        });
+
+        FragmentExport {
+            name: str!(name),
+            footnote_start: footnote_offset + 1,
+            footnote_count: tree.footnotes.len(),
+            footnotes_listed,
+            footnote_references,
+            anchors,
+            references,
+        }
+    }
+}
+
+/// A consistency problem found between fragments.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct FragmentIssue {
+    /// The index of the fragment this issue was found in.
+    pub fragment: usize,
+
+    /// The name of the fragment this issue was found in.
+    pub fragment_name: String,
+
+    /// What the issue is.
+    pub kind: FragmentIssueKind,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", tag = "type", content = "data")]
+pub enum FragmentIssueKind {
+    /// This anchor was already defined, in the given fragment.
+    DuplicateAnchor {
+        anchor: String,
+        first_fragment: usize,
+    },
+
+    /// The footnotes in this fragment do not continue from the previous.
+    FootnoteDiscontinuity { expected: usize, actual: usize },
+
+    /// This footnote is listed, but its back-link has no reference to point to.
+    FootnoteBacklinkUnresolved { footnote: usize },
+
+    /// This footnote is referenced, but is not listed in any fragment.
+    FootnoteUnlisted { footnote: usize },
+
+    /// This in-page link does not point to an anchor in any fragment.
+    UnresolvedReference { anchor: String },
+}
+
+/// Validates the exports of each fragment of a page, in display order.
+pub fn check_fragments(fragments: &[FragmentExport]) -> Vec<FragmentIssue> {
+    let mut issues = Vec::new();
+    let mut anchors = HashMap::new();
+    let mut next_footnote = 1;
+
+    macro_rules! issue {
+        ($index:expr, $kind:expr $(,)?) => {
+            issues.push(FragmentIssue {
+                fragment: $index,
+                fragment_name: fragments[$index].name.clone(),
+                kind: $kind,
+            })
+        };
+    }
+
+    for (index, fragment) in fragments.iter().enumerate() {
+        // Footnotes only need to be continuous if there are any
+        if fragment.footnote_count > 0 {
+            if fragment.footnote_start != next_footnote {
+                issue!(
+                    index,
+                    FragmentIssueKind::FootnoteDiscontinuity {
+                        expected: next_footnote,
+                        actual: fragment.footnote_start,
+                    },
+                );
+            }
+
+            next_footnote = fragment.footnote_start + fragment.footnote_count;
+        }
+
+        for anchor in &fragment.anchors {
+            match anchors.get(anchor.as_str()) {
+                Some(&first_fragment) => issue!(
+                    index,
+                    FragmentIssueKind::DuplicateAnchor {
+                        anchor: anchor.clone(),
+                        first_fragment,
+                    },
+                ),
+                None => {
+                    anchors.insert(anchor.as_str(), index);
+                }
+            }
+        }
+    }
+
+    // Check references after all anchors and footnotes are known,
+    // since links can point to later fragments.
+    let footnote_references: HashSet<usize> = fragments
+        .iter()
+        .flat_map(|fragment| fragment.footnote_references.iter().copied())
+        .collect();
+
+    let footnotes_listed: HashSet<usize> = fragments
+        .iter()
+        .filter(|fragment| fragment.footnotes_listed)
+        .flat_map(|fragment| {
+            fragment.footnote_start..fragment.footnote_start + fragment.footnote_count
+        })
+        .collect();
+
+    for (index, fragment) in fragments.iter().enumerate() {
+        if fragment.footnotes_listed {
+            for footnote in
+                fragment.footnote_start..fragment.footnote_start + fragment.footnote_count
+            {
+                if !footnote_references.contains(&footnote) {
+                    issue!(
+                        index,
+                        FragmentIssueKind::FootnoteBacklinkUnresolved { footnote },
+                    );
+                }
+            }
+        }
+
+        for &footnote in &fragment.footnote_references {
+            if !footnotes_listed.contains(&footnote) {
+                issue!(index, FragmentIssueKind::FootnoteUnlisted { footnote });
+            }
+        }
+
+        for anchor in &fragment.references {
+            if !anchors.contains_key(anchor.as_str()) {
+                issue!(
+                    index,
+                    FragmentIssueKind::UnresolvedReference {
+                        anchor: anchor.clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    issues
+}
+
+#[test]
+fn fragments() {
+    use crate::data::PageInfo;
+    use crate::render::{html::HtmlRender, Render};
+    use crate::settings::WikitextMode;
+
+    let page_info = PageInfo::dummy();
+
+    let export = |name, wikitext: &str, footnote_offset| {
+        let settings = WikitextSettings {
+            footnote_offset,
+            ..WikitextSettings::from_mode(WikitextMode::Page)
+        };
+
+        let mut text = str!(wikitext);
+        crate::preprocess(&mut text);
+        let tokens = crate::tokenize(&text);
+        let (tree, _) = crate::parse(&tokens, &page_info, &settings).into();
+        let export = FragmentExport::from_tree(name, &tree, &settings);
+        let html = HtmlRender.render(&tree, &page_info, &settings).body;
+        (export, html)
+    };
+
+    let (first, _) = export(
+        "first",
+        "[[# intro]] A[[footnote]]1[[/footnote]] B[[footnote]]2[[/footnote]] [#ending Ending]",
+        0,
+    );
+    assert_eq!(first.anchors, vec!["intro"]);
+    assert_eq!(first.references, vec!["ending"]);
+    assert_eq!(first.footnote_start, 1);
+    assert_eq!(first.footnote_count, 2);
+    assert_eq!(first.footnote_references, vec![1, 2]);
+    assert!(first.footnotes_listed);
+
+    // Rendering continues the footnote numbering from the offset
+    let (second, html) = export(
+        "second",
+        "+ Heading\n\n[[div id=\"ending\"]]\nC[[footnote]]3[[/footnote]]\n[[/div]]\n\n[#toc0 Top]",
+        2,
+    );
+    assert_eq!(second.footnote_start, 3);
+    assert_eq!(second.anchors, vec!["toc0", "ending"]);
+    assert!(html.contains(r#"data-id="3">3</wj-footnote-ref-marker>"#));
+    assert_eq!(check_fragments(&[first.clone(), second.clone()]), vec![]);
+
+    let (third, _) = export(
+        "third",
+        "[[# intro]] D[[footnote]]4[[/footnote]] [#missing Missing]\n\n\
+         [[footnoteblock hide=\"true\"]]",
+        2,
+    );
+    assert_eq!(
+        check_fragments(&[first, second, third]),
+        vec![
+            FragmentIssue {
+                fragment: 2,
+                fragment_name: str!("third"),
+                kind: FragmentIssueKind::FootnoteDiscontinuity {
+                    expected: 4,
+                    actual: 3,
+                },
+            },
+            FragmentIssue {
+                fragment: 2,
+                fragment_name: str!("third"),
+                kind: FragmentIssueKind::DuplicateAnchor {
+                    anchor: str!("intro"),
+                    first_fragment: 0,
+                },
+            },
+            FragmentIssue {
+                fragment: 2,
+                fragment_name: str!("third"),
+                kind: FragmentIssueKind::UnresolvedReference {
+                    anchor: str!("missing"),
+                },
+            },
+        ],
+    );
+
+    // Footnote references and list entries must point to each other
+    let unlisted = FragmentExport {
+        name: str!("unlisted"),
+        footnote_start: 1,
+        footnote_count: 1,
+        footnote_references: vec![1],
+        ..FragmentExport::default()
+    };
+    let unreferenced = FragmentExport {
+        name: str!("unreferenced"),
+        footnote_start: 2,
+        footnote_count: 1,
+        footnotes_listed: true,
+        ..FragmentExport::default()
+    };
+    assert_eq!(
+        check_fragments(&[unlisted, unreferenced]),
+        vec![
+            FragmentIssue {
+                fragment: 0,
+                fragment_name: str!("unlisted"),
+                kind: FragmentIssueKind::FootnoteUnlisted { footnote: 1 },
+            },
+            FragmentIssue {
+                fragment: 1,
+                fragment_name: str!("unreferenced"),
+                kind: FragmentIssueKind::FootnoteBacklinkUnresolved { footnote: 2 },
+            },
+        ],
+    );
+}
@@ -0,0 +1,287 @@
+/*
+ * graph.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Building a site-wide graph of the connections between pages.
+//!
+//! This consumes the syntax trees for many pages, and records each link,
+//! include, and embedded file between them. It can then be used for
+//! orphan page detection or other crosslink analysis, and serializes to
+//! a compact form where edges refer to nodes by index.
+//!
+//! Note that `[[include-messy]]` is substituted before parsing, and so those
+//! includes are not visible here. Only `[[include-elements]]` produces edges.
+
+use crate::data::{extract_backlinks, PageRef};
+use crate::tree::SyntaxTree;
+use std::collections::{HashMap, HashSet};
+use wikidot_normalize::normalize;
+
+/// A directed graph of page connections for a site.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct LinkGraph {
+    /// Each page in the graph.
+    ///
+    /// Edges refer to pages by their index in this list.
+    pub nodes: Vec<LinkNode>,
+
+    /// Each connection between two pages, as `(from, to, type)`.
+    ///
+    /// Duplicate connections of the same type are only recorded once.
+    pub edges: Vec<(usize, usize, LinkEdgeType)>,
+}
+
+/// A page within a [`LinkGraph`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct LinkNode {
+    pub site: String,
+    pub page: String,
+
+    /// Whether this page's syntax tree was added to the graph.
+    ///
+    /// If `false`, the page was only found as the target of another page.
+    pub added: bool,
+}
+
+/// What kind of connection an edge represents.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinkEdgeType {
+    /// The source page links to the target page.
+    Link,
+
+    /// The source page includes the target page.
+    Include,
+
+    /// The source page embeds a file attached to the target page.
+    Embed,
+}
+
+impl LinkGraph {
+    /// Gets the index of the given page, if it is present.
+    pub fn find(&self, site: &str, page: &str) -> Option<usize> {
+        self.nodes
+            .iter()
+            .position(|node| node.site == site && node.page == page)
+    }
+
+    /// Iterates over all edges originating from the given node.
+    pub fn outgoing(
+        &self,
+        node: usize,
+    ) -> impl Iterator<Item = (usize, LinkEdgeType)> + '_ {
+        self.edges
+            .iter()
+            .filter(move |(from, _, _)| *from == node)
+            .map(|&(_, to, etype)| (to, etype))
+    }
+
+    /// Iterates over all edges pointing to the given node.
+    pub fn incoming(
+        &self,
+        node: usize,
+    ) -> impl Iterator<Item = (usize, LinkEdgeType)> + '_ {
+        self.edges
+            .iter()
+            .filter(move |(_, to, _)| *to == node)
+            .map(|&(from, _, etype)| (from, etype))
+    }
+
+    /// Returns all added pages which no other page connects to.
+    pub fn orphans(&self) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|&node| self.nodes[node].added)
+            .filter(|&node| self.incoming(node).all(|(from, _)| from == node))
+            .collect()
+    }
+
+    /// Returns all pages which are connected to, but were never added.
+    ///
+    /// On a complete site graph, these are links to pages which do not exist.
+    pub fn wanted(&self) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|&node| !self.nodes[node].added)
+            .collect()
+    }
+}
+
+/// Constructs a [`LinkGraph`] from pages on a site.
+#[derive(Debug)]
+pub struct LinkGraphBuilder {
+    site: String,
+    graph: LinkGraph,
+    indices: HashMap<(String, String), usize>,
+    edges: HashSet<(usize, usize, LinkEdgeType)>,
+}
+
+impl LinkGraphBuilder {
+    /// Creates a new builder for the given site.
+    ///
+    /// Page references without an explicit site are considered to be on this one.
+    pub fn new(site: &str) -> Self {
+        LinkGraphBuilder {
+            site: str!(site),
+            graph: LinkGraph::default(),
+            indices: HashMap::new(),
+            edges: HashSet::new(),
+        }
+    }
+
+    /// Adds all of the connections within a page's syntax tree.
+    pub fn add_page(&mut self, page_ref: &PageRef, tree: &SyntaxTree) {
+        let from = self.node(page_ref);
+        self.graph.nodes[from].added = true;
+
+        // Uses the same walk as backlinks, so both agree on what a page references
+        let backlinks = extract_backlinks(tree);
+        let targets = backlinks
+            .internal_links
+            .iter()
+            .map(|page_ref| (Some(page_ref), LinkEdgeType::Link))
+            .chain(
+                backlinks
+                    .included_pages
+                    .iter()
+                    .map(|page_ref| (Some(page_ref), LinkEdgeType::Include)),
+            )
+            .chain(
+                backlinks
+                    .file_references
+                    .iter()
+                    .map(|file| (file.page.as_ref(), LinkEdgeType::Embed)),
+            );
+
+        for (target, etype) in targets {
+            // Files without a page are attached to this one
+            let to = match target {
+                Some(page_ref) if page_ref.page().is_empty() => continue,
+                Some(page_ref) => self.node(page_ref),
+                None => from,
+            };
+
+            self.edge(from, to, etype);
+        }
+    }
+
+    /// Finishes building, returning the graph.
+    #[inline]
+    pub fn build(self) -> LinkGraph {
+        self.graph
+    }
+
+    fn node(&mut self, page_ref: &PageRef) -> usize {
+        let (site, page) = page_ref.fields_or(&self.site);
+        let page = {
+            // Strip any anchor, then normalize
+            let mut page = str!(page.split('#').next().unwrap_or(page));
+            normalize(&mut page);
+            page
+        };
+
+        let key = (str!(site), page);
+        if let Some(&index) = self.indices.get(&key) {
+            return index;
+        }
+
+        let index = self.graph.nodes.len();
+        self.graph.nodes.push(LinkNode {
+            site: key.0.clone(),
+            page: key.1.clone(),
+            added: false,
+        });
+        self.indices.insert(key, index);
+        index
+    }
+
+    fn edge(&mut self, from: usize, to: usize, etype: LinkEdgeType) {
+        let key = (from, to, etype);
+        if self.edges.insert(key) {
+            self.graph.edges.push(key);
+        }
+    }
+}
+
+#[test]
+fn link_graph() {
+    use crate::data::PageInfo;
+    use crate::settings::{WikitextMode, WikitextSettings};
+
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let mut builder = LinkGraphBuilder::new("test");
+
+    macro_rules! add {
+        ($page:expr, $wikitext:expr $(,)?) => {{
+            let mut page_info = PageInfo::dummy();
+            page_info.site = cow!("test");
+            page_info.page = cow!($page);
+
+            let mut text = str!($wikitext);
+            crate::preprocess(&mut text);
+            let tokens = crate::tokenize(&text);
+            let (tree, _) = crate::parse(&tokens, &page_info, &settings).into();
+            builder.add_page(&PageRef::page_only($page), &tree);
+        }};
+    }
+
+    add!(
+        "start",
+        "[[[Apple]]] [/banana Banana] [[image cherry/photo.png]]"
+    );
+    add!(
+        "apple",
+        "[[[start]]] [[[:other:thing]]] [https://example.com/ Example]"
+    );
+    add!("banana", "[[[apple#section]]] [[[apple]]]");
+    add!("lonely", "Nobody links here.");
+    add!(
+        "media",
+        "[[gallery]]\n: /apple/a.png\n: b.png\n[[/gallery]]\n\n\
+         [[audio /banana/song.mp3]] [[video https://example.com/clip.mp4]]",
+    );
+
+    let graph = builder.build();
+    let node = |site, page| graph.find(site, page).expect("Missing node");
+
+    let start = node("test", "start");
+    let apple = node("test", "apple");
+    let banana = node("test", "banana");
+    let cherry = node("test", "cherry");
+    let lonely = node("test", "lonely");
+    let other = node("other", "thing");
+    let media = node("test", "media");
+
+    assert_eq!(
+        graph.edges,
+        vec![
+            (start, apple, LinkEdgeType::Link),
+            (start, banana, LinkEdgeType::Link),
+            (start, cherry, LinkEdgeType::Embed),
+            (apple, start, LinkEdgeType::Link),
+            (apple, other, LinkEdgeType::Link),
+            (banana, apple, LinkEdgeType::Link),
+            (media, apple, LinkEdgeType::Embed),
+            (media, media, LinkEdgeType::Embed),
+            (media, banana, LinkEdgeType::Embed),
+        ],
+    );
+    assert_eq!(graph.orphans(), vec![lonely, media]);
+    assert_eq!(graph.wanted(), vec![cherry, other]);
+}
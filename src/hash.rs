@@ -0,0 +1,53 @@
+/*
+ * hash.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! A stable hash for deriving identifiers from content.
+//!
+//! Unlike [`DefaultHasher`](std::collections::hash_map::DefaultHasher),
+//! this gives the same value across Rust versions and platforms, so it
+//! can be used for identifiers which are stored outside of ftml.
+
+const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const PRIME: u64 = 0x0100_0000_01b3;
+
+/// Hashes the given strings using 64-bit FNV-1a.
+///
+/// Each part is followed by a `0xff` byte, which cannot occur in UTF-8,
+/// so that `["ab", "c"]` and `["a", "bc"]` hash differently.
+pub fn stable_hash(parts: &[&str]) -> u64 {
+    let mut hash = OFFSET_BASIS;
+
+    for part in parts {
+        for &byte in part.as_bytes().iter().chain(&[0xff]) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(PRIME);
+        }
+    }
+
+    hash
+}
+
+#[test]
+fn test_stable_hash() {
+    assert_eq!(stable_hash(&[]), OFFSET_BASIS);
+    assert_eq!(stable_hash(&["apple"]), stable_hash(&["apple"]));
+    assert_ne!(stable_hash(&["ab", "c"]), stable_hash(&["a", "bc"]));
+    assert_ne!(stable_hash(&["apple"]), stable_hash(&["banana"]));
+}
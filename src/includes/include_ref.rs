@@ -20,6 +20,7 @@
 
 use crate::data::PageRef;
 use crate::tree::VariableMap;
+use std::borrow::Cow;
 
 /// Represents an include block before it has been replaced with the fetched page.
 ///
@@ -55,6 +56,17 @@ impl<'t> IncludeRef<'t> {
     pub fn variables(&self) -> &VariableMap<'t> {
         &self.variables
     }
+
+    pub fn to_owned(&self) -> IncludeRef<'static> {
+        IncludeRef {
+            page_ref: self.page_ref.to_owned(),
+            variables: self
+                .variables
+                .iter()
+                .map(|(key, value)| (Cow::Owned(str!(key)), Cow::Owned(str!(value))))
+                .collect(),
+        }
+    }
 }
 
 impl<'t> From<IncludeRef<'t>> for (PageRef<'t>, VariableMap<'t>) {
@@ -59,4 +59,19 @@ pub trait Includer<'t> {
         &mut self,
         page_ref: &PageRef<'t>,
     ) -> Result<Cow<'t, str>, Self::Error>;
+
+    /// Handles the inclusion of a page which would recurse.
+    ///
+    /// This is used for a page which includes itself, directly or indirectly,
+    /// and for includes nested more than [`max_include_depth`] levels deep.
+    ///
+    /// By default, the include is replaced with nothing.
+    ///
+    /// [`max_include_depth`]: crate::settings::ParseLimits::max_include_depth
+    fn recursive_include(
+        &mut self,
+        _page_ref: &PageRef<'t>,
+    ) -> Result<Cow<'t, str>, Self::Error> {
+        Ok(Cow::Borrowed(""))
+    }
 }
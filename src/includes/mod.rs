@@ -31,16 +31,25 @@ mod test;
 mod include_ref;
 mod includer;
 mod parse;
+mod source_map;
 
 pub use self::include_ref::IncludeRef;
 pub use self::includer::{DebugIncluder, FetchedPage, Includer, NullIncluder};
 
+pub use self::source_map::{SourceLocation, SourceMap, SourceRegion};
+
 use self::parse::parse_include_block;
+use self::source_map::Segment;
 use crate::data::PageRef;
-use crate::settings::WikitextSettings;
+use crate::error::FtmlError;
+use crate::settings::{GatedElement, WikitextSettings};
 use crate::tree::VariableMap;
 use once_cell::sync::Lazy;
 use regex::{Regex, RegexBuilder};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::ops::Range;
 
 static INCLUDE_REGEX: Lazy<Regex> = Lazy::new(|| {
     RegexBuilder::new(r"^\[\[\s*include-messy\s+")
@@ -50,36 +59,161 @@ static INCLUDE_REGEX: Lazy<Regex> = Lazy::new(|| {
         .build()
         .unwrap()
 });
+static INCLUDE_COMPATIBILITY_REGEX: Lazy<Regex> = Lazy::new(|| {
+    RegexBuilder::new(r"^\[\[\s*include(?:-messy)?\s+")
+        .case_insensitive(true)
+        .multi_line(true)
+        .dot_matches_new_line(true)
+        .build()
+        .unwrap()
+});
 static VARIABLE_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"\{\$(?P<name>[a-zA-Z0-9_\-]+)\}").unwrap());
 
 /// Replaces the include blocks in a string with the content of the pages referenced by those
 /// blocks.
-pub fn include<'t, I, E, F>(
+///
+/// Included pages are themselves expanded, up to [`max_include_depth`] levels deep.
+/// A page which would include itself, directly or through other pages, is not
+/// expanded again, and is replaced using [`Includer::recursive_include`].
+/// Each page is only fetched once, however many times it is included.
+///
+/// The returned list has every page which was included, in document order,
+/// with nested includes following the page which included them.
+///
+/// Fails with [`FtmlError::Include`] if the includer returns an error,
+/// or returns pages which do not match those requested. If expanding would
+/// exceed [`max_includes`] or [`max_include_bytes`], this fails with
+/// [`FtmlError::IncludeLimitExceeded`] instead.
+///
+/// [`max_include_depth`]: crate::settings::ParseLimits::max_include_depth
+/// [`max_includes`]: crate::settings::ParseLimits::max_includes
+/// [`max_include_bytes`]: crate::settings::ParseLimits::max_include_bytes
+#[inline]
+pub fn include<'t, I>(
+    input: &'t str,
+    settings: &WikitextSettings,
+    includer: I,
+) -> Result<(String, Vec<PageRef<'t>>), FtmlError>
+where
+    I: Includer<'t>,
+    I::Error: Display,
+{
+    include_with_source_map(input, settings, includer)
+        .map(|(output, pages, _)| (output, pages))
+}
+
+/// Like [`include`], but also returns where each part of the output came from.
+///
+/// The [`SourceMap`] can be used to point parser warnings at the page
+/// they originated from, see [`SourceMap::annotate`]. Preprocess the output with
+/// [`preprocess_with_source_map`](crate::preprocess_with_source_map) to keep the map
+/// in line with the text.
+pub fn include_with_source_map<'t, I>(
     input: &'t str,
     settings: &WikitextSettings,
     mut includer: I,
-    invalid_return: F,
-) -> Result<(String, Vec<PageRef<'t>>), E>
+) -> Result<(String, Vec<PageRef<'t>>, SourceMap), FtmlError>
 where
-    I: Includer<'t, Error = E>,
-    F: FnOnce() -> E,
+    I: Includer<'t>,
+    I::Error: Display,
 {
-    if !settings.enable_page_syntax {
+    if !GatedElement::Include.is_allowed(settings) {
         info!("Includes are disabled for this input, skipping");
 
         let output = str!(input);
         let pages = vec![];
-        return Ok((output, pages));
+        let map = SourceMap::identity(input.len());
+        return Ok((output, pages, map));
     }
 
     info!("Finding and replacing all instances of include blocks in text");
 
+    let mut state = ExpandState::default();
+
+    match expand(input, settings, &mut includer, &mut state, |include| {
+        include
+    }) {
+        Ok((output, map)) => Ok((output, state.pages, map)),
+        Err(IncludeFailure::Includer(error)) => {
+            Err(FtmlError::Include(error.to_string()))
+        }
+        Err(IncludeFailure::Invalid) => Err(FtmlError::Include(str!(
+            "fetched pages do not match those requested"
+        ))),
+        Err(IncludeFailure::LimitExceeded) => Err(FtmlError::IncludeLimitExceeded),
+    }
+}
+
+/// State shared by every level of include expansion.
+#[derive(Debug, Default)]
+struct ExpandState<'t> {
+    /// Every page which was included so far, in document order.
+    pages: Vec<PageRef<'t>>,
+
+    /// Each page currently being expanded, outermost first.
+    stack: Vec<PageRef<'t>>,
+
+    /// The content of each page fetched so far, so that pages
+    /// included more than once are only requested once.
+    fetched: HashMap<PageRef<'t>, Option<Cow<'t, str>>>,
+
+    /// How many includes have been expanded.
+    includes: usize,
+
+    /// How many bytes of included content have been added.
+    bytes: usize,
+}
+
+/// Internal error type for include expansion.
+#[derive(Debug)]
+enum IncludeFailure<E> {
+    /// The includer returned an error.
+    Includer(E),
+
+    /// The includer returned pages which do not match those requested.
+    Invalid,
+
+    /// Expanding would exceed the include limits in the settings.
+    LimitExceeded,
+}
+
+impl<E> From<E> for IncludeFailure<E> {
+    #[inline]
+    fn from(error: E) -> Self {
+        IncludeFailure::Includer(error)
+    }
+}
+
+/// Expands all the include blocks in the input, recursing into fetched pages.
+///
+/// Alongside the output, this returns a source map where text from the input
+/// has no page, with positions in the input.
+///
+/// References found in nested pages borrow from temporary strings,
+/// so `convert` is used to give them the lifetime the includer expects.
+fn expand<'s, 't, I, E>(
+    input: &'s str,
+    settings: &WikitextSettings,
+    includer: &mut I,
+    state: &mut ExpandState<'t>,
+    convert: fn(IncludeRef<'s>) -> IncludeRef<'t>,
+) -> Result<(String, SourceMap), IncludeFailure<E>>
+where
+    I: Includer<'t, Error = E>,
+{
+    let limits = &settings.parse_limits;
+    let regex = if settings.use_include_compatibility {
+        &*INCLUDE_COMPATIBILITY_REGEX
+    } else {
+        &*INCLUDE_REGEX
+    };
+
     let mut ranges = Vec::new();
     let mut includes = Vec::new();
 
     // Get include references
-    for mtch in INCLUDE_REGEX.find_iter(input) {
+    for mtch in regex.find_iter(input) {
         let start = mtch.start();
 
         debug!(
@@ -91,37 +225,61 @@ where
         match parse_include_block(input, start, settings) {
             Ok((include, end)) => {
                 ranges.push(start..end);
-                includes.push(include);
+                includes.push(convert(include));
             }
             Err(_) => warn!("Unable to parse include regex match"),
         }
     }
 
-    // Retrieve included pages
-    let fetched_pages = includer.include_pages(&includes)?;
+    if includes.is_empty() {
+        return Ok((str!(input), SourceMap::identity(input.len())));
+    }
 
-    // Ensure it matches up with the request
-    if includes.len() != fetched_pages.len() {
-        return Err(invalid_return());
+    // Determine which includes would recurse
+    let recursive = includes
+        .iter()
+        .map(|include| {
+            state.stack.len() >= limits.max_include_depth
+                || state.stack.contains(include.page_ref())
+        })
+        .collect::<Vec<_>>();
+
+    // Retrieve included pages, skipping any fetched before
+    let mut requests: Vec<IncludeRef<'t>> = Vec::new();
+    for (include, &recursive) in includes.iter().zip(&recursive) {
+        let page_ref = include.page_ref();
+        let requested = requests
+            .iter()
+            .any(|request| request.page_ref() == page_ref);
+
+        if !recursive && !requested && !state.fetched.contains_key(page_ref) {
+            requests.push(include.clone());
+        }
     }
 
-    // Substitute inclusions
-    //
-    // We must iterate backwards for all the indices to be valid
+    if !requests.is_empty() {
+        let fetched_pages = includer.include_pages(&requests)?;
 
-    let ranges_iter = ranges.into_iter();
-    let includes_iter = includes.into_iter();
-    let fetched_iter = fetched_pages.into_iter();
+        // Ensure it matches up with the request
+        if requests.len() != fetched_pages.len() {
+            return Err(IncludeFailure::Invalid);
+        }
 
-    let joined_iter = ranges_iter.zip(includes_iter).zip(fetched_iter).rev();
+        for (request, fetched) in requests.iter().zip(fetched_pages) {
+            if request.page_ref() != &fetched.page_ref {
+                return Err(IncludeFailure::Invalid);
+            }
 
-    // Borrowing from the original text and doing in-place insertions
-    // will not work here. We are trying to both return the page names
-    // (slices from the input string), and replace it with new content.
-    let mut output = String::from(input);
-    let mut pages = Vec::new();
+            state.fetched.insert(fetched.page_ref, fetched.content);
+        }
+    }
+
+    // Substitute inclusions
+    let mut output = String::with_capacity(input.len());
+    let mut map = SourceMap::default();
+    let mut last = 0;
 
-    for ((range, include), fetched) in joined_iter {
+    for ((range, include), recursive) in ranges.into_iter().zip(includes).zip(recursive) {
         let (page_ref, variables) = include.into();
 
         info!(
@@ -129,41 +287,101 @@ where
             range.start, range.end,
         );
 
-        // Ensure the returned page reference matches
-        if page_ref != fetched.page_ref {
-            return Err(invalid_return());
+        push_mapped(&mut output, &mut map, input, last..range.start);
+        last = range.end;
+
+        // Include would loop or nest too deeply, return premade template
+        if recursive {
+            warn!("Not expanding recursive include of page {page_ref}");
+
+            let template = includer.recursive_include(&page_ref)?;
+            push_template(&mut output, &mut map, &template, range);
+            state.pages.push(page_ref);
+            continue;
         }
 
-        // Get replaced content, or error message
-        let replace_with = match fetched.content {
-            // Take fetched content, replace variables
+        let content = match state.fetched.get(&page_ref) {
+            Some(content) => content.clone(),
+            None => return Err(IncludeFailure::Invalid),
+        };
+
+        match content {
+            // Take fetched content, replace variables, then expand its includes
             Some(mut content) => {
-                replace_variables(content.to_mut(), &variables);
-                content
+                let segments = replace_variables(&mut content, &variables);
+
+                state.includes += 1;
+                state.bytes += content.len();
+
+                if exceeds(limits.max_includes, state.includes)
+                    || exceeds(limits.max_include_bytes, state.bytes)
+                {
+                    warn!("Include limits exceeded while including page {page_ref}");
+                    return Err(IncludeFailure::LimitExceeded);
+                }
+
+                state.pages.push(page_ref.clone());
+                state.stack.push(page_ref);
+
+                let (expanded, expanded_map) =
+                    expand(&content, settings, includer, state, |include| {
+                        include.to_owned()
+                    })?;
+
+                let page_ref = state.stack.pop().expect("Include stack empty");
+                map.append(expanded_map, output.len(), &page_ref, &segments);
+                output.push_str(&expanded);
             }
 
             // Include not found, return premade template
-            None => includer.no_such_include(&page_ref)?,
-        };
+            None => {
+                let template = includer.no_such_include(&page_ref)?;
+                push_template(&mut output, &mut map, &template, range);
+                state.pages.push(page_ref);
+            }
+        }
+    }
 
-        // Append page to final list
-        pages.push(page_ref);
+    push_mapped(&mut output, &mut map, input, last..input.len());
+    Ok((output, map))
+}
 
-        // Perform the substitution
-        output.replace_range(range, &replace_with);
-    }
+/// Checks whether a count is over an optional limit.
+#[inline]
+fn exceeds(limit: Option<usize>, count: usize) -> bool {
+    matches!(limit, Some(limit) if count > limit)
+}
 
-    // Since we iterate in reverse order, the pages are reversed.
-    pages.reverse();
+/// Appends part of the input to the output, recording it in the source map.
+fn push_mapped(
+    output: &mut String,
+    map: &mut SourceMap,
+    input: &str,
+    range: Range<usize>,
+) {
+    let start = output.len();
+    output.push_str(&input[range.clone()]);
+    map.push(start..output.len(), None, range);
+}
 
-    // Return
-    Ok((output, pages))
+/// Appends a template to the output, which is mapped to the include block it replaces.
+fn push_template(
+    output: &mut String,
+    map: &mut SourceMap,
+    template: &str,
+    block: Range<usize>,
+) {
+    let start = output.len();
+    output.push_str(template);
+    map.push(start..output.len(), None, block);
 }
 
 /// Replaces all specified variables in the content to be included.
 ///
+/// Returns the segments of the new content, relating it to the original.
+///
 /// Read <https://www.wikidot.com/doc-wiki-syntax:include> for more details.
-fn replace_variables(content: &mut String, variables: &VariableMap) {
+fn replace_variables(content: &mut Cow<str>, variables: &VariableMap) -> Vec<Segment> {
     let mut matches = Vec::new();
 
     // Find all variables
@@ -176,10 +394,41 @@ fn replace_variables(content: &mut String, variables: &VariableMap) {
         }
     }
 
-    // Replace the variables
-    // Iterates backwards so indices stay valid
-    matches.reverse();
+    if matches.is_empty() {
+        return vec![Segment {
+            range: 0..content.len(),
+            source: 0..content.len(),
+            exact: true,
+        }];
+    }
+
+    // Build the new content, noting where everything came from
+    let mut output = String::with_capacity(content.len());
+    let mut segments = Vec::new();
+    let mut last = 0;
+
+    let mut push = |output: &mut String, text: &str, source: Range<usize>, exact| {
+        let start = output.len();
+        output.push_str(text);
+        segments.push(Segment {
+            range: start..output.len(),
+            source,
+            exact,
+        });
+    };
+
     for (value, range) in matches {
-        content.replace_range(range, value);
+        push(
+            &mut output,
+            &content[last..range.start],
+            last..range.start,
+            true,
+        );
+        push(&mut output, value, range.clone(), false);
+        last = range.end;
     }
+
+    push(&mut output, &content[last..], last..content.len(), true);
+    *content = Cow::Owned(output);
+    segments
 }
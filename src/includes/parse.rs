@@ -21,7 +21,7 @@
 //! This module provides functions to parse strings into [`IncludeRef`]s
 
 use super::IncludeRef;
-use crate::data::{PageRef, PageRefParseError};
+use crate::data::PageRef;
 use crate::settings::WikitextSettings;
 use pest::iterators::Pairs;
 use pest::Parser;
@@ -78,7 +78,7 @@ pub fn parse_include_block<'t>(
 /// Creates an [`IncludeRef`] out of pest [`Pairs`].
 fn process_pairs(mut pairs: Pairs<Rule>) -> Result<IncludeRef, IncludeParseError> {
     let page_raw = pairs.next().ok_or(IncludeParseError)?.as_str();
-    let page_ref = PageRef::parse(page_raw)?;
+    let page_ref = PageRef::parse(page_raw).map_err(|_| IncludeParseError)?;
 
     debug!("Got page for include {page_ref:?}");
     let mut arguments = HashMap::new();
@@ -134,10 +134,3 @@ fn process_pairs(mut pairs: Pairs<Rule>) -> Result<IncludeRef, IncludeParseError
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct IncludeParseError;
-
-impl From<PageRefParseError> for IncludeParseError {
-    #[inline]
-    fn from(_: PageRefParseError) -> Self {
-        IncludeParseError
-    }
-}
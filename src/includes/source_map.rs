@@ -0,0 +1,285 @@
+/*
+ * includes/source_map.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use crate::data::PageRef;
+use crate::parsing::ParseError;
+use std::mem;
+use std::ops::Range;
+
+/// Records which page each part of the include-expanded text came from.
+///
+/// Positions are byte offsets. For included pages, source offsets are
+/// relative to that page's wikitext as fetched, before variables were substituted.
+/// Any text within a substituted variable points to the variable itself,
+/// and any missing or recursive page template to the include block.
+///
+/// This maps the output of include expansion. Since preprocessing also changes
+/// the text, such as by compressing blank lines, run it with
+/// [`preprocess_with_source_map`] so the map matches the text being parsed.
+///
+/// [`preprocess_with_source_map`]: crate::preprocess_with_source_map
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct SourceMap {
+    regions: Vec<SourceRegion>,
+}
+
+/// A contiguous region of the expanded text, and where it came from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct SourceRegion {
+    /// The range of the expanded text this region covers.
+    pub range: Range<usize>,
+
+    /// The page this text came from.
+    ///
+    /// If `None`, then it came from the original input.
+    pub page: Option<PageRef<'static>>,
+
+    /// The corresponding range in the source page or input.
+    pub source: Range<usize>,
+}
+
+/// A location in the original wikitext of a page.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct SourceLocation {
+    /// The page this location is in.
+    ///
+    /// If `None`, then it is in the original input.
+    pub page: Option<PageRef<'static>>,
+
+    /// The range within that page's wikitext.
+    pub span: Range<usize>,
+}
+
+impl SourceMap {
+    /// Creates a source map where all of the text is from the input.
+    pub fn identity(len: usize) -> Self {
+        let mut map = SourceMap::default();
+        map.push(0..len, None, 0..len);
+        map
+    }
+
+    #[inline]
+    pub fn regions(&self) -> &[SourceRegion] {
+        &self.regions
+    }
+
+    pub(crate) fn push(
+        &mut self,
+        range: Range<usize>,
+        page: Option<PageRef<'static>>,
+        source: Range<usize>,
+    ) {
+        if range.is_empty() {
+            return;
+        }
+
+        self.regions.push(SourceRegion {
+            range,
+            page,
+            source,
+        });
+    }
+
+    /// Appends another map, whose text begins at `offset` in this one.
+    ///
+    /// Regions from that map's input are attributed to `page`, and their
+    /// source positions are translated using `segments`.
+    pub(crate) fn append(
+        &mut self,
+        other: SourceMap,
+        offset: usize,
+        page: &PageRef,
+        segments: &[Segment],
+    ) {
+        for region in other.regions {
+            let shift = |range: Range<usize>| range.start + offset..range.end + offset;
+
+            // From a more deeply nested page, already translated
+            if region.page.is_some() {
+                self.push(shift(region.range), region.page, region.source);
+                continue;
+            }
+
+            // Not copied directly, so map the whole range
+            if region.range.len() != region.source.len() {
+                let start = translate(segments, region.source.start);
+                let end = translate(segments, region.source.end);
+                self.push(shift(region.range), Some(page.to_owned()), start..end);
+                continue;
+            }
+
+            // From this page, split it up over the variable substitutions
+            for segment in segments {
+                let start = region.source.start.max(segment.range.start);
+                let end = region.source.end.min(segment.range.end);
+                if start >= end {
+                    continue;
+                }
+
+                let range = region.range.start + (start - region.source.start)
+                    ..region.range.start + (end - region.source.start);
+
+                let source = if segment.exact {
+                    let translate =
+                        |offset| segment.source.start + (offset - segment.range.start);
+                    translate(start)..translate(end)
+                } else {
+                    segment.source.clone()
+                };
+
+                self.push(shift(range), Some(page.to_owned()), source);
+            }
+        }
+    }
+
+    /// Updates the map after part of the text was replaced.
+    ///
+    /// The replacement has length `len`, and maps to the replaced source
+    /// within the region it starts in. Any text after it is shifted.
+    pub(crate) fn replace(&mut self, replaced: Range<usize>, len: usize) {
+        debug_assert!(!replaced.is_empty(), "Replaced range is empty");
+
+        let shift = |offset: usize| offset - replaced.end + replaced.start + len;
+        let regions = mem::take(&mut self.regions);
+
+        for SourceRegion {
+            range,
+            page,
+            source,
+        } in regions
+        {
+            // Entirely before or after the replacement
+            if range.end <= replaced.start {
+                self.push(range, page, source);
+                continue;
+            }
+
+            if range.start >= replaced.end {
+                self.push(shift(range.start)..shift(range.end), page, source);
+                continue;
+            }
+
+            // Otherwise, split it around the replacement
+            let exact = range.len() == source.len();
+            let source_of = |part: Range<usize>| {
+                if exact {
+                    let translate = |offset| source.start + (offset - range.start);
+                    translate(part.start)..translate(part.end)
+                } else {
+                    source.clone()
+                }
+            };
+
+            if range.start < replaced.start {
+                let part = range.start..replaced.start;
+                self.push(part.clone(), page.clone(), source_of(part));
+            }
+
+            if range.start <= replaced.start {
+                let part = replaced.start..replaced.end.min(range.end);
+                let new_range = replaced.start..replaced.start + len;
+                self.push(new_range, page.clone(), source_of(part));
+            }
+
+            if range.end > replaced.end {
+                let part = replaced.end..range.end;
+                self.push(shift(part.start)..shift(part.end), page, source_of(part));
+            }
+        }
+    }
+
+    /// Finds the original location of a span in the expanded text.
+    ///
+    /// The span is assumed to be within the region its start is in,
+    /// and is truncated to the end of that region if not.
+    ///
+    /// If the region was not copied directly, such as a variable value
+    /// or a missing page template, then the whole source range is returned.
+    pub fn locate(&self, span: Range<usize>) -> Option<SourceLocation> {
+        let region = self.find_region(span.start)?;
+        let span = if region.range.len() == region.source.len() {
+            let translate = |offset: usize| {
+                let delta = offset.saturating_sub(region.range.start);
+                region.source.start + delta.min(region.source.len())
+            };
+
+            translate(span.start)..translate(span.end)
+        } else {
+            region.source.clone()
+        };
+
+        Some(SourceLocation {
+            page: region.page.clone(),
+            span,
+        })
+    }
+
+    fn find_region(&self, offset: usize) -> Option<&SourceRegion> {
+        let index = self
+            .regions
+            .partition_point(|region| region.range.end <= offset);
+
+        // Spans at the very end of the text belong to the last region
+        match self.regions.get(index) {
+            Some(region) => Some(region),
+            None => self
+                .regions
+                .last()
+                .filter(|region| region.range.end == offset),
+        }
+    }
+
+    /// Adds the original location to each of these parsing errors.
+    pub fn annotate(&self, errors: &mut [ParseError]) {
+        for error in errors {
+            if let Some(location) = self.locate(error.span()) {
+                error.set_source(location);
+            }
+        }
+    }
+}
+
+/// A piece of a page's text after variables were substituted.
+///
+/// If `exact`, then each position corresponds to the same offset within
+/// `source`, otherwise the range is a variable value, and all of it
+/// corresponds to the variable reference.
+#[derive(Debug, Clone)]
+pub(crate) struct Segment {
+    pub range: Range<usize>,
+    pub source: Range<usize>,
+    pub exact: bool,
+}
+
+/// Converts an offset in a page's text to one in its original text.
+fn translate(segments: &[Segment], offset: usize) -> usize {
+    let index = segments.partition_point(|segment| segment.range.end < offset);
+    match segments.get(index) {
+        Some(segment) if segment.exact => {
+            segment.source.start + (offset - segment.range.start)
+        }
+        Some(segment) if offset == segment.range.end => segment.source.end,
+        Some(segment) => segment.source.start,
+        None => offset,
+    }
+}
@@ -18,8 +18,10 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use super::{include, DebugIncluder, PageRef};
+use super::{include, DebugIncluder, FetchedPage, IncludeRef, Includer, PageRef};
 use crate::settings::{WikitextMode, WikitextSettings};
+use std::borrow::Cow;
+use std::convert::Infallible;
 
 #[test]
 fn includes() {
@@ -28,7 +30,7 @@ fn includes() {
     macro_rules! test {
         ($text:expr, $expected:expr $(,)?) => {{
             let mut text = str!($text);
-            let result = include(&mut text, &settings, DebugIncluder, || panic!());
+            let result = include(&mut text, &settings, DebugIncluder);
             let (output, actual) = result.expect("Fetching pages failed");
             let expected = $expected;
 
@@ -270,3 +272,364 @@ fn includes() {
         vec![],
     );
 }
+
+struct NestedIncluder;
+
+impl<'t> Includer<'t> for NestedIncluder {
+    type Error = Infallible;
+
+    fn include_pages(
+        &mut self,
+        includes: &[IncludeRef<'t>],
+    ) -> Result<Vec<FetchedPage<'t>>, Infallible> {
+        let pages = includes
+            .iter()
+            .map(|include| {
+                let page_ref = include.page_ref().clone();
+                let content = match page_ref.page() {
+                    "a" => Some(cow!("A\n[[include-messy b x=1]]\nA")),
+                    "b" => Some(cow!("B{$x}\n[[include-messy c]]")),
+                    "c" => Some(cow!("C\n[[include-messy a]]")),
+                    "broken" => Some(cow!("Z [[span]]")),
+                    "spaced" => Some(cow!("First\n\n\n\n\tSecond [[span]]")),
+                    "missing" => None,
+                    "laugh-0" => Some(cow!("lol")),
+                    page if page.starts_with("laugh-") => {
+                        let depth: usize = page[6..].parse().unwrap();
+                        let include = format!("[[include-messy laugh-{}]]\n", depth - 1);
+                        Some(Cow::Owned(include.repeat(10)))
+                    }
+                    page => page.strip_prefix("deep-").map(|depth| {
+                        let depth: usize = depth.parse().unwrap();
+                        Cow::Owned(format!(
+                            "{depth}\n[[include-messy deep-{}]]",
+                            depth + 1
+                        ))
+                    }),
+                };
+
+                FetchedPage { page_ref, content }
+            })
+            .collect();
+
+        Ok(pages)
+    }
+
+    fn no_such_include(
+        &mut self,
+        page_ref: &PageRef<'t>,
+    ) -> Result<Cow<'t, str>, Infallible> {
+        Ok(Cow::Owned(format!("<MISSING {page_ref}>")))
+    }
+
+    fn recursive_include(
+        &mut self,
+        page_ref: &PageRef<'t>,
+    ) -> Result<Cow<'t, str>, Infallible> {
+        Ok(Cow::Owned(format!("<RECURSIVE {page_ref}>")))
+    }
+}
+
+#[test]
+fn nested_includes() {
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+
+    macro_rules! test {
+        ($settings:expr, $text:expr, $expected_output:expr, $expected_pages:expr $(,)?) => {{
+            let result = include($text, $settings, NestedIncluder);
+            let (output, pages) = result.expect("Fetching pages failed");
+            let pages = pages.iter().map(|page| page.page()).collect::<Vec<_>>();
+
+            assert_eq!(
+                output, $expected_output,
+                "Actual output doesn't match expected"
+            );
+            assert_eq!(pages, $expected_pages, "Actual pages don't match expected");
+        }};
+    }
+
+    // Nested includes and variables
+    test!(
+        &settings,
+        "[[include-messy b x=2]]\n[[include-messy missing]]",
+        "B2\nC\nA\n<RECURSIVE b>\nA\n<MISSING missing>",
+        vec!["b", "c", "a", "b", "missing"],
+    );
+
+    // Cycles
+    test!(
+        &settings,
+        "[[include-messy a]]",
+        "A\nB1\nC\n<RECURSIVE a>\nA",
+        vec!["a", "b", "c", "a"],
+    );
+
+    // Depth limit
+    test!(
+        &settings,
+        "[[include-messy deep-0]]",
+        "0\n1\n2\n3\n4\n5\n6\n7\n8\n9\n<RECURSIVE deep-10>",
+        (0..=10)
+            .map(|depth| format!("deep-{depth}"))
+            .collect::<Vec<_>>(),
+    );
+
+    // Wikidot-compatible syntax
+    let settings = WikitextSettings {
+        use_include_compatibility: true,
+        ..settings.clone()
+    };
+
+    test!(
+        &settings,
+        "[[include :scp-wiki:missing]]\n[[include-messy b x=3]]",
+        "<MISSING :scp-wiki:missing>\nB3\nC\nA\n<RECURSIVE b>\nA",
+        vec!["missing", "b", "c", "a", "b"],
+    );
+}
+
+#[test]
+fn source_map() {
+    use super::include_with_source_map;
+
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let input = "X\n[[include-messy b x=22]]\nY";
+    let result = include_with_source_map(input, &settings, NestedIncluder);
+    let (output, _, map) = result.expect("Fetching pages failed");
+    assert_eq!(output, "X\nB22\nC\nA\n<RECURSIVE b>\nA\nY");
+
+    macro_rules! check {
+        ($needle:expr, $page:expr, $span:expr $(,)?) => {{
+            let start = output.find($needle).expect("Needle not found in output");
+            let span = start..start + $needle.len();
+            let location = map.locate(span).expect("No location found");
+
+            assert_eq!(
+                location.page.as_ref().map(|page| page.page()),
+                $page,
+                "Page doesn't match for {:?}",
+                $needle,
+            );
+            assert_eq!(location.span, $span, "Span doesn't match for {:?}", $needle);
+        }};
+    }
+
+    check!("X", None, 0..1);
+    check!("B", Some("b"), 0..1);
+    check!("22", Some("b"), 1..5);
+    check!("C", Some("c"), 0..1);
+    check!("A\n<", Some("a"), 0..2);
+    check!("<RECURSIVE b>", Some("a"), 2..25);
+    check!("Y", None, 27..28);
+
+    // Whole output is covered, without gaps
+    let mut last = 0;
+    for region in map.regions() {
+        assert_eq!(region.range.start, last, "Gap in source map");
+        last = region.range.end;
+    }
+    assert_eq!(last, output.len());
+
+    // Errors are annotated
+    let input = "Apple\n[[include-messy broken]]";
+    let result = include_with_source_map(input, &settings, NestedIncluder);
+    let (output, _, map) = result.expect("Fetching pages failed");
+    let page_info = crate::data::PageInfo::dummy();
+    let tokens = crate::tokenize(&output);
+    let (_, mut errors) = crate::parse(&tokens, &page_info, &settings).into();
+    assert!(!errors.is_empty());
+    map.annotate(&mut errors);
+
+    for error in errors {
+        let source = error.source().expect("Error not annotated");
+        let page = source.page.as_ref().expect("Error not in included page");
+        assert_eq!(page.page(), "broken");
+        assert!(source.span.end <= "Z [[span]]".len());
+    }
+}
+
+#[test]
+fn source_map_preprocessed() {
+    use super::include_with_source_map;
+
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let input = "X\n\n\n\n[[include-messy spaced]]\n``Y''";
+    let result = include_with_source_map(input, &settings, NestedIncluder);
+    let (mut output, _, mut map) = result.expect("Fetching pages failed");
+    crate::preprocess_with_source_map(&mut output, &settings, &mut map);
+    assert_eq!(
+        output,
+        "X\n\nFirst\n\n    Second [[span]]\n\u{201c}Y\u{201d}"
+    );
+
+    macro_rules! check {
+        ($needle:expr, $page:expr, $span:expr $(,)?) => {{
+            let start = output.find($needle).expect("Needle not found in output");
+            let span = start..start + $needle.len();
+            let location = map.locate(span).expect("No location found");
+
+            assert_eq!(
+                location.page.as_ref().map(|page| page.page()),
+                $page,
+                "Page doesn't match for {:?}",
+                $needle,
+            );
+            assert_eq!(location.span, $span, "Span doesn't match for {:?}", $needle);
+        }};
+    }
+
+    check!("X", None, 0..1);
+    check!("First", Some("spaced"), 0..5);
+    check!("    ", Some("spaced"), 9..10);
+    check!("Second", Some("spaced"), 10..16);
+    check!("\u{201c}Y\u{201d}", None, 30..35);
+
+    // Whole output is covered, without gaps
+    let mut last = 0;
+    for region in map.regions() {
+        assert_eq!(region.range.start, last, "Gap in source map");
+        last = region.range.end;
+    }
+    assert_eq!(last, output.len());
+
+    // Errors are located in the original page
+    let page_info = crate::data::PageInfo::dummy();
+    let tokens = crate::tokenize(&output);
+    let (_, mut errors) = crate::parse(&tokens, &page_info, &settings).into();
+    assert!(!errors.is_empty());
+    map.annotate(&mut errors);
+
+    let spans = errors
+        .iter()
+        .filter_map(|error| error.source())
+        .filter(|source| source.page.is_some())
+        .map(|source| source.span.clone())
+        .collect::<Vec<_>>();
+
+    assert_eq!(spans, [17..19, 23..25], "Errors not located in the page");
+}
+
+struct FailingIncluder {
+    mismatch: bool,
+}
+
+impl<'t> Includer<'t> for FailingIncluder {
+    type Error = &'static str;
+
+    fn include_pages(
+        &mut self,
+        _includes: &[IncludeRef<'t>],
+    ) -> Result<Vec<FetchedPage<'t>>, &'static str> {
+        if self.mismatch {
+            Ok(vec![])
+        } else {
+            Err("connection refused")
+        }
+    }
+
+    fn no_such_include(
+        &mut self,
+        _page_ref: &PageRef<'t>,
+    ) -> Result<Cow<'t, str>, &'static str> {
+        Err("connection refused")
+    }
+}
+
+#[test]
+fn include_errors() {
+    use crate::FtmlError;
+
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let input = "[[include-messy a]]";
+
+    let error = include(input, &settings, FailingIncluder { mismatch: false })
+        .expect_err("Includer error not returned");
+    assert!(
+        matches!(error, FtmlError::Include(ref message) if message == "connection refused")
+    );
+
+    let error = include(input, &settings, FailingIncluder { mismatch: true })
+        .expect_err("Mismatched pages not rejected");
+    assert!(matches!(error, FtmlError::Include(_)));
+}
+
+#[test]
+fn include_limits() {
+    use crate::settings::ParseLimits;
+    use crate::FtmlError;
+    use std::cell::Cell;
+
+    struct CountingIncluder<'a>(&'a Cell<usize>);
+
+    impl<'t> Includer<'t> for CountingIncluder<'_> {
+        type Error = Infallible;
+
+        fn include_pages(
+            &mut self,
+            includes: &[IncludeRef<'t>],
+        ) -> Result<Vec<FetchedPage<'t>>, Infallible> {
+            self.0.set(self.0.get() + includes.len());
+            NestedIncluder.include_pages(includes)
+        }
+
+        fn no_such_include(
+            &mut self,
+            page_ref: &PageRef<'t>,
+        ) -> Result<Cow<'t, str>, Infallible> {
+            NestedIncluder.no_such_include(page_ref)
+        }
+    }
+
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+
+    // Each page is only fetched once
+    let requests = Cell::new(0);
+    let includer = CountingIncluder(&requests);
+    let (output, pages) = include("[[include-messy laugh-2]]", &settings, includer)
+        .expect("Fetching pages failed");
+
+    assert_eq!(output.matches("lol").count(), 100);
+    assert_eq!(pages.len(), 111);
+    assert_eq!(requests.get(), 3, "Pages were fetched more than once");
+
+    // Exponential expansion is stopped
+    let result = include("[[include-messy laugh-9]]", &settings, NestedIncluder);
+    assert!(matches!(result, Err(FtmlError::IncludeLimitExceeded)));
+
+    // Total includes
+    let limit = |max_includes, max_include_bytes| WikitextSettings {
+        parse_limits: ParseLimits {
+            max_includes,
+            max_include_bytes,
+            ..ParseLimits::default()
+        },
+        ..settings.clone()
+    };
+
+    let input = "[[include-messy laugh-2]]";
+    assert!(include(input, &limit(Some(111), None), NestedIncluder).is_ok());
+    assert!(matches!(
+        include(input, &limit(Some(110), None), NestedIncluder),
+        Err(FtmlError::IncludeLimitExceeded),
+    ));
+
+    // Total bytes
+    assert!(include(input, &limit(None, Some(4000)), NestedIncluder).is_ok());
+    assert!(matches!(
+        include(input, &limit(None, Some(3000)), NestedIncluder),
+        Err(FtmlError::IncludeLimitExceeded),
+    ));
+
+    // Include depth
+    let settings = WikitextSettings {
+        parse_limits: ParseLimits {
+            max_include_depth: 2,
+            ..ParseLimits::default()
+        },
+        ..settings.clone()
+    };
+
+    let (output, _) = include("[[include-messy deep-0]]", &settings, NestedIncluder)
+        .expect("Fetching pages failed");
+    assert_eq!(output, "0\n1\n<RECURSIVE deep-2>");
+}
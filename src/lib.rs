@@ -44,7 +44,8 @@
 //! Rendering is performed by the trait [`Render`].
 //! There are two main implementations of note,
 //! [`TextRender`] and [`HtmlRender`], which render to
-//! plain text and full HTML respectively.
+//! plain text and full HTML respectively. There is also
+//! [`MarkdownRender`], for exporting to CommonMark.
 //!
 //! # Features
 //! This crate has one feature of note:
@@ -53,6 +54,9 @@
 //! which renders LaTeX blocks using MathML. It is enabled
 //! by default.
 //!
+//! The `cache` feature adds [`ParseCache`], which memoizes
+//! parse results for repeated input.
+//!
 //! # Targets
 //! The library supports being compiled into WebAssembly.
 //! (target `wasm32-unknown-unknown`, see [`wasm-pack`] for more information)
@@ -69,6 +73,8 @@
 //! [`Render`]: ./render/trait.Render.html
 //! [`TextRender`]: ./render/html/struct.HtmlRender.html
 //! [`HtmlRender`]: ./render/text/struct.TextRender.html
+//! [`MarkdownRender`]: ./render/markdown/struct.MarkdownRender.html
+//! [`ParseCache`]: ./cache/struct.ParseCache.html
 //! [`serde`]: https://docs.rs/serde
 //! [`wasm-pack`]: https://rustwasm.github.io/docs/wasm-pack/
 
@@ -81,9 +87,6 @@ extern crate cfg_if;
 #[macro_use]
 extern crate enum_map;
 
-#[macro_use]
-extern crate log;
-
 #[macro_use]
 extern crate maplit;
 
@@ -101,12 +104,16 @@ extern crate str_macro;
 
 // Library top-level modules
 
-#[cfg(test)]
-mod test;
-
 #[macro_use]
 mod macros;
 
+#[cfg(test)]
+mod test;
+
+mod entity;
+mod error;
+mod expr;
+mod hash;
 mod id_prefix;
 mod next_index;
 mod non_empty_vec;
@@ -117,7 +124,14 @@ mod utf16;
 #[cfg(target_arch = "wasm32")]
 pub mod wasm;
 
+pub mod audit;
+
+#[cfg(feature = "cache")]
+pub mod cache;
+
 pub mod data;
+pub mod fragment;
+pub mod graph;
 pub mod includes;
 pub mod info;
 pub mod parsing;
@@ -127,19 +141,26 @@ pub mod settings;
 pub mod tokenizer;
 pub mod tree;
 
-pub use self::includes::include;
+pub use self::data::{
+    extract_backlinks, extract_mentions, extract_search_text, validate_links,
+};
+pub use self::error::FtmlError;
+pub use self::includes::{include, include_with_source_map};
 pub use self::parsing::parse;
-pub use self::preproc::preprocess;
+pub use self::preproc::{
+    preprocess, preprocess_with, preprocess_with_settings, preprocess_with_source_map,
+};
 pub use self::tokenizer::{tokenize, Tokenization};
 pub use self::utf16::Utf16IndexMap;
 
 /// This module collects commonly used traits from this crate.
 pub mod prelude {
     pub use super::data::{PageInfo, ScoreValue};
+    pub use super::error::FtmlError;
     pub use super::includes::{include, Includer};
     pub use super::parsing::{parse, ParseError, ParseResult};
     pub use super::preprocess;
-    pub use super::render::Render;
+    pub use super::render::{Render, RenderStream};
     pub use super::settings::{
         InterwikiSettings, WikitextMode, WikitextSettings, DEFAULT_INTERWIKI,
         EMPTY_INTERWIKI,
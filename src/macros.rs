@@ -66,3 +66,118 @@ macro_rules! str_writeln {
         writeln!($dest, $($arg)*).expect("Writing to string failed");
     }};
 }
+
+/// Runs the given function, which is a separate stack frame in debug builds.
+///
+/// Unoptimized builds otherwise reserve space for every event's temporaries
+/// in the caller's frame, which overflows the stack on deeply nested input.
+/// Optimized builds always inline this, so it has no cost there.
+#[cfg(feature = "tracing")]
+#[cfg_attr(debug_assertions, inline(never))]
+#[cfg_attr(not(debug_assertions), inline(always))]
+pub fn outlined<T, F: FnOnce() -> T>(f: F) -> T {
+    f()
+}
+
+/// Logging macros, forwarding to `tracing` if that feature is enabled.
+///
+/// Otherwise they compile to nothing, though their arguments are still
+/// type-checked so that builds with and without the feature stay in sync.
+///
+/// Events are emitted through [`outlined()`] to keep their temporaries out
+/// of the caller's stack frame, since the parser and renderers recurse deeply.
+#[cfg(feature = "tracing")]
+macro_rules! error {
+    ($($arg:tt)*) => {
+        crate::macros::outlined(|| tracing::error!($($arg)*))
+    };
+}
+
+#[cfg(feature = "tracing")]
+macro_rules! warn {
+    ($($arg:tt)*) => {
+        crate::macros::outlined(|| tracing::warn!($($arg)*))
+    };
+}
+
+#[cfg(feature = "tracing")]
+macro_rules! info {
+    ($($arg:tt)*) => {
+        crate::macros::outlined(|| tracing::info!($($arg)*))
+    };
+}
+
+#[cfg(feature = "tracing")]
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        crate::macros::outlined(|| tracing::debug!($($arg)*))
+    };
+}
+
+#[cfg(feature = "tracing")]
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        crate::macros::outlined(|| tracing::trace!($($arg)*))
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! discard_log {
+    ($($arg:tt)*) => {
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! error {
+    ($($arg:tt)*) => {
+        discard_log!($($arg)*)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! warn {
+    ($($arg:tt)*) => {
+        discard_log!($($arg)*)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! info {
+    ($($arg:tt)*) => {
+        discard_log!($($arg)*)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        discard_log!($($arg)*)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        discard_log!($($arg)*)
+    };
+}
+
+/// Opens a trace-level span which lasts until the end of the enclosing block.
+///
+/// Fields are given as `name = value`, where each value must be
+/// something `tracing` can record directly, such as a string or integer.
+macro_rules! enter_span {
+    ($name:literal $(, $field:ident = $value:expr)* $(,)?) => {
+        // Outlined for the same reason as the logging macros.
+        #[cfg(feature = "tracing")]
+        let _span = crate::macros::outlined(|| tracing::trace_span!($name $(, $field = $value)*)).entered();
+
+        #[cfg(not(feature = "tracing"))]
+        if false {
+            $(let _ = &$value;)*
+        }
+    };
+}
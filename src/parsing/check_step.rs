@@ -18,16 +18,16 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use super::{ExtractedToken, ParseError, Parser, Token};
+use super::{ExtractedToken, ParseError, ParseErrorKind, Parser, Token};
 
-/// Helper function to assert that the current token matches, then step.
+/// Helper function to check that the current token matches, then step.
 ///
 /// # Returns
 /// The `ExtractedToken` which was checked and stepped over.
 ///
-/// # Panics
-/// Since an assert is used, this function will panic
-/// if the extracted token does not match the one specified.
+/// # Errors
+/// Returns [`ParseErrorKind::RuleFailed`] if the extracted token
+/// does not match the one specified, without stepping.
 #[inline]
 pub fn check_step<'r, 't>(
     parser: &mut Parser<'r, 't>,
@@ -35,7 +35,10 @@ pub fn check_step<'r, 't>(
 ) -> Result<&'r ExtractedToken<'t>, ParseError> {
     let current = parser.current();
 
-    assert_eq!(current.token, token, "Opening token isn't {}", token.name());
+    if current.token != token {
+        warn!("Opening token isn't {}", token.name());
+        return Err(parser.make_err(ParseErrorKind::RuleFailed));
+    }
 
     parser.step()?;
 
@@ -43,7 +46,6 @@ pub fn check_step<'r, 't>(
 }
 
 #[test]
-#[should_panic]
 fn check_step_fail() {
     use crate::data::PageInfo;
     use crate::settings::{WikitextMode, WikitextSettings};
@@ -53,5 +55,8 @@ fn check_step_fail() {
     let tokenization = crate::tokenize("**Apple** banana");
     let mut parser = Parser::new(&tokenization, &page_info, &settings);
 
-    let _ = check_step(&mut parser, Token::Italics);
+    let current = parser.current();
+    let result = check_step(&mut parser, Token::Italics);
+    assert!(result.is_err());
+    assert_eq!(parser.current(), current);
 }
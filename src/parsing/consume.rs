@@ -42,6 +42,17 @@ pub fn consume<'r, 't>(parser: &mut Parser<'r, 't>) -> ParseResult<'r, 't, Eleme
         parser.current().slice,
     );
 
+    // If a limit on parser work has been passed, or parsing
+    // was cancelled, stop trying rules and leave the rest as text.
+    parser.check_time_limit();
+    parser.check_cancellation();
+    if parser.limit_exceeded() {
+        debug!("Parser limit exceeded, consuming token as text");
+        let element = text!(parser.current().slice);
+        parser.step()?;
+        return ok!(element);
+    }
+
     // Incrementing recursion depth
     // Will fail if we're too many layers in
     parser.depth_increment()?;
@@ -55,7 +66,7 @@ pub fn consume<'r, 't>(parser: &mut Parser<'r, 't>) -> ParseResult<'r, 't, Eleme
 
         let old_remaining = parser.remaining();
         match rule.try_consume(parser) {
-            Ok(output) => {
+            Ok(mut output) => {
                 info!("Rule {} matched, returning generated result", rule.name());
 
                 // If the pointer hasn't moved, we step one token.
@@ -63,6 +74,20 @@ pub fn consume<'r, 't>(parser: &mut Parser<'r, 't>) -> ParseResult<'r, 't, Eleme
                     parser.step()?;
                 }
 
+                parser.add_elements(output.item.len());
+
+                // Record where this container came from in the source
+                if parser.settings().record_spans {
+                    if let Elements::Single(Element::Container(ref mut container)) =
+                        output.item
+                    {
+                        if container.span().is_none() {
+                            let span = current.span.start..parser.current().span.start;
+                            container.set_span(span);
+                        }
+                    }
+                }
+
                 // Explicitly drop errors
                 //
                 // We're returning the successful consumption
@@ -19,6 +19,7 @@
  */
 
 use super::{rule::Rule, ExtractedToken, Token};
+use crate::includes::SourceLocation;
 use crate::utf16::Utf16IndexMap;
 use std::borrow::Cow;
 use std::ops::Range;
@@ -38,6 +39,12 @@ pub struct ParseError {
     rule: Cow<'static, str>,
     span: Range<usize>,
     kind: ParseErrorKind,
+
+    /// Where in the original pages this error is, if include expansion was performed.
+    ///
+    /// See [`SourceMap::annotate`](crate::includes::SourceMap::annotate).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source: Option<SourceLocation>,
 }
 
 impl ParseError {
@@ -52,6 +59,7 @@ impl ParseError {
             rule,
             span,
             kind,
+            source: None,
         }
     }
 
@@ -75,6 +83,21 @@ impl ParseError {
         self.kind
     }
 
+    #[inline]
+    pub fn severity(&self) -> ParseSeverity {
+        self.kind.severity()
+    }
+
+    #[inline]
+    pub fn source(&self) -> Option<&SourceLocation> {
+        self.source.as_ref()
+    }
+
+    #[inline]
+    pub(crate) fn set_source(&mut self, source: SourceLocation) {
+        self.source = Some(source);
+    }
+
     #[must_use]
     pub fn to_utf16_indices(&self, map: &Utf16IndexMap) -> Self {
         // Copy fields
@@ -83,9 +106,13 @@ impl ParseError {
             rule,
             span,
             kind,
+            source,
         } = self.clone();
 
         // Map indices to UTF-16
+        //
+        // The source location is left as-is, since
+        // it refers to the text of another page.
         let start = map.get_index(span.start);
         let end = map.get_index(span.end);
         let span = start..end;
@@ -96,6 +123,7 @@ impl ParseError {
             rule,
             span,
             kind,
+            source,
         }
     }
 }
@@ -106,6 +134,17 @@ pub enum ParseErrorKind {
     /// The self-enforced recursion limit has been passed, giving up.
     RecursionDepthExceeded,
 
+    /// A configured limit on parser work has been passed, leaving the rest as text.
+    LimitExceeded,
+
+    /// The configured time limit on parsing has been passed, leaving the rest as text.
+    ///
+    /// Unlike the other limits, this depends on how fast the host is rather than on the input.
+    TimeLimitExceeded,
+
+    /// Parsing was cancelled using the settings' cancellation token, leaving the rest as text.
+    Cancelled,
+
     /// Attempting to process this rule failed because the end of input was reached.
     EndOfInput,
 
@@ -196,6 +235,25 @@ pub enum ParseErrorKind {
     /// An end block was found, but of the incorrect type.
     BlockEndMismatch,
 
+    /// A declaration in a `style` attribute was removed by the style policy.
+    StyleDeclarationRemoved,
+
+    /// A class or ID on a block was removed by the container attribute policy.
+    ContainerAttributeRemoved,
+
+    /// The size given to a `[[size]]` block is not valid, so it is ignored.
+    InvalidSize,
+
+    /// The size given to a `[[size]]` block is outside the configured limits,
+    /// so it will be clamped.
+    SizeOutOfRange,
+
+    /// An anchor with this name already exists on the page.
+    DuplicateAnchorName,
+
+    /// Another radio button with this name is already checked.
+    RadioButtonMultipleChecked,
+
     /// No embed with this name exists.
     NoSuchEmbed,
 
@@ -213,6 +271,9 @@ pub enum ParseErrorKind {
 
     /// The URL passed here was invalid.
     InvalidUrl,
+
+    /// The page reference passed here was invalid, such as `category:` or `::page`.
+    InvalidPageReference,
 }
 
 impl ParseErrorKind {
@@ -220,4 +281,96 @@ impl ParseErrorKind {
     pub fn name(self) -> &'static str {
         self.into()
     }
+
+    /// How serious this kind of issue is.
+    pub fn severity(self) -> ParseSeverity {
+        match self {
+            // Ordinary parser backtracking, expected in normal wikitext
+            ParseErrorKind::EndOfInput
+            | ParseErrorKind::NoRulesMatch
+            | ParseErrorKind::RuleFailed
+            | ParseErrorKind::NotStartOfLine => ParseSeverity::Info,
+
+            // Malformed syntax, which is shown as-is or dropped
+            ParseErrorKind::NotSupportedMode
+            | ParseErrorKind::ListEmpty
+            | ParseErrorKind::ListContainsNonItem
+            | ParseErrorKind::ListItemOutsideList
+            | ParseErrorKind::TableContainsNonRow
+            | ParseErrorKind::TableRowContainsNonCell
+            | ParseErrorKind::TableRowOutsideTable
+            | ParseErrorKind::TableCellOutsideTable
+            | ParseErrorKind::TabViewEmpty
+            | ParseErrorKind::TabViewContainsNonTab
+            | ParseErrorKind::TabOutsideTabView
+            | ParseErrorKind::FootnotesNested
+            | ParseErrorKind::RubyTextOutsideRuby
+            | ParseErrorKind::BibliographyContainsNonDefinitionList
+            | ParseErrorKind::NoSuchBlock
+            | ParseErrorKind::BlockDisallowsStar
+            | ParseErrorKind::BlockDisallowsScore
+            | ParseErrorKind::BlockMissingName
+            | ParseErrorKind::BlockMissingCloseBrackets
+            | ParseErrorKind::BlockMalformedArguments
+            | ParseErrorKind::BlockMissingArguments
+            | ParseErrorKind::BlockExpectedEnd
+            | ParseErrorKind::BlockEndMismatch
+            | ParseErrorKind::StyleDeclarationRemoved
+            | ParseErrorKind::ContainerAttributeRemoved
+            | ParseErrorKind::InvalidSize
+            | ParseErrorKind::SizeOutOfRange
+            | ParseErrorKind::DuplicateAnchorName
+            | ParseErrorKind::RadioButtonMultipleChecked
+            | ParseErrorKind::NoSuchEmbed
+            | ParseErrorKind::NoSuchModule
+            | ParseErrorKind::ModuleMissingName
+            | ParseErrorKind::NoSuchPage
+            | ParseErrorKind::NoSuchVariable
+            | ParseErrorKind::InvalidPageReference => ParseSeverity::Warning,
+
+            // Parser limits being hit, or content which was refused
+            ParseErrorKind::RecursionDepthExceeded
+            | ParseErrorKind::LimitExceeded
+            | ParseErrorKind::TimeLimitExceeded
+            | ParseErrorKind::Cancelled
+            | ParseErrorKind::ListDepthExceeded
+            | ParseErrorKind::BlockquoteDepthExceeded
+            | ParseErrorKind::InvalidInclude
+            | ParseErrorKind::InvalidUrl => ParseSeverity::Error,
+        }
+    }
+}
+
+/// How serious a parsing issue is.
+///
+/// These are ordered, so the most severe of a set of issues can be found with `max()`.
+#[derive(
+    Serialize,
+    Deserialize,
+    IntoStaticStr,
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum ParseSeverity {
+    /// The parser tried a rule and fell back, which is expected in normal wikitext.
+    Info,
+
+    /// The syntax was malformed, so part of the page may not render as intended.
+    Warning,
+
+    /// A limit was hit or some content was refused, and likely needs attention.
+    Error,
+}
+
+impl ParseSeverity {
+    #[inline]
+    pub fn name(self) -> &'static str {
+        self.into()
+    }
 }
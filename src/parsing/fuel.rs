@@ -0,0 +1,130 @@
+/*
+ * parsing/fuel.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Tracking of parser work against the configured [`ParseLimits`].
+
+use super::ParseError;
+use crate::settings::ParseLimits;
+use std::cell::{Cell, RefCell};
+
+cfg_if! {
+    if #[cfg(target_arch = "wasm32")] {
+        /// Stand-in for the parse start time, since there is no clock in WebAssembly.
+        #[derive(Debug)]
+        struct Clock;
+
+        impl Clock {
+            #[inline]
+            fn start() -> Self {
+                Clock
+            }
+
+            #[inline]
+            fn exceeded(&self, _max_time_ms: u64) -> bool {
+                false
+            }
+        }
+    } else {
+        use std::time::{Duration, Instant};
+
+        /// When parsing began, for enforcing the time limit.
+        #[derive(Debug)]
+        struct Clock(Instant);
+
+        impl Clock {
+            #[inline]
+            fn start() -> Self {
+                Clock(Instant::now())
+            }
+
+            #[inline]
+            fn exceeded(&self, max_time_ms: u64) -> bool {
+                self.0.elapsed() > Duration::from_millis(max_time_ms)
+            }
+        }
+    }
+}
+
+/// Work done so far during a parse.
+///
+/// This is shared between all instances of the parser, since
+/// work done by rules which backtrack still counts.
+#[derive(Debug)]
+pub struct Fuel {
+    steps: Cell<usize>,
+    elements: Cell<usize>,
+    clock: Clock,
+
+    /// The error for the limit which was exceeded, if any.
+    ///
+    /// Once this is set, no further rules are attempted.
+    exceeded: RefCell<Option<ParseError>>,
+}
+
+impl Fuel {
+    pub fn new() -> Self {
+        Fuel {
+            steps: Cell::new(0),
+            elements: Cell::new(0),
+            clock: Clock::start(),
+            exceeded: RefCell::new(None),
+        }
+    }
+
+    #[inline]
+    pub fn is_exceeded(&self) -> bool {
+        self.exceeded.borrow().is_some()
+    }
+
+    /// Records a token step, returning whether this passes the limit.
+    pub fn add_step(&self, limits: &ParseLimits) -> bool {
+        let steps = self.steps.get() + 1;
+        self.steps.set(steps);
+        matches!(limits.max_steps, Some(max) if steps > max)
+    }
+
+    /// Records produced elements, returning whether this passes the limit.
+    pub fn add_elements(&self, count: usize, limits: &ParseLimits) -> bool {
+        let elements = self.elements.get() + count;
+        self.elements.set(elements);
+        matches!(limits.max_elements, Some(max) if elements > max)
+    }
+
+    /// Checks whether the time limit has been passed.
+    pub fn out_of_time(&self, limits: &ParseLimits) -> bool {
+        match limits.max_time_ms {
+            Some(max) => self.clock.exceeded(max),
+            None => false,
+        }
+    }
+
+    /// Marks the limits as exceeded, if they haven't been already.
+    pub fn set_exceeded(&self, error: ParseError) {
+        let mut exceeded = self.exceeded.borrow_mut();
+        if exceeded.is_none() {
+            *exceeded = Some(error);
+        }
+    }
+
+    /// Takes the error for the exceeded limit, if there is one.
+    pub fn take_error(&self) -> Option<ParseError> {
+        self.exceeded.borrow_mut().take()
+    }
+}
@@ -29,10 +29,14 @@ mod consume;
 mod depth;
 mod element_condition;
 mod error;
+mod fuel;
 mod outcome;
 mod paragraph;
 mod parser;
 mod parser_wrap;
+mod radio;
+mod registry;
+mod report;
 mod result;
 mod rule;
 mod string;
@@ -53,22 +57,33 @@ use self::element_condition::{ElementCondition, ElementConditionType};
 use self::paragraph::{gather_paragraphs, NO_CLOSE_CONDITION};
 use self::parser::Parser;
 use self::parser_wrap::ParserWrap;
+use self::radio::group_radio_buttons;
 use self::rule::impls::RULE_PAGE;
-use self::string::parse_string;
+pub(crate) use self::string::parse_string;
 use self::strip::{strip_newlines, strip_whitespace};
 use crate::data::PageInfo;
-use crate::next_index::{NextIndex, TableOfContentsIndex};
-use crate::settings::WikitextSettings;
+use crate::hash::stable_hash;
+use crate::settings::{FootnoteBlockMode, WikitextSettings};
 use crate::tokenizer::Tokenization;
+use crate::tree::visit::{walk_element, walk_element_mut};
 use crate::tree::{
-    AttributeMap, BibliographyList, Element, LinkLabel, LinkLocation, LinkType, ListItem,
-    ListType, SyntaxTree,
+    AttributeMap, BibliographyList, CodeBlob, Element, HeadingLevel, LinkLabel,
+    LinkLocation, LinkType, ListItem, ListType, SyntaxTree, TableOfContentsEntry,
+    Visitor, VisitorMut,
 };
 use std::borrow::Cow;
+use std::collections::HashSet;
+use std::mem;
+use std::ops::Range;
 
 pub use self::boolean::{parse_boolean, NonBooleanValue};
-pub use self::error::{ParseError, ParseErrorKind};
+pub use self::error::{ParseError, ParseErrorKind, ParseSeverity};
 pub use self::outcome::ParseOutcome;
+pub use self::registry::{
+    block_registry, rule_registry, token_registry, BlockBody, BlockHead, BlockInfo,
+    RuleInfo, TokenInfo,
+};
+pub use self::report::{ParseReport, ReportEntry, RuleReport};
 pub use self::result::{ParseResult, ParseSuccess};
 pub use self::token::{ExtractedToken, Token};
 
@@ -83,17 +98,19 @@ pub fn parse<'r, 't>(
 where
     'r: 't,
 {
+    enter_span!("parse", tokens = tokenization.tokens().len());
+
     // Run parsing, get raw results
     let UnstructuredParseResult {
         result,
         table_of_contents_depths,
-        footnotes,
+        mut footnotes,
         has_footnote_block,
         bibliographies,
     } = parse_internal(page_info, settings, tokenization);
 
     // For producing table of contents indexes
-    let mut incrementer = Incrementer(0);
+    let mut heading_ids = HeadingIds::new(settings);
 
     info!("Finished paragraph gathering, matching on consumption");
     match result {
@@ -107,21 +124,68 @@ where
                 errors.len(),
             );
 
+            // In strict mode, any malformed syntax fails the whole parse
+            if settings.strict_parsing {
+                let offending = errors
+                    .iter()
+                    .filter(|error| error.severity() >= ParseSeverity::Warning)
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                if !offending.is_empty() {
+                    warn!(
+                        "Strict parsing failed ({} offending errors)",
+                        offending.len(),
+                    );
+                    let (tree, errors) = failed_parse(tokenization, offending).into();
+                    return ParseOutcome::rejected(tree, errors);
+                }
+            }
+
             // process_depths() wants a "list type", so we map in a () for each.
-            let table_of_contents_depths = table_of_contents_depths
-                .into_iter()
-                .map(|(depth, contents)| (depth, (), contents));
+            //
+            // Headings are 1-indexed (e.g. H1), but depth lists are 0-indexed
+            let table_of_contents_depths =
+                table_of_contents_depths
+                    .into_iter()
+                    .map(|(level, name, span)| {
+                        let depth = usize::from(level.value()) - 1;
+                        (depth, (), (level, name, span))
+                    });
 
             // Convert TOC depth lists
+            let mut table_of_contents_entries = Vec::new();
             let table_of_contents = process_depths((), table_of_contents_depths)
                 .into_iter()
-                .map(|(_, items)| build_toc_list_element(&mut incrementer, items))
+                .map(|(_, items)| {
+                    build_toc_list_element(
+                        &mut heading_ids,
+                        &mut table_of_contents_entries,
+                        items,
+                    )
+                })
                 .collect::<Vec<_>>();
 
-            // Add a footnote block at the end,
-            // if the user doesn't have one already
-            if !has_footnote_block {
-                info!("No footnote block in elements, appending one");
+            // Gather runs of radio buttons into groups
+            group_radio_buttons(&mut elements);
+            for footnote in &mut footnotes {
+                group_radio_buttons(footnote);
+            }
+
+            // Gather stylesheets, now that excluded elements are removed
+            let styles = collect_styles(&elements);
+
+            // Number equations, so references can be resolved
+            let equations = collect_equations(&elements, &footnotes);
+
+            // Add a footnote block at the end, if the user doesn't have one already.
+            //
+            // When splitting footnotes between blocks, one is always added
+            // to list any which come after the last block.
+            if !has_footnote_block
+                || settings.footnote_block_mode == FootnoteBlockMode::Split
+            {
+                info!("Appending trailing footnote block to elements");
 
                 elements.push(Element::FootnoteBlock {
                     title: None,
@@ -129,14 +193,26 @@ where
                 });
             }
 
-            SyntaxTree::from_element_result(
+            let (mut tree, errors) = SyntaxTree::from_element_result(
                 elements,
                 errors,
                 table_of_contents,
                 footnotes,
                 bibliographies,
+                styles,
                 tokenization.full_text().len(),
             )
+            .into();
+
+            tree.table_of_contents_entries = table_of_contents_entries;
+            tree.equations = equations;
+
+            // Move large code block bodies out of the tree, for the host to store
+            if let Some(threshold) = settings.code_blob_threshold {
+                tree.code_blobs = extract_code_blobs(&mut tree, page_info, threshold);
+            }
+
+            ParseOutcome::new(tree, errors)
         }
         Err(error) => {
             // This path is only reachable if a very bad error occurs.
@@ -145,25 +221,37 @@ where
             // and the error.
 
             error!("Fatal error occurred at highest-level parsing: {error:#?}");
-            let wikitext = tokenization.full_text().inner();
-            let elements = vec![text!(wikitext)];
-            let errors = vec![error];
-            let table_of_contents = vec![];
-            let footnotes = vec![];
-            let bibliographies = BibliographyList::new();
-
-            SyntaxTree::from_element_result(
-                elements,
-                errors,
-                table_of_contents,
-                footnotes,
-                bibliographies,
-                tokenization.full_text().len(),
-            )
+            failed_parse(tokenization, vec![error])
         }
     }
 }
 
+/// Produces the output for a parse which could not complete.
+///
+/// The input source is returned as-is, as a single text element,
+/// along with the errors which caused the failure.
+fn failed_parse<'t>(
+    tokenization: &Tokenization<'t>,
+    errors: Vec<ParseError>,
+) -> ParseOutcome<SyntaxTree<'t>> {
+    let wikitext = tokenization.full_text().inner();
+    let elements = vec![text!(wikitext)];
+    let table_of_contents = vec![];
+    let footnotes = vec![];
+    let bibliographies = BibliographyList::new();
+    let styles = vec![];
+
+    SyntaxTree::from_element_result(
+        elements,
+        errors,
+        table_of_contents,
+        footnotes,
+        bibliographies,
+        styles,
+        tokenization.full_text().len(),
+    )
+}
+
 /// Runs the parser, but returns the raw internal results prior to conversion.
 pub fn parse_internal<'r, 't>(
     page_info: &'r PageInfo<'t>,
@@ -177,7 +265,18 @@ where
 
     // At the top level, we gather elements into paragraphs
     info!("Running parser on tokens");
-    let result = gather_paragraphs(&mut parser, RULE_PAGE, NO_CLOSE_CONDITION);
+    let mut result = gather_paragraphs(&mut parser, RULE_PAGE, NO_CLOSE_CONDITION);
+
+    // Report if parsing was cut short by a limit,
+    // and any warnings found along the way
+    let warnings = parser.remove_warnings();
+    if let Ok(ref mut success) = result {
+        success.errors.extend(warnings);
+
+        if let Some(error) = parser.take_limit_error() {
+            success.errors.push(error);
+        }
+    }
 
     // Build and return
     let table_of_contents_depths = parser.remove_table_of_contents();
@@ -197,15 +296,25 @@ where
 // Helper functions
 
 fn build_toc_list_element(
-    incr: &mut Incrementer,
-    list: DepthList<(), String>,
+    heading_ids: &mut HeadingIds,
+    entries: &mut Vec<TableOfContentsEntry<'static>>,
+    list: DepthList<(), TableOfContentsHeading>,
 ) -> Element<'static> {
     let build_item = |item| match item {
         DepthItem::List(_, list) => ListItem::SubList {
-            element: Box::new(build_toc_list_element(incr, list)),
+            element: Box::new(build_toc_list_element(heading_ids, entries, list)),
         },
-        DepthItem::Item(name) => {
-            let anchor = format!("#toc{}", incr.next());
+        DepthItem::Item((level, name, span)) => {
+            let id = heading_ids.next(&name);
+            let anchor = format!("#{id}");
+
+            entries.push(TableOfContentsEntry {
+                id: Cow::Owned(id),
+                level,
+                name: Cow::Owned(name.clone()),
+                span,
+            });
+
             let link = Element::Link {
                 ltype: LinkType::TableOfContents,
                 link: LinkLocation::Url(Cow::Owned(anchor)),
@@ -230,19 +339,150 @@ fn build_toc_list_element(
     }
 }
 
-// Incrementer for TOC
+// Stylesheet collection
+
+fn collect_styles<'t>(elements: &[Element<'t>]) -> Vec<Cow<'t, str>> {
+    struct StyleCollector<'t>(Vec<Cow<'t, str>>);
+
+    impl<'t> Visitor<'t> for StyleCollector<'t> {
+        fn visit_element(&mut self, element: &Element<'t>) {
+            match element {
+                Element::Style(css) => self.0.push(css.clone()),
+                _ => walk_element(self, element),
+            }
+        }
+    }
+
+    let mut collector = StyleCollector(vec![]);
+    collector.visit_elements(elements);
+    collector.0
+}
+
+// Out-of-line code blocks
+
+/// Replaces each code block larger than the threshold with an `Element::CodeBlob`.
+///
+/// The removed bodies are returned in page order, each with the
+/// identifier its `Element::CodeBlob` refers to. Identifiers name the page
+/// and a hash of the body, such as `sandbox/_default:some-page/code-0123456789abcdef`,
+/// so they are unique across pages. Identical bodies are only returned once.
+fn extract_code_blobs<'t>(
+    tree: &mut SyntaxTree<'t>,
+    page_info: &PageInfo,
+    threshold: usize,
+) -> Vec<CodeBlob<'t>> {
+    struct CodeBlobExtractor<'t> {
+        page: String,
+        threshold: usize,
+        blobs: Vec<CodeBlob<'t>>,
+    }
+
+    impl<'t> VisitorMut<'t> for CodeBlobExtractor<'t> {
+        fn visit_element_mut(&mut self, element: &mut Element<'t>) {
+            match element {
+                Element::Code { contents, language }
+                    if contents.len() > self.threshold =>
+                {
+                    debug!("Storing code block out-of-line (length {})", contents.len());
+
+                    let contents = mem::take(contents);
+                    let language = language.take();
+                    let hash = stable_hash(&[&self.page, &contents]);
+                    let blob: Cow<'t, str> =
+                        Cow::Owned(format!("{}/code-{hash:016x}", self.page));
+
+                    *element = Element::CodeBlob {
+                        blob: blob.clone(),
+                        length: contents.len(),
+                        language,
+                    };
+
+                    if !self.blobs.iter().any(|code_blob| code_blob.blob == blob) {
+                        self.blobs.push(CodeBlob { blob, contents });
+                    }
+                }
+                _ => walk_element_mut(self, element),
+            }
+        }
+    }
+
+    let category = page_info.category.as_deref().unwrap_or("_default");
+    let mut extractor = CodeBlobExtractor {
+        page: format!("{}/{}:{}", page_info.site, category, page_info.page),
+        threshold,
+        blobs: vec![],
+    };
+    extractor.visit_tree_mut(tree);
+    extractor.blobs
+}
+
+/// Lists the names of each numbered equation, in page order.
+///
+/// Equations within a footnote are numbered where the footnote is referenced.
+/// This matches the order math blocks are numbered when rendering.
+fn collect_equations<'t>(
+    elements: &[Element<'t>],
+    footnotes: &[Vec<Element<'t>>],
+) -> Vec<Option<Cow<'t, str>>> {
+    struct EquationCollector<'f, 't> {
+        equations: Vec<Option<Cow<'t, str>>>,
+        footnotes: std::slice::Iter<'f, Vec<Element<'t>>>,
+    }
+
+    impl<'t> Visitor<'t> for EquationCollector<'_, 't> {
+        fn visit_element(&mut self, element: &Element<'t>) {
+            match element {
+                Element::Math { name, .. } => self.equations.push(name.clone()),
+                Element::Footnote => {
+                    if let Some(contents) = self.footnotes.next() {
+                        self.visit_elements(contents);
+                    }
+                }
+                _ => walk_element(self, element),
+            }
+        }
+    }
+
+    let mut collector = EquationCollector {
+        equations: vec![],
+        footnotes: footnotes.iter(),
+    };
+    collector.visit_elements(elements);
+    collector.equations
+}
+
+// Heading ID generation for TOC
 
 #[derive(Debug)]
-struct Incrementer(usize);
+struct HeadingIds<'s> {
+    settings: &'s WikitextSettings,
+    index: usize,
+    used: HashSet<String>,
+}
+
+impl<'s> HeadingIds<'s> {
+    fn new(settings: &'s WikitextSettings) -> Self {
+        HeadingIds {
+            settings,
+            index: 0,
+            used: HashSet::new(),
+        }
+    }
 
-impl NextIndex<TableOfContentsIndex> for Incrementer {
-    fn next(&mut self) -> usize {
-        let index = self.0;
-        self.0 += 1;
-        index
+    fn next(&mut self, name: &str) -> String {
+        let index = self.index;
+        self.index += 1;
+        self.settings
+            .heading_ids
+            .generate(index, name, &mut self.used)
     }
 }
 
+/// A heading to be listed in the table of contents.
+///
+/// This is the heading level, its name as text, and its span in the source.
+pub type TableOfContentsHeading = (HeadingLevel, String, Range<usize>);
+
 /// Represents the result of an internal parse.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct UnstructuredParseResult<'r, 't> {
@@ -250,9 +490,7 @@ pub struct UnstructuredParseResult<'r, 't> {
     pub result: ParseResult<'r, 't, Vec<Element<'t>>>,
 
     /// The "depths" list for table of content entries.
-    ///
-    /// Each value is a zero-indexed depth of how
-    pub table_of_contents_depths: Vec<(usize, String)>,
+    pub table_of_contents_depths: Vec<TableOfContentsHeading>,
 
     /// The list of footnotes.
     ///
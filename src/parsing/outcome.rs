@@ -25,6 +25,9 @@ use std::borrow::{Borrow, BorrowMut};
 pub struct ParseOutcome<T> {
     value: T,
     errors: Vec<ParseError>,
+
+    #[serde(default)]
+    rejected: bool,
 }
 
 impl<T> ParseOutcome<T> {
@@ -36,6 +39,34 @@ impl<T> ParseOutcome<T> {
         ParseOutcome {
             value,
             errors: errors.into(),
+            rejected: false,
+        }
+    }
+
+    /// Creates an outcome for input which was rejected outright.
+    ///
+    /// See [`is_rejected()`](ParseOutcome::is_rejected).
+    #[inline]
+    pub fn rejected<I>(value: T, errors: I) -> Self
+    where
+        I: Into<Vec<ParseError>>,
+    {
+        ParseOutcome {
+            value,
+            errors: errors.into(),
+            rejected: true,
+        }
+    }
+
+    /// Converts the value, keeping the errors and rejection status.
+    pub fn map<F, U>(self, f: F) -> ParseOutcome<U>
+    where
+        F: FnOnce(T) -> U,
+    {
+        ParseOutcome {
+            value: f(self.value),
+            errors: self.errors,
+            rejected: self.rejected,
         }
     }
 
@@ -49,6 +80,18 @@ impl<T> ParseOutcome<T> {
     pub fn errors(&self) -> &[ParseError] {
         &self.errors
     }
+
+    /// Whether the input was rejected, rather than parsed.
+    ///
+    /// This happens when [`strict_parsing`] is enabled and the input has
+    /// malformed syntax. The value is then only a fallback, such as the
+    /// source text, and the errors are those which caused the rejection.
+    ///
+    /// [`strict_parsing`]: crate::settings::WikitextSettings::strict_parsing
+    #[inline]
+    pub fn is_rejected(&self) -> bool {
+        self.rejected
+    }
 }
 
 impl<U> ParseOutcome<Vec<U>> {
@@ -67,6 +110,7 @@ where
         ParseOutcome {
             value: self.value.clone(),
             errors: self.errors.clone(),
+            rejected: self.rejected,
         }
     }
 }
@@ -80,6 +124,7 @@ where
         ParseOutcome {
             value: T::default(),
             errors: Vec::new(),
+            rejected: false,
         }
     }
 }
@@ -101,7 +146,7 @@ impl<T> BorrowMut<T> for ParseOutcome<T> {
 impl<T> From<ParseOutcome<T>> for (T, Vec<ParseError>) {
     #[inline]
     fn from(outcome: ParseOutcome<T>) -> (T, Vec<ParseError>) {
-        let ParseOutcome { value, errors } = outcome;
+        let ParseOutcome { value, errors, .. } = outcome;
 
         (value, errors)
     }
@@ -121,6 +166,11 @@ fn outcome() {
 
     let outcome_2 = outcome.clone();
     assert_eq!(outcome, outcome_2);
+    assert!(!outcome.is_rejected());
+
+    let outcome = ParseOutcome::rejected('c', vec![]).map(|c| c.to_string());
+    assert_eq!(outcome.value(), "c");
+    assert!(outcome.is_rejected());
 }
 
 #[test]
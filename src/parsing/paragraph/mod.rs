@@ -27,6 +27,7 @@ use super::parser::Parser;
 use super::prelude::*;
 use super::rule::Rule;
 use super::token::Token;
+use std::num::NonZeroU32;
 
 /// Wrapper type to satisfy the issue with generic closure types.
 ///
@@ -66,6 +67,7 @@ where
     let mut stack = ParagraphStack::new();
 
     loop {
+        let start = parser.current().span.start;
         let (elements, mut errors, paragraph_safe) = match parser.current().token {
             Token::InputEnd => {
                 if close_condition_fn.is_some() {
@@ -92,6 +94,16 @@ where
                 // Paragraph break -- end the paragraph and start a new one!
                 stack.end_paragraph();
 
+                // Keep any blank lines beyond the first, if requested
+                if parser.settings().preserve_blank_lines {
+                    let newlines = parser.current().slice.matches('\n').count();
+
+                    if let Some(extra) = NonZeroU32::new(newlines.saturating_sub(2) as u32) {
+                        debug!("Preserving {extra} extra blank lines");
+                        stack.push_element(Element::LineBreaks(extra), false);
+                    }
+                }
+
                 // We must manually bump up this pointer because
                 // we 'continue' here, skipping the usual pointer update.
                 parser.step()?;
@@ -118,7 +130,12 @@ where
         debug!("Tokens consumed to produce element");
 
         // Add new elements to the list
-        push_elements(&mut stack, elements, paragraph_safe);
+        let pushed = push_elements(&mut stack, elements, paragraph_safe);
+
+        // Record the source covered by the current paragraph
+        if pushed && paragraph_safe && parser.settings().record_spans {
+            stack.extend_span(start..parser.current().span.start);
+        }
 
         // Process errors
         stack.push_errors(&mut errors);
@@ -127,11 +144,13 @@ where
     stack.into_result()
 }
 
+/// Adds elements to the stack, returning whether any were added.
 fn push_elements<'t>(
     stack: &mut ParagraphStack<'t>,
     elements: Elements<'t>,
     paragraph_safe: bool,
-) {
+) -> bool {
+    let mut pushed = false;
     stack.reserve_elements(elements.len());
 
     for element in elements {
@@ -141,5 +160,8 @@ fn push_elements<'t>(
         }
 
         stack.push_element(element, paragraph_safe);
+        pushed = true;
     }
+
+    pushed
 }
@@ -21,12 +21,16 @@
 use crate::parsing::prelude::*;
 use crate::tree::{AttributeMap, Container, ContainerType};
 use std::mem;
+use std::ops::Range;
 
 #[derive(Debug, Default)]
 pub struct ParagraphStack<'t> {
     /// Elements being accumulated in the current paragraph.
     current: Vec<Element<'t>>,
 
+    /// The source span of the current paragraph, if being recorded.
+    current_span: Option<Range<usize>>,
+
     /// Previous elements created, to be outputted in the final [`SyntaxTree`].
     finished: Vec<Element<'t>>,
 
@@ -69,6 +73,14 @@ impl<'t> ParagraphStack<'t> {
         }
     }
 
+    /// Extends the source span of the current paragraph to cover this range.
+    pub fn extend_span(&mut self, span: Range<usize>) {
+        self.current_span = match self.current_span.take() {
+            Some(current) => Some(current.start..span.end),
+            None => Some(span),
+        };
+    }
+
     #[inline]
     pub fn push_errors(&mut self, errors: &mut Vec<ParseError>) {
         info!("Pushing errors to stack (length {})", errors.len());
@@ -106,8 +118,13 @@ impl<'t> ParagraphStack<'t> {
 
         // Pull out gathered elements, then make a new paragraph container
         let elements = mem::take(&mut self.current);
-        let container =
+        let mut container =
             Container::new(ContainerType::Paragraph, elements, AttributeMap::new());
+
+        if let Some(span) = self.current_span.take() {
+            container.set_span(span);
+        }
+
         let element = Element::Container(container);
         Some(element)
     }
@@ -134,6 +151,7 @@ impl<'t> ParagraphStack<'t> {
         // Deconstruct stack
         let ParagraphStack {
             current: _,
+            current_span: _,
             finished: elements,
             errors,
         } = self;
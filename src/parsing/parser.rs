@@ -19,19 +19,23 @@
  */
 
 use super::condition::ParseCondition;
+use super::fuel::Fuel;
 use super::prelude::*;
 use super::rule::Rule;
-use super::RULE_PAGE;
+use super::{TableOfContentsHeading, RULE_PAGE};
 use crate::data::PageInfo;
 use crate::render::text::TextRender;
+use crate::settings::GatedElement;
 use crate::tokenizer::Tokenization;
 use crate::tree::{AcceptsPartial, Bibliography, BibliographyList, HeadingLevel};
+use std::borrow::Cow;
 use std::cell::RefCell;
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::ops::Range;
 use std::rc::Rc;
 use std::{mem, ptr};
 
-const MAX_RECURSION_DEPTH: usize = 100;
-
 /// Parser for a set of tokens.
 #[derive(Debug, Clone)]
 pub struct Parser<'r, 't> {
@@ -48,21 +52,39 @@ pub struct Parser<'r, 't> {
     rule: Rule,
     depth: usize,
 
+    // Work done, shared between parser instances
+    fuel: Rc<Fuel>,
+
     // Table of Contents
     //
-    // Schema: Vec<(depth, _, name)>
+    // Schema: Vec<(level, name, span)>
     //
     // Note: These three are in Rc<_> items so that the Parser
     //       can be cloned. This struct is intended as a
     //       cheap pointer object, with the true contents
     //       here preserved across parser child instances.
-    table_of_contents: Rc<RefCell<Vec<(usize, String)>>>,
+    table_of_contents: Rc<RefCell<Vec<TableOfContentsHeading>>>,
 
     // Footnotes
     //
     // Schema: Vec<List of elements in a footnote>
     footnotes: Rc<RefCell<Vec<Vec<Element<'t>>>>>,
 
+    // Named footnotes, which may be referenced again
+    //
+    // Schema: HashMap<name, footnote index>
+    footnote_names: Rc<RefCell<HashMap<Cow<'t, str>, NonZeroUsize>>>,
+
+    // Anchor names, to find duplicates
+    //
+    // Schema: HashMap<name, start of its first anchor>
+    anchor_names: Rc<RefCell<HashMap<Cow<'t, str>, usize>>>,
+
+    // Names of checked radio buttons, to find duplicates
+    //
+    // Schema: HashMap<name, start of its first checked radio button>
+    checked_radio_names: Rc<RefCell<HashMap<Cow<'t, str>, usize>>>,
+
     // Bibliographies
     //
     // Each bibliography block is separate, but the citations
@@ -70,6 +92,12 @@ pub struct Parser<'r, 't> {
     // overriding later ones.
     bibliographies: Rc<RefCell<BibliographyList<'t>>>,
 
+    // Warnings
+    //
+    // Issues found while parsing which do not cause a rule to fail,
+    // such as content removed by a policy in the settings.
+    warnings: Rc<RefCell<Vec<ParseError>>>,
+
     // Flags
     accepts_partial: AcceptsPartial,
     in_footnote: bool, // Whether we're currently inside [[footnote]] ... [[/footnote]].
@@ -101,9 +129,14 @@ impl<'r, 't> Parser<'r, 't> {
             full_text,
             rule: RULE_PAGE,
             depth: 0,
+            fuel: Rc::new(Fuel::new()),
             table_of_contents: make_shared_vec(),
             footnotes: make_shared_vec(),
+            footnote_names: Rc::new(RefCell::new(HashMap::new())),
+            anchor_names: Rc::new(RefCell::new(HashMap::new())),
+            checked_radio_names: Rc::new(RefCell::new(HashMap::new())),
             bibliographies: Rc::new(RefCell::new(BibliographyList::new())),
+            warnings: make_shared_vec(),
             accepts_partial: AcceptsPartial::None,
             in_footnote: false,
             has_footnote_block: false,
@@ -168,7 +201,7 @@ impl<'r, 't> Parser<'r, 't> {
         self.depth += 1;
         debug!("Incrementing recursion depth to {}", self.depth);
 
-        if self.depth > MAX_RECURSION_DEPTH {
+        if self.depth > self.settings.parse_limits.max_depth {
             return Err(self.make_err(ParseErrorKind::RecursionDepthExceeded));
         }
 
@@ -181,6 +214,49 @@ impl<'r, 't> Parser<'r, 't> {
         debug!("Decrementing recursion depth to {}", self.depth);
     }
 
+    // Parser limits
+
+    /// Whether a limit on parser work has been exceeded.
+    ///
+    /// Once this is true, the rest of the input is consumed as text.
+    #[inline]
+    pub fn limit_exceeded(&self) -> bool {
+        self.fuel.is_exceeded()
+    }
+
+    /// Records produced elements, checking them against the limit.
+    pub fn add_elements(&mut self, count: usize) {
+        if self.fuel.add_elements(count, &self.settings.parse_limits) {
+            warn!("Element limit exceeded, leaving the rest as text");
+            self.fuel
+                .set_exceeded(self.make_err(ParseErrorKind::LimitExceeded));
+        }
+    }
+
+    /// Checks how long parsing has taken against the limit.
+    pub fn check_time_limit(&mut self) {
+        if self.fuel.out_of_time(&self.settings.parse_limits) {
+            warn!("Time limit exceeded, leaving the rest as text");
+            self.fuel
+                .set_exceeded(self.make_err(ParseErrorKind::TimeLimitExceeded));
+        }
+    }
+
+    /// Checks whether parsing has been cancelled, stopping as with a limit.
+    pub fn check_cancellation(&mut self) {
+        if self.settings.cancellation.is_cancelled() && !self.fuel.is_exceeded() {
+            warn!("Parsing cancelled, leaving the rest as text");
+            self.fuel
+                .set_exceeded(self.make_err(ParseErrorKind::Cancelled));
+        }
+    }
+
+    /// Takes the error for an exceeded limit, if there is one.
+    #[inline]
+    pub fn take_limit_error(&mut self) -> Option<ParseError> {
+        self.fuel.take_error()
+    }
+
     #[inline]
     pub fn set_accepts_partial(&mut self, value: AcceptsPartial) {
         self.accepts_partial = value;
@@ -197,10 +273,15 @@ impl<'r, 't> Parser<'r, 't> {
     }
 
     // Parse settings helpers
-    pub fn check_page_syntax(&self) -> Result<(), ParseError> {
-        if self.settings.enable_page_syntax {
+
+    /// Checks whether the settings permit this kind of element.
+    ///
+    /// This uses the same table as the renderers, see [`GatedElement`].
+    pub fn check_capability(&self, kind: GatedElement) -> Result<(), ParseError> {
+        if kind.is_allowed(self.settings) {
             Ok(())
         } else {
+            warn!("Element '{}' not permitted by settings", kind.name());
             Err(self.make_err(ParseErrorKind::NotSupportedMode))
         }
     }
@@ -210,25 +291,42 @@ impl<'r, 't> Parser<'r, 't> {
         &mut self,
         heading: HeadingLevel,
         name_elements: &[Element],
+        span: Range<usize>,
     ) {
-        // Headings are 1-indexed (e.g. H1), but depth lists are 0-indexed
-        let level = usize::from(heading.value()) - 1;
-
         // Render name as text, so it lacks formatting
         let name =
             TextRender.render_partial(name_elements, self.page_info, self.settings, 0);
 
-        self.table_of_contents.borrow_mut().push((level, name));
+        self.table_of_contents
+            .borrow_mut()
+            .push((heading, name, span));
     }
 
     #[cold]
-    pub fn remove_table_of_contents(&mut self) -> Vec<(usize, String)> {
+    pub fn remove_table_of_contents(&mut self) -> Vec<TableOfContentsHeading> {
         mem::take(&mut self.table_of_contents.borrow_mut())
     }
 
     // Footnotes
-    pub fn push_footnote(&mut self, contents: Vec<Element<'t>>) {
-        self.footnotes.borrow_mut().push(contents);
+
+    /// Adds a footnote, returning its one-indexed position.
+    pub fn push_footnote(&mut self, contents: Vec<Element<'t>>) -> NonZeroUsize {
+        let mut guard = self.footnotes.borrow_mut();
+        guard.push(contents);
+        NonZeroUsize::new(guard.len()).unwrap()
+    }
+
+    pub fn get_named_footnote(&self, name: &str) -> Option<NonZeroUsize> {
+        self.footnote_names.borrow().get(name).copied()
+    }
+
+    pub fn set_named_footnote(&mut self, name: Cow<'t, str>, index: NonZeroUsize) {
+        self.footnote_names.borrow_mut().insert(name, index);
+    }
+
+    /// Forgets all footnote names, so later uses define new footnotes.
+    pub fn clear_named_footnotes(&mut self) {
+        self.footnote_names.borrow_mut().clear();
     }
 
     #[cold]
@@ -236,6 +334,38 @@ impl<'r, 't> Parser<'r, 't> {
         mem::take(&mut self.footnotes.borrow_mut())
     }
 
+    // Anchors
+
+    /// Records an anchor name, warning if it is already used by another anchor.
+    ///
+    /// Anchors are keyed by their position, so re-parsing the
+    /// same anchor while backtracking is not a duplicate.
+    pub fn add_anchor_name(&mut self, name: Cow<'t, str>, start: usize) {
+        let first_start = *self.anchor_names.borrow_mut().entry(name).or_insert(start);
+
+        if first_start != start {
+            self.push_warning(ParseErrorKind::DuplicateAnchorName);
+        }
+    }
+
+    // Radio buttons
+
+    /// Records the name of a checked radio button, warning if
+    /// another button with this name is already checked.
+    ///
+    /// Like anchors, these are keyed by position to allow backtracking.
+    pub fn add_checked_radio_name(&mut self, name: Cow<'t, str>, start: usize) {
+        let first_start = *self
+            .checked_radio_names
+            .borrow_mut()
+            .entry(name)
+            .or_insert(start);
+
+        if first_start != start {
+            self.push_warning(ParseErrorKind::RadioButtonMultipleChecked);
+        }
+    }
+
     // Bibliography
     pub fn push_bibliography(&mut self, bibliography: Bibliography<'t>) -> usize {
         let mut guard = self.bibliographies.borrow_mut();
@@ -249,10 +379,21 @@ impl<'r, 't> Parser<'r, 't> {
         mem::take(&mut self.bibliographies.borrow_mut())
     }
 
+    // Warnings
+    pub fn push_warning(&self, kind: ParseErrorKind) {
+        let warning = self.make_err(kind);
+        self.warnings.borrow_mut().push(warning);
+    }
+
+    #[cold]
+    pub fn remove_warnings(&mut self) -> Vec<ParseError> {
+        mem::take(&mut self.warnings.borrow_mut())
+    }
+
     // Special for [[include]], appending a SyntaxTree
     pub fn append_shared_items(
         &mut self,
-        table_of_contents: &mut Vec<(usize, String)>,
+        table_of_contents: &mut Vec<TableOfContentsHeading>,
         footnotes: &mut Vec<Vec<Element<'t>>>,
         bibliographies: &mut BibliographyList<'t>,
     ) {
@@ -387,6 +528,13 @@ impl<'r, 't> Parser<'r, 't> {
             Token::InputStart | Token::LineBreak | Token::ParagraphBreak,
         );
 
+        // Count this against the step limit.
+        if self.fuel.add_step(&self.settings.parse_limits) {
+            warn!("Step limit exceeded, leaving the rest as text");
+            self.fuel
+                .set_exceeded(self.make_err(ParseErrorKind::LimitExceeded));
+        }
+
         // Step to the next token.
         match self.remaining.split_first() {
             Some((current, remaining)) => {
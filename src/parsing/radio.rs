@@ -0,0 +1,219 @@
+/*
+ * parsing/radio.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Grouping of consecutive radio buttons which share a name.
+//!
+//! Radio buttons are parsed individually, since they are inline elements.
+//! Afterwards, each run of buttons with the same name is gathered into an
+//! `Element::RadioGroup`, along with the text labelling each of them.
+//!
+//! A group with a label is rendered as a `<fieldset>`, which cannot be
+//! within a paragraph, so paragraphs containing one are split around it.
+
+use crate::tree::visit::transform;
+use crate::tree::{AttributeMap, Container, ContainerType, Element, Elements, ListItem};
+use std::mem;
+
+/// Groups radio buttons within these elements, and any of their children.
+pub fn group_radio_buttons(elements: &mut Vec<Element>) {
+    transform(elements, |mut element| {
+        for_each_list(&mut element, group_list);
+        split_paragraph(element)
+    });
+
+    group_list(elements);
+}
+
+/// Calls the given function on each list of child elements of this element.
+fn for_each_list<'t, F>(element: &mut Element<'t>, mut f: F)
+where
+    F: FnMut(&mut Vec<Element<'t>>),
+{
+    match element {
+        Element::Container(container) => f(container.elements_mut()),
+        Element::Table(table) => {
+            for row in &mut table.rows {
+                for cell in &mut row.cells {
+                    f(&mut cell.elements);
+                }
+            }
+        }
+        Element::TabView(tabs) => {
+            for tab in tabs {
+                f(&mut tab.elements);
+            }
+        }
+        Element::Anchor { elements, .. }
+        | Element::Collapsible { elements, .. }
+        | Element::Quote { elements, .. }
+        | Element::Color { elements, .. }
+        | Element::Size { elements, .. }
+        | Element::Include { elements, .. } => f(elements),
+        Element::List { items, .. } => {
+            for item in items {
+                if let ListItem::Elements { elements, .. } = item {
+                    f(elements);
+                }
+            }
+        }
+        Element::DefinitionList(items) => {
+            for item in items {
+                f(&mut item.key_elements);
+                f(&mut item.value_elements);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Gathers each run of radio buttons in this list into a group.
+///
+/// A group continues through any inline elements, until a radio button
+/// with a different name or a block element. The elements following the
+/// last button until the end of its line are included, as its label.
+///
+/// Lone radio buttons are only grouped if they have a label.
+fn group_list(elements: &mut Vec<Element>) {
+    let mut index = 0;
+
+    while index < elements.len() {
+        let name = match &elements[index] {
+            Element::RadioButton { name, .. } => name.clone(),
+            _ => {
+                index += 1;
+                continue;
+            }
+        };
+
+        // Find the last button in this group
+        let mut last = index;
+        for (offset, element) in elements[index + 1..].iter().enumerate() {
+            match element {
+                Element::RadioButton { name: other, .. } if *other == name => {
+                    last = index + 1 + offset;
+                }
+                Element::RadioButton { .. } => break,
+                _ if !element.paragraph_safe() => break,
+                _ => (),
+            }
+        }
+
+        // Include the last button's label
+        let end = elements[last + 1..]
+            .iter()
+            .position(|element| {
+                matches!(element, Element::LineBreak | Element::RadioButton { .. })
+                    || !element.paragraph_safe()
+            })
+            .map_or(elements.len(), |offset| last + 1 + offset);
+
+        let mut group = elements.drain(index..end).collect::<Vec<_>>();
+
+        // The first label given in the group is used as its caption
+        let label = group.iter_mut().find_map(|element| match element {
+            Element::RadioButton { label, .. } => label.take(),
+            _ => None,
+        });
+
+        if last == index && label.is_none() {
+            debug!("Leaving lone radio button ungrouped (name '{name}')");
+            elements.splice(index..index, group);
+            index = end;
+            continue;
+        }
+
+        info!(
+            "Grouping radio buttons (name '{name}', elements length {})",
+            group.len(),
+        );
+
+        elements.insert(
+            index,
+            Element::RadioGroup {
+                name,
+                label,
+                elements: group,
+            },
+        );
+        index += 1;
+    }
+}
+
+/// Splits a paragraph around any groups which cannot be within one.
+fn split_paragraph(element: Element) -> Elements {
+    let mut container = match element {
+        Element::Container(container)
+            if container.ctype() == ContainerType::Paragraph
+                && container.elements().iter().any(is_fieldset) =>
+        {
+            container
+        }
+        _ => return Elements::Single(element),
+    };
+
+    let attributes = container.attributes().clone();
+    let mut output = Vec::new();
+    let mut paragraph = Vec::new();
+
+    for child in mem::take(container.elements_mut()) {
+        if is_fieldset(&child) {
+            finish_paragraph(&mut paragraph, &mut output, &attributes);
+            output.push(child);
+        } else {
+            paragraph.push(child);
+        }
+    }
+
+    finish_paragraph(&mut paragraph, &mut output, &attributes);
+    Elements::Multiple(output)
+}
+
+/// Adds the elements gathered so far as a paragraph, if there are any.
+fn finish_paragraph<'t>(
+    paragraph: &mut Vec<Element<'t>>,
+    output: &mut Vec<Element<'t>>,
+    attributes: &AttributeMap<'t>,
+) {
+    // Trim line breaks from the ends of the split paragraph
+    while paragraph.last() == Some(&Element::LineBreak) {
+        paragraph.pop();
+    }
+
+    let start = paragraph
+        .iter()
+        .position(|element| *element != Element::LineBreak)
+        .unwrap_or(paragraph.len());
+
+    paragraph.drain(..start);
+
+    if !paragraph.is_empty() {
+        output.push(Element::Container(Container::new(
+            ContainerType::Paragraph,
+            mem::take(paragraph),
+            attributes.clone(),
+        )));
+    }
+}
+
+/// Whether this is a labelled group, which is rendered as a `<fieldset>`.
+#[inline]
+fn is_fieldset(element: &Element) -> bool {
+    matches!(element, Element::RadioGroup { label: Some(_), .. })
+}
@@ -0,0 +1,273 @@
+/*
+ * parsing/registry.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Metadata for every token, parse rule, and block.
+//!
+//! This is intended for tooling such as documentation generators
+//! or editor syntax help, so that they can be built from the same
+//! definitions the parser itself uses.
+
+use super::rule::impls::*;
+use super::rule::{get_rules_for_token, Rule};
+use super::token::{ExtractedToken, Token};
+use enum_map::Enum;
+
+pub use super::rule::impls::{BlockBody, BlockHead};
+
+/// Every parse rule, in alphabetical order.
+const RULES: [Rule; 43] = [
+    RULE_ANCHOR,
+    RULE_BIBCITE,
+    RULE_BLOCK,
+    RULE_BLOCK_SKIP_NEWLINE,
+    RULE_BLOCK_STAR,
+    RULE_BLOCKQUOTE,
+    RULE_BOLD,
+    RULE_CENTER,
+    RULE_CLEAR_FLOAT,
+    RULE_COLOR,
+    RULE_COMMENT,
+    RULE_DASH,
+    RULE_DEFINITION_LIST,
+    RULE_DEFINITION_LIST_SKIP_NEWLINE,
+    RULE_DOUBLE_ANGLE,
+    RULE_EMAIL,
+    RULE_FALLBACK,
+    RULE_FORUM_QUOTE,
+    RULE_HEADER,
+    RULE_HORIZONTAL_RULE,
+    RULE_ITALICS,
+    RULE_LINE_BREAK,
+    RULE_LINE_BREAK_PARAGRAPH,
+    RULE_LINK_ANCHOR,
+    RULE_LINK_SINGLE,
+    RULE_LINK_SINGLE_NEW_TAB,
+    RULE_LINK_TRIPLE,
+    RULE_LINK_TRIPLE_NEW_TAB,
+    RULE_LIST,
+    RULE_MATH,
+    RULE_MONOSPACE,
+    RULE_NULL,
+    RULE_PAGE,
+    RULE_RAW,
+    RULE_STRIKETHROUGH,
+    RULE_SUBSCRIPT,
+    RULE_SUPERSCRIPT,
+    RULE_TABLE,
+    RULE_TEXT,
+    RULE_UNDERLINE,
+    RULE_UNDERSCORE_LINE_BREAK,
+    RULE_URL,
+    RULE_VARIABLE,
+];
+
+/// Metadata describing a token produced by the lexer.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct TokenInfo {
+    pub token: Token,
+    pub description: &'static str,
+    pub example: &'static str,
+
+    /// The names of the rules which may begin with this token, in order of precedence.
+    pub rules: Vec<&'static str>,
+}
+
+/// Metadata describing a parse rule.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct RuleInfo {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub example: &'static str,
+}
+
+/// Metadata describing a block, such as `[[div]]`.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct BlockInfo {
+    pub name: &'static str,
+
+    /// The names this block can be invoked with, matched case-insensitively.
+    pub aliases: &'static [&'static str],
+
+    pub accepts_star: bool,
+    pub accepts_score: bool,
+    pub accepts_newlines: bool,
+    pub head: BlockHead,
+    pub body: BlockBody,
+}
+
+/// Returns metadata for every token, in declaration order.
+pub fn token_registry() -> Vec<TokenInfo> {
+    (0..Token::LENGTH)
+        .map(|index| {
+            let token = Token::from_usize(index);
+            let extracted = ExtractedToken {
+                token,
+                slice: "",
+                span: 0..0,
+            };
+
+            TokenInfo {
+                token,
+                description: token.description(),
+                example: token.example(),
+                rules: get_rules_for_token(&extracted)
+                    .iter()
+                    .map(|rule| rule.name())
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+/// Returns metadata for every parse rule, in alphabetical order.
+pub fn rule_registry() -> Vec<RuleInfo> {
+    RULES
+        .iter()
+        .map(|rule| RuleInfo {
+            name: rule.name(),
+            description: rule.description(),
+            example: rule.example(),
+        })
+        .collect()
+}
+
+/// Returns metadata for every block, in alphabetical order of their names.
+pub fn block_registry() -> Vec<BlockInfo> {
+    let mut blocks: Vec<BlockInfo> = BLOCK_RULES
+        .iter()
+        .map(|block| BlockInfo {
+            name: block.name(),
+            aliases: block.accepts_names(),
+            accepts_star: block.accepts_star(),
+            accepts_score: block.accepts_score(),
+            accepts_newlines: block.accepts_newlines(),
+            head: block.head(),
+            body: block.body(),
+        })
+        .collect();
+
+    blocks.sort_by_key(|block| block.name);
+    blocks
+}
+
+#[test]
+fn registry() {
+    use std::collections::HashSet;
+
+    let tokens = token_registry();
+    let rules = rule_registry();
+
+    // Check rules are unique, sorted, and described
+    let mut names = HashSet::new();
+    for rule in &rules {
+        assert!(names.insert(rule.name), "Duplicate rule {}", rule.name);
+        assert!(
+            !rule.description.is_empty(),
+            "No description for {}",
+            rule.name
+        );
+    }
+
+    assert!(
+        rules.windows(2).all(|pair| pair[0].name < pair[1].name),
+        "Rule registry is not in alphabetical order",
+    );
+
+    // Check tokens are described, and their rules are all registered
+    for info in &tokens {
+        assert!(
+            !info.description.is_empty(),
+            "No description for token {:?}",
+            info.token,
+        );
+
+        for name in &info.rules {
+            assert!(
+                names.contains(name),
+                "Rule {} for token {:?} is not registered",
+                name,
+                info.token,
+            );
+        }
+
+        // Check the example actually lexes as this token
+        //
+        // Emails are skipped, since the identifier token has higher
+        // priority and so only matches their leading word in isolation.
+        if !info.example.is_empty() && info.token != Token::Email {
+            let extracted = Token::extract_all(info.example);
+            let actual: Vec<Token> = extracted.iter().map(|token| token.token).collect();
+
+            assert_eq!(
+                actual,
+                vec![Token::InputStart, info.token, Token::InputEnd],
+                "Example for token {:?} doesn't lex as expected",
+                info.token,
+            );
+        }
+    }
+}
+
+#[test]
+fn blocks() {
+    use std::collections::HashSet;
+
+    let blocks = block_registry();
+    assert_eq!(blocks.len(), BLOCK_RULES.len());
+
+    // Check blocks and their aliases are unique
+    let mut names = HashSet::new();
+    let mut aliases = HashSet::new();
+    for block in &blocks {
+        assert!(names.insert(block.name), "Duplicate block {}", block.name);
+        assert!(!block.aliases.is_empty(), "No aliases for {}", block.name);
+
+        for alias in block.aliases {
+            assert!(
+                aliases.insert(alias.to_ascii_lowercase()),
+                "Duplicate block alias {alias}",
+            );
+        }
+    }
+
+    let div = blocks
+        .iter()
+        .find(|block| block.name == "block-div")
+        .expect("No div block");
+
+    assert!(div.accepts_score);
+    assert_eq!(div.head, BlockHead::Map);
+    assert_eq!(div.body, BlockBody::Elements);
+
+    // Serializes the same as conf/blocks.toml
+    let json = serde_json::to_value(&blocks).unwrap();
+    let image = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|block| block["name"] == "block-image")
+        .expect("No image block");
+
+    assert_eq!(image["head"], "value+map");
+    assert_eq!(image["body"], "none");
+}
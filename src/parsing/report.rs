@@ -0,0 +1,254 @@
+/*
+ * parsing/report.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Summarizing parser warnings for review.
+//!
+//! The flat list of [`ParseError`]s is mostly parser backtracking, which makes
+//! it hard to tell whether a page has any real problems. A [`ParseReport`]
+//! groups them by rule, counts them by severity, and includes a short excerpt
+//! of the source for each, so tooling can decide how to treat the page.
+
+use super::{ParseError, ParseErrorKind, ParseSeverity, Token};
+use crate::utf16::Utf16IndexMap;
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// How many bytes of surrounding text to include on either side of an excerpt.
+const EXCERPT_CONTEXT: usize = 20;
+
+/// An aggregated summary of the warnings produced by a parse.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ParseReport {
+    /// The most severe level of any warning, or `None` if there are no warnings.
+    pub severity: Option<ParseSeverity>,
+
+    /// The number of warnings with info severity.
+    pub info_count: usize,
+
+    /// The number of warnings with warning severity.
+    pub warning_count: usize,
+
+    /// The number of warnings with error severity.
+    pub error_count: usize,
+
+    /// The warnings, grouped by the rule which produced them.
+    ///
+    /// These are sorted by rule name.
+    pub rules: Vec<RuleReport>,
+}
+
+/// All of the warnings produced by a particular rule.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct RuleReport {
+    /// The name of the rule.
+    pub rule: String,
+
+    /// The most severe level of any warning from this rule.
+    pub severity: ParseSeverity,
+
+    /// How many warnings this rule produced.
+    pub count: usize,
+
+    /// Each warning from this rule, in source order.
+    pub entries: Vec<ReportEntry>,
+}
+
+/// A single warning, with the source it refers to.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ReportEntry {
+    pub kind: ParseErrorKind,
+    pub severity: ParseSeverity,
+    pub token: Token,
+    pub span: Range<usize>,
+
+    /// The source text around the warning, limited to the lines it is on.
+    pub excerpt: String,
+}
+
+impl ParseReport {
+    /// Builds a report from the warnings of a parse and the text which was parsed.
+    ///
+    /// The warning spans must be byte indices into `text`.
+    pub fn new(errors: &[ParseError], text: &str) -> Self {
+        let mut report = ParseReport::default();
+        let mut rules = BTreeMap::<&str, Vec<ReportEntry>>::new();
+
+        for error in errors {
+            let severity = error.severity();
+            match severity {
+                ParseSeverity::Info => report.info_count += 1,
+                ParseSeverity::Warning => report.warning_count += 1,
+                ParseSeverity::Error => report.error_count += 1,
+            }
+
+            let span = error.span();
+            rules.entry(error.rule()).or_default().push(ReportEntry {
+                kind: error.kind(),
+                severity,
+                token: error.token(),
+                excerpt: excerpt(text, &span),
+                span,
+            });
+        }
+
+        report.rules = rules
+            .into_iter()
+            .map(|(rule, mut entries)| {
+                entries.sort_by_key(|entry| (entry.span.start, entry.span.end));
+
+                RuleReport {
+                    rule: str!(rule),
+                    severity: entries
+                        .iter()
+                        .map(|entry| entry.severity)
+                        .max()
+                        .expect("Rule report has no entries"),
+                    count: entries.len(),
+                    entries,
+                }
+            })
+            .collect();
+
+        report.severity = report.rules.iter().map(|rule| rule.severity).max();
+        report
+    }
+
+    /// Determines if any warnings are at least as severe as the given level.
+    #[inline]
+    pub fn has_severity(&self, severity: ParseSeverity) -> bool {
+        self.severity >= Some(severity)
+    }
+
+    #[must_use]
+    pub fn to_utf16_indices(&self, map: &Utf16IndexMap) -> Self {
+        let mut report = self.clone();
+
+        for entry in report
+            .rules
+            .iter_mut()
+            .flat_map(|rule| rule.entries.iter_mut())
+        {
+            let start = map.get_index(entry.span.start);
+            let end = map.get_index(entry.span.end);
+            entry.span = start..end;
+        }
+
+        report
+    }
+}
+
+/// Gets the source text around a span, without crossing onto other lines.
+fn excerpt(text: &str, span: &Range<usize>) -> String {
+    let span_end = span.end.min(text.len());
+    let span_start = span.start.min(span_end);
+
+    let line_start = text[..span_start].rfind('\n').map_or(0, |index| index + 1);
+    let line_end = text[span_end..]
+        .find('\n')
+        .map_or(text.len(), |index| span_end + index);
+
+    let mut start = span_start.saturating_sub(EXCERPT_CONTEXT).max(line_start);
+    while !text.is_char_boundary(start) {
+        start -= 1;
+    }
+
+    let mut end = (span_end + EXCERPT_CONTEXT).min(line_end);
+    while !text.is_char_boundary(end) {
+        end += 1;
+    }
+
+    str!(text[start..end])
+}
+
+#[test]
+fn report() {
+    use crate::data::PageInfo;
+    use crate::settings::{WikitextMode, WikitextSettings};
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let text = "Apple [[toc minDepth=\"9\"]]\nBanana [[span]] cherry";
+
+    let tokens = crate::tokenize(text);
+    let (_tree, errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let report = ParseReport::new(&errors, text);
+
+    assert_eq!(report.severity, Some(ParseSeverity::Warning));
+    assert!(report.has_severity(ParseSeverity::Info));
+    assert!(report.has_severity(ParseSeverity::Warning));
+    assert!(!report.has_severity(ParseSeverity::Error));
+    assert_eq!(
+        report.info_count + report.warning_count + report.error_count,
+        errors.len(),
+    );
+    assert_eq!(report.error_count, 0);
+
+    let toc = report
+        .rules
+        .iter()
+        .find(|rule| rule.rule == "block-toc")
+        .expect("No report for table of contents rule");
+
+    assert_eq!(toc.severity, ParseSeverity::Warning);
+    assert_eq!(toc.count, 1);
+    assert_eq!(toc.entries[0].kind, ParseErrorKind::BlockMalformedArguments);
+    assert!(!toc.entries[0].excerpt.contains('\n'));
+
+    // Rules are sorted by name
+    let names: Vec<_> = report.rules.iter().map(|rule| rule.rule.as_str()).collect();
+    let mut sorted_names = names.clone();
+    sorted_names.sort_unstable();
+    assert_eq!(names, sorted_names);
+
+    // No warnings means no severity
+    let report = ParseReport::new(&[], "");
+    assert_eq!(report.severity, None);
+    assert!(!report.has_severity(ParseSeverity::Info));
+}
+
+#[test]
+fn excerpts() {
+    let text = "Apple banana\nCherry durian eggplant fig grape honeydew\nKiwi";
+
+    // Limited to the line
+    assert_eq!(excerpt(text, &(0..5)), "Apple banana");
+    assert_eq!(excerpt(text, &(55..59)), "Kiwi");
+
+    // Limited to the surrounding context
+    assert_eq!(
+        excerpt(text, &(40..45)),
+        "durian eggplant fig grape honeydew"
+    );
+
+    // Spans across lines include both
+    assert_eq!(
+        excerpt(text, &(6..19)),
+        "Apple banana\nCherry durian eggplant fig"
+    );
+
+    // Empty span at the end of input
+    assert_eq!(excerpt(text, &(59..59)), "Kiwi");
+
+    // Stays on character boundaries
+    assert_eq!(excerpt("ααααααααααααααααααααα!", &(42..43)), "αααααααααα!");
+}
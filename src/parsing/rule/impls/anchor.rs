@@ -30,6 +30,8 @@ use std::borrow::Cow;
 
 pub const RULE_ANCHOR: Rule = Rule {
     name: "anchor",
+    description: "An anchor which can be linked to within the page.",
+    example: "[[# anchor-name]]",
     position: LineRequirement::Any,
     try_consume_fn,
 };
@@ -38,6 +40,7 @@ fn try_consume_fn<'r, 't>(
     parser: &mut Parser<'r, 't>,
 ) -> ParseResult<'r, 't, Elements<'t>> {
     info!("Trying to create a named anchor");
+    let start = parser.current().span.start;
     check_step(parser, Token::LeftBlockAnchor)?;
 
     // Requires a space before the name
@@ -63,6 +66,8 @@ fn try_consume_fn<'r, 't>(
         cow!(name)
     };
 
+    parser.add_anchor_name(name.clone(), start);
+
     // Build and return link element
     ok!(Element::AnchorName(name))
 }
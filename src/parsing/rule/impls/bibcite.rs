@@ -22,6 +22,8 @@ use super::prelude::*;
 
 pub const RULE_BIBCITE: Rule = Rule {
     name: "bibcite",
+    description: "A citation of a bibliography entry.",
+    example: "((bibcite label))",
     position: LineRequirement::Any,
     try_consume_fn,
 };
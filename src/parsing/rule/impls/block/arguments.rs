@@ -19,7 +19,6 @@
  */
 
 use crate::parsing::{parse_boolean, ParseError, ParseErrorKind, Parser};
-use crate::settings::WikitextSettings;
 use crate::tree::AttributeMap;
 use std::borrow::Cow;
 use std::collections::HashMap;
@@ -108,12 +107,26 @@ impl<'t> Arguments<'t> {
 
     /// Similar to `to_hash_map()`, but creates an `AttributeMap` instead.
     ///
-    /// Because all fields are passed from the user, this does ID isolation
-    /// if that is enabled, and so needs `WikitextSettings` to be passed in.
-    #[inline]
-    pub fn to_attribute_map(&self, settings: &WikitextSettings) -> AttributeMap<'t> {
+    /// Because all fields are passed from the user, this applies the class and ID
+    /// policy, does ID isolation if that is enabled, and applies the style policy,
+    /// adding a warning to the parser for each value removed.
+    pub fn to_attribute_map(&self, parser: &Parser<'_, 't>) -> AttributeMap<'t> {
+        let settings = parser.settings();
         let mut map = AttributeMap::from_arguments(&self.inner);
+
+        // Check IDs against the policy before they are isolated
+        let removed = map.restrict_container(&settings.container_attributes);
+        for _ in 0..removed {
+            parser.push_warning(ParseErrorKind::ContainerAttributeRemoved);
+        }
+
         map.isolate_id(settings);
+
+        let removed = map.restrict_style(&settings.style_policy);
+        for _ in 0..removed {
+            parser.push_warning(ParseErrorKind::StyleDeclarationRemoved);
+        }
+
         map
     }
 }
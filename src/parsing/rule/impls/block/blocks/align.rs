@@ -33,6 +33,8 @@ macro_rules! make_align_block {
             accepts_star: false,
             accepts_score: false,
             accepts_newlines: true,
+            head: BlockHead::None,
+            body: BlockBody::Elements,
             parse_fn,
         };
 
@@ -28,6 +28,8 @@ pub const BLOCK_ANCHOR: BlockRule = BlockRule {
     accepts_star: true,
     accepts_score: true,
     accepts_newlines: false,
+    head: BlockHead::Map,
+    body: BlockBody::Elements,
     parse_fn,
 };
 
@@ -42,7 +44,7 @@ fn parse_fn<'r, 't>(
     assert_block_name(&BLOCK_ANCHOR, name);
 
     let arguments = parser.get_head_map(&BLOCK_ANCHOR, in_head)?;
-    let attributes = arguments.to_attribute_map(parser.settings());
+    let attributes = arguments.to_attribute_map(parser);
 
     // "a" means we wrap interpret as-is
     // "a_" means we strip out any newlines or paragraph breaks
@@ -26,6 +26,8 @@ pub const BLOCK_BIBCITE: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: true,
     accepts_newlines: false,
+    head: BlockHead::Value,
+    body: BlockBody::None,
     parse_fn,
 };
 
@@ -27,6 +27,8 @@ pub const BLOCK_BIBLIOGRAPHY: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::Map,
+    body: BlockBody::Elements,
     parse_fn,
 };
 
@@ -26,6 +26,8 @@ pub const BLOCK_BLOCKQUOTE: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::Map,
+    body: BlockBody::Elements,
     parse_fn,
 };
 
@@ -50,7 +52,7 @@ fn parse_fn<'r, 't>(
     let element = Element::Container(Container::new(
         ContainerType::Blockquote,
         elements,
-        arguments.to_attribute_map(parser.settings()),
+        arguments.to_attribute_map(parser),
     ));
 
     ok!(element, errors)
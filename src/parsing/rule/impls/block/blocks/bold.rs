@@ -26,6 +26,8 @@ pub const BLOCK_BOLD: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: false,
+    head: BlockHead::Map,
+    body: BlockBody::Elements,
     parse_fn,
 };
 
@@ -50,7 +52,7 @@ fn parse_fn<'r, 't>(
     let element = Element::Container(Container::new(
         ContainerType::Bold,
         elements,
-        arguments.to_attribute_map(parser.settings()),
+        arguments.to_attribute_map(parser),
     ));
 
     ok!(paragraph_safe; element, errors)
@@ -0,0 +1,62 @@
+/*
+ * parsing/rule/impls/block/blocks/calc.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::expr;
+
+pub const BLOCK_CALC: BlockRule = BlockRule {
+    name: "block-calc",
+    accepts_names: &["calc"],
+    accepts_star: false,
+    accepts_score: false,
+    accepts_newlines: false,
+    head: BlockHead::Value,
+    body: BlockBody::None,
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    flag_star: bool,
+    flag_score: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    info!("Parsing calc block (name '{name}', in-head {in_head})");
+    assert!(!flag_star, "Calc doesn't allow star flag");
+    assert!(!flag_score, "Calc doesn't allow score flag");
+    assert_block_name(&BLOCK_CALC, name);
+
+    let expression =
+        parser.get_head_value(&BLOCK_CALC, in_head, |parser, value| match value {
+            Some(expression) => Ok(expression.trim()),
+            None => Err(parser.make_err(ParseErrorKind::BlockMissingArguments)),
+        })?;
+
+    // Only check syntax here, evaluation happens at render time
+    if let Err(error) = expr::validate(expression) {
+        warn!("Calc block has invalid expression: {error}");
+        return Err(parser.make_err(ParseErrorKind::BlockMalformedArguments));
+    }
+
+    ok!(Element::Calc {
+        expression: cow!(expression),
+    })
+}
@@ -19,24 +19,8 @@
  */
 
 use super::prelude::*;
-use entities::ENTITIES;
-use once_cell::sync::Lazy;
+use crate::entity::{find_entity, strip_entity};
 use std::borrow::Cow;
-use std::char;
-use std::collections::HashMap;
-
-static ENTITY_MAPPING: Lazy<HashMap<&'static str, &'static str>> = Lazy::new(|| {
-    let mut mapping = HashMap::new();
-
-    for entity in &ENTITIES {
-        let key = strip_entity(entity.entity);
-        let value = entity.characters;
-
-        mapping.insert(key, value);
-    }
-
-    mapping
-});
 
 pub const BLOCK_CHAR: BlockRule = BlockRule {
     name: "block-char",
@@ -44,6 +28,8 @@ pub const BLOCK_CHAR: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: false,
+    head: BlockHead::Value,
+    body: BlockBody::None,
     parse_fn,
 };
 
@@ -79,163 +65,3 @@ fn parse_entity<'t>(
         None => Err(parser.make_err(ParseErrorKind::BlockMalformedArguments)),
     }
 }
-
-/// Find the string corresponding to the passed entity, if any.
-fn find_entity(entity: &str) -> Option<Cow<str>> {
-    // Named entity
-    if let Some(result) = ENTITY_MAPPING.get(entity) {
-        return Some(cow!(result));
-    }
-
-    // Hexadecimal entity
-    if let Some(value) = entity.strip_prefix("#x") {
-        if let Some(result) = get_char(value, 16) {
-            return Some(result);
-        }
-    }
-
-    // Decimal entity
-    if let Some(value) = entity.strip_prefix('#') {
-        if let Some(result) = get_char(value, 10) {
-            return Some(result);
-        }
-    }
-
-    // Not found
-    None
-}
-
-/// Gets the appropriate character from the number specified in the string.
-///
-/// Using the passed radix, it gets the integer value, then finds the appropriate
-/// character, if one exists.
-///
-/// Then converts the character into a string with only that value.
-fn get_char(value: &str, radix: u32) -> Option<Cow<str>> {
-    let codepoint = match u32::from_str_radix(value, radix) {
-        Ok(codepoint) => codepoint,
-        Err(_) => return None,
-    };
-
-    let ch = match char::from_u32(codepoint) {
-        Some(ch) => ch,
-        None => return None,
-    };
-
-    Some(Cow::Owned(ch.to_string()))
-}
-
-/// If a string starts with `&` or ends with `;`, those are removed.
-/// First trims the string of whitespace.
-fn strip_entity(mut s: &str) -> &str {
-    s = s.trim();
-
-    if let Some(stripped) = s.strip_prefix('&') {
-        s = stripped;
-    }
-
-    if let Some(stripped) = s.strip_suffix(';') {
-        s = stripped;
-    }
-
-    s
-}
-
-/* Tests */
-
-#[test]
-fn test_get_entity() {
-    macro_rules! check {
-        ($input:expr, $expected:expr $(,)?) => {{
-            let actual = find_entity($input);
-            let expected = $expected;
-
-            assert_eq!(
-                actual, expected,
-                "Actual entity string doesn't match expected",
-            );
-        }};
-    }
-
-    check!("", None);
-
-    // Names
-    check!("amp", Some(cow!("&")));
-    check!("lt", Some(cow!("<")));
-    check!("gt", Some(cow!(">")));
-    check!("copy", Some(cow!("©")));
-    check!("xxxzzz", None);
-
-    // Decimal
-    check!("#32", Some(cow!(" ")));
-    check!("#255", Some(cow!("\u{ff}")));
-    check!("#128175", Some(cow!("💯")));
-    check!("#2097151", None);
-
-    // Hex
-    check!("#x20", Some(cow!(" ")));
-    check!("#xff", Some(cow!("\u{ff}")));
-    check!("#x1f4af", Some(cow!("💯")));
-    check!("#x1fffff", None);
-}
-
-#[test]
-fn test_get_char() {
-    macro_rules! check {
-        ($value:expr, $radix:expr, $expected:expr $(,)?) => {{
-            let actual = get_char($value, $radix);
-            let expected = $expected;
-
-            assert_eq!(
-                actual, expected,
-                "Actual character value doesn't match expected",
-            );
-        }};
-    }
-
-    // Decimal
-    check!("32", 10, Some(Cow::Owned(str!(' '))));
-    check!("255", 10, Some(Cow::Owned(str!('\u{ff}'))));
-    check!("128175", 10, Some(Cow::Owned(str!('💯'))));
-    check!("2097151", 10, None);
-
-    // Hex
-    check!("20", 16, Some(Cow::Owned(str!(' '))));
-    check!("ff", 16, Some(Cow::Owned(str!('\u{ff}'))));
-    check!("1f4af", 16, Some(Cow::Owned(str!('💯'))));
-    check!("1fffff", 16, None);
-}
-
-#[test]
-fn test_strip_entity() {
-    macro_rules! check {
-        ($input:expr, $expected:expr $(,)?) => {{
-            let actual = strip_entity($input);
-            let expected = $expected;
-
-            assert_eq!(
-                actual, expected,
-                "Actual stripped entity value didn't match expected",
-            );
-        }};
-    }
-
-    check!("", "");
-    check!("abc", "abc");
-    check!("legumes1", "legumes1");
-    check!("&amp;", "amp");
-    check!("&#100;", "#100");
-    check!("&xdeadbeef;", "xdeadbeef");
-
-    check!("&amp", "amp");
-    check!("amp;", "amp");
-    check!("&#100", "#100");
-    check!("#100;", "#100");
-
-    check!(" ", "");
-    check!(" abc", "abc");
-    check!(" legumes1", "legumes1");
-    check!(" &amp;", "amp");
-    check!(" &#100;", "#100");
-    check!(" &xdeadbeef;", "xdeadbeef");
-}
@@ -26,6 +26,8 @@ pub const BLOCK_CHECKBOX: BlockRule = BlockRule {
     accepts_star: true,
     accepts_score: false,
     accepts_newlines: false,
+    head: BlockHead::Map,
+    body: BlockBody::None,
     parse_fn,
 };
 
@@ -45,7 +47,7 @@ fn parse_fn<'r, 't>(
 
     let element = Element::CheckBox {
         checked: flag_star,
-        attributes: arguments.to_attribute_map(parser.settings()),
+        attributes: arguments.to_attribute_map(parser),
     };
 
     ok!(element)
@@ -26,6 +26,8 @@ pub const BLOCK_CODE: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::Map,
+    body: BlockBody::Raw,
     parse_fn,
 };
 
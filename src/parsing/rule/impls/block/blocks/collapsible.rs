@@ -27,6 +27,8 @@ pub const BLOCK_COLLAPSIBLE: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::Map,
+    body: BlockBody::Elements,
     parse_fn,
 };
 
@@ -57,6 +59,11 @@ fn parse_fn<'r, 't>(
         None => (true, false),
     };
 
+    // Get anchor behavior
+    let open_if_anchored = arguments
+        .get_bool(parser, "openIfAnchored")?
+        .unwrap_or(false);
+
     // Get body content, with paragraphs.
     // Discard paragraph_safe, since collapsibles never are.
     let (elements, errors, _) =
@@ -65,12 +72,13 @@ fn parse_fn<'r, 't>(
     // Build element and return
     let element = Element::Collapsible {
         elements,
-        attributes: arguments.to_attribute_map(parser.settings()),
+        attributes: arguments.to_attribute_map(parser),
         start_open,
         show_text,
         hide_text,
         show_top,
         show_bottom,
+        open_if_anchored,
     };
 
     ok!(element, errors)
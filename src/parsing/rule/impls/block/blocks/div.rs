@@ -26,6 +26,8 @@ pub const BLOCK_DIV: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: true,
     accepts_newlines: true,
+    head: BlockHead::Map,
+    body: BlockBody::Elements,
     parse_fn,
 };
 
@@ -56,7 +58,7 @@ fn parse_fn<'r, 't>(
     let element = Element::Container(Container::new(
         ContainerType::Div,
         elements,
-        arguments.to_attribute_map(parser.settings()),
+        arguments.to_attribute_map(parser),
     ));
 
     ok!(element, errors)
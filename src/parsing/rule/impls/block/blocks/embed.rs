@@ -20,6 +20,8 @@
 
 use super::prelude::*;
 use crate::tree::Embed;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 
 type EmbedBuilderFn = for<'p, 't> fn(
     &'p Parser<'_, 't>,
@@ -32,6 +34,8 @@ pub const BLOCK_EMBED: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::ValueMap,
+    body: BlockBody::None,
     parse_fn,
 };
 
@@ -43,6 +47,7 @@ fn parse_fn<'r, 't>(
     in_head: bool,
 ) -> ParseResult<'r, 't, Elements<'t>> {
     info!("Parsing embed block (name '{name}', in-head {in_head})");
+    parser.check_capability(GatedElement::Embed)?;
     assert!(!flag_star, "Embed doesn't allow star flag");
     assert!(!flag_score, "Embed doesn't allow star flag");
     assert_block_name(&BLOCK_EMBED, name);
@@ -70,6 +75,11 @@ where
         }
     }
 
+    // Otherwise, check the providers configured in the settings
+    if parser.settings().embed_providers.get(name).is_some() {
+        return build_provider(parser, name, arguments);
+    }
+
     Err(parser.make_err(ParseErrorKind::NoSuchEmbed))
 }
 
@@ -97,6 +107,35 @@ fn build_vimeo<'p, 't>(
     Ok(Embed::Vimeo { video_id })
 }
 
+fn build_provider<'p, 't>(
+    parser: &'p Parser<'_, 't>,
+    name: &str,
+    arguments: &'p mut Arguments<'t>,
+) -> Result<Embed<'t>, ParseError> {
+    let provider = parser
+        .settings()
+        .embed_providers
+        .get(name)
+        .expect("Embed provider not registered");
+
+    let id = arguments
+        .get("id")
+        .ok_or_else(|| parser.make_err(ParseErrorKind::BlockMissingArguments))?;
+
+    // Only keep arguments the provider accepts
+    let params = arguments
+        .to_hash_map()
+        .into_iter()
+        .filter(|(key, _)| provider.allows_param(key))
+        .collect::<BTreeMap<_, _>>();
+
+    Ok(Embed::Provider {
+        provider: Cow::Owned(name.to_ascii_lowercase()),
+        id,
+        params,
+    })
+}
+
 #[test]
 fn embed_builder_types() {
     let _: EmbedBuilderFn = build_youtube;
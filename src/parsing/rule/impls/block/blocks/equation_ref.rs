@@ -26,6 +26,8 @@ pub const BLOCK_EQUATION_REF: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: false,
+    head: BlockHead::Value,
+    body: BlockBody::None,
     parse_fn,
 };
 
@@ -19,6 +19,7 @@
  */
 
 use super::prelude::*;
+use crate::settings::{FootnoteBlockMode, FootnotePlacement};
 use std::ops::{Deref, DerefMut};
 
 pub const BLOCK_FOOTNOTE: BlockRule = BlockRule {
@@ -27,6 +28,8 @@ pub const BLOCK_FOOTNOTE: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: false,
+    head: BlockHead::Map,
+    body: BlockBody::Elements,
     parse_fn: parse_footnote_ref,
 };
 
@@ -36,6 +39,8 @@ pub const BLOCK_FOOTNOTE_BLOCK: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::Map,
+    body: BlockBody::None,
     parse_fn: parse_footnote_block,
 };
 
@@ -64,7 +69,22 @@ fn parse_footnote_ref<'r, 't>(
     assert!(!flag_score, "Footnote reference doesn't allow score flag");
     assert_block_name(&BLOCK_FOOTNOTE, name);
 
-    parser.get_head_none(&BLOCK_FOOTNOTE, in_head)?;
+    let mut arguments = parser.get_head_map(&BLOCK_FOOTNOTE, in_head)?;
+    let name = arguments.get("name");
+
+    if !arguments.is_empty() {
+        warn!("Invalid argument keys found");
+        return Err(parser.make_err(ParseErrorKind::BlockMalformedArguments));
+    }
+
+    // If this name has been used already, refer to that footnote.
+    // There is no body in this case.
+    if let Some(ref name) = name {
+        if let Some(index) = parser.get_named_footnote(name) {
+            debug!("Repeating named footnote '{name}' (index {index})");
+            return ok!(Element::FootnoteRepeat(index));
+        }
+    }
 
     // Gather footnote contents with paragraphs.
     //
@@ -89,7 +109,11 @@ fn parse_footnote_ref<'r, 't>(
     }
 
     // Append footnote contents and return.
-    parser.push_footnote(elements);
+    let index = parser.push_footnote(elements);
+
+    if let Some(name) = name {
+        parser.set_named_footnote(name, index);
+    }
 
     ok!(Element::Footnote, errors)
 }
@@ -106,6 +130,12 @@ fn parse_footnote_block<'r, 't>(
     assert!(!flag_score, "Footnote block doesn't allow score flag");
     assert_block_name(&BLOCK_FOOTNOTE_BLOCK, name);
 
+    // A footnote block inside a footnote would list itself
+    if parser.in_footnote() {
+        warn!("Footnote block found inside a footnote");
+        return Err(parser.make_err(ParseErrorKind::FootnotesNested));
+    }
+
     // Parse arguments
     let mut arguments = parser.get_head_map(&BLOCK_FOOTNOTE_BLOCK, in_head)?;
 
@@ -117,9 +147,21 @@ fn parse_footnote_block<'r, 't>(
         return Err(parser.make_err(ParseErrorKind::BlockMalformedArguments));
     }
 
+    // If footnotes always go at the end, it will be added there instead
+    if parser.settings().footnote_placement == FootnotePlacement::End {
+        debug!("Footnote block placement is at end, ignoring");
+        return ok!(Elements::None);
+    }
+
     // Tell parser that a footnote block was added
     parser.set_footnote_block();
 
+    // Footnotes after a split block are listed separately,
+    // so named footnotes cannot be repeated across it.
+    if parser.settings().footnote_block_mode == FootnoteBlockMode::Split {
+        parser.clear_named_footnotes();
+    }
+
     // Build and return
     ok!(Element::FootnoteBlock { title, hide })
 }
@@ -0,0 +1,171 @@
+/*
+ * parsing/rule/impls/block/blocks/gallery.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::parsing::parse_string;
+use crate::tree::{
+    GalleryImage, GalleryOrder, GalleryOrderField, GallerySize, ImageSource, LinkLocation,
+};
+use std::borrow::Cow;
+
+pub const BLOCK_GALLERY: BlockRule = BlockRule {
+    name: "block-gallery",
+    accepts_names: &["gallery"],
+    accepts_star: false,
+    accepts_score: false,
+    accepts_newlines: true,
+    head: BlockHead::Map,
+    body: BlockBody::Other,
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    flag_star: bool,
+    flag_score: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    info!("Parsing gallery block (in-head {in_head})");
+    assert!(!flag_star, "Gallery doesn't allow star flag");
+    assert!(!flag_score, "Gallery doesn't allow score flag");
+    assert_block_name(&BLOCK_GALLERY, name);
+
+    let mut arguments = parser.get_head_map(&BLOCK_GALLERY, in_head)?;
+
+    let size = match arguments.get("size") {
+        Some(size) => match GallerySize::parse(&size) {
+            Some(size) => size,
+            None => return Err(parser.make_err(ParseErrorKind::BlockMalformedArguments)),
+        },
+        None => GallerySize::default(),
+    };
+
+    let order = match arguments.get("order") {
+        Some(order) => Some(parse_order(parser, &order)?),
+        None => None,
+    };
+
+    // Each non-empty line of the body is an image
+    let body = parser.get_body_text(&BLOCK_GALLERY)?;
+    let mut images = Vec::new();
+
+    for line in body.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        match parse_image(parser, line) {
+            Some(image) => images.push(image),
+            None => {
+                warn!("Invalid gallery image line: {line:?}");
+                return Err(parser.make_err(ParseErrorKind::BlockMalformedArguments));
+            }
+        }
+    }
+
+    ok!(Element::Gallery {
+        images,
+        size,
+        order,
+    })
+}
+
+fn parse_order(parser: &Parser, value: &str) -> Result<GalleryOrder, ParseError> {
+    let mut parts = value.split_whitespace();
+    let field = match parts.next().and_then(GalleryOrderField::parse) {
+        Some(field) => field,
+        None => return Err(parser.make_err(ParseErrorKind::BlockMalformedArguments)),
+    };
+
+    let descending = match parts.next() {
+        None => false,
+        Some(direction) if direction.eq_ignore_ascii_case("asc") => false,
+        Some(direction) if direction.eq_ignore_ascii_case("desc") => true,
+        Some(_) => return Err(parser.make_err(ParseErrorKind::BlockMalformedArguments)),
+    };
+
+    if parts.next().is_some() {
+        return Err(parser.make_err(ParseErrorKind::BlockMalformedArguments));
+    }
+
+    Ok(GalleryOrder { field, descending })
+}
+
+/// Parses a line of the form `: source key="value" key=value ...`.
+fn parse_image<'t>(parser: &Parser<'_, 't>, line: &'t str) -> Option<GalleryImage<'t>> {
+    let line = line.strip_prefix(':')?.trim_start();
+    let (source, mut rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let source = ImageSource::parse(source)?;
+    let mut arguments = Arguments::new();
+
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+
+        let (key, value) = rest.split_once('=')?;
+        let key = key.trim_end();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return None;
+        }
+
+        let (value, remaining) = split_value(value.trim_start())?;
+        arguments.insert(key, value);
+        rest = remaining;
+    }
+
+    let link = arguments.get("link").map(LinkLocation::parse);
+    let title = arguments.get("title");
+
+    Some(GalleryImage {
+        source,
+        link,
+        title,
+        attributes: arguments.to_attribute_map(parser),
+    })
+}
+
+/// Splits off an argument value, which is either a quoted string or a bare word.
+fn split_value(value: &str) -> Option<(Cow<'_, str>, &str)> {
+    if !value.starts_with('"') {
+        let (value, rest) = value.split_once(char::is_whitespace).unwrap_or((value, ""));
+
+        return Some((cow!(value), rest));
+    }
+
+    // Find the closing quote, skipping over escapes
+    let mut escaped = false;
+    for (index, ch) in value.char_indices().skip(1) {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => {
+                let (string, rest) = value.split_at(index + 1);
+                return Some((parse_string(string), rest));
+            }
+            _ => (),
+        }
+    }
+
+    None
+}
@@ -26,6 +26,8 @@ pub const BLOCK_HTML: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::None,
+    body: BlockBody::Raw,
     parse_fn,
 };
 
@@ -37,6 +39,7 @@ fn parse_fn<'r, 't>(
     in_head: bool,
 ) -> ParseResult<'r, 't, Elements<'t>> {
     info!("Parsing HTML block (in-head {in_head})");
+    parser.check_capability(GatedElement::Html)?;
     assert!(!flag_star, "HTML doesn't allow star flag");
     assert!(!flag_score, "HTML doesn't allow score flag");
     assert_block_name(&BLOCK_HTML, name);
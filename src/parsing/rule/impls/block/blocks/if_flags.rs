@@ -0,0 +1,89 @@
+/*
+ * parsing/rule/impls/block/blocks/if_flags.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::parsing::ElementCondition;
+use std::borrow::Cow;
+
+pub const BLOCK_IF: BlockRule = BlockRule {
+    name: "block-if",
+    accepts_names: &["if"],
+    accepts_star: false,
+    accepts_score: false,
+    accepts_newlines: true,
+    head: BlockHead::Value,
+    body: BlockBody::Elements,
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    flag_star: bool,
+    flag_score: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    info!("Parsing if block (name '{name}', in-head {in_head})");
+    assert!(!flag_star, "If doesn't allow star flag");
+    assert!(!flag_score, "If doesn't allow score flag");
+    assert_block_name(&BLOCK_IF, name);
+
+    // Parse out flag conditions
+    let conditions =
+        parser.get_head_value(&BLOCK_IF, in_head, |parser, spec| match spec {
+            Some(spec) => Ok(ElementCondition::parse(spec)),
+            None => Err(parser.make_err(ParseErrorKind::BlockMissingArguments)),
+        })?;
+
+    // Get body content, never with paragraphs
+    let (elements, errors, paragraph_safe) =
+        parser.get_body_elements(&BLOCK_IF, false)?.into();
+
+    debug!(
+        "If conditions parsed (conditions length {}, elements length {})",
+        conditions.len(),
+        elements.len(),
+    );
+
+    // Return elements based on condition
+    let elements = if check_if(parser.settings(), &conditions) {
+        debug!("Conditions passed, including elements");
+
+        Elements::Multiple(elements)
+    } else {
+        debug!("Conditions failed, excluding elements");
+
+        Elements::None
+    };
+
+    ok!(paragraph_safe; elements, errors)
+}
+
+pub fn check_if(settings: &WikitextSettings, conditions: &[ElementCondition]) -> bool {
+    debug!("Checking if (flags {:?})", settings.condition_flags);
+
+    let flags = settings
+        .condition_flags
+        .iter()
+        .map(|flag| Cow::Borrowed(flag.as_str()))
+        .collect::<Vec<_>>();
+
+    ElementCondition::check(conditions, &flags)
+}
@@ -27,6 +27,8 @@ pub const BLOCK_IFRAME: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::ValueMap,
+    body: BlockBody::None,
     parse_fn,
 };
 
@@ -38,6 +40,7 @@ fn parse_fn<'r, 't>(
     in_head: bool,
 ) -> ParseResult<'r, 't, Elements<'t>> {
     info!("Parsing iframe block (in-head {in_head})");
+    parser.check_capability(GatedElement::Iframe)?;
     assert!(!flag_star, "iframe doesn't allow star flag");
     assert!(!flag_score, "iframe doesn't allow score flag");
     assert_block_name(&BLOCK_IFRAME, name);
@@ -50,7 +53,7 @@ fn parse_fn<'r, 't>(
 
     let element = Element::Iframe {
         url: cow!(url),
-        attributes: arguments.to_attribute_map(parser.settings()),
+        attributes: arguments.to_attribute_map(parser),
     };
 
     ok!(element)
@@ -28,6 +28,8 @@ pub const BLOCK_IFTAGS: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::Value,
+    body: BlockBody::Elements,
     parse_fn,
 };
 
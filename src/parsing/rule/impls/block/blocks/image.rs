@@ -27,6 +27,8 @@ pub const BLOCK_IMAGE: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: false,
+    head: BlockHead::ValueMap,
+    body: BlockBody::None,
     parse_fn,
 };
 
@@ -57,7 +59,7 @@ fn parse_fn<'r, 't>(
         source,
         link,
         alignment,
-        attributes: arguments.to_attribute_map(parser.settings()),
+        attributes: arguments.to_attribute_map(parser),
     };
 
     ok!(element)
@@ -32,6 +32,8 @@ pub const BLOCK_INCLUDE_ELEMENTS: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::ValueMap,
+    body: BlockBody::None,
     parse_fn,
 };
 
@@ -43,7 +45,7 @@ fn parse_fn<'r, 't>(
     in_head: bool,
 ) -> ParseResult<'r, 't, Elements<'t>> {
     info!("Found invalid include-elements block");
-    parser.check_page_syntax()?;
+    parser.check_capability(GatedElement::Include)?;
     assert!(!flag_star, "Include (elements) doesn't allow star flag");
     assert!(!flag_score, "Include (elements) doesn't allow score flag");
     assert_block_name(&BLOCK_INCLUDE_ELEMENTS, name);
@@ -35,6 +35,8 @@ pub const BLOCK_INCLUDE_MESSY: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::Value,
+    body: BlockBody::None,
     parse_fn,
 };
 
@@ -46,7 +48,7 @@ fn parse_fn<'r, 't>(
     _in_head: bool,
 ) -> ParseResult<'r, 't, Elements<'t>> {
     info!("Found invalid include-messy block");
-    parser.check_page_syntax()?;
+    parser.check_capability(GatedElement::Include)?;
     assert!(!flag_star, "Include (messy) doesn't allow star flag");
     assert!(!flag_score, "Include (messy) doesn't allow score flag");
     assert_block_name(&BLOCK_INCLUDE_MESSY, name);
@@ -35,6 +35,8 @@ pub const BLOCK_LATER: BlockRule = BlockRule {
     accepts_star: true,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::None,
+    body: BlockBody::None,
     parse_fn,
 };
 
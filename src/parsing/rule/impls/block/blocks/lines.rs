@@ -27,6 +27,8 @@ pub const BLOCK_LINES: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::Value,
+    body: BlockBody::None,
     parse_fn,
 };
 
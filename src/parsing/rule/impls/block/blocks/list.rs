@@ -30,6 +30,8 @@ pub const BLOCK_UL: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: true,
     accepts_newlines: true,
+    head: BlockHead::Map,
+    body: BlockBody::Elements,
     parse_fn: parse_unordered_block,
 };
 
@@ -39,6 +41,8 @@ pub const BLOCK_OL: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: true,
     accepts_newlines: true,
+    head: BlockHead::Map,
+    body: BlockBody::Elements,
     parse_fn: parse_ordered_block,
 };
 
@@ -48,6 +52,8 @@ pub const BLOCK_LI: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: true,
     accepts_newlines: true,
+    head: BlockHead::Map,
+    body: BlockBody::Elements,
     parse_fn: parse_list_item,
 };
 
@@ -115,7 +121,7 @@ fn parse_list_block<'r, 't>(
 
     // Get attributes
     let arguments = parser.get_head_map(block_rule, in_head)?;
-    let attributes = arguments.to_attribute_map(parser.settings());
+    let attributes = arguments.to_attribute_map(parser);
 
     // Get body and convert into list form.
     let (mut elements, errors, _) = parser.get_body_elements(block_rule, false)?.into();
@@ -198,7 +204,7 @@ fn parse_list_item<'r, 't>(
 
     // Get attributes
     let arguments = parser.get_head_map(&BLOCK_LI, in_head)?;
-    let attributes = arguments.to_attribute_map(parser.settings());
+    let attributes = arguments.to_attribute_map(parser);
 
     // Get body elements
     let (mut elements, errors, _) = parser.get_body_elements(&BLOCK_LI, false)?.into();
@@ -26,6 +26,8 @@ pub const BLOCK_MATH: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::Value,
+    body: BlockBody::Raw,
     parse_fn,
 };
 
@@ -0,0 +1,141 @@
+/*
+ * parsing/rule/impls/block/blocks/media.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::tree::{ImageSource, MediaFlags, MediaType};
+
+// Definitions
+
+pub const BLOCK_AUDIO: BlockRule = BlockRule {
+    name: "block-audio",
+    accepts_names: &["audio"],
+    accepts_star: false,
+    accepts_score: false,
+    accepts_newlines: false,
+    head: BlockHead::ValueMap,
+    body: BlockBody::None,
+    parse_fn: parse_audio,
+};
+
+pub const BLOCK_VIDEO: BlockRule = BlockRule {
+    name: "block-video",
+    accepts_names: &["video"],
+    accepts_star: false,
+    accepts_score: false,
+    accepts_newlines: false,
+    head: BlockHead::ValueMap,
+    body: BlockBody::None,
+    parse_fn: parse_video,
+};
+
+fn parse_audio<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    flag_star: bool,
+    flag_score: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    parse_media(
+        (&BLOCK_AUDIO, MediaType::Audio),
+        parser,
+        name,
+        flag_star,
+        flag_score,
+        in_head,
+    )
+}
+
+fn parse_video<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    flag_star: bool,
+    flag_score: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    parse_media(
+        (&BLOCK_VIDEO, MediaType::Video),
+        parser,
+        name,
+        flag_star,
+        flag_score,
+        in_head,
+    )
+}
+
+// Shared helper
+
+fn parse_media<'r, 't>(
+    (block_rule, media_type): (&BlockRule, MediaType),
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    flag_star: bool,
+    flag_score: bool,
+    in_head: bool,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    info!("Parsing media block (name {name}, in-head {in_head})");
+    assert!(!flag_star, "Media doesn't allow star flag");
+    assert!(!flag_score, "Media doesn't allow score flag");
+    assert_block_name(block_rule, name);
+
+    let (source, mut arguments) = parser.get_head_name_map(block_rule, in_head)?;
+
+    // Parse the sources, a lone file name being an attachment on this page.
+    // Any fallbacks are alternative formats, in order of preference.
+    let fallbacks = arguments.get("fallback");
+    let sources = Some(source)
+        .into_iter()
+        .chain(fallbacks.iter().flat_map(|value| value.split_whitespace()))
+        .map(|source| ImageSource::parse(source).map(|source| source.to_owned()))
+        .collect::<Option<Vec<_>>>();
+
+    let sources = match sources {
+        Some(sources) => sources,
+        None => return Err(parser.make_err(ParseErrorKind::BlockMalformedArguments)),
+    };
+
+    let defaults = MediaFlags::default();
+    let flags = MediaFlags {
+        controls: arguments
+            .get_bool(parser, "controls")?
+            .unwrap_or(defaults.controls),
+        autoplay: arguments
+            .get_bool(parser, "autoplay")?
+            .unwrap_or(defaults.autoplay),
+        looping: arguments
+            .get_bool(parser, "loop")?
+            .unwrap_or(defaults.looping),
+        muted: arguments
+            .get_bool(parser, "muted")?
+            .unwrap_or(defaults.muted),
+    };
+
+    let caption = arguments.get("caption");
+
+    // Build media element
+    let element = Element::Media {
+        media_type,
+        sources,
+        caption,
+        flags,
+        attributes: arguments.to_attribute_map(parser),
+    };
+
+    ok!(element)
+}
@@ -19,10 +19,11 @@
  */
 
 mod prelude {
-    pub use super::super::{Arguments, BlockRule};
+    pub use super::super::{Arguments, BlockBody, BlockHead, BlockRule};
     pub use crate::parsing::parser::Parser;
     pub use crate::parsing::prelude::*;
     pub use crate::parsing::ParseError;
+    pub use crate::settings::GatedElement;
     pub use crate::tree::{Container, ContainerType, Element};
 
     #[cfg(debug)]
@@ -64,18 +65,22 @@ mod bibcite;
 mod bibliography;
 mod blockquote;
 mod bold;
+mod calc;
 mod char;
 mod checkbox;
 mod code;
 mod collapsible;
+mod css;
 mod date;
 mod del;
 mod div;
 mod embed;
 mod equation_ref;
 mod footnote;
+mod gallery;
 mod hidden;
 mod html;
+mod if_flags;
 mod ifcategory;
 mod iframe;
 mod iftags;
@@ -90,6 +95,7 @@ mod lines;
 mod list;
 mod mark;
 mod math;
+mod media;
 mod module;
 mod monospace;
 mod paragraph;
@@ -116,18 +122,22 @@ pub use self::bibcite::BLOCK_BIBCITE;
 pub use self::bibliography::BLOCK_BIBLIOGRAPHY;
 pub use self::blockquote::BLOCK_BLOCKQUOTE;
 pub use self::bold::BLOCK_BOLD;
+pub use self::calc::BLOCK_CALC;
 pub use self::char::BLOCK_CHAR;
 pub use self::checkbox::BLOCK_CHECKBOX;
 pub use self::code::BLOCK_CODE;
 pub use self::collapsible::BLOCK_COLLAPSIBLE;
+pub use self::css::BLOCK_CSS;
 pub use self::date::BLOCK_DATE;
 pub use self::del::BLOCK_DEL;
 pub use self::div::BLOCK_DIV;
 pub use self::embed::BLOCK_EMBED;
 pub use self::equation_ref::BLOCK_EQUATION_REF;
 pub use self::footnote::{BLOCK_FOOTNOTE, BLOCK_FOOTNOTE_BLOCK};
+pub use self::gallery::BLOCK_GALLERY;
 pub use self::hidden::BLOCK_HIDDEN;
 pub use self::html::BLOCK_HTML;
+pub use self::if_flags::BLOCK_IF;
 pub use self::ifcategory::BLOCK_IFCATEGORY;
 pub use self::iframe::BLOCK_IFRAME;
 pub use self::iftags::BLOCK_IFTAGS;
@@ -142,6 +152,7 @@ pub use self::lines::BLOCK_LINES;
 pub use self::list::{BLOCK_LI, BLOCK_OL, BLOCK_UL};
 pub use self::mark::BLOCK_MARK;
 pub use self::math::BLOCK_MATH;
+pub use self::media::{BLOCK_AUDIO, BLOCK_VIDEO};
 pub use self::module::BLOCK_MODULE;
 pub use self::monospace::BLOCK_MONOSPACE;
 pub use self::paragraph::BLOCK_PARAGRAPH;
@@ -23,13 +23,15 @@ use once_cell::sync::Lazy;
 use std::collections::HashMap;
 use unicase::UniCase;
 
-pub const MODULE_RULES: [ModuleRule; 6] = [
+pub const MODULE_RULES: [ModuleRule; 8] = [
     MODULE_BACKLINKS,
     MODULE_CATEGORIES,
     MODULE_CSS,
     MODULE_JOIN,
+    MODULE_LIST_PAGES,
     MODULE_PAGE_TREE,
     MODULE_RATE,
+    MODULE_REDIRECT,
 ];
 
 pub type ModuleRuleMap = HashMap<UniCase<&'static str>, &'static ModuleRule>;
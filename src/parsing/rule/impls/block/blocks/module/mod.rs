@@ -68,6 +68,8 @@ impl ModuleRule {
 
         Rule {
             name: self.name,
+            description: "Pseudo rule for a module.",
+            example: "",
             position: LineRequirement::Any,
             try_consume_fn,
         }
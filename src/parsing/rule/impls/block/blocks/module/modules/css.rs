@@ -32,6 +32,7 @@ fn parse_fn<'r, 't>(
     _arguments: Arguments<'t>,
 ) -> ParseResult<'r, 't, ModuleParseOutput<'t>> {
     info!("Parsing categories module");
+    parser.check_capability(GatedElement::Style)?;
     assert_module_name(&MODULE_CSS, name);
 
     let css = parser.get_body_text(&BLOCK_MODULE)?;
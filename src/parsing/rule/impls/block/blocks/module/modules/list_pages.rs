@@ -0,0 +1,247 @@
+/*
+ * parsing/rule/impls/block/blocks/module/modules/list_pages.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::tree::{
+    ListPagesFilter, ListPagesOrder, ListPagesSelector, ListPagesTemplatePart,
+    ModuleListPages,
+};
+use std::borrow::Cow;
+use std::str::FromStr;
+
+pub const MODULE_LIST_PAGES: ModuleRule = ModuleRule {
+    name: "module-list-pages",
+    accepts_names: &["ListPages"],
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    mut arguments: Arguments<'t>,
+) -> ParseResult<'r, 't, ModuleParseOutput<'t>> {
+    info!("Parsing ListPages module");
+    assert_module_name(&MODULE_LIST_PAGES, name);
+
+    let selector = ListPagesSelector {
+        page_type: arguments.get("pagetype"),
+        categories: parse_filter(arguments.get("category")),
+        tags: parse_filter(arguments.get("tags")),
+        parent: arguments.get("parent"),
+        link_to: arguments.get("link_to"),
+        name: arguments.get("name"),
+        created_by: arguments.get("created_by"),
+        created_at: arguments.get("created_at"),
+        updated_at: arguments.get("updated_at"),
+        rating: arguments.get("rating"),
+        votes: arguments.get("votes"),
+    };
+
+    let order = match arguments.get("order") {
+        Some(order) => Some(parse_order(parser, order)?),
+        None => None,
+    };
+
+    let limit = get_number(parser, &mut arguments, "limit")?;
+    let per_page = get_number(parser, &mut arguments, "perPage")?;
+    let offset = get_number(parser, &mut arguments, "offset")?;
+    let separate = arguments.get_bool(parser, "separate")?.unwrap_or(true);
+    let wrapper = arguments.get_bool(parser, "wrapper")?.unwrap_or(true);
+    let prepend_line = arguments.get("prependLine");
+    let append_line = arguments.get("appendLine");
+
+    let body = parser.get_body_text(&BLOCK_MODULE)?;
+    let template = ListPagesTemplatePart::parse(body);
+
+    ok!(false; Module::ListPages(Box::new(ModuleListPages {
+        selector,
+        order,
+        limit,
+        per_page,
+        offset,
+        separate,
+        wrapper,
+        prepend_line,
+        append_line,
+        template,
+    })))
+}
+
+/// Gets a numeric argument, which may be given as `@URL|default`.
+///
+/// On Wikidot, this form reads the value from the page's URL, falling back
+/// to the default. The URL is not known when parsing, so the default is used,
+/// or no value if there isn't one.
+fn get_number<'t, T: FromStr>(
+    parser: &Parser<'_, 't>,
+    arguments: &mut Arguments<'t>,
+    key: &'t str,
+) -> Result<Option<T>, ParseError> {
+    let value = match arguments.get(key) {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+
+    match strip_url_default(&value) {
+        Some(value) => match value.parse() {
+            Ok(value) => Ok(Some(value)),
+            Err(_) => Err(parser.make_err(ParseErrorKind::BlockMalformedArguments)),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Takes the default out of an `@URL|default` value.
+///
+/// Values without the `@URL` prefix are returned as-is,
+/// and `@URL` without a default gives `None`.
+fn strip_url_default(value: &str) -> Option<&str> {
+    let value = value.trim();
+    match value.get(..4) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("@url") => {
+            let rest = value[4..].trim_start();
+            if rest.is_empty() {
+                return None;
+            }
+
+            match rest.strip_prefix('|') {
+                Some(default) if default.trim().is_empty() => None,
+                Some(default) => Some(default.trim()),
+                None => Some(value),
+            }
+        }
+        _ => Some(value),
+    }
+}
+
+/// Splits a list of values separated by whitespace or commas.
+fn split_values(value: Cow<'_, str>) -> Vec<Cow<'_, str>> {
+    fn is_separator(c: char) -> bool {
+        c.is_whitespace() || c == ','
+    }
+
+    match value {
+        Cow::Borrowed(value) => value
+            .split(is_separator)
+            .filter(|item| !item.is_empty())
+            .map(Cow::Borrowed)
+            .collect(),
+        Cow::Owned(value) => value
+            .split(is_separator)
+            .filter(|item| !item.is_empty())
+            .map(|item| Cow::Owned(str!(item)))
+            .collect(),
+    }
+}
+
+/// Removes a one-byte prefix from the value.
+fn strip_prefix(value: Cow<'_, str>) -> Cow<'_, str> {
+    match value {
+        Cow::Borrowed(value) => Cow::Borrowed(&value[1..]),
+        Cow::Owned(mut value) => {
+            value.remove(0);
+            Cow::Owned(value)
+        }
+    }
+}
+
+fn parse_filter(value: Option<Cow<'_, str>>) -> ListPagesFilter<'_> {
+    let mut filter = ListPagesFilter::default();
+
+    for item in value.map(split_values).unwrap_or_default() {
+        let (list, item) = match item.as_bytes()[0] {
+            b'+' => (&mut filter.required, strip_prefix(item)),
+            b'-' => (&mut filter.excluded, strip_prefix(item)),
+            _ => (&mut filter.any, item),
+        };
+
+        if !item.is_empty() {
+            list.push(item);
+        }
+    }
+
+    filter
+}
+
+fn parse_order<'t>(
+    parser: &Parser<'_, 't>,
+    value: Cow<'t, str>,
+) -> Result<ListPagesOrder<'t>, ParseError> {
+    let mut parts = split_values(value).into_iter();
+    let field = match parts.next() {
+        Some(field) => field,
+        None => return Err(parser.make_err(ParseErrorKind::BlockMalformedArguments)),
+    };
+
+    let descending = match parts.next() {
+        None => false,
+        Some(direction) if direction.eq_ignore_ascii_case("asc") => false,
+        Some(direction) if direction.eq_ignore_ascii_case("desc") => true,
+        Some(_) => return Err(parser.make_err(ParseErrorKind::BlockMalformedArguments)),
+    };
+
+    if parts.next().is_some() {
+        return Err(parser.make_err(ParseErrorKind::BlockMalformedArguments));
+    }
+
+    Ok(ListPagesOrder { field, descending })
+}
+
+#[test]
+fn filter() {
+    let filter = parse_filter(Some(cow!("apple +banana, -cherry - durian")));
+    assert_eq!(filter.any, vec![cow!("apple"), cow!("durian")]);
+    assert_eq!(filter.required, vec![cow!("banana")]);
+    assert_eq!(filter.excluded, vec![cow!("cherry")]);
+    assert!(parse_filter(None).is_empty());
+}
+
+#[test]
+fn url_default() {
+    assert_eq!(strip_url_default("10"), Some("10"));
+    assert_eq!(strip_url_default("@URL|10"), Some("10"));
+    assert_eq!(strip_url_default("@url | 5"), Some("5"));
+    assert_eq!(strip_url_default("@URL"), None);
+    assert_eq!(strip_url_default("@URL|"), None);
+    assert_eq!(strip_url_default("@URLx"), Some("@URLx"));
+}
+
+#[test]
+fn url_arguments() {
+    use crate::data::PageInfo;
+    use crate::settings::{WikitextMode, WikitextSettings};
+    use crate::tree::Element;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let text = "[[module ListPages limit=\"@URL|10\" perPage=\"@URL\" offset=\"2\"]]\n%%title%%\n[[/module]]";
+    let tokens = crate::tokenize(text);
+    let (tree, errors) = crate::parse(&tokens, &page_info, &settings).into();
+    assert!(errors.is_empty(), "Errors produced: {errors:?}");
+
+    match tree.elements.first() {
+        Some(Element::Module(Module::ListPages(list_pages))) => {
+            assert_eq!(list_pages.limit.map(|n| n.get()), Some(10));
+            assert_eq!(list_pages.per_page, None);
+            assert_eq!(list_pages.offset, Some(2));
+        }
+        element => panic!("Unexpected element: {element:?}"),
+    }
+}
@@ -32,12 +32,16 @@ mod backlinks;
 mod categories;
 mod css;
 mod join;
+mod list_pages;
 mod page_tree;
 mod rate;
+mod redirect;
 
 pub use self::backlinks::MODULE_BACKLINKS;
 pub use self::categories::MODULE_CATEGORIES;
 pub use self::css::MODULE_CSS;
 pub use self::join::MODULE_JOIN;
+pub use self::list_pages::MODULE_LIST_PAGES;
 pub use self::page_tree::MODULE_PAGE_TREE;
 pub use self::rate::MODULE_RATE;
+pub use self::redirect::MODULE_REDIRECT;
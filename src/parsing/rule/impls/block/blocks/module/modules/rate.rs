@@ -31,7 +31,7 @@ fn parse_fn<'r, 't>(
     name: &'t str,
     _arguments: Arguments<'t>,
 ) -> ParseResult<'r, 't, ModuleParseOutput<'t>> {
-    info!("Parsing categories module");
+    info!("Parsing rate module");
     assert_module_name(&MODULE_RATE, name);
     ok!(false; Module::Rate)
 }
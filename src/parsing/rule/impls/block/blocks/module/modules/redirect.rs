@@ -0,0 +1,44 @@
+/*
+ * parsing/rule/impls/block/blocks/module/modules/redirect.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::tree::LinkLocation;
+
+pub const MODULE_REDIRECT: ModuleRule = ModuleRule {
+    name: "module-redirect",
+    accepts_names: &["Redirect"],
+    parse_fn,
+};
+
+fn parse_fn<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    name: &'t str,
+    mut arguments: Arguments<'t>,
+) -> ParseResult<'r, 't, ModuleParseOutput<'t>> {
+    info!("Parsing redirect module");
+    assert_module_name(&MODULE_REDIRECT, name);
+
+    let destination = match arguments.get("destination") {
+        Some(destination) => LinkLocation::parse(destination),
+        None => return Err(parser.make_err(ParseErrorKind::BlockMissingArguments)),
+    };
+
+    ok!(false; Element::Redirect { destination })
+}
@@ -27,6 +27,8 @@ pub const BLOCK_MODULE: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::ValueMap,
+    body: BlockBody::Other,
     parse_fn,
 };
 
@@ -38,7 +40,7 @@ fn parse_fn<'r, 't>(
     in_head: bool,
 ) -> ParseResult<'r, 't, Elements<'t>> {
     info!("Parsing module block (in-head {in_head})");
-    parser.check_page_syntax()?;
+    parser.check_capability(GatedElement::Module)?;
     assert!(!flag_star, "Module doesn't allow star flag");
     assert!(!flag_score, "Module doesn't allow score flag");
     assert_block_name(&BLOCK_MODULE, name);
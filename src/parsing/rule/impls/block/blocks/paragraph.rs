@@ -27,6 +27,8 @@ pub const BLOCK_PARAGRAPH: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::Map,
+    body: BlockBody::Elements,
     parse_fn,
 };
 
@@ -44,7 +46,7 @@ fn parse_fn<'r, 't>(
 
     // Gather paragraphs
     let arguments = parser.get_head_map(&BLOCK_PARAGRAPH, in_head)?;
-    let attributes = arguments.to_attribute_map(parser.settings());
+    let attributes = arguments.to_attribute_map(parser);
     let (mut elements, errors, _) =
         parser.get_body_elements(&BLOCK_PARAGRAPH, true)?.into();
 
@@ -26,6 +26,8 @@ pub const BLOCK_RADIO: BlockRule = BlockRule {
     accepts_star: true,
     accepts_score: false,
     accepts_newlines: false,
+    head: BlockHead::ValueMap,
+    body: BlockBody::None,
     parse_fn,
 };
 
@@ -42,13 +44,22 @@ fn parse_fn<'r, 't>(
     assert!(!flag_score, "Radio buttons don't allow score flag");
     assert_block_name(&BLOCK_RADIO, name);
 
-    let (name, arguments) = parser.get_head_name_map(&BLOCK_RADIO, in_head)?;
+    let start = parser.current().span.start;
+    let (name, mut arguments) = parser.get_head_name_map(&BLOCK_RADIO, in_head)?;
     parser.get_optional_space()?;
 
+    // Only one button in each group may start checked
+    if flag_star {
+        parser.add_checked_radio_name(cow!(name), start);
+    }
+
+    let label = arguments.get("label");
+
     let element = Element::RadioButton {
         name: cow!(name),
         checked: flag_star,
-        attributes: arguments.to_attribute_map(parser.settings()),
+        label,
+        attributes: arguments.to_attribute_map(parser),
     };
 
     ok!(element)
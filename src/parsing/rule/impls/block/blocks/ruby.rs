@@ -29,6 +29,8 @@ pub const BLOCK_RUBY: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::Map,
+    body: BlockBody::Elements,
     parse_fn: parse_block,
 };
 
@@ -38,6 +40,8 @@ pub const BLOCK_RT: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::Map,
+    body: BlockBody::Elements,
     parse_fn: parse_text,
 };
 
@@ -47,6 +51,8 @@ pub const BLOCK_RB: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: false,
+    head: BlockHead::Value,
+    body: BlockBody::None,
     parse_fn: parse_shortcut,
 };
 
@@ -113,7 +119,7 @@ fn parse_block<'r, 't>(
     let element = Element::Container(Container::new(
         ContainerType::Ruby,
         elements,
-        arguments.to_attribute_map(parser.settings()),
+        arguments.to_attribute_map(parser),
     ));
 
     ok!(paragraph_safe; element, errors)
@@ -143,7 +149,7 @@ fn parse_text<'r, 't>(
 
     let element = Element::Partial(PartialElement::RubyText(RubyText {
         elements,
-        attributes: arguments.to_attribute_map(parser.settings()),
+        attributes: arguments.to_attribute_map(parser),
     }));
 
     ok!(paragraph_safe; element, errors)
@@ -19,8 +19,7 @@
  */
 
 use super::prelude::*;
-use crate::tree::AttributeMap;
-use std::borrow::Cow;
+use crate::settings::SizeCheck;
 
 pub const BLOCK_SIZE: BlockRule = BlockRule {
     name: "block-size",
@@ -28,6 +27,8 @@ pub const BLOCK_SIZE: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: false,
+    head: BlockHead::Value,
+    body: BlockBody::Elements,
     parse_fn,
 };
 
@@ -45,22 +46,31 @@ fn parse_fn<'r, 't>(
 
     let size =
         parser.get_head_value(&BLOCK_SIZE, in_head, |parser, value| match value {
-            Some(size) => Ok(format!("font-size: {size};")),
+            Some(size) => Ok(size),
             None => Err(parser.make_err(ParseErrorKind::BlockMissingArguments)),
         })?;
 
+    // Check the size, the value is kept as-is and limited when rendering
+    match parser.settings().size_limits.check(size) {
+        SizeCheck::Valid => (),
+        SizeCheck::OutOfRange => {
+            warn!("Size '{size}' is outside of the configured limits");
+            parser.push_warning(ParseErrorKind::SizeOutOfRange);
+        }
+        SizeCheck::Invalid => {
+            warn!("Size '{size}' is not valid, it will be ignored");
+            parser.push_warning(ParseErrorKind::InvalidSize);
+        }
+    }
+
     // Get body content, without paragraphs
     let (elements, errors, paragraph_safe) =
         parser.get_body_elements(&BLOCK_SIZE, false)?.into();
 
-    let attributes = {
-        let mut map = AttributeMap::new();
-        map.insert("style", Cow::Owned(size));
-        map
+    let element = Element::Size {
+        size: cow!(size.trim()),
+        elements,
     };
 
-    let element =
-        Element::Container(Container::new(ContainerType::Size, elements, attributes));
-
     ok!(paragraph_safe; element, errors)
 }
@@ -31,6 +31,8 @@ pub const BLOCK_TABLE: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::Map,
+    body: BlockBody::Elements,
     parse_fn: parse_table,
 };
 
@@ -40,6 +42,8 @@ pub const BLOCK_TABLE_ROW: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::Map,
+    body: BlockBody::Elements,
     parse_fn: parse_row,
 };
 
@@ -49,6 +53,8 @@ pub const BLOCK_TABLE_CELL_REGULAR: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::Map,
+    body: BlockBody::Elements,
     parse_fn: parse_cell_regular,
 };
 
@@ -58,6 +64,8 @@ pub const BLOCK_TABLE_CELL_HEADER: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::Map,
+    body: BlockBody::Elements,
     parse_fn: parse_cell_header,
 };
 
@@ -95,7 +103,7 @@ where
 
     // Get attributes
     let arguments = parser.get_head_map(block_rule, in_head)?;
-    let attributes = arguments.to_attribute_map(parser.settings());
+    let attributes = arguments.to_attribute_map(parser);
 
     // Get body elements
     let (elements, errors, _) = parser.get_body_elements(block_rule, false)?.into();
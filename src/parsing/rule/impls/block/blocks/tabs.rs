@@ -28,6 +28,8 @@ pub const BLOCK_TABVIEW: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::None,
+    body: BlockBody::Elements,
     parse_fn: parse_tabview,
 };
 
@@ -37,6 +39,8 @@ pub const BLOCK_TAB: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::Value,
+    body: BlockBody::Elements,
     parse_fn: parse_tab,
 };
 
@@ -28,6 +28,8 @@ pub const BLOCK_TARGET: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::Value,
+    body: BlockBody::None,
     parse_fn,
 };
 
@@ -43,6 +45,7 @@ fn parse_fn<'r, 't>(
     assert!(!flag_score, "Target doesn't allow score flag");
     assert_block_name(&BLOCK_TARGET, name);
 
+    let start = parser.current().span.start;
     let name =
         parser.get_head_value(&BLOCK_TARGET, in_head, |parser, value| match value {
             Some(name) => Ok(name.trim()),
@@ -61,5 +64,6 @@ fn parse_fn<'r, 't>(
         cow!(name)
     };
 
+    parser.add_anchor_name(name.clone(), start);
     ok!(Element::AnchorName(name))
 }
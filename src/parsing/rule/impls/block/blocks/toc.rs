@@ -27,6 +27,8 @@ pub const BLOCK_TABLE_OF_CONTENTS: BlockRule = BlockRule {
     accepts_star: false,
     accepts_score: false,
     accepts_newlines: true,
+    head: BlockHead::Map,
+    body: BlockBody::None,
     parse_fn,
 };
 
@@ -38,14 +40,35 @@ fn parse_fn<'r, 't>(
     in_head: bool,
 ) -> ParseResult<'r, 't, Elements<'t>> {
     info!("Parsing table-of-contents block (name '{name}', in-head {in_head})");
-    parser.check_page_syntax()?;
+    parser.check_capability(GatedElement::TableOfContents)?;
     assert!(!flag_star, "Table of Contents doesn't allow star flag");
     assert!(!flag_score, "Table of Contents doesn't allow score flag");
     assert_block_name(&BLOCK_TABLE_OF_CONTENTS, name);
 
-    let arguments = parser.get_head_map(&BLOCK_TABLE_OF_CONTENTS, in_head)?;
-    let attributes = arguments.to_attribute_map(parser.settings());
+    let mut arguments = parser.get_head_map(&BLOCK_TABLE_OF_CONTENTS, in_head)?;
+    let min_depth = arguments.get_value(parser, "minDepth")?;
+    let max_depth = arguments.get_value(parser, "maxDepth")?;
+
+    // Depths are heading levels, so they must be within 1 to 6
+    for depth in [min_depth, max_depth].into_iter().flatten() {
+        if !(1..=6).contains(&depth) {
+            return Err(parser.make_err(ParseErrorKind::BlockMalformedArguments));
+        }
+    }
+
+    if let (Some(min_depth), Some(max_depth)) = (min_depth, max_depth) {
+        if min_depth > max_depth {
+            return Err(parser.make_err(ParseErrorKind::BlockMalformedArguments));
+        }
+    }
+
+    let attributes = arguments.to_attribute_map(parser);
     let align = FloatAlignment::parse(name).map(|float| float.align);
-    let element = Element::TableOfContents { align, attributes };
+    let element = Element::TableOfContents {
+        align,
+        attributes,
+        min_depth,
+        max_depth,
+    };
     ok!(false; element)
 }
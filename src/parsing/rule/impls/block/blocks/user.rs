@@ -26,6 +26,8 @@ pub const BLOCK_USER: BlockRule = BlockRule {
     accepts_star: true,
     accepts_score: false,
     accepts_newlines: false,
+    head: BlockHead::Value,
+    body: BlockBody::None,
     parse_fn,
 };
 
@@ -23,20 +23,23 @@ use once_cell::sync::Lazy;
 use std::collections::HashMap;
 use unicase::UniCase;
 
-pub const BLOCK_RULES: [BlockRule; 60] = [
+pub const BLOCK_RULES: [BlockRule; 66] = [
     BLOCK_ALIGN_CENTER,
     BLOCK_ALIGN_JUSTIFY,
     BLOCK_ALIGN_LEFT,
     BLOCK_ALIGN_RIGHT,
     BLOCK_ANCHOR,
+    BLOCK_AUDIO,
     BLOCK_BIBCITE,
     BLOCK_BIBLIOGRAPHY,
     BLOCK_BLOCKQUOTE,
     BLOCK_BOLD,
+    BLOCK_CALC,
     BLOCK_CHAR,
     BLOCK_CHECKBOX,
     BLOCK_CODE,
     BLOCK_COLLAPSIBLE,
+    BLOCK_CSS,
     BLOCK_DATE,
     BLOCK_DEL,
     BLOCK_DIV,
@@ -44,8 +47,10 @@ pub const BLOCK_RULES: [BlockRule; 60] = [
     BLOCK_EQUATION_REF,
     BLOCK_FOOTNOTE,
     BLOCK_FOOTNOTE_BLOCK,
+    BLOCK_GALLERY,
     BLOCK_HIDDEN,
     BLOCK_HTML,
+    BLOCK_IF,
     BLOCK_IFCATEGORY,
     BLOCK_IFRAME,
     BLOCK_IFTAGS,
@@ -84,6 +89,7 @@ pub const BLOCK_RULES: [BlockRule; 60] = [
     BLOCK_UL,
     BLOCK_UNDERLINE,
     BLOCK_USER,
+    BLOCK_VIDEO,
 ];
 
 pub type BlockRuleMap = HashMap<UniCase<&'static str>, &'static BlockRule>;
@@ -38,6 +38,7 @@ mod rule;
 pub mod blocks;
 
 pub use self::arguments::Arguments;
+pub use self::mapping::BLOCK_RULES;
 pub use self::rule::{RULE_BLOCK, RULE_BLOCK_SKIP_NEWLINE, RULE_BLOCK_STAR};
 
 /// Define a rule for how to parse a block.
@@ -84,11 +85,52 @@ pub struct BlockRule {
     /// ```
     accepts_newlines: bool,
 
+    /// What kind of arguments this block takes in its head.
+    head: BlockHead,
+
+    /// What kind of contents this block has between its head and tail.
+    body: BlockBody,
+
     /// Function which implements the processing for this rule.
     parse_fn: BlockParseFn,
 }
 
 impl BlockRule {
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    #[inline]
+    pub fn accepts_names(&self) -> &'static [&'static str] {
+        self.accepts_names
+    }
+
+    #[inline]
+    pub fn accepts_star(&self) -> bool {
+        self.accepts_star
+    }
+
+    #[inline]
+    pub fn accepts_score(&self) -> bool {
+        self.accepts_score
+    }
+
+    #[inline]
+    pub fn accepts_newlines(&self) -> bool {
+        self.accepts_newlines
+    }
+
+    #[inline]
+    pub fn head(&self) -> BlockHead {
+        self.head
+    }
+
+    #[inline]
+    pub fn body(&self) -> BlockBody {
+        self.body
+    }
+
     /// Produces a pseudo parse `Rule` associated with this `BlockRule`.
     ///
     /// It should not be invoked, it is for error construction.
@@ -103,6 +145,8 @@ impl BlockRule {
 
         Rule {
             name: self.name,
+            description: "Pseudo rule for a block.",
+            example: "",
             position: LineRequirement::Any,
             try_consume_fn,
         }
@@ -117,11 +161,52 @@ impl Debug for BlockRule {
             .field("accepts_star", &self.accepts_star)
             .field("accepts_score", &self.accepts_score)
             .field("accepts_newlines", &self.accepts_newlines)
+            .field("head", &self.head)
+            .field("body", &self.body)
             .field("parse_fn", &(self.parse_fn as *const ()))
             .finish()
     }
 }
 
+/// What a block accepts in its head, such as `[[span class="x"]]`.
+///
+/// This is the same as the `head` field in `conf/blocks.toml`.
+#[derive(Serialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum BlockHead {
+    /// No arguments, e.g. `[[tabview]]`.
+    None,
+
+    /// A single value, e.g. `[[size 80%]]`.
+    Value,
+
+    /// A map of arguments, e.g. `[[div class="block"]]`.
+    Map,
+
+    /// A value followed by a map of arguments, e.g. `[[image a.png width="100"]]`.
+    #[serde(rename = "value+map")]
+    ValueMap,
+}
+
+/// What a block contains, between its head and its closing tail.
+///
+/// This is the same as the `body` field in `conf/blocks.toml`.
+#[derive(Serialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum BlockBody {
+    /// The block has no body or tail, e.g. `[[user aismallard]]`.
+    None,
+
+    /// The body is kept as raw text, e.g. `[[code]]`.
+    Raw,
+
+    /// The body is parsed as elements, e.g. `[[div]]`.
+    Elements,
+
+    /// The body has its own special syntax, e.g. `[[gallery]]`.
+    Other,
+}
+
 /// Function pointer type to implement block parsing.
 ///
 /// The arguments are, in order:
@@ -23,18 +23,24 @@ use super::mapping::get_block_rule_with_name;
 
 pub const RULE_BLOCK: Rule = Rule {
     name: "block",
+    description: "A block, such as a div or image.",
+    example: "[[div]]contents[[/div]]",
     position: LineRequirement::Any,
     try_consume_fn: block_regular,
 };
 
 pub const RULE_BLOCK_STAR: Rule = Rule {
     name: "block-star",
+    description: "A block invoked with the star flag.",
+    example: "[[*user name]]",
     position: LineRequirement::Any,
     try_consume_fn: block_star,
 };
 
 pub const RULE_BLOCK_SKIP_NEWLINE: Rule = Rule {
     name: "block-skip",
+    description: "Consumes the newline before a block which accepts newlines.",
+    example: "",
     position: LineRequirement::Any, // this rule happens *on* a newline, not after one
     try_consume_fn: block_skip,
 };
@@ -100,6 +106,9 @@ where
         RULE_BLOCK
     });
 
+    // Save start position, in case this isn't really a block
+    let start = parser.current();
+
     // Get block name
     parser.get_optional_space()?;
 
@@ -114,7 +123,15 @@ where
     // Get the block rule for this name
     let block = match get_block_rule_with_name(name) {
         Some(block) => block,
-        None => return Err(parser.make_err(ParseErrorKind::NoSuchBlock)),
+        None => {
+            if parser.settings().literal_unknown_blocks {
+                if let Some(element) = parse_literal(parser, start, name, in_head)? {
+                    return ok!(element);
+                }
+            }
+
+            return Err(parser.make_err(ParseErrorKind::NoSuchBlock));
+        }
     };
 
     // Set block rule for better errors
@@ -139,3 +156,97 @@ where
     // then processing the body (if any) and tail block.
     (block.parse_fn)(parser, name, flag_star, flag_score, in_head)
 }
+
+/// Attempts to interpret an unknown block as literal text instead.
+///
+/// Prose like `[[citation needed]]` or `[[2,3]]` begins like a block,
+/// but isn't meant to be one. If the bracketed text doesn't resemble
+/// a block invocation, it is returned as-is rather than producing errors.
+///
+/// Text resembles a block if it has arguments (`key="value"`), spans
+/// multiple lines, or is a single word containing letters, since that
+/// could be a misspelled block name.
+fn parse_literal<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+    start: &'r ExtractedToken<'t>,
+    name: &'t str,
+    in_head: bool,
+) -> Result<Option<Element<'t>>, ParseError> {
+    debug!("Checking if unknown block '{name}' is literal text");
+
+    // Names like "f=image" are malformed blocks, not prose
+    if name.contains('=') {
+        debug!("Block name contains '=', not literal");
+        return Ok(None);
+    }
+
+    // Without a head, the block name was the only contents
+    if !in_head {
+        if name.chars().any(|ch| ch.is_ascii_alphabetic()) {
+            debug!("Single word block name, could be a misspelling");
+            return Ok(None);
+        }
+
+        return Ok(Some(literal_text(parser, start)));
+    }
+
+    // Otherwise, check the rest of the head
+    let mut words = 1;
+    let mut in_whitespace = true;
+    loop {
+        let current = parser.current();
+        match current.token {
+            Token::RightBlock => {
+                parser.step()?;
+                break;
+            }
+            Token::Whitespace => in_whitespace = true,
+            Token::Equals
+            | Token::String
+            | Token::LineBreak
+            | Token::ParagraphBreak
+            | Token::InputEnd
+            | Token::LeftBlock
+            | Token::LeftBlockEnd
+            | Token::LeftBlockStar
+            | Token::LeftBlockAnchor => {
+                debug!(
+                    "Found block-like token {}, not literal",
+                    current.token.name()
+                );
+                return Ok(None);
+            }
+            _ => {
+                if in_whitespace {
+                    words += 1;
+                    in_whitespace = false;
+                }
+            }
+        }
+
+        parser.step()?;
+    }
+
+    if words < 2 {
+        debug!("Block head has no further words, not literal");
+        return Ok(None);
+    }
+
+    let element = literal_text(parser, start);
+    if matches!(element, Element::Text(ref text) if text.contains('\n')) {
+        debug!("Block name was followed by a newline, not literal");
+        return Ok(None);
+    }
+
+    Ok(Some(element))
+}
+
+fn literal_text<'r, 't>(
+    parser: &Parser<'r, 't>,
+    start: &'r ExtractedToken<'t>,
+) -> Element<'t> {
+    let end = parser.current();
+    let slice = parser.full_text().slice_partial(start, end);
+    info!("Treating unknown block as literal text '{slice}'");
+    Element::Text(cow!(slice))
+}
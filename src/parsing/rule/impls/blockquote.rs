@@ -18,19 +18,35 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+use super::list::{build_list_element, get_list_type, MAX_LIST_DEPTH};
 use super::prelude::*;
 use crate::parsing::paragraph::ParagraphStack;
 use crate::parsing::{process_depths, DepthItem, DepthList};
-use crate::tree::{AttributeMap, Container, ContainerType};
+use crate::tree::{AttributeMap, Container, ContainerType, ListType};
 
 const MAX_BLOCKQUOTE_DEPTH: usize = 30;
 
 pub const RULE_BLOCKQUOTE: Rule = Rule {
     name: "blockquote",
+    description: "A blockquote, nested by the number of angle brackets.",
+    example: "> quoted text",
     position: LineRequirement::StartOfLine,
     try_consume_fn,
 };
 
+/// The contents of a line (or run of lines) within a native blockquote.
+#[derive(Debug)]
+enum QuoteLine<'t> {
+    /// Inline elements, which may be joined into a paragraph.
+    Text {
+        elements: Vec<Element<'t>>,
+        paragraph_safe: bool,
+    },
+
+    /// Consecutive list item lines at the same blockquote depth.
+    List(Vec<(usize, ListType, Vec<Element<'t>>)>),
+}
+
 fn try_consume_fn<'r, 't>(
     parser: &mut Parser<'r, 't>,
 ) -> ParseResult<'r, 't, Elements<'t>> {
@@ -54,7 +70,15 @@ fn try_consume_fn<'r, 't>(
             }
         };
         parser.step()?;
-        parser.get_optional_space()?; // allow whitespace after ">"
+
+        // Allow whitespace after ">", which also indents list items
+        let indent = match parser.current() {
+            current if current.token == Token::Whitespace => {
+                parser.step()?;
+                current.slice.len()
+            }
+            _ => 0,
+        };
 
         // Check that the depth isn't obscenely deep, to avoid DOS attacks via stack overflow.
         if depth > MAX_BLOCKQUOTE_DEPTH {
@@ -62,31 +86,80 @@ fn try_consume_fn<'r, 't>(
             return Err(parser.make_err(ParseErrorKind::BlockquoteDepthExceeded));
         }
 
-        // Parse elements until we hit the end of the line
-        let mut paragraph_safe = true;
-        let mut elements = collect_consume(
-            parser,
-            RULE_BLOCKQUOTE,
-            &[
-                ParseCondition::current(Token::LineBreak),
-                ParseCondition::current(Token::ParagraphBreak),
-                ParseCondition::current(Token::InputEnd),
-            ],
-            &[],
-            None,
-        )?
-        .chain(&mut errors, &mut paragraph_safe);
-
-        // Add a line break for the end of the line
-        elements.push(Element::LineBreak);
-
-        // Append blockquote line
-        //
         // Depth lists expect zero-based list depths, but tokens are one-based.
         // So, we subtract one.
         //
         // This will not overflow because Token::Quote requires at least one ">".
-        depths.push((depth - 1, (), (elements, paragraph_safe)))
+        let depth = depth - 1;
+
+        // Check if this line is a list item, such as "> * item"
+        let list_type = match parser.next_two_tokens() {
+            (token, Some(Token::Whitespace)) => get_list_type(token),
+            _ => None,
+        };
+
+        // Parse elements until we hit the end of the line
+        let mut paragraph_safe = true;
+        let consume_line = |parser: &mut Parser<'r, 't>,
+                            errors: &mut Vec<ParseError>,
+                            paragraph_safe: &mut bool| {
+            collect_consume(
+                parser,
+                RULE_BLOCKQUOTE,
+                &[
+                    ParseCondition::current(Token::LineBreak),
+                    ParseCondition::current(Token::ParagraphBreak),
+                    ParseCondition::current(Token::InputEnd),
+                ],
+                &[],
+                None,
+            )
+            .map(|success| success.chain(errors, paragraph_safe))
+        };
+
+        match list_type {
+            Some(list_type) => {
+                // The first space only separates the item from the ">"
+                let list_depth = indent.saturating_sub(1);
+                if list_depth > MAX_LIST_DEPTH {
+                    warn!("List item has a depth {list_depth} greater than the maximum ({MAX_LIST_DEPTH})! Failing");
+                    return Err(parser.make_err(ParseErrorKind::ListDepthExceeded));
+                }
+
+                // Skip the bullet and its following space
+                parser.step_n(2)?;
+
+                let elements = consume_line(parser, &mut errors, &mut paragraph_safe)?;
+                let item = (list_depth, list_type, elements);
+
+                // Continue the list from the previous line, if at the same depth
+                match depths.last_mut() {
+                    Some((last_depth, (), QuoteLine::List(items)))
+                        if *last_depth == depth =>
+                    {
+                        items.push(item);
+                    }
+                    _ => depths.push((depth, (), QuoteLine::List(vec![item]))),
+                }
+            }
+            None => {
+                let mut elements =
+                    consume_line(parser, &mut errors, &mut paragraph_safe)?;
+
+                // Add a line break for the end of the line
+                elements.push(Element::LineBreak);
+
+                // Append blockquote line
+                depths.push((
+                    depth,
+                    (),
+                    QuoteLine::Text {
+                        elements,
+                        paragraph_safe,
+                    },
+                ));
+            }
+        }
     }
 
     // This blockquote has no rows, so the rule fails
@@ -103,17 +176,28 @@ fn try_consume_fn<'r, 't>(
     ok!(false; elements, errors)
 }
 
-fn build_blockquote_element(list: DepthList<(), (Vec<Element>, bool)>) -> Element {
+fn build_blockquote_element(list: DepthList<(), QuoteLine>) -> Element {
     let mut stack = ParagraphStack::new();
 
     // Convert depth list into a list of elements
     for item in list {
         match item {
-            DepthItem::Item((elements, paragraph_safe)) => {
+            DepthItem::Item(QuoteLine::Text {
+                elements,
+                paragraph_safe,
+            }) => {
                 for element in elements {
                     stack.push_element(element, paragraph_safe);
                 }
             }
+            DepthItem::Item(QuoteLine::List(items)) => {
+                stack.pop_line_break();
+
+                for (ltype, depth_list) in process_depths(ListType::Generic, items) {
+                    let list = build_list_element(ltype, depth_list);
+                    stack.push_element(list, false);
+                }
+            }
             DepthItem::List(_, list) => {
                 let blockquote = build_blockquote_element(list);
                 stack.pop_line_break();
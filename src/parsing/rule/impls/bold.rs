@@ -22,6 +22,8 @@ use super::prelude::*;
 
 pub const RULE_BOLD: Rule = Rule {
     name: "bold",
+    description: "Bold text.",
+    example: "**bold**",
     position: LineRequirement::Any,
     try_consume_fn,
 };
@@ -23,6 +23,8 @@ use crate::tree::Alignment;
 
 pub const RULE_CENTER: Rule = Rule {
     name: "center",
+    description: "A centered line of text.",
+    example: "= centered text",
     position: LineRequirement::StartOfLine,
     try_consume_fn,
 };
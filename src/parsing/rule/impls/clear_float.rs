@@ -23,6 +23,8 @@ use crate::tree::ClearFloat;
 
 pub const RULE_CLEAR_FLOAT: Rule = Rule {
     name: "clear-float",
+    description: "Clears floating elements on one or both sides.",
+    example: "~~~~",
     position: LineRequirement::StartOfLine,
     try_consume_fn,
 };
@@ -28,6 +28,8 @@ static HEX_COLOR: Lazy<Regex> =
 
 pub const RULE_COLOR: Rule = Rule {
     name: "color",
+    description: "Colored text, using a color name or hex code.",
+    example: "##blue|colored text##",
     position: LineRequirement::Any,
     try_consume_fn,
 };
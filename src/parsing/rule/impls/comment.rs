@@ -22,6 +22,8 @@ use super::prelude::*;
 
 pub const RULE_COMMENT: Rule = Rule {
     name: "comment",
+    description: "A comment, which is not rendered.",
+    example: "[!-- comment --]",
     position: LineRequirement::Any,
     try_consume_fn,
 };
@@ -22,13 +22,20 @@ use super::prelude::*;
 
 pub const RULE_DASH: Rule = Rule {
     name: "dash",
+    description: "An em dash.",
+    example: "a -- b",
     position: LineRequirement::Any,
     try_consume_fn,
 };
 
 fn try_consume_fn<'r, 't>(
-    _parser: &mut Parser<'r, 't>,
+    parser: &mut Parser<'r, 't>,
 ) -> ParseResult<'r, 't, Elements<'t>> {
+    if !parser.settings().typography.dashes {
+        info!("Em dash conversion disabled, consuming token as text");
+        return ok!(text!(parser.current().slice));
+    }
+
     info!("Consuming token to create an em dash");
 
     // — - EM DASH
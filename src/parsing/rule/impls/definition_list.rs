@@ -24,12 +24,16 @@ use crate::tree::DefinitionListItem;
 
 pub const RULE_DEFINITION_LIST: Rule = Rule {
     name: "definition-list",
+    description: "A definition list, pairing each term with its definition.",
+    example: ": term : definition",
     position: LineRequirement::StartOfLine,
     try_consume_fn: parse_definition_list,
 };
 
 pub const RULE_DEFINITION_LIST_SKIP_NEWLINE: Rule = Rule {
     name: "definition-list-skip-newline",
+    description: "Consumes the newline between definition list items.",
+    example: "",
     position: LineRequirement::Any,
     try_consume_fn: skip_newline,
 };
@@ -22,6 +22,8 @@ use super::prelude::*;
 
 pub const RULE_DOUBLE_ANGLE: Rule = Rule {
     name: "double-angle",
+    description: "A pair of double angle quotation marks.",
+    example: "<<quoted>>",
     position: LineRequirement::Any,
     try_consume_fn,
 };
@@ -35,7 +37,15 @@ fn try_consume_fn<'r, 't>(
         current.token.name(),
     );
 
+    let guillemets = parser.settings().typography.guillemets;
+
     match current.token {
+        // Conversion disabled, keep the original characters
+        Token::LeftDoubleAngle if !guillemets => ok!(text!(current.slice)),
+        Token::Quote if current.slice == ">>" && !guillemets => {
+            ok!(text!(current.slice))
+        }
+
         // « - LEFT-POINTING DOUBLE ANGLE QUOTATION MARK
         Token::LeftDoubleAngle => ok!(text!("\u{0ab}")),
 
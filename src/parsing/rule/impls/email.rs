@@ -22,6 +22,8 @@ use super::prelude::*;
 
 pub const RULE_EMAIL: Rule = Rule {
     name: "email",
+    description: "A bare email address, which is automatically linked.",
+    example: "user@example.com",
     position: LineRequirement::Any,
     try_consume_fn,
 };
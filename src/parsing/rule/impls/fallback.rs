@@ -22,6 +22,8 @@ use super::prelude::*;
 
 pub const RULE_FALLBACK: Rule = Rule {
     name: "fallback",
+    description: "Interprets a token as text when no other rule matches.",
+    example: "",
     position: LineRequirement::Any,
     try_consume_fn,
 };
@@ -0,0 +1,136 @@
+/*
+ * parsing/rule/impls/forum_quote.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Rule for quoted replies in forum posts.
+//!
+//! These use the form `[quote=username]` ... `[/quote]`, where the
+//! attribution is optional. Unlike `[[quote]]`, which is an alias for
+//! `[[blockquote]]`, this produces an `Element::Quote` so the quoted
+//! author can be shown.
+
+use super::prelude::*;
+use crate::parsing::gather_paragraphs;
+use std::borrow::Cow;
+
+pub const RULE_FORUM_QUOTE: Rule = Rule {
+    name: "forum-quote",
+    description: "A quoted forum reply, with an optional attribution.",
+    example: "[quote=username]quoted text[/quote]",
+    position: LineRequirement::StartOfLine,
+    try_consume_fn,
+};
+
+fn try_consume_fn<'r, 't>(
+    parser: &mut Parser<'r, 't>,
+) -> ParseResult<'r, 't, Elements<'t>> {
+    info!("Trying to create forum quote");
+
+    if !parser.settings().enable_forum_quotes {
+        debug!("Forum quotes are disabled, failing rule");
+        return Err(parser.make_err(ParseErrorKind::RuleFailed));
+    }
+
+    // Parse "[quote" opener
+    parser.get_token(Token::LeftBracket, ParseErrorKind::RuleFailed)?;
+    let name = parser.get_token(Token::Identifier, ParseErrorKind::RuleFailed)?;
+    if !name.eq_ignore_ascii_case("quote") {
+        return Err(parser.make_err(ParseErrorKind::RuleFailed));
+    }
+
+    // Parse optional "=attribution", then "]"
+    let attribution = match parser.current().token {
+        Token::RightBracket => None,
+        Token::Equals => {
+            parser.step()?;
+            parse_attribution(parser)?
+        }
+        _ => return Err(parser.make_err(ParseErrorKind::RuleFailed)),
+    };
+
+    parser.get_token(Token::RightBracket, ParseErrorKind::RuleFailed)?;
+    parser.get_optional_line_break()?;
+
+    // Gather quoted contents until "[/quote]"
+    let (elements, errors, _) = gather_paragraphs(
+        parser,
+        RULE_FORUM_QUOTE,
+        Some(|parser: &mut Parser<'r, 't>| {
+            Ok(parser.save_evaluate_fn(parse_quote_end).is_some())
+        }),
+    )?
+    .into();
+
+    let element = Element::Quote {
+        attribution,
+        elements,
+    };
+
+    ok!(false; element, errors)
+}
+
+/// Collects the attribution up to the closing `]`.
+///
+/// Returns `None` if the attribution is empty.
+fn parse_attribution<'t>(
+    parser: &mut Parser<'_, 't>,
+) -> Result<Option<Cow<'t, str>>, ParseError> {
+    let start = parser.current();
+
+    loop {
+        match parser.current().token {
+            Token::RightBracket => break,
+            Token::LineBreak | Token::ParagraphBreak | Token::InputEnd => {
+                warn!("Hit end of line while parsing forum quote attribution");
+                return Err(parser.make_err(ParseErrorKind::RuleFailed));
+            }
+            _ => {
+                parser.step()?;
+            }
+        }
+    }
+
+    let end = parser.current();
+    let attribution = parser.full_text().slice_partial(start, end).trim();
+
+    if attribution.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(cow!(attribution)))
+    }
+}
+
+/// Checks for a `[/quote]` closer, stepping past it.
+fn parse_quote_end(parser: &mut Parser) -> Result<bool, ParseError> {
+    parser.get_token(Token::LeftBracket, ParseErrorKind::RuleFailed)?;
+
+    let slash = parser.current();
+    if slash.token != Token::Other || slash.slice != "/" {
+        return Ok(false);
+    }
+    parser.step()?;
+
+    let name = parser.get_token(Token::Identifier, ParseErrorKind::RuleFailed)?;
+    if !name.eq_ignore_ascii_case("quote") {
+        return Ok(false);
+    }
+
+    parser.get_token(Token::RightBracket, ParseErrorKind::RuleFailed)?;
+    Ok(true)
+}
@@ -23,6 +23,8 @@ use std::convert::TryInto;
 
 pub const RULE_HEADER: Rule = Rule {
     name: "header",
+    description: "A heading, from level one to six.",
+    example: "+ Heading",
     position: LineRequirement::StartOfLine,
     try_consume_fn,
 };
@@ -45,6 +47,7 @@ fn try_consume_fn<'r, 't>(
     }
 
     // Get header depth
+    let start = parser.current().span.start;
     let heading = step!(Token::Heading)
         .slice
         .try_into()
@@ -76,8 +79,12 @@ fn try_consume_fn<'r, 't>(
             _ => panic!("Collected heading produced a non-single non-container element"),
         };
 
-        // Create table of contents entry with the given level and name.
-        parser.push_table_of_contents_entry(heading.level, elements);
+        // Create table of contents entry with the given level, name, and location.
+        //
+        // The span ends before the line break which terminated the heading.
+        let source = &parser.full_text().inner()[start..parser.current().span.start];
+        let span = start..start + source.trim_end_matches(['\r', '\n']).len();
+        parser.push_table_of_contents_entry(heading.level, elements, span);
     }
 
     // Recursively collect headings until we hit an error.
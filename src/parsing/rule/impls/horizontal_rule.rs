@@ -22,6 +22,8 @@ use super::prelude::*;
 
 pub const RULE_HORIZONTAL_RULE: Rule = Rule {
     name: "horizontal-rule",
+    description: "A horizontal rule.",
+    example: "----",
     position: LineRequirement::StartOfLine,
     try_consume_fn,
 };
@@ -22,6 +22,8 @@ use super::prelude::*;
 
 pub const RULE_ITALICS: Rule = Rule {
     name: "italics",
+    description: "Italic text.",
+    example: "//italics//",
     position: LineRequirement::Any,
     try_consume_fn,
 };
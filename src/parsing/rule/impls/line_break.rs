@@ -22,12 +22,16 @@ use super::prelude::*;
 
 pub const RULE_LINE_BREAK: Rule = Rule {
     name: "line-break",
+    description: "A line break.",
+    example: "line one\nline two",
     position: LineRequirement::Any,
     try_consume_fn: line_break,
 };
 
 pub const RULE_LINE_BREAK_PARAGRAPH: Rule = Rule {
     name: "line-break-paragraph",
+    description: "A paragraph break, where it does not end a paragraph.",
+    example: "",
     position: LineRequirement::Any,
     try_consume_fn: line_break_paragraph,
 };
@@ -30,6 +30,8 @@ use wikidot_normalize::normalize;
 
 pub const RULE_LINK_ANCHOR: Rule = Rule {
     name: "link-anchor",
+    description: "A link to an anchor within the page.",
+    example: "[# Label]",
     position: LineRequirement::Any,
     try_consume_fn,
 };
@@ -30,12 +30,16 @@ use crate::url::is_url;
 
 pub const RULE_LINK_SINGLE: Rule = Rule {
     name: "link-single",
+    description: "A link to a URL, with an optional label.",
+    example: "[https://example.com/ Label]",
     position: LineRequirement::Any,
     try_consume_fn: link,
 };
 
 pub const RULE_LINK_SINGLE_NEW_TAB: Rule = Rule {
     name: "link-single-new-tab",
+    description: "A link to a URL which opens in a new tab.",
+    example: "[*https://example.com/ Label]",
     position: LineRequirement::Any,
     try_consume_fn: link_new_tab,
 };
@@ -34,12 +34,16 @@ use std::borrow::Cow;
 
 pub const RULE_LINK_TRIPLE: Rule = Rule {
     name: "link-triple",
+    description: "A link to a page or URL, with an optional label.",
+    example: "[[[page-name | Label]]]",
     position: LineRequirement::Any,
     try_consume_fn: link,
 };
 
 pub const RULE_LINK_TRIPLE_NEW_TAB: Rule = Rule {
     name: "link-triple-new-tab",
+    description: "A link to a page or URL which opens in a new tab.",
+    example: "[[[*page-name | Label]]]",
     position: LineRequirement::Any,
     try_consume_fn: link_new_tab,
 };
@@ -123,6 +127,8 @@ fn build_same<'r, 't>(
         None => return Err(parser.make_err(ParseErrorKind::RuleFailed)),
     };
 
+    check_page_reference(parser, url, &link);
+
     // Build and return element
     let element = Element::Link {
         ltype,
@@ -176,6 +182,8 @@ fn build_separate<'r, 't>(
         None => return Err(parser.make_err(ParseErrorKind::RuleFailed)),
     };
 
+    check_page_reference(parser, url, &link);
+
     // Build link element
     let element = Element::Link {
         ltype,
@@ -262,3 +270,21 @@ fn test_strip_category() {
     check!(": snippets : redirect", Some("redirect"));
     check!(":", None);
 }
+
+/// Warns if this link was meant to be a page, but the reference is malformed.
+///
+/// The link is still produced, since Wikidot accepts these too.
+fn check_page_reference(parser: &mut Parser, url: &str, link: &LinkLocation) {
+    let valid = match link {
+        LinkLocation::Page(page_ref) => page_ref.is_valid(),
+
+        // Page references which fail to parse, such as "::page",
+        // are passed through as URLs instead.
+        LinkLocation::Url(_) => !url.trim_start_matches('/').starts_with(':'),
+    };
+
+    if !valid {
+        warn!("Invalid page reference in link: '{url}'");
+        parser.push_warning(ParseErrorKind::InvalidPageReference);
+    }
+}
@@ -22,9 +22,9 @@ use super::prelude::*;
 use crate::parsing::{process_depths, DepthItem, DepthList};
 use crate::tree::{AttributeMap, ListItem, ListType};
 
-const MAX_LIST_DEPTH: usize = 20;
+pub(super) const MAX_LIST_DEPTH: usize = 20;
 
-const fn get_list_type(token: Token) -> Option<ListType> {
+pub(super) const fn get_list_type(token: Token) -> Option<ListType> {
     match token {
         Token::BulletItem => Some(ListType::Bullet),
         Token::NumberedItem => Some(ListType::Numbered),
@@ -34,6 +34,8 @@ const fn get_list_type(token: Token) -> Option<ListType> {
 
 pub const RULE_LIST: Rule = Rule {
     name: "list",
+    description: "A bulleted or numbered list.",
+    example: "* item",
     position: LineRequirement::StartOfLine,
     try_consume_fn,
 };
@@ -134,7 +136,7 @@ fn try_consume_fn<'r, 't>(
     ok!(paragraph_safe; elements, errors)
 }
 
-fn build_list_element(
+pub(super) fn build_list_element(
     top_ltype: ListType,
     list: DepthList<ListType, Vec<Element>>,
 ) -> Element {
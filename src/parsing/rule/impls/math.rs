@@ -22,6 +22,8 @@ use super::prelude::*;
 
 pub const RULE_MATH: Rule = Rule {
     name: "math",
+    description: "An inline math expression, written in LaTeX.",
+    example: "[[$ x^2 $]]",
     position: LineRequirement::Any,
     try_consume_fn,
 };
@@ -45,6 +45,7 @@ mod definition_list;
 mod double_angle;
 mod email;
 mod fallback;
+mod forum_quote;
 mod header;
 mod horizontal_rule;
 mod italics;
@@ -70,7 +71,10 @@ mod variable;
 
 pub use self::anchor::RULE_ANCHOR;
 pub use self::bibcite::RULE_BIBCITE;
-pub use self::block::{RULE_BLOCK, RULE_BLOCK_SKIP_NEWLINE, RULE_BLOCK_STAR};
+pub use self::block::{
+    BlockBody, BlockHead, BLOCK_RULES, RULE_BLOCK, RULE_BLOCK_SKIP_NEWLINE,
+    RULE_BLOCK_STAR,
+};
 pub use self::blockquote::RULE_BLOCKQUOTE;
 pub use self::bold::RULE_BOLD;
 pub use self::center::RULE_CENTER;
@@ -84,6 +88,7 @@ pub use self::definition_list::{
 pub use self::double_angle::RULE_DOUBLE_ANGLE;
 pub use self::email::RULE_EMAIL;
 pub use self::fallback::RULE_FALLBACK;
+pub use self::forum_quote::RULE_FORUM_QUOTE;
 pub use self::header::RULE_HEADER;
 pub use self::horizontal_rule::RULE_HORIZONTAL_RULE;
 pub use self::italics::RULE_ITALICS;
@@ -22,6 +22,8 @@ use super::prelude::*;
 
 pub const RULE_MONOSPACE: Rule = Rule {
     name: "monospace",
+    description: "Monospaced text.",
+    example: "{{monospace}}",
     position: LineRequirement::Any,
     try_consume_fn,
 };
@@ -22,6 +22,8 @@ use super::prelude::*;
 
 pub const RULE_NULL: Rule = Rule {
     name: "null",
+    description: "Consumes a token without producing any output.",
+    example: "",
     position: LineRequirement::Any,
     try_consume_fn,
 };
@@ -22,6 +22,8 @@ use super::prelude::*;
 
 pub const RULE_PAGE: Rule = Rule {
     name: "page",
+    description: "The rule for the page as a whole.",
+    example: "",
     position: LineRequirement::Any,
     try_consume_fn,
 };
@@ -19,6 +19,8 @@
  */
 
 use super::prelude::*;
+use crate::entity::split_entities;
+use std::borrow::Cow;
 
 macro_rules! raw {
     ($value:expr) => {
@@ -28,6 +30,8 @@ macro_rules! raw {
 
 pub const RULE_RAW: Rule = Rule {
     name: "raw",
+    description: "Raw text or HTML entities, which are not interpreted as wikitext.",
+    example: "@@**not bold**@@",
     position: LineRequirement::Any,
     try_consume_fn,
 };
@@ -139,10 +143,40 @@ fn try_consume_fn<'r, 't>(
                     let slice = parser.full_text().slice_partial(start, end);
                     parser.step()?;
 
+                    // "@<&mdash;>@" is an HTML entity rather than raw text
+                    if ending_token == Token::RightRaw {
+                        if let Some(names) = split_entities(slice) {
+                            debug!("Raw contains only HTML entities, returning");
+
+                            let elements: Vec<_> = names
+                                .into_iter()
+                                .map(|name| Element::HtmlEntity(cow!(name)))
+                                .collect();
+
+                            return ok!(elements);
+                        }
+                    }
+
                     let element = Element::Raw(cow!(slice));
                     return ok!(element);
                 }
 
+                // "@@ text @>@@" is lexed as "@@", "text @", ">@", "@",
+                // so the closing "@@" overlaps the right raw token.
+                if ending_token == Token::Raw && *token == Token::RightRaw {
+                    let next = parser.look_ahead(0);
+                    if matches!(next, Some(next) if next.token == Token::Other && next.slice == "@")
+                    {
+                        trace!("Reached end of raw within '>@', returning");
+
+                        let slice = parser.full_text().slice_partial(start, end);
+                        parser.step_n(2)?;
+
+                        let element = Element::Raw(Cow::Owned(format!("{slice}>")));
+                        return ok!(element);
+                    }
+                }
+
                 trace!("Wasn't end of raw, continuing");
             }
 
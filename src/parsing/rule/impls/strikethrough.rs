@@ -22,6 +22,8 @@ use super::prelude::*;
 
 pub const RULE_STRIKETHROUGH: Rule = Rule {
     name: "strikethrough",
+    description: "Struck-through text.",
+    example: "--strikethrough--",
     position: LineRequirement::Any,
     try_consume_fn,
 };
@@ -22,6 +22,8 @@ use super::prelude::*;
 
 pub const RULE_SUBSCRIPT: Rule = Rule {
     name: "subscript",
+    description: "Subscript text.",
+    example: ",,subscript,,",
     position: LineRequirement::Any,
     try_consume_fn,
 };
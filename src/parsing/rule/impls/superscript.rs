@@ -22,6 +22,8 @@ use super::prelude::*;
 
 pub const RULE_SUPERSCRIPT: Rule = Rule {
     name: "superscript",
+    description: "Superscript text.",
+    example: "^^superscript^^",
     position: LineRequirement::Any,
     try_consume_fn,
 };
@@ -32,6 +32,8 @@ struct TableCellStart {
 
 pub const RULE_TABLE: Rule = Rule {
     name: "table",
+    description: "A table, with one row per line.",
+    example: "|| cell || cell ||",
     position: LineRequirement::StartOfLine,
     try_consume_fn,
 };
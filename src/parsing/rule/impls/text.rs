@@ -22,6 +22,8 @@ use super::prelude::*;
 
 pub const RULE_TEXT: Rule = Rule {
     name: "text",
+    description: "Plain text.",
+    example: "text",
     position: LineRequirement::Any,
     try_consume_fn,
 };
@@ -22,6 +22,8 @@ use super::prelude::*;
 
 pub const RULE_UNDERLINE: Rule = Rule {
     name: "underline",
+    description: "Underlined text.",
+    example: "__underline__",
     position: LineRequirement::Any,
     try_consume_fn,
 };
@@ -22,6 +22,8 @@ use super::prelude::*;
 
 pub const RULE_UNDERSCORE_LINE_BREAK: Rule = Rule {
     name: "underscore-line-break",
+    description: "A forced line break, using an underscore at the end of a line.",
+    example: "line one _\nline two",
     position: LineRequirement::Any,
     try_consume_fn,
 };
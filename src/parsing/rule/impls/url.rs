@@ -20,9 +20,12 @@
 
 use super::prelude::*;
 use crate::tree::{LinkLabel, LinkLocation, LinkType};
+use crate::url::split_url_suffix;
 
 pub const RULE_URL: Rule = Rule {
     name: "url",
+    description: "A bare URL, which is automatically linked.",
+    example: "https://example.com/",
     position: LineRequirement::Any,
     try_consume_fn,
 };
@@ -32,14 +35,28 @@ fn try_consume_fn<'r, 't>(
 ) -> ParseResult<'r, 't, Elements<'t>> {
     info!("Consuming token as a URL");
     let token = parser.current();
-    let url = cow!(token.slice);
+    if !parser.settings().enable_bare_urls {
+        debug!("Bare URLs disabled in this mode, treating as text");
+        return ok!(text!(token.slice));
+    }
+
+    let (url, suffix) = if parser.settings().trim_url_suffixes {
+        split_url_suffix(token.slice)
+    } else {
+        (token.slice, "")
+    };
 
     let element = Element::Link {
         ltype: LinkType::Direct,
-        link: LinkLocation::Url(url),
+        link: LinkLocation::Url(cow!(url)),
         label: LinkLabel::Url(None),
         target: None,
     };
 
-    ok!(element)
+    if suffix.is_empty() {
+        ok!(element)
+    } else {
+        debug!("Excluding trailing punctuation from URL (suffix '{suffix}')");
+        ok!(vec![element, text!(suffix)])
+    }
 }
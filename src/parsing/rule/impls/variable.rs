@@ -26,6 +26,8 @@ static VARIABLE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{\$(.+)\}").unwr
 
 pub const RULE_VARIABLE: Rule = Rule {
     name: "variable",
+    description: "An include variable, substituted with its value when rendered.",
+    example: "{$name}",
     position: LineRequirement::Any,
     try_consume_fn,
 };
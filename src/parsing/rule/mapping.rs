@@ -34,7 +34,7 @@ use once_cell::sync::Lazy;
 pub static RULE_MAP: Lazy<EnumMap<Token, Vec<Rule>>> = Lazy::new(|| {
     enum_map! {
         // Symbols
-        Token::LeftBracket => vec![RULE_LINK_SINGLE, RULE_TEXT],
+        Token::LeftBracket => vec![RULE_FORUM_QUOTE, RULE_LINK_SINGLE, RULE_TEXT],
         Token::LeftBracketAnchor => vec![RULE_LINK_ANCHOR],
         Token::LeftBracketStar => vec![RULE_LINK_SINGLE_NEW_TAB],
         Token::RightBracket => vec![RULE_TEXT],
@@ -44,7 +44,7 @@ pub static RULE_MAP: Lazy<EnumMap<Token, Vec<Rule>>> = Lazy::new(|| {
         Token::LeftBlockStar => vec![RULE_BLOCK_STAR],
         Token::RightBlock => vec![],
         Token::LeftParentheses => vec![RULE_BIBCITE, RULE_TEXT],
-        Token::RightParentheses => vec![RULE_BIBCITE, RULE_TEXT],
+        Token::RightParentheses => vec![RULE_TEXT],
         Token::LeftMath => vec![RULE_MATH],
         Token::RightMath => vec![],
         Token::DoubleDash => vec![RULE_STRIKETHROUGH, RULE_DASH],
@@ -36,6 +36,14 @@ pub struct Rule {
     /// It must be globally unique.
     name: &'static str,
 
+    /// A short, human-readable description of what this rule parses.
+    description: &'static str,
+
+    /// An example of wikitext this rule parses.
+    ///
+    /// This is empty for internal rules which do not correspond to any syntax.
+    example: &'static str,
+
     /// What requirements this rule needs regarding its position in a line.
     position: LineRequirement,
 
@@ -49,11 +57,30 @@ impl Rule {
         self.name
     }
 
+    #[inline]
+    pub fn description(self) -> &'static str {
+        self.description
+    }
+
+    #[inline]
+    pub fn example(self) -> &'static str {
+        self.example
+    }
+
     #[inline]
     pub fn try_consume<'r, 't>(
         self,
         parser: &mut Parser<'r, 't>,
     ) -> ParseResult<'r, 't, Elements<'t>> {
+        let current = parser.current();
+        enter_span!(
+            "rule",
+            rule = self.name,
+            token = current.token.name(),
+            start = current.span.start,
+            end = current.span.end,
+        );
+
         info!("Trying to consume for parse rule {}", self.name);
 
         // Check that the line position matches what the rule wants.
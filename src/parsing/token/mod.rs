@@ -21,6 +21,8 @@
 #[cfg(test)]
 mod test;
 
+mod plain;
+
 mod lexer {
     // Since pest makes enums automatically that clippy doesn't like
     #![allow(clippy::upper_case_acronyms)]
@@ -32,6 +34,7 @@ mod lexer {
 }
 
 use self::lexer::*;
+use self::plain::{find_plain_runs, lex_plain_run};
 use crate::utf16::Utf16IndexMap;
 use pest::iterators::Pair;
 use pest::Parser;
@@ -169,40 +172,87 @@ pub enum Token {
 
 impl Token {
     /// Extracts all tokens from the given text.
+    ///
+    /// Long runs of plain text are split into tokens directly, and pest is
+    /// used for everything in between. See the `plain` module for details.
+    ///
     /// # Errors
     /// Returns an error if something goes wrong with the parsing process. This will result in the
     /// only [`Token`] being a raw text containing all of the input.
     pub(crate) fn extract_all(text: &str) -> Vec<ExtractedToken> {
         info!("Running lexer on input");
 
-        match TokenLexer::parse(Rule::document, text) {
-            Ok(pairs) => {
-                info!("Lexer produced pairs for processing");
-
-                // Map pairs to tokens, and add a Token::InputStart at the beginning
-                // Pest already adds a Token::InputEnd at the end
-                let start = ExtractedToken {
-                    token: Token::InputStart,
-                    slice: "",
-                    span: 0..0,
-                };
-
-                let mut tokens = vec![start];
-                tokens.extend(pairs.map(Token::convert_pair));
-                tokens
+        let runs = find_plain_runs(text);
+        debug!("Found {} runs of plain text to lex directly", runs.len());
+
+        // Add a Token::InputStart at the beginning
+        let start = ExtractedToken {
+            token: Token::InputStart,
+            slice: "",
+            span: 0..0,
+        };
+
+        let mut tokens = vec![start];
+        let mut offset = 0;
+
+        for run in runs {
+            if let Err(error) = Token::extract_chunk(text, offset..run.start, &mut tokens)
+            {
+                return Token::extract_error(text, error);
             }
-            Err(error) => {
-                // Return all of the input as one big raw text
-                // and log this as an error, since it shouldn't be happening
-
-                error!("Error while lexing input in pest: {error}");
-                vec![ExtractedToken {
-                    token: Token::Other,
-                    slice: text,
-                    span: 0..text.len(),
-                }]
+
+            offset = run.end;
+            lex_plain_run(text, run, &mut tokens);
+        }
+
+        // Pest adds a Token::InputEnd at the end of the final chunk
+        if let Err(error) = Token::extract_chunk(text, offset..text.len(), &mut tokens) {
+            return Token::extract_error(text, error);
+        }
+
+        info!("Lexer produced {} tokens", tokens.len());
+        tokens
+    }
+
+    /// Runs pest over part of the text, adding the tokens it produces.
+    ///
+    /// The [`Token::InputEnd`] is only kept if this is the end of the text.
+    fn extract_chunk<'a>(
+        text: &'a str,
+        range: Range<usize>,
+        tokens: &mut Vec<ExtractedToken<'a>>,
+    ) -> Result<(), pest::error::Error<Rule>> {
+        let is_end = range.end == text.len();
+        let offset = range.start;
+        let pairs = TokenLexer::parse(Rule::document, &text[range])?;
+
+        for pair in pairs {
+            let mut token = Token::convert_pair(pair);
+
+            if token.token == Token::InputEnd && !is_end {
+                continue;
             }
+
+            token.span = token.span.start + offset..token.span.end + offset;
+            tokens.push(token);
         }
+
+        Ok(())
+    }
+
+    /// Returns all of the input as one big raw text.
+    ///
+    /// This is logged as an error, since it shouldn't be happening.
+    fn extract_error(
+        text: &str,
+        error: pest::error::Error<Rule>,
+    ) -> Vec<ExtractedToken<'_>> {
+        error!("Error while lexing input in pest: {error}");
+        vec![ExtractedToken {
+            token: Token::Other,
+            slice: text,
+            span: 0..text.len(),
+        }]
     }
 
     /// Converts a single [`Pair`] from pest into its corresponding [`ExtractedToken`].
@@ -309,4 +359,138 @@ impl Token {
     pub fn name(self) -> &'static str {
         self.into()
     }
+
+    /// A short, human-readable description of this token.
+    pub fn description(self) -> &'static str {
+        match self {
+            Token::LeftBracket => "An opening single bracket.",
+            Token::LeftBracketAnchor => "An opening single bracket for an anchor link.",
+            Token::LeftBracketStar => {
+                "An opening single bracket for a link in a new tab."
+            }
+            Token::RightBracket => "A closing single bracket.",
+            Token::LeftBlock => "The opening of a block.",
+            Token::LeftBlockEnd => "The opening of a block's end tag.",
+            Token::LeftBlockAnchor => "The opening of an anchor block.",
+            Token::LeftBlockStar => "The opening of a block with the star flag.",
+            Token::LeftMath => "The opening of an inline math expression.",
+            Token::LeftParentheses => "A pair of opening parentheses.",
+            Token::RightBlock => "The closing of a block.",
+            Token::RightMath => "The closing of an inline math expression.",
+            Token::RightParentheses => "A pair of closing parentheses.",
+            Token::DoubleDash => "A double dash.",
+            Token::TripleDash => "A triple dash, or longer.",
+            Token::LeftDoubleAngle => "A pair of opening angle brackets.",
+            Token::ClearFloatBoth => "A float clear on both sides.",
+            Token::ClearFloatLeft => "A float clear on the left side.",
+            Token::ClearFloatRight => "A float clear on the right side.",
+            Token::Pipe => "A vertical bar.",
+            Token::Equals => "An equals sign.",
+            Token::Colon => "A colon.",
+            Token::Underscore => "An underscore.",
+            Token::Quote => "A right angle bracket, as used for quotes.",
+            Token::Heading => "A heading marker.",
+            Token::LineBreak => "A single newline.",
+            Token::ParagraphBreak => "Two or more newlines.",
+            Token::Whitespace => "Spaces or tabs.",
+            Token::Bold => "A bold marker.",
+            Token::Italics => "An italics marker.",
+            Token::Underline => "An underline marker.",
+            Token::Superscript => "A superscript marker.",
+            Token::Subscript => "A subscript marker.",
+            Token::LeftMonospace => "The opening of monospaced text.",
+            Token::RightMonospace => "The closing of monospaced text.",
+            Token::Color => "A color marker.",
+            Token::Raw => "A raw text marker.",
+            Token::LeftRaw => "The opening of raw text.",
+            Token::RightRaw => "The closing of raw text.",
+            Token::BulletItem => "A bulleted list item marker.",
+            Token::NumberedItem => "A numbered list item marker.",
+            Token::LeftLink => "The opening of a triple-bracket link.",
+            Token::LeftLinkStar => "The opening of a triple-bracket link in a new tab.",
+            Token::RightLink => "The closing of a triple-bracket link.",
+            Token::TableColumn => "A table column separator.",
+            Token::TableColumnLeft => "A left-aligned table column separator.",
+            Token::TableColumnRight => "A right-aligned table column separator.",
+            Token::TableColumnCenter => "A center-aligned table column separator.",
+            Token::TableColumnTitle => "A table header column separator.",
+            Token::Identifier => "A run of alphanumeric characters.",
+            Token::Email => "An email address.",
+            Token::Url => "A URL.",
+            Token::Variable => "An include variable.",
+            Token::String => "A double-quoted string.",
+            Token::LeftComment => "The opening of a comment.",
+            Token::RightComment => "The closing of a comment.",
+            Token::InputStart => "The start of the input.",
+            Token::InputEnd => "The end of the input.",
+            Token::Other => "Any other character.",
+        }
+    }
+
+    /// An example of text which lexes as this token.
+    ///
+    /// This is empty for tokens which do not correspond to any text.
+    pub fn example(self) -> &'static str {
+        match self {
+            Token::LeftBracket => "[",
+            Token::LeftBracketAnchor => "[#",
+            Token::LeftBracketStar => "[*",
+            Token::RightBracket => "]",
+            Token::LeftBlock => "[[",
+            Token::LeftBlockEnd => "[[/",
+            Token::LeftBlockAnchor => "[[#",
+            Token::LeftBlockStar => "[[*",
+            Token::LeftMath => "[[$",
+            Token::LeftParentheses => "((",
+            Token::RightBlock => "]]",
+            Token::RightMath => "$]]",
+            Token::RightParentheses => "))",
+            Token::DoubleDash => "--",
+            Token::TripleDash => "---",
+            Token::LeftDoubleAngle => "<<",
+            Token::ClearFloatBoth => "~~~~",
+            Token::ClearFloatLeft => "~~~~<",
+            Token::ClearFloatRight => "~~~~>",
+            Token::Pipe => "|",
+            Token::Equals => "=",
+            Token::Colon => ":",
+            Token::Underscore => "_",
+            Token::Quote => ">",
+            Token::Heading => "++",
+            Token::LineBreak => "\n",
+            Token::ParagraphBreak => "\n\n",
+            Token::Whitespace => "  ",
+            Token::Bold => "**",
+            Token::Italics => "//",
+            Token::Underline => "__",
+            Token::Superscript => "^^",
+            Token::Subscript => ",,",
+            Token::LeftMonospace => "{{",
+            Token::RightMonospace => "}}",
+            Token::Color => "##",
+            Token::Raw => "@@",
+            Token::LeftRaw => "@<",
+            Token::RightRaw => ">@",
+            Token::BulletItem => "*",
+            Token::NumberedItem => "#",
+            Token::LeftLink => "[[[",
+            Token::LeftLinkStar => "[[[*",
+            Token::RightLink => "]]]",
+            Token::TableColumn => "||",
+            Token::TableColumnLeft => "||<",
+            Token::TableColumnRight => "||>",
+            Token::TableColumnCenter => "||=",
+            Token::TableColumnTitle => "||~",
+            Token::Identifier => "apple",
+            Token::Email => "user@example.com",
+            Token::Url => "https://example.com/",
+            Token::Variable => "{$name}",
+            Token::String => "\"text\"",
+            Token::LeftComment => "[!--",
+            Token::RightComment => "--]",
+            Token::InputStart => "",
+            Token::InputEnd => "",
+            Token::Other => "%",
+        }
+    }
 }
@@ -0,0 +1,168 @@
+/*
+ * parsing/token/plain.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Fast path for lexing runs of plain text.
+//!
+//! Prose is mostly words separated by spaces, which the lexer turns into
+//! alternating [`Token::Identifier`] and [`Token::Whitespace`] tokens.
+//! Rather than running pest over these, long runs of such text are found
+//! ahead of time and split into tokens directly. Everything else is still
+//! lexed by pest, so the output is exactly the same either way.
+//!
+//! A run is trimmed so that no pest token could extend into it:
+//! * The first word may continue a URL or variable from before the run.
+//! * The last word may begin a URL, such as `https` before `://`.
+//! * A string may span the whole run, if there are quotes on either
+//!   side of it within the same line. Such runs are left to pest.
+
+use super::{ExtractedToken, Token};
+use std::ops::Range;
+
+/// The shortest run of plain text worth lexing separately from pest.
+///
+/// Shorter runs save little over lexing them in place.
+const MIN_PLAIN_LEN: usize = 32;
+
+/// Whether this byte can be part of a plain text run.
+#[inline]
+fn is_plain(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || is_space(byte)
+}
+
+#[inline]
+fn is_space(byte: u8) -> bool {
+    byte == b' ' || byte == b'\t'
+}
+
+#[inline]
+fn is_newline(byte: u8) -> bool {
+    byte == b'\n' || byte == b'\r'
+}
+
+/// Finds each long run of plain text which can be lexed without pest.
+///
+/// The returned ranges are in order and do not overlap.
+pub fn find_plain_runs(text: &str) -> Vec<Range<usize>> {
+    let bytes = text.as_bytes();
+    let mut runs = Vec::new();
+
+    // Whether a quote has been seen since the last newline
+    let mut quote_before = false;
+
+    // The next quote or newline, and whether it's a quote
+    let mut line_end: Option<(usize, bool)> = None;
+
+    let mut index = 0;
+    while index < bytes.len() {
+        let byte = bytes[index];
+
+        if !is_plain(byte) {
+            if is_newline(byte) {
+                quote_before = false;
+            } else if byte == b'"' {
+                quote_before = true;
+            }
+
+            index += 1;
+            continue;
+        }
+
+        let start = index;
+        while index < bytes.len() && is_plain(bytes[index]) {
+            index += 1;
+        }
+
+        let run = match trim_run(bytes, start..index) {
+            Some(run) if run.len() >= MIN_PLAIN_LEN => run,
+            _ => continue,
+        };
+
+        // If a quote both precedes and follows this run on the same line,
+        // the run may be inside a string.
+        if quote_before {
+            let quote_after = match line_end {
+                Some((end, quote_after)) if end >= index => quote_after,
+                _ => {
+                    let (end, quote_after) = bytes[index..]
+                        .iter()
+                        .position(|&byte| byte == b'"' || is_newline(byte))
+                        .map(|offset| (index + offset, bytes[index + offset] == b'"'))
+                        .unwrap_or((bytes.len(), false));
+
+                    line_end = Some((end, quote_after));
+                    quote_after
+                }
+            };
+
+            if quote_after {
+                continue;
+            }
+        }
+
+        runs.push(run);
+    }
+
+    runs
+}
+
+/// Trims the words at either end of a run, if a token could extend into them.
+fn trim_run(bytes: &[u8], run: Range<usize>) -> Option<Range<usize>> {
+    let Range { mut start, mut end } = run;
+
+    if start > 0 && !is_newline(bytes[start - 1]) {
+        start += bytes[start..end].iter().position(|&byte| is_space(byte))?;
+    }
+
+    if end < bytes.len() && !is_newline(bytes[end]) {
+        end = start + bytes[start..end].iter().rposition(|&byte| is_space(byte))? + 1;
+    }
+
+    Some(start..end)
+}
+
+/// Splits a run of plain text into identifier and whitespace tokens.
+pub fn lex_plain_run<'a>(
+    text: &'a str,
+    run: Range<usize>,
+    tokens: &mut Vec<ExtractedToken<'a>>,
+) {
+    let bytes = text.as_bytes();
+    let mut start = run.start;
+
+    while start < run.end {
+        let space = is_space(bytes[start]);
+        let end = bytes[start..run.end]
+            .iter()
+            .position(|&byte| is_space(byte) != space)
+            .map_or(run.end, |offset| start + offset);
+
+        tokens.push(ExtractedToken {
+            token: if space {
+                Token::Whitespace
+            } else {
+                Token::Identifier
+            },
+            slice: &text[start..end],
+            span: start..end,
+        });
+
+        start = end;
+    }
+}
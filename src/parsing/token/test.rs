@@ -233,3 +233,71 @@ fn tokens() {
         ],
     );
 }
+
+#[test]
+fn plain_runs() {
+    use std::fs;
+    use std::path::PathBuf;
+
+    // Lexes the whole input with pest, without the fast path
+    fn extract_pest(text: &str) -> Vec<ExtractedToken<'_>> {
+        let mut tokens = vec![ExtractedToken {
+            token: Token::InputStart,
+            slice: "",
+            span: 0..0,
+        }];
+
+        Token::extract_chunk(text, 0..text.len(), &mut tokens)
+            .expect("Unable to lex input with pest");
+
+        tokens
+    }
+
+    macro_rules! check {
+        ($input:expr $(,)?) => {{
+            let input: &str = $input;
+            assert_eq!(
+                Token::extract_all(input),
+                extract_pest(input),
+                "Tokens from fast path do not match pest for input {:?}",
+                input,
+            );
+        }};
+    }
+
+    let prose = "the quick brown fox jumps over the lazy dog again and again";
+    assert_eq!(plain::find_plain_runs(prose), vec![0..prose.len()]);
+
+    check!(prose);
+    check!(&format!("{prose}\n\n{prose}\r\n{prose}"));
+    check!(&format!("**{prose}** //{prose}//"));
+    check!(&format!("https {prose} https://example.com/{prose}"));
+    check!(&format!("{prose} https://example.com/ {prose}"));
+    check!(&format!("{prose} http://{prose}"));
+    check!(&format!("{{$var {prose}}} {{$var}}{prose}"));
+    check!(&format!("\"{prose}\" \"{prose}"));
+    check!(&format!("\"a\" {prose} \"b\"\n{prose} \"c"));
+    check!(&format!("\"\\\"{prose}\\\"\""));
+    check!(&format!("[[span]]{prose}[[/span]]"));
+    check!(&format!("\t{prose}\t\t{prose}  "));
+    check!(&format!("é{prose}é {prose}é"));
+
+    // Check every input from the syntax tree tests
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("test");
+
+    for entry in fs::read_dir(path).expect("Unable to read test directory") {
+        let path = entry.expect("Unable to read directory entry").path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+
+        let contents = fs::read_to_string(&path).expect("Unable to read test file");
+        let test: serde_json::Value =
+            serde_json::from_str(&contents).expect("Unable to parse test file");
+
+        if let Some(input) = test["input"].as_str() {
+            check!(input);
+        }
+    }
+}
@@ -27,7 +27,10 @@ pub mod whitespace;
 #[cfg(test)]
 mod test;
 
+use crate::includes::SourceMap;
+use crate::settings::{TypographySettings, WikitextSettings};
 use regex::Regex;
+use std::ops::Range;
 
 /// Helper struct to easily perform string replacements.
 #[derive(Debug)]
@@ -61,7 +64,14 @@ pub enum Replacer {
 impl Replacer {
     /// Replaces the text in the manner defined by its enum, using the buffer as a temporary space
     /// to copy to.
-    fn replace(&self, text: &mut String, buffer: &mut String) {
+    ///
+    /// If a source map is given, it is updated for each replacement.
+    fn replace(
+        &self,
+        text: &mut String,
+        buffer: &mut String,
+        map: &mut Option<&mut SourceMap>,
+    ) {
         use self::Replacer::*;
 
         match *self {
@@ -87,7 +97,7 @@ impl Replacer {
                         mtch.range()
                     };
 
-                    text.replace_range(range, replacement);
+                    replace_range(text, range, replacement, map);
                 }
             }
             RegexSurround {
@@ -123,13 +133,27 @@ impl Replacer {
                     buffer.push_str(mtch.as_str());
                     buffer.push_str(end);
 
-                    text.replace_range(range, buffer);
+                    replace_range(text, range, buffer, map);
                 }
             }
         }
     }
 }
 
+/// Replaces part of the text, updating the source map if there is one.
+fn replace_range(
+    text: &mut String,
+    range: Range<usize>,
+    replacement: &str,
+    map: &mut Option<&mut SourceMap>,
+) {
+    if let Some(map) = map {
+        map.replace(range.clone(), replacement.len());
+    }
+
+    text.replace_range(range, replacement);
+}
+
 /// Run the preprocessor on the given wikitext, which is modified in-place.
 ///
 /// The following modifications are performed:
@@ -142,8 +166,49 @@ impl Replacer {
 /// This call always succeeds. The return value designates where issues occurred
 /// to allow programmatic determination of where things were not as expected.
 pub fn preprocess(text: &mut String) {
+    preprocess_with(text, &TypographySettings::default());
+}
+
+/// Run the preprocessor, with only the given typography substitutions.
+///
+/// This is the same as [`preprocess`], except that the quote and ellipsis
+/// transformations are only performed if enabled in the settings.
+pub fn preprocess_with(text: &mut String, typography: &TypographySettings) {
     whitespace::substitute(text);
-    typography::substitute(text);
+    typography::substitute_with(text, typography);
+    info!("Finished preprocessing of text");
+}
+
+/// Run the preprocessor, as configured by the given settings.
+///
+/// In addition to the typography substitutions of [`preprocess_with`],
+/// groups of blank lines are kept as-is if `preserve_blank_lines` is set.
+pub fn preprocess_with_settings(text: &mut String, settings: &WikitextSettings) {
+    preprocess_mapped(text, settings, &mut None);
+}
+
+/// Run the preprocessor as configured by the given settings, updating the source map.
+///
+/// This is the same as [`preprocess_with_settings`], except that the map from
+/// [`include_with_source_map`] is kept in line with the preprocessed text,
+/// so that spans from parsing can be located in the original pages.
+///
+/// [`include_with_source_map`]: crate::include_with_source_map
+pub fn preprocess_with_source_map(
+    text: &mut String,
+    settings: &WikitextSettings,
+    map: &mut SourceMap,
+) {
+    preprocess_mapped(text, settings, &mut Some(map));
+}
+
+fn preprocess_mapped(
+    text: &mut String,
+    settings: &WikitextSettings,
+    map: &mut Option<&mut SourceMap>,
+) {
+    whitespace::substitute_mapped(text, settings.preserve_blank_lines, map);
+    typography::substitute_mapped(text, &settings.typography, map);
     info!("Finished preprocessing of text");
 }
 
@@ -33,6 +33,8 @@
 //! the `--` in `[!--` and `--]` into em dashes.
 
 use super::Replacer;
+use crate::includes::SourceMap;
+use crate::settings::TypographySettings;
 use once_cell::sync::Lazy;
 use regex::Regex;
 
@@ -66,23 +68,43 @@ static HORIZONTAL_ELLIPSIS: Lazy<Replacer> = Lazy::new(|| Replacer::RegexReplace
 });
 
 /// Performs all typographic substitutions in-place in the given text
+#[inline]
 pub fn substitute(text: &mut String) {
+    substitute_with(text, &TypographySettings::default());
+}
+
+/// Performs the enabled typographic substitutions in-place in the given text
+#[inline]
+pub fn substitute_with(text: &mut String, settings: &TypographySettings) {
+    substitute_mapped(text, settings, &mut None);
+}
+
+/// Performs the enabled typographic substitutions, updating the source map if there is one.
+pub(crate) fn substitute_mapped(
+    text: &mut String,
+    settings: &TypographySettings,
+    map: &mut Option<&mut SourceMap>,
+) {
     let mut buffer = String::new();
     info!("Performing typography substitutions");
 
     macro_rules! replace {
         ($replacer:expr) => {
-            $replacer.replace(text, &mut buffer)
+            $replacer.replace(text, &mut buffer, map)
         };
     }
 
     // Quotes
-    replace!(DOUBLE_QUOTES);
-    replace!(LOW_DOUBLE_QUOTES);
-    replace!(SINGLE_QUOTES);
+    if settings.quotes {
+        replace!(DOUBLE_QUOTES);
+        replace!(LOW_DOUBLE_QUOTES);
+        replace!(SINGLE_QUOTES);
+    }
 
     // Miscellaneous
-    replace!(HORIZONTAL_ELLIPSIS);
+    if settings.ellipsis {
+        replace!(HORIZONTAL_ELLIPSIS);
+    }
 }
 
 #[cfg(test)]
@@ -147,3 +169,30 @@ fn test_substitute() {
 
     test_substitution("typography", substitute, &TEST_CASES);
 }
+
+#[test]
+fn test_substitute_with() {
+    let run = |input: &str, settings: TypographySettings| {
+        let mut text = str!(input);
+        substitute_with(&mut text, &settings);
+        text
+    };
+
+    let input = "``Wait...'' `he' said";
+    let quotes_only = TypographySettings {
+        quotes: true,
+        ..TypographySettings::NONE
+    };
+    let ellipsis_only = TypographySettings {
+        ellipsis: true,
+        ..TypographySettings::NONE
+    };
+
+    assert_eq!(
+        run(input, TypographySettings::default()),
+        "“Wait…” ‘he’ said"
+    );
+    assert_eq!(run(input, TypographySettings::NONE), input);
+    assert_eq!(run(input, quotes_only), "“Wait...” ‘he’ said");
+    assert_eq!(run(input, ellipsis_only), "``Wait…'' `he' said");
+}
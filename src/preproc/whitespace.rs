@@ -25,9 +25,10 @@
 //! * Concatenating lines that end with backslashes
 //! * Convert tabs to four spaces
 //! * Convert null characters to regular spaces
-//! * Compress groups of 3+ newlines into 2 newlines
+//! * Compress groups of 3+ newlines into 2 newlines, unless blank lines are preserved
 
-use super::Replacer;
+use super::{replace_range, Replacer};
+use crate::includes::SourceMap;
 use once_cell::sync::Lazy;
 use regex::{Regex, RegexBuilder};
 
@@ -44,6 +45,14 @@ static WHITESPACE_ONLY_LINE: Lazy<Replacer> = Lazy::new(|| Replacer::RegexReplac
         .unwrap(),
     replacement: "",
 });
+static WHITESPACE_ONLY_LINE_KEEP_NEWLINES: Lazy<Replacer> =
+    Lazy::new(|| Replacer::RegexReplace {
+        regex: RegexBuilder::new(r"^[^\S\n]+$")
+            .multi_line(true)
+            .build()
+            .unwrap(),
+        replacement: "",
+    });
 static LEADING_NEWLINES: Lazy<Replacer> = Lazy::new(|| Replacer::RegexReplace {
     regex: Regex::new(r"^\n+").unwrap(),
     replacement: "",
@@ -70,12 +79,32 @@ static NULL_SPACE: Lazy<Replacer> = Lazy::new(|| Replacer::RegexReplace {
 });
 
 /// Performs all whitespace substitutions in-place in the given text.
+#[inline]
 pub fn substitute(text: &mut String) {
+    substitute_with(text, false);
+}
+
+/// Performs all whitespace substitutions, optionally keeping groups of blank lines.
+///
+/// Normally stripping whitespace lines also compresses runs of
+/// three or more newlines into two, which is skipped here if
+/// `preserve_blank_lines` is set.
+#[inline]
+pub fn substitute_with(text: &mut String, preserve_blank_lines: bool) {
+    substitute_mapped(text, preserve_blank_lines, &mut None);
+}
+
+/// Performs all whitespace substitutions, updating the source map if there is one.
+pub(crate) fn substitute_mapped(
+    text: &mut String,
+    preserve_blank_lines: bool,
+    map: &mut Option<&mut SourceMap>,
+) {
     let mut buffer = String::new();
 
     macro_rules! replace {
         ($replacer:expr) => {
-            $replacer.replace(text, &mut buffer)
+            $replacer.replace(text, &mut buffer, map)
         };
     }
 
@@ -85,10 +114,14 @@ pub fn substitute(text: &mut String) {
     // Replace leading non-standard spaces with regular spaces
     // Leave other non-standard spaces as-is (such as nbsp in
     // the middle of paragraphs)
-    replace_leading_spaces(text);
+    replace_leading_spaces(text, map);
 
     // Strip lines with only whitespace
-    replace!(WHITESPACE_ONLY_LINE);
+    if preserve_blank_lines {
+        replace!(WHITESPACE_ONLY_LINE_KEEP_NEWLINES);
+    } else {
+        replace!(WHITESPACE_ONLY_LINE);
+    }
 
     // Join concatenated lines (ending with '\')
     replace!(CONCAT_LINES);
@@ -105,7 +138,7 @@ pub fn substitute(text: &mut String) {
 }
 
 /// In-place replaces the leading non-standard spaces (such as nbsp) on each line with standard spaces
-fn replace_leading_spaces(text: &mut String) {
+fn replace_leading_spaces(text: &mut String, map: &mut Option<&mut SourceMap>) {
     debug!("Replacing leading non-standard spaces with regular spaces");
 
     let mut offset = 0;
@@ -120,7 +153,7 @@ fn replace_leading_spaces(text: &mut String) {
 
         offset = mtch.start() + count;
 
-        text.replace_range(mtch.range(), &spaces);
+        replace_range(text, mtch.range(), &spaces, map);
     }
 }
 
@@ -156,6 +189,7 @@ const TEST_CASES: [(&str, &str); 7] = [
 fn regexes() {
     let _ = &*LEADING_NONSTANDARD_WHITESPACE;
     let _ = &*WHITESPACE_ONLY_LINE;
+    let _ = &*WHITESPACE_ONLY_LINE_KEEP_NEWLINES;
     let _ = &*LEADING_NEWLINES;
     let _ = &*TRAILING_NEWLINES;
     let _ = &*DOS_MAC_NEWLINES;
@@ -170,3 +204,10 @@ fn test_substitute() {
 
     test_substitution("miscellaneous", substitute, &TEST_CASES);
 }
+
+#[test]
+fn test_preserve_blank_lines() {
+    let mut text = str!("apple\n\n\n  \n\nbanana\n \ncherry\n\n");
+    substitute_with(&mut text, true);
+    assert_eq!(text, "apple\n\n\n\n\nbanana\n\ncherry");
+}
@@ -0,0 +1,62 @@
+/*
+ * render/async_handle.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use crate::data::UserInfo;
+use std::future::Future;
+
+/// Asynchronous lookups performed on behalf of the renderer.
+///
+/// This is implemented by the consumer of this library, typically to
+/// back these calls with database queries. It is used by
+/// [`HtmlRender::render_async()`](crate::render::html::HtmlRender::render_async).
+///
+/// Since rendering itself is synchronous, all of the lookups a page needs
+/// are performed before rendering begins. Any lookup which returns `None`
+/// (or for messages, any key which isn't prefetched) falls back to the
+/// default behavior of the renderer.
+pub trait AsyncHandle {
+    /// Fetches information about the given user.
+    ///
+    /// Returns `None` if no such user exists.
+    fn get_user_info(
+        &self,
+        name: &str,
+    ) -> impl Future<Output = Option<UserInfo<'static>>> + Send;
+
+    /// Fetches the title of the given page, for use as a link label.
+    ///
+    /// Returns `None` if the page does not exist, in which case
+    /// the page's name is used instead.
+    fn get_page_title(
+        &self,
+        site: &str,
+        page: &str,
+    ) -> impl Future<Output = Option<String>> + Send;
+
+    /// Fetches a localized message with the given key.
+    ///
+    /// Returns `None` to use the message from
+    /// [`WikitextSettings::messages`](crate::settings::WikitextSettings::messages).
+    fn get_message(
+        &self,
+        language: &str,
+        message: &str,
+    ) -> impl Future<Output = Option<String>> + Send;
+}
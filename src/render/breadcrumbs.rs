@@ -0,0 +1,107 @@
+/*
+ * render/breadcrumbs.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Generation of the navigation trail for a page.
+
+use super::Handle;
+use crate::data::{Breadcrumb, BreadcrumbType, PageInfo};
+
+/// Produces the breadcrumbs for the given page.
+///
+/// This is the page's category (if not `_default`), followed by each of its
+/// parent pages from the outermost inwards, then the page itself.
+pub fn get_breadcrumbs(page_info: &PageInfo) -> Vec<Breadcrumb> {
+    build_breadcrumbs(&Handle::default(), page_info)
+}
+
+pub(crate) fn build_breadcrumbs(
+    handle: &Handle,
+    page_info: &PageInfo,
+) -> Vec<Breadcrumb> {
+    info!(
+        "Building breadcrumbs (site {}, page {})",
+        page_info.site.as_ref(),
+        page_info.page.as_ref(),
+    );
+
+    let mut breadcrumbs = Vec::new();
+    let slug = match page_info.category {
+        Some(ref category) if category != "_default" => {
+            breadcrumbs.push(Breadcrumb {
+                btype: BreadcrumbType::Category,
+                slug: str!(category),
+                title: str!(category),
+            });
+
+            format!("{category}:{}", page_info.page)
+        }
+        _ => str!(page_info.page),
+    };
+
+    for parent in handle.get_page_parents(&page_info.site, &slug) {
+        let title = handle
+            .get_page_title(&page_info.site, &parent)
+            .unwrap_or_else(|| parent.clone());
+
+        breadcrumbs.push(Breadcrumb {
+            btype: BreadcrumbType::Parent,
+            slug: parent,
+            title,
+        });
+    }
+
+    breadcrumbs.push(Breadcrumb {
+        btype: BreadcrumbType::Current,
+        slug,
+        title: str!(page_info.title),
+    });
+
+    breadcrumbs
+}
+
+#[test]
+fn breadcrumbs() {
+    let mut page_info = PageInfo::dummy();
+    assert_eq!(
+        get_breadcrumbs(&page_info),
+        vec![Breadcrumb {
+            btype: BreadcrumbType::Current,
+            slug: str!("some-page"),
+            title: str!("A page for the age"),
+        }],
+    );
+
+    page_info.category = Some(cow!("fragment"));
+    assert_eq!(
+        get_breadcrumbs(&page_info),
+        vec![
+            Breadcrumb {
+                btype: BreadcrumbType::Category,
+                slug: str!("fragment"),
+                title: str!("fragment"),
+            },
+            Breadcrumb {
+                btype: BreadcrumbType::Current,
+                slug: str!("fragment:some-page"),
+                title: str!("A page for the age"),
+            },
+        ],
+    );
+}
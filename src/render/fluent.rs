@@ -0,0 +1,222 @@
+/*
+ * render/fluent.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::messages::{MessageCatalog, BUILTIN_MESSAGES, FALLBACK_LANGUAGE};
+use crate::FtmlError;
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::FluentResource;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::{self, Debug};
+use unic_langid::LanguageIdentifier;
+
+/// A message catalog built from Fluent resources.
+///
+/// This begins with the built-in messages. Sites may add their own
+/// languages, or replace any of the built-in messages.
+pub struct FluentCatalog {
+    bundles: HashMap<LanguageIdentifier, FluentBundle<FluentResource>>,
+}
+
+impl FluentCatalog {
+    /// Creates a catalog with the built-in messages.
+    pub fn new() -> Self {
+        let mut catalog = FluentCatalog::empty();
+
+        for (language, source) in BUILTIN_MESSAGES {
+            catalog
+                .add_messages(language, source)
+                .expect("Built-in messages are invalid");
+        }
+
+        catalog
+    }
+
+    /// Creates a catalog with no messages.
+    pub fn empty() -> Self {
+        FluentCatalog {
+            bundles: HashMap::new(),
+        }
+    }
+
+    /// Adds the messages in this Fluent resource for the given language.
+    ///
+    /// Any messages which already exist for this language are replaced.
+    pub fn add_messages(
+        &mut self,
+        language: &str,
+        source: &str,
+    ) -> Result<(), FtmlError> {
+        info!("Adding messages for language '{language}'");
+
+        let language = parse_language(language).ok_or_else(|| {
+            FtmlError::InvalidMessages(format!("invalid language: {language}"))
+        })?;
+
+        let resource = FluentResource::try_new(str!(source)).map_err(|(_, errors)| {
+            FtmlError::InvalidMessages(format!("invalid resource: {errors:?}"))
+        })?;
+
+        self.bundles
+            .entry(language.clone())
+            .or_insert_with(|| {
+                let mut bundle = FluentBundle::new_concurrent(vec![language]);
+
+                // Messages are inserted into HTML, so Unicode isolation
+                // marks around placeables are not needed.
+                bundle.set_use_isolating(false);
+                bundle
+            })
+            .add_resource_overriding(resource);
+
+        Ok(())
+    }
+
+    /// Gets the message from this language's bundle, if it has one.
+    fn format(&self, language: &LanguageIdentifier, key: &str) -> Option<Cow<'_, str>> {
+        let bundle = self.bundles.get(language)?;
+        let pattern = bundle.get_message(key)?.value()?;
+        let mut errors = vec![];
+        let message = bundle.format_pattern(pattern, None, &mut errors);
+
+        if !errors.is_empty() {
+            warn!("Errors formatting message {key} in {language}: {errors:?}");
+        }
+
+        Some(message)
+    }
+}
+
+impl MessageCatalog for FluentCatalog {
+    fn get_message(&self, language: &str, key: &str) -> Option<Cow<'_, str>> {
+        // Try the language as given, then without any region or script,
+        // for instance "pt-BR" then "pt", and finally the fallback.
+        if let Some(language) = parse_language(language) {
+            if let Some(message) = self.format(&language, key) {
+                return Some(message);
+            }
+
+            let base = LanguageIdentifier::from_parts(language.language, None, None, &[]);
+            if let Some(message) = self.format(&base, key) {
+                return Some(message);
+            }
+        }
+
+        let fallback = parse_language(FALLBACK_LANGUAGE)?;
+        self.format(&fallback, key)
+    }
+}
+
+impl Default for FluentCatalog {
+    #[inline]
+    fn default() -> Self {
+        FluentCatalog::new()
+    }
+}
+
+impl Debug for FluentCatalog {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut languages = self
+            .bundles
+            .keys()
+            .map(|language| language.to_string())
+            .collect::<Vec<_>>();
+
+        languages.sort();
+
+        f.debug_struct("FluentCatalog")
+            .field("languages", &languages)
+            .finish()
+    }
+}
+
+/// Parses a language identifier, such as `en` or `pt-BR`.
+fn parse_language(language: &str) -> Option<LanguageIdentifier> {
+    match language.parse::<LanguageIdentifier>() {
+        Ok(language) if !language.language.is_empty() => Some(language),
+        _ => None,
+    }
+}
+
+#[test]
+fn fluent_catalog() {
+    use super::handle::MESSAGE_KEYS;
+
+    let catalog = FluentCatalog::new();
+
+    // Each built-in language has every message
+    for (language, _) in BUILTIN_MESSAGES {
+        let language = parse_language(language).unwrap();
+
+        for key in MESSAGE_KEYS {
+            assert!(
+                catalog.format(&language, key).is_some(),
+                "Message {key} missing for language {language}",
+            );
+        }
+    }
+
+    macro_rules! check {
+        ($catalog:expr, $language:expr, $key:expr, $expected:expr $(,)?) => {
+            assert_eq!(
+                $catalog.get_message($language, $key).as_deref(),
+                $expected,
+                "Message {} in {} doesn't match expected",
+                $key,
+                $language,
+            );
+        };
+    }
+
+    check!(catalog, "en", "footnote-block-title", Some("Footnotes"));
+    check!(
+        catalog,
+        "fr",
+        "footnote-block-title",
+        Some("Notes de bas de page")
+    );
+    check!(catalog, "de-AT", "footnote-block-title", Some("Fußnoten"));
+    check!(catalog, "ja", "footnote-block-title", Some("Footnotes"));
+    check!(
+        catalog,
+        "default",
+        "footnote-block-title",
+        Some("Footnotes")
+    );
+    check!(catalog, "en", "nonexistent", None);
+
+    // Site-provided messages, partially overriding the built-in ones
+    let mut catalog = FluentCatalog::new();
+    catalog
+        .add_messages("es", "collapsible-open = + mostrar")
+        .expect("Unable to add messages");
+    catalog
+        .add_messages("pt-BR", "collapsible-open = + abrir")
+        .expect("Unable to add messages");
+
+    check!(catalog, "es", "collapsible-open", Some("+ mostrar"));
+    check!(catalog, "es", "collapsible-hide", Some("- ocultar bloque"));
+    check!(catalog, "pt-BR", "collapsible-open", Some("+ abrir"));
+    check!(catalog, "pt-BR", "collapsible-hide", Some("- hide block"));
+    check!(catalog, "pt", "collapsible-open", Some("+ open block"));
+
+    assert!(catalog.add_messages("!", "key = value").is_err());
+    assert!(catalog.add_messages("en", "= value").is_err());
+}
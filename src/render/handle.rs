@@ -18,33 +18,123 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+use crate::audit::{record_lookup, AuditLookup, AuditRecorder};
 use crate::data::{PageInfo, UserInfo};
 use crate::settings::WikitextSettings;
-use crate::tree::{ImageSource, LinkLabel, LinkLocation, Module};
-use crate::url::BuildSiteUrl;
+use crate::tree::{ImageSource, LinkLabel, LinkLocation, Module, ModuleListPages};
+use crate::url::{normalize_link, split_local_file, BuildSiteUrl};
 use std::borrow::Cow;
 use std::num::NonZeroUsize;
 use wikidot_normalize::normalize;
 
-#[derive(Debug)]
-pub struct Handle;
+/// The keys of all messages which may be requested by renderers.
+#[cfg_attr(not(feature = "async"), allow(dead_code))]
+pub const MESSAGE_KEYS: [&str; 22] = [
+    "button-copy-clipboard",
+    "collapsible-open",
+    "collapsible-hide",
+    "table-of-contents",
+    "footnote",
+    "footnote-block-title",
+    "footnote-backlink",
+    "bibliography-reference",
+    "bibliography-block-title",
+    "bibliography-cite-not-found",
+    "image-context-bad",
+    "media-context-bad",
+    "iframe-blocked",
+    "element-blocked",
+    "breadcrumbs",
+    "rating",
+    "rate-up",
+    "rate-down",
+    "rate-cancel",
+    "quote-wrote",
+    "user-missing",
+    "html-placeholder",
+];
+
+#[derive(Debug, Default)]
+pub struct Handle {
+    audit: AuditRecorder,
+}
 
 impl Handle {
+    #[inline]
+    pub fn new(settings: &WikitextSettings) -> Self {
+        Handle {
+            audit: settings.audit.clone(),
+        }
+    }
+
     pub fn render_module(&self, buffer: &mut String, module: &Module) {
         // Modules only render to HTML
         info!("Rendering module '{}'", module.name());
-        str_write!(buffer, "<p>TODO: module {}</p>", module.name());
+        record_lookup(&self.audit, || AuditLookup::Module {
+            name: str!(module.name()),
+        });
+
+        match module {
+            Module::ListPages(list_pages) => self.render_list_pages(buffer, list_pages),
+            _ => str_write!(buffer, "<p>TODO: module {}</p>", module.name()),
+        }
+    }
+
+    pub fn render_list_pages(&self, buffer: &mut String, list_pages: &ModuleListPages) {
+        info!(
+            "Rendering ListPages module ({} template parts)",
+            list_pages.template.len(),
+        );
+
+        // TODO
+        str_write!(buffer, "<p>TODO: module ListPages</p>");
     }
 
     pub fn get_page_title(&self, _site: &str, _page: &str) -> Option<String> {
         info!("Fetching page title");
+        record_lookup(&self.audit, || AuditLookup::PageTitle {
+            site: str!(_site),
+            page: str!(_page),
+        });
 
         // TODO
         Some(format!("TODO: actual title ({_site} {_page})"))
     }
 
+    pub fn get_page_field(&self, site: &str, page: &str, field: &str) -> Option<String> {
+        info!("Fetching page field (field '{field}')");
+        record_lookup(&self.audit, || AuditLookup::PageField {
+            site: str!(site),
+            page: str!(page),
+            field: str!(field),
+        });
+
+        // TODO
+        match field {
+            "created_by" | "created_at" | "updated_by" | "updated_at" => {
+                Some(format!("TODO: actual {field} ({site} {page})"))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn get_page_parents(&self, site: &str, page: &str) -> Vec<String> {
+        info!("Fetching page parents");
+        record_lookup(&self.audit, || AuditLookup::PageParents {
+            site: str!(site),
+            page: str!(page),
+        });
+
+        // TODO
+        vec![]
+    }
+
     pub fn get_page_exists(&self, _site: &str, _page: &str) -> bool {
         info!("Checking page existence");
+        record_lookup(&self.audit, || AuditLookup::PageExists {
+            site: str!(_site),
+            page: str!(_page),
+        });
 
         // For testing
         #[cfg(test)]
@@ -58,6 +148,7 @@ impl Handle {
 
     pub fn get_user_info<'a>(&self, name: &'a str) -> Option<UserInfo<'a>> {
         info!("Fetching user info (name '{name}')");
+        record_lookup(&self.audit, || AuditLookup::UserInfo { name: str!(name) });
         let mut info = UserInfo::dummy();
         info.user_name = cow!(name);
         info.user_profile_url = Cow::Owned(format!("/user:info/{name}"));
@@ -71,6 +162,9 @@ impl Handle {
         settings: &WikitextSettings,
     ) -> Option<Cow<'a, str>> {
         info!("Getting file link for image");
+        record_lookup(&self.audit, || AuditLookup::ImageLink {
+            source: format!("{source:?}"),
+        });
 
         let (site, page, file): (&str, &str, &str) = match source {
             ImageSource::Url(url) => return Some(Cow::clone(url)),
@@ -87,12 +181,61 @@ impl Handle {
             ImageSource::File3 { site, page, file } => (site, page, file),
         };
 
-        // TODO: emit url
-        Some(Cow::Owned(format!(
-            "https://{site}.wjfiles.com/local--files/{page}/{file}",
-        )))
+        Some(Cow::Owned(self.get_file_url(site, page, file)))
     }
 
+    /// Gets the URL of a file attached to a page.
+    ///
+    /// This is used for image sources and links to attached files,
+    /// such as `/local--files/page/file.pdf`.
+    pub fn get_file_url(&self, site: &str, page: &str, file: &str) -> String {
+        info!("Getting URL for file (site {site}, page {page}, file {file})");
+        record_lookup(&self.audit, || AuditLookup::FileUrl {
+            site: str!(site),
+            page: str!(page),
+            file: str!(file),
+        });
+
+        // TODO
+        format!("https://{site}.wjfiles.com/local--files/{page}/{file}")
+    }
+
+    /// Gets the URL for a link, resolving links to attached files.
+    ///
+    /// Paths such as `/local--files/page/file.pdf` are passed to
+    /// `get_file_url()`, all other links are normalized as usual.
+    pub fn get_link_url<'a>(
+        &self,
+        link: &'a LinkLocation<'a>,
+        info: &PageInfo,
+    ) -> Cow<'a, str> {
+        if let LinkLocation::Url(url) = link {
+            if let Some((page, file)) = split_local_file(url) {
+                return Cow::Owned(self.get_file_url(&info.site, page, file));
+            }
+        }
+
+        normalize_link(link, self)
+    }
+
+    pub fn get_image_dimensions(
+        &self,
+        source: &ImageSource,
+        info: &PageInfo,
+    ) -> Option<(u32, u32)> {
+        info!("Getting dimensions for image");
+        record_lookup(&self.audit, || AuditLookup::ImageDimensions {
+            source: format!("{source:?}"),
+        });
+
+        let _ = source;
+        let _ = info;
+
+        // TODO
+        None
+    }
+
+    #[inline]
     pub fn get_link_label<F>(
         &self,
         site: &str,
@@ -101,6 +244,27 @@ impl Handle {
         f: F,
     ) where
         F: FnOnce(&str),
+    {
+        self.get_link_label_with(
+            site,
+            link,
+            label,
+            |site, page| self.get_page_title(site, page),
+            f,
+        );
+    }
+
+    /// Like `get_link_label()`, but fetches page titles using `get_page_title`.
+    pub fn get_link_label_with<T, F>(
+        &self,
+        site: &str,
+        link: &LinkLocation,
+        label: &LinkLabel,
+        get_page_title: T,
+        f: F,
+    ) where
+        T: FnOnce(&str, &str) -> Option<String>,
+        F: FnOnce(&str),
     {
         let page_title;
         let label_text = match *label {
@@ -111,12 +275,13 @@ impl Handle {
                 LinkLocation::Page(page_ref) => page_ref.page(),
             },
             LinkLabel::Page => match link {
-                LinkLocation::Url(_) => {
-                    panic!("Requested link label of page for a URL");
+                LinkLocation::Url(url) => {
+                    warn!("Requested link label of page for a URL, using URL instead");
+                    url.as_ref()
                 }
                 LinkLocation::Page(page_ref) => {
                     let (site, page) = page_ref.fields_or(site);
-                    page_title = match self.get_page_title(site, page) {
+                    page_title = match get_page_title(site, page) {
                         Some(title) => title,
                         None => page_ref.to_string(),
                     };
@@ -129,32 +294,9 @@ impl Handle {
         f(label_text);
     }
 
-    pub fn get_message(&self, language: &str, message: &str) -> &'static str {
-        info!("Fetching message (language {language}, key {message})");
-
-        let _ = language;
-
-        // TODO
-        match message {
-            "button-copy-clipboard" => "Copy to Clipboard",
-            "collapsible-open" => "+ open block",
-            "collapsible-hide" => "- hide block",
-            "table-of-contents" => "Table of Contents",
-            "footnote" => "Footnote",
-            "footnote-block-title" => "Footnotes",
-            "bibliography-reference" => "Reference",
-            "bibliography-block-title" => "Bibliography",
-            "bibliography-cite-not-found" => "Bibliography item not found",
-            "image-context-bad" => "No images in this context",
-            _ => {
-                error!("Unknown message requested (key {message})");
-                "?"
-            }
-        }
-    }
-
     pub fn post_html(&self, info: &PageInfo, html: &str) -> String {
         info!("Submitting HTML to create iframe-able snippet");
+        record_lookup(&self.audit, || AuditLookup::PostHtml { length: html.len() });
 
         let _ = info;
         let _ = html;
@@ -165,12 +307,53 @@ impl Handle {
 
     pub fn post_code(&self, index: NonZeroUsize, code: &str) {
         info!("Submitting code snippet (index {})", index.get());
+        record_lookup(&self.audit, || AuditLookup::PostCode {
+            index: index.get(),
+            length: code.len(),
+        });
 
         let _ = index;
         let _ = code;
 
         // TODO
     }
+
+    pub fn post_code_blob(&self, index: NonZeroUsize, blob: &str) {
+        info!(
+            "Submitting out-of-line code snippet (index {}, blob {blob})",
+            index.get(),
+        );
+        record_lookup(&self.audit, || AuditLookup::PostCodeBlob {
+            index: index.get(),
+            blob: str!(blob),
+        });
+
+        // TODO
+    }
+
+    /// Reads a body back from the blob store, passing it to `f` in chunks.
+    ///
+    /// Returns `false` if the blob could not be found.
+    pub fn stream_blob<F>(&self, blob: &str, f: F) -> bool
+    where
+        F: FnMut(&str),
+    {
+        info!("Loading blob {blob}");
+        record_lookup(&self.audit, || AuditLookup::LoadBlob { blob: str!(blob) });
+
+        // For testing
+        #[cfg(test)]
+        {
+            return test_blobs::stream(blob, f);
+        }
+
+        // TODO
+        #[allow(unreachable_code)]
+        {
+            let _ = f;
+            false
+        }
+    }
 }
 
 impl BuildSiteUrl for Handle {
@@ -188,3 +371,59 @@ impl BuildSiteUrl for Handle {
         format!("https://{site}.wikijump.com/{path}")
     }
 }
+
+#[cfg(test)]
+pub(crate) mod test_blobs {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    thread_local! {
+        static BLOBS: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
+    }
+
+    pub fn store(blob: &str, contents: &str) {
+        BLOBS.with(|blobs| {
+            blobs.borrow_mut().insert(str!(blob), str!(contents));
+        });
+    }
+
+    pub fn stream<F>(blob: &str, mut f: F) -> bool
+    where
+        F: FnMut(&str),
+    {
+        BLOBS.with(|blobs| match blobs.borrow().get(blob) {
+            Some(contents) => {
+                // Deliver in pieces, as a real store would
+                let mut chunk = String::new();
+                for ch in contents.chars() {
+                    chunk.push(ch);
+                    if chunk.len() >= 64 {
+                        f(&chunk);
+                        chunk.clear();
+                    }
+                }
+
+                if !chunk.is_empty() {
+                    f(&chunk);
+                }
+
+                true
+            }
+            None => false,
+        })
+    }
+}
+
+#[test]
+fn message_keys() {
+    use super::{MessageCatalog, MessageSource};
+
+    let messages = MessageSource::builtin();
+
+    for key in MESSAGE_KEYS {
+        assert!(
+            messages.get_message("en", key).is_some(),
+            "Unknown message key {key}",
+        );
+    }
+}
@@ -0,0 +1,77 @@
+/*
+ * render/html/accessibility.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Accessibility problems found while rendering.
+//!
+//! These are only collected if
+//! [`accessibility_audit`](crate::settings::WikitextSettings::accessibility_audit)
+//! is enabled, and are returned in [`HtmlOutput`](super::HtmlOutput).
+
+use crate::tree::{ContainerType, Element};
+use strum_macros::IntoStaticStr;
+
+/// A potential accessibility problem in the rendered page.
+#[derive(Serialize, Deserialize, IntoStaticStr, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", tag = "type", content = "data")]
+#[strum(serialize_all = "kebab-case")]
+pub enum AccessibilityIssue {
+    /// An image has no alternative text.
+    ///
+    /// The source is the URL of the image.
+    ImageMissingAlt { source: String },
+
+    /// A heading is more than one level below the heading before it.
+    ///
+    /// For instance, a `+++` heading directly following a `+` heading.
+    HeadingLevelSkipped { previous: u8, level: u8 },
+
+    /// A link has no visible label.
+    EmptyLinkLabel { url: String },
+
+    /// Text is set apart only by its color, with no other emphasis.
+    ColorOnlyEmphasis { color: String },
+}
+
+impl AccessibilityIssue {
+    /// The name of this kind of issue, such as `image-missing-alt`.
+    #[inline]
+    pub fn kind(&self) -> &'static str {
+        self.into()
+    }
+}
+
+/// Whether any of these elements are emphasized in a way other than color.
+pub fn has_emphasis(elements: &[Element]) -> bool {
+    elements.iter().any(|element| match element {
+        Element::Container(container) => {
+            matches!(
+                container.ctype(),
+                ContainerType::Bold
+                    | ContainerType::Italics
+                    | ContainerType::Underline
+                    | ContainerType::Mark
+                    | ContainerType::Header(_),
+            ) || has_emphasis(container.elements())
+        }
+        Element::Color { elements, .. } => has_emphasis(elements),
+        Element::Size { .. } => true,
+        _ => false,
+    })
+}
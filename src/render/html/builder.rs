@@ -20,6 +20,7 @@
 
 use super::attributes::AddedAttributes;
 use super::context::HtmlContext;
+use super::escape::escape;
 use super::render::ItemRender;
 use std::collections::HashSet;
 
@@ -32,7 +33,7 @@ macro_rules! tag_method {
 }
 
 /// These are HTML tags which do not need a closing pair.
-const SOLO_HTML_TAGS: [&str; 14] = [
+pub const SOLO_HTML_TAGS: [&str; 14] = [
     "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param",
     "source", "track", "wbr",
 ];
@@ -103,22 +104,33 @@ where
     }
 
     tag_method!(a);
+    tag_method!(article);
+    tag_method!(aside);
+    tag_method!(blockquote);
     tag_method!(br);
+    tag_method!(cite);
     tag_method!(code);
     tag_method!(dd);
     tag_method!(details);
     tag_method!(div);
     tag_method!(dl);
     tag_method!(dt);
+    tag_method!(fieldset);
+    tag_method!(figcaption);
+    tag_method!(figure);
     tag_method!(hr);
     tag_method!(iframe);
     tag_method!(img);
     tag_method!(input);
+    tag_method!(legend);
     tag_method!(li);
+    tag_method!(nav);
     tag_method!(ol);
     tag_method!(pre);
     tag_method!(rp);
     tag_method!(script);
+    tag_method!(section);
+    tag_method!(source);
     tag_method!(style);
     tag_method!(span);
     tag_method!(sub);
@@ -126,6 +138,7 @@ where
     tag_method!(summary);
     tag_method!(table);
     tag_method!(tbody);
+    tag_method!(time);
     tag_method!(tr);
     tag_method!(ul);
 
@@ -150,8 +163,11 @@ where
 
 impl<'c, 'i, 'h, 'e, 't> HtmlBuilderTag<'c, 'i, 'h, 'e, 't> {
     pub fn new(ctx: &'c mut HtmlContext<'i, 'h, 'e, 't>, tag: &'t str) -> Self {
-        ctx.push_raw('<');
-        ctx.push_raw_str(tag);
+        // Tag syntax is written to the buffer directly,
+        // and recorded as an element node instead.
+        ctx.buffer().push('<');
+        ctx.buffer().push_str(tag);
+        ctx.with_nodes(|nodes| nodes.open(tag));
 
         HtmlBuilderTag {
             ctx,
@@ -165,22 +181,36 @@ impl<'c, 'i, 'h, 'e, 't> HtmlBuilderTag<'c, 'i, 'h, 'e, 't> {
         debug_assert!(is_alphanumeric(key));
         debug_assert!(self.in_tag);
 
-        self.ctx.push_raw(' ');
-        self.ctx.push_escaped(key);
+        let buffer = self.ctx.buffer();
+        buffer.push(' ');
+        escape(buffer, key);
 
         if has_value {
-            self.ctx.push_raw('=');
+            buffer.push('=');
         }
     }
 
     fn attr_value(&mut self, value_parts: &[&str]) {
-        self.ctx.push_raw('"');
+        let buffer = self.ctx.buffer();
+        buffer.push('"');
 
         for part in value_parts {
-            self.ctx.push_escaped(part);
+            escape(buffer, part);
         }
 
-        self.ctx.push_raw('"');
+        buffer.push('"');
+    }
+
+    fn attr_node(&mut self, key: &str, has_value: bool, value_parts: &[&str]) {
+        self.ctx.with_nodes(|nodes| {
+            let value = if has_value {
+                value_parts.concat()
+            } else {
+                String::new()
+            };
+
+            nodes.attribute(key, value);
+        });
     }
 
     pub fn attr_single(&mut self, key: &str, value_parts: &[&str]) -> &mut Self {
@@ -197,12 +227,36 @@ impl<'c, 'i, 'h, 'e, 't> HtmlBuilderTag<'c, 'i, 'h, 'e, 't> {
 
         let has_value = !value_parts.iter().all(|s| s.is_empty());
 
+        if !self.ctx.element_ids() && key.eq_ignore_ascii_case("id") {
+            return self;
+        }
+
+        // Apply any site restrictions on the output.
+        let policy = &self.ctx.settings().sanitization;
+        if !policy.is_empty() {
+            let value = value_parts.concat();
+
+            if let Some(value) = policy.sanitize_attribute(key, &value) {
+                self.attr_key(key, has_value);
+
+                if has_value {
+                    self.attr_value(&[&value]);
+                }
+
+                self.attr_node(key, has_value, &[&value]);
+            }
+
+            return self;
+        }
+
         self.attr_key(key, has_value);
 
         if has_value {
             self.attr_value(value_parts);
         }
 
+        self.attr_node(key, has_value, value_parts);
+
         self
     }
 
@@ -224,11 +278,19 @@ impl<'c, 'i, 'h, 'e, 't> HtmlBuilderTag<'c, 'i, 'h, 'e, 't> {
         let mut merged = HashSet::new();
         let mut merged_value = Vec::new();
 
+        // User-provided data attributes are subject to the site's policy.
+        let data_policy = &self.ctx.settings().data_attributes;
+        let is_allowed = |key: &str| data_policy.is_allowed(key);
+
         // Merge any attributes in common.
         if let Some(attribute_map) = attributes.map {
             let attribute_map = attribute_map.get();
 
             for (key, value_parts) in filter_entries(&attributes) {
+                if !is_allowed(key) {
+                    continue;
+                }
+
                 if let Some(map_value) = attribute_map.get(&cow!(key)) {
                     // Merge keys by prepending value_parts before
                     // the attribute map value.
@@ -254,7 +316,7 @@ impl<'c, 'i, 'h, 'e, 't> HtmlBuilderTag<'c, 'i, 'h, 'e, 't> {
         // Add attributes from user-provided map.
         if let Some(attribute_map) = attributes.map {
             for (key, value) in attribute_map.get() {
-                if !merged.contains(key.as_ref()) {
+                if !merged.contains(key.as_ref()) && is_allowed(key) {
                     self.attr_single(key, &[value]);
                 }
             }
@@ -265,7 +327,7 @@ impl<'c, 'i, 'h, 'e, 't> HtmlBuilderTag<'c, 'i, 'h, 'e, 't> {
 
     fn content_start(&mut self) {
         if self.in_tag {
-            self.ctx.push_raw('>');
+            self.ctx.buffer().push('>');
             self.in_tag = false;
         }
 
@@ -295,14 +357,17 @@ impl<'c, 'i, 'h, 'e, 't> HtmlBuilderTag<'c, 'i, 'h, 'e, 't> {
 impl<'c, 'i, 'h, 'e, 't> Drop for HtmlBuilderTag<'c, 'i, 'h, 'e, 't> {
     fn drop(&mut self) {
         if self.in_tag && !self.in_contents {
-            self.ctx.push_raw('>');
+            self.ctx.buffer().push('>');
         }
 
         if should_close_tag(self.tag) {
-            self.ctx.push_raw_str("</");
-            self.ctx.push_raw_str(self.tag);
-            self.ctx.push_raw('>');
+            let buffer = self.ctx.buffer();
+            buffer.push_str("</");
+            buffer.push_str(self.tag);
+            buffer.push('>');
         }
+
+        self.ctx.with_nodes(|nodes| nodes.close());
     }
 }
 
@@ -18,25 +18,38 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+use super::accessibility::AccessibilityIssue;
 use super::builder::HtmlBuilder;
+use super::dom::{HtmlNode, NodeRecorder};
 use super::escape::escape;
+use super::memo::IncludeMemo;
 use super::meta::{HtmlMeta, HtmlMetaType};
+use super::minify::minify;
 use super::output::HtmlOutput;
 use super::random::Random;
+use super::word_break::escape_word_breaks;
 use crate::data::PageRef;
-use crate::data::{Backlinks, PageInfo};
+use crate::data::{Backlinks, LinkValidation, PageInfo, UserInfo, UserMention};
 use crate::info;
 use crate::next_index::{NextIndex, TableOfContentsIndex};
-use crate::render::Handle;
-use crate::settings::WikitextSettings;
+use crate::render::text::TextRender;
+use crate::render::{expect_message, Handle, Prefetched};
+use crate::settings::{FootnoteBlockMode, WikitextSettings};
+use crate::tree::visit::{walk_element, Visitor};
 use crate::tree::{
-    Bibliography, BibliographyList, Element, LinkLocation, VariableScopes,
+    Bibliography, BibliographyList, Element, LinkLocation, LinkType, VariableScopes,
 };
-use crate::url::is_url;
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{self, Write};
+use std::mem;
 use std::num::NonZeroUsize;
+use std::ops::Range;
+
+/// Estimated bytes of output which don't depend on the input length.
+///
+/// This covers the `<wj-body>` wrapper and a paragraph around its contents.
+const HTML_OUTPUT_OVERHEAD: usize = 64;
 
 #[derive(Debug)]
 pub struct HtmlContext<'i, 'h, 'e, 't>
@@ -44,10 +57,13 @@ where
     'e: 't,
 {
     body: String,
+    nodes: Option<NodeRecorder>,
     meta: Vec<HtmlMeta>,
     backlinks: Backlinks<'static>,
     info: &'i PageInfo<'i>,
     handle: &'h Handle,
+    prefetched: Option<&'h Prefetched>,
+    link_validation: Option<&'h LinkValidation>,
     settings: &'e WikitextSettings,
     random: Random,
 
@@ -62,19 +78,30 @@ where
     table_of_contents: &'e [Element<'t>],
     footnotes: &'e [Vec<Element<'t>>],
     bibliographies: &'e BibliographyList<'t>,
+    equations: &'e [Option<Cow<'t, str>>],
 
     //
     // Cached data
     //
     pages_exists: HashMap<PageRef<'static>, bool>,
+    include_memo: IncludeMemo,
 
     //
     // Other fields to track
     //
     code_snippet_index: NonZeroUsize,
     table_of_contents_index: usize,
+    heading_ids: HashSet<String>,
     equation_index: NonZeroUsize,
+    equation_ids: bool,
+    element_ids: bool,
+    footnote_equations: Vec<NonZeroUsize>,
     footnote_index: NonZeroUsize,
+    footnote_block_starts: Vec<usize>,
+    footnote_repeats: HashMap<NonZeroUsize, usize>,
+    footnote_repeats_rendered: HashMap<NonZeroUsize, usize>,
+    heading_level: Option<u8>,
+    accessibility: Vec<AccessibilityIssue>,
 }
 
 impl<'i, 'h, 'e, 't> HtmlContext<'i, 'h, 'e, 't> {
@@ -92,38 +119,43 @@ impl<'i, 'h, 'e, 't> HtmlContext<'i, 'h, 'e, 't> {
         //
         // Looking at test data, the outputted HTML byte length usually stays
         // below ~12% of the wikitext input byte length, with the greatest differences
-        // being small inputs.
-        let capacity = {
-            let input = wikitext_len as f32;
-            let output = input * 1.12;
-
-            // Basic sanity check, if this fails
-            // just return 0 to avoid weirdness.
-            if output.is_finite() {
-                output as usize
-            } else {
-                0
-            }
-        };
+        // being small inputs, where the wrapping elements make up most of the output.
+        let capacity = wikitext_len
+            .saturating_add(wikitext_len / 8)
+            .saturating_add(HTML_OUTPUT_OVERHEAD);
 
         // Build and return
         HtmlContext {
             body: String::with_capacity(capacity),
+            nodes: None,
             meta: Self::initial_metadata(info),
             backlinks: Backlinks::new(),
             info,
             handle,
+            prefetched: None,
+            link_validation: None,
             settings,
             random: Random::default(),
             variables: VariableScopes::new(),
             table_of_contents,
             footnotes,
             bibliographies,
+            equations: &[],
             pages_exists: HashMap::new(),
+            include_memo: IncludeMemo::default(),
             code_snippet_index: NonZeroUsize::new(1).unwrap(),
             table_of_contents_index: 0,
+            heading_ids: HashSet::new(),
             equation_index: NonZeroUsize::new(1).unwrap(),
+            equation_ids: true,
+            element_ids: true,
+            footnote_equations: Vec::new(),
             footnote_index: NonZeroUsize::new(1).unwrap(),
+            footnote_block_starts: vec![0],
+            footnote_repeats: HashMap::new(),
+            footnote_repeats_rendered: HashMap::new(),
+            heading_level: None,
+            accessibility: Vec::new(),
         }
     }
 
@@ -169,7 +201,7 @@ impl<'i, 'h, 'e, 't> HtmlContext<'i, 'h, 'e, 't> {
     }
 
     #[inline]
-    pub fn settings(&self) -> &WikitextSettings {
+    pub fn settings(&self) -> &'e WikitextSettings {
         self.settings
     }
 
@@ -178,6 +210,83 @@ impl<'i, 'h, 'e, 't> HtmlContext<'i, 'h, 'e, 't> {
         self.handle
     }
 
+    /// Sets lookup results fetched ahead of time, to be used over the handle.
+    #[inline]
+    pub fn set_prefetched(&mut self, prefetched: &'h Prefetched) {
+        self.prefetched = Some(prefetched);
+    }
+
+    /// Sets which linked pages exist, to be used over the handle.
+    #[inline]
+    pub fn set_link_validation(&mut self, link_validation: &'h LinkValidation) {
+        self.link_validation = Some(link_validation);
+    }
+
+    /// Sets the equations from the syntax tree, to resolve references against.
+    #[inline]
+    pub fn set_equations(&mut self, equations: &'e [Option<Cow<'t, str>>]) {
+        self.equations = equations;
+    }
+
+    /// Counts the repeated references to each named footnote in the given elements.
+    pub fn set_footnote_repeats(&mut self, elements: &[Element]) {
+        struct RepeatCounter<'a>(&'a mut HashMap<NonZeroUsize, usize>);
+
+        impl<'t> Visitor<'t> for RepeatCounter<'_> {
+            fn visit_element(&mut self, element: &Element<'t>) {
+                match element {
+                    Element::FootnoteRepeat(index) => {
+                        *self.0.entry(*index).or_default() += 1
+                    }
+                    _ => walk_element(self, element),
+                }
+            }
+        }
+
+        self.footnote_repeats.clear();
+        RepeatCounter(&mut self.footnote_repeats).visit_elements(elements);
+    }
+
+    /// Finds the number of the first equation in each footnote.
+    ///
+    /// Equations within a footnote are numbered where it is referenced,
+    /// matching the equation list from parsing.
+    pub fn set_footnote_equations(&mut self, elements: &[Element<'t>]) {
+        struct EquationCounter<'e, 't> {
+            footnotes: &'e [Vec<Element<'t>>],
+            starts: Vec<NonZeroUsize>,
+            next: NonZeroUsize,
+        }
+
+        impl<'t> Visitor<'t> for EquationCounter<'_, 't> {
+            fn visit_element(&mut self, element: &Element<'t>) {
+                match element {
+                    Element::Math { .. } => {
+                        self.next = NonZeroUsize::new(self.next.get() + 1).unwrap();
+                    }
+                    Element::Footnote => {
+                        let footnotes = self.footnotes;
+                        let contents = footnotes.get(self.starts.len());
+                        self.starts.push(self.next);
+
+                        if let Some(contents) = contents {
+                            self.visit_elements(contents);
+                        }
+                    }
+                    _ => walk_element(self, element),
+                }
+            }
+        }
+
+        let mut counter = EquationCounter {
+            footnotes: self.footnotes,
+            starts: Vec::new(),
+            next: NonZeroUsize::new(1).unwrap(),
+        };
+        counter.visit_elements(elements);
+        self.footnote_equations = counter.starts;
+    }
+
     #[inline]
     pub fn random(&mut self) -> &mut Random {
         &mut self.random
@@ -213,6 +322,40 @@ impl<'i, 'h, 'e, 't> HtmlContext<'i, 'h, 'e, 't> {
         self.bibliographies.get_bibliography(index)
     }
 
+    // Handle lookups
+    pub fn get_user_info<'a>(&self, name: &'a str) -> Option<UserInfo<'a>> {
+        match self.prefetched.and_then(|p| p.get_user_info(name)) {
+            Some(user_info) => user_info.clone(),
+            None => self.handle.get_user_info(name),
+        }
+    }
+
+    pub fn get_message(&self, message: &str) -> Cow<'e, str> {
+        match self.prefetched.and_then(|p| p.get_message(message)) {
+            Some(message) => Cow::Owned(str!(message)),
+            None => expect_message(&self.settings.messages, self.language(), message),
+        }
+    }
+
+    /// Returns a function fetching page titles, for use in link labels.
+    pub fn page_title_fetcher(&self) -> impl Fn(&str, &str) -> Option<String> + 'h {
+        let handle = self.handle;
+        let prefetched = self.prefetched;
+
+        move |site, page| match prefetched.and_then(|p| p.get_page_title(site, page)) {
+            Some(title) => title.clone(),
+            None => handle.get_page_title(site, page),
+        }
+    }
+
+    /// Returns the number of the equation with the given name, if any.
+    pub fn get_equation_number(&self, name: &str) -> Option<NonZeroUsize> {
+        self.equations
+            .iter()
+            .position(|equation| equation.as_deref() == Some(name))
+            .and_then(|index| NonZeroUsize::new(index + 1))
+    }
+
     pub fn get_bibliography_ref(
         &self,
         label: &str,
@@ -232,18 +375,147 @@ impl<'i, 'h, 'e, 't> HtmlContext<'i, 'h, 'e, 't> {
         index
     }
 
+    /// Produces the ID for the next heading with a table of contents entry.
+    ///
+    /// This matches the link targets generated for the table of contents.
+    pub fn next_heading_id(&mut self, name_elements: &[Element]) -> String {
+        let index = self.next_table_of_contents_index();
+        let name = TextRender.render_partial(name_elements, self.info, self.settings, 0);
+
+        self.settings
+            .heading_ids
+            .generate(index, &name, &mut self.heading_ids)
+    }
+
     pub fn next_equation_index(&mut self) -> NonZeroUsize {
         let index = self.equation_index;
         self.equation_index = NonZeroUsize::new(index.get() + 1).unwrap();
         index
     }
 
+    /// Whether numbered equations currently get IDs for references to link to.
+    #[inline]
+    pub fn equation_ids(&self) -> bool {
+        self.equation_ids
+    }
+
+    /// Whether `id` attributes are currently emitted.
+    #[inline]
+    pub fn element_ids(&self) -> bool {
+        self.element_ids
+    }
+
+    /// Runs the given function with `id` attributes emitted or omitted.
+    ///
+    /// This is used for second copies of content, which would otherwise
+    /// repeat the IDs already in the page.
+    pub fn with_element_ids<F, T>(&mut self, element_ids: bool, f: F) -> T
+    where
+        F: FnOnce(&mut Self) -> T,
+    {
+        let previous = mem::replace(&mut self.element_ids, element_ids);
+        let result = f(self);
+        self.element_ids = previous;
+        result
+    }
+
+    /// Renders the contents of a footnote, numbering its equations as at its reference.
+    ///
+    /// Footnote contents are rendered twice, once in the preview at the reference,
+    /// and once in the footnote block. Only the latter has IDs for its equations,
+    /// so references link to the footnote block.
+    pub fn render_footnote_contents<F>(
+        &mut self,
+        index: NonZeroUsize,
+        preview: bool,
+        f: F,
+    ) where
+        F: FnOnce(&mut Self),
+    {
+        let equation_index = self.equation_index;
+        let equation_ids = self.equation_ids;
+
+        if let Some(start) = self.footnote_equations.get(index.get() - 1) {
+            self.equation_index = *start;
+        }
+
+        self.equation_ids = !preview;
+        f(self);
+        self.equation_ids = equation_ids;
+
+        // The first preview is where these equations are numbered,
+        // so later equations in the body continue after them.
+        self.equation_index = if preview {
+            self.equation_index.max(equation_index)
+        } else {
+            equation_index
+        };
+    }
+
     pub fn next_footnote_index(&mut self) -> NonZeroUsize {
         let index = self.footnote_index;
         self.footnote_index = NonZeroUsize::new(index.get() + 1).unwrap();
         index
     }
 
+    /// Returns which reference to the given footnote this repeat is.
+    ///
+    /// The original reference is the first, so this starts from 2.
+    pub fn next_footnote_repeat(&mut self, index: NonZeroUsize) -> usize {
+        let count = self.footnote_repeats_rendered.entry(index).or_default();
+        *count += 1;
+        *count + 1
+    }
+
+    /// Returns how many repeated references the given footnote has.
+    #[inline]
+    pub fn footnote_repeat_count(&self, index: NonZeroUsize) -> usize {
+        self.footnote_repeats.get(&index).copied().unwrap_or(0)
+    }
+
+    /// Returns the displayed number for the footnote with the given index.
+    ///
+    /// If footnotes are split between blocks, this is the footnote's position
+    /// within the block listing it. Repeated references to a footnote in an
+    /// earlier block use that block's number. Footnotes numbered from the
+    /// start of the page continue on from the `footnote_offset` setting.
+    pub fn footnote_number(&self, index: NonZeroUsize) -> NonZeroUsize {
+        let block = self
+            .footnote_block_starts
+            .partition_point(|&start| start < index.get());
+
+        let start = self.footnote_block_starts[block - 1];
+        let offset = if start == 0 {
+            self.settings.footnote_offset
+        } else {
+            0
+        };
+
+        NonZeroUsize::new(index.get() - start + offset).unwrap()
+    }
+
+    /// Returns the ID for the footnote with the given index.
+    ///
+    /// This is offset by the `footnote_offset` setting, so that IDs
+    /// are unique across fragments of the same page.
+    #[inline]
+    pub fn footnote_id(&self, index: NonZeroUsize) -> usize {
+        index.get() + self.settings.footnote_offset
+    }
+
+    /// Returns the range of footnotes to be listed by the next footnote block.
+    pub fn next_footnote_block_range(&mut self) -> Range<usize> {
+        match self.settings.footnote_block_mode {
+            FootnoteBlockMode::Repeat => 0..self.footnotes.len(),
+            FootnoteBlockMode::Split => {
+                let start = *self.footnote_block_starts.last().unwrap();
+                let end = (self.footnote_index.get() - 1).min(self.footnotes.len());
+                self.footnote_block_starts.push(end);
+                start..end
+            }
+        }
+    }
+
     #[inline]
     pub fn get_footnote(&self, index_one: NonZeroUsize) -> Option<&'e [Element<'t>]> {
         self.footnotes
@@ -251,41 +523,66 @@ impl<'i, 'h, 'e, 't> HtmlContext<'i, 'h, 'e, 't> {
             .map(|elements| elements.as_slice())
     }
 
-    // Backlinks
-    #[inline]
-    pub fn add_link(&mut self, link: &LinkLocation) {
-        // TODO: set to internal link if domain matches site
-        // See https://scuttle.atlassian.net/browse/WJ-24
+    // Accessibility
+    /// Records an accessibility problem, if auditing is enabled.
+    pub fn report_accessibility<F>(&mut self, f: F)
+    where
+        F: FnOnce() -> AccessibilityIssue,
+    {
+        if self.settings.accessibility_audit {
+            self.accessibility.push(f());
+        }
+    }
 
-        match link {
-            LinkLocation::Page(page) => {
-                self.backlinks.internal_links.push(page.to_owned());
+    /// Records a heading, reporting it if it skips any levels.
+    pub fn add_heading_level(&mut self, level: u8) {
+        if let Some(previous) = self.heading_level.replace(level) {
+            if level > previous + 1 {
+                self.report_accessibility(|| AccessibilityIssue::HeadingLevelSkipped {
+                    previous,
+                    level,
+                });
             }
-            LinkLocation::Url(link) => {
-                let mut link: &str = link;
+        }
+    }
 
-                if link == "javascript:;" {
-                    return;
-                }
+    // Metadata
+    /// Adds a `refresh` meta tag redirecting to this URL.
+    ///
+    /// Only the first redirect on a page is used, later ones are ignored.
+    pub fn add_meta_refresh(&mut self, url: &str) {
+        let exists = self.meta.iter().any(|meta| {
+            meta.tag_type == HtmlMetaType::HttpEquiv && meta.name == "refresh"
+        });
+
+        if !exists {
+            self.meta.push(HtmlMeta {
+                tag_type: HtmlMetaType::HttpEquiv,
+                name: str!("refresh"),
+                value: format!("0; url={url}"),
+            });
+        }
+    }
 
-                // Also support [ links pointing to local pages.
-                // e.g. [/scp-001 SCP-001] in addition to [[[SCP-001]]].
-                if link.starts_with('/') {
-                    link = &link[1..];
-                }
+    // Backlinks
+    #[inline]
+    pub fn add_link(&mut self, ltype: LinkType, link: &LinkLocation) {
+        self.backlinks.add_link(ltype, &link.to_owned());
+    }
 
-                if is_url(link) {
-                    let link = Cow::Owned(str!(link));
-                    self.backlinks.external_links.push(link);
-                } else {
-                    let page_ref = PageRef::page_only(cow!(link));
-                    self.backlinks.internal_links.push(page_ref.to_owned());
-                }
-            }
-        }
+    #[inline]
+    pub fn add_mention(&mut self, name: &str, show_avatar: bool) {
+        self.backlinks.mentions.push(UserMention {
+            name: Cow::Owned(str!(name)),
+            show_avatar,
+        });
     }
 
     pub fn page_exists(&mut self, page_ref: &PageRef) -> bool {
+        if let Some(exists) = self.link_validation.and_then(|v| v.exists(page_ref)) {
+            return exists;
+        }
+
         let (site, page) = page_ref.fields_or(&self.info.site);
 
         // Get from cache, or fetch and add
@@ -306,25 +603,154 @@ impl<'i, 'h, 'e, 't> HtmlContext<'i, 'h, 'e, 't> {
         self.backlinks.included_pages.push(page.to_owned());
     }
 
+    #[inline]
+    pub fn include_memo(&self) -> &IncludeMemo {
+        &self.include_memo
+    }
+
+    #[inline]
+    pub fn include_memo_mut(&mut self) -> &mut IncludeMemo {
+        &mut self.include_memo
+    }
+
     // Buffer management
+
+    /// The rendered body.
+    ///
+    /// Output written here directly is not recorded as nodes.
     #[inline]
     pub fn buffer(&mut self) -> &mut String {
         &mut self.body
     }
 
+    /// Writes out and clears the rendered body, for streaming output.
+    ///
+    /// Memoized include output refers to the body, so the memo is
+    /// cleared as well, rather than holding on to copies of it.
+    pub fn flush<W: Write + ?Sized>(&mut self, writer: &mut W) -> fmt::Result {
+        self.include_memo.clear();
+        if self.settings.minify_output {
+            writer.write_str(&minify(&self.body))?;
+        } else {
+            writer.write_str(&self.body)?;
+        }
+        self.body.clear();
+        Ok(())
+    }
+
+    /// Builds a tree of nodes from the output, alongside the body.
+    ///
+    /// Memoized includes are not reused while recording,
+    /// since only their HTML is stored.
+    #[inline]
+    pub fn record_nodes(&mut self) {
+        self.nodes = Some(NodeRecorder::new());
+    }
+
+    #[inline]
+    pub fn records_nodes(&self) -> bool {
+        self.nodes.is_some()
+    }
+
+    /// Removes and returns the nodes recorded so far.
+    pub fn take_nodes(&mut self) -> Vec<HtmlNode> {
+        match self.nodes {
+            Some(ref mut nodes) => nodes.take(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Calls the given function on the node recorder, if one is active.
+    ///
+    /// This is used by the HTML builder to mirror the elements it writes.
+    #[inline]
+    pub fn with_nodes<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut NodeRecorder),
+    {
+        if let Some(ref mut nodes) = self.nodes {
+            f(nodes);
+        }
+    }
+
+    /// Renders HTML separately from the body, returning it.
+    pub fn render_fragment<F>(&mut self, f: F) -> String
+    where
+        F: FnOnce(&mut Self),
+    {
+        let start = self.body.len();
+        f(self);
+        let fragment = self.body.split_off(start);
+        self.include_memo.truncate(start);
+        self.finish_html(fragment)
+    }
+
+    /// Renders HTML separately from the body, returning it as a list of nodes.
+    ///
+    /// Node recording must already be enabled.
+    pub fn render_fragment_nodes<F>(&mut self, f: F) -> Vec<HtmlNode>
+    where
+        F: FnOnce(&mut Self),
+    {
+        debug_assert!(self.records_nodes(), "Node recording not enabled");
+
+        let start = self.body.len();
+        let previous = self.take_nodes();
+        f(self);
+        self.body.truncate(start);
+        self.include_memo.truncate(start);
+
+        match self.nodes {
+            Some(ref mut nodes) => nodes.replace(previous),
+            None => Vec::new(),
+        }
+    }
+
+    /// Applies any post-processing requested by the settings to rendered HTML.
+    fn finish_html(&self, html: String) -> String {
+        if self.settings.minify_output {
+            minify(&html)
+        } else {
+            html
+        }
+    }
+
     #[inline]
     pub fn push_raw(&mut self, ch: char) {
-        self.buffer().push(ch);
+        self.push_raw_str(ch.encode_utf8(&mut [0; 4]));
     }
 
     #[inline]
     pub fn push_raw_str(&mut self, s: &str) {
-        self.buffer().push_str(s);
+        self.body.push_str(s);
+        self.with_nodes(|nodes| nodes.raw(s));
+    }
+
+    /// Appends a copy of previously rendered output.
+    pub fn push_rendered(&mut self, range: Range<usize>) {
+        let output = self.body[range].to_owned();
+        self.push_raw_str(&output);
     }
 
     #[inline]
     pub fn push_escaped(&mut self, s: &str) {
-        escape(self.buffer(), s);
+        escape(&mut self.body, s);
+        self.with_nodes(|nodes| nodes.text(s));
+    }
+
+    /// Pushes escaped text, inserting word breaks if configured.
+    pub fn push_text(&mut self, s: &str) {
+        match self.settings.word_break {
+            Some(ref word_break) => {
+                let start = self.body.len();
+                escape_word_breaks(&mut self.body, s, word_break);
+
+                if let Some(ref mut nodes) = self.nodes {
+                    nodes.raw(&self.body[start..]);
+                }
+            }
+            None => self.push_escaped(s),
+        }
     }
 
     #[inline]
@@ -335,11 +761,13 @@ impl<'i, 'h, 'e, 't> HtmlContext<'i, 'h, 'e, 't> {
 
 impl<'i, 'h, 'e, 't> From<HtmlContext<'i, 'h, 'e, 't>> for HtmlOutput {
     #[inline]
-    fn from(ctx: HtmlContext<'i, 'h, 'e, 't>) -> HtmlOutput {
+    fn from(mut ctx: HtmlContext<'i, 'h, 'e, 't>) -> HtmlOutput {
+        let body = mem::take(&mut ctx.body);
+        let body = ctx.finish_html(body);
         let HtmlContext {
-            body,
             meta,
             backlinks,
+            accessibility,
             ..
         } = ctx;
 
@@ -347,6 +775,9 @@ impl<'i, 'h, 'e, 't> From<HtmlContext<'i, 'h, 'e, 't>> for HtmlOutput {
             body,
             meta,
             backlinks,
+            table_of_contents: None,
+            footnotes: None,
+            accessibility,
         }
     }
 }
@@ -354,7 +785,8 @@ impl<'i, 'h, 'e, 't> From<HtmlContext<'i, 'h, 'e, 't>> for HtmlOutput {
 impl<'i, 'h, 'e, 't> Write for HtmlContext<'i, 'h, 'e, 't> {
     #[inline]
     fn write_str(&mut self, s: &str) -> fmt::Result {
-        self.buffer().write_str(s)
+        self.push_raw_str(s);
+        Ok(())
     }
 }
 
@@ -0,0 +1,267 @@
+/*
+ * render/html/dom.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Structured HTML output, as a tree of nodes rather than a flat string.
+//!
+//! The nodes are built by the HTML builder as it renders, alongside the
+//! usual output. Anything not written through the builder, such as module
+//! output or preformatted markup, is kept as [`HtmlNode::Raw`], so that
+//! serializing the tree reproduces the output of [`HtmlRender`].
+//!
+//! [`HtmlRender`]: super::HtmlRender
+
+use super::builder::SOLO_HTML_TAGS;
+use super::escape::escape;
+use super::meta::HtmlMeta;
+use super::{render_body, render_fragments, AccessibilityIssue, HtmlOutput};
+use crate::data::{Backlinks, PageInfo};
+use crate::render::{substitute_page_variables, Handle, Render};
+use crate::settings::WikitextSettings;
+use crate::tree::SyntaxTree;
+use std::mem;
+
+#[derive(Debug)]
+pub struct HtmlTreeRender;
+
+impl Render for HtmlTreeRender {
+    type Output = HtmlTreeOutput;
+
+    fn render(
+        &self,
+        tree: &SyntaxTree,
+        page_info: &PageInfo,
+        settings: &WikitextSettings,
+    ) -> HtmlTreeOutput {
+        let handle = Handle::new(settings);
+        let tree = substitute_page_variables(&handle, page_info, settings, tree);
+        let tree = &*tree;
+        let (mut ctx, _) =
+            render_body(tree, page_info, &handle, settings, None, None, true, None);
+        let body = ctx.take_nodes();
+        let (table_of_contents, footnotes) =
+            render_fragments(&mut ctx, tree, settings, |ctx, f| {
+                ctx.render_fragment_nodes(f)
+            });
+
+        let HtmlOutput {
+            meta,
+            backlinks,
+            accessibility,
+            ..
+        } = HtmlOutput::from(ctx);
+
+        HtmlTreeOutput {
+            body,
+            meta,
+            backlinks,
+            table_of_contents,
+            footnotes,
+            accessibility,
+        }
+    }
+}
+
+/// The same as [`HtmlOutput`], but with each body of HTML as a list of nodes.
+///
+/// The nodes are never minified, regardless of the settings.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HtmlTreeOutput {
+    pub body: Vec<HtmlNode>,
+    pub meta: Vec<HtmlMeta>,
+    pub backlinks: Backlinks<'static>,
+    pub table_of_contents: Option<Vec<HtmlNode>>,
+    pub footnotes: Option<Vec<HtmlNode>>,
+    pub accessibility: Vec<AccessibilityIssue>,
+}
+
+/// A node in the rendered HTML tree.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", tag = "node", content = "data")]
+pub enum HtmlNode {
+    /// An HTML element, with its attributes in output order.
+    ///
+    /// Attributes with an empty value are output without one,
+    /// for instance `<input checked>`.
+    Element {
+        tag: String,
+        attributes: Vec<(String, String)>,
+        children: Vec<HtmlNode>,
+    },
+
+    /// Text, which is escaped when serialized.
+    Text(String),
+
+    /// HTML which is serialized as-is.
+    Raw(String),
+}
+
+impl HtmlNode {
+    /// Serializes this node and its children into an HTML string.
+    pub fn to_html(&self) -> String {
+        let mut buffer = String::new();
+        self.write_html(&mut buffer);
+        buffer
+    }
+
+    /// Appends this node and its children as HTML to the given buffer.
+    pub fn write_html(&self, buffer: &mut String) {
+        match self {
+            HtmlNode::Element {
+                tag,
+                attributes,
+                children,
+            } => {
+                write_open_tag(buffer, tag, attributes);
+
+                if SOLO_HTML_TAGS.contains(&tag.as_str()) {
+                    return;
+                }
+
+                for child in children {
+                    child.write_html(buffer);
+                }
+
+                buffer.push_str("</");
+                buffer.push_str(tag);
+                buffer.push('>');
+            }
+            HtmlNode::Text(text) => escape(buffer, text),
+            HtmlNode::Raw(html) => buffer.push_str(html),
+        }
+    }
+}
+
+fn write_open_tag(buffer: &mut String, tag: &str, attributes: &[(String, String)]) {
+    buffer.push('<');
+    buffer.push_str(tag);
+
+    for (key, value) in attributes {
+        buffer.push(' ');
+        escape(buffer, key);
+
+        if !value.is_empty() {
+            buffer.push_str("=\"");
+            escape(buffer, value);
+            buffer.push('"');
+        }
+    }
+
+    buffer.push('>');
+}
+
+/// Serializes a list of nodes into an HTML string.
+pub fn nodes_to_html(nodes: &[HtmlNode]) -> String {
+    let mut buffer = String::new();
+
+    for node in nodes {
+        node.write_html(&mut buffer);
+    }
+
+    buffer
+}
+
+/// An element which is still being built, as (tag, attributes, children).
+type OpenElement = (String, Vec<(String, String)>, Vec<HtmlNode>);
+
+/// Builds a tree of nodes from the HTML builder as it renders.
+#[derive(Debug)]
+pub struct NodeRecorder {
+    /// Stack of open elements.
+    /// The bottom layer is the top-level list of nodes.
+    stack: Vec<OpenElement>,
+}
+
+impl NodeRecorder {
+    pub fn new() -> Self {
+        NodeRecorder {
+            stack: vec![(String::new(), Vec::new(), Vec::new())],
+        }
+    }
+
+    fn children(&mut self) -> &mut Vec<HtmlNode> {
+        &mut self.stack.last_mut().expect("Node stack is empty").2
+    }
+
+    /// Begins a new element, which further nodes are added to.
+    pub fn open(&mut self, tag: &str) {
+        self.stack.push((str!(tag), Vec::new(), Vec::new()));
+    }
+
+    /// Adds an attribute to the element which was most recently opened.
+    pub fn attribute(&mut self, key: &str, value: String) {
+        debug_assert!(self.stack.len() > 1, "No element is open");
+
+        if let Some((_, attributes, _)) = self.stack.last_mut() {
+            attributes.push((str!(key), value));
+        }
+    }
+
+    /// Ends the current element, adding it to its parent.
+    pub fn close(&mut self) {
+        debug_assert!(self.stack.len() > 1, "No element is open");
+
+        if self.stack.len() > 1 {
+            let (tag, attributes, children) = self.stack.pop().unwrap();
+            push_node(
+                self.children(),
+                HtmlNode::Element {
+                    tag,
+                    attributes,
+                    children,
+                },
+            );
+        }
+    }
+
+    #[inline]
+    pub fn text(&mut self, text: &str) {
+        if !text.is_empty() {
+            push_node(self.children(), HtmlNode::Text(str!(text)));
+        }
+    }
+
+    #[inline]
+    pub fn raw(&mut self, html: &str) {
+        if !html.is_empty() {
+            push_node(self.children(), HtmlNode::Raw(str!(html)));
+        }
+    }
+
+    /// Removes and returns all the top-level nodes.
+    #[inline]
+    pub fn take(&mut self) -> Vec<HtmlNode> {
+        self.replace(Vec::new())
+    }
+
+    /// Replaces the top-level nodes, returning the previous ones.
+    pub fn replace(&mut self, nodes: Vec<HtmlNode>) -> Vec<HtmlNode> {
+        debug_assert_eq!(self.stack.len(), 1, "Elements are still open");
+        mem::replace(&mut self.stack[0].2, nodes)
+    }
+}
+
+/// Adds a node to the list, joining adjacent text or raw nodes.
+fn push_node(nodes: &mut Vec<HtmlNode>, node: HtmlNode) {
+    match (nodes.last_mut(), node) {
+        (Some(HtmlNode::Text(last)), HtmlNode::Text(text)) => last.push_str(&text),
+        (Some(HtmlNode::Raw(last)), HtmlNode::Raw(html)) => last.push_str(&html),
+        (_, node) => nodes.push(node),
+    }
+}
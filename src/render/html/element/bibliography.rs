@@ -28,9 +28,7 @@ pub fn render_bibcite(ctx: &mut HtmlContext, label: &str, brackets: bool) {
         // Valid bibliography reference, render it
         Some((index, contents)) => {
             // TODO make this into a locale template string
-            let reference_string = ctx
-                .handle()
-                .get_message(ctx.language(), "bibliography-reference");
+            let reference_string = ctx.get_message("bibliography-reference");
             let label = format!("{reference_string} {index}.");
 
             // TODO: For now, copied from footnotes
@@ -88,14 +86,12 @@ pub fn render_bibcite(ctx: &mut HtmlContext, label: &str, brackets: bool) {
         }
         None => {
             // We need to produce an error for invalid bibliography references
-            let message = ctx
-                .handle()
-                .get_message(ctx.language(), "bibliography-cite-not-found");
+            let message = ctx.get_message("bibliography-cite-not-found");
 
             ctx.html()
                 .span()
                 .attr(attr!("class" => "wj-error-inline"))
-                .contents(message);
+                .contents(message.as_ref());
         }
     }
 }
@@ -116,11 +112,9 @@ pub fn render_bibliography(
     let title: &str = match title {
         Some(title) => title,
         None => {
-            title_default = ctx
-                .handle()
-                .get_message(ctx.language(), "bibliography-block-title");
+            title_default = ctx.get_message("bibliography-block-title");
 
-            title_default
+            &title_default
         }
     };
 
@@ -0,0 +1,41 @@
+/*
+ * render/html/element/blocked.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::settings::GatedElement;
+
+/// Renders a placeholder for an element not permitted by the settings.
+///
+/// See [`GatedElement`] for which elements these are.
+pub fn render_blocked(ctx: &mut HtmlContext, kind: GatedElement) {
+    warn!(
+        "Element '{}' not permitted by settings, replacing",
+        kind.name()
+    );
+
+    let message = ctx.get_message("element-blocked");
+    ctx.html()
+        .div()
+        .attr(attr!(
+            "class" => "wj-error-block wj-element-blocked",
+            "data-element" => kind.name(),
+        ))
+        .contents(message.as_ref());
+}
@@ -0,0 +1,72 @@
+/*
+ * render/html/element/breadcrumbs.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::data::BreadcrumbType;
+use crate::render::breadcrumbs::build_breadcrumbs;
+
+pub fn render_breadcrumbs(ctx: &mut HtmlContext) {
+    info!("Rendering breadcrumbs");
+
+    let breadcrumbs = build_breadcrumbs(ctx.handle(), ctx.info());
+    let label = ctx.get_message("breadcrumbs");
+
+    ctx.html()
+        .nav()
+        .attr(attr!(
+            "class" => "wj-breadcrumbs",
+            "aria-label" => &label,
+        ))
+        .inner(|ctx| {
+            ctx.html().ol().inner(|ctx| {
+                for breadcrumb in &breadcrumbs {
+                    let class = match breadcrumb.btype {
+                        BreadcrumbType::Category => {
+                            "wj-breadcrumb wj-breadcrumb-category"
+                        }
+                        BreadcrumbType::Parent => "wj-breadcrumb wj-breadcrumb-parent",
+                        BreadcrumbType::Current => "wj-breadcrumb wj-breadcrumb-current",
+                    };
+
+                    ctx.html().li().attr(attr!("class" => class)).inner(|ctx| {
+                        match breadcrumb.btype {
+                            BreadcrumbType::Parent => {
+                                let href = format!("/{}", breadcrumb.slug);
+
+                                ctx.html()
+                                    .a()
+                                    .attr(attr!("href" => &href))
+                                    .contents(&breadcrumb.title);
+                            }
+                            BreadcrumbType::Category => {
+                                ctx.html().span().contents(&breadcrumb.title);
+                            }
+                            BreadcrumbType::Current => {
+                                ctx.html()
+                                    .span()
+                                    .attr(attr!("aria-current" => "page"))
+                                    .contents(&breadcrumb.title);
+                            }
+                        }
+                    });
+                }
+            });
+        });
+}
@@ -0,0 +1,52 @@
+/*
+ * render/html/element/calc.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::expr;
+
+pub fn render_calc(ctx: &mut HtmlContext, expression: &str) {
+    info!("Rendering calc block (expression '{expression}')");
+
+    match expr::evaluate(expression) {
+        Ok(value) => {
+            let value = value.to_string();
+
+            ctx.html()
+                .span()
+                .attr(attr!(
+                    "class" => "wj-calc",
+                    "data-expression" => expression,
+                ))
+                .contents(value);
+        }
+        Err(error) => {
+            warn!("Error evaluating calc expression: {error}");
+            let error = str!(error);
+
+            ctx.html()
+                .span()
+                .attr(attr!(
+                    "class" => "wj-calc wj-error-inline",
+                    "data-expression" => expression,
+                ))
+                .contents(error);
+        }
+    }
+}
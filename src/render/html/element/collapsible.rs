@@ -19,40 +19,19 @@
  */
 
 use super::prelude::*;
-use crate::tree::{AttributeMap, Element};
+use crate::tree::{walk_elements, AttributeMap, Element};
+use std::borrow::Cow;
 
 #[derive(Debug, Copy, Clone)]
 pub struct Collapsible<'a> {
-    elements: &'a [Element<'a>],
-    attributes: &'a AttributeMap<'a>,
-    start_open: bool,
-    show_text: Option<&'a str>,
-    hide_text: Option<&'a str>,
-    show_top: bool,
-    show_bottom: bool,
-}
-
-impl<'a> Collapsible<'a> {
-    #[inline]
-    pub fn new(
-        elements: &'a [Element<'a>],
-        attributes: &'a AttributeMap<'a>,
-        start_open: bool,
-        show_text: Option<&'a str>,
-        hide_text: Option<&'a str>,
-        show_top: bool,
-        show_bottom: bool,
-    ) -> Self {
-        Collapsible {
-            elements,
-            attributes,
-            start_open,
-            show_text,
-            hide_text,
-            show_top,
-            show_bottom,
-        }
-    }
+    pub elements: &'a [Element<'a>],
+    pub attributes: &'a AttributeMap<'a>,
+    pub start_open: bool,
+    pub show_text: Option<&'a str>,
+    pub hide_text: Option<&'a str>,
+    pub show_top: bool,
+    pub show_bottom: bool,
+    pub open_if_anchored: bool,
 }
 
 pub fn render_collapsible(ctx: &mut HtmlContext, collapsible: Collapsible) {
@@ -64,23 +43,33 @@ pub fn render_collapsible(ctx: &mut HtmlContext, collapsible: Collapsible) {
         hide_text,
         show_top,
         show_bottom,
+        open_if_anchored,
     } = collapsible;
 
     info!(
-        "Rendering collapsible (elements length {}, start-open {}, show-text {}, hide-text {}, show-top {}, show-bottom {})",
+        "Rendering collapsible (elements length {}, start-open {}, show-text {}, hide-text {}, show-top {}, show-bottom {}, open-if-anchored {})",
         elements.len(),
         start_open,
         show_text.unwrap_or("<default>"),
         hide_text.unwrap_or("<default>"),
         show_top,
         show_bottom,
+        open_if_anchored,
     );
 
-    let show_text = show_text
-        .unwrap_or_else(|| ctx.handle().get_message(ctx.language(), "collapsible-open"));
+    // List anchors within, so the client knows to open this
+    // when a link to one of them is followed.
+    let anchors = if open_if_anchored {
+        collect_anchors(elements)
+    } else {
+        String::new()
+    };
 
-    let hide_text = hide_text
-        .unwrap_or_else(|| ctx.handle().get_message(ctx.language(), "collapsible-hide"));
+    let show_text =
+        show_text.map_or_else(|| ctx.get_message("collapsible-open"), Cow::Borrowed);
+
+    let hide_text =
+        hide_text.map_or_else(|| ctx.get_message("collapsible-hide"), Cow::Borrowed);
 
     ctx.html()
         .details()
@@ -88,7 +77,9 @@ pub fn render_collapsible(ctx: &mut HtmlContext, collapsible: Collapsible) {
             "class" => "wj-collapsible",
             "open"; if start_open,
             "data-show-top"; if show_top,
-            "data-show-bottom"; if show_bottom;;
+            "data-show-bottom"; if show_bottom,
+            "data-open-if-anchored"; if open_if_anchored,
+            "data-anchors" => &anchors; if !anchors.is_empty();;
             attributes,
         ))
         .inner(|ctx| {
@@ -103,13 +94,13 @@ pub fn render_collapsible(ctx: &mut HtmlContext, collapsible: Collapsible) {
                     ctx.html()
                         .span()
                         .attr(attr!("class" => "wj-collapsible-show-text"))
-                        .contents(show_text);
+                        .contents(show_text.as_ref());
 
                     // Block is unfolded text
                     ctx.html()
                         .span()
                         .attr(attr!("class" => "wj-collapsible-hide-text"))
-                        .contents(hide_text);
+                        .contents(hide_text.as_ref());
                 });
 
             // Content block
@@ -130,8 +121,35 @@ pub fn render_collapsible(ctx: &mut HtmlContext, collapsible: Collapsible) {
                         ctx.html()
                             .span()
                             .attr(attr!("class" => "wj-collapsible-hide-text"))
-                            .contents(hide_text);
+                            .contents(hide_text.as_ref());
                     });
             }
         });
 }
+
+/// Gathers the names of all anchors within these elements, space-separated.
+///
+/// This includes anchors in nested collapsibles, so that each
+/// collapsible containing a linked anchor is opened.
+fn collect_anchors(elements: &[Element]) -> String {
+    let mut anchors = String::new();
+    let mut add = |name: &str| {
+        if !anchors.is_empty() {
+            anchors.push(' ');
+        }
+
+        anchors.push_str(name);
+    };
+
+    walk_elements(elements, &mut |element| {
+        if let Element::AnchorName(name) = element {
+            add(name);
+        }
+
+        if let Some(id) = element.attributes().and_then(|map| map.get().get("id")) {
+            add(id);
+        }
+    });
+
+    anchors
+}
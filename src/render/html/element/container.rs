@@ -19,7 +19,10 @@
  */
 
 use super::prelude::*;
+use crate::render::html::accessibility::has_emphasis;
+use crate::render::html::AccessibilityIssue;
 use crate::tree::{Container, ContainerType, HtmlTag};
+use std::borrow::Cow;
 
 pub fn render_container(ctx: &mut HtmlContext, container: &Container) {
     info!("Rendering container '{}'", container.ctype().name());
@@ -41,7 +44,20 @@ pub fn render_container(ctx: &mut HtmlContext, container: &Container) {
 
 pub fn render_container_internal(ctx: &mut HtmlContext, container: &Container) {
     // Get HTML tag type for this type of container
-    let tag_spec = container.ctype().html_tag(ctx);
+    if let ContainerType::Header(heading) = container.ctype() {
+        ctx.add_heading_level(heading.level.value());
+    }
+
+    let tag_spec = match container.ctype() {
+        ContainerType::Header(heading) if heading.has_toc => {
+            let id = ctx.next_heading_id(container.elements());
+            HtmlTag::with_id(heading.level.html_tag(), id)
+        }
+        ContainerType::Div if ctx.settings().semantic_html && is_floating(container) => {
+            HtmlTag::new("aside")
+        }
+        ctype => ctype.html_tag(ctx),
+    };
 
     // Get correct ID, based on the render setting
     let random_id = choose_id(ctx, &tag_spec);
@@ -72,14 +88,62 @@ pub fn render_container_internal(ctx: &mut HtmlContext, container: &Container) {
 pub fn render_color(ctx: &mut HtmlContext, color: &str, elements: &[Element]) {
     info!("Rendering color container (color '{color}')");
 
+    let color = match &ctx.settings().color_variables {
+        Some(variables) => variables
+            .build(color)
+            .map_or(Cow::Borrowed(color), Cow::Owned),
+        None => Cow::Borrowed(color),
+    };
+
+    if !has_emphasis(elements) {
+        ctx.report_accessibility(|| AccessibilityIssue::ColorOnlyEmphasis {
+            color: str!(color),
+        });
+    }
+
     ctx.html()
         .span()
         .attr(attr!(
-            "style" => "color: " color ";",
+            "style" => "color: " color.as_ref() ";",
         ))
         .contents(elements);
 }
 
+pub fn render_size(ctx: &mut HtmlContext, size: &str, elements: &[Element]) {
+    info!("Rendering size container (size '{size}')");
+
+    let size = ctx.settings().size_limits.clamp(size);
+    if size.is_none() {
+        warn!("Invalid size, rendering contents without it");
+    }
+
+    ctx.html()
+        .span()
+        .attr(attr!(
+            "style" => "font-size: " size.as_deref().unwrap_or_default() ";";
+                if size.is_some(),
+        ))
+        .contents(elements);
+}
+
+/// Whether this container is floated to one side by its inline style.
+fn is_floating(container: &Container) -> bool {
+    let style = match container.attributes().get().get("style") {
+        Some(style) => style,
+        None => return false,
+    };
+
+    style
+        .split(';')
+        .any(|declaration| match declaration.split_once(':') {
+            Some((property, value)) => {
+                property.trim().eq_ignore_ascii_case("float")
+                    && !value.trim().eq_ignore_ascii_case("none")
+            }
+            None => false,
+        })
+}
+
 fn choose_id(ctx: &mut HtmlContext, tag_spec: &HtmlTag) -> Option<String> {
     // If we're in a situation where we want a randomly generated ID
     if matches!(tag_spec, HtmlTag::TagAndId { .. }) && !ctx.settings().use_true_ids {
@@ -27,12 +27,9 @@ pub fn render_date(
     date_format: Option<&str>,
     hover: bool,
 ) {
-    // TEMP
-    if date_format.is_some() {
-        warn!("Time format passed, feature currently not supported!");
-    }
-
     // Get attribute values
+    let date_format = date_format.unwrap_or_else(|| date.default_format());
+    let iso = date.to_iso();
     let timestamp = str!(date.timestamp());
     let delta = str!(date.time_since());
     let (space, hover_class) = if hover {
@@ -41,23 +38,18 @@ pub fn render_date(
         ("", "")
     };
 
-    // Format datetime
-    // TODO handle error
-    let formatted_datetime = match date.format() {
-        Ok(datetime) => datetime,
-        Err(error) => {
-            error!("Error formatting date into string: {error}");
-            str!("<ERROR>")
-        }
-    };
-
     // Build HTML elements
+    //
+    // The format and timestamp are included so the
+    // date can be localized client-side.
     ctx.html()
-        .span()
+        .time()
         .attr(attr!(
             "class" => "wj-date" space hover_class,
+            "datetime" => &iso,
+            "data-format" => date_format,
             "data-timestamp" => &timestamp,
             "data-delta" => &delta,
         ))
-        .contents(formatted_datetime);
+        .contents(date.format(date_format));
 }
@@ -0,0 +1,151 @@
+/*
+ * render/html/element/dimensions.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Helpers for emitting intrinsic sizing on replaced elements.
+//!
+//! If both the width and height of an image or iframe are known ahead of
+//! time, we can tell the browser via `width`, `height`, and `aspect-ratio`,
+//! so that space is reserved for it before it loads.
+
+use crate::tree::AttributeMap;
+use std::num::NonZeroU32;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Dimensions {
+    pub width: NonZeroU32,
+    pub height: NonZeroU32,
+}
+
+impl Dimensions {
+    #[inline]
+    pub fn new(width: u32, height: u32) -> Option<Self> {
+        Some(Dimensions {
+            width: NonZeroU32::new(width)?,
+            height: NonZeroU32::new(height)?,
+        })
+    }
+
+    /// Reads the dimensions from the `width` and `height` attributes.
+    ///
+    /// Only plain pixel values (e.g. `640` or `640px`) are considered,
+    /// relative values like `100%` cannot give an aspect ratio.
+    pub fn from_attributes(attributes: &AttributeMap) -> Option<Self> {
+        let attributes = attributes.get();
+        let width = parse_pixels(attributes.get("width")?)?;
+        let height = parse_pixels(attributes.get("height")?)?;
+
+        Some(Dimensions { width, height })
+    }
+
+    /// Produces the CSS declaration for this aspect ratio.
+    pub fn aspect_ratio_style(self) -> String {
+        format!("aspect-ratio: {} / {};", self.width, self.height)
+    }
+}
+
+/// Holds the stringified attribute values for emission.
+///
+/// Any of `width` or `height` already present in the user's attributes
+/// are left out, since those are copied over as-is.
+#[derive(Debug, Default)]
+pub struct SizeAttributes {
+    pub width: Option<String>,
+    pub height: Option<String>,
+    pub style: Option<String>,
+}
+
+impl SizeAttributes {
+    pub fn new(dimensions: Option<Dimensions>, attributes: &AttributeMap) -> Self {
+        match dimensions {
+            None => SizeAttributes::default(),
+            Some(dimensions) => {
+                let attributes = attributes.get();
+                let width = if attributes.contains_key("width") {
+                    None
+                } else {
+                    Some(dimensions.width.to_string())
+                };
+                let height = if attributes.contains_key("height") {
+                    None
+                } else {
+                    Some(dimensions.height.to_string())
+                };
+
+                SizeAttributes {
+                    width,
+                    height,
+                    style: Some(dimensions.aspect_ratio_style()),
+                }
+            }
+        }
+    }
+
+    #[inline]
+    pub fn width(&self) -> &str {
+        self.width.as_deref().unwrap_or("")
+    }
+
+    #[inline]
+    pub fn height(&self) -> &str {
+        self.height.as_deref().unwrap_or("")
+    }
+
+    #[inline]
+    pub fn style(&self) -> &str {
+        self.style.as_deref().unwrap_or("")
+    }
+}
+
+fn parse_pixels(value: &str) -> Option<NonZeroU32> {
+    let value = value.trim();
+    let value = value.strip_suffix("px").unwrap_or(value).trim_end();
+    value.parse().ok()
+}
+
+#[test]
+fn dimensions() {
+    use std::borrow::Cow;
+
+    macro_rules! check {
+        ($width:expr, $height:expr, $expected:expr $(,)?) => {{
+            let mut attributes = AttributeMap::new();
+            attributes.insert("width", Cow::Borrowed($width));
+            attributes.insert("height", Cow::Borrowed($height));
+
+            let actual = Dimensions::from_attributes(&attributes)
+                .map(|dimensions| dimensions.aspect_ratio_style());
+            let expected: Option<&str> = $expected;
+
+            assert_eq!(
+                actual.as_deref(),
+                expected,
+                "Actual aspect ratio doesn't match expected",
+            );
+        }};
+    }
+
+    check!("640", "480", Some("aspect-ratio: 640 / 480;"));
+    check!("640px", "360px", Some("aspect-ratio: 640 / 360;"));
+    check!(" 300 ", "200 px", Some("aspect-ratio: 300 / 200;"));
+    check!("100%", "480", None);
+    check!("0", "480", None);
+    check!("640", "auto", None);
+    check!("-1", "1", None);
+}
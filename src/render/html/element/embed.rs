@@ -19,13 +19,17 @@
  */
 
 use super::prelude::*;
+use crate::settings::EmbedOutput;
 use crate::tree::Embed;
 
 pub fn render_embed(ctx: &mut HtmlContext, embed: &Embed) {
     info!(
         "Rendering embed (variant '{}', url '{}')",
         embed.name(),
-        embed.direct_url(),
+        embed
+            .direct_url(&ctx.settings().embed_providers)
+            .as_deref()
+            .unwrap_or("<none>"),
     );
 
     ctx.html()
@@ -69,5 +73,40 @@ pub fn render_embed(ctx: &mut HtmlContext, embed: &Embed) {
 
                 ctx.html().script().attr(attr!("src" => &url));
             }
+
+            Embed::Provider {
+                provider: provider_name,
+                id,
+                params,
+            } => {
+                let provider = match ctx.settings().embed_providers.get(provider_name) {
+                    Some(provider) => provider,
+                    None => {
+                        warn!("No embed provider '{provider_name}' registered, skipping");
+                        return;
+                    }
+                };
+
+                let url = provider.build_url(id, params);
+                match provider.output {
+                    EmbedOutput::Iframe => {
+                        ctx.html().iframe().attr(attr!(
+                            "src" => &url,
+                            "frameborder" => "0",
+                            "allowfullscreen",
+                        ));
+                    }
+                    EmbedOutput::Link => {
+                        ctx.html()
+                            .a()
+                            .attr(attr!(
+                                "href" => &url,
+                                "target" => "_blank",
+                                "rel" => "noopener noreferrer",
+                            ))
+                            .contents(&url);
+                    }
+                }
+            }
         });
 }
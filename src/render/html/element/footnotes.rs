@@ -19,20 +19,57 @@
  */
 
 use super::prelude::*;
+use crate::render::text::TextRender;
+use std::num::NonZeroUsize;
+use std::ops::Range;
 
 pub fn render_footnote(ctx: &mut HtmlContext) {
     info!("Rendering footnote reference");
 
     let index = ctx.next_footnote_index();
-    let id = str!(index);
+    render_footnote_marker(ctx, index, None);
+}
+
+pub fn render_footnote_repeat(ctx: &mut HtmlContext, index: NonZeroUsize) {
+    info!("Rendering repeated footnote reference (index {index})");
+
+    let repeat = ctx.next_footnote_repeat(index);
+    render_footnote_marker(ctx, index, Some(repeat));
+}
+
+/// Renders a reference to a footnote.
+///
+/// For repeated references, `repeat` is which reference this is,
+/// starting from 2 since the original is the first.
+fn render_footnote_marker(
+    ctx: &mut HtmlContext,
+    index: NonZeroUsize,
+    repeat: Option<usize>,
+) {
+    let id = str!(ctx.footnote_id(index));
+    let ref_id = match repeat {
+        Some(repeat) => format!("wj-footnote-ref-{id}-{repeat}"),
+        None => format!("wj-footnote-ref-{id}"),
+    };
+    let displayed = ctx.footnote_number(index);
+    let settings = ctx.settings();
+    let use_true_ids = settings.use_true_ids;
+    let marker = settings
+        .footnote_style
+        .label(displayed, &settings.footnote_numbering);
 
     // TODO make this into a locale template string
-    let footnote_string = ctx.handle().get_message(ctx.language(), "footnote");
-    let label = format!("{footnote_string} {index}.");
+    let footnote_string = ctx.get_message("footnote");
+    let number = settings.footnote_numbering.format(displayed);
+    let label = format!("{footnote_string} {number}.");
 
-    let contents = ctx
-        .get_footnote(index)
-        .expect("Footnote index out of bounds from gathered footnote list");
+    let contents = match ctx.get_footnote(index) {
+        Some(contents) => contents,
+        None => {
+            warn!("Footnote index {index} out of bounds from gathered footnote list");
+            &[]
+        }
+    };
 
     ctx.html()
         .span()
@@ -43,11 +80,14 @@ pub fn render_footnote(ctx: &mut HtmlContext) {
                 .element("wj-footnote-ref-marker")
                 .attr(attr!(
                     "class" => "wj-footnote-ref-marker",
+                    "id" => &ref_id; if use_true_ids,
                     "role" => "link",
                     "aria-label" => &label,
+                    "aria-describedby" => &format!("wj-footnote-{id}"); if use_true_ids,
                     "data-id" => &id,
+                    "data-ref" => &str!(repeat.unwrap_or(1)); if repeat.is_some(),
                 ))
-                .contents(&id);
+                .contents(&marker);
 
             // Tooltip shown on hover.
             // Is aria-hidden due to difficulty in getting a simultaneous
@@ -67,30 +107,37 @@ pub fn render_footnote(ctx: &mut HtmlContext) {
                         .contents(&label);
 
                     // Actual tooltip contents
-                    ctx.html()
-                        .span()
-                        .attr(attr!("class" => "wj-footnote-ref-contents"))
-                        .contents(contents);
+                    ctx.render_footnote_contents(index, true, |ctx| {
+                        ctx.html()
+                            .span()
+                            .attr(attr!("class" => "wj-footnote-ref-contents"))
+                            .contents(contents);
+                    });
                 });
         });
 }
 
-pub fn render_footnote_block(ctx: &mut HtmlContext, title: Option<&str>) {
+pub fn render_footnote_block(
+    ctx: &mut HtmlContext,
+    title: Option<&str>,
+    footnotes: Range<usize>,
+) {
     info!(
-        "Rendering footnote block (title {})",
+        "Rendering footnote block (title {}, footnotes {footnotes:?})",
         title.unwrap_or("<default>"),
     );
 
     let title_default;
     let title: &str = match title {
         Some(title) => title,
-        None => {
-            title_default = ctx
-                .handle()
-                .get_message(ctx.language(), "footnote-block-title");
-
-            title_default
-        }
+        None => match ctx.settings().footnote_block_title {
+            Some(ref title) => title,
+            None => {
+                title_default = ctx.get_message("footnote-block-title");
+
+                &title_default
+            }
+        },
     };
 
     ctx.html()
@@ -103,17 +150,28 @@ pub fn render_footnote_block(ctx: &mut HtmlContext, title: Option<&str>) {
                 .contents(title);
 
             ctx.html().ol().inner(|ctx| {
+                let use_true_ids = ctx.settings().use_true_ids;
+                let backlink_label = ctx.get_message("footnote-backlink");
+
                 // TODO make this into a footnote helper method
-                for (index, contents) in ctx.footnotes().iter().enumerate() {
-                    let index = index + 1;
-                    let id = &format!("{index}");
+                let start = footnotes.start;
+                for (index, contents) in
+                    ctx.footnotes()[footnotes.clone()].iter().enumerate()
+                {
+                    let index = NonZeroUsize::new(start + index + 1).unwrap();
+                    let id = &str!(ctx.footnote_id(index));
+                    let number = ctx.footnote_number(index);
+                    let number = ctx.settings().footnote_numbering.format(number);
+                    let preview = footnote_preview(ctx, contents);
 
                     // Build actual footnote item
                     ctx.html()
                         .li()
                         .attr(attr!(
                             "class" => "wj-footnote-list-item",
+                            "id" => &format!("wj-footnote-{id}"); if use_true_ids,
                             "data-id" => id,
+                            "data-footnote-content" => &preview,
                         ))
                         .inner(|ctx| {
                             // Number and clickable anchor
@@ -125,7 +183,7 @@ pub fn render_footnote_block(ctx: &mut HtmlContext, title: Option<&str>) {
                                     "role" => "link",
                                 ))
                                 .inner(|ctx| {
-                                    str_write!(ctx, "{index}");
+                                    ctx.push_escaped(&number);
 
                                     // Period after entry number. Has special class to permit styling.
                                     ctx.html()
@@ -135,12 +193,50 @@ pub fn render_footnote_block(ctx: &mut HtmlContext, title: Option<&str>) {
                                 });
 
                             // Footnote contents
+                            ctx.render_footnote_contents(index, false, |ctx| {
+                                ctx.html()
+                                    .span()
+                                    .attr(attr!(
+                                        "class" => "wj-footnote-list-item-contents",
+                                    ))
+                                    .contents(contents);
+                            });
+
+                            // Clickable link back to the reference
                             ctx.html()
-                                .span()
-                                .attr(attr!("class" => "wj-footnote-list-item-contents"))
-                                .contents(contents);
+                                .element("wj-footnote-list-item-backlink")
+                                .attr(attr!(
+                                    "class" => "wj-footnote-list-item-backlink",
+                                    "role" => "link",
+                                    "aria-label" => &backlink_label,
+                                    "data-id" => id,
+                                ))
+                                .contents("↩");
+
+                            // And to each repeated reference of a named footnote
+                            for repeat in 2..=ctx.footnote_repeat_count(index) + 1 {
+                                ctx.html()
+                                    .element("wj-footnote-list-item-backlink")
+                                    .attr(attr!(
+                                        "class" => "wj-footnote-list-item-backlink",
+                                        "role" => "link",
+                                        "aria-label" => &backlink_label,
+                                        "data-id" => id,
+                                        "data-ref" => &str!(repeat),
+                                    ))
+                                    .contents("↩");
+                            }
                         });
                 }
             });
         });
 }
+
+/// Renders footnote contents as plain text, collapsed onto a single line.
+///
+/// This is used for hover previews, so frontends do not need to
+/// extract it from the rendered footnote block.
+fn footnote_preview(ctx: &HtmlContext, contents: &[Element]) -> String {
+    let text = TextRender.render_partial(contents, ctx.info(), ctx.settings(), 0);
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
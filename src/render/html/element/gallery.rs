@@ -0,0 +1,105 @@
+/*
+ * render/html/element/gallery.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::image::render_image_missing;
+use super::prelude::*;
+use crate::tree::{sort_gallery_images, GalleryImage, GalleryOrder, GallerySize};
+
+pub fn render_gallery(
+    ctx: &mut HtmlContext,
+    images: &[GalleryImage],
+    size: GallerySize,
+    order: Option<GalleryOrder>,
+) {
+    info!(
+        "Rendering gallery (images {}, size {size:?}, order {order:?})",
+        images.len(),
+    );
+
+    ctx.html()
+        .div()
+        .attr(attr!("class" => "wj-gallery " size.html_class()))
+        .inner(|ctx| {
+            for image in sort_gallery_images(images, order) {
+                // Images with titles are captioned figures
+                let tag = if ctx.settings().semantic_html && image.title.is_some() {
+                    "figure"
+                } else {
+                    "div"
+                };
+
+                ctx.html()
+                    .tag(tag)
+                    .attr(attr!("class" => "wj-gallery-item"))
+                    .inner(|ctx| render_gallery_image(ctx, image));
+            }
+        });
+}
+
+fn render_gallery_image(ctx: &mut HtmlContext, image: &GalleryImage) {
+    let GalleryImage {
+        source,
+        link,
+        title,
+        attributes,
+    } = image;
+
+    let url = match ctx
+        .handle()
+        .get_image_link(source, ctx.info(), ctx.settings())
+    {
+        Some(url) => url,
+        None => return render_image_missing(ctx),
+    };
+
+    let build_image = |ctx: &mut HtmlContext| {
+        ctx.html().img().attr(attr!(
+            "class" => "wj-gallery-image",
+            "src" => &url,
+            "alt" => title.as_deref().unwrap_or_default(); if title.is_some(),
+            "crossorigin";;
+            attributes
+        ));
+    };
+
+    match link {
+        Some(link) => {
+            let href = ctx.handle().get_link_url(link, ctx.info());
+            ctx.html()
+                .a()
+                .attr(attr!("href" => &href))
+                .inner(build_image);
+        }
+        None => build_image(ctx),
+    }
+
+    if let Some(title) = title {
+        let tag = if ctx.settings().semantic_html {
+            "figcaption"
+        } else {
+            "div"
+        };
+
+        ctx.html()
+            .tag(tag)
+            .attr(attr!("class" => "wj-gallery-title"))
+            .contents(title.as_ref());
+    }
+}
@@ -18,26 +18,97 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+use super::dimensions::{Dimensions, SizeAttributes};
 use super::prelude::*;
+use crate::settings::HtmlBlockOutput;
 use crate::tree::AttributeMap;
 
 pub fn render_iframe(ctx: &mut HtmlContext, url: &str, attributes: &AttributeMap) {
     info!("Rendering iframe block (url '{url}')");
 
+    if !ctx.settings().sanitization.is_iframe_allowed(url) {
+        warn!("Iframe host not permitted by sanitization policy, replacing");
+
+        let message = ctx.get_message("iframe-blocked");
+        ctx.html()
+            .div()
+            .attr(attr!("class" => "wj-error-block wj-iframe-blocked"))
+            .contents(message.as_ref());
+
+        return;
+    }
+
+    let sandbox = ctx.settings().sanitization.iframe_sandbox();
+    let size = if ctx.settings().emit_aspect_ratio {
+        SizeAttributes::new(Dimensions::from_attributes(attributes), attributes)
+    } else {
+        SizeAttributes::default()
+    };
+
     ctx.html().iframe().attr(attr!(
         "src" => url,
-        "crossorigin";;
+        "crossorigin",
+        "sandbox" => sandbox.as_deref().unwrap_or(""); if sandbox.is_some(),
+        "style" => size.style(); if size.style.is_some();;
         attributes
     ));
 }
 
 pub fn render_html(ctx: &mut HtmlContext, contents: &str) {
-    info!("Rendering html block (submitting to remote for iframe)");
+    let output = ctx.settings().html_block_output;
+    info!("Rendering html block (output {output:?})");
 
-    // Submit HTML to be hosted on wjfiles, then get back its URL for the iframe.
-    let iframe_url = ctx.handle().post_html(ctx.info(), contents);
-    ctx.html().iframe().attr(attr!(
-        "src" => &iframe_url,
-        "crossorigin",
-    ));
+    match output {
+        HtmlBlockOutput::Hosted => {
+            // Submit HTML to be hosted on wjfiles, then get back its URL for the iframe.
+            let iframe_url = ctx.handle().post_html(ctx.info(), contents);
+            ctx.html().iframe().attr(attr!(
+                "src" => &iframe_url,
+                "crossorigin",
+            ));
+        }
+        HtmlBlockOutput::Srcdoc => {
+            // Without a sandbox, srcdoc documents share the page's origin,
+            // so one is always applied here.
+            let sandbox = ctx.settings().sanitization.html_sandbox();
+            ctx.html().iframe().attr(attr!(
+                "srcdoc" => contents,
+                "sandbox" => &sandbox,
+            ));
+        }
+        HtmlBlockOutput::DataUri => {
+            let sandbox = ctx.settings().sanitization.html_sandbox();
+            let url = data_uri(contents);
+            ctx.html().iframe().attr(attr!(
+                "src" => &url,
+                "sandbox" => &sandbox,
+            ));
+        }
+    }
+}
+
+/// Produces a `data:` URI for the given HTML, percent-encoding as needed.
+fn data_uri(html: &str) -> String {
+    let mut url = String::from("data:text/html;charset=utf-8,");
+
+    for byte in html.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                url.push(char::from(byte));
+            }
+            _ => str_write!(url, "%{byte:02X}"),
+        }
+    }
+
+    url
+}
+
+#[test]
+fn test_data_uri() {
+    assert_eq!(data_uri(""), "data:text/html;charset=utf-8,");
+    assert_eq!(
+        data_uri("<b>Hi & bye</b>"),
+        "data:text/html;charset=utf-8,%3Cb%3EHi%20%26%20bye%3C%2Fb%3E",
+    );
+    assert_eq!(data_uri("é"), "data:text/html;charset=utf-8,%C3%A9");
 }
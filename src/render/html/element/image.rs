@@ -18,9 +18,10 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+use super::dimensions::{Dimensions, SizeAttributes};
 use super::prelude::*;
+use crate::render::html::AccessibilityIssue;
 use crate::tree::{AttributeMap, FloatAlignment, ImageSource, LinkLocation};
-use crate::url::normalize_link;
 
 pub fn render_image(
     ctx: &mut HtmlContext,
@@ -52,7 +53,21 @@ pub fn render_image(
 
     match source_url {
         // Found URL
-        Some(url) => render_image_element(ctx, &url, link, alignment, attributes),
+        Some(url) => {
+            let size = if ctx.settings().emit_aspect_ratio {
+                let dimensions = Dimensions::from_attributes(attributes).or_else(|| {
+                    ctx.handle()
+                        .get_image_dimensions(source, ctx.info())
+                        .and_then(|(width, height)| Dimensions::new(width, height))
+                });
+
+                SizeAttributes::new(dimensions, attributes)
+            } else {
+                SizeAttributes::default()
+            };
+
+            render_image_element(ctx, &url, link, alignment, attributes, &size)
+        }
 
         // Missing or error
         None => render_image_missing(ctx),
@@ -65,32 +80,62 @@ fn render_image_element(
     link: &Option<LinkLocation>,
     alignment: Option<FloatAlignment>,
     attributes: &AttributeMap,
+    size: &SizeAttributes,
 ) {
     debug!("Found URL, rendering image (value '{url}')");
 
+    if !attributes.get().contains_key("alt") {
+        ctx.report_accessibility(|| AccessibilityIssue::ImageMissingAlt {
+            source: str!(url),
+        });
+    }
+
     let (space, align_class) = match alignment {
         Some(align) => (" ", align.html_class()),
         None => ("", ""),
     };
 
+    // Floating images are set apart from the page, and aligned ones stand alone
+    let tag = match alignment {
+        Some(FloatAlignment { float: true, .. }) if ctx.settings().semantic_html => {
+            "aside"
+        }
+        Some(_) if ctx.settings().semantic_html => "figure",
+        _ => "div",
+    };
+
     ctx.html()
-        .div()
+        .tag(tag)
         .attr(attr!(
             "class" => "wj-image-container" space align_class,
         ))
         .inner(|ctx| {
+            let images = &ctx.settings().images;
+            let srcset = images.srcset.as_ref().and_then(|srcset| srcset.build(url));
+            let sizes = images
+                .srcset
+                .as_ref()
+                .and_then(|srcset| srcset.sizes.as_deref());
+
             let build_image = |ctx: &mut HtmlContext| {
                 ctx.html().img().attr(attr!(
                     "class" => "wj-image",
                     "src" => url,
-                    "crossorigin";;
+                    "srcset" => srcset.as_deref().unwrap_or_default(); if srcset.is_some(),
+                    "sizes" => sizes.unwrap_or_default(); if srcset.is_some() && sizes.is_some(),
+                    "loading" => "lazy"; if images.lazy_loading,
+                    "decoding" => "async"; if images.async_decoding,
+                    "crossorigin",
+                    "width" => size.width(); if size.width.is_some(),
+                    "height" => size.height(); if size.height.is_some(),
+                    "style" => size.style(); if size.style.is_some();;
                     attributes
                 ));
             };
 
             match link {
                 Some(link) => {
-                    let url = normalize_link(link, ctx.handle());
+                    let url = ctx.handle().get_link_url(link, ctx.info());
                     ctx.html()
                         .a()
                         .attr(attr!("href" => &url))
@@ -101,15 +146,13 @@ fn render_image_element(
         });
 }
 
-fn render_image_missing(ctx: &mut HtmlContext) {
+pub(super) fn render_image_missing(ctx: &mut HtmlContext) {
     debug!("Image URL unresolved, missing or error");
 
-    let message = ctx
-        .handle()
-        .get_message(ctx.language(), "image-context-bad");
+    let message = ctx.get_message("image-context-bad");
 
     ctx.html()
         .div()
         .attr(attr!("class" => "wj-error-block"))
-        .contents(message);
+        .contents(message.as_ref());
 }
@@ -20,6 +20,7 @@
 
 use super::prelude::*;
 use crate::data::PageRef;
+use crate::render::html::memo::{memo_key, replay_backlinks};
 use crate::tree::VariableMap;
 
 pub fn render_include(
@@ -30,7 +31,30 @@ pub fn render_include(
 ) {
     info!("Rendering include (location {location:?})");
     ctx.variables_mut().push_scope(variables);
-    render_elements(ctx, elements);
+
+    // Memoized output is only stored as HTML, not as nodes
+    let key = match ctx.records_nodes() {
+        false => memo_key(ctx, elements),
+        true => None,
+    };
+
+    match key {
+        Some(key) => match ctx.include_memo().get(&key) {
+            Some(range) => {
+                debug!("Reusing memoized include output");
+                replay_backlinks(ctx, elements);
+                ctx.push_rendered(range);
+            }
+            None => {
+                let start = ctx.buffer().len();
+                render_elements(ctx, elements);
+                let end = ctx.buffer().len();
+                ctx.include_memo_mut().insert(key, start..end);
+            }
+        },
+        None => render_elements(ctx, elements),
+    }
+
     ctx.variables_mut().pop_scope();
 }
 
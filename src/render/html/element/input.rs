@@ -37,6 +37,39 @@ pub fn render_radio_button(
     ));
 }
 
+pub fn render_radio_group(
+    ctx: &mut HtmlContext,
+    name: &str,
+    label: Option<&str>,
+    elements: &[Element],
+) {
+    info!(
+        "Creating radio button group (name '{name}', label {})",
+        label.unwrap_or("<none>"),
+    );
+
+    match label {
+        Some(label) => {
+            ctx.html()
+                .fieldset()
+                .attr(attr!("class" => "wj-radio-group"))
+                .inner(|ctx| {
+                    ctx.html().legend().contents(label);
+                    render_elements(ctx, elements);
+                });
+        }
+        None => {
+            ctx.html()
+                .span()
+                .attr(attr!(
+                    "class" => "wj-radio-group",
+                    "role" => "radiogroup",
+                ))
+                .inner(|ctx| render_elements(ctx, elements));
+        }
+    }
+}
+
 pub fn render_checkbox(ctx: &mut HtmlContext, checked: bool, attributes: &AttributeMap) {
     info!("Creating checkbox (checked {checked})");
 
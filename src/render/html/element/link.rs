@@ -19,10 +19,11 @@
  */
 
 use super::prelude::*;
+use crate::render::html::AccessibilityIssue;
+use crate::settings::{LinkRewrite, RedirectMode};
 use crate::tree::{
     AnchorTarget, AttributeMap, Element, LinkLabel, LinkLocation, LinkType,
 };
-use crate::url::normalize_link;
 
 pub fn render_anchor(
     ctx: &mut HtmlContext,
@@ -58,9 +59,10 @@ pub fn render_link(
     let handle = ctx.handle();
 
     // Add to backlinks
-    ctx.add_link(link);
+    ctx.add_link(ltype, link);
 
-    let url = normalize_link(link, ctx.handle());
+    let url = ctx.handle().get_link_url(link, ctx.info());
+    let LinkRewrite { url, rel } = ctx.settings().link_rewrites.apply(ltype, url);
 
     let target_value = match target {
         Some(target) => target.html_attr(),
@@ -76,7 +78,7 @@ pub fn render_link(
             if ctx.page_exists(page) {
                 "wj-link-internal"
             } else {
-                "wj-link-internal wj-link-missing"
+                "wj-link-internal wj-link-missing newpage"
             }
         }
     };
@@ -88,16 +90,38 @@ pub fn render_link(
     };
 
     let site = ctx.info().site.as_ref().to_string();
+    let get_page_title = ctx.page_title_fetcher();
     let mut tag = ctx.html().a();
     tag.attr(attr!(
         "href" => &url,
         "target" => target_value; if target.is_some(),
+        "rel" => rel.unwrap_or_default(); if rel.is_some(),
         "class" => "wj-link " css_class interwiki_class,
         "data-link-type" => ltype.name(),
     ));
 
     // Add <a> internals, i.e. the link name
-    handle.get_link_label(&site, link, label, |label| {
-        tag.contents(label);
+    let mut empty_label = false;
+    handle.get_link_label_with(&site, link, label, get_page_title, |label| {
+        empty_label = label.trim().is_empty();
+        tag.inner(|ctx| ctx.push_text(label));
     });
+
+    // Close the <a> before using the context again
+    drop(tag);
+
+    if empty_label {
+        ctx.report_accessibility(|| AccessibilityIssue::EmptyLinkLabel {
+            url: url.into_owned(),
+        });
+    }
+}
+
+pub fn render_redirect(ctx: &mut HtmlContext, destination: &LinkLocation) {
+    info!("Rendering redirect '{:?}'", destination);
+
+    if ctx.settings().redirect_mode == RedirectMode::MetaRefresh {
+        let url = ctx.handle().get_link_url(destination, ctx.info());
+        ctx.add_meta_refresh(&url);
+    }
 }
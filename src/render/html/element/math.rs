@@ -19,6 +19,7 @@
  */
 
 use super::prelude::*;
+use crate::settings::MathOutput;
 use cfg_if::cfg_if;
 use std::num::NonZeroUsize;
 
@@ -65,11 +66,20 @@ fn render_latex(
         DisplayStyle::Inline => ("span", "wj-math-inline", "wj-error-inline"),
     };
 
+    // Anchor for equation references
+    let id = match index {
+        Some(index) if ctx.settings().use_true_ids && ctx.equation_ids() => {
+            Some(format!("wj-equation-{index}"))
+        }
+        _ => None,
+    };
+
     // Outer container
     ctx.html()
         .tag(html_tag)
         .attr(attr!(
             "class" => "wj-math " wj_type,
+            "id" => id.as_deref().unwrap_or(""); if id.is_some(),
             "data-name" => name.unwrap_or(""); if name.is_some(),
         ))
         .inner(|ctx| {
@@ -99,37 +109,48 @@ fn render_latex(
                     });
             }
 
-            // Add LaTeX source (hidden)
-            // Can't use a pre tag because that won't work for inline tags
-            ctx.html()
-                .code()
-                .attr(attr!(
-                    "class" => "wj-math-source wj-hidden",
-                    "aria-hidden" => "true",
-                ))
-                .contents(latex_source);
-
-            // Add generated MathML
-            cfg_if! {
-                if #[cfg(feature = "mathml")] {
-                    match latex_to_mathml(latex_source, display) {
-                        Ok(mathml) => {
-                            info!("Processed LaTeX -> MathML");
-
-                            // Inject MathML elements
-                            ctx.html()
-                                .element("wj-math-ml")
-                                .attr(attr!("class" => "wj-math-ml"))
-                                .inner(|ctx| ctx.push_raw_str(&mathml));
-                        }
-                        Err(error) => {
-                            warn!("Error processing LaTeX -> MathML: {error}");
-                            let error = str!(error);
-
-                            ctx.html()
-                                .span()
-                                .attr(attr!("class" => _error_type))
-                                .contents(error);
+            match ctx.settings().math_output {
+                // Leave TeX as-is, for typesetting on the client
+                MathOutput::Source => {
+                    ctx.html()
+                        .span()
+                        .attr(attr!("class" => "math"))
+                        .contents(latex_source);
+                }
+                MathOutput::MathMl => {
+                    // Add LaTeX source (hidden)
+                    // Can't use a pre tag because that won't work for inline tags
+                    ctx.html()
+                        .code()
+                        .attr(attr!(
+                            "class" => "wj-math-source wj-hidden",
+                            "aria-hidden" => "true",
+                        ))
+                        .contents(latex_source);
+
+                    // Add generated MathML
+                    cfg_if! {
+                        if #[cfg(feature = "mathml")] {
+                            match latex_to_mathml(latex_source, display) {
+                                Ok(mathml) => {
+                                    info!("Processed LaTeX -> MathML");
+
+                                    // Inject MathML elements
+                                    ctx.html()
+                                        .element("wj-math-ml")
+                                        .attr(attr!("class" => "wj-math-ml"))
+                                        .inner(|ctx| ctx.push_raw_str(&mathml));
+                                }
+                                Err(error) => {
+                                    warn!("Error processing LaTeX -> MathML: {error}");
+                                    let error = str!(error);
+
+                                    ctx.html()
+                                        .span()
+                                        .attr(attr!("class" => _error_type))
+                                        .contents(error);
+                                }
+                            }
                         }
                     }
                 }
@@ -140,19 +161,39 @@ fn render_latex(
 pub fn render_equation_reference(ctx: &mut HtmlContext, name: &str) {
     info!("Rendering equation reference (name '{name}')");
 
+    let number = ctx.get_equation_number(name);
+    let use_true_ids = ctx.settings().use_true_ids;
+
     ctx.html()
         .span()
         .attr(attr!("class" => "wj-equation-ref"))
         .inner(|ctx| {
-            // Equation marker that is hoverable
-            ctx.html()
-                .element("wj-equation-ref-marker")
-                .attr(attr!(
-                    "class" => "wj-equation-ref-marker",
-                    "type" => "button",
-                    "data-name" => name,
-                ))
-                .contents(name);
+            match number {
+                // Resolved, link to the numbered equation
+                Some(number) => {
+                    ctx.html()
+                        .a()
+                        .attr(attr!(
+                            "class" => "wj-equation-ref-marker",
+                            "href" => &format!("#wj-equation-{number}"); if use_true_ids,
+                            "data-name" => name,
+                            "data-id" => &str!(number),
+                        ))
+                        .contents(format!("({number})"));
+                }
+
+                // Equation marker that is hoverable
+                None => {
+                    ctx.html()
+                        .element("wj-equation-ref-marker")
+                        .attr(attr!(
+                            "class" => "wj-equation-ref-marker",
+                            "type" => "button",
+                            "data-name" => name,
+                        ))
+                        .contents(name);
+                }
+            }
 
             // Tooltip shown on hover.
             ctx.html().span().attr(attr!(
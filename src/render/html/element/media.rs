@@ -0,0 +1,90 @@
+/*
+ * render/html/element/media.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+use crate::tree::{AttributeMap, ImageSource, MediaFlags, MediaType};
+
+pub fn render_media(
+    ctx: &mut HtmlContext,
+    media_type: MediaType,
+    sources: &[ImageSource],
+    caption: Option<&str>,
+    flags: MediaFlags,
+    attributes: &AttributeMap,
+) {
+    info!(
+        "Rendering media element (type {}, sources {}, caption {})",
+        media_type.name(),
+        sources.len(),
+        caption.unwrap_or("<none>"),
+    );
+
+    // Skip any sources which cannot be used here
+    let urls = sources
+        .iter()
+        .filter_map(|source| {
+            ctx.handle()
+                .get_image_link(source, ctx.info(), ctx.settings())
+        })
+        .collect::<Vec<_>>();
+
+    if urls.is_empty() {
+        debug!("No media URLs resolved, rendering error");
+
+        let message = ctx.get_message("media-context-bad");
+        ctx.html()
+            .div()
+            .attr(attr!("class" => "wj-error-block"))
+            .contents(message.as_ref());
+
+        return;
+    }
+
+    let preload = ctx.settings().media_preload.value();
+    let class = match media_type {
+        MediaType::Audio => "wj-media wj-media-audio",
+        MediaType::Video => "wj-media wj-media-video",
+    };
+
+    ctx.html()
+        .figure()
+        .attr(attr!("class" => class))
+        .inner(|ctx| {
+            ctx.html()
+                .tag(media_type.name())
+                .attr(attr!(
+                    "preload" => preload,
+                    "controls"; if flags.controls,
+                    "autoplay"; if flags.autoplay,
+                    "loop"; if flags.looping,
+                    "muted"; if flags.muted;;
+                    attributes
+                ))
+                .inner(|ctx| {
+                    for url in &urls {
+                        ctx.html().source().attr(attr!("src" => url));
+                    }
+                });
+
+            if let Some(caption) = caption {
+                ctx.html().figcaption().contents(caption);
+            }
+        });
+}
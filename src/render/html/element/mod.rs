@@ -21,12 +21,17 @@
 //! Module that implements HTML rendering for `Element` and its children.
 
 mod bibliography;
+mod blocked;
+mod breadcrumbs;
+mod calc;
 mod collapsible;
 mod container;
 mod date;
 mod definition_list;
+mod dimensions;
 mod embed;
 mod footnotes;
+mod gallery;
 mod iframe;
 mod image;
 mod include;
@@ -34,6 +39,10 @@ mod input;
 mod link;
 mod list;
 mod math;
+mod media;
+mod quote;
+mod rate;
+mod section;
 mod style;
 mod table;
 mod tabs;
@@ -50,28 +59,42 @@ mod prelude {
 }
 
 use self::bibliography::{render_bibcite, render_bibliography};
+use self::blocked::render_blocked;
+pub use self::breadcrumbs::render_breadcrumbs;
+use self::calc::render_calc;
 use self::collapsible::{render_collapsible, Collapsible};
-use self::container::{render_color, render_container};
+use self::container::{render_color, render_container, render_size};
 use self::date::render_date;
 use self::definition_list::render_definition_list;
 use self::embed::render_embed;
-use self::footnotes::{render_footnote, render_footnote_block};
+pub use self::footnotes::render_footnote_block;
+use self::footnotes::{render_footnote, render_footnote_repeat};
+use self::gallery::render_gallery;
 use self::iframe::{render_html, render_iframe};
 use self::image::render_image;
 use self::include::{render_include, render_variable};
-use self::input::{render_checkbox, render_radio_button};
-use self::link::{render_anchor, render_link};
+use self::input::{render_checkbox, render_radio_button, render_radio_group};
+use self::link::{render_anchor, render_link, render_redirect};
 use self::list::render_list;
 use self::math::{render_equation_reference, render_math_block, render_math_inline};
+use self::media::render_media;
+use self::quote::render_quote;
+use self::rate::render_rate;
+pub use self::section::render_section;
 use self::style::render_style;
+pub use self::style::scope_class;
 use self::table::render_table;
 use self::tabs::render_tabview;
-use self::text::{render_code, render_email, render_wikitext_raw};
-use self::toc::render_table_of_contents;
+use self::text::{
+    render_code, render_code_blob, render_email, render_html_entity, render_wikitext_raw,
+};
+pub use self::toc::render_table_of_contents;
 use self::user::render_user;
 use super::attributes::AddedAttributes;
 use super::HtmlContext;
-use crate::tree::Element;
+use crate::render::metrics::record_element;
+use crate::settings::GatedElement;
+use crate::tree::{Element, Module};
 use ref_map::*;
 
 pub fn render_elements(ctx: &mut HtmlContext, elements: &[Element]) {
@@ -89,13 +112,38 @@ pub fn render_element(ctx: &mut HtmlContext, element: &Element) {
         };
     }
 
+    enter_span!(
+        "render-element",
+        renderer = "html",
+        element = element.name()
+    );
     info!("Rendering element '{}'", element.name());
 
+    if ctx.settings().cancellation.is_cancelled() {
+        debug!("Rendering cancelled, skipping element");
+        return;
+    }
+
+    record_element();
+
+    if let Some(kind) = GatedElement::from_element(element) {
+        if !kind.is_allowed(ctx.settings()) {
+            render_blocked(ctx, kind);
+            return;
+        }
+    }
+
     match element {
         Element::Container(container) => render_container(ctx, container),
-        Element::Module(module) => ctx.handle().render_module(ctx.buffer(), module),
-        Element::Text(text) => ctx.push_escaped(text),
+        Element::Module(Module::Rate) => render_rate(ctx),
+        Element::Module(module) => {
+            let mut html = String::new();
+            ctx.handle().render_module(&mut html, module);
+            ctx.push_raw_str(&html);
+        }
+        Element::Text(text) => ctx.push_text(text),
         Element::Raw(text) => render_wikitext_raw(ctx, text),
+        Element::HtmlEntity(name) => render_html_entity(ctx, name),
         Element::Variable(name) => render_variable(ctx, name),
         Element::Email(email) => render_email(ctx, email),
         Element::Table(table) => render_table(ctx, table),
@@ -114,12 +162,32 @@ pub fn render_element(ctx: &mut HtmlContext, element: &Element) {
             label,
             target,
         } => render_link(ctx, link, label, *target, *ltype),
+        Element::Redirect { destination } => render_redirect(ctx, destination),
         Element::Image {
             source,
             link,
             alignment,
             attributes,
         } => render_image(ctx, source, link, *alignment, attributes),
+        Element::Media {
+            media_type,
+            sources,
+            caption,
+            flags,
+            attributes,
+        } => render_media(
+            ctx,
+            *media_type,
+            sources,
+            ref_cow!(caption),
+            *flags,
+            attributes,
+        ),
+        Element::Gallery {
+            images,
+            size,
+            order,
+        } => render_gallery(ctx, images, *size, *order),
         Element::List {
             ltype,
             items,
@@ -130,7 +198,13 @@ pub fn render_element(ctx: &mut HtmlContext, element: &Element) {
             name,
             checked,
             attributes,
+            ..
         } => render_radio_button(ctx, name, *checked, attributes),
+        Element::RadioGroup {
+            name,
+            label,
+            elements,
+        } => render_radio_group(ctx, name, ref_cow!(label), elements),
         Element::CheckBox {
             checked,
             attributes,
@@ -143,25 +217,43 @@ pub fn render_element(ctx: &mut HtmlContext, element: &Element) {
             hide_text,
             show_top,
             show_bottom,
+            open_if_anchored,
         } => render_collapsible(
             ctx,
-            Collapsible::new(
+            Collapsible {
                 elements,
                 attributes,
-                *start_open,
-                ref_cow!(show_text),
-                ref_cow!(hide_text),
-                *show_top,
-                *show_bottom,
-            ),
+                start_open: *start_open,
+                show_text: ref_cow!(show_text),
+                hide_text: ref_cow!(hide_text),
+                show_top: *show_top,
+                show_bottom: *show_bottom,
+                open_if_anchored: *open_if_anchored,
+            },
         ),
-        Element::TableOfContents { align, attributes } => {
-            render_table_of_contents(ctx, *align, attributes)
+        Element::Quote {
+            attribution,
+            elements,
+        } => render_quote(ctx, ref_cow!(attribution), elements),
+        Element::TableOfContents {
+            align,
+            attributes,
+            min_depth,
+            max_depth,
+        } => {
+            if ctx.settings().fragment_placement.inline() {
+                render_table_of_contents(ctx, *align, attributes, *min_depth, *max_depth);
+            }
         }
         Element::Footnote => render_footnote(ctx),
+        Element::FootnoteRepeat(index) => render_footnote_repeat(ctx, *index),
         Element::FootnoteBlock { title, hide } => {
-            if !(*hide || ctx.footnotes().is_empty()) {
-                render_footnote_block(ctx, ref_cow!(title));
+            if ctx.settings().fragment_placement.inline() {
+                let footnotes = ctx.next_footnote_block_range();
+
+                if !(*hide || footnotes.is_empty()) {
+                    render_footnote_block(ctx, ref_cow!(title), footnotes);
+                }
             }
         }
         Element::BibliographyCite { label, brackets } => {
@@ -181,13 +273,18 @@ pub fn render_element(ctx: &mut HtmlContext, element: &Element) {
             hover,
         } => render_date(ctx, *value, ref_cow!(format), *hover),
         Element::Color { color, elements } => render_color(ctx, color, elements),
+        Element::Size { size, elements } => render_size(ctx, size, elements),
         Element::Code { contents, language } => {
             render_code(ctx, ref_cow!(language), contents)
         }
+        Element::CodeBlob { blob, language, .. } => {
+            render_code_blob(ctx, ref_cow!(language), blob)
+        }
         Element::Math { name, latex_source } => {
             render_math_block(ctx, ref_cow!(name), latex_source)
         }
         Element::MathInline { latex_source } => render_math_inline(ctx, latex_source),
+        Element::Calc { expression } => render_calc(ctx, expression),
         Element::EquationReference(name) => render_equation_reference(ctx, name),
         Element::Embed(embed) => render_embed(ctx, embed),
         Element::Html { contents } => render_html(ctx, contents),
@@ -217,6 +314,8 @@ pub fn render_element(ctx: &mut HtmlContext, element: &Element) {
         Element::HorizontalRule => {
             ctx.html().hr();
         }
-        Element::Partial(_) => panic!("Encountered partial element during parsing"),
+        Element::Partial(_) => {
+            error!("Encountered partial element during rendering, skipping");
+        }
     }
 }
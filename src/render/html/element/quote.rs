@@ -0,0 +1,57 @@
+/*
+ * render/html/element/quote.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+
+pub fn render_quote(
+    ctx: &mut HtmlContext,
+    attribution: Option<&str>,
+    elements: &[Element],
+) {
+    info!(
+        "Rendering quote (attribution '{}', elements length {})",
+        attribution.unwrap_or("<none>"),
+        elements.len(),
+    );
+
+    ctx.html()
+        .blockquote()
+        .attr(attr!("class" => "wj-quote"))
+        .inner(|ctx| {
+            // Header naming who is being quoted
+            if let Some(attribution) = attribution {
+                let wrote = ctx.get_message("quote-wrote");
+
+                ctx.html()
+                    .div()
+                    .attr(attr!("class" => "wj-quote-header"))
+                    .inner(|ctx| {
+                        ctx.html().cite().contents(attribution);
+                        ctx.push_raw(' ');
+                        ctx.push_escaped(&wrote);
+                    });
+            }
+
+            ctx.html()
+                .div()
+                .attr(attr!("class" => "wj-quote-content"))
+                .contents(elements);
+        });
+}
@@ -0,0 +1,70 @@
+/*
+ * render/html/element/rate.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::prelude::*;
+
+/// The vote buttons in the rating widget.
+///
+/// Each is the message key for its title, its class, its vote value, and its label.
+const VOTE_BUTTONS: [(&str, &str, &str, &str); 3] = [
+    ("rate-up", "wj-rate-up", "1", "+"),
+    ("rate-down", "wj-rate-down", "-1", "\u{2212}"),
+    ("rate-cancel", "wj-rate-cancel", "0", "x"),
+];
+
+pub fn render_rate(ctx: &mut HtmlContext) {
+    info!("Rendering rate module");
+
+    let score = ctx.info().score.to_rating_string();
+    let label = ctx.get_message("rating");
+
+    // Voting is handled by the frontend, using the data attributes
+    ctx.html()
+        .div()
+        .attr(attr!("class" => "wj-rate"))
+        .inner(|ctx| {
+            ctx.html()
+                .span()
+                .attr(attr!("class" => "wj-rate-points"))
+                .inner(|ctx| {
+                    ctx.push_escaped(&label);
+                    ctx.push_raw_str(": ");
+
+                    ctx.html()
+                        .span()
+                        .attr(attr!("class" => "wj-rate-score"))
+                        .contents(&score);
+                });
+
+            for (message, class, vote, text) in VOTE_BUTTONS {
+                let title = ctx.get_message(message);
+
+                ctx.html()
+                    .a()
+                    .attr(attr!(
+                        "class" => "wj-rate-vote " class,
+                        "role" => "button",
+                        "title" => &title,
+                        "data-vote" => vote,
+                    ))
+                    .contents(text);
+            }
+        });
+}
@@ -0,0 +1,79 @@
+/*
+ * render/html/element/section.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Grouping of headings and their content into `<section>`s.
+//!
+//! A section begins at a heading, and continues until the next heading
+//! of the same or a higher level. Deeper headings form nested sections.
+
+use super::prelude::*;
+use crate::tree::ContainerType;
+
+/// Renders elements, grouping each heading and its content into a `<section>`.
+pub fn render_sections(ctx: &mut HtmlContext, elements: &[Element]) {
+    let mut index = 0;
+    while index < elements.len() {
+        index = render_section(ctx, elements, index);
+    }
+}
+
+/// Renders the element at this index, or the whole section if it's a heading.
+///
+/// Returns the index of the next element to render.
+pub fn render_section(
+    ctx: &mut HtmlContext,
+    elements: &[Element],
+    index: usize,
+) -> usize {
+    let level = match heading_level(&elements[index]) {
+        Some(level) => level,
+        None => {
+            render_element(ctx, &elements[index]);
+            return index + 1;
+        }
+    };
+
+    let end = elements[index + 1..]
+        .iter()
+        .position(|element| heading_level(element).is_some_and(|next| next <= level))
+        .map_or(elements.len(), |offset| index + 1 + offset);
+
+    debug!(
+        "Rendering section (level {level}, {} elements)",
+        end - index
+    );
+
+    ctx.html().section().inner(|ctx| {
+        render_element(ctx, &elements[index]);
+        render_sections(ctx, &elements[index + 1..end]);
+    });
+
+    end
+}
+
+fn heading_level(element: &Element) -> Option<u8> {
+    match element {
+        Element::Container(container) => match container.ctype() {
+            ContainerType::Header(heading) => Some(heading.level.value()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
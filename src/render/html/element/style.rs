@@ -19,7 +19,14 @@
  */
 
 use super::prelude::*;
+use crate::data::PageInfo;
+use crate::settings::SanitizationPolicy;
+use cssparser::CowRcStr;
+use parcel_css::declaration::DeclarationBlock;
+use parcel_css::properties::Property;
+use parcel_css::rules::{CssRule, CssRuleList};
 use parcel_css::stylesheet::{ParserOptions, PrinterOptions, StyleSheet};
+use parcel_selectors::parser::{Combinator, Component, Selector, SelectorImpl};
 
 pub fn render_style(ctx: &mut HtmlContext, input_css: &str) {
     let minify = ctx.settings().minify_css;
@@ -35,9 +42,21 @@ pub fn render_style(ctx: &mut HtmlContext, input_css: &str) {
     };
 
     info!("Parsing input CSS ({} bytes)", input_css.len());
-    let stylesheet = StyleSheet::parse(input_css, parser_options)
+    let mut stylesheet = StyleSheet::parse(input_css, parser_options)
         .expect("Produced error with recovery enabled");
 
+    let policy = &ctx.settings().sanitization;
+    if !policy.blocked_css.is_empty() {
+        debug!("Removing CSS blocked by sanitization policy");
+        sanitize_rules(&mut stylesheet.rules, policy);
+    }
+
+    if ctx.settings().scope_css {
+        let class = scope_class(ctx.info());
+        debug!("Scoping CSS selectors to class '{class}'");
+        scope_rules(&mut stylesheet.rules, &class);
+    }
+
     debug!("Rendering CSS into HTML (minify: {minify})");
     let output_css = match stylesheet.to_css(print_options) {
         Ok(output) => output.code,
@@ -56,3 +75,128 @@ pub fn render_style(ctx: &mut HtmlContext, input_css: &str) {
         ctx.push_raw_str(&output_css);
     });
 }
+
+/// Produces the class used to scope CSS to this page's body.
+///
+/// Characters in the page or category name which are not valid
+/// in a class name, such as spaces, are escaped as `_` followed by
+/// the hex digits of each byte, and `_` itself is escaped as `__`.
+/// The category is separated from the page by `_-`, which an escaped
+/// name never contains, so each page has a distinct class.
+pub fn scope_class(info: &PageInfo) -> String {
+    let mut class = str!("wj-page-");
+    if let Some(ref category) = info.category {
+        push_class_name(&mut class, category);
+        class.push_str("_-");
+    }
+
+    push_class_name(&mut class, &info.page);
+    class
+}
+
+fn push_class_name(class: &mut String, name: &str) {
+    for c in name.chars() {
+        match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' => class.push(c),
+            '_' => class.push_str("__"),
+            _ => {
+                let mut buffer = [0; 4];
+                for byte in c.encode_utf8(&mut buffer).bytes() {
+                    str_write!(class, "_{byte:02x}");
+                }
+            }
+        }
+    }
+}
+
+/// Removes declarations blocked by the sanitization policy from these rules.
+///
+/// This is the stylesheet counterpart to [`SanitizationPolicy::sanitize_style()`],
+/// and applies to nested rules as well.
+fn sanitize_rules(rules: &mut CssRuleList, policy: &SanitizationPolicy) {
+    for rule in &mut rules.0 {
+        match rule {
+            CssRule::Style(rule) => {
+                sanitize_declarations(&mut rule.declarations, policy);
+                sanitize_rules(&mut rule.rules, policy);
+            }
+            CssRule::Media(rule) => sanitize_rules(&mut rule.rules, policy),
+            CssRule::Supports(rule) => sanitize_rules(&mut rule.rules, policy),
+            CssRule::LayerBlock(rule) => sanitize_rules(&mut rule.rules, policy),
+            CssRule::Container(rule) => sanitize_rules(&mut rule.rules, policy),
+            CssRule::MozDocument(rule) => sanitize_rules(&mut rule.rules, policy),
+            CssRule::Nesting(rule) => {
+                sanitize_declarations(&mut rule.style.declarations, policy);
+                sanitize_rules(&mut rule.style.rules, policy);
+            }
+            CssRule::Page(rule) => sanitize_declarations(&mut rule.declarations, policy),
+            _ => (),
+        }
+    }
+}
+
+fn sanitize_declarations(block: &mut DeclarationBlock, policy: &SanitizationPolicy) {
+    let is_allowed = |property: &Property, important| match property
+        .to_css_string(important, PrinterOptions::default())
+    {
+        Ok(declaration) => !policy.is_declaration_blocked(&declaration),
+        Err(_) => false,
+    };
+
+    block
+        .declarations
+        .retain(|property| is_allowed(property, false));
+    block
+        .important_declarations
+        .retain(|property| is_allowed(property, true));
+}
+
+/// Prefixes every selector in these rules with the given class.
+///
+/// Rules nested in at-rules such as `@media` are also scoped.
+/// Nested style rules are left alone, since they are relative
+/// to their (already scoped) parent.
+///
+/// Imported stylesheets cannot be scoped, so `@import` rules are removed.
+fn scope_rules(rules: &mut CssRuleList, class: &str) {
+    rules.0.retain(|rule| match rule {
+        CssRule::Import(rule) => {
+            warn!("Removing unscoped stylesheet import of '{}'", rule.url);
+            false
+        }
+        _ => true,
+    });
+
+    for rule in &mut rules.0 {
+        match rule {
+            CssRule::Style(rule) => {
+                for selector in rule.selectors.0.iter_mut() {
+                    *selector = scope_selector(selector, class);
+                }
+            }
+            CssRule::Media(rule) => scope_rules(&mut rule.rules, class),
+            CssRule::Supports(rule) => scope_rules(&mut rule.rules, class),
+            CssRule::LayerBlock(rule) => scope_rules(&mut rule.rules, class),
+            CssRule::Container(rule) => scope_rules(&mut rule.rules, class),
+            CssRule::MozDocument(rule) => scope_rules(&mut rule.rules, class),
+            _ => (),
+        }
+    }
+}
+
+fn scope_selector<'i, Impl>(
+    selector: &Selector<'i, Impl>,
+    class: &str,
+) -> Selector<'i, Impl>
+where
+    Impl: SelectorImpl<'i>,
+    Impl::Identifier: From<CowRcStr<'i>>,
+{
+    let mut components = vec![
+        Component::Class(CowRcStr::from(str!(class)).into()),
+        Component::Combinator(Combinator::Descendant),
+    ];
+
+    components.extend(selector.iter_raw_parse_order_from(0).cloned());
+    Selector::from_vec2(components)
+}
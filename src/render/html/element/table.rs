@@ -43,9 +43,11 @@ pub fn render_table(ctx: &mut HtmlContext, table: &Table) {
                             // Each cell in a row
                             for cell in &row.cells {
                                 let elements: &[Element] = &cell.elements;
-                                let align_class = match cell.align {
-                                    Some(align) => align.html_class(),
-                                    None => "",
+                                let (align_class, align_style) = match cell.align {
+                                    Some(align) => {
+                                        (align.html_class(), align.html_style())
+                                    }
+                                    None => ("", ""),
                                 };
 
                                 if cell.column_span > value_one {
@@ -62,6 +64,8 @@ pub fn render_table(ctx: &mut HtmlContext, table: &Table) {
 
                                         // Add alignment if specified
                                         "class" => align_class;
+                                            if cell.align.is_some(),
+                                        "style" => align_style;
                                             if cell.align.is_some();;
 
                                         &cell.attributes,
@@ -19,6 +19,8 @@
  */
 
 use super::prelude::*;
+use crate::entity::find_entity;
+use crate::render::html::highlight::highlight_code;
 
 pub fn render_wikitext_raw(ctx: &mut HtmlContext, text: &str) {
     info!("Escaping raw string '{text}'");
@@ -31,6 +33,20 @@ pub fn render_wikitext_raw(ctx: &mut HtmlContext, text: &str) {
         .contents(text);
 }
 
+pub fn render_html_entity(ctx: &mut HtmlContext, name: &str) {
+    info!("Rendering HTML entity '{name}'");
+
+    // Only known entities are passed through, anything else is escaped
+    if find_entity(name).is_some() {
+        ctx.push_raw('&');
+        ctx.push_raw_str(name);
+        ctx.push_raw(';');
+    } else {
+        warn!("Unknown HTML entity '{name}', escaping");
+        ctx.push_escaped(&format!("&{name};"));
+    }
+}
+
 pub fn render_email(ctx: &mut HtmlContext, email: &str) {
     info!("Rendering email address '{email}'");
 
@@ -51,6 +67,46 @@ pub fn render_code(ctx: &mut HtmlContext, language: Option<&str>, contents: &str
     let index = ctx.next_code_snippet_index();
     ctx.handle().post_code(index, contents);
 
+    // Highlight on the server, if enabled and the language is known
+    let highlighted = match language {
+        Some(language) if ctx.settings().highlight_code => {
+            highlight_code(language, contents)
+        }
+        _ => None,
+    };
+
+    render_code_block(ctx, language, |ctx| match highlighted {
+        Some(ref html) => {
+            ctx.html().code().inner(|ctx| ctx.push_raw_str(html));
+        }
+        None => {
+            ctx.html().code().contents(contents);
+        }
+    });
+}
+
+pub fn render_code_blob(ctx: &mut HtmlContext, language: Option<&str>, blob: &str) {
+    info!(
+        "Rendering out-of-line code block (blob {blob}, language {})",
+        language.unwrap_or("<none>"),
+    );
+    let index = ctx.next_code_snippet_index();
+    ctx.handle().post_code_blob(index, blob);
+
+    render_code_block(ctx, language, |ctx| {
+        ctx.html().code().inner(|ctx| {
+            let handle = ctx.handle();
+            if !handle.stream_blob(blob, |chunk| ctx.push_escaped(chunk)) {
+                warn!("Unable to load code block body from blob '{blob}'");
+            }
+        });
+    });
+}
+
+fn render_code_block<F>(ctx: &mut HtmlContext, language: Option<&str>, mut body: F)
+where
+    F: FnMut(&mut HtmlContext),
+{
     let class = {
         let mut class = format!("wj-code wj-language-{}", language.unwrap_or("none"));
         class.make_ascii_lowercase();
@@ -68,9 +124,7 @@ pub fn render_code(ctx: &mut HtmlContext, language: Option<&str>, contents: &str
                     "class" => "wj-code-panel",
                 ))
                 .inner(|ctx| {
-                    let button_title = ctx
-                        .handle()
-                        .get_message(ctx.language(), "button-copy-clipboard");
+                    let button_title = ctx.get_message("button-copy-clipboard");
 
                     // Copy to clipboard button
                     ctx.html()
@@ -78,7 +132,7 @@ pub fn render_code(ctx: &mut HtmlContext, language: Option<&str>, contents: &str
                         .attr(attr!(
                             "type" => "button",
                             "class" => "wj-code-copy",
-                            "title" => button_title,
+                            "title" => &button_title,
                         ))
                         .inner(|ctx| {
                             ctx.html().sprite("wj-clipboard");
@@ -96,8 +150,6 @@ pub fn render_code(ctx: &mut HtmlContext, language: Option<&str>, contents: &str
                 });
 
             // Code block containing highlighted contents
-            ctx.html().pre().inner(|ctx| {
-                ctx.html().code().contents(contents);
-            });
+            ctx.html().pre().inner(&mut body);
         });
 }
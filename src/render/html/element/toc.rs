@@ -19,12 +19,15 @@
  */
 
 use super::prelude::*;
+use crate::render::filter_table_of_contents;
 use crate::tree::{Alignment, AttributeMap, FloatAlignment};
 
 pub fn render_table_of_contents(
     ctx: &mut HtmlContext,
     align: Option<Alignment>,
     attributes: &AttributeMap,
+    min_depth: Option<u8>,
+    max_depth: Option<u8>,
 ) {
     info!("Creating table of contents");
     let use_true_ids = ctx.settings().use_true_ids;
@@ -37,8 +40,15 @@ pub fn render_table_of_contents(
         }
     };
 
+    // Floating tables of contents are set apart from the page
+    let tag = if ctx.settings().semantic_html && align.is_some() {
+        "aside"
+    } else {
+        "div"
+    };
+
     ctx.html()
-        .div()
+        .tag(tag)
         .attr(attr!(
             "id" => "wj-toc"; if use_true_ids,
             "class" => class_value; if align.is_some();;
@@ -58,21 +68,20 @@ pub fn render_table_of_contents(
                 });
 
             // TOC Heading
-            let table_of_contents_title = ctx
-                .handle()
-                .get_message(ctx.language(), "table-of-contents");
+            let table_of_contents_title = ctx.get_message("table-of-contents");
 
             ctx.html()
                 .div()
                 .attr(attr!("class" => "title"))
-                .contents(table_of_contents_title);
+                .contents(table_of_contents_title.as_ref());
 
             // TOC List
-            let table_of_contents = ctx.table_of_contents();
+            let table_of_contents =
+                filter_table_of_contents(ctx.table_of_contents(), min_depth, max_depth);
 
             ctx.html()
                 .div()
                 .attr(attr!("id" => "wj-toc-list"; if use_true_ids))
-                .contents(table_of_contents);
+                .contents(table_of_contents.as_ref());
         });
 }
@@ -22,11 +22,12 @@ use super::prelude::*;
 
 pub fn render_user(ctx: &mut HtmlContext, name: &str, show_avatar: bool) {
     info!("Rendering user block (name '{name}', show-avatar {show_avatar})");
+    ctx.add_mention(name, show_avatar);
 
     ctx.html()
         .span()
         .attr(attr!("class" => "wj-user-info"))
-        .inner(|ctx| match ctx.handle().get_user_info(name) {
+        .inner(|ctx| match ctx.get_user_info(name) {
             Some(info) => {
                 debug!(
                     "Got user information (user id {}, name {})",
@@ -66,10 +67,14 @@ pub fn render_user(ctx: &mut HtmlContext, name: &str, show_avatar: bool) {
             }
             None => {
                 debug!("No such user found");
+                let message = ctx.get_message("user-missing");
 
                 ctx.html()
                     .span()
-                    .attr(attr!("class" => "wj-error-inline"))
+                    .attr(attr!(
+                        "class" => "wj-error-inline",
+                        "title" => &message,
+                    ))
                     .inner(|ctx| {
                         if show_avatar {
                             // Karma SVG
@@ -0,0 +1,68 @@
+/*
+ * render/html/highlight.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Server-side syntax highlighting for code blocks.
+//!
+//! Each token is wrapped in a `<span>` with classes for its scope,
+//! prefixed with `wj-hl-`, such as `wj-hl-keyword`. The colors are
+//! left to the site's CSS, so any theme may be used.
+
+use cfg_if::cfg_if;
+
+cfg_if! {
+    if #[cfg(feature = "highlight")] {
+        use once_cell::sync::Lazy;
+        use syntect::html::{ClassStyle, ClassedHTMLGenerator};
+        use syntect::parsing::SyntaxSet;
+        use syntect::util::LinesWithEndings;
+
+        static SYNTAXES: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
+
+        /// Highlights code in the given language, producing escaped HTML.
+        ///
+        /// The language may be its name or a file extension, such as `Rust` or `rs`.
+        /// Returns `None` if the language isn't known.
+        pub fn highlight_code(language: &str, contents: &str) -> Option<String> {
+            let syntax = SYNTAXES.find_syntax_by_token(language)?;
+            let mut generator = ClassedHTMLGenerator::new_with_class_style(
+                syntax,
+                &SYNTAXES,
+                ClassStyle::SpacedPrefixed { prefix: "wj-hl-" },
+            );
+
+            for line in LinesWithEndings::from(contents) {
+                if let Err(error) = generator.parse_html_for_line_which_includes_newline(line) {
+                    warn!("Unable to highlight code block: {error}");
+                    return None;
+                }
+            }
+
+            Some(generator.finalize())
+        }
+    } else {
+        /// Mocked version, since highlighting requires the `highlight` feature.
+        ///
+        /// Code is always output as-is.
+        #[inline]
+        pub fn highlight_code(_language: &str, _contents: &str) -> Option<String> {
+            None
+        }
+    }
+}
@@ -0,0 +1,161 @@
+/*
+ * render/html/memo.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Memoization of rendered include contents.
+//!
+//! Pages frequently include the same component many times over, with
+//! the same arguments each time. Since rendering a subtree is (nearly)
+//! a pure function of its elements and the variables in scope, we can
+//! render it once and copy the produced HTML for every repeat.
+//!
+//! Some elements consume or generate state from the render context,
+//! such as footnote and equation numbering, or random IDs. Subtrees
+//! containing any of these are never memoized, since each copy must
+//! differ. Links and user mentions only record backlinks, so they are
+//! replayed on a cache hit instead.
+//!
+//! Entries refer to ranges of the rendered body, so they are forgotten
+//! whenever the body is flushed while streaming.
+
+use super::HtmlContext;
+use crate::render::metrics;
+use crate::tree::{walk_elements, ContainerType, Element};
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Rendered include output, keyed by the serialized subtree and variables.
+///
+/// The full key is stored so that a lookup only hits on an exact match.
+#[derive(Debug, Default)]
+pub struct IncludeMemo {
+    entries: HashMap<String, Range<usize>>,
+}
+
+impl IncludeMemo {
+    #[inline]
+    pub fn get(&self, key: &str) -> Option<Range<usize>> {
+        self.entries.get(key).cloned()
+    }
+
+    #[inline]
+    pub fn insert(&mut self, key: String, range: Range<usize>) {
+        self.entries.insert(key, range);
+    }
+
+    /// Forgets all entries, since their output is no longer in the body.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Forgets entries whose output extends past this length of the body.
+    ///
+    /// This is used when output is split off from the end of the body.
+    pub fn truncate(&mut self, len: usize) {
+        self.entries.retain(|_, range| range.end <= len);
+    }
+}
+
+/// Produces the memoization key for rendering these elements, if possible.
+///
+/// The key is the JSON serialization of the elements, the visible variables,
+/// and whether IDs are being emitted, since copies rendered without IDs
+/// (such as separate fragments) must not reuse output which has them.
+/// It is built from a `serde_json::Value`, whose maps are sorted, so it
+/// does not depend on the iteration order of any `HashMap`.
+///
+/// Returns `None` if the subtree contains any elements whose output
+/// depends on rendering state, and thus must be rendered each time.
+/// This is also the case while auditing accessibility or recording
+/// metrics, since issues and counts are recorded as each element
+/// is rendered.
+pub fn memo_key(ctx: &HtmlContext, elements: &[Element]) -> Option<String> {
+    if ctx.settings().accessibility_audit {
+        debug!("Auditing accessibility, not memoizing");
+        return None;
+    }
+
+    if metrics::is_recording() {
+        debug!("Recording metrics, not memoizing");
+        return None;
+    }
+
+    let mut memoizable = true;
+    walk_elements(elements, &mut |element| {
+        if is_stateful(element) {
+            memoizable = false;
+        }
+    });
+
+    if !memoizable {
+        debug!("Include subtree contains stateful elements, not memoizing");
+        return None;
+    }
+
+    let key = (
+        elements,
+        ctx.variables().visible_values(),
+        ctx.element_ids(),
+        ctx.equation_ids(),
+    );
+    match serde_json::to_value(key) {
+        Ok(value) => Some(value.to_string()),
+        Err(error) => {
+            warn!("Unable to serialize elements for memoization, not memoizing: {error}");
+            None
+        }
+    }
+}
+
+/// Records the backlinks in this subtree.
+///
+/// This is used when memoized output is reused, since the elements
+/// are not rendered again.
+pub fn replay_backlinks(ctx: &mut HtmlContext, elements: &[Element]) {
+    walk_elements(elements, &mut |element| match element {
+        Element::Link { ltype, link, .. } => ctx.add_link(*ltype, link),
+        Element::User { name, show_avatar } => ctx.add_mention(name, *show_avatar),
+        _ => (),
+    });
+}
+
+fn is_stateful(element: &Element) -> bool {
+    match element {
+        // Headings are numbered for the table of contents,
+        // and may receive random IDs.
+        Element::Container(container) => {
+            matches!(container.ctype(), ContainerType::Header(_))
+        }
+
+        // These consume a counter from the context.
+        Element::Footnote
+        | Element::FootnoteRepeat(_)
+        | Element::FootnoteBlock { .. }
+        | Element::Math { .. }
+        | Element::Code { .. }
+        | Element::CodeBlob { .. }
+        | Element::TableOfContents { .. } => true,
+
+        // Tab IDs are randomly generated.
+        Element::TabView(_) => true,
+
+        _ => false,
+    }
+}
@@ -0,0 +1,341 @@
+/*
+ * render/html/minify.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Post-render minification of HTML output.
+//!
+//! This is not a general-purpose HTML minifier, it expects input in
+//! the shape produced by this renderer. Whitespace is collapsed in text,
+//! and removed entirely next to block-level tags where it cannot
+//! affect layout. Attribute values which do not need quoting are
+//! emitted without quotes.
+//!
+//! The content of `<pre>` and `<textarea>` elements, as well as raw text
+//! in `<script>` and `<style>`, are copied unchanged.
+
+/// Tags whose content is raw text, not markup.
+const RAW_TEXT_TAGS: [&str; 2] = ["script", "style"];
+
+/// Tags whose content has significant whitespace.
+const PREFORMATTED_TAGS: [&str; 2] = ["pre", "textarea"];
+
+/// Tags where whitespace before or after them does not affect rendering.
+const BLOCK_TAGS: [&str; 39] = [
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "br",
+    "caption",
+    "col",
+    "colgroup",
+    "dd",
+    "details",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "nav",
+    "ol",
+    "p",
+    "section",
+    "style",
+    "summary",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "tr",
+    "ul",
+];
+
+pub fn minify(html: &str) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+    let mut preformatted_depth = 0_usize;
+
+    // Whether the last tag was block-level, in which case
+    // leading whitespace in the following text is dropped.
+    //
+    // The start and end of input are not treated as block boundaries,
+    // so that fragments (such as streamed chunks) are handled safely.
+    let mut after_block = false;
+
+    while !rest.is_empty() {
+        if rest.starts_with("<!--") {
+            let end = rest.find("-->").map(|idx| idx + 3).unwrap_or(rest.len());
+            output.push_str(&rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+
+        if rest.starts_with('<') {
+            let end = match tag_end(rest) {
+                Some(end) => end,
+                None => {
+                    // Unterminated tag, leave as-is
+                    output.push_str(rest);
+                    break;
+                }
+            };
+
+            let tag = &rest[..end];
+            let (name, closing) = tag_name(tag);
+            write_tag(&mut output, tag);
+            rest = &rest[end..];
+
+            if PREFORMATTED_TAGS.contains(&name.as_str()) {
+                if closing {
+                    preformatted_depth = preformatted_depth.saturating_sub(1);
+                } else {
+                    preformatted_depth += 1;
+                }
+            }
+
+            if !closing && RAW_TEXT_TAGS.contains(&name.as_str()) {
+                let end = find_closing_tag(rest, &name).unwrap_or(rest.len());
+                output.push_str(&rest[..end]);
+                rest = &rest[end..];
+            }
+
+            after_block = BLOCK_TAGS.contains(&name.as_str());
+            continue;
+        }
+
+        // Text content, up to the next tag
+        let end = rest.find('<').unwrap_or(rest.len());
+        let mut text = &rest[..end];
+        rest = &rest[end..];
+
+        if preformatted_depth > 0 {
+            output.push_str(text);
+            continue;
+        }
+
+        if after_block {
+            text = text.trim_start_matches(is_html_whitespace);
+        }
+
+        if next_is_block(rest) {
+            text = text.trim_end_matches(is_html_whitespace);
+        }
+
+        collapse_whitespace(&mut output, text);
+    }
+
+    output
+}
+
+/// Writes out text, replacing each run of whitespace with a single space.
+fn collapse_whitespace(output: &mut String, text: &str) {
+    let mut in_whitespace = false;
+
+    for ch in text.chars() {
+        if is_html_whitespace(ch) {
+            if !in_whitespace {
+                output.push(' ');
+                in_whitespace = true;
+            }
+        } else {
+            output.push(ch);
+            in_whitespace = false;
+        }
+    }
+}
+
+/// Rewrites a tag, normalizing whitespace and removing unneeded quotes.
+fn write_tag(output: &mut String, tag: &str) {
+    let self_closing = tag.ends_with("/>");
+    let inner = tag[1..tag.len() - 1].trim_end_matches('/');
+    let name_end = inner
+        .find(|c: char| is_html_whitespace(c))
+        .unwrap_or(inner.len());
+
+    output.push('<');
+    output.push_str(&inner[..name_end]);
+
+    let mut rest = inner[name_end..].trim_start_matches(is_html_whitespace);
+    while !rest.is_empty() {
+        let key_end = rest
+            .find(|c: char| is_html_whitespace(c) || c == '=')
+            .unwrap_or(rest.len());
+
+        output.push(' ');
+        output.push_str(&rest[..key_end]);
+        rest = rest[key_end..].trim_start_matches(is_html_whitespace);
+
+        if let Some(after_equals) = rest.strip_prefix('=') {
+            let after_equals = after_equals.trim_start_matches(is_html_whitespace);
+            let (value, quote, remaining) = match after_equals.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let value = &after_equals[1..];
+                    let end = value.find(quote).unwrap_or(value.len());
+                    let remaining = value.get(end + 1..).unwrap_or("");
+                    (&value[..end], Some(quote), remaining)
+                }
+                _ => {
+                    let end = after_equals
+                        .find(is_html_whitespace)
+                        .unwrap_or(after_equals.len());
+
+                    (&after_equals[..end], None, &after_equals[end..])
+                }
+            };
+
+            // Keep quotes in self-closing tags, where an unquoted
+            // value would absorb the trailing slash.
+            if !value.is_empty() {
+                output.push('=');
+
+                match quote {
+                    Some(quote) if self_closing || !can_unquote(value) => {
+                        output.push(quote);
+                        output.push_str(value);
+                        output.push(quote);
+                    }
+                    _ => output.push_str(value),
+                }
+            }
+
+            rest = remaining.trim_start_matches(is_html_whitespace);
+        }
+    }
+
+    if self_closing {
+        output.push('/');
+    }
+
+    output.push('>');
+}
+
+/// Whether an attribute value can be written without quotes.
+fn can_unquote(value: &str) -> bool {
+    !value.is_empty()
+        && !value.contains(|c: char| {
+            is_html_whitespace(c) || matches!(c, '"' | '\'' | '=' | '<' | '>' | '`')
+        })
+}
+
+/// Finds the end of the tag at the start of the input, including the `>`.
+fn tag_end(html: &str) -> Option<usize> {
+    let mut quote = None;
+
+    for (idx, ch) in html.char_indices() {
+        match (quote, ch) {
+            (None, '"' | '\'') => quote = Some(ch),
+            (Some(q), _) if q == ch => quote = None,
+            (None, '>') => return Some(idx + 1),
+            _ => (),
+        }
+    }
+
+    None
+}
+
+/// Gets the lowercased name of a tag, and whether it is a closing tag.
+fn tag_name(tag: &str) -> (String, bool) {
+    let inner = &tag[1..];
+    let (inner, closing) = match inner.strip_prefix('/') {
+        Some(inner) => (inner, true),
+        None => (inner, false),
+    };
+
+    let end = inner
+        .find(|c: char| is_html_whitespace(c) || c == '/' || c == '>')
+        .unwrap_or(inner.len());
+
+    (inner[..end].to_ascii_lowercase(), closing)
+}
+
+/// Finds the start of `</name`, case-insensitively.
+fn find_closing_tag(html: &str, name: &str) -> Option<usize> {
+    html.match_indices("</").map(|(idx, _)| idx).find(|&idx| {
+        html.get(idx + 2..idx + 2 + name.len())
+            .map(|candidate| candidate.eq_ignore_ascii_case(name))
+            .unwrap_or(false)
+    })
+}
+
+/// Whether the input begins with a block-level tag.
+fn next_is_block(html: &str) -> bool {
+    html.starts_with('<')
+        && !html.starts_with("<!--")
+        && BLOCK_TAGS.contains(&tag_name(html).0.as_str())
+}
+
+#[inline]
+fn is_html_whitespace(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r' | '\x0c')
+}
+
+#[test]
+fn test() {
+    macro_rules! check {
+        ($input:expr, $expected:expr $(,)?) => {
+            assert_eq!(
+                minify($input),
+                $expected,
+                "Minified HTML doesn't match expected"
+            );
+        };
+    }
+
+    check!("", "");
+    check!("apple", "apple");
+    check!(
+        "<div class=\"wj-body\">\n  <p>Apple   \n banana</p>\n</div>",
+        "<div class=wj-body><p>Apple banana</p></div>",
+    );
+    check!(
+        "<b>apple</b>  \n  <i>banana</i>",
+        "<b>apple</b> <i>banana</i>",
+    );
+    check!(
+        "<span class=\"a b\" id=\"x\" title=\"\" hidden>c</span>",
+        "<span class=\"a b\" id=x title hidden>c</span>",
+    );
+    check!(
+        "<a href=\"/scp-173\" title=\"x=y\">link</a>",
+        "<a href=/scp-173 title=\"x=y\">link</a>",
+    );
+    check!(
+        "<pre>  a\n  b  </pre>  <p> c </p>",
+        "<pre>  a\n  b  </pre><p>c</p>"
+    );
+    check!(
+        "<style>a > b {  color: red; }</style> <p>x</p>",
+        "<style>a > b {  color: red; }</style><p>x</p>",
+    );
+    check!("<mspace width=\"1em\"/>", "<mspace width=\"1em\"/>");
+    check!("<!-- a  b -->\n<p>c</p>", "<!-- a  b --><p>c</p>");
+    check!("a <span", "a <span");
+}
@@ -23,15 +23,23 @@ mod test;
 
 #[macro_use]
 mod attributes;
+mod accessibility;
 mod builder;
 mod context;
+mod dom;
 mod element;
 mod escape;
+mod highlight;
+mod memo;
 mod meta;
+mod minify;
 mod output;
 mod random;
 mod render;
+mod word_break;
 
+pub use self::accessibility::AccessibilityIssue;
+pub use self::dom::{nodes_to_html, HtmlNode, HtmlTreeOutput, HtmlTreeRender};
 pub use self::meta::{HtmlMeta, HtmlMetaType};
 pub use self::output::HtmlOutput;
 
@@ -40,25 +48,39 @@ use super::prelude;
 
 use self::attributes::AddedAttributes;
 use self::context::HtmlContext;
-use crate::data::PageInfo;
-use crate::render::{Handle, Render};
+use self::element::{
+    render_breadcrumbs, render_element, render_elements, render_footnote_block,
+    render_section, render_table_of_contents, scope_class,
+};
+use crate::data::{LinkValidation, PageInfo};
+use crate::render::{
+    substitute_page_variables, substitute_page_variables_partial, Handle, Prefetched,
+    Render, RenderStream,
+};
+
+#[cfg(feature = "async")]
+use crate::render::AsyncHandle;
 use crate::settings::WikitextSettings;
-use crate::tree::SyntaxTree;
+use crate::tree::{AttributeMap, BibliographyList, Element, SyntaxTree};
+use std::fmt;
 
 #[derive(Debug)]
 pub struct HtmlRender;
 
-impl Render for HtmlRender {
-    type Output = HtmlOutput;
-
-    fn render(
+impl HtmlRender {
+    /// Renders a subset of elements, without the surrounding body element.
+    ///
+    /// Since there is no syntax tree, the table of contents, footnotes,
+    /// bibliographies, and equations are all treated as empty.
+    pub fn render_partial(
         &self,
-        tree: &SyntaxTree,
+        elements: &[Element],
         page_info: &PageInfo,
         settings: &WikitextSettings,
+        wikitext_len: usize,
     ) -> HtmlOutput {
         info!(
-            "Rendering HTML (site {}, page {}, category {})",
+            "Rendering partial HTML (site {}, page {}, category {})",
             page_info.site.as_ref(),
             page_info.page.as_ref(),
             match &page_info.category {
@@ -67,23 +89,309 @@ impl Render for HtmlRender {
             },
         );
 
+        let handle = Handle::new(settings);
+        let bibliographies = BibliographyList::new();
         let mut ctx = HtmlContext::new(
             page_info,
-            &Handle,
+            &handle,
             settings,
-            &tree.table_of_contents,
-            &tree.footnotes,
-            &tree.bibliographies,
-            tree.wikitext_len,
+            &[],
+            &[],
+            &bibliographies,
+            wikitext_len,
         );
 
-        // Crawl through elements and generate HTML
-        ctx.html()
-            .element("wj-body")
-            .attr(attr!("class" => "wj-body"))
-            .contents(&tree.elements);
+        let elements =
+            substitute_page_variables_partial(&handle, page_info, settings, elements);
+        render_elements(&mut ctx, &elements);
+        HtmlOutput::from(ctx)
+    }
 
-        // Build and return HtmlOutput
-        ctx.into()
+    /// Renders a preview of the page, without the surrounding body element.
+    ///
+    /// This ends after the first paragraph or `max_len` characters of
+    /// visible text, and leaves out the table of contents, footnotes,
+    /// and modules. See [`SyntaxTree::excerpt`].
+    pub fn render_excerpt(
+        &self,
+        tree: &SyntaxTree,
+        page_info: &PageInfo,
+        settings: &WikitextSettings,
+        max_len: usize,
+    ) -> HtmlOutput {
+        let elements = tree.excerpt(max_len);
+        self.render_partial(&elements, page_info, settings, tree.wikitext_len)
     }
+
+    /// Renders the syntax tree, using an asynchronous handle for lookups.
+    ///
+    /// All the user information, page titles, and messages needed by the page
+    /// are fetched from the handle first, then rendering proceeds as usual.
+    #[cfg(feature = "async")]
+    pub async fn render_async<H: AsyncHandle>(
+        &self,
+        tree: &SyntaxTree<'_>,
+        page_info: &PageInfo<'_>,
+        settings: &WikitextSettings,
+        handle: &H,
+    ) -> HtmlOutput {
+        let prefetched = Prefetched::fetch(handle, tree, page_info).await;
+        self.render_full(tree, page_info, settings, Some(&prefetched), None)
+    }
+
+    /// Renders the syntax tree, using the results of [`validate_links()`]
+    /// to determine which linked pages exist.
+    ///
+    /// Pages which weren't validated are checked with the handle as usual.
+    ///
+    /// [`validate_links()`]: crate::data::validate_links
+    pub fn render_validated(
+        &self,
+        tree: &SyntaxTree,
+        page_info: &PageInfo,
+        settings: &WikitextSettings,
+        link_validation: &LinkValidation,
+    ) -> HtmlOutput {
+        self.render_full(tree, page_info, settings, None, Some(link_validation))
+    }
+
+    fn render_full(
+        &self,
+        tree: &SyntaxTree,
+        page_info: &PageInfo,
+        settings: &WikitextSettings,
+        prefetched: Option<&Prefetched>,
+        link_validation: Option<&LinkValidation>,
+    ) -> HtmlOutput {
+        let handle = Handle::new(settings);
+        let tree = substitute_page_variables(&handle, page_info, settings, tree);
+        let tree = &*tree;
+        let (ctx, _) = render_body(
+            tree,
+            page_info,
+            &handle,
+            settings,
+            prefetched,
+            link_validation,
+            false,
+            None,
+        );
+        finish_output(ctx, tree, settings)
+    }
+}
+
+impl Render for HtmlRender {
+    type Output = HtmlOutput;
+
+    #[inline]
+    fn render(
+        &self,
+        tree: &SyntaxTree,
+        page_info: &PageInfo,
+        settings: &WikitextSettings,
+    ) -> HtmlOutput {
+        self.render_full(tree, page_info, settings, None, None)
+    }
+}
+
+impl RenderStream for HtmlRender {
+    fn render_stream<W: fmt::Write>(
+        &self,
+        tree: &SyntaxTree,
+        page_info: &PageInfo,
+        settings: &WikitextSettings,
+        writer: &mut W,
+    ) -> Result<HtmlOutput, fmt::Error> {
+        let handle = Handle::new(settings);
+        let tree = substitute_page_variables(&handle, page_info, settings, tree);
+        let tree = &*tree;
+        let (ctx, result) = render_body(
+            tree,
+            page_info,
+            &handle,
+            settings,
+            None,
+            None,
+            false,
+            Some(writer),
+        );
+
+        result?;
+        Ok(finish_output(ctx, tree, settings))
+    }
+}
+
+/// Renders the page body into a new context.
+///
+/// If `record_nodes` is set, the context also builds the output as a node tree.
+///
+/// If a `writer` is given, the output is streamed to it, being written
+/// out after each top-level element once enough has accumulated.
+/// Any error from the writer is returned alongside the context.
+#[allow(clippy::too_many_arguments)]
+fn render_body<'i, 'h, 'e, 't>(
+    tree: &'e SyntaxTree<'t>,
+    page_info: &'i PageInfo<'i>,
+    handle: &'h Handle,
+    settings: &'e WikitextSettings,
+    prefetched: Option<&'h Prefetched>,
+    link_validation: Option<&'h LinkValidation>,
+    record_nodes: bool,
+    mut writer: Option<&mut dyn fmt::Write>,
+) -> (HtmlContext<'i, 'h, 'e, 't>, fmt::Result) {
+    info!(
+        "Rendering HTML (site {}, page {}, category {})",
+        page_info.site.as_ref(),
+        page_info.page.as_ref(),
+        match &page_info.category {
+            Some(category) => category.as_ref(),
+            None => "_default",
+        },
+    );
+
+    // Don't preallocate for the whole page if it's written out in chunks
+    let wikitext_len = match writer {
+        Some(_) => 0,
+        None => tree.wikitext_len,
+    };
+
+    let mut ctx = HtmlContext::new(
+        page_info,
+        handle,
+        settings,
+        &tree.table_of_contents,
+        &tree.footnotes,
+        &tree.bibliographies,
+        wikitext_len,
+    );
+
+    if record_nodes {
+        ctx.record_nodes();
+    }
+    ctx.set_equations(&tree.equations);
+    ctx.set_footnote_repeats(&tree.elements);
+    ctx.set_footnote_equations(&tree.elements);
+
+    if let Some(prefetched) = prefetched {
+        ctx.set_prefetched(prefetched);
+    }
+
+    if let Some(link_validation) = link_validation {
+        ctx.set_link_validation(link_validation);
+    }
+
+    if settings.redirect_mode.skips(tree) {
+        info!("Page redirects, skipping rendering");
+        return (ctx, Ok(()));
+    }
+
+    // Crawl through elements and generate HTML
+    let mut result = Ok(());
+    ctx.html()
+        .element("wj-body")
+        .attr(attr!("class" => &body_class(page_info, settings)))
+        .inner(|ctx| {
+            if ctx.settings().emit_breadcrumbs {
+                render_breadcrumbs(ctx);
+            }
+
+            let semantic_html = ctx.settings().semantic_html;
+            let mut render_top_level = |ctx: &mut HtmlContext| {
+                let mut index = 0;
+                while index < tree.elements.len() && result.is_ok() {
+                    // With semantic HTML, each top-level section is rendered at once
+                    if semantic_html {
+                        index = render_section(ctx, &tree.elements, index);
+                    } else {
+                        render_element(ctx, &tree.elements[index]);
+                        index += 1;
+                    }
+
+                    if let Some(writer) = writer.as_deref_mut() {
+                        if ctx.buffer().len() >= STREAM_CHUNK_SIZE {
+                            result = ctx.flush(writer);
+                        }
+                    }
+                }
+            };
+
+            if semantic_html {
+                ctx.html().article().inner(&mut render_top_level);
+            } else {
+                render_top_level(ctx);
+            }
+        });
+
+    if let (Ok(()), Some(writer)) = (&result, writer) {
+        result = ctx.flush(writer);
+    }
+
+    (ctx, result)
+}
+
+/// Produces the class for the body element, including the CSS scope if enabled.
+fn body_class(page_info: &PageInfo, settings: &WikitextSettings) -> String {
+    if settings.scope_css {
+        format!("wj-body {}", scope_class(page_info))
+    } else {
+        str!("wj-body")
+    }
+}
+
+/// Renders the table of contents and footnotes separately, if requested.
+///
+/// The `render` function renders the given fragment and returns its output.
+fn render_fragments<'i, 'h, 'e, 't, T, F>(
+    ctx: &mut HtmlContext<'i, 'h, 'e, 't>,
+    tree: &SyntaxTree,
+    settings: &WikitextSettings,
+    mut render: F,
+) -> (Option<T>, Option<T>)
+where
+    F: FnMut(
+        &mut HtmlContext<'i, 'h, 'e, 't>,
+        &dyn Fn(&mut HtmlContext<'i, 'h, 'e, 't>),
+    ) -> T,
+{
+    let separate = settings.fragment_placement.separate();
+
+    // If these are also in the body, only that copy keeps its IDs,
+    // so that they remain unique within the page.
+    let element_ids = !settings.fragment_placement.inline();
+
+    ctx.with_element_ids(element_ids, |ctx| {
+        let table_of_contents =
+            (separate && !tree.table_of_contents.is_empty()).then(|| {
+                render(ctx, &|ctx| {
+                    render_table_of_contents(ctx, None, &AttributeMap::new(), None, None);
+                })
+            });
+
+        let footnotes = (separate && !tree.footnotes.is_empty()).then(|| {
+            render(ctx, &|ctx| {
+                render_footnote_block(ctx, None, 0..tree.footnotes.len());
+            })
+        });
+
+        (table_of_contents, footnotes)
+    })
+}
+
+/// How much HTML to accumulate before writing it out when streaming.
+const STREAM_CHUNK_SIZE: usize = 8192;
+
+/// Renders any separate fragments, then builds the final output.
+fn finish_output(
+    mut ctx: HtmlContext,
+    tree: &SyntaxTree,
+    settings: &WikitextSettings,
+) -> HtmlOutput {
+    let (table_of_contents, footnotes) =
+        render_fragments(&mut ctx, tree, settings, |ctx, f| ctx.render_fragment(f));
+
+    // Build and return HtmlOutput
+    let mut output = HtmlOutput::from(ctx);
+    output.table_of_contents = table_of_contents;
+    output.footnotes = footnotes;
+    output
 }
@@ -18,12 +18,38 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+use super::accessibility::AccessibilityIssue;
 use super::meta::HtmlMeta;
 use crate::data::Backlinks;
+use crate::render::OutputSize;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct HtmlOutput {
     pub body: String,
     pub meta: Vec<HtmlMeta>,
     pub backlinks: Backlinks<'static>,
+
+    /// The table of contents, if emitted as a separate fragment.
+    ///
+    /// See [`FragmentPlacement`](crate::settings::FragmentPlacement).
+    pub table_of_contents: Option<String>,
+
+    /// The footnote block, if emitted as a separate fragment.
+    ///
+    /// See [`FragmentPlacement`](crate::settings::FragmentPlacement).
+    pub footnotes: Option<String>,
+
+    /// Accessibility problems found while rendering, if enabled.
+    ///
+    /// See [`accessibility_audit`](crate::settings::WikitextSettings::accessibility_audit).
+    pub accessibility: Vec<AccessibilityIssue>,
+}
+
+impl OutputSize for HtmlOutput {
+    /// The size of the body and any separate fragments.
+    fn output_size(&self) -> usize {
+        self.body.len()
+            + self.table_of_contents.as_ref().map_or(0, String::len)
+            + self.footnotes.as_ref().map_or(0, String::len)
+    }
 }
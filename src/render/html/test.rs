@@ -32,8 +32,1131 @@ fn html() {
         vec![],
         vec![],
         BibliographyList::new(),
+        vec![],
         0,
     );
     let (tree, _) = result.into();
     let _output = HtmlRender.render(&tree, &page_info, &settings);
 }
+
+#[test]
+fn partial() {
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let tokens = crate::tokenize("Apple **banana** cherry");
+    let (tree, _) = crate::parse(&tokens, &page_info, &settings).into();
+    let output = HtmlRender.render_partial(&tree.elements, &page_info, &settings, 0);
+
+    assert_eq!(output.body, "<p>Apple <strong>banana</strong> cherry</p>",);
+}
+
+#[test]
+fn tree() {
+    use super::{nodes_to_html, HtmlNode, HtmlTreeRender};
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let tokens = crate::tokenize("Apple **banana** [[span class=\"x\"]]cherry[[/span]]");
+    let (tree, _) = crate::parse(&tokens, &page_info, &settings).into();
+
+    let html = HtmlRender.render(&tree, &page_info, &settings);
+    let output = HtmlTreeRender.render(&tree, &page_info, &settings);
+    assert_eq!(nodes_to_html(&output.body), html.body);
+
+    // Post-process the tree, then serialize it
+    let mut body = output.body;
+    if let HtmlNode::Element { attributes, .. } = &mut body[0] {
+        attributes.push((str!("data-nonce"), str!("abc")));
+    }
+
+    assert_eq!(
+        nodes_to_html(&body),
+        "<wj-body class=\"wj-body\" data-nonce=\"abc\"><p>Apple <strong>banana</strong> \
+         <span class=\"x\">cherry</span></p></wj-body>",
+    );
+}
+
+#[test]
+fn memoized_includes() {
+    use crate::data::PageRef;
+    use std::borrow::Cow;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+
+    macro_rules! include {
+        ($name:expr, $elements:expr $(,)?) => {
+            Element::Include {
+                paragraph_safe: true,
+                variables: hashmap! {
+                    Cow::Borrowed("name") => Cow::Borrowed($name),
+                },
+                location: PageRef::page_only("component:test"),
+                elements: $elements,
+            }
+        };
+    }
+
+    let render = |elements: Vec<Element<'static>>| {
+        let result = SyntaxTree::from_element_result(
+            elements,
+            vec![],
+            vec![],
+            vec![],
+            BibliographyList::new(),
+            vec![],
+            0,
+        );
+        let (tree, _) = result.into();
+        HtmlRender.render(&tree, &page_info, &settings)
+    };
+
+    let contents = |body: &str| {
+        body.trim_start_matches(r#"<wj-body class="wj-body">"#)
+            .trim_end_matches("</wj-body>")
+            .to_owned()
+    };
+
+    // Identical subtrees, with variables and a link
+    let component = || {
+        vec![
+            Element::Variable(Cow::Borrowed("name")),
+            Element::Link {
+                ltype: crate::tree::LinkType::Page,
+                link: crate::tree::LinkLocation::Page(PageRef::page_only("target")),
+                label: crate::tree::LinkLabel::Page,
+                target: None,
+            },
+        ]
+    };
+
+    let output = render(vec![
+        include!("apple", component()),
+        include!("banana", component()),
+        include!("apple", component()),
+    ]);
+
+    let apple = contents(&render(vec![include!("apple", component())]).body);
+    let banana = contents(&render(vec![include!("banana", component())]).body);
+    assert_eq!(
+        contents(&output.body),
+        format!("{apple}{banana}{apple}"),
+        "Memoized include output doesn't match individually rendered output",
+    );
+    assert_eq!(
+        output.backlinks.internal_links.len(),
+        3,
+        "Links in memoized include output weren't recorded",
+    );
+
+    // Stateful subtrees must be rendered each time
+    let math = || {
+        vec![Element::Math {
+            name: None,
+            latex_source: Cow::Borrowed("x"),
+        }]
+    };
+
+    let output = render(vec![include!("apple", math()), include!("apple", math())]);
+    let single = contents(&render(vec![include!("apple", math())]).body);
+    assert_ne!(
+        contents(&output.body),
+        single.repeat(2),
+        "Stateful include subtree was memoized",
+    );
+}
+
+#[test]
+fn memo_keys() {
+    use super::context::HtmlContext;
+    use super::memo::memo_key;
+    use crate::render::Handle;
+    use crate::tree::VariableMap;
+    use std::borrow::Cow;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let handle = Handle::new(&settings);
+    let bibliographies = BibliographyList::new();
+    let elements = vec![Element::Variable(Cow::Borrowed("a"))];
+    let key = |names: &[&'static str], value: &'static str, element_ids: bool| {
+        let mut map = VariableMap::new();
+        for name in names {
+            map.insert(Cow::Borrowed(*name), Cow::Borrowed(value));
+        }
+
+        let mut ctx = HtmlContext::new(
+            &page_info,
+            &handle,
+            &settings,
+            &[],
+            &[],
+            &bibliographies,
+            0,
+        );
+        ctx.variables_mut().push_scope(&map);
+        ctx.with_element_ids(element_ids, |ctx| memo_key(ctx, &elements))
+    };
+
+    // Keys don't depend on map iteration order
+    let names = ["a", "b", "c", "d", "e", "f", "g", "h"];
+    let mut reversed = names;
+    reversed.reverse();
+    assert_eq!(
+        key(&names, "x", true),
+        key(&reversed, "x", true),
+        "Memo key depends on variable insertion order",
+    );
+
+    // Differing values produce differing keys
+    assert_ne!(
+        key(&names, "x", true),
+        key(&names, "y", true),
+        "Memo keys for different variables are equal",
+    );
+
+    // Output without IDs is kept apart from output with them
+    assert_ne!(
+        key(&names, "x", true),
+        key(&names, "x", false),
+        "Memo keys with and without element IDs are equal",
+    );
+}
+
+#[test]
+fn separate_fragments() {
+    use crate::settings::FragmentPlacement;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+
+    let mut text = str!("[[toc]]\n\n+ Heading\n\nApple[[footnote]]Banana[[/footnote]]");
+    crate::preprocess(&mut text);
+    let tokens = crate::tokenize(&text);
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+
+    macro_rules! render {
+        ($placement:expr) => {{
+            let settings = WikitextSettings {
+                fragment_placement: $placement,
+                ..settings.clone()
+            };
+            HtmlRender.render(&tree, &page_info, &settings)
+        }};
+    }
+
+    // Inline only, as usual
+    let output = render!(FragmentPlacement::Inline);
+    assert!(output.body.contains("wj-toc-list"));
+    assert!(output.body.contains("wj-footnote-list"));
+    assert_eq!(output.table_of_contents, None);
+    assert_eq!(output.footnotes, None);
+
+    // Separate only
+    let output = render!(FragmentPlacement::Separate);
+    let table_of_contents = output.table_of_contents.expect("No TOC fragment");
+    let footnotes = output.footnotes.expect("No footnotes fragment");
+    assert!(!output.body.contains("wj-toc-list"));
+    assert!(!output.body.contains("wj-footnote-list"));
+    assert!(table_of_contents.contains("Heading"));
+    assert!(footnotes.contains("Banana"));
+
+    // Both
+    let output = render!(FragmentPlacement::Both);
+    assert!(output.body.contains("wj-toc-list"));
+    assert!(output.body.contains("wj-footnote-list"));
+    let both_table_of_contents = output.table_of_contents.expect("No TOC fragment");
+    let both_footnotes = output.footnotes.expect("No footnotes fragment");
+    assert!(table_of_contents.contains(r#"id="wj-toc""#));
+    assert!(footnotes.contains(r#"id="wj-footnote-1""#));
+    assert!(output.body.contains(r#"id="wj-toc""#));
+    assert!(output.body.contains(r#"id="wj-footnote-1""#));
+
+    // No duplicate IDs in the separate copies
+    assert!(!both_table_of_contents.contains(" id="));
+    assert!(!both_footnotes.contains(" id="));
+    assert!(both_table_of_contents.contains("Heading"));
+    assert!(both_footnotes.contains("Banana"));
+
+    // Memoized includes don't carry IDs into the separate copies
+    let include = Element::Include {
+        paragraph_safe: true,
+        variables: hashmap! {},
+        location: crate::data::PageRef::page_only("component:test"),
+        elements: vec![Element::Anchor {
+            target: None,
+            attributes: AttributeMap::from(btreemap! {
+                cow!("id") => cow!("cherry"),
+            }),
+            elements: vec![text!("Cherry")],
+        }],
+    };
+    let tree = SyntaxTree {
+        elements: vec![
+            Element::Footnote,
+            Element::Footnote,
+            Element::FootnoteBlock {
+                title: None,
+                hide: false,
+            },
+        ],
+        footnotes: vec![vec![include.clone()], vec![include]],
+        ..SyntaxTree::default()
+    };
+    let settings = WikitextSettings {
+        fragment_placement: FragmentPlacement::Both,
+        ..settings
+    };
+    let output = HtmlRender.render(&tree, &page_info, &settings);
+    let both_footnotes = output.footnotes.expect("No footnotes fragment");
+    assert!(output.body.contains(r#"id="cherry""#));
+    assert!(both_footnotes.contains("Cherry"));
+    assert!(!both_footnotes.contains(" id="));
+}
+
+#[test]
+fn footnote_settings() {
+    use crate::settings::{FootnoteNumbering, FootnotePlacement};
+
+    let page_info = PageInfo::dummy();
+
+    let render = |settings: &WikitextSettings| {
+        let mut text =
+            str!("A[[footnote]]One[[/footnote]]\n\n[[footnoteblock]]\n\nB[[footnote]]Two[[/footnote]]");
+        crate::preprocess(&mut text);
+        let tokens = crate::tokenize(&text);
+        let (tree, _errors) = crate::parse(&tokens, &page_info, settings).into();
+        HtmlRender.render(&tree, &page_info, settings).body
+    };
+
+    // Defaults
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let body = render(&settings);
+    let block = body.find("wj-footnote-list").expect("No footnote block");
+    assert!(block < body.find(">B<").expect("No text after block"));
+    assert!(body.contains(">Footnotes<"));
+
+    // Custom title, numbering, and placement
+    let settings = WikitextSettings {
+        footnote_numbering: FootnoteNumbering::LowerRoman,
+        footnote_block_title: Some(str!("Notes")),
+        footnote_placement: FootnotePlacement::End,
+        ..settings
+    };
+    let body = render(&settings);
+    let block = body.find("wj-footnote-list").expect("No footnote block");
+    assert!(block > body.find(">B<").expect("No text before block"));
+    assert_eq!(body.matches("wj-footnote-list\"").count(), 1);
+    assert!(body.contains(">Notes<"));
+    assert!(body.contains(r#"data-id="2">ii</wj-footnote-ref-marker>"#));
+}
+
+#[test]
+fn footnote_block_split() {
+    use crate::settings::FootnoteBlockMode;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings {
+        footnote_block_mode: FootnoteBlockMode::Split,
+        ..WikitextSettings::from_mode(WikitextMode::Page)
+    };
+
+    let mut text = str!(concat!(
+        "A[[footnote]]One[[/footnote]]\n\n[[footnoteblock]]\n\n",
+        "B[[footnote]]Two[[/footnote]]\n\n[[footnoteblock]]\n\n[[footnoteblock]]\n\n",
+        "C[[footnote]]Three[[/footnote]]",
+    ));
+    crate::preprocess(&mut text);
+    let tokens = crate::tokenize(&text);
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let body = HtmlRender.render(&tree, &page_info, &settings).body;
+
+    // Empty blocks are omitted, and trailing footnotes get their own block
+    assert_eq!(body.matches("wj-footnote-list\"").count(), 3);
+    assert!(body.find(">Three<").unwrap() > body.find(">C<").unwrap());
+
+    // Numbering restarts, but IDs remain unique
+    assert!(body.contains(r#"data-id="2">1</wj-footnote-ref-marker>"#));
+    assert!(body.contains(r#"data-id="3">1</wj-footnote-ref-marker>"#));
+    assert_eq!(body.matches(r#"id="wj-footnote-3""#).count(), 1);
+
+    // Repeated references keep the number from the block listing the footnote,
+    // even if the tree was parsed without splitting footnote blocks
+    let mut text = str!(concat!(
+        "A[[footnote name=\"x\"]]One[[/footnote]] B[[footnote]]Two[[/footnote]]\n\n",
+        "[[footnoteblock]]\n\n",
+        "C[[footnote]]Three[[/footnote]] D[[footnote name=\"x\"]].",
+    ));
+    crate::preprocess(&mut text);
+    let tokens = crate::tokenize(&text);
+    let parse_settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &parse_settings).into();
+    let body = HtmlRender.render(&tree, &page_info, &settings).body;
+
+    assert!(body.contains(r#"data-id="1" data-ref="2">1</wj-footnote-ref-marker>"#));
+    assert!(body.contains(r#"data-id="3">1</wj-footnote-ref-marker>"#));
+}
+
+#[test]
+fn math_source_output() {
+    use crate::settings::MathOutput;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings {
+        math_output: MathOutput::Source,
+        ..WikitextSettings::from_mode(WikitextMode::Page)
+    };
+
+    let mut text = str!("[[math]]\na < b\n[[/math]]\n\nInline [[$ x^2 $]]");
+    crate::preprocess(&mut text);
+    let tokens = crate::tokenize(&text);
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let body = HtmlRender.render(&tree, &page_info, &settings).body;
+
+    assert!(body.contains(r#"<span class="math">a &lt; b</span>"#));
+    assert!(body.contains(r#"<span class="math">x^2</span>"#));
+    assert!(!body.contains("wj-math-source"));
+    assert!(!body.contains("<math"));
+}
+
+#[test]
+fn sanitization_policy() {
+    use crate::settings::SanitizationPolicy;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings {
+        sanitization: SanitizationPolicy {
+            blocked_attributes: vec![str!("title")],
+            blocked_css: vec![str!("position: fixed")],
+            blocked_url_schemes: vec![str!("ftp")],
+            iframe_hosts: Some(vec![str!("example.com")]),
+            iframe_sandbox: Some(vec![str!("allow-scripts")]),
+            html_sandbox: None,
+        },
+        ..WikitextSettings::from_mode(WikitextMode::Page)
+    };
+
+    let mut text = str!(concat!(
+        "[[span title=\"A\" style=\"color: red; position: fixed\"]]X[[/span]]\n",
+        "[ftp://example.org/file File]\n",
+        "[[iframe https://evil.net/]]\n",
+        "[[iframe https://www.example.com/embed]]\n",
+        "[[css]]\n.a { color: red; position: fixed; }\n",
+        "@media print { .b { position: fixed !important; top: 0; } }\n[[/css]]",
+    ));
+    crate::preprocess(&mut text);
+    let tokens = crate::tokenize(&text);
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let body = HtmlRender.render(&tree, &page_info, &settings).body;
+
+    assert!(body.contains(r#"<span style="color: red">X</span>"#));
+    assert!(!body.contains("title="));
+    assert!(!body.contains("ftp://"));
+    assert!(!body.contains("evil.net"));
+    assert!(body.contains(
+        r#"<div class="wj-error-block wj-iframe-blocked">Embedded content from this site is not permitted</div>"#
+    ));
+    assert!(body.contains(r#"src="https://www.example.com/embed""#));
+    assert!(body.contains(r#"sandbox="allow-scripts""#));
+
+    // Stylesheets are also sanitized
+    assert!(body.contains(".a{color:red}"));
+    assert!(body.contains("@media print{.b{top:0}}"));
+    assert!(!body.contains("position"));
+}
+
+#[test]
+fn mode_gating() {
+    use crate::parsing::ParseErrorKind;
+    use crate::render::text::TextRender;
+
+    let page_info = PageInfo::dummy();
+    let tree = SyntaxTree {
+        elements: vec![
+            text!("Apple"),
+            Element::Iframe {
+                url: cow!("https://example.com/"),
+                attributes: AttributeMap::new(),
+            },
+        ],
+        ..SyntaxTree::default()
+    };
+
+    // Permitted on pages
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let body = HtmlRender.render(&tree, &page_info, &settings).body;
+    assert!(body.contains(r#"<iframe src="https://example.com/""#));
+
+    // But not in forum posts, even if present in the tree
+    let settings = WikitextSettings::from_mode(WikitextMode::ForumPost);
+    let body = HtmlRender.render(&tree, &page_info, &settings).body;
+    assert_eq!(
+        body,
+        "<wj-body class=\"wj-body\">Apple\
+         <div class=\"wj-error-block wj-element-blocked\" data-element=\"iframe\">\
+         This content is not permitted here</div></wj-body>",
+    );
+
+    let text = TextRender.render(&tree, &page_info, &settings);
+    assert_eq!(text, "Apple");
+
+    // The parser uses the same table, and warns about blocked elements
+    let input = "[[iframe https://example.com/]]\n\n[[module Rate]]";
+    let tokens = crate::tokenize(input);
+    let (_tree, errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let kinds = errors.iter().map(|error| error.kind()).collect::<Vec<_>>();
+    assert!(kinds.contains(&ParseErrorKind::NotSupportedMode));
+
+    // Which is derived from the settings rather than the mode
+    let settings = WikitextSettings {
+        enable_page_syntax: true,
+        ..settings
+    };
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let body = HtmlRender.render(&tree, &page_info, &settings).body;
+    assert!(body.contains("wj-rate"), "Module not rendered");
+    assert!(!body.contains("wj-element-blocked"));
+}
+
+#[test]
+fn localized_messages() {
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let tree = SyntaxTree {
+        elements: vec![Element::Quote {
+            attribution: Some(cow!("Apple")),
+            elements: vec![text!("Banana")],
+        }],
+        ..SyntaxTree::default()
+    };
+
+    let mut page_info = PageInfo::dummy();
+    let mut render = |language| {
+        page_info.language = cow!(language);
+        HtmlRender.render(&tree, &page_info, &settings).body
+    };
+
+    assert!(render("en").contains("<cite>Apple</cite> wrote:"));
+    assert!(render("fr-CA").contains("<cite>Apple</cite> a écrit :"));
+
+    // Unknown languages fall back to English
+    assert!(render("xx").contains("<cite>Apple</cite> wrote:"));
+}
+
+#[test]
+fn html_block_output() {
+    use crate::settings::{HtmlBlockOutput, SanitizationPolicy};
+
+    let page_info = PageInfo::dummy();
+    let text = "[[html]]\n<p class=\"x\">A & B</p>\n[[/html]]";
+    let render = |output| {
+        let settings = WikitextSettings {
+            html_block_output: output,
+            sanitization: SanitizationPolicy {
+                // Embed permissions don't apply to user HTML
+                iframe_sandbox: Some(vec![str!("allow-same-origin allow-scripts")]),
+                ..SanitizationPolicy::default()
+            },
+            ..WikitextSettings::from_mode(WikitextMode::Page)
+        };
+        let tokens = crate::tokenize(text);
+        let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+        HtmlRender.render(&tree, &page_info, &settings).body
+    };
+
+    let body = render(HtmlBlockOutput::Hosted);
+    assert!(body.contains(r#"<iframe src="https://example.com/" crossorigin>"#));
+
+    let body = render(HtmlBlockOutput::Srcdoc);
+    assert!(body.contains(
+        r#"<iframe srcdoc="&lt;p class=&quot;x&quot;&gt;A &amp; B&lt;/p&gt;" sandbox="allow-scripts">"#
+    ));
+
+    let body = render(HtmlBlockOutput::DataUri);
+    assert!(body.contains(
+        r#"src="data:text/html;charset=utf-8,%3Cp%20class%3D%22x%22%3EA%20%26%20B%3C%2Fp%3E" sandbox="allow-scripts">"#
+    ));
+    assert!(!body.contains("srcdoc"));
+    assert!(!body.contains("allow-same-origin"));
+}
+
+#[test]
+fn streaming_output() {
+    use crate::data::PageRef;
+    use crate::render::RenderStream;
+    use std::borrow::Cow;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+
+    // Large enough that the memoized output is flushed before being reused
+    let text = "apple ".repeat(2000);
+    let include = || Element::Include {
+        paragraph_safe: true,
+        variables: hashmap! {},
+        location: PageRef::page_only("component:test"),
+        elements: vec![Element::Text(Cow::Borrowed(&text))],
+    };
+
+    let result = SyntaxTree::from_element_result(
+        vec![include(), Element::HorizontalRule, include(), include()],
+        vec![],
+        vec![],
+        vec![],
+        BibliographyList::new(),
+        vec![],
+        0,
+    );
+    let (tree, _) = result.into();
+
+    let expected = HtmlRender.render(&tree, &page_info, &settings);
+
+    let mut streamed = String::new();
+    let output = HtmlRender
+        .render_stream(&tree, &page_info, &settings, &mut streamed)
+        .expect("Writing to string failed");
+    assert_eq!(streamed, expected.body, "Streamed output doesn't match");
+    assert!(output.body.is_empty(), "Streamed body wasn't flushed");
+    assert_eq!(output.backlinks, expected.backlinks);
+
+    let mut bytes = Vec::new();
+    HtmlRender
+        .render_stream_io(&tree, &page_info, &settings, &mut bytes)
+        .expect("Writing to buffer failed");
+    assert_eq!(bytes, expected.body.as_bytes());
+}
+
+#[test]
+fn heading_id_strategy() {
+    use crate::settings::HeadingIdStrategy;
+
+    let page_info = PageInfo::dummy();
+    let render = |heading_ids| {
+        let settings = WikitextSettings {
+            heading_ids,
+            ..WikitextSettings::from_mode(WikitextMode::Page)
+        };
+
+        let text = "[[toc]]\n+ Early History\n++ Notes\n+ Early history!\n++ Notes";
+        let tokens = crate::tokenize(text);
+        let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+        HtmlRender.render(&tree, &page_info, &settings).body
+    };
+
+    let body = render(HeadingIdStrategy::Numeric);
+    assert!(body.contains(r##"href="#toc2""##));
+    assert!(body.contains(r#"<h1 id="toc2">"#));
+
+    let body = render(HeadingIdStrategy::SlugifiedTitle);
+    for id in ["early-history", "notes", "early-history-2", "notes-2"] {
+        assert!(
+            body.contains(&format!(r##"href="#{id}""##)),
+            "No TOC link to {id}"
+        );
+        assert!(
+            body.contains(&format!(r#"id="{id}""#)),
+            "No heading with ID {id}"
+        );
+    }
+
+    let body = render(HeadingIdStrategy::Prefixed(str!("h-")));
+    assert!(body.contains(r##"href="#h-notes-2""##));
+    assert!(body.contains(r#"<h2 id="h-notes-2">"#));
+}
+
+#[test]
+fn scoped_css() {
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings {
+        scope_css: true,
+        minify_css: true,
+        ..WikitextSettings::from_mode(WikitextMode::Page)
+    };
+
+    let text = "[[css]]\n@import url(https://example.com/global.css);\n\
+                .apple, p > b { color: red; }\n\
+                @media (max-width: 100px) { .banana { display: none; } }\n[[/css]]";
+    let tokens = crate::tokenize(text);
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let body = HtmlRender.render(&tree, &page_info, &settings).body;
+
+    let class = super::element::scope_class(&page_info);
+    assert!(body.starts_with(&format!(r#"<wj-body class="wj-body {class}">"#)));
+
+    // Names are escaped to valid class characters
+    let scope_class = |category: Option<&'static str>, page: &'static str| {
+        let info = PageInfo {
+            page: cow!(page),
+            category: category.map(|category| cow!(category)),
+            ..PageInfo::dummy()
+        };
+
+        super::element::scope_class(&info)
+    };
+
+    assert_eq!(
+        scope_class(Some("fruit:red"), "apple pie\"><b>"),
+        "wj-page-fruit_3ared_-apple_20pie_22_3e_3cb_3e",
+    );
+    assert_eq!(scope_class(None, "snake_case"), "wj-page-snake__case");
+
+    // Different pages always have different classes
+    assert_ne!(scope_class(Some("a"), "b"), scope_class(None, "a-b"));
+    assert_ne!(scope_class(Some("a"), "b"), scope_class(None, "a_-b"));
+    assert_ne!(scope_class(None, "a b"), scope_class(None, "a_b"));
+
+    // Imports are removed, since they cannot be scoped
+    assert!(!body.contains("@import"));
+    assert!(!body.contains("global.css"));
+    assert!(body.contains(&format!(".{class} .apple,.{class} p>b{{color:red}}")));
+    assert!(body.contains(&format!(
+        "@media (max-width:100px){{.{class} .banana{{display:none}}}}"
+    )));
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn render_async() {
+    use crate::data::UserInfo;
+    use crate::render::AsyncHandle;
+    use std::borrow::Cow;
+    use std::future::Future;
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+
+    struct TestHandle;
+
+    impl AsyncHandle for TestHandle {
+        async fn get_user_info(&self, name: &str) -> Option<UserInfo<'static>> {
+            let mut info = UserInfo::dummy();
+            info.user_profile_url = Cow::Owned(format!("/profile/{name}"));
+            Some(info)
+        }
+
+        async fn get_page_title(&self, site: &str, page: &str) -> Option<String> {
+            Some(format!("Title of {site}:{page}"))
+        }
+
+        async fn get_message(&self, _language: &str, message: &str) -> Option<String> {
+            match message {
+                "collapsible-open" => Some(str!("Show")),
+                _ => None,
+            }
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut cx = Context::from_waker(Waker::noop());
+
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let tokens = crate::tokenize(
+        "[[user aismallard]] [[[apple|]]]\n\n\
+         [[collapsible]]\nBanana\n[[/collapsible]]",
+    );
+    let (tree, _) = crate::parse(&tokens, &page_info, &settings).into();
+    let output =
+        block_on(HtmlRender.render_async(&tree, &page_info, &settings, &TestHandle));
+
+    assert!(output.body.contains(r#"href="/profile/aismallard""#));
+    assert!(output.body.contains("Title of sandbox:apple"));
+    assert!(output.body.contains("Show"));
+    assert!(output.body.contains("- hide block"));
+
+    // The synchronous renderer is unaffected
+    let output = HtmlRender.render(&tree, &page_info, &settings);
+    assert!(!output.body.contains("/profile/aismallard"));
+    assert!(output.body.contains("+ open block"));
+}
+
+#[test]
+fn minify_output() {
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let minify_settings = WikitextSettings {
+        minify_output: true,
+        ..settings.clone()
+    };
+    let tokens = crate::tokenize(
+        "[[div class=\"outer box\"]]\n**Apple** __banana__\n[[/div]]\n\n\
+         [[code]]\n  cherry\n    durian\n[[/code]]",
+    );
+    let (tree, _) = crate::parse(&tokens, &page_info, &settings).into();
+
+    let expected = HtmlRender.render(&tree, &page_info, &settings);
+    let output = HtmlRender.render(&tree, &page_info, &minify_settings);
+    assert!(output.body.len() < expected.body.len());
+    assert!(output.body.starts_with("<wj-body class=wj-body>"));
+    assert!(output.body.contains(r#"<div class="outer box">"#));
+    assert!(output.body.contains("<strong>Apple</strong> <u>banana</u>"));
+    assert!(output.body.contains("  cherry\n    durian"));
+
+    // Streamed output is minified the same way
+    let mut streamed = String::new();
+    HtmlRender
+        .render_stream(&tree, &page_info, &minify_settings, &mut streamed)
+        .expect("Writing to string failed");
+    assert_eq!(streamed, output.body);
+}
+
+#[test]
+fn metrics() {
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let tokens = crate::tokenize("Apple **banana** [[[cherry]]]");
+    let (tree, _) = crate::parse(&tokens, &page_info, &settings).into();
+
+    let (output, metrics) = HtmlRender.render_with_metrics(&tree, &page_info, &settings);
+    assert_eq!(
+        output.body,
+        HtmlRender.render(&tree, &page_info, &settings).body
+    );
+    assert!(metrics.elements_rendered >= 5);
+    assert_eq!(metrics.bytes_written, output.body.len());
+    assert_eq!(metrics.handle_calls.get("page-exists"), Some(&1));
+
+    // Repeated includes are counted for each copy
+    let include = Element::Include {
+        paragraph_safe: true,
+        variables: hashmap! {},
+        location: crate::data::PageRef::page_only("component:test"),
+        elements: tree.elements[..1].to_vec(),
+    };
+    let render = |count| {
+        let tree = SyntaxTree {
+            elements: vec![include.clone(); count],
+            ..SyntaxTree::default()
+        };
+        let (_, metrics) = HtmlRender.render_with_metrics(&tree, &page_info, &settings);
+        metrics.elements_rendered
+    };
+    assert_eq!(render(2), render(1) * 2);
+}
+
+#[test]
+fn highlight_code() {
+    let page_info = PageInfo::dummy();
+    let text = "[[code type=\"rust\"]]\nlet x = 1;\n[[/code]]\n\
+                [[code type=\"no-such-language\"]]\nlet x = 1;\n[[/code]]";
+    let render = |highlight_code| {
+        let settings = WikitextSettings {
+            highlight_code,
+            ..WikitextSettings::from_mode(WikitextMode::Page)
+        };
+        let tokens = crate::tokenize(text);
+        let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+        HtmlRender.render(&tree, &page_info, &settings).body
+    };
+
+    let body = render(false);
+    assert!(!body.contains("wj-hl-"));
+    assert_eq!(body.matches("<code>let x = 1;</code>").count(), 2);
+
+    // Unknown languages are always output as-is
+    let body = render(true);
+    assert!(body.contains("<code>let x = 1;</code>"));
+
+    if cfg!(feature = "highlight") {
+        assert!(body
+            .contains(r#"<span class="wj-hl-storage wj-hl-type wj-hl-rust">let</span>"#));
+    }
+}
+
+#[test]
+fn file_urls() {
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let tokens = crate::tokenize(concat!(
+        "[/local--files/other-page/report.pdf Report]\n",
+        "[[image /local--files/other-page/photo.png]]\n",
+        "[[image photo.png]]",
+    ));
+    let (tree, _) = crate::parse(&tokens, &page_info, &settings).into();
+    let body = HtmlRender.render(&tree, &page_info, &settings).body;
+
+    assert!(body.contains(
+        r#"href="https://sandbox.wjfiles.com/local--files/other-page/report.pdf""#
+    ));
+    assert!(body.contains(
+        r#"src="https://sandbox.wjfiles.com/local--files/other-page/photo.png""#
+    ));
+    assert!(body.contains(
+        r#"src="https://sandbox.wjfiles.com/local--files/some-page/photo.png""#
+    ));
+}
+
+#[test]
+fn accessibility_audit() {
+    use super::AccessibilityIssue;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let text = concat!(
+        "+ Title\n",
+        "+++ Skipped\n",
+        "[[image photo.png]]\n",
+        "[[image photo.png alt=\"A photo\"]]\n",
+        "[https://example.com/ ]\n",
+        "##red|Warning## ##blue|**Note**##",
+    );
+    let tokens = crate::tokenize(text);
+    let (tree, _) = crate::parse(&tokens, &page_info, &settings).into();
+
+    // Not collected by default
+    let output = HtmlRender.render(&tree, &page_info, &settings);
+    assert!(output.accessibility.is_empty());
+
+    let settings = WikitextSettings {
+        accessibility_audit: true,
+        ..settings.clone()
+    };
+    let output = HtmlRender.render(&tree, &page_info, &settings);
+    assert_eq!(
+        output.accessibility,
+        vec![
+            AccessibilityIssue::HeadingLevelSkipped {
+                previous: 1,
+                level: 3,
+            },
+            AccessibilityIssue::ImageMissingAlt {
+                source: str!(
+                    "https://sandbox.wjfiles.com/local--files/some-page/photo.png"
+                ),
+            },
+            AccessibilityIssue::EmptyLinkLabel {
+                url: str!("https://example.com/"),
+            },
+            AccessibilityIssue::ColorOnlyEmphasis { color: str!("red") },
+        ],
+    );
+
+    // Issues in repeated includes are reported for each copy
+    let tokens = crate::tokenize("[[image photo.png]]");
+    let (image, _) = crate::parse(&tokens, &page_info, &settings).into();
+    let include = Element::Include {
+        paragraph_safe: true,
+        variables: hashmap! {},
+        location: crate::data::PageRef::page_only("component:test"),
+        elements: vec![image.elements[0].clone()],
+    };
+    let tree = SyntaxTree {
+        elements: vec![include.clone(), include],
+        ..SyntaxTree::default()
+    };
+    let output = HtmlRender.render(&tree, &page_info, &settings);
+    assert_eq!(output.accessibility.len(), 2);
+}
+
+#[test]
+fn semantic_html() {
+    use crate::render::RenderStream;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings {
+        semantic_html: true,
+        use_true_ids: false,
+        ..WikitextSettings::from_mode(WikitextMode::Page)
+    };
+
+    let text = concat!(
+        "Intro\n\n",
+        "+ One\n\n",
+        "A\n\n",
+        "++ Two\n\n",
+        "[[div style=\"float: right\"]]\nB\n[[/div]]\n\n",
+        "+ Three\n\n",
+        "[[f>toc]]\n\n",
+        "[[gallery]]\n: a.png\n: b.png title=\"Bee\"\n[[/gallery]]\n\n",
+        "[[f<image c.png]]\n\n",
+        "[[=image d.png]]",
+    );
+    let tokens = crate::tokenize(text);
+    let (tree, _) = crate::parse(&tokens, &page_info, &settings).into();
+    let output = HtmlRender.render(&tree, &page_info, &settings);
+    let body = &output.body;
+
+    assert!(
+        body.starts_with(r#"<wj-body class="wj-body"><article><p>Intro</p><section><h1"#)
+    );
+    assert!(body.contains("</p><section><h2"));
+    assert!(body.contains(
+        r#"<aside style="float: right"><p>B</p></aside></section></section><section><h1"#
+    ));
+    assert!(body.contains(r#"<aside class="wj-float-right">"#));
+    assert!(body.contains(r#"<div class="wj-gallery-item"><img"#));
+    assert!(body.contains(r#"<figure class="wj-gallery-item"><img"#));
+    assert!(body
+        .contains(r#"<figcaption class="wj-gallery-title">Bee</figcaption></figure>"#));
+    assert!(body.contains(r#"<aside class="wj-image-container wj-float-left"><img"#));
+    assert!(body.contains(r#"<figure class="wj-image-container wj-align-center"><img"#));
+    assert!(body.ends_with("</section></article></wj-body>"));
+
+    let mut streamed = String::new();
+    HtmlRender
+        .render_stream(&tree, &page_info, &settings, &mut streamed)
+        .expect("Writing to string failed");
+    assert_eq!(&streamed, body, "Streamed output doesn't match");
+
+    // Only used if enabled
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let output = HtmlRender.render(&tree, &page_info, &settings);
+    assert!(!output.body.contains("<article>"));
+    assert!(!output.body.contains("<section>"));
+    assert!(!output.body.contains("<aside"));
+    assert!(!output.body.contains("<figure"));
+}
+
+#[test]
+fn redirect() {
+    use super::meta::HtmlMetaType;
+    use super::HtmlOutput;
+    use crate::settings::RedirectMode;
+    use crate::tree::LinkLocation;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let text = concat!(
+        "[[module Redirect destination=\"scp-001\"]]\n",
+        "[[module Redirect destination=\"https://example.com/\"]]\n",
+        "Apple",
+    );
+    let tokens = crate::tokenize(text);
+    let (tree, _) = crate::parse(&tokens, &page_info, &settings).into();
+
+    assert_eq!(
+        tree.redirects(),
+        vec![
+            &LinkLocation::parse(cow!("scp-001")),
+            &LinkLocation::parse(cow!("https://example.com/")),
+        ],
+    );
+
+    let refreshes = |output: &HtmlOutput| {
+        output
+            .meta
+            .iter()
+            .filter(|meta| {
+                meta.tag_type == HtmlMetaType::HttpEquiv && meta.name == "refresh"
+            })
+            .map(|meta| meta.value.clone())
+            .collect::<Vec<_>>()
+    };
+
+    // Ignored by default
+    let output = HtmlRender.render(&tree, &page_info, &settings);
+    assert_eq!(
+        output.body,
+        r#"<wj-body class="wj-body"><p>Apple</p></wj-body>"#
+    );
+    assert!(refreshes(&output).is_empty());
+
+    // Only the first redirect is used
+    let settings = WikitextSettings {
+        redirect_mode: RedirectMode::MetaRefresh,
+        ..settings.clone()
+    };
+    let output = HtmlRender.render(&tree, &page_info, &settings);
+    assert_eq!(
+        output.body,
+        r#"<wj-body class="wj-body"><p>Apple</p></wj-body>"#
+    );
+    assert_eq!(refreshes(&output), vec!["0; url=/scp-001"]);
+
+    // Nothing is rendered
+    let settings = WikitextSettings {
+        redirect_mode: RedirectMode::Skip,
+        ..settings.clone()
+    };
+    let output = HtmlRender.render(&tree, &page_info, &settings);
+    assert_eq!(output.body, "");
+    assert!(refreshes(&output).is_empty());
+
+    // Pages without redirects are unaffected
+    let tokens = crate::tokenize("Apple");
+    let (tree, _) = crate::parse(&tokens, &page_info, &settings).into();
+    let output = HtmlRender.render(&tree, &page_info, &settings);
+    assert_eq!(
+        output.body,
+        r#"<wj-body class="wj-body"><p>Apple</p></wj-body>"#
+    );
+}
+
+#[test]
+fn validated_links() {
+    use crate::data::{validate_links, PageRef};
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let tokens = crate::tokenize("[[[apple]]] [[[banana]]]");
+    let (tree, _) = crate::parse(&tokens, &page_info, &settings).into();
+
+    let resolver = |page_ref: &PageRef| page_ref.page() == "apple";
+    let validation = validate_links(&tree, &resolver);
+    let output = HtmlRender.render_validated(&tree, &page_info, &settings, &validation);
+
+    assert!(output.body.contains(
+        r#"<a href="/apple" class="wj-link wj-link-internal" data-link-type="page">"#,
+    ));
+    assert!(output.body.contains(
+        r#"<a href="/banana" class="wj-link wj-link-internal wj-link-missing newpage" data-link-type="page">"#,
+    ));
+
+    // Without validation, the handle is used
+    let output = HtmlRender.render(&tree, &page_info, &settings);
+    assert!(!output.body.contains("newpage"));
+}
+
+#[test]
+fn footnote_equations() {
+    use crate::settings::MathOutput;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings {
+        math_output: MathOutput::Source,
+        ..WikitextSettings::from_mode(WikitextMode::Page)
+    };
+    let text = concat!(
+        "Apple[[footnote]]\n[[math a]]\nx\n[[/math]]\n[[/footnote]]\n\n",
+        "[[math b]]\ny\n[[/math]]\n\n",
+        "[[eref a]] [[eref b]]",
+    );
+    let tokens = crate::tokenize(text);
+    let (tree, _) = crate::parse(&tokens, &page_info, &settings).into();
+    assert_eq!(tree.equations, vec![Some(cow!("a")), Some(cow!("b"))]);
+
+    let body = HtmlRender.render(&tree, &page_info, &settings).body;
+    let number = |index| {
+        format!(
+            r#"<span class="wj-equation-number"><span class="wj-equation-paren wj-equation-paren-open">(</span>{index}<span class="wj-equation-paren wj-equation-paren-close">)</span></span>"#,
+        )
+    };
+
+    // Each equation has one ID, with the footnote's in the footnote block
+    assert_eq!(body.matches(r#"id="wj-equation-1""#).count(), 1);
+    assert_eq!(body.matches(r#"id="wj-equation-2""#).count(), 1);
+    assert!(body.contains(&format!(
+        r#"<div class="wj-math wj-math-block" id="wj-equation-2" data-name="b">{}"#,
+        number(2),
+    )));
+
+    let block = &body[body.find("wj-footnote-list").unwrap()..];
+    assert!(block.contains(&format!(
+        r#"<div class="wj-math wj-math-block" id="wj-equation-1" data-name="a">{}"#,
+        number(1),
+    )));
+
+    // The preview has the same number, without an ID
+    let preview = &body[..body.find("wj-footnote-list").unwrap()];
+    assert!(preview.contains(&format!(
+        r#"<div class="wj-math wj-math-block" data-name="a">{}"#,
+        number(1),
+    )));
+    assert!(!preview.contains(&number(3)));
+}
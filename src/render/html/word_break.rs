@@ -0,0 +1,147 @@
+/*
+ * render/html/word_break.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Insertion of break opportunities into long words.
+//!
+//! Words longer than the configured threshold get a `<wbr>` after
+//! punctuation which commonly separates parts of a URL or name, and
+//! a forced break of the configured style wherever a stretch reaches
+//! the threshold anyway.
+
+use super::escape::escape_char;
+use crate::settings::WordBreakSettings;
+
+/// Escapes the text into the buffer, inserting word breaks as needed.
+pub fn escape_word_breaks(buffer: &mut String, s: &str, settings: &WordBreakSettings) {
+    let threshold = settings.threshold.max(1);
+    let marker = settings.style.html();
+
+    for word in s.split_inclusive(char::is_whitespace) {
+        let length = word.trim_end().chars().count();
+        let mut chars = word.chars().peekable();
+        let mut stretch = 0;
+
+        while let Some(ch) = chars.next() {
+            push_escaped_char(buffer, ch);
+            stretch += 1;
+
+            if length <= threshold {
+                continue;
+            }
+
+            // Never break at the end of a word, or before punctuation
+            match chars.peek() {
+                Some(&next) if !next.is_whitespace() && !is_boundary(next) => (),
+                _ => continue,
+            }
+
+            // Punctuation already marks the break, so only a forced
+            // break in the middle of a run needs a visible hyphen.
+            if is_boundary(ch) {
+                buffer.push_str("<wbr>");
+                stretch = 0;
+            } else if stretch >= threshold {
+                buffer.push_str(marker);
+                stretch = 0;
+            }
+        }
+    }
+}
+
+fn push_escaped_char(buffer: &mut String, ch: char) {
+    match escape_char(ch) {
+        Some(s) => buffer.push_str(s),
+        None => buffer.push(ch),
+    }
+}
+
+/// Characters after which a break looks natural.
+fn is_boundary(ch: char) -> bool {
+    matches!(
+        ch,
+        '/' | '.' | '-' | '_' | '?' | '&' | '=' | ',' | ';' | ':' | '#' | '~' | ')',
+    )
+}
+
+#[test]
+fn word_breaks() {
+    use crate::settings::WordBreakStyle;
+
+    macro_rules! test {
+        ($threshold:expr, $style:expr, $input:expr, $expected:expr $(,)?) => {{
+            let settings = WordBreakSettings {
+                threshold: $threshold,
+                style: $style,
+            };
+
+            let mut buffer = String::new();
+            escape_word_breaks(&mut buffer, $input, &settings);
+
+            assert_eq!(
+                &buffer, $expected,
+                "Word-broken HTML doesn't match expected",
+            );
+        }};
+    }
+
+    // Short words are left alone
+    test!(10, WordBreakStyle::Wbr, "", "");
+    test!(10, WordBreakStyle::Wbr, "apple banana", "apple banana");
+    test!(10, WordBreakStyle::Wbr, "a/b.c-d_e", "a/b.c-d_e");
+    test!(10, WordBreakStyle::Wbr, "<script>", "&lt;script&gt;");
+
+    // Long words break at boundaries
+    test!(
+        10,
+        WordBreakStyle::Wbr,
+        "see https://example.com/some/page here",
+        "see https://<wbr>example.<wbr>com/<wbr>some/<wbr>page here",
+    );
+    test!(
+        10,
+        WordBreakStyle::Wbr,
+        "https://example.com/?a=1&b=2",
+        "https://<wbr>example.<wbr>com/?<wbr>a=<wbr>1&amp;<wbr>b=<wbr>2",
+    );
+
+    // Forced breaks in long runs
+    test!(
+        4,
+        WordBreakStyle::Wbr,
+        "abcdefghij klm",
+        "abcd<wbr>efgh<wbr>ij klm",
+    );
+    test!(
+        12,
+        WordBreakStyle::SoftHyphen,
+        "methylpropylbutylpentyl-ether",
+        "methylpropyl&shy;butylpentyl-<wbr>ether",
+    );
+    test!(
+        8,
+        WordBreakStyle::SoftHyphen,
+        "https://example.com/page",
+        "https://<wbr>example.<wbr>com/<wbr>page",
+    );
+
+    // No trailing break
+    test!(3, WordBreakStyle::Wbr, "abcdef.", "abc<wbr>def.");
+    test!(3, WordBreakStyle::Wbr, "abcdef\n", "abc<wbr>def\n");
+}
@@ -0,0 +1,371 @@
+/*
+ * render/markdown/context.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use crate::data::PageInfo;
+use crate::render::{expect_message, Handle};
+use crate::settings::{Fallback, FallbackElement, WikitextSettings};
+use crate::tree::{BibliographyList, Element, VariableScopes};
+use std::borrow::Cow;
+use std::fmt::{self, Write};
+use std::mem;
+use std::num::NonZeroUsize;
+
+#[derive(Debug)]
+pub struct MarkdownContext<'i, 'h, 'e, 't>
+where
+    'e: 't,
+{
+    output: String,
+    info: &'i PageInfo<'i>,
+    handle: &'h Handle,
+    settings: &'e WikitextSettings,
+
+    //
+    // Included page scopes
+    //
+    variables: VariableScopes,
+
+    //
+    // Elements from the syntax tree
+    //
+    table_of_contents: &'e [Element<'t>],
+    footnotes: &'e [Vec<Element<'t>>],
+    bibliographies: &'e BibliographyList<'t>,
+    equations: &'e [Option<Cow<'t, str>>],
+
+    //
+    // Other fields to track
+    //
+    /// Strings prepended to each new line, such as for blockquotes.
+    prefixes: Vec<String>,
+
+    /// How deep we currently are in nested lists.
+    list_depth: usize,
+
+    /// Whether nothing has been written since the last newline.
+    line_start: bool,
+
+    /// Whether the last line written was empty.
+    blank_line: bool,
+
+    /// Whether only digits have been written since the last newline,
+    /// so that a following `.` or `)` would begin an ordered list.
+    line_digits: bool,
+
+    /// Whether text is being written inside a code span,
+    /// where Markdown syntax is not interpreted.
+    code_span: bool,
+
+    /// The current footnote index, for rendering.
+    footnote_index: NonZeroUsize,
+}
+
+impl<'i, 'h, 'e, 't> MarkdownContext<'i, 'h, 'e, 't>
+where
+    'e: 't,
+{
+    #[inline]
+    pub fn new(
+        info: &'i PageInfo<'i>,
+        handle: &'h Handle,
+        settings: &'e WikitextSettings,
+        table_of_contents: &'e [Element<'t>],
+        footnotes: &'e [Vec<Element<'t>>],
+        bibliographies: &'e BibliographyList<'t>,
+        wikitext_len: usize,
+    ) -> Self {
+        MarkdownContext {
+            output: String::with_capacity(wikitext_len),
+            info,
+            handle,
+            settings,
+            variables: VariableScopes::new(),
+            table_of_contents,
+            footnotes,
+            bibliographies,
+            equations: &[],
+            prefixes: Vec::new(),
+            list_depth: 0,
+            line_start: true,
+            blank_line: true,
+            line_digits: false,
+            code_span: false,
+            footnote_index: NonZeroUsize::new(1).unwrap(),
+        }
+    }
+
+    // Getters
+    #[inline]
+    pub fn info(&self) -> &'i PageInfo<'i> {
+        self.info
+    }
+
+    #[inline]
+    pub fn settings(&self) -> &WikitextSettings {
+        self.settings
+    }
+
+    #[inline]
+    pub fn fallback(&self, element: FallbackElement) -> Fallback {
+        self.settings.markdown_fallbacks.get(element)
+    }
+
+    #[inline]
+    pub fn language(&self) -> &str {
+        &self.info.language
+    }
+
+    #[inline]
+    pub fn handle(&self) -> &'h Handle {
+        self.handle
+    }
+
+    #[inline]
+    pub fn get_message(&self, key: &str) -> Cow<'e, str> {
+        expect_message(&self.settings.messages, self.language(), key)
+    }
+
+    #[inline]
+    pub fn variables(&self) -> &VariableScopes {
+        &self.variables
+    }
+
+    #[inline]
+    pub fn variables_mut(&mut self) -> &mut VariableScopes {
+        &mut self.variables
+    }
+
+    #[inline]
+    pub fn table_of_contents(&self) -> &'e [Element<'t>] {
+        self.table_of_contents
+    }
+
+    #[inline]
+    pub fn footnotes(&self) -> &'e [Vec<Element<'t>>] {
+        self.footnotes
+    }
+
+    pub fn get_bibliography_ref(
+        &self,
+        label: &str,
+    ) -> Option<(usize, &'e [Element<'t>])> {
+        self.bibliographies.get_reference(label)
+    }
+
+    /// Sets the equations from the syntax tree, to resolve references against.
+    #[inline]
+    pub fn set_equations(&mut self, equations: &'e [Option<Cow<'t, str>>]) {
+        self.equations = equations;
+    }
+
+    /// Returns the number of the equation with the given name, if any.
+    pub fn get_equation_number(&self, name: &str) -> Option<NonZeroUsize> {
+        self.equations
+            .iter()
+            .position(|equation| equation.as_deref() == Some(name))
+            .and_then(|index| NonZeroUsize::new(index + 1))
+    }
+
+    /// Returns the label for the footnote with the given index.
+    ///
+    /// This is offset by the `footnote_offset` setting, so that labels
+    /// are unique across fragments of the same page.
+    #[inline]
+    pub fn footnote_id(&self, index: NonZeroUsize) -> usize {
+        index.get() + self.settings.footnote_offset
+    }
+
+    pub fn next_footnote_index(&mut self) -> NonZeroUsize {
+        let index = self.footnote_index;
+        self.footnote_index = NonZeroUsize::new(index.get() + 1).unwrap();
+        index
+    }
+
+    // List depth
+    #[inline]
+    pub fn list_depth(&self) -> usize {
+        self.list_depth
+    }
+
+    #[inline]
+    pub fn incr_list_depth(&mut self) {
+        self.list_depth += 1;
+    }
+
+    #[inline]
+    pub fn decr_list_depth(&mut self) {
+        self.list_depth -= 1;
+    }
+
+    // Prefixes
+
+    /// Begins a prefixed region, such as a blockquote or list item.
+    ///
+    /// The `first` string is written to the current line, which must be empty,
+    /// and `rest` is prepended to each following line until [`pop_prefix`] is called.
+    ///
+    /// [`pop_prefix`]: MarkdownContext::pop_prefix
+    pub fn begin_prefix(&mut self, first: &str, rest: String) {
+        debug_assert!(self.line_start, "Prefix began in the middle of a line");
+
+        self.output.push_str(first);
+        self.prefixes.push(rest);
+        self.blank_line = true;
+    }
+
+    #[inline]
+    pub fn pop_prefix(&mut self) {
+        self.prefixes.pop();
+    }
+
+    // Buffer management
+
+    /// Appends text to the output as-is.
+    pub fn push_str(&mut self, s: &str) {
+        if s.is_empty() {
+            return;
+        }
+
+        self.output.push_str(s);
+        self.line_start = false;
+        self.line_digits = false;
+        self.blank_line = false;
+    }
+
+    /// Appends text to the output, escaping any Markdown syntax.
+    ///
+    /// Within a code span, text is appended as-is instead.
+    pub fn push_escaped(&mut self, s: &str) {
+        for ch in s.chars() {
+            match ch {
+                '\n' => {
+                    self.add_newline();
+                    continue;
+                }
+                _ if self.code_span => (),
+                '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '~' | '&' => {
+                    self.output.push('\\');
+                }
+                '#' | '-' | '+' | '=' if self.line_start => self.output.push('\\'),
+                '.' | ')' if self.line_digits => self.output.push('\\'),
+                _ => (),
+            }
+
+            self.output.push(ch);
+            self.line_digits =
+                ch.is_ascii_digit() && (self.line_start || self.line_digits);
+            self.line_start = false;
+            self.blank_line = false;
+        }
+    }
+
+    pub fn add_newline(&mut self) {
+        self.blank_line = self.line_start;
+        self.line_start = true;
+        self.line_digits = false;
+        self.output.push('\n');
+
+        for prefix in &self.prefixes {
+            self.output.push_str(prefix);
+        }
+    }
+
+    /// Moves to a new line, unless already at the start of one.
+    pub fn end_line(&mut self) {
+        if !self.line_start {
+            self.add_newline();
+        }
+    }
+
+    /// Ensures the next output starts a new block, separated by a blank line.
+    pub fn start_block(&mut self) {
+        if self.output.is_empty() {
+            return;
+        }
+
+        self.end_line();
+
+        if !self.blank_line {
+            self.add_newline();
+        }
+    }
+
+    /// Renders output separately on a single line, returning it.
+    ///
+    /// Any newlines produced are converted to `<br>` tags, so the
+    /// result can be used within constructs such as table cells.
+    pub fn render_fragment<F>(&mut self, f: F) -> String
+    where
+        F: FnOnce(&mut Self),
+    {
+        let output = mem::take(&mut self.output);
+        let prefixes = mem::take(&mut self.prefixes);
+        let line_start = mem::replace(&mut self.line_start, false);
+        let line_digits = mem::replace(&mut self.line_digits, false);
+        let blank_line = mem::replace(&mut self.blank_line, false);
+
+        f(self);
+
+        let fragment = mem::replace(&mut self.output, output);
+        self.prefixes = prefixes;
+        self.line_start = line_start;
+        self.line_digits = line_digits;
+        self.blank_line = blank_line;
+
+        fragment
+            .split('\n')
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("<br>")
+    }
+
+    /// Renders the contents of a code span separately, returning them.
+    ///
+    /// Text is not escaped, since code spans display it literally,
+    /// and newlines are converted to spaces.
+    pub fn render_code_span<F>(&mut self, f: F) -> String
+    where
+        F: FnOnce(&mut Self),
+    {
+        let code_span = mem::replace(&mut self.code_span, true);
+        let fragment = self.render_fragment(f);
+        self.code_span = code_span;
+        fragment.replace("<br>", " ")
+    }
+}
+
+impl<'i, 'h, 'e, 't> From<MarkdownContext<'i, 'h, 'e, 't>> for String {
+    #[inline]
+    fn from(ctx: MarkdownContext<'i, 'h, 'e, 't>) -> String {
+        ctx.output
+    }
+}
+
+impl<'i, 'h, 'e, 't> Write for MarkdownContext<'i, 'h, 'e, 't>
+where
+    'e: 't,
+{
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_escaped(s);
+        Ok(())
+    }
+}
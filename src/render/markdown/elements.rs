@@ -0,0 +1,676 @@
+/*
+ * render/markdown/elements.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Module that implements Markdown rendering for `Element` and its children.
+//!
+//! Output follows CommonMark, plus the widely supported extensions for
+//! tables, strikethrough, footnotes, and math. Inline HTML is used for
+//! formatting which has no Markdown equivalent.
+
+use super::MarkdownContext;
+use crate::entity::find_entity;
+use crate::expr;
+use crate::render::filter_table_of_contents;
+use crate::render::metrics::record_element;
+use crate::settings::{Fallback, FallbackElement};
+use crate::tree::{
+    sort_gallery_images, Alignment, ContainerType, DefinitionListItem, Element,
+    ImageSource, LinkLocation, ListItem, ListType, Tab, Table,
+};
+use crate::url::normalize_href;
+use std::borrow::Cow;
+use std::num::NonZeroUsize;
+
+pub fn render_elements(ctx: &mut MarkdownContext, elements: &[Element]) {
+    info!("Rendering elements (length {})", elements.len());
+
+    for element in elements {
+        render_element(ctx, element);
+    }
+}
+
+pub fn render_element(ctx: &mut MarkdownContext, element: &Element) {
+    enter_span!(
+        "render-element",
+        renderer = "markdown",
+        element = element.name()
+    );
+    info!("Rendering element {}", element.name());
+
+    if ctx.settings().cancellation.is_cancelled() {
+        debug!("Rendering cancelled, skipping element");
+        return;
+    }
+
+    record_element();
+
+    if !ctx.settings().permits(element) {
+        warn!(
+            "Element '{}' not permitted by settings, skipping",
+            element.name(),
+        );
+        return;
+    }
+
+    match element {
+        Element::Container(container) => {
+            let (start, end) = match container.ctype() {
+                // Not shown, Markdown has no way to hide text
+                ContainerType::Hidden | ContainerType::Invisible => return,
+
+                // Block containers
+                ContainerType::Paragraph
+                | ContainerType::Div
+                | ContainerType::Align(_) => {
+                    ctx.start_block();
+                    render_elements(ctx, container.elements());
+                    return;
+                }
+                ContainerType::Blockquote => {
+                    ctx.start_block();
+                    ctx.begin_prefix("> ", str!("> "));
+                    render_elements(ctx, container.elements());
+                    ctx.pop_prefix();
+                    ctx.start_block();
+                    return;
+                }
+                ContainerType::Header(heading) => {
+                    ctx.start_block();
+                    for _ in 0..heading.level.value() {
+                        ctx.push_str("#");
+                    }
+                    ctx.push_str(" ");
+                    render_elements(ctx, container.elements());
+                    ctx.start_block();
+                    return;
+                }
+
+                // Code spans cannot contain other formatting
+                ContainerType::Monospace => {
+                    let contents = ctx.render_code_span(|ctx| {
+                        render_elements(ctx, container.elements())
+                    });
+                    push_code_span(ctx, &contents);
+                    return;
+                }
+
+                // Inline containers
+                ContainerType::Bold => ("**", "**"),
+                ContainerType::Italics => ("*", "*"),
+                ContainerType::Strikethrough => ("~~", "~~"),
+                ContainerType::Underline => ("<u>", "</u>"),
+                ContainerType::Superscript => ("<sup>", "</sup>"),
+                ContainerType::Subscript => ("<sub>", "</sub>"),
+                ContainerType::Mark => ("<mark>", "</mark>"),
+                ContainerType::Insertion => ("<ins>", "</ins>"),
+                ContainerType::Deletion => ("<del>", "</del>"),
+                ContainerType::RubyText => ("(", ")"),
+                ContainerType::Span | ContainerType::Ruby => ("", ""),
+            };
+
+            ctx.push_str(start);
+            render_elements(ctx, container.elements());
+            ctx.push_str(end);
+        }
+        Element::Module(_) => {
+            // Modules only render to HTML, the only fallback is omission
+        }
+        Element::Text(text) | Element::Raw(text) => ctx.push_escaped(text),
+        Element::HtmlEntity(name) => match find_entity(name) {
+            Some(value) => ctx.push_escaped(&value),
+            None => ctx.push_escaped(&format!("&{name};")),
+        },
+        Element::Email(email) => ctx.push_str(&format!("<{email}>")),
+        Element::Variable(name) => {
+            let value = match ctx.variables().get(name) {
+                Some(value) => str!(value),
+                None => format!("{{${name}}}"),
+            };
+
+            info!(
+                "Rendering variable (name '{}', value {})",
+                name.as_ref(),
+                value,
+            );
+            ctx.push_escaped(&value);
+        }
+        Element::Table(table) => render_table(ctx, table),
+        Element::TabView(tabs) => {
+            for Tab { label, elements } in tabs {
+                ctx.start_block();
+                ctx.push_str("**");
+                ctx.push_escaped(label);
+                ctx.push_str("**");
+
+                ctx.start_block();
+                render_elements(ctx, elements);
+            }
+        }
+        Element::Anchor {
+            elements,
+            attributes,
+            ..
+        } => match attributes.get().get("href") {
+            Some(href) => {
+                let url = normalize_href(href);
+                ctx.push_str("[");
+                render_elements(ctx, elements);
+                ctx.push_str("](");
+                push_url(ctx, &url);
+                ctx.push_str(")");
+            }
+            None => render_elements(ctx, elements),
+        },
+        Element::AnchorName(_) => {
+            // Anchor names have no equivalent in Markdown, so they are ignored.
+        }
+        Element::Link { link, label, .. } => {
+            let site = ctx.info().site.as_ref();
+            let url = ctx.handle().get_link_url(link, ctx.info());
+
+            ctx.push_str("[");
+            ctx.handle().get_link_label(site, link, label, |label| {
+                ctx.push_escaped(label);
+            });
+            ctx.push_str("](");
+            push_url(ctx, &url);
+            ctx.push_str(")");
+        }
+        Element::Image {
+            source,
+            link,
+            attributes,
+            ..
+        } => {
+            let alt = attributes.get().get("alt").map(|alt| alt.as_ref());
+            render_image(ctx, source, link, alt);
+        }
+        Element::Media { sources, .. } => {
+            // Markdown cannot embed media, so we link to it instead.
+            if ctx.fallback(FallbackElement::Media) != Fallback::Link {
+                return;
+            }
+
+            let link = sources.iter().find_map(|source| {
+                ctx.handle()
+                    .get_image_link(source, ctx.info(), ctx.settings())
+            });
+            if let Some(url) = link {
+                ctx.push_str(&format!("<{url}>"));
+            }
+        }
+        Element::Gallery { images, order, .. } => {
+            ctx.start_block();
+
+            for image in sort_gallery_images(images, *order) {
+                ctx.end_line();
+                render_image(ctx, &image.source, &image.link, image.title.as_deref());
+            }
+
+            ctx.start_block();
+        }
+        Element::List { ltype, items, .. } => {
+            // Nested lists are placed directly under their parent item
+            if ctx.list_depth() == 0 {
+                ctx.start_block();
+            } else {
+                ctx.end_line();
+            }
+
+            ctx.incr_list_depth();
+
+            let mut index = 1;
+            let mut open = false;
+            for item in items {
+                match item {
+                    ListItem::SubList { element } => render_element(ctx, element),
+                    ListItem::Elements { elements, .. } => {
+                        if elements.is_empty() {
+                            continue;
+                        }
+
+                        if open {
+                            ctx.pop_prefix();
+                        }
+
+                        let marker = match ltype {
+                            ListType::Numbered => format!("{index}. "),
+                            ListType::Bullet | ListType::Generic => str!("- "),
+                        };
+
+                        ctx.end_line();
+                        ctx.begin_prefix(&marker, " ".repeat(marker.len()));
+                        render_elements(ctx, elements);
+                        open = true;
+                        index += 1;
+                    }
+                }
+            }
+
+            if open {
+                ctx.pop_prefix();
+            }
+
+            ctx.decr_list_depth();
+
+            if ctx.list_depth() == 0 {
+                ctx.start_block();
+            }
+        }
+        Element::DefinitionList(items) => {
+            for DefinitionListItem {
+                key_elements,
+                value_elements,
+                ..
+            } in items
+            {
+                ctx.start_block();
+                ctx.push_str("**");
+                render_elements(ctx, key_elements);
+                ctx.push_str("**\\");
+                ctx.add_newline();
+                render_elements(ctx, value_elements);
+            }
+
+            ctx.start_block();
+        }
+        Element::RadioButton { .. } | Element::CheckBox { .. } => {
+            // Form inputs cannot be represented in Markdown, and so are ignored.
+        }
+        Element::RadioGroup { elements, .. } => {
+            // Only the labels of the buttons are shown.
+            render_elements(ctx, elements);
+        }
+        Element::Quote {
+            attribution,
+            elements,
+        } => {
+            ctx.start_block();
+            ctx.begin_prefix("> ", str!("> "));
+
+            if let Some(attribution) = attribution {
+                let wrote = ctx.get_message("quote-wrote");
+
+                ctx.push_str("**");
+                ctx.push_escaped(attribution);
+                ctx.push_str("** ");
+                ctx.push_escaped(&wrote);
+                ctx.start_block();
+            }
+
+            render_elements(ctx, elements);
+            ctx.pop_prefix();
+            ctx.start_block();
+        }
+        Element::Collapsible {
+            elements,
+            show_text,
+            ..
+        } => {
+            let summary = match show_text {
+                Some(text) => Cow::Borrowed(text.as_ref()),
+                None => ctx.get_message("collapsible-open"),
+            };
+
+            ctx.start_block();
+            ctx.push_str("<details>");
+            ctx.add_newline();
+            ctx.push_str("<summary>");
+            push_html_escaped(ctx, &summary);
+            ctx.push_str("</summary>");
+
+            ctx.start_block();
+            render_elements(ctx, elements);
+
+            ctx.start_block();
+            ctx.push_str("</details>");
+            ctx.start_block();
+        }
+        Element::TableOfContents {
+            min_depth,
+            max_depth,
+            ..
+        } => {
+            let table_of_contents =
+                filter_table_of_contents(ctx.table_of_contents(), *min_depth, *max_depth);
+
+            ctx.start_block();
+            render_elements(ctx, &table_of_contents);
+            ctx.start_block();
+        }
+        Element::Footnote => {
+            // Partial renders have no footnote list, so nothing is added.
+            let index = ctx.next_footnote_index();
+
+            if ctx.footnotes().len() >= index.get() {
+                let id = ctx.footnote_id(index);
+                ctx.push_str(&format!("[^{id}]"));
+            }
+        }
+        Element::FootnoteRepeat(index) => {
+            if ctx.footnotes().len() >= index.get() {
+                let id = ctx.footnote_id(*index);
+                ctx.push_str(&format!("[^{id}]"));
+            }
+        }
+        Element::FootnoteBlock { hide, .. } => {
+            if *hide {
+                return;
+            }
+
+            for (index, contents) in ctx.footnotes().iter().enumerate() {
+                let id = ctx.footnote_id(NonZeroUsize::new(index + 1).unwrap());
+                let marker = format!("[^{id}]: ");
+
+                ctx.start_block();
+                ctx.begin_prefix(&marker, str!("    "));
+                render_elements(ctx, contents);
+                ctx.pop_prefix();
+            }
+
+            ctx.start_block();
+        }
+        Element::BibliographyCite { label, .. } => {
+            if let Some((index, _)) = ctx.get_bibliography_ref(label) {
+                str_write!(ctx, "[{index}]");
+            }
+        }
+        Element::BibliographyBlock { .. } => {
+            // Bibliographies have no equivalent in Markdown, so they are skipped.
+        }
+        Element::User { name, .. } => ctx.push_escaped(name),
+        Element::Date { value, format, .. } => {
+            ctx.push_escaped(&value.format_or_default(format.as_deref()));
+        }
+        Element::Color { elements, .. } | Element::Size { elements, .. } => {
+            render_elements(ctx, elements)
+        }
+        Element::Code { contents, language } => {
+            render_code_block(ctx, language.as_deref().unwrap_or(""), contents);
+        }
+        Element::CodeBlob { blob, language, .. } => {
+            // The fence length depends on the contents, so it must be loaded fully
+            let mut contents = String::new();
+            let handle = ctx.handle();
+            if !handle.stream_blob(blob, |chunk| contents.push_str(chunk)) {
+                warn!("Unable to load code block body from blob '{blob}'");
+            }
+
+            render_code_block(ctx, language.as_deref().unwrap_or(""), &contents);
+        }
+        Element::Math { latex_source, .. } => {
+            render_code_block(ctx, "math", latex_source);
+        }
+        Element::MathInline { latex_source } => {
+            ctx.push_str("$");
+            push_code_span(ctx, latex_source);
+            ctx.push_str("$");
+        }
+        Element::Calc { expression } => match expr::evaluate(expression) {
+            Ok(value) => str_write!(ctx, "{value}"),
+            Err(error) => {
+                warn!("Error evaluating calc expression: {error}");
+                str_write!(ctx, "[{error}]");
+            }
+        },
+        Element::EquationReference(name) => match ctx.get_equation_number(name) {
+            Some(number) => str_write!(ctx, "({number})"),
+            None => str_write!(ctx, "[{name}]"),
+        },
+        // Interactive or HTML elements like this cannot be embedded,
+        // so we link to them instead.
+        Element::Embed(embed) => {
+            if ctx.fallback(FallbackElement::Embed) == Fallback::Link {
+                if let Some(url) = embed.direct_url(&ctx.settings().embed_providers) {
+                    ctx.push_str(&format!("<{url}>"));
+                }
+            }
+        }
+        Element::Iframe { url, .. } => {
+            if ctx.fallback(FallbackElement::Iframe) == Fallback::Link {
+                ctx.push_str(&format!("<{url}>"));
+            }
+        }
+        Element::Html { contents } => match ctx.fallback(FallbackElement::Html) {
+            // Raw HTML is only passed through if explicitly enabled.
+            Fallback::Source => {
+                ctx.start_block();
+                for (index, line) in contents.trim_matches('\n').lines().enumerate() {
+                    if index > 0 {
+                        ctx.add_newline();
+                    }
+
+                    ctx.push_str(line);
+                }
+                ctx.start_block();
+            }
+            Fallback::Placeholder => {
+                let message = ctx.get_message("html-placeholder");
+
+                ctx.start_block();
+                ctx.push_str("*");
+                ctx.push_escaped(&format!("[{message}]"));
+                ctx.push_str("*");
+                ctx.start_block();
+            }
+            _ => (),
+        },
+        Element::Include {
+            variables,
+            elements,
+            ..
+        } => {
+            info!(
+                "Rendering include (variables length {}, elements length {})",
+                variables.len(),
+                elements.len(),
+            );
+
+            ctx.variables_mut().push_scope(variables);
+            render_elements(ctx, elements);
+            ctx.variables_mut().pop_scope();
+        }
+        Element::Style(_) | Element::ClearFloat(_) | Element::Redirect { .. } => {
+            // Style blocks, clear float, and redirects do not do anything in Markdown
+        }
+        Element::LineBreak => {
+            ctx.push_str("\\");
+            ctx.add_newline();
+        }
+        Element::LineBreaks(_) => ctx.start_block(),
+        Element::HorizontalRule => {
+            ctx.start_block();
+            ctx.push_str("---");
+            ctx.start_block();
+        }
+        Element::Partial(_) => {
+            error!("Encountered partial element during rendering, skipping");
+        }
+    }
+}
+
+fn render_table(ctx: &mut MarkdownContext, table: &Table) {
+    info!("Rendering table (rows {})", table.rows.len());
+
+    if table.rows.is_empty() {
+        return;
+    }
+
+    // Render each cell separately, spreading out column spans
+    let mut rows = Vec::with_capacity(table.rows.len());
+    for row in &table.rows {
+        let mut cells = Vec::new();
+        for cell in &row.cells {
+            let contents =
+                ctx.render_fragment(|ctx| render_elements(ctx, &cell.elements));
+            cells.push((contents, cell.align));
+
+            for _ in 1..cell.column_span.get() {
+                cells.push((String::new(), cell.align));
+            }
+        }
+
+        rows.push(cells);
+    }
+
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    if columns == 0 {
+        return;
+    }
+
+    ctx.start_block();
+
+    for (index, cells) in rows.iter().enumerate() {
+        ctx.end_line();
+        ctx.push_str("|");
+
+        for column in 0..columns {
+            let contents = cells.get(column).map(|(contents, _)| contents.as_str());
+            ctx.push_str(" ");
+            ctx.push_str(contents.unwrap_or(""));
+            ctx.push_str(" |");
+        }
+
+        // The first row is used as the header
+        if index == 0 {
+            ctx.add_newline();
+            ctx.push_str("|");
+
+            for column in 0..columns {
+                let delimiter = match cells.get(column).and_then(|(_, align)| *align) {
+                    Some(Alignment::Left) => " :-- |",
+                    Some(Alignment::Center) => " :-: |",
+                    Some(Alignment::Right) => " --: |",
+                    Some(Alignment::Justify) | None => " --- |",
+                };
+
+                ctx.push_str(delimiter);
+            }
+        }
+    }
+
+    ctx.start_block();
+}
+
+fn render_code_block(ctx: &mut MarkdownContext, language: &str, contents: &str) {
+    info!("Rendering code block (language {language})");
+
+    // The fence must be longer than any run of backticks within
+    let fence = "`".repeat((longest_backtick_run(contents) + 1).max(3));
+
+    ctx.start_block();
+    ctx.push_str(&fence);
+    ctx.push_str(language);
+
+    for line in contents.lines() {
+        ctx.add_newline();
+        ctx.push_str(line);
+    }
+
+    ctx.add_newline();
+    ctx.push_str(&fence);
+    ctx.start_block();
+}
+
+fn push_code_span(ctx: &mut MarkdownContext, contents: &str) {
+    let fence = "`".repeat(longest_backtick_run(contents) + 1);
+
+    // Padding is stripped from code spans, so it must be
+    // added to keep backticks or spaces at either end.
+    let padded = contents.starts_with('`')
+        || contents.ends_with('`')
+        || (contents.starts_with(' ') && contents.ends_with(' '));
+    let padding = if padded { " " } else { "" };
+
+    ctx.push_str(&fence);
+    ctx.push_str(padding);
+    ctx.push_str(contents);
+    ctx.push_str(padding);
+    ctx.push_str(&fence);
+}
+
+fn longest_backtick_run(contents: &str) -> usize {
+    let mut longest = 0;
+    let mut run = 0;
+    for ch in contents.chars() {
+        if ch == '`' {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    }
+
+    longest
+}
+
+fn render_image(
+    ctx: &mut MarkdownContext,
+    source: &ImageSource,
+    link: &Option<LinkLocation>,
+    alt: Option<&str>,
+) {
+    let source_url = match ctx
+        .handle()
+        .get_image_link(source, ctx.info(), ctx.settings())
+    {
+        Some(url) => url,
+        None => return,
+    };
+
+    if link.is_some() {
+        ctx.push_str("[");
+    }
+
+    ctx.push_str("![");
+    if let Some(alt) = alt {
+        ctx.push_escaped(alt);
+    }
+    ctx.push_str("](");
+    push_url(ctx, &source_url);
+    ctx.push_str(")");
+
+    if let Some(link) = link {
+        let url = ctx.handle().get_link_url(link, ctx.info());
+        ctx.push_str("](");
+        push_url(ctx, &url);
+        ctx.push_str(")");
+    }
+}
+
+fn push_url(ctx: &mut MarkdownContext, url: &str) {
+    if url.contains(|ch: char| ch.is_whitespace() || matches!(ch, '(' | ')' | '<' | '>'))
+    {
+        ctx.push_str("<");
+        ctx.push_str(&url.replace('<', "%3C").replace('>', "%3E"));
+        ctx.push_str(">");
+    } else {
+        ctx.push_str(url);
+    }
+}
+
+fn push_html_escaped(ctx: &mut MarkdownContext, text: &str) {
+    let text = text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+
+    ctx.push_str(&text);
+}
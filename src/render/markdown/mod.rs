@@ -0,0 +1,91 @@
+/*
+ * render/markdown/mod.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! A renderer which produces CommonMark output.
+//!
+//! This is intended for exporting wiki content to systems which only
+//! accept Markdown. Constructs which Markdown cannot express are either
+//! written as inline HTML (such as underlines or superscripts), use common
+//! extensions (such as pipe tables and footnotes), or are omitted.
+
+#[cfg(test)]
+mod test;
+
+mod context;
+mod elements;
+
+use self::context::MarkdownContext;
+use self::elements::render_elements;
+use crate::data::PageInfo;
+use crate::render::{Handle, Render};
+use crate::settings::WikitextSettings;
+use crate::tree::SyntaxTree;
+
+#[derive(Debug)]
+pub struct MarkdownRender;
+
+impl Render for MarkdownRender {
+    type Output = String;
+
+    fn render(
+        &self,
+        tree: &SyntaxTree,
+        page_info: &PageInfo,
+        settings: &WikitextSettings,
+    ) -> String {
+        info!(
+            "Rendering Markdown (site {}, page {}, category {})",
+            page_info.site.as_ref(),
+            page_info.page.as_ref(),
+            match &page_info.category {
+                Some(category) => category.as_ref(),
+                None => "_default",
+            },
+        );
+
+        if settings.redirect_mode.skips(tree) {
+            info!("Page redirects, skipping rendering");
+            return String::new();
+        }
+
+        let handle = Handle::new(settings);
+        let mut ctx = MarkdownContext::new(
+            page_info,
+            &handle,
+            settings,
+            &tree.table_of_contents,
+            &tree.footnotes,
+            &tree.bibliographies,
+            tree.wikitext_len,
+        );
+        ctx.set_equations(&tree.equations);
+        render_elements(&mut ctx, &tree.elements);
+
+        // Remove leading and trailing whitespace
+        let mut output: String = ctx.into();
+        let end = output.trim_end().len();
+        output.truncate(end);
+
+        let start = output.len() - output.trim_start_matches('\n').len();
+        output.drain(..start);
+
+        output
+    }
+}
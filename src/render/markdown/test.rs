@@ -0,0 +1,97 @@
+/*
+ * render/markdown/test.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::MarkdownRender;
+use crate::data::PageInfo;
+use crate::render::Render;
+use crate::settings::{WikitextMode, WikitextSettings};
+
+#[test]
+fn markdown() {
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+
+    macro_rules! check {
+        ($input:expr, $expected:expr $(,)?) => {{
+            let mut text = str!($input);
+            crate::preprocess(&mut text);
+            let tokens = crate::tokenize(&text);
+            let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+            let output = MarkdownRender.render(&tree, &page_info, &settings);
+
+            println!("Input:  {:?}", $input);
+            println!("Output: {:?}", output);
+
+            assert_eq!(
+                output, $expected,
+                "Rendered Markdown doesn't match expected"
+            );
+        }};
+    }
+
+    check!("", "");
+    check!(
+        "**bold** //italics// __underline__ --strike-- {{mono}}",
+        "**bold** *italics* <u>underline</u> ~~strike~~ `mono`",
+    );
+    check!("plain*star [not] a #tag", r"plain\*star \[not\] a #tag");
+    check!("1. not a list", r"1\. not a list");
+    check!("2) nor this", r"2\) nor this");
+    check!("version 1.2 (or 3)", "version 1.2 (or 3)");
+    check!("a\n10. b", "a\\\n10\\. b");
+    check!("```not code", r"\`\`\`not code");
+    check!("@@~~~not code@@", r"\~\~\~not code");
+    check!("@@> not a quote@@", r"\> not a quote");
+    check!("line one\nline two", "line one\\\nline two");
+    check!("+ Heading\n\nText", "# Heading\n\nText");
+    check!("> quote\n> more", "> quote\\\n> more");
+    check!("* a\n * b\n* c", "- a\n  - b\n- c");
+    check!("# one\n# two", "1. one\n2. two");
+    check!(
+        "||~ A ||~ B ||\n|| 1 || 2 ||",
+        "| A | B |\n| --- | --- |\n| 1 | 2 |",
+    );
+    check!(
+        "[https://example.com/ Example] [[[page|Page]]]",
+        "[Example](https://example.com/) [Page](/page)",
+    );
+    check!(
+        "[[code type=\"rust\"]]\nlet x = `y`;\n[[/code]]",
+        "```rust\nlet x = `y`;\n```",
+    );
+    check!(
+        "See[[eref area]].\n\n[[math area]]\nx\n[[/math]]",
+        "See(1).\n\n```math\nx\n```",
+    );
+    check!(
+        "Note[[footnote]]Footnote text[[/footnote]].\n\n----",
+        "Note[^1].\n\n---\n\n[^1]: Footnote text",
+    );
+    check!(
+        "[[collapsible show=\"Open\"]]\nHidden stuff\n[[/collapsible]]",
+        "<details>\n<summary>Open</summary>\n\nHidden stuff\n\n</details>",
+    );
+    check!("{{a*b_[c]}}", "`a*b_[c]`");
+    check!("{{x `y` z}}", "``x `y` z``");
+    check!("{{`tick}}", "`` `tick ``");
+    check!("[[$ a`b $]]", "$``a`b``$");
+    check!("[[html]]\n<b>hi</b>\n[[/html]]", r"*\[HTML content\]*",);
+    check!("[[date 1700000000 format=\"%Y\"]]", "2023");
+}
@@ -0,0 +1,270 @@
+/*
+ * render/messages.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Localized strings for interface text added by renderers.
+//!
+//! Text such as the default collapsible labels or the footnote block title
+//! is not part of the wikitext, and so must be produced in the language of
+//! the page, as given by [`PageInfo::language`](crate::data::PageInfo::language).
+//!
+//! Renderers use the catalog in [`WikitextSettings::messages`], which is
+//! the built-in one unless a site sets its own. The built-in messages are
+//! found in `locales/`. Languages without their own messages, or messages
+//! missing from one, fall back to English.
+//!
+//! [`WikitextSettings::messages`]: crate::settings::WikitextSettings::messages
+
+use once_cell::sync::Lazy;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::{self, Debug};
+use std::sync::Arc;
+
+/// The built-in messages, by language.
+pub(super) const BUILTIN_MESSAGES: [(&str, &str); 4] = [
+    ("en", include_str!("../../locales/en.ftl")),
+    ("de", include_str!("../../locales/de.ftl")),
+    ("es", include_str!("../../locales/es.ftl")),
+    ("fr", include_str!("../../locales/fr.ftl")),
+];
+
+/// The language used for any message not available in the requested one.
+pub(super) const FALLBACK_LANGUAGE: &str = "en";
+
+static DEFAULT_CATALOG: Lazy<BuiltinCatalog> = Lazy::new(BuiltinCatalog::new);
+
+/// A source of localized messages, such as `collapsible-open`.
+///
+/// See `MESSAGE_KEYS` for the full list of messages renderers may request.
+pub trait MessageCatalog {
+    /// Returns the message with this key in the given language, if any.
+    ///
+    /// The language is as in [`PageInfo::language`](crate::data::PageInfo::language).
+    fn get_message(&self, language: &str, key: &str) -> Option<Cow<'_, str>>;
+}
+
+/// Gets a message from the catalog, logging an error if it doesn't exist.
+pub(crate) fn expect_message<'a, C>(
+    catalog: &'a C,
+    language: &str,
+    key: &str,
+) -> Cow<'a, str>
+where
+    C: MessageCatalog + ?Sized,
+{
+    match catalog.get_message(language, key) {
+        Some(message) => message,
+        None => {
+            error!("Unknown message requested (key {key})");
+            cow!("?")
+        }
+    }
+}
+
+/// The message catalog used by renderers, as set in the settings.
+///
+/// By default this is the built-in catalog. Clones share the same catalog,
+/// so one may be loaded once and then used for every render.
+///
+/// Catalogs are not serialized, and two sources only compare as equal
+/// if they use the same catalog.
+#[derive(Clone, Default)]
+pub struct MessageSource(Option<Arc<dyn MessageCatalog + Send + Sync>>);
+
+impl MessageSource {
+    /// Uses the given catalog instead of the built-in one.
+    pub fn new<C>(catalog: C) -> Self
+    where
+        C: MessageCatalog + Send + Sync + 'static,
+    {
+        MessageSource(Some(Arc::new(catalog)))
+    }
+
+    /// Uses the built-in catalog.
+    #[inline]
+    pub fn builtin() -> Self {
+        MessageSource(None)
+    }
+}
+
+impl MessageCatalog for MessageSource {
+    fn get_message(&self, language: &str, key: &str) -> Option<Cow<'_, str>> {
+        info!("Fetching message (language {language}, key {key})");
+
+        match self.0 {
+            Some(ref catalog) => catalog.get_message(language, key),
+            None => DEFAULT_CATALOG.get_message(language, key),
+        }
+    }
+}
+
+impl Debug for MessageSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self.0 {
+            Some(_) => "custom",
+            None => "builtin",
+        };
+
+        f.debug_tuple("MessageSource").field(&name).finish()
+    }
+}
+
+impl PartialEq for MessageSource {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.0, &other.0) {
+            (Some(x), Some(y)) => Arc::ptr_eq(x, y),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for MessageSource {}
+
+/// The built-in messages.
+///
+/// The resources in `locales/` only contain simple `key = value` messages,
+/// so they are read directly, without needing a Fluent implementation.
+#[derive(Debug)]
+struct BuiltinCatalog {
+    languages: HashMap<&'static str, HashMap<&'static str, &'static str>>,
+}
+
+impl BuiltinCatalog {
+    fn new() -> Self {
+        let languages = BUILTIN_MESSAGES
+            .iter()
+            .map(|&(language, source)| {
+                let messages = source
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .filter_map(|line| line.split_once('='))
+                    .map(|(key, value)| (key.trim(), value.trim()))
+                    .collect();
+
+                (language, messages)
+            })
+            .collect();
+
+        BuiltinCatalog { languages }
+    }
+
+    fn format(&self, language: &str, key: &str) -> Option<&'static str> {
+        self.languages.get(language)?.get(key).copied()
+    }
+}
+
+impl MessageCatalog for BuiltinCatalog {
+    fn get_message(&self, language: &str, key: &str) -> Option<Cow<'_, str>> {
+        // Try the language as given, then without any region or script,
+        // for instance "pt-BR" then "pt", and finally the fallback.
+        let language = language.to_ascii_lowercase();
+        let base = language.split(['-', '_']).next().unwrap_or_default();
+
+        self.format(&language, key)
+            .or_else(|| self.format(base, key))
+            .or_else(|| self.format(FALLBACK_LANGUAGE, key))
+            .map(Cow::Borrowed)
+    }
+}
+
+#[test]
+fn builtin_catalog() {
+    use super::handle::MESSAGE_KEYS;
+
+    let catalog = BuiltinCatalog::new();
+
+    // Each built-in language has every message
+    for (language, _) in BUILTIN_MESSAGES {
+        for key in MESSAGE_KEYS {
+            assert!(
+                catalog.format(language, key).is_some(),
+                "Message {key} missing for language {language}",
+            );
+        }
+    }
+
+    macro_rules! check {
+        ($language:expr, $key:expr, $expected:expr $(,)?) => {
+            assert_eq!(
+                catalog.get_message($language, $key).as_deref(),
+                $expected,
+                "Message {} in {} doesn't match expected",
+                $key,
+                $language,
+            );
+        };
+    }
+
+    check!("en", "footnote-block-title", Some("Footnotes"));
+    check!("fr", "footnote-block-title", Some("Notes de bas de page"));
+    check!("de-AT", "footnote-block-title", Some("Fußnoten"));
+    check!("ja", "footnote-block-title", Some("Footnotes"));
+    check!("default", "footnote-block-title", Some("Footnotes"));
+    check!("en", "rate-down", Some("I don't like it"));
+    check!("en", "nonexistent", None);
+}
+
+#[test]
+fn custom_catalog() {
+    use crate::data::PageInfo;
+    use crate::render::text::TextRender;
+    use crate::render::Render;
+    use crate::settings::{WikitextMode, WikitextSettings};
+
+    struct TestCatalog;
+
+    impl MessageCatalog for TestCatalog {
+        fn get_message(&self, language: &str, key: &str) -> Option<Cow<'_, str>> {
+            match key {
+                "footnote-block-title" => Some(Cow::Owned(format!("Notes ({language})"))),
+                _ => None,
+            }
+        }
+    }
+
+    let page_info = PageInfo::dummy();
+    let mut settings = WikitextSettings::from_mode(WikitextMode::Page);
+    settings.messages = MessageSource::new(TestCatalog);
+    assert_ne!(settings.messages, MessageSource::builtin());
+    assert_eq!(settings.messages, settings.clone().messages);
+
+    let tokens = crate::tokenize("apple[[footnote]]banana[[/footnote]]");
+    let result = crate::parse(&tokens, &page_info, &settings);
+    let (tree, _errors) = result.into();
+    let output = TextRender.render(&tree, &page_info, &settings);
+    assert!(
+        output.contains("Notes (default)"),
+        "Custom catalog not used in text output: {output:?}",
+    );
+
+    #[cfg(feature = "html")]
+    {
+        use crate::render::html::HtmlRender;
+
+        let output = HtmlRender.render(&tree, &page_info, &settings);
+        assert!(
+            output.body.contains("Notes (default)"),
+            "Custom catalog not used in HTML output: {:?}",
+            output.body,
+        );
+    }
+}
@@ -0,0 +1,124 @@
+/*
+ * render/metrics.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Measurements of the work done in a single render.
+//!
+//! See [`Render::render_with_metrics()`](super::Render::render_with_metrics).
+
+use crate::audit::time;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+thread_local! {
+    static METRICS: RefCell<Option<RenderMetrics>> = const { RefCell::new(None) };
+}
+
+/// Figures describing the cost of a single render.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct RenderMetrics {
+    /// How many elements were rendered.
+    ///
+    /// This includes elements rendered internally, such as when
+    /// heading names are rendered as text for their IDs.
+    pub elements_rendered: usize,
+
+    /// The size of the output, in bytes.
+    pub bytes_written: usize,
+
+    /// How long the render took, in microseconds.
+    ///
+    /// On WebAssembly there is no monotonic clock available,
+    /// so this is always zero.
+    pub elapsed: u64,
+
+    /// How many times each kind of handle callback was made.
+    ///
+    /// The keys are the same as the lookup types in an
+    /// [`AuditLog`](crate::audit::AuditLog), such as `page-exists`.
+    pub handle_calls: BTreeMap<String, usize>,
+}
+
+/// Output types whose size can be reported in [`RenderMetrics`].
+pub trait OutputSize {
+    /// The size of this output, in bytes.
+    fn output_size(&self) -> usize;
+}
+
+impl OutputSize for String {
+    #[inline]
+    fn output_size(&self) -> usize {
+        self.len()
+    }
+}
+
+impl OutputSize for () {
+    #[inline]
+    fn output_size(&self) -> usize {
+        0
+    }
+}
+
+/// Runs a render, recording metrics about it.
+///
+/// Any metrics already being recorded on this thread are set aside
+/// and restored afterwards, so that nested renders don't interfere.
+pub(crate) fn measure<O, F>(f: F) -> (O, RenderMetrics)
+where
+    O: OutputSize,
+    F: FnOnce() -> O,
+{
+    let previous =
+        METRICS.with(|metrics| metrics.replace(Some(RenderMetrics::default())));
+
+    let (output, elapsed) = time(f);
+
+    let mut metrics = METRICS
+        .with(|metrics| metrics.replace(previous))
+        .unwrap_or_default();
+
+    metrics.elapsed = elapsed;
+    metrics.bytes_written = output.output_size();
+    (output, metrics)
+}
+
+/// Whether metrics are being recorded on this thread.
+#[inline]
+pub(crate) fn is_recording() -> bool {
+    METRICS.with(|metrics| metrics.borrow().is_some())
+}
+
+/// Records that an element was rendered, if metrics are being recorded.
+pub(crate) fn record_element() {
+    METRICS.with(|metrics| {
+        if let Some(metrics) = metrics.borrow_mut().as_mut() {
+            metrics.elements_rendered += 1;
+        }
+    });
+}
+
+/// Records a handle callback of the given kind, if metrics are being recorded.
+pub(crate) fn record_handle_call(kind: &str) {
+    METRICS.with(|metrics| {
+        if let Some(metrics) = metrics.borrow_mut().as_mut() {
+            *metrics.handle_calls.entry(str!(kind)).or_default() += 1;
+        }
+    });
+}
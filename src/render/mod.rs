@@ -20,25 +20,57 @@
 
 #[allow(unused_imports)]
 mod prelude {
-    pub use super::Render;
+    pub use super::{Render, RenderStream};
     pub use crate::data::PageInfo;
     pub use crate::settings::{WikitextMode, WikitextSettings};
     pub use crate::tree::{AttributeMap, Container, ContainerType, Element, SyntaxTree};
 }
 
+pub mod breadcrumbs;
 pub mod debug;
+pub mod markdown;
 pub mod null;
 pub mod text;
 
 #[cfg(feature = "html")]
 pub mod html;
 
+#[cfg(feature = "async")]
+mod async_handle;
+
+#[cfg(feature = "fluent")]
+mod fluent;
+
 mod handle;
+mod messages;
+pub(crate) mod metrics;
+mod page_variables;
+mod prefetch;
+mod toc;
+
+#[cfg(feature = "async")]
+pub use self::async_handle::AsyncHandle;
 
-use self::handle::Handle;
+#[cfg(feature = "fluent")]
+pub use self::fluent::FluentCatalog;
+#[cfg(test)]
+pub(crate) use self::handle::test_blobs;
+pub(crate) use self::handle::Handle;
+pub(crate) use self::messages::expect_message;
+pub use self::messages::{MessageCatalog, MessageSource};
+pub use self::metrics::{OutputSize, RenderMetrics};
+pub(crate) use self::page_variables::{
+    substitute_page_variables, substitute_page_variables_partial,
+};
+#[cfg(feature = "html")]
+pub(crate) use self::prefetch::Prefetched;
+pub(crate) use self::toc::filter_table_of_contents;
 use crate::data::PageInfo;
 use crate::settings::WikitextSettings;
 use crate::tree::SyntaxTree;
+use crate::FtmlError;
+use std::fmt;
+use std::io;
 
 /// Abstract trait for any ftml renderer.
 ///
@@ -65,4 +97,99 @@ pub trait Render {
         page_info: &PageInfo,
         settings: &WikitextSettings,
     ) -> Self::Output;
+
+    /// Render an abstract syntax tree, also reporting metrics about the render.
+    ///
+    /// This includes the number of elements rendered, the output size,
+    /// the time taken, and how many callbacks were made to the handle.
+    fn render_with_metrics(
+        &self,
+        tree: &SyntaxTree,
+        page_info: &PageInfo,
+        settings: &WikitextSettings,
+    ) -> (Self::Output, RenderMetrics)
+    where
+        Self::Output: OutputSize,
+    {
+        metrics::measure(|| self.render(tree, page_info, settings))
+    }
+
+    /// Render an abstract syntax tree, failing if it was cancelled partway.
+    ///
+    /// Renderers stop early once the
+    /// [`CancellationToken`](crate::settings::CancellationToken) in the settings
+    /// is cancelled, leaving the output incomplete. In that case this returns
+    /// [`FtmlError::Cancelled`] rather than the partial output.
+    fn try_render(
+        &self,
+        tree: &SyntaxTree,
+        page_info: &PageInfo,
+        settings: &WikitextSettings,
+    ) -> Result<Self::Output, FtmlError> {
+        let output = self.render(tree, page_info, settings);
+
+        if settings.cancellation.is_cancelled() {
+            warn!("Rendering was cancelled, discarding output");
+            return Err(FtmlError::Cancelled);
+        }
+
+        Ok(output)
+    }
+}
+
+/// Trait for renderers which can write their output incrementally.
+///
+/// Rather than building the entire output in memory, the renderer writes
+/// it to the sink in chunks as rendering progresses. This keeps memory use
+/// down for very large pages, and allows responses to be flushed early.
+pub trait RenderStream: Render {
+    /// Render an abstract syntax tree, writing the output into `writer`.
+    ///
+    /// Any parts of the output which are not streamed, such as metadata,
+    /// are returned as usual. The streamed portion is left empty.
+    fn render_stream<W: fmt::Write>(
+        &self,
+        tree: &SyntaxTree,
+        page_info: &PageInfo,
+        settings: &WikitextSettings,
+        writer: &mut W,
+    ) -> Result<Self::Output, fmt::Error>;
+
+    /// Like [`render_stream()`](RenderStream::render_stream), but for byte sinks
+    /// such as files or sockets.
+    fn render_stream_io<W: io::Write>(
+        &self,
+        tree: &SyntaxTree,
+        page_info: &PageInfo,
+        settings: &WikitextSettings,
+        writer: &mut W,
+    ) -> io::Result<Self::Output> {
+        let mut adapter = IoAdapter {
+            writer,
+            error: None,
+        };
+
+        match self.render_stream(tree, page_info, settings, &mut adapter) {
+            Ok(output) => Ok(output),
+            Err(_) => Err(adapter
+                .error
+                .unwrap_or_else(|| io::Error::other("formatter error"))),
+        }
+    }
+}
+
+/// Adapter to write formatted output into a byte sink, keeping any I/O error.
+#[derive(Debug)]
+struct IoAdapter<'w, W: io::Write> {
+    writer: &'w mut W,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> fmt::Write for IoAdapter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.writer.write_all(s.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
 }
@@ -53,6 +53,7 @@ fn null() {
         vec![],
         vec![],
         BibliographyList::new(),
+        vec![],
         0,
     );
     let (tree, _) = result.into();
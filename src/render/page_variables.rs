@@ -0,0 +1,234 @@
+/*
+ * render/page_variables.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Substitution of Wikidot page variables, such as `%%title%%`.
+//!
+//! These placeholders are most common in ListPages templates, and are
+//! replaced with information about the page being rendered. Any
+//! unknown variables are left as-is.
+
+use super::Handle;
+use crate::data::{PageInfo, PageRef};
+use crate::settings::WikitextSettings;
+use crate::tree::visit::transform_lists;
+use crate::tree::{
+    walk_elements, Element, LinkLabel, LinkLocation, LinkType, ListPagesTemplatePart,
+    SyntaxTree,
+};
+use std::borrow::Cow;
+use std::mem;
+
+/// Replaces page variables within the text of this tree, if enabled.
+///
+/// This is done once before rendering. If the setting is off, or there
+/// is nothing to substitute, the original tree is returned as-is.
+pub fn substitute_page_variables<'a, 't>(
+    handle: &Handle,
+    info: &PageInfo,
+    settings: &WikitextSettings,
+    tree: &'a SyntaxTree<'t>,
+) -> Cow<'a, SyntaxTree<'t>> {
+    if !settings.substitute_page_variables {
+        return Cow::Borrowed(tree);
+    }
+
+    let mut has_marker = has_marker(&tree.table_of_contents);
+    tree.walk(|element| has_marker |= is_marker(element));
+
+    if !has_marker {
+        return Cow::Borrowed(tree);
+    }
+
+    let mut tree = tree.clone();
+    let mut substitute = |elements: &mut Vec<Element<'t>>| {
+        substitute_elements(handle, info, elements);
+    };
+
+    transform_lists(&mut tree.table_of_contents, &mut substitute);
+    tree.transform_lists(substitute);
+    Cow::Owned(tree)
+}
+
+/// Replaces page variables within the text of these elements, if enabled.
+///
+/// This is the same as [`substitute_page_variables()`], for rendering
+/// elements outside of a syntax tree.
+pub fn substitute_page_variables_partial<'a, 't>(
+    handle: &Handle,
+    info: &PageInfo,
+    settings: &WikitextSettings,
+    elements: &'a [Element<'t>],
+) -> Cow<'a, [Element<'t>]> {
+    if !settings.substitute_page_variables || !has_marker(elements) {
+        return Cow::Borrowed(elements);
+    }
+
+    let mut elements = elements.to_vec();
+    transform_lists(&mut elements, |elements| {
+        substitute_elements(handle, info, elements);
+    });
+    Cow::Owned(elements)
+}
+
+fn has_marker(elements: &[Element]) -> bool {
+    let mut has_marker = false;
+    walk_elements(elements, &mut |element| has_marker |= is_marker(element));
+    has_marker
+}
+
+#[inline]
+fn is_marker(element: &Element) -> bool {
+    matches!(element, Element::Text(text) if text.contains('%'))
+}
+
+/// Replaces page variables within a list of sibling elements.
+///
+/// The tokenizer splits `%` characters into separate text elements,
+/// so each run of adjacent text elements is joined before searching.
+fn substitute_elements<'t>(
+    handle: &Handle,
+    info: &PageInfo,
+    elements: &mut Vec<Element<'t>>,
+) {
+    if !elements.iter().any(is_marker) {
+        return;
+    }
+
+    let mut output = Vec::with_capacity(elements.len());
+    let mut run = Vec::new();
+
+    for element in mem::take(elements) {
+        match element {
+            Element::Text(text) => run.push(text),
+            element => {
+                substitute_run(handle, info, &mut run, &mut output);
+                output.push(element);
+            }
+        }
+    }
+
+    substitute_run(handle, info, &mut run, &mut output);
+    *elements = output;
+}
+
+/// Moves a run of text into the output, replacing any page variables in it.
+fn substitute_run<'t>(
+    handle: &Handle,
+    info: &PageInfo,
+    run: &mut Vec<Cow<'t, str>>,
+    output: &mut Vec<Element<'t>>,
+) {
+    if run.is_empty() {
+        return;
+    }
+
+    let text = run.concat();
+    match substitute_text(handle, info, &text) {
+        Some(parts) => {
+            output.extend(parts);
+            run.clear();
+        }
+        None => output.extend(run.drain(..).map(Element::Text)),
+    }
+}
+
+fn substitute_text(
+    handle: &Handle,
+    info: &PageInfo,
+    text: &str,
+) -> Option<Vec<Element<'static>>> {
+    let mut elements = Vec::new();
+    let mut substituted = false;
+
+    for part in ListPagesTemplatePart::parse(text) {
+        match part {
+            ListPagesTemplatePart::Text(text) => {
+                elements.push(Element::Text(Cow::Owned(text.into_owned())));
+            }
+            ListPagesTemplatePart::Field(name) => {
+                match get_page_variable(handle, info, &name) {
+                    Some(element) => {
+                        elements.push(element);
+                        substituted = true;
+                    }
+                    None => {
+                        debug!("Unknown page variable '{name}', leaving as-is");
+                        elements.push(Element::Text(Cow::Owned(format!("%%{name}%%"))));
+                    }
+                }
+            }
+        }
+    }
+
+    if substituted {
+        Some(elements)
+    } else {
+        None
+    }
+}
+
+fn get_page_variable(
+    handle: &Handle,
+    info: &PageInfo,
+    name: &str,
+) -> Option<Element<'static>> {
+    info!("Substituting page variable '{name}'");
+
+    let category = match &info.category {
+        Some(category) => category.as_ref(),
+        None => "_default",
+    };
+
+    let fullname = match &info.category {
+        Some(category) => format!("{category}:{}", info.page),
+        None => info.page.to_string(),
+    };
+
+    let value = match name {
+        "title" => info.title.to_string(),
+        "title_linked" => {
+            return Some(Element::Link {
+                ltype: LinkType::Page,
+                link: LinkLocation::Page(PageRef::page_only(fullname)),
+                label: LinkLabel::Text(Cow::Owned(info.title.to_string())),
+                target: None,
+            });
+        }
+        "name" => info.page.to_string(),
+        "fullname" => fullname,
+        "category" => str!(category),
+        "rating" => info.score.to_rating_string(),
+        "tags" => join_tags(info, false),
+        "_tags" => join_tags(info, true),
+        _ => handle.get_page_field(&info.site, &fullname, name)?,
+    };
+
+    Some(Element::Text(Cow::Owned(value)))
+}
+
+/// Joins either the visible or the hidden (underscore-prefixed) tags.
+fn join_tags(info: &PageInfo, hidden: bool) -> String {
+    info.tags
+        .iter()
+        .filter(|tag| tag.starts_with('_') == hidden)
+        .map(|tag| tag.as_ref())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
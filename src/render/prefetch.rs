@@ -0,0 +1,120 @@
+/*
+ * render/prefetch.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Lookup results retrieved before rendering, for asynchronous handles.
+
+use crate::data::UserInfo;
+use std::collections::HashMap;
+
+#[cfg(feature = "async")]
+use {
+    super::{handle::MESSAGE_KEYS, AsyncHandle},
+    crate::data::PageInfo,
+    crate::tree::{Element, LinkLabel, LinkLocation, SyntaxTree},
+    std::collections::BTreeSet,
+};
+
+/// Results of lookups fetched ahead of time by an [`AsyncHandle`].
+///
+/// Anything not present here is looked up using the regular handle.
+#[derive(Debug, Default)]
+#[cfg_attr(not(feature = "async"), allow(dead_code))]
+pub struct Prefetched {
+    users: HashMap<String, Option<UserInfo<'static>>>,
+    page_titles: HashMap<String, HashMap<String, Option<String>>>,
+    messages: HashMap<&'static str, String>,
+}
+
+#[cfg_attr(not(feature = "async"), allow(dead_code))]
+impl Prefetched {
+    #[inline]
+    pub fn get_user_info(&self, name: &str) -> Option<&Option<UserInfo<'static>>> {
+        self.users.get(name)
+    }
+
+    #[inline]
+    pub fn get_page_title(&self, site: &str, page: &str) -> Option<&Option<String>> {
+        self.page_titles
+            .get(site)
+            .and_then(|titles| titles.get(page))
+    }
+
+    #[inline]
+    pub fn get_message(&self, message: &str) -> Option<&str> {
+        self.messages.get(message).map(String::as_str)
+    }
+}
+
+#[cfg(feature = "async")]
+impl Prefetched {
+    /// Performs all the lookups needed to render the given tree.
+    pub async fn fetch<H: AsyncHandle>(
+        handle: &H,
+        tree: &SyntaxTree<'_>,
+        page_info: &PageInfo<'_>,
+    ) -> Self {
+        info!("Prefetching lookups for rendering");
+
+        // Gather the users and page titles needed
+        let mut users = BTreeSet::new();
+        let mut pages = BTreeSet::new();
+
+        tree.walk(|element| match element {
+            Element::User { name, .. } => {
+                users.insert(name.as_ref());
+            }
+            Element::Link {
+                link: LinkLocation::Page(page_ref),
+                label: LinkLabel::Page,
+                ..
+            } => {
+                pages.insert(page_ref.fields_or(&page_info.site));
+            }
+            _ => (),
+        });
+
+        // Perform the lookups
+        let mut prefetched = Prefetched::default();
+
+        for name in users {
+            debug!("Prefetching user info (name '{name}')");
+            let user_info = handle.get_user_info(name).await;
+            prefetched.users.insert(str!(name), user_info);
+        }
+
+        for (site, page) in pages {
+            debug!("Prefetching page title (site {site}, page {page})");
+            let title = handle.get_page_title(site, page).await;
+            prefetched
+                .page_titles
+                .entry(str!(site))
+                .or_default()
+                .insert(str!(page), title);
+        }
+
+        for key in MESSAGE_KEYS {
+            if let Some(message) = handle.get_message(&page_info.language, key).await {
+                prefetched.messages.insert(key, message);
+            }
+        }
+
+        prefetched
+    }
+}
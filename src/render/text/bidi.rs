@@ -0,0 +1,116 @@
+/*
+ * render/text/bidi.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Helpers for handling bidirectional and combining text in plain-text output.
+
+use unicode_bidi::{bidi_class, BidiClass};
+use unicode_width::UnicodeWidthChar;
+
+/// Unicode "first strong isolate", which starts an isolated run of text.
+pub const FIRST_STRONG_ISOLATE: char = '\u{2068}';
+
+/// Unicode "pop directional isolate", which ends an isolated run of text.
+pub const POP_DIRECTIONAL_ISOLATE: char = '\u{2069}';
+
+/// Languages which are written right-to-left, by primary subtag.
+const RTL_LANGUAGES: [&str; 13] = [
+    "ar", "arc", "ckb", "dv", "fa", "he", "iw", "ps", "sd", "syr", "ug", "ur", "yi",
+];
+
+/// The direction of a run of text.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Direction {
+    LeftToRight,
+    RightToLeft,
+}
+
+impl Direction {
+    /// Determines the base direction for text in the given language, such as `he` or `en-US`.
+    pub fn from_language(language: &str) -> Self {
+        let primary = language.split(['-', '_']).next().unwrap_or("");
+
+        if RTL_LANGUAGES
+            .iter()
+            .any(|rtl| rtl.eq_ignore_ascii_case(primary))
+        {
+            Direction::RightToLeft
+        } else {
+            Direction::LeftToRight
+        }
+    }
+}
+
+/// Returns the strong direction of this character, if it has one.
+///
+/// This is based on the character's Unicode bidirectional class.
+pub fn strong_direction(ch: char) -> Option<Direction> {
+    match bidi_class(ch) {
+        BidiClass::L => Some(Direction::LeftToRight),
+        BidiClass::R | BidiClass::AL => Some(Direction::RightToLeft),
+        _ => None,
+    }
+}
+
+/// Determines if this text should be wrapped in directional isolates.
+///
+/// This is the case if it has any strongly directional characters which
+/// go against the base direction, since otherwise the surrounding text
+/// could be reordered around it.
+pub fn needs_isolation(text: &str, base: Direction) -> bool {
+    text.chars()
+        .filter_map(strong_direction)
+        .any(|direction| direction != base)
+}
+
+/// Determines if this character takes up no space of its own.
+///
+/// This covers combining characters, which attach to the previous one,
+/// as well as joiners, variation selectors, and directional formatting.
+/// These shouldn't be counted separately when measuring text.
+#[inline]
+pub fn is_zero_width(ch: char) -> bool {
+    ch.width() == Some(0)
+}
+
+#[test]
+fn bidi() {
+    assert_eq!(Direction::from_language("en"), Direction::LeftToRight);
+    assert_eq!(Direction::from_language("he"), Direction::RightToLeft);
+    assert_eq!(Direction::from_language("ar-EG"), Direction::RightToLeft);
+    assert_eq!(Direction::from_language("FA"), Direction::RightToLeft);
+    assert_eq!(Direction::from_language("fr"), Direction::LeftToRight);
+
+    assert!(!needs_isolation("SCP-173", Direction::LeftToRight));
+    assert!(needs_isolation("SCP-173", Direction::RightToLeft));
+    assert!(needs_isolation("שלום", Direction::LeftToRight));
+    assert!(!needs_isolation("שלום 123", Direction::RightToLeft));
+    assert!(needs_isolation("مرحبا SCP", Direction::RightToLeft));
+    assert!(!needs_isolation("123 -- !", Direction::RightToLeft));
+    assert!(needs_isolation("\u{07CA}\u{07CB}", Direction::LeftToRight));
+    assert!(needs_isolation("\u{10900}", Direction::LeftToRight));
+
+    assert!(is_zero_width('\u{0301}'));
+    assert!(is_zero_width('\u{05B8}'));
+    assert!(is_zero_width(FIRST_STRONG_ISOLATE));
+    assert!(is_zero_width('\u{0941}'));
+    assert!(is_zero_width('\u{0E31}'));
+    assert!(!is_zero_width('a'));
+    assert!(!is_zero_width('ש'));
+}
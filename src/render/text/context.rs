@@ -18,13 +18,16 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+use super::bidi::is_zero_width;
 use crate::data::PageInfo;
 use crate::non_empty_vec::NonEmptyVec;
-use crate::render::Handle;
-use crate::settings::WikitextSettings;
+use crate::render::{expect_message, Handle};
+use crate::settings::{Fallback, FallbackElement, FootnoteBlockMode, WikitextSettings};
 use crate::tree::{Bibliography, BibliographyList, Element, VariableScopes};
+use std::borrow::Cow;
 use std::fmt::{self, Write};
 use std::num::NonZeroUsize;
+use std::ops::Range;
 
 #[derive(Debug)]
 pub struct TextContext<'i, 'h, 'e, 't>
@@ -47,6 +50,7 @@ where
     table_of_contents: &'e [Element<'t>],
     footnotes: &'e [Vec<Element<'t>>],
     bibliographies: &'e BibliographyList<'t>,
+    equations: &'e [Option<Cow<'t, str>>],
 
     //
     // Other fields to track
@@ -67,6 +71,10 @@ where
 
     /// The current footnote index, for rendering.
     footnote_index: NonZeroUsize,
+
+    /// How many footnotes were listed before each footnote block,
+    /// ending with the block currently being filled.
+    footnote_block_starts: Vec<usize>,
 }
 
 impl<'i, 'h, 'e, 't> TextContext<'i, 'h, 'e, 't>
@@ -83,6 +91,7 @@ where
         bibliographies: &'e BibliographyList<'t>,
         wikitext_len: usize,
     ) -> Self {
+        // Text output is rarely longer than its wikitext, since markup is removed.
         TextContext {
             output: String::with_capacity(wikitext_len),
             info,
@@ -92,11 +101,13 @@ where
             table_of_contents,
             footnotes,
             bibliographies,
+            equations: &[],
             prefixes: Vec::new(),
             list_depths: NonEmptyVec::new(1),
             invisible: 0,
             equation_index: NonZeroUsize::new(1).unwrap(),
             footnote_index: NonZeroUsize::new(1).unwrap(),
+            footnote_block_starts: vec![0],
         }
     }
 
@@ -112,10 +123,15 @@ where
     }
 
     #[inline]
-    pub fn settings(&self) -> &WikitextSettings {
+    pub fn settings(&self) -> &'e WikitextSettings {
         self.settings
     }
 
+    #[inline]
+    pub fn fallback(&self, element: FallbackElement) -> Fallback {
+        self.settings.text_fallbacks.get(element)
+    }
+
     #[inline]
     pub fn language(&self) -> &str {
         &self.info.language
@@ -126,6 +142,11 @@ where
         self.handle
     }
 
+    #[inline]
+    pub fn get_message(&self, key: &str) -> Cow<'e, str> {
+        expect_message(&self.settings.messages, self.language(), key)
+    }
+
     #[inline]
     pub fn variables(&self) -> &VariableScopes {
         &self.variables
@@ -158,6 +179,20 @@ where
         self.bibliographies.get_reference(label)
     }
 
+    /// Sets the equations from the syntax tree, to resolve references against.
+    #[inline]
+    pub fn set_equations(&mut self, equations: &'e [Option<Cow<'t, str>>]) {
+        self.equations = equations;
+    }
+
+    /// Returns the number of the equation with the given name, if any.
+    pub fn get_equation_number(&self, name: &str) -> Option<NonZeroUsize> {
+        self.equations
+            .iter()
+            .position(|equation| equation.as_deref() == Some(name))
+            .and_then(|index| NonZeroUsize::new(index + 1))
+    }
+
     pub fn next_equation_index(&mut self) -> NonZeroUsize {
         let index = self.equation_index;
         self.equation_index = NonZeroUsize::new(index.get() + 1).unwrap();
@@ -170,6 +205,40 @@ where
         index
     }
 
+    /// Returns the displayed number for the footnote with the given index.
+    ///
+    /// If footnotes are split between blocks, this is the footnote's position
+    /// within the block listing it. Repeated references to a footnote in an
+    /// earlier block use that block's number. Footnotes numbered from the
+    /// start of the page continue on from the `footnote_offset` setting.
+    pub fn footnote_number(&self, index: NonZeroUsize) -> NonZeroUsize {
+        let block = self
+            .footnote_block_starts
+            .partition_point(|&start| start < index.get());
+
+        let start = self.footnote_block_starts[block - 1];
+        let offset = if start == 0 {
+            self.settings.footnote_offset
+        } else {
+            0
+        };
+
+        NonZeroUsize::new(index.get() - start + offset).unwrap()
+    }
+
+    /// Returns the range of footnotes to be listed by the next footnote block.
+    pub fn next_footnote_block_range(&mut self) -> Range<usize> {
+        match self.settings.footnote_block_mode {
+            FootnoteBlockMode::Repeat => 0..self.footnotes.len(),
+            FootnoteBlockMode::Split => {
+                let start = *self.footnote_block_starts.last().unwrap();
+                let end = (self.footnote_index.get() - 1).min(self.footnotes.len());
+                self.footnote_block_starts.push(end);
+                start..end
+            }
+        }
+    }
+
     // Prefixes
     #[inline]
     pub fn push_prefix(&mut self, prefix: &'static str) {
@@ -230,7 +299,9 @@ where
 
     pub fn push_str(&mut self, s: &str) {
         if self.invisible() {
-            let chars = s.chars().count();
+            // Characters such as combining marks have no width,
+            // so they don't get their own space.
+            let chars = s.chars().filter(|&ch| !is_zero_width(ch)).count();
             for _ in 0..chars {
                 self.output.push(' ');
             }
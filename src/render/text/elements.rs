@@ -27,8 +27,21 @@
 //! (such as indenting each line of a blockquote) should not occur.
 //! Any formatting present must be directly justifiable.
 
+use super::bidi::{
+    needs_isolation, Direction, FIRST_STRONG_ISOLATE, POP_DIRECTIONAL_ISOLATE,
+};
 use super::TextContext;
-use crate::tree::{ContainerType, DefinitionListItem, Element, ListItem, Tab};
+use crate::entity::find_entity;
+use crate::expr;
+use crate::render::filter_table_of_contents;
+use crate::render::metrics::record_element;
+use crate::settings::{Fallback, FallbackElement};
+use crate::tree::{
+    sort_gallery_images, ContainerType, DefinitionListItem, Element, ListItem, Module,
+    Tab,
+};
+use std::num::NonZeroUsize;
+use std::slice;
 
 pub fn render_elements(ctx: &mut TextContext, elements: &[Element]) {
     info!("Rendering elements (length {})", elements.len());
@@ -39,8 +52,28 @@ pub fn render_elements(ctx: &mut TextContext, elements: &[Element]) {
 }
 
 pub fn render_element(ctx: &mut TextContext, element: &Element) {
+    enter_span!(
+        "render-element",
+        renderer = "text",
+        element = element.name()
+    );
     info!("Rendering element {}", element.name());
 
+    if ctx.settings().cancellation.is_cancelled() {
+        debug!("Rendering cancelled, skipping element");
+        return;
+    }
+
+    record_element();
+
+    if !ctx.settings().permits(element) {
+        warn!(
+            "Element '{}' not permitted by settings, skipping",
+            element.name(),
+        );
+        return;
+    }
+
     match element {
         Element::Container(container) => {
             let mut invisible = false;
@@ -94,12 +127,24 @@ pub fn render_element(ctx: &mut TextContext, element: &Element) {
                 ctx.disable_invisible();
             }
         }
+        Element::Module(Module::Rate) => {
+            if !ctx.ends_with_newline() {
+                ctx.add_newline();
+            }
+
+            let score = ctx.info().score.to_rating_string();
+            ctx.push_str(&format!("rating: {score}"));
+        }
         Element::Module(_) => {
-            // We don't want to render modules at all
+            // Modules only render to HTML, the only fallback is omission
         }
         Element::Text(text) | Element::Raw(text) | Element::Email(text) => {
             ctx.push_str(text);
         }
+        Element::HtmlEntity(name) => match find_entity(name) {
+            Some(value) => ctx.push_str(&value),
+            None => ctx.push_str(&format!("&{name};")),
+        },
         Element::Variable(name) => {
             let value = match ctx.variables().get(name) {
                 Some(value) => str!(value),
@@ -130,7 +175,11 @@ pub fn render_element(ctx: &mut TextContext, element: &Element) {
         }
         Element::TabView(tabs) => {
             for Tab { label, elements } in tabs {
-                // Add tab name
+                // Add tab name, as a header on its own line
+                if !ctx.ends_with_newline() {
+                    ctx.add_newline();
+                }
+
                 ctx.push_str(label);
                 ctx.add_newline();
 
@@ -149,11 +198,60 @@ pub fn render_element(ctx: &mut TextContext, element: &Element) {
 
             ctx.handle().get_link_label(site, link, label, |label| {
                 // Only write the label, i.e. the part that's visible
-                ctx.push_str(label);
+                if ctx.settings().text_bidi_isolation
+                    && needs_isolation(label, Direction::from_language(ctx.language()))
+                {
+                    let label =
+                        format!("{FIRST_STRONG_ISOLATE}{label}{POP_DIRECTIONAL_ISOLATE}");
+                    ctx.push_str(&label);
+                } else {
+                    ctx.push_str(label);
+                }
             });
         }
-        Element::Image { .. } => {
-            // Text cannot render images, so we don't add anything
+        Element::Image { source, .. } => {
+            // Text cannot render images, but we can point to them
+            if ctx.fallback(FallbackElement::Image) == Fallback::Link {
+                let link =
+                    ctx.handle()
+                        .get_image_link(source, ctx.info(), ctx.settings());
+                if let Some(url) = link {
+                    str_write!(ctx, "[image: {url}]");
+                }
+            }
+        }
+        Element::Media {
+            media_type,
+            sources,
+            ..
+        } => {
+            // Text cannot play media, but we can point to it
+            if ctx.fallback(FallbackElement::Media) == Fallback::Link {
+                let link = sources.iter().find_map(|source| {
+                    ctx.handle()
+                        .get_image_link(source, ctx.info(), ctx.settings())
+                });
+                if let Some(url) = link {
+                    str_write!(ctx, "[{}: {url}]", media_type.name());
+                }
+            }
+        }
+        Element::Gallery { images, order, .. } => {
+            if !ctx.ends_with_newline() {
+                ctx.add_newline();
+            }
+
+            for image in sort_gallery_images(images, *order) {
+                let link = ctx.handle().get_image_link(
+                    &image.source,
+                    ctx.info(),
+                    ctx.settings(),
+                );
+                if let Some(url) = link {
+                    ctx.push_str(&url);
+                    ctx.add_newline();
+                }
+            }
         }
         Element::List { items, .. } => {
             if !ctx.ends_with_newline() {
@@ -194,54 +292,227 @@ pub fn render_element(ctx: &mut TextContext, element: &Element) {
         Element::RadioButton { .. } | Element::CheckBox { .. } => {
             // These cannot be rendered in text mode, and so are ignored.
         }
+        Element::RadioGroup { elements, .. } => {
+            // Only the labels of the buttons are shown.
+            render_elements(ctx, elements);
+        }
         Element::Collapsible { elements, .. } => {
-            // For collapsibles, we simply show the contents.
+            // If not omitted, we simply show the contents.
             // No collapsible labels (open or close) are shown.
 
+            if ctx.fallback(FallbackElement::Collapsible) == Fallback::Contents {
+                render_elements(ctx, elements);
+            }
+        }
+        Element::Quote {
+            attribution,
+            elements,
+        } => {
+            if !ctx.ends_with_newline() {
+                ctx.add_newline();
+            }
+
+            // Render the quote separately, so each of its lines can be prefixed
+            let start = ctx.buffer().len();
+
+            if let Some(attribution) = attribution {
+                let wrote = ctx.get_message("quote-wrote");
+
+                ctx.push_str(attribution);
+                ctx.push(' ');
+                ctx.push_str(&wrote);
+                ctx.add_newline();
+            }
+
             render_elements(ctx, elements);
+
+            let quoted = ctx.buffer().split_off(start);
+            for line in quoted.trim_matches('\n').lines() {
+                if line.is_empty() {
+                    ctx.push('>');
+                } else {
+                    ctx.push_str("> ");
+                    ctx.push_str(line);
+                }
+
+                ctx.add_newline();
+            }
         }
-        Element::TableOfContents { .. } => {
-            // Doesn't make sense to have a textual table of contents, skip
+        Element::TableOfContents {
+            min_depth,
+            max_depth,
+            ..
+        } => {
+            // Generally doesn't make sense to have a textual table of contents,
+            // but if requested, render the heading list.
+
+            if ctx.fallback(FallbackElement::TableOfContents) == Fallback::Contents {
+                if !ctx.ends_with_newline() {
+                    ctx.add_newline();
+                }
+
+                let table_of_contents = filter_table_of_contents(
+                    ctx.table_of_contents(),
+                    *min_depth,
+                    *max_depth,
+                );
+                render_table_of_contents(ctx, &table_of_contents, 0);
+            }
         }
-        Element::Footnote
-        | Element::FootnoteBlock { .. }
-        | Element::BibliographyCite { .. }
-        | Element::BibliographyBlock { .. } => {
-            // Footnotes and bibliographies cannot be cleanly rendered in text mode,
-            // so they are skipped.
+        Element::Footnote => {
+            // Only the reference marker is rendered, since the footnote
+            // contents cannot be cleanly placed in text mode.
+            let index = ctx.next_footnote_index();
+            render_footnote_label(ctx, index);
         }
-        Element::User { name, .. } => ctx.push_str(name),
-        Element::Date { value, format, .. } => {
-            // TEMP
-            if format.is_some() {
-                warn!("Time format passed, feature currently not supported!");
+        Element::FootnoteRepeat(index) => render_footnote_label(ctx, *index),
+        Element::FootnoteBlock { title, hide } => {
+            let footnotes = ctx.next_footnote_block_range();
+
+            if *hide
+                || footnotes.is_empty()
+                || ctx.fallback(FallbackElement::FootnoteBlock) != Fallback::Contents
+            {
+                return;
+            }
+
+            // Render the title and each footnote on its own line,
+            // numbered to match the inline markers.
+            let title_default;
+            let title = match title {
+                Some(title) => title.as_ref(),
+                None => match ctx.settings().footnote_block_title {
+                    Some(ref title) => title.as_str(),
+                    None => {
+                        title_default = ctx.get_message("footnote-block-title");
+                        &title_default
+                    }
+                },
+            };
+
+            if !ctx.ends_with_newline() {
+                ctx.add_newline();
             }
 
-            // TODO handle error
-            match value.format() {
-                Ok(datetime) => str_write!(ctx, "{}", datetime),
-                Err(error) => {
-                    error!("Error formatting date into string: {error}");
-                    str_write!(ctx, "<ERROR>");
+            ctx.push_str(title);
+            ctx.add_newline();
+
+            let start = footnotes.start;
+            for (index, elements) in ctx.footnotes()[footnotes].iter().enumerate() {
+                let index = NonZeroUsize::new(start + index + 1).unwrap();
+                let number = ctx.footnote_number(index);
+                let number = ctx.settings().footnote_numbering.format(number);
+
+                str_write!(ctx, "{number}. ");
+                render_elements(ctx, elements);
+                ctx.add_newline();
+            }
+        }
+        Element::BibliographyCite { label, brackets } => {
+            match ctx.get_bibliography_ref(label) {
+                Some((index, _)) if *brackets => str_write!(ctx, "[{index}]"),
+                Some((index, _)) => str_write!(ctx, "{index}"),
+                None => {
+                    let message = ctx.get_message("bibliography-cite-not-found");
+                    ctx.push_str(&message);
                 }
+            }
+        }
+        Element::BibliographyBlock { index, title, hide } => {
+            if *hide {
+                return;
+            }
+
+            // Render the title and each entry on its own line
+            let title = match title {
+                Some(title) => title.as_ref(),
+                None => &ctx.get_message("bibliography-block-title"),
             };
+
+            if !ctx.ends_with_newline() {
+                ctx.add_newline();
+            }
+
+            ctx.push_str(title);
+            ctx.add_newline();
+
+            let bibliography = ctx.get_bibliography(*index);
+            for (entry_index, (_, elements)) in bibliography.slice().iter().enumerate() {
+                str_write!(ctx, "{}. ", entry_index + 1);
+                render_elements(ctx, elements);
+                ctx.add_newline();
+            }
+        }
+        Element::User { name, .. } => ctx.push_str(name),
+        Element::Date { value, format, .. } => {
+            ctx.push_str(&value.format_or_default(format.as_deref()));
+        }
+        Element::Color { elements, .. } | Element::Size { elements, .. } => {
+            render_elements(ctx, elements)
         }
-        Element::Color { elements, .. } => render_elements(ctx, elements),
         Element::Code { contents, .. } => {
             ctx.add_newline();
             ctx.push_str(contents);
             ctx.add_newline();
         }
-        Element::Math { .. } | Element::MathInline { .. } => {
-            // No real way to render arbitrary LaTeX, so we skip it.
+        Element::CodeBlob { blob, .. } => {
+            ctx.add_newline();
+            let handle = ctx.handle();
+            if !handle.stream_blob(blob, |chunk| ctx.push_str(chunk)) {
+                warn!("Unable to load code block body from blob '{blob}'");
+            }
+            ctx.add_newline();
+        }
+        Element::Math { latex_source, .. } => {
+            // No real way to render arbitrary LaTeX, but we can show the source.
+            if ctx.fallback(FallbackElement::Math) == Fallback::Source {
+                ctx.add_newline();
+                ctx.push_str(latex_source);
+                ctx.add_newline();
+            }
+        }
+        Element::MathInline { latex_source } => {
+            if ctx.fallback(FallbackElement::MathInline) == Fallback::Source {
+                ctx.push_str(latex_source);
+            }
         }
-        Element::EquationReference(name) => {
-            str_write!(ctx, "[{name}]");
+        Element::Calc { expression } => match expr::evaluate(expression) {
+            Ok(value) => str_write!(ctx, "{value}"),
+            Err(error) => {
+                warn!("Error evaluating calc expression: {error}");
+                str_write!(ctx, "[{error}]");
+            }
+        },
+        Element::EquationReference(name) => match ctx.get_equation_number(name) {
+            Some(number) => str_write!(ctx, "({number})"),
+            None => str_write!(ctx, "[{name}]"),
+        },
+        // Interactive or HTML elements like this don't make sense in
+        // text mode, so we either skip them or refer to them.
+        Element::Embed(embed) => {
+            if ctx.fallback(FallbackElement::Embed) == Fallback::Link {
+                if let Some(url) = embed.direct_url(&ctx.settings().embed_providers) {
+                    ctx.push_str(&url);
+                }
+            }
         }
-        Element::Embed(_) | Element::Html { .. } | Element::Iframe { .. } => {
-            // Interactive or HTML elements like this don't make sense in
-            // text mode, so we skip them.
+        Element::Iframe { url, .. } => {
+            if ctx.fallback(FallbackElement::Iframe) == Fallback::Link {
+                ctx.push_str(url);
+            }
         }
+        Element::Html { contents } => match ctx.fallback(FallbackElement::Html) {
+            Fallback::Placeholder => {
+                let label = ctx.get_message("html-placeholder");
+                str_write!(ctx, "[{label}]");
+            }
+            Fallback::Source => {
+                ctx.add_newline();
+                ctx.push_str(contents);
+                ctx.add_newline();
+            }
+            _ => (),
+        },
         Element::Include {
             variables,
             elements,
@@ -257,8 +528,8 @@ pub fn render_element(ctx: &mut TextContext, element: &Element) {
             render_elements(ctx, elements);
             ctx.variables_mut().pop_scope();
         }
-        Element::Style(_) | Element::ClearFloat(_) => {
-            // Style blocks and clear float do not do anything in text mode
+        Element::Style(_) | Element::ClearFloat(_) | Element::Redirect { .. } => {
+            // Style blocks, clear float, and redirects do not do anything in text mode
         }
         Element::LineBreak => ctx.add_newline(),
         Element::LineBreaks(amount) => {
@@ -272,6 +543,48 @@ pub fn render_element(ctx: &mut TextContext, element: &Element) {
             //
             // So we take the safe option of doing nothing.
         }
-        Element::Partial(_) => panic!("Encountered partial element during parsing"),
+        Element::Partial(_) => {
+            error!("Encountered partial element during rendering, skipping");
+        }
+    }
+}
+
+/// Renders the marker for a reference to the given footnote.
+///
+/// Partial renders have no footnote list, so nothing is added.
+fn render_footnote_label(ctx: &mut TextContext, index: NonZeroUsize) {
+    if ctx.footnotes().len() >= index.get() {
+        let number = ctx.footnote_number(index);
+        let settings = ctx.settings();
+        let label = settings
+            .footnote_style
+            .text_label(number, &settings.footnote_numbering);
+        ctx.push_str(&label);
+    }
+}
+
+/// Renders the table of contents lists as an outline, indented by heading depth.
+fn render_table_of_contents(ctx: &mut TextContext, elements: &[Element], depth: usize) {
+    for element in elements {
+        let items = match element {
+            Element::List { items, .. } => items,
+            _ => continue,
+        };
+
+        for item in items {
+            match item {
+                ListItem::SubList { element } => {
+                    render_table_of_contents(ctx, slice::from_ref(element), depth + 1);
+                }
+                ListItem::Elements { elements, .. } => {
+                    for _ in 0..depth {
+                        ctx.push_str("  ");
+                    }
+
+                    render_elements(ctx, elements);
+                    ctx.add_newline();
+                }
+            }
+        }
     }
 }
@@ -18,15 +18,19 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+mod bidi;
 mod context;
 mod elements;
 
 use self::context::TextContext;
 use self::elements::render_elements;
 use crate::data::PageInfo;
-use crate::render::{Handle, Render};
+use crate::render::{
+    substitute_page_variables, substitute_page_variables_partial, Handle, Render,
+};
 use crate::settings::WikitextSettings;
 use crate::tree::{BibliographyList, Element, SyntaxTree};
+use std::borrow::Cow;
 
 #[derive(Debug)]
 pub struct TextRender;
@@ -40,13 +44,21 @@ impl TextRender {
         settings: &WikitextSettings,
         wikitext_len: usize,
     ) -> String {
-        self.render_partial_direct(RenderPartial {
+        let elements = substitute_page_variables_partial(
+            &Handle::new(settings),
+            page_info,
+            settings,
             elements,
+        );
+
+        self.render_partial_direct(RenderPartial {
+            elements: &elements,
             page_info,
             settings,
             table_of_contents: &[],
             footnotes: &[],
             bibliographies: &BibliographyList::new(),
+            equations: &[],
             wikitext_len,
         })
     }
@@ -60,6 +72,7 @@ impl TextRender {
             table_of_contents,
             footnotes,
             bibliographies,
+            equations,
             wikitext_len,
         }: RenderPartial,
     ) -> String {
@@ -73,25 +86,29 @@ impl TextRender {
             },
         );
 
+        let handle = Handle::new(settings);
         let mut ctx = TextContext::new(
             page_info,
-            &Handle,
+            &handle,
             settings,
             table_of_contents,
             footnotes,
             bibliographies,
             wikitext_len,
         );
+        ctx.set_equations(equations);
         render_elements(&mut ctx, elements);
 
         // Remove leading and trailing newlines
-        while ctx.buffer().starts_with('\n') {
-            ctx.buffer().remove(0);
-        }
+        //
+        // These are trimmed as ranges, rather than one character at a time,
+        // since removing from the front shifts the entire buffer.
+        let buffer = ctx.buffer();
+        let end = buffer.trim_end_matches('\n').len();
+        buffer.truncate(end);
 
-        while ctx.buffer().ends_with('\n') {
-            ctx.buffer().pop();
-        }
+        let start = buffer.len() - buffer.trim_start_matches('\n').len();
+        buffer.drain(..start);
 
         ctx.into()
     }
@@ -107,6 +124,14 @@ impl Render for TextRender {
         page_info: &PageInfo,
         settings: &WikitextSettings,
     ) -> String {
+        if settings.redirect_mode.skips(tree) {
+            info!("Page redirects, skipping rendering");
+            return String::new();
+        }
+
+        let tree =
+            substitute_page_variables(&Handle::new(settings), page_info, settings, tree);
+
         self.render_partial_direct(RenderPartial {
             elements: &tree.elements,
             page_info,
@@ -114,6 +139,7 @@ impl Render for TextRender {
             table_of_contents: &tree.table_of_contents,
             footnotes: &tree.footnotes,
             bibliographies: &tree.bibliographies,
+            equations: &tree.equations,
             wikitext_len: tree.wikitext_len,
         })
     }
@@ -131,5 +157,232 @@ struct RenderPartial<'a> {
     table_of_contents: &'a [Element<'a>],
     footnotes: &'a [Vec<Element<'a>>],
     bibliographies: &'a BibliographyList<'a>,
+    equations: &'a [Option<Cow<'a, str>>],
     wikitext_len: usize,
 }
+
+#[test]
+fn bibliography() {
+    use crate::settings::WikitextMode;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let text = "Apple((bibcite b)) banana[[bibcite a]] cherry((bibcite c))\n\n\
+                [[bibliography title=\"Sources\"]]\n: a : First\n: b : Second\n[[/bibliography]]";
+
+    let tokens = crate::tokenize(text);
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let output = TextRender.render(&tree, &page_info, &settings);
+
+    assert_eq!(
+        output,
+        "Apple2 banana[1] cherryBibliography item not found\n\
+         Sources\n1. First\n2. Second",
+    );
+}
+
+#[test]
+fn tabview() {
+    use crate::settings::WikitextMode;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let text = "Apple\n[[tabview]]\n[[tab First]]\nBanana\n[[/tab]]\n\
+                [[tab Second]]\nCherry\n[[/tab]]\n[[/tabview]]";
+
+    let tokens = crate::tokenize(text);
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let output = TextRender.render(&tree, &page_info, &settings);
+
+    assert_eq!(output, "Apple\nFirst\n\nBanana\n\nSecond\n\nCherry");
+}
+
+#[test]
+fn image() {
+    use crate::settings::{Fallback, FallbackElement, WikitextMode};
+
+    let page_info = PageInfo::dummy();
+    let mut settings = WikitextSettings::from_mode(WikitextMode::Page);
+    settings
+        .text_fallbacks
+        .set(FallbackElement::Image, Fallback::Link);
+    let text = "Apple [[image https://example.com/banana.png]] cherry";
+
+    let tokens = crate::tokenize(text);
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let output = TextRender.render(&tree, &page_info, &settings);
+
+    assert_eq!(
+        output,
+        "Apple [image: https://example.com/banana.png] cherry"
+    );
+}
+
+#[test]
+fn gallery() {
+    use crate::settings::WikitextMode;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let text = "Apple\n[[gallery order=\"title\"]]\n\
+                : https://example.com/cherry.png title=\"Cherry\"\n\
+                : https://example.com/banana.png title=\"Banana\"\n\
+                [[/gallery]]";
+
+    let tokens = crate::tokenize(text);
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let output = TextRender.render(&tree, &page_info, &settings);
+
+    assert_eq!(
+        output,
+        "Apple\nhttps://example.com/banana.png\nhttps://example.com/cherry.png",
+    );
+}
+
+#[test]
+fn rate() {
+    use crate::settings::WikitextMode;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let text = "Apple\n[[module Rate]]\nBanana";
+
+    let tokens = crate::tokenize(text);
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let output = TextRender.render(&tree, &page_info, &settings);
+
+    assert_eq!(output, "Apple\nrating: +69\nBanana");
+}
+
+#[test]
+fn date() {
+    use crate::settings::WikitextMode;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let text = "Apple [[date 1216502818]] banana \
+                [[date 2008-07-19T17:26:58 tz=\"-04:00\" format=\"%e %b %Y, %I:%M %p %z\"]]";
+
+    let tokens = crate::tokenize(text);
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let output = TextRender.render(&tree, &page_info, &settings);
+
+    assert_eq!(
+        output,
+        "Apple July 19, 2008 21:26:58 banana 19 Jul 2008, 05:26 PM -0400",
+    );
+}
+
+#[test]
+fn footnotes_and_toc() {
+    use crate::settings::{Fallback, FallbackElement, FootnoteBlockMode, WikitextMode};
+
+    let page_info = PageInfo::dummy();
+    let mut settings = WikitextSettings::from_mode(WikitextMode::Page);
+    settings
+        .text_fallbacks
+        .set(FallbackElement::TableOfContents, Fallback::Contents);
+    let text = "[[toc]]\n\n+ Apple\n\n++ Banana\n\n+ Cherry\n\n\
+                See [[[durian|]]][[footnote]]First[[/footnote]] \
+                and more[[footnote]]Second[[/footnote]].";
+
+    let tokens = crate::tokenize(text);
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let output = TextRender.render(&tree, &page_info, &settings);
+
+    assert_eq!(
+        output,
+        "Apple\n  Banana\nCherry\n\nApple\n\nBanana\n\nCherry\n\n\
+         See TODO: actual title (sandbox durian)¹ and more².\n\
+         Footnotes\n1. First\n2. Second",
+    );
+
+    // Split between footnote blocks
+    let mut split_settings = settings.clone();
+    split_settings.footnote_block_mode = FootnoteBlockMode::Split;
+    let text = "A[[footnote]]One[[/footnote]]\n\n[[footnoteblock]]\n\n\
+                B[[footnote]]Two[[/footnote]]";
+
+    let tokens = crate::tokenize(text);
+    let (tree_split, _errors) = crate::parse(&tokens, &page_info, &split_settings).into();
+    let output = TextRender.render(&tree_split, &page_info, &split_settings);
+
+    assert_eq!(output, "A¹\nFootnotes\n1. One\n\nB¹\nFootnotes\n1. Two");
+
+    // Repeated references keep the number from the block listing the footnote,
+    // even if the tree was parsed without splitting footnote blocks
+    let text = "A[[footnote name=\"x\"]]One[[/footnote]] B[[footnote]]Two[[/footnote]]\n\n\
+                [[footnoteblock]]\n\nC[[footnote]]Three[[/footnote]] D[[footnote name=\"x\"]].";
+
+    let tokens = crate::tokenize(text);
+    let (tree_repeat, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let output = TextRender.render(&tree_repeat, &page_info, &split_settings);
+
+    assert_eq!(output, "A¹ B²\nFootnotes\n1. One\n2. Two\n\nC¹ D¹.",);
+
+    // Numbering continues from the offset of earlier fragments
+    let mut offset_settings = settings.clone();
+    offset_settings.footnote_offset = 2;
+    let text = "A[[footnote]]One[[/footnote]]";
+
+    let tokens = crate::tokenize(text);
+    let (tree_offset, _errors) =
+        crate::parse(&tokens, &page_info, &offset_settings).into();
+    let output = TextRender.render(&tree_offset, &page_info, &offset_settings);
+
+    assert_eq!(output, "A³\nFootnotes\n3. One");
+
+    // Without the fallback, the footnote block is omitted
+    let mut settings = settings.clone();
+    settings
+        .text_fallbacks
+        .set(FallbackElement::FootnoteBlock, Fallback::Omit);
+    let output = TextRender.render(&tree, &page_info, &settings);
+    assert!(!output.contains("Footnotes"));
+}
+
+#[test]
+fn bidi_and_combining() {
+    use crate::settings::WikitextMode;
+
+    let mut page_info = PageInfo::dummy();
+    page_info.language = Cow::Borrowed("he");
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let render = |text: &str, settings: &WikitextSettings| {
+        let tokens = crate::tokenize(text);
+        let (tree, _errors) = crate::parse(&tokens, &page_info, settings).into();
+        TextRender.render(&tree, &page_info, settings)
+    };
+
+    // Labels against the page direction are isolated
+    let text = "שלום [[[scp-173|SCP-173]]] [[[main|עולם]]]";
+    assert_eq!(render(text, &settings), "שלום \u{2068}SCP-173\u{2069} עולם",);
+
+    let mut no_isolation = settings.clone();
+    no_isolation.text_bidi_isolation = false;
+    assert_eq!(render(text, &no_isolation), "שלום SCP-173 עולם");
+
+    // Combining characters take up no space when invisible
+    let text = "[[invisible]]e\u{0301}שָׁ[[/invisible]]|";
+    assert_eq!(render(text, &settings), "  |");
+}
+
+#[test]
+fn equation_reference() {
+    use crate::settings::WikitextMode;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let text = "See[[eref area]] and[[eref missing]].\n\n\
+                [[math]]\nx = 1\n[[/math]]\n\n\
+                [[math area]]\nA = \\pi r^2\n[[/math]]";
+
+    let tokens = crate::tokenize(text);
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let output = TextRender.render(&tree, &page_info, &settings);
+
+    assert!(
+        output.starts_with("See(2) and[missing]."),
+        "Equation reference not numbered: {output:?}",
+    );
+}
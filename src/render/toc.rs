@@ -0,0 +1,110 @@
+/*
+ * render/toc.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Depth filtering for the table of contents.
+
+use crate::tree::{Element, ListItem};
+use std::borrow::Cow;
+use std::ops::RangeInclusive;
+
+/// Restricts the table of contents lists to headings within the given depths.
+///
+/// Depths are heading levels, so `1` corresponds to `+` headings.
+/// Any sub-headings of those above the minimum depth are moved up in their place.
+pub fn filter_table_of_contents<'e, 't>(
+    elements: &'e [Element<'t>],
+    min_depth: Option<u8>,
+    max_depth: Option<u8>,
+) -> Cow<'e, [Element<'t>]> {
+    if min_depth.is_none() && max_depth.is_none() {
+        return Cow::Borrowed(elements);
+    }
+
+    let depths = min_depth.unwrap_or(1)..=max_depth.unwrap_or(u8::MAX);
+    let mut output = Vec::new();
+
+    for element in elements {
+        filter_list(element, 1, &depths, &mut output);
+    }
+
+    Cow::Owned(output)
+}
+
+fn filter_list<'t>(
+    element: &Element<'t>,
+    depth: u8,
+    depths: &RangeInclusive<u8>,
+    output: &mut Vec<Element<'t>>,
+) {
+    let (ltype, items, attributes) = match element {
+        Element::List {
+            ltype,
+            items,
+            attributes,
+        } => (*ltype, items, attributes),
+        _ => {
+            output.push(element.clone());
+            return;
+        }
+    };
+
+    // Too deep, remove this list and everything within it
+    if depth > *depths.end() {
+        return;
+    }
+
+    // Too shallow, remove these headings but keep their sub-headings
+    if depth < *depths.start() {
+        for item in items {
+            if let ListItem::SubList { element } = item {
+                filter_list(element, depth + 1, depths, output);
+            }
+        }
+
+        return;
+    }
+
+    // Within range, keep headings and filter sub-headings
+    let mut filtered_items = Vec::new();
+
+    for item in items {
+        match item {
+            ListItem::Elements { .. } => filtered_items.push(item.clone()),
+            ListItem::SubList { element } => {
+                let mut sub_lists = Vec::new();
+                filter_list(element, depth + 1, depths, &mut sub_lists);
+
+                filtered_items.extend(sub_lists.into_iter().map(|element| {
+                    ListItem::SubList {
+                        element: Box::new(element),
+                    }
+                }));
+            }
+        }
+    }
+
+    if !filtered_items.is_empty() {
+        output.push(Element::List {
+            ltype,
+            items: filtered_items,
+            attributes: attributes.clone(),
+        });
+    }
+}
@@ -0,0 +1,82 @@
+/*
+ * settings/cancellation.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A flag for stopping parsing or rendering early.
+///
+/// This allows a server to abandon work for requests which have timed out
+/// or whose clients have disconnected. Clones share the same flag, so the
+/// token can be kept while a clone is passed in through the settings.
+///
+/// Cancellation is checked periodically, so work stops shortly after,
+/// rather than immediately. The parser leaves the rest of the input
+/// as text and returns a
+/// [`Cancelled`](crate::parsing::ParseErrorKind::Cancelled) error,
+/// and [`Render::try_render()`](crate::render::Render::try_render)
+/// returns [`FtmlError::Cancelled`](crate::FtmlError::Cancelled).
+///
+/// Tokens are not serialized, and all tokens compare as equal,
+/// since they do not affect the output of a completed render.
+#[derive(Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    #[inline]
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    /// Requests that any work using this token stop.
+    pub fn cancel(&self) {
+        info!("Cancelling work using this token");
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("CancellationToken")
+            .field(&self.is_cancelled())
+            .finish()
+    }
+}
+
+impl PartialEq for CancellationToken {
+    #[inline]
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for CancellationToken {}
+
+impl Hash for CancellationToken {
+    #[inline]
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
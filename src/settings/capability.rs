@@ -0,0 +1,138 @@
+/*
+ * settings/capability.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Describes which elements are permitted by the [`WikitextSettings`].
+//!
+//! For instance, forum posts cannot contain iframes, and direct messages
+//! cannot contain modules. This table is checked by the parser, which
+//! rejects these blocks with a
+//! [`NotSupportedMode`](crate::parsing::ParseErrorKind::NotSupportedMode)
+//! warning. Syntax trees can also be built or loaded directly, so renderers
+//! check it again before rendering each element, and emit a placeholder
+//! instead of any which are not permitted.
+
+use super::WikitextSettings;
+use crate::tree::Element;
+
+/// Elements which are only permitted by some settings.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum GatedElement {
+    Module,
+    Include,
+    TableOfContents,
+    Style,
+    Html,
+    Iframe,
+    Embed,
+}
+
+impl GatedElement {
+    /// Determines which kind of gated element this is, if any.
+    pub fn from_element(element: &Element) -> Option<Self> {
+        let kind = match element {
+            Element::Module(_) => GatedElement::Module,
+            Element::Include { .. } => GatedElement::Include,
+            Element::TableOfContents { .. } => GatedElement::TableOfContents,
+            Element::Style(_) => GatedElement::Style,
+            Element::Html { .. } => GatedElement::Html,
+            Element::Iframe { .. } => GatedElement::Iframe,
+            Element::Embed(_) => GatedElement::Embed,
+            _ => return None,
+        };
+
+        Some(kind)
+    }
+
+    /// Whether this element is permitted by the given settings.
+    pub fn is_allowed(self, settings: &WikitextSettings) -> bool {
+        match self {
+            GatedElement::Module
+            | GatedElement::Include
+            | GatedElement::TableOfContents => settings.enable_page_syntax,
+            GatedElement::Style | GatedElement::Html | GatedElement::Iframe => {
+                settings.enable_raw_content
+            }
+            GatedElement::Embed => settings.enable_embeds,
+        }
+    }
+
+    #[inline]
+    pub fn name(self) -> &'static str {
+        match self {
+            GatedElement::Module => "module",
+            GatedElement::Include => "include",
+            GatedElement::TableOfContents => "table-of-contents",
+            GatedElement::Style => "style",
+            GatedElement::Html => "html",
+            GatedElement::Iframe => "iframe",
+            GatedElement::Embed => "embed",
+        }
+    }
+}
+
+impl WikitextSettings {
+    /// Whether the given element is permitted by these settings.
+    ///
+    /// Elements which are not listed in [`GatedElement`] are always permitted.
+    pub fn permits(&self, element: &Element) -> bool {
+        match GatedElement::from_element(element) {
+            Some(kind) => kind.is_allowed(self),
+            None => true,
+        }
+    }
+}
+
+#[test]
+fn capabilities() {
+    use super::WikitextMode;
+    use crate::tree::{Embed, Module};
+
+    let iframe = Element::Iframe {
+        url: cow!("https://example.com/"),
+        attributes: Default::default(),
+    };
+    let module = Element::Module(Module::Rate);
+    let embed = Element::Embed(Embed::GithubGist {
+        username: cow!("user"),
+        hash: cow!("0123456789abcdef"),
+    });
+
+    let page = WikitextSettings::from_mode(WikitextMode::Page);
+    let list = WikitextSettings::from_mode(WikitextMode::List);
+    let forum_post = WikitextSettings::from_mode(WikitextMode::ForumPost);
+    let direct_message = WikitextSettings::from_mode(WikitextMode::DirectMessage);
+
+    assert!(page.permits(&iframe));
+    assert!(!forum_post.permits(&iframe));
+    assert!(list.permits(&module));
+    assert!(!direct_message.permits(&module));
+    assert!(forum_post.permits(&embed));
+    assert!(!direct_message.permits(&embed));
+    assert!(direct_message.permits(&Element::LineBreak));
+
+    // Derived from the settings, not the mode
+    let forum_post = WikitextSettings {
+        enable_page_syntax: true,
+        ..forum_post
+    };
+    assert!(forum_post.permits(&module));
+    assert!(!forum_post.permits(&iframe));
+}
@@ -0,0 +1,100 @@
+/*
+ * settings/color.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use once_cell::sync::Lazy;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// A [`ColorVariables`] instance with the default mapping.
+///
+/// This maps each of the basic CSS color keywords, such as `red`, along
+/// with a few other common ones, to a custom property of the same name,
+/// such as `--wj-color-red`.
+pub static DEFAULT_COLOR_VARIABLES: Lazy<ColorVariables> = Lazy::new(|| {
+    const COLORS: [&str; 22] = [
+        "aqua", "black", "blue", "brown", "fuchsia", "gold", "gray", "green", "grey",
+        "lime", "maroon", "navy", "olive", "orange", "pink", "purple", "red", "silver",
+        "teal", "violet", "white", "yellow",
+    ];
+
+    let colors = COLORS
+        .iter()
+        .map(|&color| (cow!(color), Cow::Owned(format!("--wj-color-{color}"))))
+        .collect();
+
+    ColorVariables { colors }
+});
+
+/// Settings to render colors as CSS custom properties, rather than literal values.
+///
+/// This allows themes, such as a dark mode, to restyle colored text by
+/// defining these properties. The original color is kept as the fallback,
+/// so output looks the same if they are not defined.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct ColorVariables {
+    /// A map from each color to the name of its custom property, including the leading `--`.
+    ///
+    /// Colors are matched case-insensitively, and may either be keywords
+    /// such as `red`, or hex codes such as `#f00`. Keys should be lowercase.
+    #[serde(flatten)]
+    pub colors: HashMap<Cow<'static, str>, Cow<'static, str>>,
+}
+
+impl ColorVariables {
+    /// Creates a new instance with no colors mapped.
+    #[inline]
+    pub fn new() -> Self {
+        ColorVariables::default()
+    }
+
+    /// Produces the CSS value to use for the given color.
+    ///
+    /// # Example
+    /// ```
+    /// # use ftml::settings::*;
+    /// assert_eq!(DEFAULT_COLOR_VARIABLES.build("Red").unwrap(), "var(--wj-color-red, Red)");
+    /// ```
+    ///
+    /// Returns `None` if this color has no custom property.
+    pub fn build(&self, color: &str) -> Option<String> {
+        let key = color.trim().to_ascii_lowercase();
+        let variable = self.colors.get(key.as_str())?;
+        Some(format!("var({variable}, {color})"))
+    }
+}
+
+#[test]
+fn color_variables() {
+    let mut variables = DEFAULT_COLOR_VARIABLES.clone();
+    variables
+        .colors
+        .insert(cow!("#ff0000"), cow!("--theme-danger"));
+
+    assert_eq!(
+        variables.build("blue").as_deref(),
+        Some("var(--wj-color-blue, blue)"),
+    );
+    assert_eq!(
+        variables.build("#FF0000").as_deref(),
+        Some("var(--theme-danger, #FF0000)"),
+    );
+    assert_eq!(variables.build("#00ff00"), None);
+    assert_eq!(variables.build("rgb(1, 2, 3)"), None);
+}
@@ -0,0 +1,145 @@
+/*
+ * settings/container.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::borrow::Cow;
+
+/// Restrictions on the user-provided `class` and `id` attributes of blocks.
+///
+/// This applies to every block which accepts attributes, such as `[[span]]`,
+/// `[[div]]`, or `[[blockquote]]`. Sites may wish to use this to prevent
+/// users from impersonating site chrome, such as `page-rate-widget-box`.
+///
+/// Values are matched case-sensitively, and entries ending in `*` match any
+/// value with that prefix. Rejected values are removed while parsing,
+/// and each produces a warning.
+///
+/// The default policy has no restrictions.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct ContainerAttributePolicy {
+    /// If set, the only classes which may be used.
+    pub allowed_classes: Option<Vec<String>>,
+
+    /// Classes which may not be used.
+    pub denied_classes: Vec<String>,
+
+    /// If set, the only IDs which may be used.
+    pub allowed_ids: Option<Vec<String>>,
+
+    /// IDs which may not be used.
+    pub denied_ids: Vec<String>,
+
+    /// If set, a prefix added to each user class which is kept.
+    ///
+    /// For instance, with a prefix of `u-`, then `class="box"` becomes `class="u-box"`.
+    pub class_prefix: Option<String>,
+}
+
+impl ContainerAttributePolicy {
+    /// Whether this policy has no restrictions.
+    pub fn is_empty(&self) -> bool {
+        self.allowed_classes.is_none()
+            && self.denied_classes.is_empty()
+            && self.allowed_ids.is_none()
+            && self.denied_ids.is_empty()
+            && self.class_prefix.is_none()
+    }
+
+    /// Whether this class may be used.
+    pub fn is_class_allowed(&self, class: &str) -> bool {
+        is_allowed(&self.allowed_classes, &self.denied_classes, class)
+    }
+
+    /// Whether this ID may be used.
+    pub fn is_id_allowed(&self, id: &str) -> bool {
+        is_allowed(&self.allowed_ids, &self.denied_ids, id)
+    }
+
+    /// Filters and prefixes a space-separated list of classes.
+    ///
+    /// Returns the resultant classes and the number of classes removed.
+    pub fn restrict_classes<'a>(&self, classes: &'a str) -> (Cow<'a, str>, usize) {
+        let prefix = self.class_prefix.as_deref().unwrap_or("");
+        let mut kept = Vec::new();
+        let mut removed = 0;
+
+        for class in classes.split_whitespace() {
+            if self.is_class_allowed(class) {
+                kept.push(format!("{prefix}{class}"));
+            } else {
+                removed += 1;
+            }
+        }
+
+        if removed == 0 && prefix.is_empty() {
+            return (Cow::Borrowed(classes), 0);
+        }
+
+        (Cow::Owned(kept.join(" ")), removed)
+    }
+}
+
+fn is_allowed(allowed: &Option<Vec<String>>, denied: &[String], value: &str) -> bool {
+    let matches = |pattern: &String| match pattern.strip_suffix('*') {
+        Some(prefix) => value.starts_with(prefix),
+        None => value == pattern,
+    };
+
+    let permitted = match allowed {
+        Some(allowed) => allowed.iter().any(matches),
+        None => true,
+    };
+
+    permitted && !denied.iter().any(matches)
+}
+
+#[test]
+fn container_attribute_policy() {
+    let policy = ContainerAttributePolicy::default();
+    assert!(policy.is_empty());
+    assert_eq!(policy.restrict_classes("a  b"), (cow!("a  b"), 0));
+
+    let policy = ContainerAttributePolicy {
+        allowed_classes: None,
+        denied_classes: vec![str!("page-rate-widget-box"), str!("wj-*")],
+        allowed_ids: Some(vec![str!("u-*")]),
+        denied_ids: vec![str!("u-admin")],
+        class_prefix: None,
+    };
+    assert!(policy.is_class_allowed("box"));
+    assert!(!policy.is_class_allowed("wj-body"));
+    assert!(policy.is_id_allowed("u-1"));
+    assert!(!policy.is_id_allowed("u-admin"));
+    assert!(!policy.is_id_allowed("main"));
+    assert_eq!(
+        policy.restrict_classes("box page-rate-widget-box wj-toc blue"),
+        (cow!("box blue"), 2),
+    );
+
+    let policy = ContainerAttributePolicy {
+        allowed_classes: Some(vec![str!("box"), str!("blue")]),
+        class_prefix: Some(str!("u-")),
+        ..Default::default()
+    };
+    assert_eq!(
+        policy.restrict_classes("box red blue"),
+        (cow!("u-box u-blue"), 1)
+    );
+}
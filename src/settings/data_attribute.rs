@@ -0,0 +1,77 @@
+/*
+ * settings/data_attribute.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+/// Which user-provided `data-*` attributes are emitted in HTML output.
+///
+/// This only applies to attributes given in blocks, such as
+/// `[[div data-widget="rating"]]`. Attributes the renderer adds
+/// itself are always emitted.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", tag = "type", content = "prefixes")]
+pub enum DataAttributePolicy {
+    /// All `data-*` attributes are emitted.
+    #[default]
+    Preserve,
+
+    /// Only attributes starting with one of these prefixes are emitted.
+    ///
+    /// Prefixes include the `data-` part, for instance `data-widget-`.
+    Allowlist(Vec<String>),
+
+    /// No `data-*` attributes are emitted.
+    Drop,
+}
+
+impl DataAttributePolicy {
+    /// Whether the given user-provided attribute may be emitted.
+    ///
+    /// Attributes which aren't `data-*` are always permitted.
+    pub fn is_allowed(&self, key: &str) -> bool {
+        if !starts_with_ignore_case(key, "data-") {
+            return true;
+        }
+
+        match self {
+            DataAttributePolicy::Preserve => true,
+            DataAttributePolicy::Drop => false,
+            DataAttributePolicy::Allowlist(prefixes) => prefixes
+                .iter()
+                .any(|prefix| starts_with_ignore_case(key, prefix)),
+        }
+    }
+}
+
+fn starts_with_ignore_case(value: &str, prefix: &str) -> bool {
+    value.len() >= prefix.len()
+        && value.as_bytes()[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
+}
+
+#[test]
+fn data_attributes() {
+    let policy = DataAttributePolicy::Allowlist(vec![str!("data-widget-")]);
+    assert!(policy.is_allowed("data-widget-id"));
+    assert!(policy.is_allowed("DATA-Widget-id"));
+    assert!(!policy.is_allowed("data-tracking"));
+    assert!(policy.is_allowed("class"));
+
+    assert!(DataAttributePolicy::Preserve.is_allowed("data-tracking"));
+    assert!(!DataAttributePolicy::Drop.is_allowed("data-widget-id"));
+    assert!(DataAttributePolicy::Drop.is_allowed("aria-label"));
+}
@@ -0,0 +1,159 @@
+/*
+ * settings/embed.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Additional providers which may be used in `[[embed]]` blocks.
+///
+/// Built-in embeds, such as YouTube, take priority over any provider
+/// registered here with the same name.
+///
+/// By default no additional providers are registered.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct EmbedProviders {
+    /// A map of each provider's name to its configuration.
+    ///
+    /// Names are matched case-insensitively.
+    pub providers: BTreeMap<String, EmbedProvider>,
+}
+
+impl EmbedProviders {
+    /// Registers a provider, replacing any with the same name.
+    pub fn insert<S: Into<String>>(&mut self, name: S, provider: EmbedProvider) {
+        self.providers.insert(name.into(), provider);
+    }
+
+    /// Looks up the provider with the given name.
+    pub fn get(&self, name: &str) -> Option<&EmbedProvider> {
+        self.providers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, provider)| provider)
+    }
+}
+
+/// An external site which content can be embedded from.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct EmbedProvider {
+    /// The URL to embed.
+    ///
+    /// All instances of `$$` are replaced with the ID passed to the embed.
+    pub url: String,
+
+    /// How the embedded content is emitted in HTML output.
+    #[serde(default)]
+    pub output: EmbedOutput,
+
+    /// Which additional arguments may be passed to the embed.
+    ///
+    /// These are appended to the URL as query parameters, and any other
+    /// arguments are ignored.
+    #[serde(default)]
+    pub params: Vec<String>,
+}
+
+impl EmbedProvider {
+    /// Whether the given argument may be passed to this provider.
+    pub fn allows_param(&self, name: &str) -> bool {
+        self.params
+            .iter()
+            .any(|param| param.eq_ignore_ascii_case(name))
+    }
+
+    /// Builds the URL for the embed with the given ID and parameters.
+    ///
+    /// The ID and parameters are percent-encoded.
+    pub fn build_url(&self, id: &str, params: &BTreeMap<Cow<str>, Cow<str>>) -> String {
+        let mut url = self.url.replace("$$", &percent_encode(id));
+
+        for (index, (key, value)) in params.iter().enumerate() {
+            let separator = if index == 0 && !url.contains('?') {
+                '?'
+            } else {
+                '&'
+            };
+
+            url.push(separator);
+            url.push_str(&percent_encode(key));
+            url.push('=');
+            url.push_str(&percent_encode(value));
+        }
+
+        url
+    }
+}
+
+/// How content from an [`EmbedProvider`] is emitted in HTML output.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum EmbedOutput {
+    /// Loads the URL in an `<iframe>`.
+    #[default]
+    Iframe,
+
+    /// Links to the URL, for sites which do not permit framing.
+    Link,
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                output.push(char::from(byte));
+            }
+            _ => write!(output, "%{byte:02X}").unwrap(),
+        }
+    }
+
+    output
+}
+
+#[test]
+fn build_url() {
+    let provider = EmbedProvider {
+        url: str!("https://example.com/embed/$$"),
+        output: EmbedOutput::Iframe,
+        params: vec![str!("start")],
+    };
+
+    let mut params = BTreeMap::new();
+    assert_eq!(
+        provider.build_url("a b/c", &params),
+        "https://example.com/embed/a%20b%2Fc",
+    );
+
+    params.insert(cow!("start"), cow!("1&2"));
+    assert_eq!(
+        provider.build_url("abc", &params),
+        "https://example.com/embed/abc?start=1%262",
+    );
+
+    let mut providers = EmbedProviders::default();
+    providers.insert("Example", provider);
+    assert!(providers.get("example").is_some());
+    assert!(providers.get("other").is_none());
+    assert!(providers.get("example").unwrap().allows_param("START"));
+}
@@ -0,0 +1,227 @@
+/*
+ * settings/fallback.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Describes how elements degrade in renderers which cannot display them.
+//!
+//! For instance, the text renderer cannot show an iframe, but it can
+//! write out the URL the iframe points to. Rather than deciding this
+//! ad-hoc for each element, the choice is made here and can be adjusted
+//! per site with [`WikitextSettings::text_fallbacks`] and
+//! [`WikitextSettings::markdown_fallbacks`].
+//!
+//! The HTML renderer can display all of these elements, so it does not
+//! consult a table. The Markdown renderer only does so for elements
+//! without a native Markdown form, see [`FallbackTable::markdown`].
+//!
+//! [`WikitextSettings::text_fallbacks`]: crate::settings::WikitextSettings::text_fallbacks
+//! [`WikitextSettings::markdown_fallbacks`]: crate::settings::WikitextSettings::markdown_fallbacks
+
+use crate::tree::Element;
+use std::collections::HashMap;
+
+/// Elements which the text or Markdown renderers may not be able to display.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum FallbackElement {
+    Module,
+    Image,
+    Media,
+    Iframe,
+    Embed,
+    Html,
+    Math,
+    MathInline,
+    TableOfContents,
+    FootnoteBlock,
+    Collapsible,
+}
+
+impl FallbackElement {
+    /// Determines which kind of degradable element this is, if any.
+    pub fn from_element(element: &Element) -> Option<Self> {
+        let kind = match element {
+            Element::Module(_) => FallbackElement::Module,
+            Element::Image { .. } => FallbackElement::Image,
+            Element::Media { .. } => FallbackElement::Media,
+            Element::Iframe { .. } => FallbackElement::Iframe,
+            Element::Embed(_) => FallbackElement::Embed,
+            Element::Html { .. } => FallbackElement::Html,
+            Element::Math { .. } => FallbackElement::Math,
+            Element::MathInline { .. } => FallbackElement::MathInline,
+            Element::TableOfContents { .. } => FallbackElement::TableOfContents,
+            Element::FootnoteBlock { .. } => FallbackElement::FootnoteBlock,
+            Element::Collapsible { .. } => FallbackElement::Collapsible,
+            _ => return None,
+        };
+
+        Some(kind)
+    }
+
+    /// Which fallbacks are possible for this element.
+    ///
+    /// [`Fallback::Omit`] is always supported.
+    pub fn supported(self) -> &'static [Fallback] {
+        use self::Fallback::*;
+
+        match self {
+            FallbackElement::Module => &[Omit],
+            FallbackElement::Image
+            | FallbackElement::Media
+            | FallbackElement::Iframe
+            | FallbackElement::Embed => &[Omit, Link],
+            FallbackElement::Html => &[Omit, Placeholder, Source],
+            FallbackElement::Math | FallbackElement::MathInline => &[Omit, Source],
+            FallbackElement::TableOfContents
+            | FallbackElement::FootnoteBlock
+            | FallbackElement::Collapsible => &[Omit, Contents],
+        }
+    }
+}
+
+/// How an element degrades when it cannot be rendered directly.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Fallback {
+    /// Nothing is rendered.
+    Omit,
+
+    /// The inner contents are rendered without the surrounding element.
+    Contents,
+
+    /// The URL of the external resource is rendered.
+    Link,
+
+    /// The source of the element (e.g. LaTeX or HTML) is rendered as-is.
+    Source,
+
+    /// A short label in place of the element, such as `[HTML content]`.
+    Placeholder,
+}
+
+/// A table of how each element should degrade in a renderer.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FallbackTable {
+    #[serde(flatten)]
+    inner: HashMap<FallbackElement, Fallback>,
+}
+
+impl FallbackTable {
+    /// The default fallbacks for the text renderer.
+    pub fn text() -> Self {
+        FallbackTable {
+            inner: hashmap! {
+                FallbackElement::Module => Fallback::Omit,
+                FallbackElement::Image => Fallback::Link,
+                FallbackElement::Media => Fallback::Link,
+                FallbackElement::Iframe => Fallback::Omit,
+                FallbackElement::Embed => Fallback::Omit,
+                FallbackElement::Html => Fallback::Omit,
+                FallbackElement::Math => Fallback::Omit,
+                FallbackElement::MathInline => Fallback::Omit,
+                FallbackElement::TableOfContents => Fallback::Omit,
+                FallbackElement::FootnoteBlock => Fallback::Contents,
+                FallbackElement::Collapsible => Fallback::Contents,
+            },
+        }
+    }
+
+    /// The default fallbacks for the Markdown renderer.
+    ///
+    /// Only modules, media, iframes, embeds, and HTML blocks are looked up,
+    /// since the other elements are rendered as their Markdown equivalents.
+    /// Setting [`Fallback::Source`] for HTML passes it through as raw HTML.
+    pub fn markdown() -> Self {
+        FallbackTable {
+            inner: hashmap! {
+                FallbackElement::Module => Fallback::Omit,
+                FallbackElement::Media => Fallback::Link,
+                FallbackElement::Iframe => Fallback::Link,
+                FallbackElement::Embed => Fallback::Link,
+                FallbackElement::Html => Fallback::Placeholder,
+            },
+        }
+    }
+
+    /// Gets the fallback for the given element.
+    ///
+    /// If the element is missing from the table, or it is set to
+    /// a fallback the element does not support, then it is omitted.
+    pub fn get(&self, element: FallbackElement) -> Fallback {
+        match self.inner.get(&element) {
+            Some(&fallback) if element.supported().contains(&fallback) => fallback,
+            Some(&fallback) => {
+                warn!(
+                    "Fallback {fallback:?} not supported for element {element:?}, omitting",
+                );
+
+                Fallback::Omit
+            }
+            None => Fallback::Omit,
+        }
+    }
+
+    /// Sets the fallback for the given element.
+    ///
+    /// Returns `false` if the element does not support this kind of fallback,
+    /// in which case the table is not modified.
+    pub fn set(&mut self, element: FallbackElement, fallback: Fallback) -> bool {
+        let supported = element.supported().contains(&fallback);
+        if supported {
+            self.inner.insert(element, fallback);
+        }
+
+        supported
+    }
+}
+
+#[test]
+fn fallback_table() {
+    let mut table = FallbackTable::text();
+
+    assert_eq!(table.get(FallbackElement::Image), Fallback::Link);
+    assert_eq!(table.get(FallbackElement::Iframe), Fallback::Omit);
+    assert_eq!(table.get(FallbackElement::Math), Fallback::Omit);
+
+    assert!(table.set(FallbackElement::Iframe, Fallback::Link));
+    assert_eq!(table.get(FallbackElement::Iframe), Fallback::Link);
+
+    assert!(table.set(FallbackElement::Math, Fallback::Source));
+    assert_eq!(table.get(FallbackElement::Math), Fallback::Source);
+
+    assert!(!table.set(FallbackElement::Module, Fallback::Link));
+    assert_eq!(table.get(FallbackElement::Module), Fallback::Omit);
+
+    assert!(!table.set(FallbackElement::Html, Fallback::Link));
+    assert!(table.set(FallbackElement::Html, Fallback::Placeholder));
+    assert_eq!(table.get(FallbackElement::Html), Fallback::Placeholder);
+
+    // Markdown only degrades elements without a native form
+    let table = FallbackTable::markdown();
+    assert_eq!(table.get(FallbackElement::Iframe), Fallback::Link);
+    assert_eq!(table.get(FallbackElement::Html), Fallback::Placeholder);
+
+    // Unsupported values from deserialization are ignored
+    let table: FallbackTable =
+        serde_json::from_str(r#"{"image": "source", "html": "source"}"#).unwrap();
+
+    assert_eq!(table.get(FallbackElement::Image), Fallback::Omit);
+    assert_eq!(table.get(FallbackElement::Html), Fallback::Source);
+    assert_eq!(table.get(FallbackElement::Embed), Fallback::Omit);
+}
@@ -0,0 +1,274 @@
+/*
+ * settings/footnote.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::num::NonZeroUsize;
+
+/// The placeholder in [`FootnoteStyle::Template`] replaced with the footnote number.
+pub const FOOTNOTE_INDEX_PLACEHOLDER: &str = "{index}";
+
+/// How footnote references are labeled when rendered.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", tag = "type", content = "template")]
+pub enum FootnoteStyle {
+    /// A superscript number, such as <sup>1</sup>.
+    ///
+    /// In HTML the superscripting is left to styling, in text
+    /// the Unicode superscript digits are used instead.
+    #[default]
+    Superscript,
+
+    /// The number surrounded in square brackets, such as `[1]`.
+    Brackets,
+
+    /// A custom template, such as `(note {index})`.
+    ///
+    /// All instances of `{index}` are replaced with the footnote number.
+    Template(String),
+}
+
+impl FootnoteStyle {
+    /// Produces the label for the footnote reference with the given number.
+    pub fn label(&self, index: NonZeroUsize, numbering: &FootnoteNumbering) -> String {
+        let number = numbering.format(index);
+
+        match self {
+            FootnoteStyle::Superscript => number,
+            FootnoteStyle::Brackets => format!("[{number}]"),
+            FootnoteStyle::Template(template) => {
+                template.replace(FOOTNOTE_INDEX_PLACEHOLDER, &number)
+            }
+        }
+    }
+
+    /// Produces the label for this footnote reference in plain text.
+    ///
+    /// This is the same as [`FootnoteStyle::label`], except superscripts
+    /// are represented with Unicode superscript digits.
+    pub fn text_label(
+        &self,
+        index: NonZeroUsize,
+        numbering: &FootnoteNumbering,
+    ) -> String {
+        match self {
+            FootnoteStyle::Superscript => numbering
+                .format(index)
+                .chars()
+                .map(|ch| match ch {
+                    '0' => '⁰',
+                    '1' => '¹',
+                    '2' => '²',
+                    '3' => '³',
+                    '4' => '⁴',
+                    '5' => '⁵',
+                    '6' => '⁶',
+                    '7' => '⁷',
+                    '8' => '⁸',
+                    '9' => '⁹',
+                    _ => ch,
+                })
+                .collect(),
+            _ => self.label(index, numbering),
+        }
+    }
+}
+
+/// How footnotes are numbered, both in references and the footnote block.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", tag = "type", content = "symbols")]
+pub enum FootnoteNumbering {
+    /// Arabic numerals, such as `1`, `2`, `3`.
+    #[default]
+    Numeric,
+
+    /// Lowercase Roman numerals, such as `i`, `ii`, `iii`.
+    LowerRoman,
+
+    /// Uppercase Roman numerals, such as `I`, `II`, `III`.
+    UpperRoman,
+
+    /// A custom sequence of symbols, such as `*`, `†`, `‡`.
+    ///
+    /// Once the symbols are exhausted, they are repeated
+    /// to continue the sequence, such as `**`, `††`, `‡‡`.
+    /// Symbols are repeated at most [`FOOTNOTE_SYMBOL_MAX_REPEAT`] times,
+    /// after which numerals are used instead.
+    /// If no symbols are given, then numerals are used.
+    Symbols(Vec<String>),
+}
+
+/// How many times a footnote symbol may be repeated before falling back to numerals.
+///
+/// Without a limit, the labels grow with the number of footnotes,
+/// making the total output quadratic in size.
+pub const FOOTNOTE_SYMBOL_MAX_REPEAT: usize = 3;
+
+impl FootnoteNumbering {
+    /// Produces the number for the footnote with the given index.
+    pub fn format(&self, index: NonZeroUsize) -> String {
+        match self {
+            FootnoteNumbering::Numeric => str!(index),
+            FootnoteNumbering::LowerRoman => {
+                roman_numeral(index.get()).to_ascii_lowercase()
+            }
+            FootnoteNumbering::UpperRoman => roman_numeral(index.get()),
+            FootnoteNumbering::Symbols(symbols) if symbols.is_empty() => str!(index),
+            FootnoteNumbering::Symbols(symbols) => {
+                let offset = index.get() - 1;
+                let count = offset / symbols.len() + 1;
+                if count > FOOTNOTE_SYMBOL_MAX_REPEAT {
+                    return str!(index);
+                }
+
+                let symbol = &symbols[offset % symbols.len()];
+                symbol.repeat(count)
+            }
+        }
+    }
+}
+
+fn roman_numeral(mut value: usize) -> String {
+    const NUMERALS: [(usize, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+
+    let mut output = String::new();
+    for (amount, numeral) in NUMERALS {
+        while value >= amount {
+            output.push_str(numeral);
+            value -= amount;
+        }
+    }
+
+    output
+}
+
+/// Where the footnote block is placed in the page.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum FootnotePlacement {
+    /// Wherever a `[[footnoteblock]]` is, or at the end of the page if there is none.
+    #[default]
+    Block,
+
+    /// Always at the end of the page.
+    ///
+    /// Any `[[footnoteblock]]` in the page is ignored.
+    End,
+}
+
+/// How footnotes are listed when a page has multiple footnote blocks.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum FootnoteBlockMode {
+    /// Every footnote block lists all of the footnotes on the page.
+    #[default]
+    Repeat,
+
+    /// Each footnote block lists only the footnotes referenced since the previous one.
+    ///
+    /// Numbering restarts after each block, and any footnotes after the last
+    /// block are listed at the end of the page.
+    Split,
+}
+
+#[test]
+fn footnote_labels() {
+    macro_rules! check {
+        ($style:expr, $index:expr, $label:expr, $text_label:expr $(,)?) => {{
+            let style = $style;
+            let index = NonZeroUsize::new($index).unwrap();
+            let numbering = FootnoteNumbering::Numeric;
+
+            assert_eq!(
+                style.label(index, &numbering),
+                $label,
+                "HTML label doesn't match",
+            );
+            assert_eq!(
+                style.text_label(index, &numbering),
+                $text_label,
+                "Text label doesn't match",
+            );
+        }};
+    }
+
+    check!(FootnoteStyle::Superscript, 1, "1", "¹");
+    check!(FootnoteStyle::Superscript, 209, "209", "²⁰⁹");
+    check!(FootnoteStyle::Brackets, 4, "[4]", "[4]");
+    check!(
+        FootnoteStyle::Template(str!("(note {index})")),
+        12,
+        "(note 12)",
+        "(note 12)",
+    );
+    check!(FootnoteStyle::Template(str!("*")), 3, "*", "*");
+}
+
+#[test]
+fn footnote_numbering() {
+    macro_rules! check {
+        ($numbering:expr, $index:expr, $expected:expr $(,)?) => {{
+            let index = NonZeroUsize::new($index).unwrap();
+
+            assert_eq!(
+                $numbering.format(index),
+                $expected,
+                "Footnote number doesn't match",
+            );
+        }};
+    }
+
+    check!(FootnoteNumbering::Numeric, 1, "1");
+    check!(FootnoteNumbering::Numeric, 42, "42");
+    check!(FootnoteNumbering::LowerRoman, 4, "iv");
+    check!(FootnoteNumbering::LowerRoman, 14, "xiv");
+    check!(FootnoteNumbering::UpperRoman, 1994, "MCMXCIV");
+
+    let symbols = FootnoteNumbering::Symbols(vec![str!("*"), str!("†"), str!("‡")]);
+    check!(symbols, 1, "*");
+    check!(symbols, 3, "‡");
+    check!(symbols, 4, "**");
+    check!(symbols, 8, "†††");
+    check!(symbols, 9, "‡‡‡");
+    check!(symbols, 10, "10");
+    check!(symbols, 100_000, "100000");
+    check!(FootnoteNumbering::Symbols(vec![]), 5, "5");
+
+    // Numbering is applied to labels
+    let index = NonZeroUsize::new(3).unwrap();
+    let numbering = FootnoteNumbering::UpperRoman;
+    assert_eq!(FootnoteStyle::Brackets.label(index, &numbering), "[III]");
+    assert_eq!(
+        FootnoteStyle::Superscript.text_label(index, &numbering),
+        "III"
+    );
+}
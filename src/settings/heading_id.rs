@@ -0,0 +1,137 @@
+/*
+ * settings/heading_id.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashSet;
+
+/// How IDs are generated for headings which have table of contents entries.
+///
+/// These IDs are used both on the heading elements themselves
+/// and as the link targets in the table of contents.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", tag = "type", content = "prefix")]
+pub enum HeadingIdStrategy {
+    /// Sequential IDs based on the heading's position, such as `toc0`.
+    #[default]
+    Numeric,
+
+    /// IDs derived from the heading text, such as `early-history`.
+    ///
+    /// Unlike numeric IDs, these remain stable if other headings are
+    /// added or reordered. Repeated titles are suffixed with `-2`, `-3`, etc.
+    SlugifiedTitle,
+
+    /// Like `SlugifiedTitle`, but with the given string prepended.
+    ///
+    /// This avoids collisions with other IDs on the page.
+    Prefixed(String),
+}
+
+impl HeadingIdStrategy {
+    /// Produces the ID for the heading with the given index and title.
+    ///
+    /// IDs which have already been produced are tracked in `used`,
+    /// so that each generated ID is unique.
+    pub fn generate(
+        &self,
+        index: usize,
+        title: &str,
+        used: &mut HashSet<String>,
+    ) -> String {
+        let slug = slugify(title);
+        let base = match self {
+            HeadingIdStrategy::SlugifiedTitle if !slug.is_empty() => slug,
+            HeadingIdStrategy::Prefixed(prefix) if !slug.is_empty() => {
+                format!("{prefix}{slug}")
+            }
+            HeadingIdStrategy::Prefixed(prefix) => format!("{prefix}{index}"),
+            _ => format!("toc{index}"),
+        };
+
+        let mut id = base.clone();
+        let mut count = 1;
+        while used.contains(&id) {
+            count += 1;
+            id = format!("{base}-{count}");
+        }
+
+        used.insert(id.clone());
+        id
+    }
+}
+
+/// Converts text into a lowercase, hyphen-separated form suitable for IDs.
+fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+        if word.is_empty() {
+            continue;
+        }
+
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+
+        slug.extend(word.chars().flat_map(char::to_lowercase));
+    }
+
+    slug
+}
+
+#[test]
+fn heading_ids() {
+    let mut used = HashSet::new();
+
+    macro_rules! check {
+        ($strategy:expr, $index:expr, $title:expr, $expected:expr $(,)?) => {
+            assert_eq!(
+                $strategy.generate($index, $title, &mut used),
+                $expected,
+                "Generated heading ID didn't match expected",
+            );
+        };
+    }
+
+    check!(HeadingIdStrategy::Numeric, 0, "Apple", "toc0");
+    check!(HeadingIdStrategy::Numeric, 1, "Apple", "toc1");
+    check!(
+        HeadingIdStrategy::SlugifiedTitle,
+        2,
+        "Early History",
+        "early-history"
+    );
+    check!(
+        HeadingIdStrategy::SlugifiedTitle,
+        3,
+        "Early  history!",
+        "early-history-2"
+    );
+    check!(
+        HeadingIdStrategy::SlugifiedTitle,
+        4,
+        "Ärger über Äpfel",
+        "ärger-über-äpfel"
+    );
+    check!(HeadingIdStrategy::SlugifiedTitle, 5, "???", "toc5");
+
+    let prefixed = HeadingIdStrategy::Prefixed(str!("h-"));
+    check!(prefixed, 6, "Early History", "h-early-history");
+    check!(prefixed, 7, "", "h-7");
+}
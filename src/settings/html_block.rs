@@ -0,0 +1,45 @@
+/*
+ * settings/html_block.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+/// How the contents of `[[html]]` blocks are emitted in HTML output.
+///
+/// In every case the HTML is placed in an `<iframe>`, so that it cannot
+/// interfere with the rest of the page.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum HtmlBlockOutput {
+    /// Submits the HTML to be hosted separately, then links to it.
+    ///
+    /// This goes through `Handle::post_html()`.
+    #[default]
+    Hosted,
+
+    /// Places the HTML inline in the iframe's `srcdoc` attribute.
+    ///
+    /// The iframe is always sandboxed, since otherwise the document would
+    /// share an origin with the page. See
+    /// [`SanitizationPolicy::html_sandbox`](crate::settings::SanitizationPolicy::html_sandbox).
+    Srcdoc,
+
+    /// Encodes the HTML as a `data:` URI in the iframe's `src` attribute.
+    ///
+    /// This is sandboxed in the same way as `Srcdoc`.
+    DataUri,
+}
@@ -0,0 +1,107 @@
+/*
+ * settings/image.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+/// Which loading-related attributes are added to rendered images.
+///
+/// By default none are added, so output matches what Wikidot produces.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct ImageSettings {
+    /// Whether to add `loading="lazy"`, so images are only fetched when near the viewport.
+    pub lazy_loading: bool,
+
+    /// Whether to add `decoding="async"`, so decoding images doesn't delay other content.
+    pub async_decoding: bool,
+
+    /// How to generate `srcset` and `sizes` attributes, if at all.
+    pub srcset: Option<ImageSrcset>,
+}
+
+/// Configuration for generating responsive image candidates.
+///
+/// Each candidate is produced by substituting into the URL template:
+/// * `$url` is replaced with the original image URL.
+/// * `$width` is replaced with the candidate width, in pixels.
+///
+/// For instance, `https://img.example.com/resize?w=$width&src=$url`.
+/// The image URL is inserted as-is, so the service must accept it unencoded.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ImageSrcset {
+    /// The URL template for resized versions of an image.
+    pub url: String,
+
+    /// Which widths to generate candidates for.
+    pub widths: Vec<u32>,
+
+    /// The value of the `sizes` attribute, if any.
+    #[serde(default)]
+    pub sizes: Option<String>,
+}
+
+impl ImageSrcset {
+    /// Builds the value of the `srcset` attribute for the given image URL.
+    ///
+    /// Returns `None` if there are no widths configured.
+    pub fn build(&self, image_url: &str) -> Option<String> {
+        if self.widths.is_empty() {
+            return None;
+        }
+
+        let candidates = self
+            .widths
+            .iter()
+            .map(|width| {
+                let url = self
+                    .url
+                    .replace("$width", &width.to_string())
+                    .replace("$url", image_url);
+
+                format!("{url} {width}w")
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        Some(candidates)
+    }
+}
+
+#[test]
+fn srcset() {
+    let srcset = ImageSrcset {
+        url: str!("https://img.example.com/$width/$url"),
+        widths: vec![320, 640],
+        sizes: None,
+    };
+
+    assert_eq!(
+        srcset.build("https://example.com/a.png").as_deref(),
+        Some(
+            "https://img.example.com/320/https://example.com/a.png 320w, \
+             https://img.example.com/640/https://example.com/a.png 640w",
+        ),
+    );
+
+    let srcset = ImageSrcset {
+        widths: vec![],
+        ..srcset
+    };
+    assert_eq!(srcset.build("https://example.com/a.png"), None);
+}
@@ -0,0 +1,99 @@
+/*
+ * settings/limits.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+/// The default maximum depth of nested rules.
+pub const DEFAULT_MAX_DEPTH: usize = 100;
+
+/// The default maximum depth of nested includes which will be expanded.
+pub const DEFAULT_MAX_INCLUDE_DEPTH: usize = 10;
+
+/// The default maximum number of includes which will be expanded.
+pub const DEFAULT_MAX_INCLUDES: usize = 1000;
+
+/// The default maximum number of bytes which includes may add to the input.
+pub const DEFAULT_MAX_INCLUDE_BYTES: usize = 8 * 1024 * 1024;
+
+/// Limits on how much work the parser may do, to bound pathological inputs.
+///
+/// Except for the depth limit, once any of the parser limits is exceeded
+/// the rest of the input is left as plain text, and a
+/// [`LimitExceeded`](crate::parsing::ParseErrorKind::LimitExceeded)
+/// error is returned alongside the truncated tree.
+///
+/// The include limits instead apply when expanding `[[include-messy]]` blocks,
+/// so that pages including each other many times cannot grow without bound.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct ParseLimits {
+    /// How deeply rules may be nested before giving up.
+    ///
+    /// Unlike the other limits, exceeding this fails parsing entirely.
+    pub max_depth: usize,
+
+    /// How many tokens may be stepped over.
+    ///
+    /// This includes steps by rules which later backtrack, so it reflects
+    /// the actual work done rather than the length of the input.
+    pub max_steps: Option<usize>,
+
+    /// How many elements may be produced, including by rules which later backtrack.
+    pub max_elements: Option<usize>,
+
+    /// How long parsing may take, in milliseconds.
+    ///
+    /// Exceeding this produces a
+    /// [`TimeLimitExceeded`](crate::parsing::ParseErrorKind::TimeLimitExceeded)
+    /// error instead. This is ignored in WebAssembly, where no clock is available.
+    pub max_time_ms: Option<u64>,
+
+    /// How deeply includes may be nested before they are no longer expanded.
+    ///
+    /// Includes nested deeper than this are handled by
+    /// [`Includer::recursive_include`](crate::includes::Includer::recursive_include)
+    /// instead of being fetched.
+    pub max_include_depth: usize,
+
+    /// How many includes may be expanded in total, including nested ones.
+    ///
+    /// Exceeding this fails [`include`](crate::include) with
+    /// [`FtmlError::IncludeLimitExceeded`](crate::FtmlError::IncludeLimitExceeded).
+    pub max_includes: Option<usize>,
+
+    /// How many bytes of included pages may be added to the input, including nested ones.
+    ///
+    /// Exceeding this fails [`include`](crate::include) with
+    /// [`FtmlError::IncludeLimitExceeded`](crate::FtmlError::IncludeLimitExceeded).
+    pub max_include_bytes: Option<usize>,
+}
+
+impl Default for ParseLimits {
+    /// Only the recursion depth and include limits, with no bound on parser work.
+    fn default() -> Self {
+        ParseLimits {
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_steps: None,
+            max_elements: None,
+            max_time_ms: None,
+            max_include_depth: DEFAULT_MAX_INCLUDE_DEPTH,
+            max_includes: Some(DEFAULT_MAX_INCLUDES),
+            max_include_bytes: Some(DEFAULT_MAX_INCLUDE_BYTES),
+        }
+    }
+}
@@ -0,0 +1,261 @@
+/*
+ * settings/link_rewrite.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use crate::tree::LinkType;
+use std::borrow::Cow;
+use url::Url;
+
+/// Rules for transforming outgoing links as they are rendered.
+///
+/// These are applied to the final URL of each link, so page links
+/// and URL links can still be told apart by their [`LinkType`].
+///
+/// By default no links are rewritten.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Hash, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct LinkRewrites {
+    /// The rules to check, in order. Only the first matching rule is applied.
+    pub rules: Vec<LinkRewriteRule>,
+}
+
+/// A single link rewriting rule.
+///
+/// An empty rule matches every link and changes nothing.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct LinkRewriteRule {
+    /// Which kinds of link this rule applies to. If empty, it applies to all of them.
+    pub link_types: Vec<LinkType>,
+
+    /// Which hosts this rule applies to. If empty, it applies to all of them.
+    ///
+    /// Hosts are matched case-insensitively, and entries of the form
+    /// `*.example.com` match any subdomain. Links without a host,
+    /// such as those to local pages, never match a non-empty list.
+    pub hosts: Vec<String>,
+
+    /// Replaces the start of the URL, as a pair of the prefix and its replacement.
+    ///
+    /// For instance, this can point links at a mirror of a site.
+    pub replace_prefix: Option<(String, String)>,
+
+    /// Query parameters to add to the URL.
+    ///
+    /// These are added as-is, so they should already be URL-encoded.
+    pub query: Vec<(String, String)>,
+
+    /// The value of the `rel` attribute to add to the link, such as `nofollow noopener`.
+    pub rel: Option<String>,
+}
+
+/// The result of rewriting a link.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkRewrite<'a> {
+    pub url: Cow<'a, str>,
+    pub rel: Option<&'a str>,
+}
+
+impl LinkRewrites {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Applies the first matching rule to the given link URL.
+    ///
+    /// Links to anchors on the current page are never rewritten.
+    pub fn apply<'a>(&'a self, ltype: LinkType, url: Cow<'a, str>) -> LinkRewrite<'a> {
+        if url.starts_with('#') || url == "javascript:;" {
+            return LinkRewrite { url, rel: None };
+        }
+
+        match self.rules.iter().find(|rule| rule.matches(ltype, &url)) {
+            Some(rule) => rule.apply(url),
+            None => LinkRewrite { url, rel: None },
+        }
+    }
+}
+
+impl LinkRewriteRule {
+    fn matches(&self, ltype: LinkType, url: &str) -> bool {
+        if !self.link_types.is_empty() && !self.link_types.contains(&ltype) {
+            return false;
+        }
+
+        if self.hosts.is_empty() {
+            return true;
+        }
+
+        match get_host(url) {
+            Some(host) => self
+                .hosts
+                .iter()
+                .any(|pattern| host_matches(pattern, &host)),
+            None => false,
+        }
+    }
+
+    fn apply<'a>(&'a self, url: Cow<'a, str>) -> LinkRewrite<'a> {
+        let mut url = url;
+
+        if let Some((prefix, replacement)) = &self.replace_prefix {
+            if let Some(rest) = url.strip_prefix(prefix.as_str()) {
+                url = Cow::Owned(format!("{replacement}{rest}"));
+            }
+        }
+
+        if !self.query.is_empty() {
+            let mut url_owned = url.into_owned();
+
+            // Parameters go before any fragment
+            let fragment = match url_owned.find('#') {
+                Some(index) => url_owned.split_off(index),
+                None => String::new(),
+            };
+
+            for (key, value) in &self.query {
+                url_owned.push(if url_owned.contains('?') { '&' } else { '?' });
+                str_write!(url_owned, "{key}={value}");
+            }
+
+            url_owned.push_str(&fragment);
+            url = Cow::Owned(url_owned);
+        }
+
+        LinkRewrite {
+            url,
+            rel: self.rel.as_deref(),
+        }
+    }
+}
+
+/// Gets the host of an absolute URL, such as `example.com` in `https://user@example.com:80/`.
+///
+/// This uses the same parsing as browsers, so the host is the one
+/// which would actually be visited.
+fn get_host(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    url.host_str().map(str::to_owned)
+}
+
+fn host_matches(pattern: &str, host: &str) -> bool {
+    match pattern.strip_prefix("*.") {
+        Some(domain) => {
+            host.len() > domain.len() + 1
+                && host.as_bytes()[host.len() - domain.len() - 1] == b'.'
+                && host[host.len() - domain.len()..].eq_ignore_ascii_case(domain)
+        }
+        None => host.eq_ignore_ascii_case(pattern),
+    }
+}
+
+#[test]
+fn link_rewrites() {
+    let rewrites = LinkRewrites {
+        rules: vec![
+            LinkRewriteRule {
+                hosts: vec![str!("*.example.com")],
+                replace_prefix: Some((
+                    str!("https://files.example.com/"),
+                    str!("https://cdn.example.net/"),
+                )),
+                ..Default::default()
+            },
+            LinkRewriteRule {
+                link_types: vec![LinkType::Direct],
+                query: vec![(str!("utm_source"), str!("wiki"))],
+                rel: Some(str!("nofollow noopener")),
+                ..Default::default()
+            },
+        ],
+    };
+
+    macro_rules! check {
+        ($ltype:expr, $url:expr, $expected_url:expr, $expected_rel:expr $(,)?) => {{
+            let rewrite = rewrites.apply($ltype, cow!($url));
+            assert_eq!(rewrite.url, $expected_url, "Rewritten URL doesn't match");
+            assert_eq!(rewrite.rel, $expected_rel, "Rel value doesn't match");
+        }};
+    }
+
+    check!(
+        LinkType::Direct,
+        "https://files.example.com/a.png",
+        "https://cdn.example.net/a.png",
+        None,
+    );
+    check!(
+        LinkType::Direct,
+        "https://EXAMPLE.com/page",
+        "https://EXAMPLE.com/page?utm_source=wiki",
+        Some("nofollow noopener"),
+    );
+    check!(
+        LinkType::Direct,
+        "https://example.org/search?q=1#top",
+        "https://example.org/search?q=1&utm_source=wiki#top",
+        Some("nofollow noopener"),
+    );
+    check!(LinkType::Page, "/scp-001", "/scp-001", None);
+    check!(LinkType::Direct, "#footnote-1", "#footnote-1", None);
+
+    assert_eq!(
+        get_host("https://user@example.com:8080/path").as_deref(),
+        Some("example.com"),
+    );
+    assert_eq!(
+        get_host("https://spam.net\\@example.com/").as_deref(),
+        Some("spam.net"),
+    );
+    assert_eq!(get_host("mailto:user@example.com"), None);
+    assert_eq!(get_host("/local"), None);
+    assert!(host_matches("*.example.com", "a.b.example.com"));
+    assert!(!host_matches("*.example.com", "example.com"));
+    assert!(!host_matches("*.example.com", "badexample.com"));
+}
+
+#[test]
+fn link_rewrites_userinfo() {
+    // Hosts are found as browsers would, so userinfo can't hide the real one
+    let rewrites = LinkRewrites {
+        rules: vec![
+            LinkRewriteRule {
+                hosts: vec![str!("example.com")],
+                ..Default::default()
+            },
+            LinkRewriteRule {
+                link_types: vec![LinkType::Direct],
+                rel: Some(str!("nofollow noopener")),
+                ..Default::default()
+            },
+        ],
+    };
+
+    macro_rules! check {
+        ($url:expr, $expected_rel:expr $(,)?) => {{
+            let rewrite = rewrites.apply(LinkType::Direct, cow!($url));
+            assert_eq!(rewrite.url, $url, "Rewritten URL doesn't match");
+            assert_eq!(rewrite.rel, $expected_rel, "Rel value doesn't match");
+        }};
+    }
+
+    check!("https://example.com/page", None);
+    check!("https://spam.net\\@example.com/", Some("nofollow noopener"));
+}
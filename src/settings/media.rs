@@ -0,0 +1,47 @@
+/*
+ * settings/media.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+/// How much of an audio or video file is fetched before it is played.
+///
+/// This is a hint to the browser, emitted as the `preload` attribute.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum MediaPreload {
+    /// Nothing is fetched until playback starts.
+    None,
+
+    /// Only the duration and dimensions are fetched.
+    #[default]
+    Metadata,
+
+    /// The whole file may be fetched.
+    Auto,
+}
+
+impl MediaPreload {
+    /// The value of the `preload` attribute.
+    pub fn value(self) -> &'static str {
+        match self {
+            MediaPreload::None => "none",
+            MediaPreload::Metadata => "metadata",
+            MediaPreload::Auto => "auto",
+        }
+    }
+}
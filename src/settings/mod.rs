@@ -18,9 +18,60 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+mod cancellation;
+mod capability;
+mod color;
+mod container;
+mod data_attribute;
+mod embed;
+mod fallback;
+mod footnote;
+mod heading_id;
+mod html_block;
+mod image;
 mod interwiki;
+mod limits;
+mod link_rewrite;
+mod media;
+mod preset;
+mod redirect;
+mod sanitize;
+mod size;
+mod style;
+mod typography;
+mod word_break;
 
+pub use self::cancellation::CancellationToken;
+pub use self::capability::GatedElement;
+pub use self::color::{ColorVariables, DEFAULT_COLOR_VARIABLES};
+pub use self::container::ContainerAttributePolicy;
+pub use self::data_attribute::DataAttributePolicy;
+pub use self::embed::{EmbedOutput, EmbedProvider, EmbedProviders};
+pub use self::fallback::{Fallback, FallbackElement, FallbackTable};
+pub use self::footnote::{
+    FootnoteBlockMode, FootnoteNumbering, FootnotePlacement, FootnoteStyle,
+    FOOTNOTE_INDEX_PLACEHOLDER, FOOTNOTE_SYMBOL_MAX_REPEAT,
+};
+pub use self::heading_id::HeadingIdStrategy;
+pub use self::html_block::HtmlBlockOutput;
+pub use self::image::{ImageSettings, ImageSrcset};
 pub use self::interwiki::{InterwikiSettings, DEFAULT_INTERWIKI, EMPTY_INTERWIKI};
+pub use self::limits::{
+    ParseLimits, DEFAULT_MAX_DEPTH, DEFAULT_MAX_INCLUDES, DEFAULT_MAX_INCLUDE_BYTES,
+    DEFAULT_MAX_INCLUDE_DEPTH,
+};
+pub use self::link_rewrite::{LinkRewrite, LinkRewriteRule, LinkRewrites};
+pub use self::media::MediaPreload;
+pub use self::preset::{SettingsPreset, SettingsPresets};
+pub use self::redirect::RedirectMode;
+pub use self::sanitize::SanitizationPolicy;
+pub use self::size::{SizeCheck, SizeLimits};
+pub use self::style::StylePolicy;
+pub use self::typography::TypographySettings;
+pub use self::word_break::{WordBreakSettings, WordBreakStyle};
+
+use crate::audit::AuditRecorder;
+use crate::render::MessageSource;
 
 const DEFAULT_MINIFY_CSS: bool = true;
 
@@ -40,6 +91,17 @@ pub struct WikitextSettings {
     /// * Button
     pub enable_page_syntax: bool,
 
+    /// Whether content which is inserted into the page as-is is permitted.
+    ///
+    /// This refers to:
+    /// * HTML
+    /// * Iframe
+    /// * CSS, including `[[module CSS]]`
+    pub enable_raw_content: bool,
+
+    /// Whether `[[embed]]` blocks are permitted.
+    pub enable_embeds: bool,
+
     /// Whether a literal `[[include]]` is permitted.
     ///
     /// If this is true, then `[[include]]` is treated as an alias
@@ -65,6 +127,223 @@ pub struct WikitextSettings {
     /// Whether to minify CSS in `<style>` blocks.
     pub minify_css: bool,
 
+    /// Whether to emit intrinsic sizing for images and iframes.
+    ///
+    /// When the dimensions of an embedded image or iframe are known,
+    /// either from its `width` and `height` attributes or from the renderer
+    /// handle, this adds those attributes and an `aspect-ratio` style so that
+    /// browsers can reserve space for it and avoid layout shift.
+    pub emit_aspect_ratio: bool,
+
+    /// Which loading-related attributes are added to images.
+    ///
+    /// This covers lazy loading, asynchronous decoding, and responsive
+    /// `srcset` candidates. See [`ImageSettings`].
+    pub images: ImageSettings,
+
+    /// How footnote references are labeled.
+    ///
+    /// This applies to both the HTML and text renderers, and allows
+    /// matching the citation style used by a particular site.
+    pub footnote_style: FootnoteStyle,
+
+    /// How footnotes are numbered, such as with numerals or symbols.
+    ///
+    /// This applies to footnote references and the footnote block.
+    pub footnote_numbering: FootnoteNumbering,
+
+    /// The title of the footnote block, overriding the localized default.
+    ///
+    /// A `title` argument on a `[[footnoteblock]]` still takes precedence.
+    pub footnote_block_title: Option<String>,
+
+    /// Where the footnote block is placed in the page.
+    pub footnote_placement: FootnotePlacement,
+
+    /// How footnotes are listed when there are multiple footnote blocks.
+    pub footnote_block_mode: FootnoteBlockMode,
+
+    /// How many footnotes come before this page's first one.
+    ///
+    /// This is for pages split into fragments which are rendered separately,
+    /// so that footnote numbers and IDs continue on from the previous fragment.
+    /// See [`FragmentExport`](crate::fragment::FragmentExport).
+    pub footnote_offset: usize,
+
+    /// How elements which cannot be shown in text degrade when using the text renderer.
+    ///
+    /// For instance, an iframe can be replaced with its URL, or math with its LaTeX source.
+    /// See [`FallbackTable::text`] for the defaults.
+    pub text_fallbacks: FallbackTable,
+
+    /// How elements which have no Markdown equivalent degrade when using the Markdown renderer.
+    ///
+    /// Only modules, media, iframes, embeds, and HTML blocks consult this table,
+    /// since everything else has a native Markdown form.
+    /// See [`FallbackTable::markdown`] for the defaults.
+    pub markdown_fallbacks: FallbackTable,
+
+    /// Whether the text renderer wraps link labels in Unicode directional isolates
+    /// if they contain text running against the page language's direction.
+    ///
+    /// This prevents labels in mixed-direction text, such as English page names
+    /// on a Hebrew or Arabic site, from reordering the text around them.
+    pub text_bidi_isolation: bool,
+
+    /// Whether to emit breadcrumb navigation at the top of the HTML output.
+    ///
+    /// This is the page's category, parent pages, and then the page itself.
+    /// See [`get_breadcrumbs()`](crate::render::breadcrumbs::get_breadcrumbs).
+    pub emit_breadcrumbs: bool,
+
+    /// Whether to use semantic HTML5 tags in the HTML output.
+    ///
+    /// If enabled, the page contents are wrapped in an `<article>`, and each
+    /// heading is grouped with the content under it into a `<section>`.
+    /// Floated `[[div]]`s, images, and tables of contents become `<aside>`s.
+    /// Other aligned images become `<figure>`s, as do gallery images with
+    /// titles, which also get a `<figcaption>`.
+    pub semantic_html: bool,
+
+    /// Whether to exclude trailing punctuation from bare URLs.
+    ///
+    /// For instance, in `see https://example.com.` the final period is
+    /// not treated as part of the link. Disabling this matches Wikidot,
+    /// which continues the URL until the next whitespace.
+    pub trim_url_suffixes: bool,
+
+    /// Whether bare URLs in text are automatically turned into links.
+    ///
+    /// When disabled, links must be written with explicit syntax,
+    /// such as `[https://example.com/ label]`, and bare URLs remain text.
+    pub enable_bare_urls: bool,
+
+    /// Whether unknown blocks which look like prose are kept as literal text.
+    ///
+    /// Text such as `[[citation needed]]` or `[[2,3]]` begins like a block,
+    /// but is usually not intended as one. When enabled, these are emitted
+    /// as-is without producing parse errors. Unknown blocks which do resemble
+    /// a block invocation, such as those with arguments, still produce errors.
+    pub literal_unknown_blocks: bool,
+
+    /// The size, in bytes, above which code block bodies are stored out-of-line.
+    ///
+    /// Bodies larger than this are moved to the syntax tree's `code_blobs`,
+    /// for the host to submit to its blob store, and the elements only hold
+    /// a reference to them. This lets hosts keep stored trees small for pages
+    /// which embed large datasets.
+    ///
+    /// If `None`, then all code blocks are kept inline.
+    pub code_blob_threshold: Option<usize>,
+
+    /// Where the table of contents and footnote block are placed in HTML output.
+    ///
+    /// Besides the usual inline placement, these can be emitted as separate
+    /// strings in [`HtmlOutput`](crate::render::html::HtmlOutput), for layouts
+    /// which show them elsewhere, such as in a sidebar.
+    pub fragment_placement: FragmentPlacement,
+
+    /// Whether to insert break opportunities into long unbroken words in HTML.
+    ///
+    /// Very long words, such as URLs or chemical names, can overflow their
+    /// container. If set, words over the threshold get `<wbr>` elements or
+    /// soft hyphens inserted. See [`WordBreakSettings`].
+    ///
+    /// If `None`, text is output unchanged.
+    pub word_break: Option<WordBreakSettings>,
+
+    /// How math blocks and inline math are output in HTML.
+    ///
+    /// By default LaTeX is converted to MathML, if the `mathml` feature
+    /// is enabled. Sites which typeset math on the client, such as with
+    /// MathJax or KaTeX, can instead receive the raw TeX in a wrapper.
+    pub math_output: MathOutput,
+
+    /// Whether code blocks are syntax highlighted when rendering HTML.
+    ///
+    /// This requires the `highlight` feature. Each token is wrapped in a
+    /// `<span>` with classes for its scope, so a highlighter doesn't need to
+    /// be run on the client. Code in unknown languages is output as-is.
+    pub highlight_code: bool,
+
+    /// Additional restrictions on what the HTML renderer will emit.
+    ///
+    /// This allows blocking particular attributes, CSS declarations,
+    /// and URL schemes, or limiting which hosts iframes may embed.
+    /// See [`SanitizationPolicy`].
+    pub sanitization: SanitizationPolicy,
+
+    /// Restrictions on the CSS in user-provided `style` attributes.
+    ///
+    /// Declarations using properties which aren't permitted, or which
+    /// exceed the configured limits, are removed while parsing, and each
+    /// produces a warning. See [`StylePolicy`].
+    pub style_policy: StylePolicy,
+
+    /// Limits on the text size set by `[[size]]` blocks.
+    ///
+    /// Sizes with units which aren't permitted, or which are outside these
+    /// limits, produce warnings while parsing. Out-of-range sizes are then
+    /// clamped when rendering. See [`SizeLimits`].
+    pub size_limits: SizeLimits,
+
+    /// Restrictions on the classes and IDs users give to blocks.
+    ///
+    /// Values which aren't permitted are removed while parsing, and each
+    /// produces a warning. See [`ContainerAttributePolicy`].
+    pub container_attributes: ContainerAttributePolicy,
+
+    /// Which user-provided `data-*` attributes are emitted in HTML output.
+    ///
+    /// This allows custom frontend components to rely on particular
+    /// data attributes while blocking others. See [`DataAttributePolicy`].
+    pub data_attributes: DataAttributePolicy,
+
+    /// Rules for transforming the URLs of links as they are rendered.
+    ///
+    /// This allows adding tracking parameters, pointing links at a mirror,
+    /// or marking links to certain hosts with `rel` values. See [`LinkRewrites`].
+    pub link_rewrites: LinkRewrites,
+
+    /// Whether to record the source location of containers in the syntax tree.
+    ///
+    /// If enabled, each container stores the byte range of the wikitext
+    /// which produced it, which allows editor tooling to map output back
+    /// to the source. This includes paragraphs, formatting, headings,
+    /// and blocks such as `[[div]]`.
+    pub record_spans: bool,
+
+    /// How IDs are generated for headings and their table of contents links.
+    ///
+    /// Only has an effect when `use_true_ids` is enabled, since otherwise
+    /// heading IDs are randomly generated.
+    pub heading_ids: HeadingIdStrategy,
+
+    /// Whether to scope page CSS so it only applies to the page body.
+    ///
+    /// If enabled, the body is given a class unique to the page, such as
+    /// `wj-page-scp-001`, and every selector in `[[css]]` blocks and
+    /// `[[module CSS]]` is prefixed with it. This prevents user styling
+    /// from leaking into the rest of the site. Since imported stylesheets
+    /// cannot be scoped, `@import` rules are removed.
+    pub scope_css: bool,
+
+    /// Which typographical substitutions are performed.
+    ///
+    /// The quote and ellipsis conversions happen during preprocessing,
+    /// so they only take effect when using [`preprocess_with`](crate::preproc::preprocess_with).
+    /// See [`TypographySettings`].
+    pub typography: TypographySettings,
+
+    /// Whether to keep the number of blank lines between paragraphs as written.
+    ///
+    /// Normally any number of blank lines is a single paragraph break.
+    /// If set, each blank line beyond the first becomes a line break
+    /// between the paragraphs, for content where spacing is intentional,
+    /// such as poetry. This requires preprocessing with
+    /// [`preprocess_with_settings`](crate::preproc::preprocess_with_settings).
+    pub preserve_blank_lines: bool,
+
     /// Whether local paths are permitted.
     ///
     /// This should be disabled in contexts where there is no "local context"
@@ -77,6 +356,110 @@ pub struct WikitextSettings {
     /// * Images
     pub allow_local_paths: bool,
 
+    /// How `[[html]]` blocks are emitted in HTML output.
+    ///
+    /// Pages use separately hosted snippets, while drafts and posts embed
+    /// the HTML directly, avoiding an upload for content which may be
+    /// short-lived or previewed repeatedly.
+    pub html_block_output: HtmlBlockOutput,
+
+    /// Whether to minify HTML output after rendering.
+    ///
+    /// This collapses whitespace between tags and drops attribute
+    /// quoting where it isn't needed. Text in `<pre>`, `<textarea>`,
+    /// `<script>`, and `<style>` is left as-is.
+    pub minify_output: bool,
+
+    /// Whether to check rendered HTML for accessibility problems.
+    ///
+    /// This reports images without alternative text, skipped heading
+    /// levels, links without labels, and text set apart only by color.
+    /// The results are in [`HtmlOutput`](crate::render::html::HtmlOutput).
+    pub accessibility_audit: bool,
+
+    /// Limits on how much work the parser may do.
+    ///
+    /// Deployments parsing untrusted wikitext may wish to set these,
+    /// to bound the time spent on adversarial inputs.
+    pub parse_limits: ParseLimits,
+
+    /// A token which may be used to stop parsing and rendering early.
+    ///
+    /// This is checked periodically, so work can be abandoned once it is
+    /// no longer needed, such as when a request times out. It is not
+    /// serialized. See [`CancellationToken`].
+    #[serde(skip)]
+    pub cancellation: CancellationToken,
+
+    /// The catalog of localized messages used by renderers.
+    ///
+    /// This provides interface text such as footnote block titles,
+    /// in the language of the page. It defaults to the built-in messages
+    /// and is not serialized. See [`MessageSource`].
+    #[serde(skip)]
+    pub messages: MessageSource,
+
+    /// Where handle lookups are recorded during an audited render.
+    ///
+    /// This is set by [`audit()`](crate::audit::audit), and does not
+    /// record anything by default. It is not serialized.
+    #[serde(skip)]
+    pub audit: AuditRecorder,
+
+    /// Additional providers which may be used in `[[embed]]` blocks.
+    ///
+    /// Each is rendered as an `<iframe>` or a link, depending on its
+    /// configuration. See [`EmbedProviders`].
+    pub embed_providers: EmbedProviders,
+
+    /// Flags which may be checked by `[[if]]` blocks, such as `beta` or `mobile`.
+    ///
+    /// This allows content to vary based on the context it is displayed in,
+    /// in the same way `[[iftags]]` varies based on page tags.
+    pub condition_flags: Vec<String>,
+
+    /// Whether to replace page variables in text, such as `%%title%%` or `%%created_by%%`.
+    ///
+    /// These are the fields used by ListPages templates, and are filled in
+    /// using the current page's information. Unknown variables are left as-is.
+    pub substitute_page_variables: bool,
+
+    /// Whether forum-style quoted replies are parsed, such as `[quote=username]`.
+    ///
+    /// These are closed by `[/quote]`, and are rendered with a header
+    /// naming the author being quoted, if there is one.
+    pub enable_forum_quotes: bool,
+
+    /// Whether malformed syntax causes the whole parse to fail.
+    ///
+    /// Normally parsing is lenient: malformed blocks and other syntax fall
+    /// back to plain text, and produce warnings. If set, any warnings or
+    /// errors instead fail the parse, which then returns the source as-is
+    /// with only the offending errors, and is marked by
+    /// [`ParseOutcome::is_rejected()`](crate::parsing::ParseOutcome::is_rejected).
+    /// This is useful for validating drafts in an editor, but should not
+    /// be used when rendering existing content.
+    pub strict_parsing: bool,
+
+    /// Whether colors are rendered as CSS custom properties, such as for `##red|text##`.
+    ///
+    /// Mapped colors are output as `var(--wj-color-red, red)` instead of
+    /// the literal value, so themes such as a dark mode can restyle them.
+    /// See [`ColorVariables`] and [`DEFAULT_COLOR_VARIABLES`].
+    ///
+    /// If `None`, colors are output as-is.
+    pub color_variables: Option<ColorVariables>,
+
+    /// How much of embedded audio and video is fetched before it is played.
+    ///
+    /// See [`MediaPreload`].
+    pub media_preload: MediaPreload,
+
+    /// How pages containing a `[[module Redirect]]` are rendered.
+    ///
+    /// See [`RedirectMode`].
+    pub redirect_mode: RedirectMode,
+
     /// What interwiki prefixes are supported.
     ///
     /// All instances of `$$` in the destination URL are replaced with the link provided
@@ -96,46 +479,244 @@ impl WikitextSettings {
     /// Returns the default settings for the given [`WikitextMode`].
     pub fn from_mode(mode: WikitextMode) -> Self {
         let interwiki = DEFAULT_INTERWIKI.clone();
+        let text_fallbacks = FallbackTable::text();
+        let markdown_fallbacks = FallbackTable::markdown();
 
         match mode {
             WikitextMode::Page => WikitextSettings {
                 mode,
                 enable_page_syntax: true,
+                enable_raw_content: true,
+                enable_embeds: true,
                 use_include_compatibility: false,
                 use_true_ids: true,
                 isolate_user_ids: false,
                 minify_css: DEFAULT_MINIFY_CSS,
+                emit_aspect_ratio: true,
+                images: ImageSettings::default(),
+                footnote_style: FootnoteStyle::Superscript,
+                footnote_numbering: FootnoteNumbering::Numeric,
+                footnote_block_title: None,
+                footnote_placement: FootnotePlacement::Block,
+                footnote_block_mode: FootnoteBlockMode::Repeat,
+                footnote_offset: 0,
+                text_fallbacks,
+                markdown_fallbacks,
+                text_bidi_isolation: true,
+                emit_breadcrumbs: false,
+                semantic_html: false,
+                trim_url_suffixes: true,
+                enable_bare_urls: true,
+                literal_unknown_blocks: true,
+                code_blob_threshold: None,
+                fragment_placement: FragmentPlacement::Inline,
+                word_break: None,
+                math_output: MathOutput::MathMl,
+                highlight_code: false,
+                sanitization: SanitizationPolicy::default(),
+                style_policy: StylePolicy::default(),
+                size_limits: SizeLimits::default(),
+                container_attributes: ContainerAttributePolicy::default(),
+                data_attributes: DataAttributePolicy::default(),
+                link_rewrites: LinkRewrites::default(),
+                record_spans: false,
+                heading_ids: HeadingIdStrategy::Numeric,
+                scope_css: false,
+                typography: TypographySettings::default(),
+                preserve_blank_lines: false,
                 allow_local_paths: true,
+                html_block_output: HtmlBlockOutput::Hosted,
+                minify_output: false,
+                accessibility_audit: false,
+                parse_limits: ParseLimits::default(),
+                cancellation: CancellationToken::new(),
+                messages: MessageSource::builtin(),
+                audit: AuditRecorder::default(),
+                embed_providers: EmbedProviders::default(),
+                condition_flags: Vec::new(),
+                substitute_page_variables: false,
+                enable_forum_quotes: false,
+                strict_parsing: false,
+                color_variables: None,
+                media_preload: MediaPreload::default(),
+                redirect_mode: RedirectMode::default(),
                 interwiki,
             },
             WikitextMode::Draft => WikitextSettings {
                 mode,
                 enable_page_syntax: true,
+                enable_raw_content: true,
+                enable_embeds: true,
                 use_include_compatibility: false,
                 use_true_ids: false,
                 isolate_user_ids: false,
                 minify_css: DEFAULT_MINIFY_CSS,
+                emit_aspect_ratio: true,
+                images: ImageSettings::default(),
+                footnote_style: FootnoteStyle::Superscript,
+                footnote_numbering: FootnoteNumbering::Numeric,
+                footnote_block_title: None,
+                footnote_placement: FootnotePlacement::Block,
+                footnote_block_mode: FootnoteBlockMode::Repeat,
+                footnote_offset: 0,
+                text_fallbacks,
+                markdown_fallbacks,
+                text_bidi_isolation: true,
+                emit_breadcrumbs: false,
+                semantic_html: false,
+                trim_url_suffixes: true,
+                enable_bare_urls: true,
+                literal_unknown_blocks: true,
+                code_blob_threshold: None,
+                fragment_placement: FragmentPlacement::Inline,
+                word_break: None,
+                math_output: MathOutput::MathMl,
+                highlight_code: false,
+                sanitization: SanitizationPolicy::default(),
+                style_policy: StylePolicy::default(),
+                size_limits: SizeLimits::default(),
+                container_attributes: ContainerAttributePolicy::default(),
+                data_attributes: DataAttributePolicy::default(),
+                link_rewrites: LinkRewrites::default(),
+                record_spans: false,
+                heading_ids: HeadingIdStrategy::Numeric,
+                scope_css: false,
+                typography: TypographySettings::default(),
+                preserve_blank_lines: false,
                 allow_local_paths: true,
+                html_block_output: HtmlBlockOutput::Srcdoc,
+                minify_output: false,
+                accessibility_audit: false,
+                parse_limits: ParseLimits::default(),
+                cancellation: CancellationToken::new(),
+                messages: MessageSource::builtin(),
+                audit: AuditRecorder::default(),
+                embed_providers: EmbedProviders::default(),
+                condition_flags: Vec::new(),
+                substitute_page_variables: false,
+                enable_forum_quotes: false,
+                strict_parsing: false,
+                color_variables: None,
+                media_preload: MediaPreload::default(),
+                redirect_mode: RedirectMode::default(),
                 interwiki,
             },
             WikitextMode::ForumPost | WikitextMode::DirectMessage => WikitextSettings {
                 mode,
                 enable_page_syntax: false,
+                enable_raw_content: false,
+                enable_embeds: mode == WikitextMode::ForumPost,
                 use_include_compatibility: false,
                 use_true_ids: false,
                 isolate_user_ids: false,
                 minify_css: DEFAULT_MINIFY_CSS,
+                emit_aspect_ratio: true,
+                images: ImageSettings::default(),
+                footnote_style: FootnoteStyle::Superscript,
+                footnote_numbering: FootnoteNumbering::Numeric,
+                footnote_block_title: None,
+                footnote_placement: FootnotePlacement::Block,
+                footnote_block_mode: FootnoteBlockMode::Repeat,
+                footnote_offset: 0,
+                text_fallbacks,
+                markdown_fallbacks,
+                text_bidi_isolation: true,
+                emit_breadcrumbs: false,
+                semantic_html: false,
+                trim_url_suffixes: true,
+                enable_bare_urls: true,
+                literal_unknown_blocks: true,
+                code_blob_threshold: None,
+                fragment_placement: FragmentPlacement::Inline,
+                word_break: None,
+                math_output: MathOutput::MathMl,
+                highlight_code: false,
+                sanitization: SanitizationPolicy::default(),
+                style_policy: StylePolicy::default(),
+                size_limits: SizeLimits::default(),
+                container_attributes: ContainerAttributePolicy::default(),
+                data_attributes: DataAttributePolicy::default(),
+                link_rewrites: LinkRewrites::default(),
+                record_spans: false,
+                heading_ids: HeadingIdStrategy::Numeric,
+                scope_css: false,
+                typography: TypographySettings::default(),
+                preserve_blank_lines: false,
                 allow_local_paths: false,
+                html_block_output: HtmlBlockOutput::Srcdoc,
+                minify_output: false,
+                accessibility_audit: false,
+                parse_limits: ParseLimits::default(),
+                cancellation: CancellationToken::new(),
+                messages: MessageSource::builtin(),
+                audit: AuditRecorder::default(),
+                embed_providers: EmbedProviders::default(),
+                condition_flags: Vec::new(),
+                substitute_page_variables: false,
+                enable_forum_quotes: true,
+                strict_parsing: false,
+                color_variables: None,
+                media_preload: MediaPreload::default(),
+                redirect_mode: RedirectMode::default(),
                 interwiki,
             },
             WikitextMode::List => WikitextSettings {
                 mode,
                 enable_page_syntax: true,
+                enable_raw_content: true,
+                enable_embeds: true,
                 use_include_compatibility: false,
                 use_true_ids: false,
                 isolate_user_ids: false,
                 minify_css: DEFAULT_MINIFY_CSS,
+                emit_aspect_ratio: true,
+                images: ImageSettings::default(),
+                footnote_style: FootnoteStyle::Superscript,
+                footnote_numbering: FootnoteNumbering::Numeric,
+                footnote_block_title: None,
+                footnote_placement: FootnotePlacement::Block,
+                footnote_block_mode: FootnoteBlockMode::Repeat,
+                footnote_offset: 0,
+                text_fallbacks,
+                markdown_fallbacks,
+                text_bidi_isolation: true,
+                emit_breadcrumbs: false,
+                semantic_html: false,
+                trim_url_suffixes: true,
+                enable_bare_urls: false,
+                literal_unknown_blocks: true,
+                code_blob_threshold: None,
+                fragment_placement: FragmentPlacement::Inline,
+                word_break: None,
+                math_output: MathOutput::MathMl,
+                highlight_code: false,
+                sanitization: SanitizationPolicy::default(),
+                style_policy: StylePolicy::default(),
+                size_limits: SizeLimits::default(),
+                container_attributes: ContainerAttributePolicy::default(),
+                data_attributes: DataAttributePolicy::default(),
+                link_rewrites: LinkRewrites::default(),
+                record_spans: false,
+                heading_ids: HeadingIdStrategy::Numeric,
+                scope_css: false,
+                typography: TypographySettings::default(),
+                preserve_blank_lines: false,
                 allow_local_paths: true,
+                html_block_output: HtmlBlockOutput::Hosted,
+                minify_output: false,
+                accessibility_audit: false,
+                parse_limits: ParseLimits::default(),
+                cancellation: CancellationToken::new(),
+                messages: MessageSource::builtin(),
+                audit: AuditRecorder::default(),
+                embed_providers: EmbedProviders::default(),
+                condition_flags: Vec::new(),
+                substitute_page_variables: true,
+                enable_forum_quotes: false,
+                strict_parsing: false,
+                color_variables: None,
+                media_preload: MediaPreload::default(),
+                redirect_mode: RedirectMode::default(),
                 interwiki,
             },
         }
@@ -166,3 +747,67 @@ pub enum WikitextMode {
     /// Processing for modules or other contexts such as `ListPages`.
     List,
 }
+
+impl WikitextMode {
+    /// Parses the name of a mode, such as `forum-post`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        const MODES: [(&str, WikitextMode); 5] = [
+            ("page", WikitextMode::Page),
+            ("draft", WikitextMode::Draft),
+            ("forum-post", WikitextMode::ForumPost),
+            ("direct-message", WikitextMode::DirectMessage),
+            ("list", WikitextMode::List),
+        ];
+
+        MODES
+            .iter()
+            .find(|(mode_name, _)| mode_name.eq_ignore_ascii_case(name))
+            .map(|(_, mode)| *mode)
+    }
+}
+
+/// Where the table of contents and footnote block are placed when rendering HTML.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum FragmentPlacement {
+    /// Rendered where they appear in the page, as usual.
+    #[default]
+    Inline,
+
+    /// Rendered only as separate fragments, and omitted from the body.
+    Separate,
+
+    /// Rendered both in the body and as separate fragments.
+    ///
+    /// The separate fragments have no `id` attributes,
+    /// so that IDs are not repeated within the page.
+    Both,
+}
+
+impl FragmentPlacement {
+    /// Whether these elements are rendered within the body.
+    #[inline]
+    pub fn inline(self) -> bool {
+        matches!(self, FragmentPlacement::Inline | FragmentPlacement::Both)
+    }
+
+    /// Whether these elements are rendered as separate fragments.
+    #[inline]
+    pub fn separate(self) -> bool {
+        matches!(self, FragmentPlacement::Separate | FragmentPlacement::Both)
+    }
+}
+
+/// How LaTeX math is emitted when rendering HTML.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum MathOutput {
+    /// Converted to MathML, with the source kept in a hidden element.
+    ///
+    /// Without the `mathml` feature, only the hidden source is emitted.
+    #[default]
+    MathMl,
+
+    /// Left as TeX in a `<span class="math">`, for client-side typesetting.
+    Source,
+}
@@ -0,0 +1,186 @@
+/*
+ * settings/preset.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::{WikitextMode, WikitextSettings};
+use crate::error::FtmlError;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Named settings configurations, such as `forum-signature` or `wiki-sidebar`.
+///
+/// This is serializable, so that a service can define its presets once
+/// and share them between each layer which renders wikitext.
+///
+/// By default no presets are registered.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct SettingsPresets {
+    /// A map of each preset's name to its configuration.
+    ///
+    /// Names are matched case-insensitively.
+    pub presets: BTreeMap<String, SettingsPreset>,
+}
+
+impl SettingsPresets {
+    /// Registers a preset, replacing any with the same name.
+    pub fn insert<S: Into<String>>(&mut self, name: S, preset: SettingsPreset) {
+        self.presets.insert(name.into(), preset);
+    }
+
+    /// Looks up the preset with the given name.
+    pub fn get(&self, name: &str) -> Option<&SettingsPreset> {
+        self.presets
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, preset)| preset)
+    }
+}
+
+/// A settings configuration, as changes from the defaults of a [`WikitextMode`].
+///
+/// Only the fields which differ are stored, so presets stay small
+/// and pick up any new settings with their mode's default value.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct SettingsPreset {
+    /// The mode whose defaults this preset is based on.
+    pub mode: WikitextMode,
+
+    /// Settings fields to replace, in the same format as a serialized [`WikitextSettings`].
+    #[serde(default)]
+    pub overrides: Map<String, Value>,
+}
+
+impl SettingsPreset {
+    /// Produces the full settings for this preset.
+    ///
+    /// This fails if any of the overrides are not valid settings fields.
+    pub fn to_settings(&self) -> Result<WikitextSettings, FtmlError> {
+        let mut fields = settings_to_map(&WikitextSettings::from_mode(self.mode));
+
+        for (key, value) in &self.overrides {
+            if !fields.contains_key(key) {
+                return Err(FtmlError::UnknownSetting(key.clone()));
+            }
+
+            fields.insert(key.clone(), value.clone());
+        }
+
+        let settings = serde_json::from_value(Value::Object(fields))?;
+        Ok(settings)
+    }
+}
+
+impl WikitextSettings {
+    /// Returns the settings for the preset with the given name.
+    ///
+    /// The name is first looked up in `presets`, and otherwise
+    /// may be the name of a [`WikitextMode`], such as `forum-post`.
+    pub fn from_named_preset(
+        presets: &SettingsPresets,
+        name: &str,
+    ) -> Result<Self, FtmlError> {
+        if let Some(preset) = presets.get(name) {
+            return preset.to_settings();
+        }
+
+        match WikitextMode::from_name(name) {
+            Some(mode) => Ok(WikitextSettings::from_mode(mode)),
+            None => Err(FtmlError::UnknownPreset(str!(name))),
+        }
+    }
+
+    /// Registers these settings in `presets` with the given name.
+    ///
+    /// Only the fields which differ from the defaults of this mode are stored.
+    pub fn to_named_preset<S: Into<String>>(
+        &self,
+        presets: &mut SettingsPresets,
+        name: S,
+    ) {
+        let defaults = settings_to_map(&WikitextSettings::from_mode(self.mode));
+        let overrides = settings_to_map(self)
+            .into_iter()
+            .filter(|(key, value)| defaults.get(key) != Some(value))
+            .collect();
+
+        let preset = SettingsPreset {
+            mode: self.mode,
+            overrides,
+        };
+
+        presets.insert(name, preset);
+    }
+}
+
+fn settings_to_map(settings: &WikitextSettings) -> Map<String, Value> {
+    match serde_json::to_value(settings) {
+        Ok(Value::Object(map)) => map,
+        _ => panic!("Settings did not serialize as an object"),
+    }
+}
+
+#[test]
+fn named_presets() {
+    let mut settings = WikitextSettings::from_mode(WikitextMode::ForumPost);
+    settings.minify_output = true;
+    settings.footnote_block_title = Some(str!("Notes"));
+
+    let mut presets = SettingsPresets::default();
+    settings.to_named_preset(&mut presets, "forum-signature");
+
+    let preset = presets.get("Forum-Signature").unwrap();
+    assert_eq!(preset.mode, WikitextMode::ForumPost);
+    assert_eq!(preset.overrides.len(), 2);
+
+    // Round-trip through serialization
+    let json = serde_json::to_string(&presets).unwrap();
+    let mut presets: SettingsPresets = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        WikitextSettings::from_named_preset(&presets, "forum-signature").unwrap(),
+        settings,
+    );
+
+    // Built-in modes
+    assert_eq!(
+        WikitextSettings::from_named_preset(&presets, "draft").unwrap(),
+        WikitextSettings::from_mode(WikitextMode::Draft),
+    );
+
+    // Errors
+    assert!(matches!(
+        WikitextSettings::from_named_preset(&presets, "wiki-sidebar"),
+        Err(FtmlError::UnknownPreset(_)),
+    ));
+
+    let mut overrides = Map::new();
+    overrides.insert(str!("not-a-setting"), Value::Bool(true));
+    presets.insert(
+        "wiki-sidebar",
+        SettingsPreset {
+            mode: WikitextMode::List,
+            overrides,
+        },
+    );
+    assert!(matches!(
+        WikitextSettings::from_named_preset(&presets, "wiki-sidebar"),
+        Err(FtmlError::UnknownSetting(_)),
+    ));
+}
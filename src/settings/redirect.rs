@@ -0,0 +1,49 @@
+/*
+ * settings/redirect.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use crate::tree::SyntaxTree;
+
+/// How pages with a `[[module Redirect]]` are rendered.
+///
+/// Hosts which perform redirects themselves can find them using
+/// [`SyntaxTree::redirects()`](crate::tree::SyntaxTree::redirects).
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum RedirectMode {
+    /// The page is rendered as usual, and the redirect produces no output.
+    #[default]
+    Ignore,
+
+    /// The page is rendered as usual, and a `refresh` meta tag pointing to
+    /// the destination is added to the HTML output.
+    ///
+    /// If there are several redirects, only the first is used.
+    MetaRefresh,
+
+    /// Nothing is rendered for pages which redirect.
+    Skip,
+}
+
+impl RedirectMode {
+    /// Whether rendering of this page should be skipped.
+    pub fn skips(self, tree: &SyntaxTree) -> bool {
+        self == RedirectMode::Skip && !tree.redirects().is_empty()
+    }
+}
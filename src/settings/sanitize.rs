@@ -0,0 +1,405 @@
+/*
+ * settings/sanitize.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use crate::tree::attribute::URL_ATTRIBUTES;
+use std::borrow::Cow;
+use unicase::UniCase;
+use url::Url;
+
+/// Restrictions on what the HTML renderer may emit.
+///
+/// This is applied on top of the usual checks, such as the list of safe
+/// attributes, to give operators hosting user content finer control over
+/// the output without needing a separate HTML sanitizer.
+///
+/// The default policy has no additional restrictions.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct SanitizationPolicy {
+    /// Attributes which are never emitted, such as `style`.
+    pub blocked_attributes: Vec<String>,
+
+    /// CSS declarations removed from `style` attributes and `[[css]]` stylesheets.
+    ///
+    /// Each entry is either a property name, such as `position`, which removes
+    /// the property entirely, or a declaration such as `position: fixed`,
+    /// which only removes the property when it has that value.
+    pub blocked_css: Vec<String>,
+
+    /// URL schemes which are not emitted in attributes such as `href` or `src`,
+    /// for instance `data`. Attributes with these URLs are omitted.
+    ///
+    /// `javascript:` URLs are always blocked, regardless of this setting.
+    pub blocked_url_schemes: Vec<String>,
+
+    /// If set, the only hosts which `[[iframe]]` may embed.
+    ///
+    /// Subdomains of the listed hosts are also permitted.
+    /// Iframes for other hosts are replaced with an error message.
+    pub iframe_hosts: Option<Vec<String>>,
+
+    /// If set, the `sandbox` attribute for `[[iframe]]` elements.
+    ///
+    /// Each entry is a sandbox permission, such as `allow-scripts`.
+    /// An empty list applies all sandbox restrictions.
+    pub iframe_sandbox: Option<Vec<String>>,
+
+    /// If set, the `sandbox` attribute for iframes holding `[[html]]` blocks,
+    /// when these are output inline rather than hosted separately.
+    ///
+    /// This is separate from `iframe_sandbox`, since this HTML is written by
+    /// users rather than a third party. `allow-same-origin` is always removed,
+    /// as the HTML would otherwise run with the page's origin.
+    ///
+    /// If unset, only scripts are permitted.
+    pub html_sandbox: Option<Vec<String>>,
+}
+
+/// Sandbox used for inline HTML blocks if the sanitization policy doesn't give one.
+const DEFAULT_HTML_SANDBOX: &str = "allow-scripts";
+
+impl SanitizationPolicy {
+    /// Whether this policy has no additional restrictions.
+    pub fn is_empty(&self) -> bool {
+        self.blocked_attributes.is_empty()
+            && self.blocked_css.is_empty()
+            && self.blocked_url_schemes.is_empty()
+            && self.iframe_hosts.is_none()
+            && self.iframe_sandbox.is_none()
+            && self.html_sandbox.is_none()
+    }
+
+    /// Checks an attribute against this policy.
+    ///
+    /// Returns the value to emit, or `None` if the attribute should be omitted.
+    pub fn sanitize_attribute<'a>(
+        &self,
+        key: &str,
+        value: &'a str,
+    ) -> Option<Cow<'a, str>> {
+        if self
+            .blocked_attributes
+            .iter()
+            .any(|blocked| blocked.eq_ignore_ascii_case(key))
+        {
+            debug!("Omitting attribute '{key}' blocked by sanitization policy");
+            return None;
+        }
+
+        if key.eq_ignore_ascii_case("style") && !self.blocked_css.is_empty() {
+            return Some(self.sanitize_style(value));
+        }
+
+        if URL_ATTRIBUTES.contains(&UniCase::ascii(key)) && self.is_url_blocked(value) {
+            warn!("Omitting attribute '{key}' with URL blocked by sanitization policy");
+            return None;
+        }
+
+        Some(Cow::Borrowed(value))
+    }
+
+    /// Whether this CSS declaration, such as `position: fixed`, is blocked.
+    ///
+    /// Escapes are decoded before matching, so they cannot be used
+    /// to hide a declaration.
+    pub fn is_declaration_blocked(&self, declaration: &str) -> bool {
+        let declaration = unescape_css(declaration);
+        let (property, value) = declaration.split_once(':').unwrap_or((&declaration, ""));
+        let property = property.trim();
+        let value = normalize_css_value(value);
+
+        self.blocked_css
+            .iter()
+            .any(|rule| match rule.split_once(':') {
+                Some((rule_property, rule_value)) => {
+                    rule_property.trim().eq_ignore_ascii_case(property)
+                        && normalize_css_value(rule_value).eq_ignore_ascii_case(&value)
+                }
+                None => rule.trim().eq_ignore_ascii_case(property),
+            })
+    }
+
+    /// Removes blocked declarations from inline CSS.
+    ///
+    /// Comments are removed from the output, and escapes are decoded
+    /// before matching, so neither can be used to hide a declaration.
+    pub fn sanitize_style<'a>(&self, style: &'a str) -> Cow<'a, str> {
+        let stripped = strip_css_comments(style);
+        let declarations = stripped
+            .split(';')
+            .filter(|declaration| !declaration.trim().is_empty());
+
+        if !declarations
+            .clone()
+            .any(|declaration| self.is_declaration_blocked(declaration))
+        {
+            return match stripped {
+                Cow::Borrowed(_) => Cow::Borrowed(style),
+                Cow::Owned(stripped) => Cow::Owned(stripped),
+            };
+        }
+
+        debug!("Removing CSS blocked by sanitization policy");
+
+        let kept = declarations
+            .filter(|declaration| !self.is_declaration_blocked(declaration))
+            .map(str::trim)
+            .collect::<Vec<_>>();
+
+        Cow::Owned(kept.join("; "))
+    }
+
+    /// Whether this URL uses a blocked scheme.
+    pub fn is_url_blocked(&self, url: &str) -> bool {
+        let scheme = match url.split_once(':') {
+            Some((scheme, _)) => scheme.trim(),
+            None => return false,
+        };
+
+        // Relative URLs, such as "/a:b", have no scheme.
+        if !scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        {
+            return false;
+        }
+
+        self.blocked_url_schemes.iter().any(|blocked| {
+            let blocked = blocked.strip_suffix(':').unwrap_or(blocked);
+            blocked.eq_ignore_ascii_case(scheme)
+        })
+    }
+
+    /// The value of the `sandbox` attribute for iframes, if any.
+    pub fn iframe_sandbox(&self) -> Option<String> {
+        self.iframe_sandbox
+            .as_ref()
+            .map(|permissions| permissions.join(" "))
+    }
+
+    /// The value of the `sandbox` attribute for inline `[[html]]` blocks.
+    ///
+    /// Unlike [`iframe_sandbox()`](Self::iframe_sandbox), this is always present,
+    /// and never contains `allow-same-origin`.
+    pub fn html_sandbox(&self) -> String {
+        match self.html_sandbox {
+            Some(ref permissions) => permissions
+                .iter()
+                .flat_map(|permission| permission.split_whitespace())
+                .filter(|permission| {
+                    !permission.eq_ignore_ascii_case("allow-same-origin")
+                })
+                .collect::<Vec<_>>()
+                .join(" "),
+            None => str!(DEFAULT_HTML_SANDBOX),
+        }
+    }
+
+    /// Whether an iframe may embed this URL.
+    pub fn is_iframe_allowed(&self, url: &str) -> bool {
+        let hosts = match self.iframe_hosts {
+            Some(ref hosts) => hosts,
+            None => return true,
+        };
+
+        let host = match url_host(url) {
+            Some(host) => host,
+            None => return false,
+        };
+
+        hosts.iter().any(|allowed| {
+            let allowed = allowed.as_str();
+
+            host.eq_ignore_ascii_case(allowed)
+                || (host.len() > allowed.len()
+                    && host.as_bytes()[host.len() - allowed.len() - 1] == b'.'
+                    && host[host.len() - allowed.len()..].eq_ignore_ascii_case(allowed))
+        })
+    }
+}
+
+/// Normalizes a CSS value for comparison, removing whitespace and `!important`.
+fn normalize_css_value(value: &str) -> String {
+    let value = value.trim();
+    let value = value.strip_suffix("!important").unwrap_or(value);
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Removes all comments from CSS.
+///
+/// An unterminated comment runs to the end of the text, as in browsers.
+fn strip_css_comments(css: &str) -> Cow<'_, str> {
+    if !css.contains("/*") {
+        return Cow::Borrowed(css);
+    }
+
+    let mut output = String::with_capacity(css.len());
+    let mut rest = css;
+
+    while let Some(start) = rest.find("/*") {
+        output.push_str(&rest[..start]);
+        rest = match rest[start + 2..].find("*/") {
+            Some(end) => &rest[start + 2 + end + 2..],
+            None => "",
+        };
+    }
+
+    output.push_str(rest);
+    Cow::Owned(output)
+}
+
+/// Decodes CSS escapes, such as `\70` or `\i` for the letters `p` and `i`.
+fn unescape_css(css: &str) -> Cow<'_, str> {
+    if !css.contains('\\') {
+        return Cow::Borrowed(css);
+    }
+
+    let mut output = String::with_capacity(css.len());
+    let mut chars = css.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            output.push(ch);
+            continue;
+        }
+
+        // Hex escapes are up to six digits, optionally followed by one whitespace
+        let mut code = String::new();
+        while code.len() < 6 {
+            match chars.peek() {
+                Some(&digit) if digit.is_ascii_hexdigit() => {
+                    code.push(digit);
+                    chars.next();
+                }
+                _ => break,
+            }
+        }
+
+        if code.is_empty() {
+            if let Some(escaped) = chars.next() {
+                output.push(escaped);
+            }
+        } else {
+            let value = u32::from_str_radix(&code, 16).unwrap_or(0);
+            output.push(char::from_u32(value).unwrap_or(char::REPLACEMENT_CHARACTER));
+
+            if chars.peek().is_some_and(|ch| ch.is_whitespace()) {
+                chars.next();
+            }
+        }
+    }
+
+    Cow::Owned(output)
+}
+
+/// Gets the host of an absolute URL, without any port or user information.
+///
+/// URLs are parsed as browsers do, so for instance backslashes in
+/// `https://evil.net\@example.com/` act as slashes.
+fn url_host(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    url.host_str().map(str::to_owned)
+}
+
+#[test]
+fn sanitization_policy() {
+    let policy = SanitizationPolicy {
+        blocked_attributes: vec![str!("onclick"), str!("title")],
+        blocked_css: vec![str!("position: fixed"), str!("z-index")],
+        blocked_url_schemes: vec![str!("data"), str!("ftp:")],
+        iframe_hosts: Some(vec![str!("example.com")]),
+        iframe_sandbox: Some(vec![str!("allow-scripts"), str!("allow-popups")]),
+        html_sandbox: Some(vec![
+            str!("allow-forms ALLOW-SAME-ORIGIN"),
+            str!("allow-scripts"),
+        ]),
+    };
+
+    // Attributes
+    assert_eq!(policy.sanitize_attribute("TITLE", "x"), None);
+    assert_eq!(policy.sanitize_attribute("class", "x"), Some(cow!("x")));
+    assert_eq!(
+        policy.sanitize_attribute("src", "data:image/png;base64,AA"),
+        None
+    );
+    assert_eq!(
+        policy.sanitize_attribute("href", "FTP://example.com/"),
+        None
+    );
+    assert_eq!(
+        policy.sanitize_attribute("href", "https://example.com/"),
+        Some(cow!("https://example.com/")),
+    );
+    assert_eq!(
+        policy.sanitize_attribute("href", "/a:b"),
+        Some(cow!("/a:b"))
+    );
+
+    // CSS
+    assert_eq!(policy.sanitize_style("color: red"), "color: red");
+    assert_eq!(
+        policy.sanitize_style("position: absolute;"),
+        "position: absolute;"
+    );
+    assert_eq!(
+        policy.sanitize_style("color: red; position:FIXED !important; z-index: 10"),
+        "color: red",
+    );
+    assert_eq!(
+        policy.sanitize_attribute("style", "z-index: 1; top: 0"),
+        Some(cow!("top: 0")),
+    );
+    assert_eq!(policy.sanitize_style("position/**/: fixed"), "");
+    assert_eq!(
+        policy.sanitize_style("color: red /*;*/; /*x;*/position: fixed"),
+        "color: red",
+    );
+    assert_eq!(policy.sanitize_style("z-ind\\65x: 1; top: 0"), "top: 0");
+    assert_eq!(policy.sanitize_style("\\7a -index: 1"), "");
+    assert_eq!(policy.sanitize_style("position: fi\\78 ed"), "");
+    assert_eq!(
+        policy.sanitize_style("color: /* blue */ red"),
+        "color:  red"
+    );
+
+    // Iframes
+    assert!(policy.is_iframe_allowed("https://example.com/embed"));
+    assert!(policy.is_iframe_allowed("https://www.Example.com:8080/"));
+    assert!(!policy.is_iframe_allowed("https://badexample.com/"));
+    assert!(!policy.is_iframe_allowed("https://example.com.evil.net/"));
+    assert!(!policy.is_iframe_allowed("/local/page"));
+    assert!(!policy.is_iframe_allowed("https://evil.net\\@example.com/"));
+    assert!(!policy.is_iframe_allowed("https://evil.net\\.example.com/"));
+    assert!(policy.is_iframe_allowed("https://user@example.com/"));
+    assert!(SanitizationPolicy::default().is_iframe_allowed("/local/page"));
+    assert!(SanitizationPolicy::default().is_empty());
+
+    // Sandbox
+    assert_eq!(
+        policy.iframe_sandbox().as_deref(),
+        Some("allow-scripts allow-popups"),
+    );
+    assert_eq!(SanitizationPolicy::default().iframe_sandbox(), None);
+    assert_eq!(policy.html_sandbox(), "allow-forms allow-scripts");
+    assert_eq!(
+        SanitizationPolicy::default().html_sandbox(),
+        "allow-scripts"
+    );
+}
@@ -0,0 +1,201 @@
+/*
+ * settings/size.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::borrow::Cow;
+
+/// The size of normal text in pixels, for converting pixel sizes.
+const BASE_PIXELS: f64 = 16.0;
+
+/// The size of normal text in points, for converting point sizes.
+const BASE_POINTS: f64 = 12.0;
+
+/// Font size keywords, which are always within reasonable bounds.
+const KEYWORDS: [&str; 10] = [
+    "xx-small",
+    "x-small",
+    "small",
+    "medium",
+    "large",
+    "x-large",
+    "xx-large",
+    "xxx-large",
+    "smaller",
+    "larger",
+];
+
+/// Limits on the text size set by `[[size]]` blocks.
+///
+/// Sizes may be given as a keyword, such as `larger`, or with one of the
+/// units `%`, `em`, `rem`, `px`, or `pt`. Sizes using other units are
+/// ignored while parsing, and produce a warning.
+///
+/// Bounds are given as a percentage of the size of normal text, with
+/// `1em` being `100%`, as are `16px` and `12pt`. Sizes outside these
+/// bounds produce a warning while parsing, and are clamped when rendering.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct SizeLimits {
+    /// The smallest permitted size, as a percentage of normal text.
+    pub min_percent: Option<u32>,
+
+    /// The largest permitted size, as a percentage of normal text.
+    pub max_percent: Option<u32>,
+}
+
+/// The result of checking a size against the [`SizeLimits`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SizeCheck {
+    /// The size is permitted.
+    Valid,
+
+    /// The size is valid, but is too small or large, and will be clamped.
+    OutOfRange,
+
+    /// The size is not a keyword or a number with a permitted unit.
+    Invalid,
+}
+
+impl SizeLimits {
+    /// Checks a size, such as `150%` or `2em`, against these limits.
+    pub fn check(&self, size: &str) -> SizeCheck {
+        match parse_size(size) {
+            None => SizeCheck::Invalid,
+            Some(None) => SizeCheck::Valid,
+            Some(Some(percent)) if self.clamp_percent(percent) == percent => {
+                SizeCheck::Valid
+            }
+            Some(Some(_)) => SizeCheck::OutOfRange,
+        }
+    }
+
+    /// Gets the size to render, bringing it within these limits.
+    ///
+    /// Returns `None` if the size is invalid.
+    pub fn clamp<'a>(&self, size: &'a str) -> Option<Cow<'a, str>> {
+        let size = size.trim();
+
+        match parse_size(size)? {
+            Some(percent) => {
+                let clamped = self.clamp_percent(percent);
+                if clamped == percent {
+                    Some(Cow::Borrowed(size))
+                } else {
+                    Some(Cow::Owned(format!("{clamped}%")))
+                }
+            }
+            None => Some(Cow::Borrowed(size)),
+        }
+    }
+
+    fn clamp_percent(&self, percent: f64) -> f64 {
+        let mut percent = percent;
+
+        if let Some(min) = self.min_percent {
+            percent = percent.max(f64::from(min));
+        }
+
+        if let Some(max) = self.max_percent {
+            percent = percent.min(f64::from(max));
+        }
+
+        percent
+    }
+}
+
+impl Default for SizeLimits {
+    /// Permits sizes from a tenth of normal text, up to eight times as large.
+    fn default() -> Self {
+        SizeLimits {
+            min_percent: Some(10),
+            max_percent: Some(800),
+        }
+    }
+}
+
+/// Parses a size into a percentage of normal text.
+///
+/// Returns `Some(None)` for keywords, and `None` if the size is invalid.
+fn parse_size(size: &str) -> Option<Option<f64>> {
+    let size = size.trim().to_ascii_lowercase();
+
+    if KEYWORDS.contains(&size.as_str()) {
+        return Some(None);
+    }
+
+    let split = size
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(size.len());
+
+    let (number, unit) = size.split_at(split);
+    let number = number.parse::<f64>().ok().filter(|n| n.is_finite())?;
+    let percent = match unit {
+        "%" => number,
+        "em" | "rem" => number * 100.0,
+        "px" => number / BASE_PIXELS * 100.0,
+        "pt" => number / BASE_POINTS * 100.0,
+        _ => return None,
+    };
+
+    Some(Some(percent))
+}
+
+#[test]
+fn size_limits() {
+    macro_rules! check {
+        ($limits:expr, $size:expr, $check:expr, $clamped:expr $(,)?) => {
+            assert_eq!(
+                $limits.check($size),
+                $check,
+                "Size check for {:?} doesn't match",
+                $size,
+            );
+            assert_eq!(
+                $limits.clamp($size).as_deref(),
+                $clamped,
+                "Clamped size for {:?} doesn't match",
+                $size,
+            );
+        };
+    }
+
+    let limits = SizeLimits::default();
+    check!(limits, "150%", SizeCheck::Valid, Some("150%"));
+    check!(limits, "1.5em", SizeCheck::Valid, Some("1.5em"));
+    check!(limits, "2rem", SizeCheck::Valid, Some("2rem"));
+    check!(limits, "12pt", SizeCheck::Valid, Some("12pt"));
+    check!(limits, " 24px ", SizeCheck::Valid, Some("24px"));
+    check!(limits, "larger", SizeCheck::Valid, Some("larger"));
+    check!(limits, "X-Large", SizeCheck::Valid, Some("X-Large"));
+    check!(limits, "10000%", SizeCheck::OutOfRange, Some("800%"));
+    check!(limits, "50em", SizeCheck::OutOfRange, Some("800%"));
+    check!(limits, "1px", SizeCheck::OutOfRange, Some("10%"));
+    check!(limits, "2vh", SizeCheck::Invalid, None);
+    check!(limits, "12", SizeCheck::Invalid, None);
+    check!(limits, "%", SizeCheck::Invalid, None);
+    check!(limits, "-5px", SizeCheck::Invalid, None);
+    check!(limits, "calc(100% + 1px)", SizeCheck::Invalid, None);
+    check!(limits, "1e9%", SizeCheck::Invalid, None);
+
+    let limits = SizeLimits {
+        min_percent: None,
+        max_percent: None,
+    };
+    check!(limits, "10000%", SizeCheck::Valid, Some("10000%"));
+}
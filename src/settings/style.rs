@@ -0,0 +1,138 @@
+/*
+ * settings/style.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use std::borrow::Cow;
+
+/// Restrictions on user-provided `style` attributes.
+///
+/// Unlike [`SanitizationPolicy`](super::SanitizationPolicy), which is applied
+/// when rendering, this is checked while parsing. Declarations which are
+/// removed are reported as warnings, so authors can see what was dropped.
+///
+/// The default policy has no restrictions.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct StylePolicy {
+    /// If set, the only CSS properties which may be used, such as `color`.
+    ///
+    /// Properties are matched case-insensitively.
+    pub allowed_properties: Option<Vec<String>>,
+
+    /// If set, the most declarations a single `style` attribute may have.
+    pub max_declarations: Option<usize>,
+
+    /// If set, the longest a `style` attribute may be, in bytes.
+    ///
+    /// Declarations which would make the attribute longer are removed.
+    pub max_length: Option<usize>,
+}
+
+impl StylePolicy {
+    /// Whether this policy has no restrictions.
+    pub fn is_empty(&self) -> bool {
+        self.allowed_properties.is_none()
+            && self.max_declarations.is_none()
+            && self.max_length.is_none()
+    }
+
+    /// Removes declarations from inline CSS which this policy does not permit.
+    ///
+    /// Returns the resultant style and the number of declarations removed.
+    pub fn restrict<'a>(&self, style: &'a str) -> (Cow<'a, str>, usize) {
+        let is_allowed = |declaration: &str| match self.allowed_properties {
+            Some(ref properties) => match declaration.split_once(':') {
+                Some((property, _)) => properties
+                    .iter()
+                    .any(|allowed| allowed.trim().eq_ignore_ascii_case(property.trim())),
+                None => false,
+            },
+            None => true,
+        };
+
+        let mut kept = Vec::new();
+        let mut length = 0;
+        let mut removed = 0;
+
+        for declaration in style.split(';').map(str::trim) {
+            if declaration.is_empty() {
+                continue;
+            }
+
+            // Include the "; " separator in the length
+            let added_length = match length {
+                0 => declaration.len(),
+                _ => declaration.len() + 2,
+            };
+
+            let within_limits = self.max_declarations.is_none_or(|max| kept.len() < max)
+                && self
+                    .max_length
+                    .is_none_or(|max| length + added_length <= max);
+
+            if within_limits && is_allowed(declaration) {
+                kept.push(declaration);
+                length += added_length;
+            } else {
+                removed += 1;
+            }
+        }
+
+        if removed == 0 {
+            return (Cow::Borrowed(style), 0);
+        }
+
+        (Cow::Owned(kept.join("; ")), removed)
+    }
+}
+
+#[test]
+fn style_policy() {
+    let policy = StylePolicy::default();
+    assert!(policy.is_empty());
+    assert_eq!(
+        policy.restrict("position: fixed; top: 0"),
+        (cow!("position: fixed; top: 0"), 0),
+    );
+
+    let policy = StylePolicy {
+        allowed_properties: Some(vec![str!("color"), str!("Font-Weight")]),
+        max_declarations: None,
+        max_length: None,
+    };
+    assert_eq!(policy.restrict("color: red;"), (cow!("color: red;"), 0));
+    assert_eq!(
+        policy.restrict("COLOR: red; position: fixed; font-weight: bold; nonsense"),
+        (cow!("COLOR: red; font-weight: bold"), 2),
+    );
+
+    let policy = StylePolicy {
+        allowed_properties: None,
+        max_declarations: Some(2),
+        max_length: Some(24),
+    };
+    assert_eq!(
+        policy.restrict("color: red; margin: 0; padding: 0"),
+        (cow!("color: red; margin: 0"), 1),
+    );
+    assert_eq!(
+        policy.restrict("color: red; background: blue"),
+        (cow!("color: red"), 1),
+    );
+}
@@ -0,0 +1,66 @@
+/*
+ * settings/typography.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+/// Which of Wikidot's typographical substitutions are performed.
+///
+/// These are all enabled by default, for compatibility. They can be
+/// disabled for content where they get in the way, such as code-heavy
+/// pages or languages with different quotation conventions.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct TypographySettings {
+    /// Whether ``` ``quotes'' ```, `,,quotes''`, and `` `quotes' ``
+    /// are converted into curly quotes.
+    ///
+    /// This is performed by the preprocessor.
+    pub quotes: bool,
+
+    /// Whether `--` is converted into an em dash.
+    pub dashes: bool,
+
+    /// Whether `...` and `. . .` are converted into an ellipsis.
+    ///
+    /// This is performed by the preprocessor.
+    pub ellipsis: bool,
+
+    /// Whether `<<` and `>>` are converted into guillemets.
+    pub guillemets: bool,
+}
+
+impl TypographySettings {
+    /// Settings with all typographical substitutions disabled.
+    pub const NONE: Self = TypographySettings {
+        quotes: false,
+        dashes: false,
+        ellipsis: false,
+        guillemets: false,
+    };
+}
+
+impl Default for TypographySettings {
+    fn default() -> Self {
+        TypographySettings {
+            quotes: true,
+            dashes: true,
+            ellipsis: true,
+            guillemets: true,
+        }
+    }
+}
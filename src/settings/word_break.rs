@@ -0,0 +1,60 @@
+/*
+ * settings/word_break.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+/// Settings for inserting break opportunities into long unbroken words.
+///
+/// This prevents long URLs or chemical names from overflowing their
+/// container in HTML output.
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct WordBreakSettings {
+    /// How many characters a word must exceed before breaks are inserted.
+    ///
+    /// This is also the longest stretch of a word left without a break.
+    pub threshold: usize,
+
+    /// What kind of break to insert.
+    pub style: WordBreakStyle,
+}
+
+/// What kind of break opportunity is inserted into long words.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum WordBreakStyle {
+    /// A `<wbr>` element, which breaks without adding any characters.
+    #[default]
+    Wbr,
+
+    /// A soft hyphen, which shows a hyphen where the word is broken.
+    ///
+    /// This is only used for breaks within a run of letters. Breaks after
+    /// punctuation use `<wbr>` instead, since a hyphen would look out of place.
+    SoftHyphen,
+}
+
+impl WordBreakStyle {
+    /// The HTML inserted at each forced break opportunity.
+    pub fn html(self) -> &'static str {
+        match self {
+            WordBreakStyle::Wbr => "<wbr>",
+            WordBreakStyle::SoftHyphen => "&shy;",
+        }
+    }
+}
@@ -26,7 +26,7 @@
 use super::includer::TestIncluder;
 use crate::data::{PageInfo, ScoreValue};
 use crate::parsing::ParseError;
-use crate::render::html::HtmlRender;
+use crate::render::html::{nodes_to_html, HtmlRender, HtmlTreeRender};
 use crate::render::Render;
 use crate::settings::{WikitextMode, WikitextSettings};
 use crate::tree::SyntaxTree;
@@ -211,9 +211,8 @@ impl Test<'_> {
 
         let settings = WikitextSettings::from_mode(WikitextMode::Page);
 
-        let (mut text, _pages) =
-            crate::include(&self.input, &settings, TestIncluder, || unreachable!())
-                .unwrap_or_else(|x| match x {});
+        let (mut text, _pages) = crate::include(&self.input, &settings, TestIncluder)
+            .expect("Fetching pages failed");
 
         crate::preprocess(&mut text);
         let tokens = crate::tokenize(&text);
@@ -268,6 +267,17 @@ impl Test<'_> {
             );
         }
 
+        // The node tree must serialize to the same HTML
+        let tree_output = HtmlTreeRender.render(&tree, &page_info, &settings);
+        let tree_html = nodes_to_html(&tree_output.body);
+        if tree_html != html_output.body {
+            result = TestResult::Fail;
+            eprintln!(
+                "HTML tree does not match:\nExpected: {:?}\nActual:   {:?}",
+                html_output.body, tree_html,
+            );
+        }
+
         result
     }
 }
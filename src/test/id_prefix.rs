@@ -18,8 +18,17 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+use crate::audit::AuditRecorder;
 use crate::data::{PageInfo, ScoreValue};
-use crate::settings::{WikitextMode, WikitextSettings, EMPTY_INTERWIKI};
+use crate::render::MessageSource;
+use crate::settings::{
+    CancellationToken, ContainerAttributePolicy, DataAttributePolicy, EmbedProviders,
+    FallbackTable, FootnoteBlockMode, FootnoteNumbering, FootnotePlacement,
+    FootnoteStyle, FragmentPlacement, HeadingIdStrategy, HtmlBlockOutput, ImageSettings,
+    LinkRewrites, MathOutput, MediaPreload, ParseLimits, RedirectMode,
+    SanitizationPolicy, SizeLimits, StylePolicy, TypographySettings, WikitextMode,
+    WikitextSettings, EMPTY_INTERWIKI,
+};
 use crate::tree::{
     AttributeMap, Container, ContainerType, Element, ImageSource, ListItem, ListType,
 };
@@ -53,11 +62,60 @@ fn isolate_user_ids() {
     let settings = WikitextSettings {
         mode: WikitextMode::Page,
         enable_page_syntax: true,
+        enable_raw_content: true,
+        enable_embeds: true,
         use_true_ids: true,
         use_include_compatibility: false,
         isolate_user_ids: true,
         minify_css: false,
+        emit_aspect_ratio: true,
+        images: ImageSettings::default(),
+        footnote_style: FootnoteStyle::Superscript,
+        footnote_numbering: FootnoteNumbering::Numeric,
+        footnote_block_title: None,
+        footnote_placement: FootnotePlacement::Block,
+        footnote_block_mode: FootnoteBlockMode::Repeat,
+        footnote_offset: 0,
+        text_fallbacks: FallbackTable::text(),
+        markdown_fallbacks: FallbackTable::markdown(),
+        text_bidi_isolation: true,
+        emit_breadcrumbs: false,
+        semantic_html: false,
+        trim_url_suffixes: true,
+        enable_bare_urls: true,
+        literal_unknown_blocks: true,
+        code_blob_threshold: None,
+        fragment_placement: FragmentPlacement::Inline,
+        word_break: None,
+        math_output: MathOutput::MathMl,
+        highlight_code: false,
+        sanitization: SanitizationPolicy::default(),
+        style_policy: StylePolicy::default(),
+        size_limits: SizeLimits::default(),
+        container_attributes: ContainerAttributePolicy::default(),
+        data_attributes: DataAttributePolicy::default(),
+        link_rewrites: LinkRewrites::default(),
+        record_spans: false,
+        heading_ids: HeadingIdStrategy::Numeric,
+        scope_css: false,
+        typography: TypographySettings::default(),
+        preserve_blank_lines: false,
         allow_local_paths: true,
+        html_block_output: HtmlBlockOutput::Hosted,
+        minify_output: false,
+        accessibility_audit: false,
+        parse_limits: ParseLimits::default(),
+        cancellation: CancellationToken::new(),
+        messages: MessageSource::builtin(),
+        audit: AuditRecorder::default(),
+        embed_providers: EmbedProviders::default(),
+        condition_flags: Vec::new(),
+        substitute_page_variables: false,
+        enable_forum_quotes: false,
+        strict_parsing: false,
+        color_variables: None,
+        media_preload: MediaPreload::default(),
+        redirect_mode: RedirectMode::default(),
         interwiki: EMPTY_INTERWIKI.clone(),
     };
 
@@ -240,27 +298,33 @@ fn isolate_user_ids() {
 [[radio vegetables class="u-cherry" id="u-durian"]] Lettuce"#,
         vec![Element::Container(Container::new(
             ContainerType::Paragraph,
-            vec![
-                Element::RadioButton {
-                    name: cow!("vegetables"),
-                    checked: false,
-                    attributes: AttributeMap::from(btreemap! {
-                        cow!("class") => cow!("apple"),
-                        cow!("id") => cow!("u-banana"),
-                    }),
-                },
-                text!("Celery"),
-                Element::LineBreak,
-                Element::RadioButton {
-                    name: cow!("vegetables"),
-                    checked: false,
-                    attributes: AttributeMap::from(btreemap! {
-                        cow!("class") => cow!("u-cherry"),
-                        cow!("id") => cow!("u-durian"),
-                    }),
-                },
-                text!("Lettuce"),
-            ],
+            vec![Element::RadioGroup {
+                name: cow!("vegetables"),
+                label: None,
+                elements: vec![
+                    Element::RadioButton {
+                        name: cow!("vegetables"),
+                        checked: false,
+                        label: None,
+                        attributes: AttributeMap::from(btreemap! {
+                            cow!("class") => cow!("apple"),
+                            cow!("id") => cow!("u-banana"),
+                        }),
+                    },
+                    text!("Celery"),
+                    Element::LineBreak,
+                    Element::RadioButton {
+                        name: cow!("vegetables"),
+                        checked: false,
+                        label: None,
+                        attributes: AttributeMap::from(btreemap! {
+                            cow!("class") => cow!("u-cherry"),
+                            cow!("id") => cow!("u-durian"),
+                        }),
+                    },
+                    text!("Lettuce"),
+                ],
+            }],
             AttributeMap::new(),
         ))],
     );
@@ -310,6 +374,7 @@ fn isolate_user_ids() {
             hide_text: None,
             show_top: true,
             show_bottom: false,
+            open_if_anchored: false,
         }],
     );
     check!(
@@ -329,6 +394,7 @@ fn isolate_user_ids() {
             hide_text: None,
             show_top: true,
             show_bottom: false,
+            open_if_anchored: false,
         }],
     );
 
@@ -340,6 +406,8 @@ fn isolate_user_ids() {
                 cow!("id") => cow!("u-apple"),
             }),
             align: None,
+            min_depth: None,
+            max_depth: None,
         }],
     );
     check!(
@@ -349,6 +417,8 @@ fn isolate_user_ids() {
                 cow!("id") => cow!("u-apple"),
             }),
             align: None,
+            min_depth: None,
+            max_depth: None,
         }],
     );
 
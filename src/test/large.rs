@@ -69,6 +69,125 @@ fn recursion_depth() {
     assert_eq!(element, &Element::Text(input_cow));
 }
 
+/// Test the configurable limits on parser work.
+#[test]
+fn parse_limits() {
+    use crate::render::{text::TextRender, Render};
+    use crate::settings::ParseLimits;
+
+    let page_info = PageInfo::dummy();
+
+    // Build wikitext input
+    let mut input = String::new();
+
+    for _ in 0..50 {
+        input.push_str("[[span]]**apple** //banana//[[/span]]\n");
+    }
+
+    crate::preprocess(&mut input);
+    let tokens = crate::tokenize(&input);
+
+    let check = |limits: ParseLimits| {
+        let settings = WikitextSettings {
+            parse_limits: limits,
+            ..WikitextSettings::from_mode(WikitextMode::Page)
+        };
+
+        let (tree, errors) = crate::parse(&tokens, &page_info, &settings).into();
+        let limit_errors = errors
+            .iter()
+            .filter(|error| {
+                matches!(
+                    error.kind(),
+                    ParseErrorKind::LimitExceeded | ParseErrorKind::TimeLimitExceeded,
+                )
+            })
+            .count();
+
+        // Everything is still present, with the rest left as text
+        let text = TextRender.render(&tree, &page_info, &settings);
+        (limit_errors, text.contains("[[span]]**apple**"))
+    };
+
+    // No limits
+    assert_eq!(check(ParseLimits::default()), (0, false));
+
+    // Step and element limits
+    let limits = ParseLimits {
+        max_steps: Some(200),
+        ..ParseLimits::default()
+    };
+    assert_eq!(check(limits), (1, true));
+
+    let limits = ParseLimits {
+        max_elements: Some(50),
+        ..ParseLimits::default()
+    };
+    assert_eq!(check(limits), (1, true));
+
+    // Time limit, which is exceeded immediately
+    let limits = ParseLimits {
+        max_time_ms: Some(0),
+        ..ParseLimits::default()
+    };
+    assert_eq!(check(limits), (1, true));
+
+    // Configurable recursion depth
+    let limits = ParseLimits {
+        max_depth: 1,
+        ..ParseLimits::default()
+    };
+    let settings = WikitextSettings {
+        parse_limits: limits,
+        ..WikitextSettings::from_mode(WikitextMode::Page)
+    };
+    let (_tree, errors) = crate::parse(&tokens, &page_info, &settings).into();
+    assert_eq!(errors[0].kind(), ParseErrorKind::RecursionDepthExceeded);
+}
+
+/// Test stopping parsing and rendering with a cancellation token.
+#[test]
+fn cancellation() {
+    use crate::render::{text::TextRender, Render};
+    use crate::settings::CancellationToken;
+    use crate::FtmlError;
+
+    let page_info = PageInfo::dummy();
+    let token = CancellationToken::new();
+    let settings = WikitextSettings {
+        cancellation: token.clone(),
+        ..WikitextSettings::from_mode(WikitextMode::Page)
+    };
+
+    let tokens = crate::tokenize("**apple** //banana//");
+
+    // Not cancelled, work proceeds as normal
+    let (tree, errors) = crate::parse(&tokens, &page_info, &settings).into();
+    assert!(errors.is_empty());
+    assert_eq!(
+        TextRender
+            .try_render(&tree, &page_info, &settings)
+            .expect("Render was cancelled"),
+        "apple banana",
+    );
+
+    // Cancelled, the input is left as text
+    token.cancel();
+    assert!(settings.cancellation.is_cancelled());
+
+    let (cancelled_tree, errors) = crate::parse(&tokens, &page_info, &settings).into();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind(), ParseErrorKind::Cancelled);
+    assert_ne!(cancelled_tree, tree);
+
+    // Rendering stops, and the partial output is not returned
+    assert_eq!(TextRender.render(&tree, &page_info, &settings), "");
+    assert!(matches!(
+        TextRender.try_render(&tree, &page_info, &settings),
+        Err(FtmlError::Cancelled),
+    ));
+}
+
 /// Test the parser's ability to process large bodies
 #[test]
 #[ignore = "slow test"]
@@ -107,3 +226,97 @@ In hac habitasse platea dictumst. Vestibulum fermentum libero nec erat porttitor
     // Check output
     assert_eq!(errors.len(), ITERATIONS * 3);
 }
+
+/// Test storing large code block bodies out-of-line.
+#[test]
+fn large_code_blob() {
+    use crate::render::{html::HtmlRender, text::TextRender, Render};
+    use std::mem;
+
+    let page_info = PageInfo::dummy();
+    let mut settings = WikitextSettings::from_mode(WikitextMode::Page);
+    settings.code_blob_threshold = Some(100);
+
+    // Build wikitext input
+    let mut body = String::new();
+
+    for i in 0..50 {
+        body.push_str(&format!("{i},<data>\n"));
+    }
+
+    let mut input =
+        format!("[[code type=\"csv\"]]\n{body}[[/code]]\n\n[[code]]\nsmall[[/code]]");
+
+    // Run parser steps
+    crate::preprocess(&mut input);
+    let tokens = crate::tokenize(&input);
+    let (tree, errors) = crate::parse(&tokens, &page_info, &settings).into();
+    assert!(errors.is_empty(), "Errors produced during parsing!");
+
+    // Check syntax tree
+    //
+    // Only the large code block is stored out-of-line
+    let body = body.trim_end();
+    match &tree.elements[..] {
+        [Element::CodeBlob {
+            length, language, ..
+        }, Element::Code { contents, .. }, Element::FootnoteBlock { .. }] => {
+            assert_eq!(*length, body.len());
+            assert_eq!(language.as_deref(), Some("csv"));
+            assert_eq!(contents, "small");
+        }
+        elements => panic!("Unexpected elements: {elements:#?}"),
+    }
+
+    // The body is kept in the tree, under an identifier naming the page
+    assert_eq!(tree.code_blobs.len(), 1);
+    assert_eq!(tree.code_blobs[0].contents, body);
+    assert!(tree.code_blobs[0]
+        .blob
+        .starts_with("sandbox/_default:some-page/code-"));
+
+    match &tree.elements[0] {
+        Element::CodeBlob { blob, .. } => assert_eq!(blob, &tree.code_blobs[0].blob),
+        element => panic!("Unexpected element: {element:#?}"),
+    }
+
+    // The same body on another page has another identifier
+    let other_page = PageInfo {
+        page: cow!("other-page"),
+        ..PageInfo::dummy()
+    };
+    let (other_tree, _) = crate::parse(&tokens, &other_page, &settings).into();
+    assert_ne!(tree.code_blobs[0].blob, other_tree.code_blobs[0].blob);
+
+    // Bodies are kept when serializing, unless the host takes them out first
+    let json = tree.to_json().expect("Unable to serialize tree");
+    assert_eq!(SyntaxTree::from_json(&json).unwrap(), tree);
+
+    let mut stored_tree = tree.clone();
+    let code_blobs = mem::take(&mut stored_tree.code_blobs);
+    assert_eq!(code_blobs, tree.code_blobs);
+
+    let json = serde_json::to_string(&stored_tree).expect("Unable to serialize tree");
+    assert!(
+        !json.contains("<data>"),
+        "Serialized tree contains code block body"
+    );
+
+    // Check that renderers load the body back in, once the host stores it
+    for code_blob in &tree.code_blobs {
+        crate::render::test_blobs::store(&code_blob.blob, &code_blob.contents);
+    }
+
+    let html_output = HtmlRender.render(&tree, &page_info, &settings);
+    let escaped_body = body.replace('<', "&lt;").replace('>', "&gt;");
+    assert!(
+        html_output.body.contains(&escaped_body),
+        "HTML output doesn't contain code block body",
+    );
+
+    let text_output = TextRender.render(&tree, &page_info, &settings);
+    assert!(
+        text_output.contains(body),
+        "Text output doesn't contain code block body",
+    );
+}
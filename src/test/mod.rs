@@ -24,3 +24,7 @@ mod includer;
 mod large;
 mod prop;
 mod settings;
+mod spans;
+
+#[cfg(feature = "tracing")]
+mod tracing;
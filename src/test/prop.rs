@@ -25,7 +25,8 @@ use crate::tree::attribute::SAFE_ATTRIBUTES;
 use crate::tree::{
     Alignment, AnchorTarget, AttributeMap, BibliographyList, ClearFloat, Container,
     ContainerType, Element, FloatAlignment, Heading, HeadingLevel, ImageSource,
-    LinkLabel, LinkLocation, LinkType, ListItem, ListType, Module, SyntaxTree,
+    LinkLabel, LinkLocation, LinkType, ListItem, ListPagesSelector,
+    ListPagesTemplatePart, ListType, Module, ModuleListPages, SyntaxTree,
 };
 use once_cell::sync::Lazy;
 use proptest::option;
@@ -98,11 +99,42 @@ fn arb_module() -> impl Strategy<Value = Element<'static>> {
             depth,
         });
 
+    let list_pages = (
+        arb_optional_str(),
+        any::<u32>().prop_map(NonZeroU32::new),
+        any::<bool>(),
+        proptest::collection::vec(
+            prop_oneof![
+                cow!(".+").prop_map(ListPagesTemplatePart::Text),
+                cow!(r"[a-z_]+").prop_map(ListPagesTemplatePart::Field),
+            ],
+            0..6,
+        ),
+    )
+        .prop_map(|(parent, limit, separate, template)| {
+            Module::ListPages(Box::new(ModuleListPages {
+                selector: ListPagesSelector {
+                    parent,
+                    ..Default::default()
+                },
+                order: None,
+                limit,
+                per_page: None,
+                offset: None,
+                separate,
+                wrapper: true,
+                prepend_line: None,
+                append_line: None,
+                template,
+            }))
+        });
+
     prop_oneof![
         Just(Module::Rate),
         arb_optional_str().prop_map(|page| Module::Backlinks { page }),
         any::<bool>().prop_map(|include_hidden| Module::Categories { include_hidden }),
         join,
+        list_pages,
         page_tree,
     ]
     .prop_map(Element::Module)
@@ -292,7 +324,6 @@ where
         Just(ContainerType::Deletion),
         Just(ContainerType::Hidden),
         Just(ContainerType::Invisible),
-        Just(ContainerType::Size),
         Just(ContainerType::Paragraph),
         alignment.prop_map(|align| ContainerType::Align(align)),
         heading.prop_map(|heading| ContainerType::Header(heading)),
@@ -317,6 +348,7 @@ where
         arb_optional_str(),
         any::<bool>(),
         any::<bool>(),
+        any::<bool>(),
     )
         .prop_map(
             |(
@@ -327,6 +359,7 @@ where
                 hide_text,
                 show_top,
                 show_bottom,
+                open_if_anchored,
             )| Element::Collapsible {
                 elements,
                 attributes,
@@ -335,6 +368,7 @@ where
                 hide_text,
                 show_top,
                 show_bottom,
+                open_if_anchored,
             },
         )
 }
@@ -345,6 +379,7 @@ fn arb_element_leaf() -> impl Strategy<Value = Element<'static>> {
     prop_oneof![
         cow!(".*").prop_map(Element::Text),
         cow!(".*").prop_map(Element::Raw),
+        cow!("[a-z]+|#[0-9]+").prop_map(Element::HtmlEntity),
         cow!(SIMPLE_EMAIL_REGEX).prop_map(Element::Email),
         arb_module(),
         arb_link_element(),
@@ -404,8 +439,12 @@ fn arb_tree() -> impl Strategy<Value = SyntaxTree<'static>> {
             SyntaxTree {
                 elements,
                 table_of_contents,
+                table_of_contents_entries: vec![],
                 footnotes,
                 bibliographies: BibliographyList::new(), // not bothering right now
+                equations: vec![],
+                styles: vec![],
+                code_blobs: vec![],
                 wikitext_len,
             }
         })
@@ -19,8 +19,10 @@
  */
 
 use crate::data::PageInfo;
+use crate::parsing::ParseErrorKind;
+use crate::render::text::TextRender;
 use crate::render::{html::HtmlRender, Render};
-use crate::settings::{WikitextMode, WikitextSettings};
+use crate::settings::{TypographySettings, WikitextMode, WikitextSettings};
 
 #[test]
 fn settings() {
@@ -76,7 +78,7 @@ fn settings() {
     check!("[[toc]]", "wj-toc", [true, false, false, false, false]);
     check!(
         "[[module Rate]]",
-        "TODO: module Rate",
+        "wj-rate",
         [true, true, false, false, true],
     );
     check!(
@@ -84,6 +86,16 @@ fn settings() {
         "INCLUDED PAGE",
         [true, true, false, false, true],
     );
+    check!(
+        "See https://example.com/.",
+        r#"href="https://example.com/""#,
+        [true, true, true, true, false],
+    );
+    check!(
+        "See [https://example.com/ the site].",
+        r#"href="https://example.com/""#,
+        [true, true, true, true, true],
+    );
     check!(
         "[[image /local-file.png]]",
         "local-file.png",
@@ -100,3 +112,556 @@ fn settings() {
         [true, true, false, false, true],
     );
 }
+
+#[test]
+fn typography() {
+    let page_info = PageInfo::dummy();
+    let input = "``Quote'' -- <<angled>>... done";
+
+    let render = |typography| {
+        let mut settings = WikitextSettings::from_mode(WikitextMode::Page);
+        settings.typography = typography;
+
+        let mut text = str!(input);
+        crate::preprocess_with(&mut text, &settings.typography);
+
+        let tokens = crate::tokenize(&text);
+        let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+        TextRender.render(&tree, &page_info, &settings)
+    };
+
+    assert_eq!(
+        render(TypographySettings::default()),
+        "“Quote” — «angled»… done",
+    );
+    assert_eq!(render(TypographySettings::NONE), input);
+    assert_eq!(
+        render(TypographySettings {
+            dashes: true,
+            ..TypographySettings::NONE
+        }),
+        "``Quote'' — <<angled>>... done",
+    );
+    assert_eq!(
+        render(TypographySettings {
+            guillemets: false,
+            ..TypographySettings::default()
+        }),
+        "“Quote” — <<angled>>… done",
+    );
+}
+
+#[test]
+fn style_policy() {
+    use crate::parsing::ParseErrorKind;
+    use crate::settings::StylePolicy;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings {
+        style_policy: StylePolicy {
+            allowed_properties: Some(vec![str!("color"), str!("font-weight")]),
+            max_declarations: None,
+            max_length: Some(32),
+        },
+        ..WikitextSettings::from_mode(WikitextMode::Page)
+    };
+    let input =
+        "[[span style=\"color: red; position: fixed; font-weight: bold\"]]A[[/span]] \
+                 [[span style=\"position: absolute\"]]B[[/span]]";
+
+    let tokens = crate::tokenize(input);
+    let (tree, errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let html = HtmlRender.render(&tree, &page_info, &settings).body;
+
+    assert!(html.contains(r#"<span style="color: red; font-weight: bold">A</span>"#));
+    assert!(html.contains("<span>B</span>"));
+    assert_eq!(
+        errors
+            .iter()
+            .filter(|error| error.kind() == ParseErrorKind::StyleDeclarationRemoved)
+            .count(),
+        2,
+    );
+}
+
+#[test]
+fn container_attributes() {
+    use crate::parsing::ParseErrorKind;
+    use crate::settings::ContainerAttributePolicy;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings {
+        container_attributes: ContainerAttributePolicy {
+            denied_classes: vec![str!("page-rate-widget-box"), str!("wj-*")],
+            denied_ids: vec![str!("page-title")],
+            class_prefix: Some(str!("u-")),
+            ..Default::default()
+        },
+        ..WikitextSettings::from_mode(WikitextMode::Page)
+    };
+    let input =
+        "[[div class=\"box page-rate-widget-box\" id=\"page-title\"]]\nA\n[[/div]]\n\
+                 [[span class=\"wj-body\"]]B[[/span]] [[span class=\"blue\"]]C[[/span]]\n\
+                 [[mark class=\"wj-body\"]]D[[/mark]]\n\n\
+                 [[blockquote class=\"quote wj-quote\" id=\"page-title\"]]\nE\n[[/blockquote]]";
+
+    let tokens = crate::tokenize(input);
+    let (tree, errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let html = HtmlRender.render(&tree, &page_info, &settings).body;
+
+    assert!(html.contains(r#"<div class="u-box"><p>A</p></div>"#));
+    assert!(html.contains("<span>B</span>"));
+    assert!(html.contains(r#"<span class="u-blue">C</span>"#));
+
+    // Other blocks are restricted as well
+    assert!(html.contains("<mark>D</mark>"));
+    assert!(html.contains(r#"<blockquote class="u-quote">"#));
+    assert_eq!(
+        errors
+            .iter()
+            .filter(|error| error.kind() == ParseErrorKind::ContainerAttributeRemoved)
+            .count(),
+        6,
+    );
+}
+
+#[test]
+fn data_attributes() {
+    use crate::settings::DataAttributePolicy;
+
+    let page_info = PageInfo::dummy();
+    let input = "[[div data-widget-id=\"rating\" data-tracking=\"x\" class=\"box\"]]\n\
+                 A\n[[/div]]\n\n\
+                 [[collapsible openIfAnchored=\"true\"]]\nB\n[[/collapsible]]";
+    let render = |policy| {
+        let settings = WikitextSettings {
+            data_attributes: policy,
+            ..WikitextSettings::from_mode(WikitextMode::Page)
+        };
+        let tokens = crate::tokenize(input);
+        let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+        HtmlRender.render(&tree, &page_info, &settings).body
+    };
+
+    let html = render(DataAttributePolicy::Preserve);
+    assert!(html.contains(r#"data-widget-id="rating""#));
+    assert!(html.contains(r#"data-tracking="x""#));
+
+    let html = render(DataAttributePolicy::Allowlist(vec![str!("data-widget-")]));
+    assert!(html.contains(r#"<div class="box" data-widget-id="rating">"#));
+    assert!(!html.contains("data-tracking"));
+
+    // Attributes added by the renderer are kept
+    let html = render(DataAttributePolicy::Drop);
+    assert!(html.contains(r#"<div class="box">"#));
+    assert!(html.contains("data-open-if-anchored"));
+}
+
+#[test]
+fn condition_flags() {
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings {
+        condition_flags: vec![str!("beta")],
+        ..WikitextSettings::from_mode(WikitextMode::Page)
+    };
+    let input =
+        "[[if beta]]\nApple\n[[/if]]\n\n[[if +beta +mobile]]\nBanana\n[[/if]]\n\n\
+                 [[if -mobile]]\nCherry\n[[/if]]";
+
+    let tokens = crate::tokenize(input);
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let text = TextRender.render(&tree, &page_info, &settings);
+
+    assert_eq!(text, "Apple\n\nCherry");
+}
+
+#[test]
+fn embed_providers() {
+    use crate::parsing::ParseErrorKind;
+    use crate::settings::{EmbedOutput, EmbedProvider};
+    use crate::tree::{Element, Embed};
+
+    let page_info = PageInfo::dummy();
+    let mut settings = WikitextSettings::from_mode(WikitextMode::Page);
+    settings.embed_providers.insert(
+        "peertube",
+        EmbedProvider {
+            url: str!("https://video.example.com/videos/embed/$$"),
+            output: EmbedOutput::Iframe,
+            params: vec![str!("start")],
+        },
+    );
+    settings.embed_providers.insert(
+        "Twitter",
+        EmbedProvider {
+            url: str!("https://twitter.com/i/status/$$"),
+            output: EmbedOutput::Link,
+            params: vec![],
+        },
+    );
+
+    let input = "[[embed PeerTube id=\"abc\" start=\"30\" autoplay=\"1\"]]\n\
+                 [[embed twitter id=\"123\"]]";
+
+    let tokens = crate::tokenize(input);
+    let (tree, errors) = crate::parse(&tokens, &page_info, &settings).into();
+    assert!(errors.is_empty(), "Unexpected errors: {errors:#?}");
+
+    match &tree.elements[0] {
+        Element::Embed(Embed::Provider {
+            provider,
+            id,
+            params,
+        }) => {
+            assert_eq!(provider, "peertube");
+            assert_eq!(id, "abc");
+            assert_eq!(params.len(), 1);
+            assert_eq!(params.get("start").map(|value| value.as_ref()), Some("30"));
+        }
+        element => panic!("Unexpected element: {element:?}"),
+    }
+
+    let html = HtmlRender.render(&tree, &page_info, &settings).body;
+    assert!(html.contains(
+        r#"<iframe src="https://video.example.com/videos/embed/abc?start=30""#
+    ));
+    assert!(html.contains(r#"<a href="https://twitter.com/i/status/123""#));
+
+    // Unregistered providers and missing IDs fail
+    for (input, kind) in [
+        ("[[embed mastodon id=\"1\"]]", ParseErrorKind::NoSuchEmbed),
+        ("[[embed peertube]]", ParseErrorKind::BlockMissingArguments),
+    ] {
+        let tokens = crate::tokenize(input);
+        let (_, errors) = crate::parse(&tokens, &page_info, &settings).into();
+        assert!(
+            errors.iter().any(|error| error.kind() == kind),
+            "Missing {kind:?} for {input:?}",
+        );
+    }
+}
+
+#[test]
+fn page_variables() {
+    let page_info = PageInfo::dummy();
+    let input = "%%title%% (%%fullname%%) by %%created_by%%, rated %%rating%%. \
+                 100%% and %%unknown%%";
+    let render = |mode| {
+        let settings = WikitextSettings::from_mode(mode);
+        let tokens = crate::tokenize(input);
+        let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+        TextRender.render(&tree, &page_info, &settings)
+    };
+
+    assert_eq!(
+        render(WikitextMode::List),
+        "A page for the age (some-page) by TODO: actual created_by (sandbox some-page), \
+         rated +69. 100%% and %%unknown%%",
+    );
+    assert_eq!(render(WikitextMode::Page), input);
+
+    // Linked title
+    let settings = WikitextSettings::from_mode(WikitextMode::List);
+    let tokens = crate::tokenize("* %%title_linked%%");
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let html = HtmlRender.render(&tree, &page_info, &settings).body;
+    assert!(html.contains(r#"href="/some-page""#));
+    assert!(html.contains("A page for the age</a>"));
+
+    // Nested elements and footnotes
+    let tokens = crate::tokenize(
+        "**%%name%%** [[size 80%]]//%%category%%//[[/size]]\n\n\
+         [[footnote]]%%title%%[[/footnote]]",
+    );
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let html = HtmlRender.render(&tree, &page_info, &settings).body;
+    assert!(html.contains("<strong>some-page</strong>"));
+    assert!(html.contains("<em>_default</em>"));
+    assert!(html.contains("A page for the age</span>"));
+    assert!(!html.contains("%%"));
+}
+
+#[test]
+fn preserve_blank_lines() {
+    let page_info = PageInfo::dummy();
+    let input = "Apple\n\n\n\nBanana\n  \n\nCherry\n\nDurian";
+    let render = |preserve_blank_lines| {
+        let settings = WikitextSettings {
+            preserve_blank_lines,
+            ..WikitextSettings::from_mode(WikitextMode::Page)
+        };
+        let mut text = str!(input);
+        crate::preprocess_with_settings(&mut text, &settings);
+
+        let tokens = crate::tokenize(&text);
+        let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+        HtmlRender.render(&tree, &page_info, &settings).body
+    };
+
+    assert_eq!(
+        render(false),
+        "<wj-body class=\"wj-body\"><p>Apple</p><p>Banana</p><p>Cherry</p><p>Durian</p></wj-body>",
+    );
+    assert_eq!(
+        render(true),
+        "<wj-body class=\"wj-body\"><p>Apple</p><br><br><p>Banana</p>\
+         <br><p>Cherry</p><p>Durian</p></wj-body>",
+    );
+}
+
+#[test]
+fn link_rewrites() {
+    use crate::settings::{LinkRewriteRule, LinkRewrites};
+    use crate::tree::LinkType;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings {
+        link_rewrites: LinkRewrites {
+            rules: vec![LinkRewriteRule {
+                link_types: vec![LinkType::Direct],
+                query: vec![(str!("ref"), str!("wiki"))],
+                rel: Some(str!("nofollow noopener")),
+                ..Default::default()
+            }],
+        },
+        ..WikitextSettings::from_mode(WikitextMode::Page)
+    };
+    let input = "[https://example.com/ Example] [[[apple]]]";
+
+    let tokens = crate::tokenize(input);
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let html = HtmlRender.render(&tree, &page_info, &settings).body;
+
+    assert!(html
+        .contains(r#"<a href="https://example.com/?ref=wiki" rel="nofollow noopener""#));
+    assert!(html.contains(r#"<a href="/apple" class="#));
+}
+
+#[test]
+fn images() {
+    use crate::settings::{ImageSettings, ImageSrcset};
+
+    let page_info = PageInfo::dummy();
+    let input = "[[image https://example.com/a.png]]";
+    let render = |images| {
+        let settings = WikitextSettings {
+            images,
+            ..WikitextSettings::from_mode(WikitextMode::Page)
+        };
+        let tokens = crate::tokenize(input);
+        let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+        HtmlRender.render(&tree, &page_info, &settings).body
+    };
+
+    let html = render(ImageSettings::default());
+    assert!(!html.contains("loading="));
+    assert!(!html.contains("srcset="));
+
+    let html = render(ImageSettings {
+        lazy_loading: true,
+        async_decoding: true,
+        srcset: Some(ImageSrcset {
+            url: str!("https://img.example.com/resize?w=$width&src=$url"),
+            widths: vec![400, 800],
+            sizes: Some(str!("(max-width: 600px) 100vw, 50vw")),
+        }),
+    });
+    assert!(html.contains(
+        "srcset=\"https://img.example.com/resize?w=400&amp;src=https://example.com/a.png 400w, \
+         https://img.example.com/resize?w=800&amp;src=https://example.com/a.png 800w\""
+    ));
+    assert!(html.contains(r#"sizes="(max-width: 600px) 100vw, 50vw""#));
+    assert!(html.contains(r#"loading="lazy" decoding="async""#));
+}
+
+#[test]
+fn forum_quotes() {
+    let page_info = PageInfo::dummy();
+    let input = "[quote=Alice]\nApple\n\n[quote]Banana[/quote]\n[/quote]\nCherry";
+    let render = |mode| {
+        let settings = WikitextSettings::from_mode(mode);
+        let tokens = crate::tokenize(input);
+        let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+        let html = HtmlRender.render(&tree, &page_info, &settings).body;
+        let text = TextRender.render(&tree, &page_info, &settings);
+        (html, text)
+    };
+
+    let (html, text) = render(WikitextMode::ForumPost);
+    assert_eq!(
+        html,
+        "<wj-body class=\"wj-body\"><blockquote class=\"wj-quote\">\
+         <div class=\"wj-quote-header\"><cite>Alice</cite> wrote:</div>\
+         <div class=\"wj-quote-content\"><p>Apple</p>\
+         <blockquote class=\"wj-quote\"><div class=\"wj-quote-content\"><p>Banana</p></div></blockquote>\
+         </div></blockquote><p>Cherry</p></wj-body>",
+    );
+    assert_eq!(text, "> Alice wrote:\n>\n> Apple\n> > Banana\n\nCherry");
+
+    // Outside of forum posts, this is left as text
+    let (html, _) = render(WikitextMode::Page);
+    assert!(!html.contains("wj-quote"));
+    assert!(html.contains("[quote=Alice]"));
+}
+
+#[test]
+fn strict_parsing() {
+    let page_info = PageInfo::dummy();
+    let parse = |input: &str, strict_parsing| {
+        let settings = WikitextSettings {
+            strict_parsing,
+            ..WikitextSettings::from_mode(WikitextMode::Page)
+        };
+        let tokens = crate::tokenize(input);
+        let outcome = crate::parse(&tokens, &page_info, &settings);
+        let rejected = outcome.is_rejected();
+        let (tree, errors) = outcome.into();
+        let html = HtmlRender.render(&tree, &page_info, &settings).body;
+        (html, errors, rejected)
+    };
+
+    // Well-formed input parses the same either way
+    let (html, errors, rejected) = parse("Apple **banana**", true);
+    assert_eq!(
+        html,
+        "<wj-body class=\"wj-body\"><p>Apple <strong>banana</strong></p></wj-body>",
+    );
+    assert!(errors.is_empty());
+    assert!(!rejected);
+
+    // Malformed blocks fall back to text when lenient
+    let input = "[[date foo]] [[abc def=1]]";
+    let (html, errors, rejected) = parse(input, false);
+    assert_eq!(
+        html,
+        "<wj-body class=\"wj-body\"><p>[[date foo]] [[abc def=1]]</p></wj-body>",
+    );
+    assert_eq!(errors.len(), 6);
+    assert!(!rejected);
+
+    // But fail the parse when strict, listing only the offending errors
+    let (html, errors, rejected) = parse(input, true);
+    assert!(rejected);
+    assert_eq!(
+        html,
+        "<wj-body class=\"wj-body\">[[date foo]] [[abc def=1]]</wj-body>",
+    );
+    assert_eq!(
+        errors
+            .iter()
+            .map(|error| (error.kind(), error.span()))
+            .collect::<Vec<_>>(),
+        vec![
+            (ParseErrorKind::BlockMalformedArguments, 12..13),
+            (ParseErrorKind::NoSuchBlock, 22..23),
+        ],
+    );
+}
+
+#[test]
+fn color_variables() {
+    use crate::settings::DEFAULT_COLOR_VARIABLES;
+
+    let page_info = PageInfo::dummy();
+    let input = "##red|Apple## ##Blue|banana## ##ff0000|cherry##";
+    let render = |color_variables| {
+        let settings = WikitextSettings {
+            color_variables,
+            ..WikitextSettings::from_mode(WikitextMode::Page)
+        };
+        let tokens = crate::tokenize(input);
+        let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+        HtmlRender.render(&tree, &page_info, &settings).body
+    };
+
+    assert_eq!(
+        render(None),
+        "<wj-body class=\"wj-body\"><p>\
+         <span style=\"color: red;\">Apple</span> \
+         <span style=\"color: Blue;\">banana</span> \
+         <span style=\"color: #ff0000;\">cherry</span>\
+         </p></wj-body>",
+    );
+
+    assert_eq!(
+        render(Some(DEFAULT_COLOR_VARIABLES.clone())),
+        "<wj-body class=\"wj-body\"><p>\
+         <span style=\"color: var(--wj-color-red, red);\">Apple</span> \
+         <span style=\"color: var(--wj-color-blue, Blue);\">banana</span> \
+         <span style=\"color: #ff0000;\">cherry</span>\
+         </p></wj-body>",
+    );
+}
+
+#[test]
+fn text_fallbacks() {
+    use crate::settings::{Fallback, FallbackElement};
+
+    let page_info = PageInfo::dummy();
+    let input =
+        "Apple\n\n[[iframe https://example.com/]]\n\n[[html]]\n<b>Banana</b>\n[[/html]]";
+    let render = |settings: &WikitextSettings| {
+        let tokens = crate::tokenize(input);
+        let (tree, _errors) = crate::parse(&tokens, &page_info, settings).into();
+        TextRender.render(&tree, &page_info, settings)
+    };
+
+    // Omitted by default
+    let mut settings = WikitextSettings::from_mode(WikitextMode::Page);
+    assert_eq!(render(&settings), "Apple");
+
+    // Placeholder and link
+    settings
+        .text_fallbacks
+        .set(FallbackElement::Html, Fallback::Placeholder);
+    settings
+        .text_fallbacks
+        .set(FallbackElement::Iframe, Fallback::Link);
+    let output = render(&settings);
+    assert!(output.contains("https://example.com/"));
+    assert!(output.contains("[HTML content]"));
+    assert!(!output.contains("Banana"));
+
+    // Source
+    settings
+        .text_fallbacks
+        .set(FallbackElement::Html, Fallback::Source);
+    assert!(render(&settings).contains("<b>Banana</b>"));
+}
+
+#[test]
+fn markdown_fallbacks() {
+    use crate::render::markdown::MarkdownRender;
+    use crate::settings::{Fallback, FallbackElement};
+
+    let page_info = PageInfo::dummy();
+    let input =
+        "Apple\n\n[[iframe https://example.com/]]\n\n[[html]]\n<b>Banana</b>\n[[/html]]";
+    let render = |settings: &WikitextSettings| {
+        let tokens = crate::tokenize(input);
+        let (tree, _errors) = crate::parse(&tokens, &page_info, settings).into();
+        MarkdownRender.render(&tree, &page_info, settings)
+    };
+
+    // Link and placeholder by default
+    let mut settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let output = render(&settings);
+    assert!(output.contains("<https://example.com/>"));
+    assert!(output.contains(r"*\[HTML content\]*"));
+    assert!(!output.contains("Banana"));
+
+    // Omitted
+    settings
+        .markdown_fallbacks
+        .set(FallbackElement::Iframe, Fallback::Omit);
+    settings
+        .markdown_fallbacks
+        .set(FallbackElement::Html, Fallback::Omit);
+    assert_eq!(render(&settings), "Apple");
+
+    // Source
+    settings
+        .markdown_fallbacks
+        .set(FallbackElement::Html, Fallback::Source);
+    assert_eq!(render(&settings), "Apple\n\n<b>Banana</b>");
+}
@@ -0,0 +1,131 @@
+/*
+ * test/spans.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use crate::data::PageInfo;
+use crate::settings::{WikitextMode, WikitextSettings};
+use crate::tree::{ContainerType, Element};
+
+#[test]
+fn spans() {
+    let page_info = PageInfo::dummy();
+    let mut settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let text = "Apple **banana** cherry\n\n[[div]]\n//Durian//\n[[/div]]";
+
+    let tokens = crate::tokenize(text);
+    let parse = |settings: &WikitextSettings| {
+        let (tree, _errors) = crate::parse(&tokens, &page_info, settings).into();
+        tree.to_owned()
+    };
+
+    // Disabled by default
+    let tree = parse(&settings);
+    tree.walk(|element| {
+        if let Element::Container(container) = element {
+            assert!(container.span().is_none(), "Span recorded when disabled");
+        }
+    });
+    assert!(!serde_json::to_string(&tree).unwrap().contains("\"span\""));
+
+    // Record spans
+    settings.record_spans = true;
+    let tree = parse(&settings);
+    let mut spans = Vec::new();
+    tree.walk(|element| {
+        if let Element::Container(container) = element {
+            let span = container.span().expect("No span recorded for container");
+            spans.push((container.ctype(), &text[span]));
+        }
+    });
+
+    assert_eq!(
+        spans,
+        vec![
+            (ContainerType::Paragraph, "Apple **banana** cherry"),
+            (ContainerType::Bold, "**banana**"),
+            (ContainerType::Div, "[[div]]\n//Durian//\n[[/div]]"),
+            (ContainerType::Paragraph, "//Durian//"),
+            (ContainerType::Italics, "//Durian//"),
+        ],
+    );
+
+    // Spans are kept in serialized output
+    let json = serde_json::to_string(&tree).unwrap();
+    assert!(json.contains(r#""span":{"start":6,"end":16}"#));
+}
+
+#[test]
+fn table_of_contents_entries() {
+    use crate::render::{html::HtmlRender, Render};
+    use crate::settings::HeadingIdStrategy;
+    use crate::tree::HeadingLevel;
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings {
+        heading_ids: HeadingIdStrategy::SlugifiedTitle,
+        ..WikitextSettings::from_mode(WikitextMode::Page)
+    };
+    let text =
+        "+ Early History\n\nApple\n\n++ Banana **Cherry**\n+* Hidden\n\n+ Early History";
+
+    let tokens = crate::tokenize(text);
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let entries: Vec<_> = tree
+        .table_of_contents_entries
+        .iter()
+        .map(|entry| {
+            (
+                entry.id.as_ref(),
+                entry.level,
+                entry.name.as_ref(),
+                &text[entry.span.clone()],
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        entries,
+        vec![
+            (
+                "early-history",
+                HeadingLevel::One,
+                "Early History",
+                "+ Early History"
+            ),
+            (
+                "banana-cherry",
+                HeadingLevel::Two,
+                "Banana Cherry",
+                "++ Banana **Cherry**",
+            ),
+            (
+                "early-history-2",
+                HeadingLevel::One,
+                "Early History",
+                "+ Early History"
+            ),
+        ],
+    );
+
+    // The IDs match those in the rendered HTML
+    let html = HtmlRender.render(&tree, &page_info, &settings).body;
+    for entry in &tree.table_of_contents_entries {
+        assert!(html.contains(&format!("id=\"{}\"", entry.id)));
+    }
+}
@@ -0,0 +1,117 @@
+/*
+ * test/tracing.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Checks that parsing and rendering emit the documented spans.
+
+use crate::data::PageInfo;
+use crate::render::{html::HtmlRender, Render};
+use crate::settings::{WikitextMode, WikitextSettings};
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// The name and fields of a created span.
+type SpanEntry = (&'static str, Vec<(String, String)>);
+
+/// Records the name and fields of each span created.
+#[derive(Debug, Default)]
+struct SpanRecorder {
+    next_id: AtomicU64,
+    spans: Arc<Mutex<Vec<SpanEntry>>>,
+}
+
+#[derive(Debug, Default)]
+struct FieldRecorder(Vec<(String, String)>);
+
+impl Visit for FieldRecorder {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push((field.name().to_string(), value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0
+            .push((field.name().to_string(), format!("{value:?}")));
+    }
+}
+
+impl Subscriber for SpanRecorder {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes) -> Id {
+        let mut fields = FieldRecorder::default();
+        span.record(&mut fields);
+        self.spans
+            .lock()
+            .unwrap()
+            .push((span.metadata().name(), fields.0));
+
+        Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+
+    fn record(&self, _: &Id, _: &Record) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, _: &Event) {}
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+#[test]
+fn spans_emitted() {
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let recorder = SpanRecorder::default();
+    let spans = Arc::clone(&recorder.spans);
+
+    tracing::subscriber::with_default(recorder, || {
+        let tokens = crate::tokenize("**Apple**");
+        let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+        HtmlRender.render(&tree, &page_info, &settings);
+    });
+
+    let spans = spans.lock().unwrap();
+    let has_span = |name: &str, field: &str, value: &str| {
+        spans.iter().any(|(span_name, fields)| {
+            *span_name == name && fields.iter().any(|(k, v)| k == field && v == value)
+        })
+    };
+
+    assert!(
+        spans.iter().any(|(name, _)| *name == "parse"),
+        "No parse span"
+    );
+    assert!(has_span("rule", "rule", "bold"), "No rule span for bold");
+    assert!(
+        has_span("render-element", "element", "Bold"),
+        "No render-element span for bold",
+    );
+    assert!(
+        has_span("render-element", "renderer", "html"),
+        "No render-element span for the HTML renderer",
+    );
+}
@@ -48,6 +48,17 @@ impl Alignment {
             Alignment::Justify => "wj-align-justify",
         }
     }
+
+    /// Inline style for this alignment, for when the stylesheet is unavailable,
+    /// such as in exported or imported pages.
+    pub fn html_style(self) -> &'static str {
+        match self {
+            Alignment::Left => "text-align: left;",
+            Alignment::Right => "text-align: right;",
+            Alignment::Center => "text-align: center;",
+            Alignment::Justify => "text-align: justify;",
+        }
+    }
 }
 
 impl TryFrom<&'_ str> for Alignment {
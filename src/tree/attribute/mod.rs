@@ -23,7 +23,7 @@ mod safe;
 use super::clone::string_to_owned;
 use crate::id_prefix::isolate_ids;
 use crate::parsing::parse_boolean;
-use crate::settings::WikitextSettings;
+use crate::settings::{ContainerAttributePolicy, StylePolicy, WikitextSettings};
 use crate::url::normalize_href;
 use std::borrow::Cow;
 use std::collections::{BTreeMap, HashMap};
@@ -111,6 +111,68 @@ impl<'t> AttributeMap<'t> {
         }
     }
 
+    /// Removes classes and IDs not permitted by the policy, and prefixes classes.
+    ///
+    /// Returns the number of values which were removed.
+    pub fn restrict_container(&mut self, policy: &ContainerAttributePolicy) -> usize {
+        if policy.is_empty() {
+            return 0;
+        }
+
+        let mut removed = 0;
+
+        if let Some(id) = self.inner.get("id") {
+            if !policy.is_id_allowed(id) {
+                debug!("Removing 'id' attribute not permitted by policy");
+                self.inner.remove("id");
+                removed += 1;
+            }
+        }
+
+        if let Some(class) = self.inner.get_mut("class") {
+            if let (Cow::Owned(restricted), count) = policy.restrict_classes(class) {
+                debug!("Removed {count} values from 'class' attribute");
+                removed += count;
+
+                if restricted.is_empty() {
+                    self.inner.remove("class");
+                } else {
+                    *class = Cow::Owned(restricted);
+                }
+            }
+        }
+
+        removed
+    }
+
+    /// Removes declarations in the `style` attribute not permitted by the policy.
+    ///
+    /// Returns the number of declarations which were removed.
+    pub fn restrict_style(&mut self, policy: &StylePolicy) -> usize {
+        if policy.is_empty() {
+            return 0;
+        }
+
+        let style = match self.inner.get_mut("style") {
+            Some(style) => style,
+            None => return 0,
+        };
+
+        let (restricted, removed) = match policy.restrict(style) {
+            (Cow::Owned(restricted), removed) => (restricted, removed),
+            (Cow::Borrowed(_), _) => return 0,
+        };
+
+        debug!("Removed {removed} declarations from 'style' attribute");
+        if restricted.is_empty() {
+            self.inner.remove("style");
+        } else {
+            *style = Cow::Owned(restricted);
+        }
+
+        removed
+    }
+
     pub fn to_owned(&self) -> AttributeMap<'static> {
         let mut inner = BTreeMap::new();
 
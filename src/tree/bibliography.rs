@@ -74,6 +74,13 @@ impl<'t> Bibliography<'t> {
         &self.0
     }
 
+    /// Mutably iterates over the contents of each entry.
+    ///
+    /// Labels are not exposed, since they must remain unique.
+    pub fn elements_mut(&mut self) -> impl Iterator<Item = &mut Vec<Element<'t>>> {
+        self.0.iter_mut().map(|(_, elements)| elements)
+    }
+
     pub fn to_owned(&self) -> Bibliography<'static> {
         Bibliography(
             self.0
@@ -123,6 +130,16 @@ impl<'t> BibliographyList<'t> {
         &self.0[index]
     }
 
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &Bibliography<'t>> {
+        self.0.iter()
+    }
+
+    #[inline]
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Bibliography<'t>> {
+        self.0.iter_mut()
+    }
+
     pub fn to_owned(&self) -> BibliographyList<'static> {
         BibliographyList(self.0.iter().map(|b| b.to_owned()).collect())
     }
@@ -0,0 +1,43 @@
+/*
+ * tree/code_blob.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::clone::string_to_owned;
+use std::borrow::Cow;
+
+/// The body of a code block which is stored out-of-line.
+///
+/// The `blob` identifier names the page and a hash of the contents,
+/// so it is unique across pages, and is what the corresponding
+/// `Element::CodeBlob` refers to.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct CodeBlob<'t> {
+    pub blob: Cow<'t, str>,
+    pub contents: Cow<'t, str>,
+}
+
+impl CodeBlob<'_> {
+    pub fn to_owned(&self) -> CodeBlob<'static> {
+        CodeBlob {
+            blob: string_to_owned(&self.blob),
+            contents: string_to_owned(&self.contents),
+        }
+    }
+}
@@ -23,6 +23,7 @@
 use super::clone::elements_to_owned;
 use super::{Alignment, AttributeMap, Element, Heading, HtmlTag};
 use crate::next_index::{NextIndex, TableOfContentsIndex};
+use std::ops::Range;
 use strum_macros::IntoStaticStr;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -32,6 +33,12 @@ pub struct Container<'t> {
     ctype: ContainerType,
     attributes: AttributeMap<'t>,
     elements: Vec<Element<'t>>,
+
+    /// The byte range of the wikitext which produced this container.
+    ///
+    /// Only present if `record_spans` is enabled in the settings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    span: Option<Range<usize>>,
 }
 
 impl<'t> Container<'t> {
@@ -45,6 +52,7 @@ impl<'t> Container<'t> {
             ctype,
             attributes,
             elements,
+            span: None,
         }
     }
 
@@ -58,6 +66,11 @@ impl<'t> Container<'t> {
         &self.elements
     }
 
+    #[inline]
+    pub fn elements_mut(&mut self) -> &mut Vec<Element<'t>> {
+        &mut self.elements
+    }
+
     #[inline]
     pub fn attributes(&self) -> &AttributeMap<'t> {
         &self.attributes
@@ -68,11 +81,27 @@ impl<'t> Container<'t> {
         &mut self.attributes
     }
 
+    #[inline]
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.span.clone()
+    }
+
+    #[inline]
+    pub fn set_span(&mut self, span: Range<usize>) {
+        self.span = Some(span);
+    }
+
+    #[inline]
+    pub fn clear_span(&mut self) {
+        self.span = None;
+    }
+
     pub fn to_owned(&self) -> Container<'static> {
         Container {
             ctype: self.ctype,
             attributes: self.attributes.to_owned(),
             elements: elements_to_owned(&self.elements),
+            span: self.span.clone(),
         }
     }
 }
@@ -106,7 +135,6 @@ pub enum ContainerType {
     Deletion,
     Hidden,
     Invisible,
-    Size,
     Ruby,
     RubyText,
     Paragraph,
@@ -138,7 +166,6 @@ impl ContainerType {
             ContainerType::Deletion => HtmlTag::new("del"),
             ContainerType::Hidden => HtmlTag::with_class("span", "wj-hidden"),
             ContainerType::Invisible => HtmlTag::with_class("span", "wj-invisible"),
-            ContainerType::Size => HtmlTag::new("span"),
             ContainerType::Ruby => HtmlTag::new("ruby"),
             ContainerType::RubyText => HtmlTag::new("rt"),
             ContainerType::Paragraph => HtmlTag::new("p"),
@@ -170,7 +197,6 @@ impl ContainerType {
             ContainerType::Deletion => true,
             ContainerType::Hidden => true,
             ContainerType::Invisible => true,
-            ContainerType::Size => true,
             ContainerType::Ruby => true,
             ContainerType::RubyText => true,
             ContainerType::Paragraph => false,
@@ -18,8 +18,6 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use std::io;
-use time::format_description::well_known::Rfc2822;
 use time::{Date, OffsetDateTime, PrimitiveDateTime, UtcOffset};
 
 #[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
@@ -61,19 +59,136 @@ impl DateItem {
         }
     }
 
-    pub fn format(self) -> io::Result<String> {
-        use time::error::Format;
+    /// The format string used when none is specified.
+    ///
+    /// This only includes the parts of the date which were given,
+    /// and only includes the timezone if it is not UTC.
+    pub fn default_format(self) -> &'static str {
+        match self {
+            DateItem::Date(_) => "%B %d, %Y",
+            DateItem::DateTime(_) => "%B %d, %Y %H:%M:%S",
+            DateItem::DateTimeTz(datetime_tz) if datetime_tz.offset().is_utc() => {
+                "%B %d, %Y %H:%M:%S"
+            }
+            DateItem::DateTimeTz(_) => "%B %d, %Y %H:%M:%S %Z",
+        }
+    }
+
+    /// Formats this date using a `strftime`-style format string.
+    ///
+    /// Unknown specifiers are output as-is. Timezones are always
+    /// shown as offsets, since no timezone names are available.
+    pub fn format(self, format: &str) -> String {
+        let mut output = String::new();
+        write_format(&mut output, self.to_datetime_tz(), format);
+        output
+    }
+
+    /// Formats this date, using the default format if none is given.
+    ///
+    /// This is what renderers display for a `[[date]]` element.
+    #[inline]
+    pub fn format_or_default(self, format: Option<&str>) -> String {
+        self.format(format.unwrap_or_else(|| self.default_format()))
+    }
+
+    /// Formats this date as an ISO 8601 / RFC 3339 string.
+    ///
+    /// Dates without a timezone are treated as UTC.
+    #[inline]
+    pub fn to_iso(self) -> String {
+        self.format("%Y-%m-%dT%H:%M:%S%Z")
+    }
+}
 
-        let result = match self {
-            DateItem::Date(date) => date.format(&Rfc2822),
-            DateItem::DateTime(datetime) => datetime.format(&Rfc2822),
-            DateItem::DateTimeTz(datetime_tz) => datetime_tz.format(&Rfc2822),
+fn write_format(output: &mut String, datetime: OffsetDateTime, format: &str) {
+    let mut chars = format.chars();
+
+    while let Some(ch) = chars.next() {
+        if ch != '%' {
+            output.push(ch);
+            continue;
+        }
+
+        let specifier = match chars.next() {
+            Some(specifier) => specifier,
+            None => {
+                output.push('%');
+                break;
+            }
         };
 
-        result.map_err(|error| match error {
-            Format::StdIo(io_error) => io_error,
-            _ => io::Error::new(io::ErrorKind::Other, error),
-        })
+        match specifier {
+            // Date
+            'Y' => str_write!(output, "{}", datetime.year()),
+            'C' => str_write!(output, "{:02}", datetime.year().div_euclid(100)),
+            'y' => str_write!(output, "{:02}", datetime.year().rem_euclid(100)),
+            'm' => str_write!(output, "{:02}", u8::from(datetime.month())),
+            'B' => str_write!(output, "{}", datetime.month()),
+            'b' | 'h' => {
+                let month = datetime.month().to_string();
+                output.push_str(&month[..3]);
+            }
+            'd' => str_write!(output, "{:02}", datetime.day()),
+            'e' => str_write!(output, "{:>2}", datetime.day()),
+            'j' => str_write!(output, "{:03}", datetime.ordinal()),
+            'A' => str_write!(output, "{}", datetime.weekday()),
+            'a' => {
+                let weekday = datetime.weekday().to_string();
+                output.push_str(&weekday[..3]);
+            }
+            'w' => str_write!(output, "{}", datetime.weekday().number_days_from_sunday()),
+            'u' => str_write!(output, "{}", datetime.weekday().number_from_monday()),
+
+            // Time
+            'H' => str_write!(output, "{:02}", datetime.hour()),
+            'k' => str_write!(output, "{:>2}", datetime.hour()),
+            'I' => str_write!(output, "{:02}", hour_12(datetime)),
+            'l' => str_write!(output, "{:>2}", hour_12(datetime)),
+            'M' => str_write!(output, "{:02}", datetime.minute()),
+            'S' => str_write!(output, "{:02}", datetime.second()),
+            'p' => output.push_str(if datetime.hour() < 12 { "AM" } else { "PM" }),
+            'P' => output.push_str(if datetime.hour() < 12 { "am" } else { "pm" }),
+            's' => str_write!(output, "{}", datetime.unix_timestamp()),
+
+            // Timezone
+            'z' => write_offset(output, datetime.offset(), ""),
+            'Z' => write_offset(output, datetime.offset(), ":"),
+
+            // Combinations
+            'D' | 'x' => write_format(output, datetime, "%m/%d/%y"),
+            'F' => write_format(output, datetime, "%Y-%m-%d"),
+            'T' | 'X' => write_format(output, datetime, "%H:%M:%S"),
+            'R' => write_format(output, datetime, "%H:%M"),
+            'r' => write_format(output, datetime, "%I:%M:%S %p"),
+            'c' => write_format(output, datetime, "%a %b %e %H:%M:%S %Y"),
+
+            // Literals
+            'n' => output.push('\n'),
+            't' => output.push('\t'),
+            '%' => output.push('%'),
+
+            // Unknown, output as-is
+            _ => {
+                output.push('%');
+                output.push(specifier);
+            }
+        }
+    }
+}
+
+fn write_offset(output: &mut String, offset: UtcOffset, separator: &str) {
+    let sign = if offset.is_negative() { '-' } else { '+' };
+    let hours = offset.whole_hours().abs();
+    let minutes = offset.minutes_past_hour().abs();
+
+    str_write!(output, "{sign}{hours:02}{separator}{minutes:02}");
+}
+
+fn hour_12(datetime: OffsetDateTime) -> u8 {
+    match datetime.hour() % 12 {
+        0 => 12,
+        hour => hour,
     }
 }
 
@@ -115,3 +230,42 @@ cfg_if! {
         }
     }
 }
+
+#[test]
+fn format() {
+    use time::macros::{date, datetime};
+
+    let date = DateItem::from(date!(2008 - 07 - 09));
+    let datetime = DateItem::from(datetime!(2008-07-19 21:06:58));
+    let datetime_tz = DateItem::from(datetime!(2008-07-19 17:06:58 -04:30));
+
+    macro_rules! check {
+        ($date:expr, $format:expr, $expected:expr $(,)?) => {
+            assert_eq!(
+                $date.format($format),
+                $expected,
+                "Formatted date string doesn't match expected",
+            );
+        };
+    }
+
+    check!(date, date.default_format(), "July 09, 2008");
+    check!(
+        datetime,
+        datetime.default_format(),
+        "July 19, 2008 21:06:58"
+    );
+    check!(
+        datetime_tz,
+        datetime_tz.default_format(),
+        "July 19, 2008 17:06:58 -04:30",
+    );
+    check!(date, "%e %b %Y (%a, %A)", " 9 Jul 2008 (Wed, Wednesday)");
+    check!(datetime, "%I:%M %p / %l%P", "09:06 PM /  9pm");
+    check!(datetime, "%F %T, day %j", "2008-07-19 21:06:58, day 201");
+    check!(datetime_tz, "%z %s", "-0430 1216503418");
+    check!(datetime, "100%% %Q%", "100% %Q%");
+
+    assert_eq!(date.to_iso(), "2008-07-09T00:00:00+00:00");
+    assert_eq!(datetime_tz.to_iso(), "2008-07-19T17:06:58-04:30");
+}
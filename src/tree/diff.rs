@@ -0,0 +1,459 @@
+/*
+ * tree/diff.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Structural comparison of two syntax trees.
+//!
+//! Rather than a textual diff, this produces a list of edits to elements,
+//! such as a paragraph being changed or a collapsible being added.
+//! This is intended for history views, which can then present changes
+//! in terms of the page's structure.
+//!
+//! Only the body of each tree is compared. Spans are only recorded for
+//! containers, so only edits to containers can have one, see `record_spans`
+//! in the settings.
+
+use super::visit::{walk_element_mut, VisitorMut};
+use super::{Element, SyntaxTree};
+use std::ops::Range;
+
+/// The list of edits transforming one syntax tree into another.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct TreeDiff {
+    pub edits: Vec<TreeEdit>,
+}
+
+impl TreeDiff {
+    /// Whether the two trees were structurally identical.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+}
+
+/// A single change between two syntax trees.
+///
+/// Paths are the indices to follow to reach an element from the body,
+/// descending into containers. The element names are those of
+/// [`Element::name()`].
+///
+/// Spans are the source ranges of containers, if `record_spans` was enabled
+/// when parsing. For any other kind of element, they are always `None`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", tag = "edit", content = "data")]
+pub enum TreeEdit {
+    /// An element present only in the new tree.
+    #[serde(rename_all = "kebab-case")]
+    Inserted {
+        element: String,
+        path: Vec<usize>,
+        span: Option<Range<usize>>,
+    },
+
+    /// An element present only in the old tree.
+    #[serde(rename_all = "kebab-case")]
+    Removed {
+        element: String,
+        path: Vec<usize>,
+        span: Option<Range<usize>>,
+    },
+
+    /// An element of the same kind whose contents differ between the trees.
+    #[serde(rename_all = "kebab-case")]
+    Modified {
+        element: String,
+        old_path: Vec<usize>,
+        new_path: Vec<usize>,
+        old_span: Option<Range<usize>>,
+        new_span: Option<Range<usize>>,
+    },
+}
+
+/// Compares two syntax trees, producing the edits from `old` to `new`.
+///
+/// Unchanged elements are matched up first, then remaining elements
+/// of the same kind are paired as modifications. Containers of the same
+/// type and attributes are compared by their contents, so that changes
+/// are reported as deeply as possible.
+pub fn diff_trees(old: &SyntaxTree, new: &SyntaxTree) -> TreeDiff {
+    info!(
+        "Comparing syntax trees (old {} elements, new {} elements)",
+        old.elements.len(),
+        new.elements.len(),
+    );
+
+    // Spans change whenever anything earlier in the page does,
+    // so they are removed before checking for equality.
+    let old_bare = without_spans(&old.elements);
+    let new_bare = without_spans(&new.elements);
+
+    let mut diff = TreeDiff::default();
+    let mut ctx = DiffContext {
+        edits: &mut diff.edits,
+        old_path: Vec::new(),
+        new_path: Vec::new(),
+    };
+
+    ctx.diff_elements(
+        Side {
+            elements: &old.elements,
+            bare: &old_bare,
+        },
+        Side {
+            elements: &new.elements,
+            bare: &new_bare,
+        },
+    );
+
+    diff
+}
+
+/// A list of elements, along with the same list with spans removed.
+#[derive(Debug, Copy, Clone)]
+struct Side<'a, 't> {
+    elements: &'a [Element<'t>],
+    bare: &'a [Element<'t>],
+}
+
+#[derive(Debug)]
+struct DiffContext<'d> {
+    edits: &'d mut Vec<TreeEdit>,
+    old_path: Vec<usize>,
+    new_path: Vec<usize>,
+}
+
+impl DiffContext<'_> {
+    fn diff_elements(&mut self, old: Side, new: Side) {
+        let mut old_index = 0;
+        let mut new_index = 0;
+
+        // Walk through each run of unmatched elements between the common ones
+        for (old_match, new_match) in common_subsequence(old.bare, new.bare)
+            .into_iter()
+            .chain([(old.bare.len(), new.bare.len())])
+        {
+            self.diff_unmatched(old, new, old_index..old_match, new_index..new_match);
+            old_index = old_match + 1;
+            new_index = new_match + 1;
+        }
+    }
+
+    fn diff_unmatched(
+        &mut self,
+        old: Side,
+        new: Side,
+        mut old_range: Range<usize>,
+        new_range: Range<usize>,
+    ) {
+        for new_index in new_range {
+            let new_element = &new.elements[new_index];
+
+            // Pair with the next removed element of the same kind, if any
+            let paired = old_range
+                .clone()
+                .find(|&i| old.elements[i].name() == new_element.name());
+
+            match paired {
+                Some(old_index) => {
+                    for index in old_range.start..old_index {
+                        self.push_removed(&old.elements[index], index);
+                    }
+
+                    self.diff_pair(old, new, old_index, new_index);
+                    old_range.start = old_index + 1;
+                }
+                None => self.push_inserted(new_element, new_index),
+            }
+        }
+
+        for index in old_range {
+            self.push_removed(&old.elements[index], index);
+        }
+    }
+
+    fn diff_pair(&mut self, old: Side, new: Side, old_index: usize, new_index: usize) {
+        let old_element = &old.elements[old_index];
+        let new_element = &new.elements[new_index];
+
+        match (&old.bare[old_index], &new.bare[new_index]) {
+            // Same container, look for what changed inside
+            (Element::Container(old_bare), Element::Container(new_bare))
+                if old_bare.ctype() == new_bare.ctype()
+                    && old_bare.attributes() == new_bare.attributes() =>
+            {
+                let (old_elements, new_elements) = match (old_element, new_element) {
+                    (Element::Container(old), Element::Container(new)) => {
+                        (old.elements(), new.elements())
+                    }
+                    _ => unreachable!(),
+                };
+
+                self.old_path.push(old_index);
+                self.new_path.push(new_index);
+                self.diff_elements(
+                    Side {
+                        elements: old_elements,
+                        bare: old_bare.elements(),
+                    },
+                    Side {
+                        elements: new_elements,
+                        bare: new_bare.elements(),
+                    },
+                );
+                self.old_path.pop();
+                self.new_path.pop();
+            }
+
+            // Otherwise the element as a whole was changed
+            _ => self.edits.push(TreeEdit::Modified {
+                element: str!(new_element.name()),
+                old_path: path_with(&self.old_path, old_index),
+                new_path: path_with(&self.new_path, new_index),
+                old_span: container_span(old_element),
+                new_span: container_span(new_element),
+            }),
+        }
+    }
+
+    fn push_inserted(&mut self, element: &Element, index: usize) {
+        self.edits.push(TreeEdit::Inserted {
+            element: str!(element.name()),
+            path: path_with(&self.new_path, index),
+            span: container_span(element),
+        });
+    }
+
+    fn push_removed(&mut self, element: &Element, index: usize) {
+        self.edits.push(TreeEdit::Removed {
+            element: str!(element.name()),
+            path: path_with(&self.old_path, index),
+            span: container_span(element),
+        });
+    }
+}
+
+/// Gets the recorded span of this element, if it is a container.
+fn container_span(element: &Element) -> Option<Range<usize>> {
+    match element {
+        Element::Container(container) => container.span(),
+        _ => None,
+    }
+}
+
+fn path_with(path: &[usize], index: usize) -> Vec<usize> {
+    let mut path = path.to_vec();
+    path.push(index);
+    path
+}
+
+/// Finds the longest common subsequence of the two lists.
+///
+/// Returns the index pairs of each matched element, in order.
+fn common_subsequence(old: &[Element], new: &[Element]) -> Vec<(usize, usize)> {
+    // Skip over any common prefix and suffix, which is the typical case
+    let prefix = old
+        .iter()
+        .zip(new)
+        .take_while(|(old, new)| old == new)
+        .count();
+
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    // Standard dynamic programming table over the remainder,
+    // where lengths[i][j] is the length for old_middle[i..] and new_middle[j..].
+    let width = new_middle.len() + 1;
+    let mut lengths = vec![0_u32; (old_middle.len() + 1) * width];
+
+    for i in (0..old_middle.len()).rev() {
+        for j in (0..new_middle.len()).rev() {
+            lengths[i * width + j] = if old_middle[i] == new_middle[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut matches: Vec<_> = (0..prefix).map(|i| (i, i)).collect();
+    let (mut i, mut j) = (0, 0);
+
+    while i < old_middle.len() && j < new_middle.len() {
+        if old_middle[i] == new_middle[j] {
+            matches.push((prefix + i, prefix + j));
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+
+    let old_suffix = old.len() - suffix;
+    let new_suffix = new.len() - suffix;
+    matches.extend((0..suffix).map(|i| (old_suffix + i, new_suffix + i)));
+    matches
+}
+
+/// Clones the elements, removing all recorded spans.
+fn without_spans<'t>(elements: &[Element<'t>]) -> Vec<Element<'t>> {
+    struct ClearSpans;
+
+    impl<'t> VisitorMut<'t> for ClearSpans {
+        fn visit_element_mut(&mut self, element: &mut Element<'t>) {
+            if let Element::Container(container) = element {
+                container.clear_span();
+            }
+
+            walk_element_mut(self, element);
+        }
+    }
+
+    let mut elements = elements.to_vec();
+    ClearSpans.visit_elements_mut(&mut elements);
+    elements
+}
+
+#[test]
+fn diff() {
+    use crate::data::PageInfo;
+    use crate::settings::{WikitextMode, WikitextSettings};
+
+    let page_info = PageInfo::dummy();
+    let mut settings = WikitextSettings::from_mode(WikitextMode::Page);
+    settings.record_spans = true;
+
+    macro_rules! check {
+        ($old:expr, $new:expr, $edits:expr $(,)?) => {{
+            let old_tokens = crate::tokenize($old);
+            let new_tokens = crate::tokenize($new);
+            let (old_tree, _) = crate::parse(&old_tokens, &page_info, &settings).into();
+            let (new_tree, _) = crate::parse(&new_tokens, &page_info, &settings).into();
+            let diff = diff_trees(&old_tree, &new_tree);
+            let edits: Vec<TreeEdit> = $edits;
+
+            assert_eq!(
+                diff.edits, edits,
+                "Tree diff doesn't match for {:?} -> {:?}",
+                $old, $new,
+            );
+        }};
+    }
+
+    // Identical, despite span changes
+    check!("Apple\n\nBanana", "Apple\n\nBanana", vec![]);
+
+    // Paragraph added, shifting later spans
+    check!(
+        "Apple\n\nCherry",
+        "Apple\n\nBanana\n\nCherry",
+        vec![TreeEdit::Inserted {
+            element: str!("Paragraph"),
+            path: vec![1],
+            span: Some(7..13),
+        }],
+    );
+
+    // Paragraph removed
+    check!(
+        "Apple\n\nBanana\n\nCherry",
+        "Apple\n\nCherry",
+        vec![TreeEdit::Removed {
+            element: str!("Paragraph"),
+            path: vec![1],
+            span: Some(7..13),
+        }],
+    );
+
+    // Text changed within a paragraph
+    check!(
+        "Apple **banana** cherry",
+        "Apple **banana** durian",
+        vec![TreeEdit::Modified {
+            element: str!("Text"),
+            old_path: vec![0, 4],
+            new_path: vec![0, 4],
+            old_span: None,
+            new_span: None,
+        }],
+    );
+
+    // Collapsible added, and another changed
+    check!(
+        "[[collapsible]]\nApple\n[[/collapsible]]",
+        "[[collapsible show=\"Open\"]]\nApple\n[[/collapsible]]\n\
+         [[collapsible]]\nBanana\n[[/collapsible]]",
+        vec![
+            TreeEdit::Modified {
+                element: str!("Collapsible"),
+                old_path: vec![0],
+                new_path: vec![0],
+                old_span: None,
+                new_span: None,
+            },
+            TreeEdit::Inserted {
+                element: str!("Collapsible"),
+                path: vec![1],
+                span: None,
+            },
+        ],
+    );
+
+    // Container attributes changed
+    check!(
+        "[[div class=\"a\"]]\nApple\n[[/div]]",
+        "[[div class=\"b\"]]\nApple\n[[/div]]",
+        vec![TreeEdit::Modified {
+            element: str!("Div"),
+            old_path: vec![0],
+            new_path: vec![0],
+            old_span: Some(0..32),
+            new_span: Some(0..32),
+        }],
+    );
+}
+
+#[test]
+fn serialize() {
+    let diff = TreeDiff {
+        edits: vec![TreeEdit::Inserted {
+            element: str!("Paragraph"),
+            path: vec![1, 0],
+            span: Some(4..9),
+        }],
+    };
+
+    let json = serde_json::to_string(&diff).unwrap();
+    assert_eq!(
+        json,
+        r#"{"edits":[{"edit":"inserted","data":{"element":"Paragraph","path":[1,0],"span":{"start":4,"end":9}}}]}"#,
+    );
+
+    let parsed: TreeDiff = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, diff);
+}
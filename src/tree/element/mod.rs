@@ -22,8 +22,10 @@ mod collection;
 mod iter_owned;
 mod iter_ref;
 mod object;
+mod walk;
 
 pub use self::collection::Elements;
 pub use self::iter_owned::*;
 pub use self::iter_ref::*;
 pub use self::object::Element;
+pub use self::walk::walk_elements;
@@ -22,12 +22,13 @@ use crate::data::PageRef;
 use crate::tree::clone::*;
 use crate::tree::{
     Alignment, AnchorTarget, AttributeMap, ClearFloat, Container, DateItem,
-    DefinitionListItem, Embed, FloatAlignment, ImageSource, LinkLabel, LinkLocation,
-    LinkType, ListItem, ListType, Module, PartialElement, Tab, Table, VariableMap,
+    DefinitionListItem, Embed, FloatAlignment, GalleryImage, GalleryOrder, GallerySize,
+    ImageSource, LinkLabel, LinkLocation, LinkType, ListItem, ListType, MediaFlags,
+    MediaType, Module, PartialElement, Tab, Table, VariableMap,
 };
 use ref_map::*;
 use std::borrow::Cow;
-use std::num::NonZeroU32;
+use std::num::{NonZeroU32, NonZeroUsize};
 
 /// Represents an element to be rendered.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -57,6 +58,13 @@ pub enum Element<'t> {
     /// produce a `&nbsp;`.
     Raw(Cow<'t, str>),
 
+    /// An HTML entity reference, such as `&mdash;`.
+    ///
+    /// This contains the name of the entity, without the `&` and `;`.
+    /// Unlike raw text, it is emitted as a reference in HTML rather than
+    /// being escaped, so a renderer should check that it is valid first.
+    HtmlEntity(Cow<'t, str>),
+
     /// A wikitext variable.
     ///
     /// During rendering, this will be replaced with its actual value,
@@ -119,6 +127,28 @@ pub enum Element<'t> {
         attributes: AttributeMap<'t>,
     },
 
+    /// An audio or video file, with its playback options.
+    ///
+    /// From `[[audio]]` and `[[video]]`. The sources are alternative
+    /// formats of the same file, in order of preference.
+    #[serde(rename_all = "kebab-case")]
+    Media {
+        media_type: MediaType,
+        sources: Vec<ImageSource<'t>>,
+        caption: Option<Cow<'t, str>>,
+        flags: MediaFlags,
+        attributes: AttributeMap<'t>,
+    },
+
+    /// A set of images displayed together as a grid.
+    ///
+    /// From `[[gallery]]`, with each image given on its own line.
+    Gallery {
+        images: Vec<GalleryImage<'t>>,
+        size: GallerySize,
+        order: Option<GalleryOrder>,
+    },
+
     /// An ordered or unordered list.
     List {
         #[serde(rename = "type")]
@@ -134,12 +164,26 @@ pub enum Element<'t> {
     ///
     /// The "name" field translates to HTML, but is standard for grouping them.
     /// The "checked" field determines if the radio button starts checked or not.
+    /// The "label" field is a caption for its group, and is moved to the
+    /// `RadioGroup` containing this button after parsing.
     RadioButton {
         name: Cow<'t, str>,
         checked: bool,
+        label: Option<Cow<'t, str>>,
         attributes: AttributeMap<'t>,
     },
 
+    /// A group of consecutive radio buttons sharing the same name.
+    ///
+    /// This contains the buttons along with the elements between them,
+    /// such as their labels. If a label is given, it is shown as a
+    /// caption for the group.
+    RadioGroup {
+        name: Cow<'t, str>,
+        label: Option<Cow<'t, str>>,
+        elements: Vec<Element<'t>>,
+    },
+
     /// A checkbox.
     ///
     /// The "checked" field determines if the radio button starts checked or not.
@@ -162,14 +206,30 @@ pub enum Element<'t> {
         hide_text: Option<Cow<'t, str>>,
         show_top: bool,
         show_bottom: bool,
+
+        /// Whether this should be opened when linked to an anchor within it.
+        open_if_anchored: bool,
+    },
+
+    /// A quoted reply, such as in a forum post.
+    ///
+    /// The attribution is the name of the person being quoted, if given.
+    Quote {
+        attribution: Option<Cow<'t, str>>,
+        elements: Vec<Element<'t>>,
     },
 
     /// A table of contents block.
     ///
     /// This contains links to sub-headings on the page.
+    ///
+    /// The depths are heading levels, restricting which headings are listed.
+    #[serde(rename_all = "kebab-case")]
     TableOfContents {
         attributes: AttributeMap<'t>,
         align: Option<Alignment>,
+        min_depth: Option<u8>,
+        max_depth: Option<u8>,
     },
 
     /// A footnote reference.
@@ -181,6 +241,13 @@ pub enum Element<'t> {
     /// It is indirectly preserved as the index of the `footnotes` list in the syntax tree.
     Footnote,
 
+    /// A repeated reference to an earlier named footnote.
+    ///
+    /// This is produced by `[[footnote name="..."]]` when a footnote with
+    /// that name already exists. The value is the one-indexed position
+    /// of that footnote in the `footnotes` list in the syntax tree.
+    FootnoteRepeat(NonZeroUsize),
+
     /// A footnote block, containing all the footnotes from throughout the page.
     ///
     /// If a `[[footnoteblock]]` is not added somewhere in the content of the page,
@@ -227,12 +294,31 @@ pub enum Element<'t> {
         elements: Vec<Element<'t>>,
     },
 
+    /// Element containing resized text.
+    ///
+    /// The size is as written, such as `150%`. It is checked against the
+    /// [`SizeLimits`](crate::settings::SizeLimits) when rendering.
+    Size {
+        size: Cow<'t, str>,
+        elements: Vec<Element<'t>>,
+    },
+
     /// Element containing a code block.
     Code {
         contents: Cow<'t, str>,
         language: Option<Cow<'t, str>>,
     },
 
+    /// Element containing a code block whose body is stored out-of-line.
+    ///
+    /// The `blob` is the identifier of the body in the tree's `code_blobs`,
+    /// and `length` is the size of the stored body in bytes.
+    CodeBlob {
+        blob: Cow<'t, str>,
+        length: usize,
+        language: Option<Cow<'t, str>>,
+    },
+
     /// Element containing a named math equation.
     #[serde(rename_all = "kebab-case")]
     Math {
@@ -244,6 +330,11 @@ pub enum Element<'t> {
     #[serde(rename_all = "kebab-case")]
     MathInline { latex_source: Cow<'t, str> },
 
+    /// Element containing an expression to be evaluated at render time.
+    ///
+    /// From `[[calc]]`.
+    Calc { expression: Cow<'t, str> },
+
     /// Element referring to an equation elsewhere in the page.
     EquationReference(Cow<'t, str>),
 
@@ -275,6 +366,12 @@ pub enum Element<'t> {
     /// Corresponds with a `<style>` entity in the body of the HTML.
     Style(Cow<'t, str>),
 
+    /// A redirect from this page to another location.
+    ///
+    /// This comes from `[[module Redirect]]`. How it is rendered
+    /// depends on the [`RedirectMode`](crate::settings::RedirectMode).
+    Redirect { destination: LinkLocation<'t> },
+
     /// A newline or line break.
     ///
     /// This calls for a newline in the final output, such as `<br>` in HTML.
@@ -298,7 +395,7 @@ pub enum Element<'t> {
     Partial(PartialElement<'t>),
 }
 
-impl Element<'_> {
+impl<'t> Element<'t> {
     /// Determines if the element is "unintentional whitespace".
     ///
     /// Specifically, it returns true if the element is:
@@ -322,6 +419,7 @@ impl Element<'_> {
             Element::Module(module) => module.name(),
             Element::Text(_) => "Text",
             Element::Raw(_) => "Raw",
+            Element::HtmlEntity(_) => "HtmlEntity",
             Element::Variable(_) => "Variable",
             Element::Email(_) => "Email",
             Element::Table(_) => "Table",
@@ -330,28 +428,37 @@ impl Element<'_> {
             Element::AnchorName(_) => "AnchorName",
             Element::Link { .. } => "Link",
             Element::Image { .. } => "Image",
+            Element::Media { .. } => "Media",
+            Element::Gallery { .. } => "Gallery",
             Element::List { .. } => "List",
             Element::DefinitionList(_) => "DefinitionList",
             Element::RadioButton { .. } => "RadioButton",
+            Element::RadioGroup { .. } => "RadioGroup",
             Element::CheckBox { .. } => "CheckBox",
             Element::Collapsible { .. } => "Collapsible",
+            Element::Quote { .. } => "Quote",
             Element::TableOfContents { .. } => "TableOfContents",
             Element::Footnote => "Footnote",
+            Element::FootnoteRepeat(_) => "FootnoteRepeat",
             Element::FootnoteBlock { .. } => "FootnoteBlock",
             Element::BibliographyCite { .. } => "BibliographyCite",
             Element::BibliographyBlock { .. } => "BibliographyBlock",
             Element::User { .. } => "User",
             Element::Date { .. } => "Date",
             Element::Color { .. } => "Color",
+            Element::Size { .. } => "Size",
             Element::Code { .. } => "Code",
+            Element::CodeBlob { .. } => "CodeBlob",
             Element::Math { .. } => "Math",
             Element::MathInline { .. } => "MathInline",
+            Element::Calc { .. } => "Calc",
             Element::EquationReference(_) => "EquationReference",
             Element::Embed(_) => "Embed",
             Element::Html { .. } => "HTML",
             Element::Iframe { .. } => "Iframe",
             Element::Include { .. } => "Include",
             Element::Style(_) => "Style",
+            Element::Redirect { .. } => "Redirect",
             Element::LineBreak => "LineBreak",
             Element::LineBreaks { .. } => "LineBreaks",
             Element::ClearFloat(_) => "ClearFloat",
@@ -360,6 +467,24 @@ impl Element<'_> {
         }
     }
 
+    /// Returns the HTML attributes of this element, if it has any.
+    pub fn attributes(&self) -> Option<&AttributeMap<'t>> {
+        match self {
+            Element::Container(container) => Some(container.attributes()),
+            Element::Table(table) => Some(&table.attributes),
+            Element::Anchor { attributes, .. }
+            | Element::Image { attributes, .. }
+            | Element::Media { attributes, .. }
+            | Element::List { attributes, .. }
+            | Element::RadioButton { attributes, .. }
+            | Element::CheckBox { attributes, .. }
+            | Element::Collapsible { attributes, .. }
+            | Element::TableOfContents { attributes, .. }
+            | Element::Iframe { attributes, .. } => Some(attributes),
+            _ => None,
+        }
+    }
+
     /// Determines if this element type is able to be embedded in a paragraph.
     ///
     /// It does *not* look into the interiors of the element, it only does a
@@ -375,6 +500,7 @@ impl Element<'_> {
             Element::Module(_) => false,
             Element::Text(_)
             | Element::Raw(_)
+            | Element::HtmlEntity(_)
             | Element::Variable(_)
             | Element::Email(_) => true,
             Element::Table(_) => false,
@@ -383,26 +509,35 @@ impl Element<'_> {
                 true
             }
             Element::Image { .. } => true,
+            Element::Media { .. } => false,
+            Element::Gallery { .. } => false,
             Element::List { .. } => false,
             Element::DefinitionList(_) => false,
             Element::RadioButton { .. } | Element::CheckBox { .. } => true,
+            Element::RadioGroup { label, .. } => label.is_none(),
             Element::Collapsible { .. } => false,
+            Element::Quote { .. } => false,
             Element::TableOfContents { .. } => false,
             Element::Footnote => true,
+            Element::FootnoteRepeat(_) => true,
             Element::FootnoteBlock { .. } => false,
             Element::BibliographyCite { .. } => true,
             Element::BibliographyBlock { .. } => false,
             Element::User { .. } => true,
             Element::Date { .. } => true,
             Element::Color { .. } => true,
+            Element::Size { .. } => true,
             Element::Code { .. } => false,
+            Element::CodeBlob { .. } => false,
             Element::Math { .. } => false,
             Element::MathInline { .. } => true,
+            Element::Calc { .. } => true,
             Element::EquationReference(_) => true,
             Element::Embed(_) => false,
             Element::Html { .. } | Element::Iframe { .. } => false,
             Element::Include { paragraph_safe, .. } => *paragraph_safe,
             Element::Style(_) => false,
+            Element::Redirect { .. } => false,
             Element::LineBreak | Element::LineBreaks { .. } => true,
             Element::ClearFloat(_) => false,
             Element::HorizontalRule => false,
@@ -423,6 +558,7 @@ impl Element<'_> {
             Element::Module(module) => Element::Module(module.to_owned()),
             Element::Text(text) => Element::Text(string_to_owned(text)),
             Element::Raw(text) => Element::Raw(string_to_owned(text)),
+            Element::HtmlEntity(name) => Element::HtmlEntity(string_to_owned(name)),
             Element::Variable(name) => Element::Variable(string_to_owned(name)),
             Element::Email(email) => Element::Email(string_to_owned(email)),
             Element::Table(table) => Element::Table(table.to_owned()),
@@ -470,18 +606,51 @@ impl Element<'_> {
                 alignment: *alignment,
                 attributes: attributes.to_owned(),
             },
+            Element::Media {
+                media_type,
+                sources,
+                caption,
+                flags,
+                attributes,
+            } => Element::Media {
+                media_type: *media_type,
+                sources: sources.iter().map(|source| source.to_owned()).collect(),
+                caption: option_string_to_owned(caption),
+                flags: *flags,
+                attributes: attributes.to_owned(),
+            },
+            Element::Gallery {
+                images,
+                size,
+                order,
+            } => Element::Gallery {
+                images: images.iter().map(|image| image.to_owned()).collect(),
+                size: *size,
+                order: *order,
+            },
             Element::DefinitionList(items) => Element::DefinitionList(
                 items.iter().map(|item| item.to_owned()).collect(),
             ),
             Element::RadioButton {
                 name,
                 checked,
+                label,
                 attributes,
             } => Element::RadioButton {
                 name: string_to_owned(name),
                 checked: *checked,
+                label: option_string_to_owned(label),
                 attributes: attributes.to_owned(),
             },
+            Element::RadioGroup {
+                name,
+                label,
+                elements,
+            } => Element::RadioGroup {
+                name: string_to_owned(name),
+                label: option_string_to_owned(label),
+                elements: elements_to_owned(elements),
+            },
             Element::CheckBox {
                 checked,
                 attributes,
@@ -497,6 +666,7 @@ impl Element<'_> {
                 hide_text,
                 show_top,
                 show_bottom,
+                open_if_anchored,
             } => Element::Collapsible {
                 elements: elements_to_owned(elements),
                 attributes: attributes.to_owned(),
@@ -505,12 +675,28 @@ impl Element<'_> {
                 hide_text: option_string_to_owned(hide_text),
                 show_top: *show_top,
                 show_bottom: *show_bottom,
+                open_if_anchored: *open_if_anchored,
+            },
+            Element::Quote {
+                attribution,
+                elements,
+            } => Element::Quote {
+                attribution: option_string_to_owned(attribution),
+                elements: elements_to_owned(elements),
             },
-            Element::TableOfContents { align, attributes } => Element::TableOfContents {
+            Element::TableOfContents {
+                align,
+                attributes,
+                min_depth,
+                max_depth,
+            } => Element::TableOfContents {
                 align: *align,
                 attributes: attributes.to_owned(),
+                min_depth: *min_depth,
+                max_depth: *max_depth,
             },
             Element::Footnote => Element::Footnote,
+            Element::FootnoteRepeat(index) => Element::FootnoteRepeat(*index),
             Element::FootnoteBlock { title, hide } => Element::FootnoteBlock {
                 title: option_string_to_owned(title),
                 hide: *hide,
@@ -543,10 +729,23 @@ impl Element<'_> {
                 color: string_to_owned(color),
                 elements: elements_to_owned(elements),
             },
+            Element::Size { size, elements } => Element::Size {
+                size: string_to_owned(size),
+                elements: elements_to_owned(elements),
+            },
             Element::Code { contents, language } => Element::Code {
                 contents: string_to_owned(contents),
                 language: option_string_to_owned(language),
             },
+            Element::CodeBlob {
+                blob,
+                length,
+                language,
+            } => Element::CodeBlob {
+                blob: string_to_owned(blob),
+                length: *length,
+                language: option_string_to_owned(language),
+            },
             Element::Math { name, latex_source } => Element::Math {
                 name: option_string_to_owned(name),
                 latex_source: string_to_owned(latex_source),
@@ -554,6 +753,9 @@ impl Element<'_> {
             Element::MathInline { latex_source } => Element::MathInline {
                 latex_source: string_to_owned(latex_source),
             },
+            Element::Calc { expression } => Element::Calc {
+                expression: string_to_owned(expression),
+            },
             Element::EquationReference(name) => {
                 Element::EquationReference(string_to_owned(name))
             }
@@ -577,6 +779,9 @@ impl Element<'_> {
                 elements: elements_to_owned(elements),
             },
             Element::Style(css) => Element::Style(string_to_owned(css)),
+            Element::Redirect { destination } => Element::Redirect {
+                destination: destination.to_owned(),
+            },
             Element::LineBreak => Element::LineBreak,
             Element::LineBreaks(amount) => Element::LineBreaks(*amount),
             Element::ClearFloat(clear_float) => Element::ClearFloat(*clear_float),
@@ -0,0 +1,304 @@
+/*
+ * tree/element/walk.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Recursive traversal over elements and their children.
+
+use super::Element;
+use crate::tree::{DefinitionListItem, ListItem, PartialElement, SyntaxTree};
+
+impl<'t> Element<'t> {
+    /// Calls the given function on each direct child of this element.
+    ///
+    /// This does not recurse, see [`walk_elements`] for that.
+    pub fn for_each_child<'e, F>(&'e self, mut f: F)
+    where
+        F: FnMut(&'e Element<'t>),
+    {
+        let mut each = |elements: &'e [Element<'t>]| elements.iter().for_each(&mut f);
+
+        match self {
+            Element::Container(container) => each(container.elements()),
+            Element::Table(table) => {
+                for row in &table.rows {
+                    for cell in &row.cells {
+                        each(&cell.elements);
+                    }
+                }
+            }
+            Element::TabView(tabs) => {
+                for tab in tabs {
+                    each(&tab.elements);
+                }
+            }
+            Element::Anchor { elements, .. }
+            | Element::Collapsible { elements, .. }
+            | Element::Quote { elements, .. }
+            | Element::RadioGroup { elements, .. }
+            | Element::Color { elements, .. }
+            | Element::Size { elements, .. }
+            | Element::Include { elements, .. } => each(elements),
+            Element::List { items, .. } => {
+                for item in items {
+                    match item {
+                        ListItem::Elements { elements, .. } => each(elements),
+                        ListItem::SubList { element } => {
+                            each(std::slice::from_ref(element))
+                        }
+                    }
+                }
+            }
+            Element::DefinitionList(items) => {
+                for DefinitionListItem {
+                    key_elements,
+                    value_elements,
+                    ..
+                } in items
+                {
+                    each(key_elements);
+                    each(value_elements);
+                }
+            }
+            Element::Partial(partial) => match partial {
+                PartialElement::ListItem(ListItem::Elements { elements, .. }) => {
+                    each(elements)
+                }
+                PartialElement::ListItem(ListItem::SubList { element }) => {
+                    each(std::slice::from_ref(element))
+                }
+                PartialElement::TableRow(row) => {
+                    for cell in &row.cells {
+                        each(&cell.elements);
+                    }
+                }
+                PartialElement::TableCell(cell) => each(&cell.elements),
+                PartialElement::Tab(tab) => each(&tab.elements),
+                PartialElement::RubyText(ruby) => each(&ruby.elements),
+            },
+            // No child elements
+            Element::Module(_)
+            | Element::Text(_)
+            | Element::Raw(_)
+            | Element::HtmlEntity(_)
+            | Element::Variable(_)
+            | Element::Email(_)
+            | Element::AnchorName(_)
+            | Element::Link { .. }
+            | Element::Image { .. }
+            | Element::Media { .. }
+            | Element::Gallery { .. }
+            | Element::RadioButton { .. }
+            | Element::CheckBox { .. }
+            | Element::TableOfContents { .. }
+            | Element::Footnote
+            | Element::FootnoteRepeat(_)
+            | Element::FootnoteBlock { .. }
+            | Element::BibliographyCite { .. }
+            | Element::BibliographyBlock { .. }
+            | Element::User { .. }
+            | Element::Date { .. }
+            | Element::Code { .. }
+            | Element::CodeBlob { .. }
+            | Element::Math { .. }
+            | Element::MathInline { .. }
+            | Element::Calc { .. }
+            | Element::EquationReference(_)
+            | Element::Embed(_)
+            | Element::Html { .. }
+            | Element::Iframe { .. }
+            | Element::Style(_)
+            | Element::Redirect { .. }
+            | Element::LineBreak
+            | Element::LineBreaks(_)
+            | Element::ClearFloat(_)
+            | Element::HorizontalRule => (),
+        }
+    }
+
+    /// Calls the given function on each direct child of this element, mutably.
+    ///
+    /// This visits children in the same order as [`Element::for_each_child`].
+    pub fn for_each_child_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut Element<'t>),
+    {
+        let mut each =
+            |elements: &mut [Element<'t>]| elements.iter_mut().for_each(&mut f);
+
+        match self {
+            Element::Container(container) => each(container.elements_mut()),
+            Element::Table(table) => {
+                for row in &mut table.rows {
+                    for cell in &mut row.cells {
+                        each(&mut cell.elements);
+                    }
+                }
+            }
+            Element::TabView(tabs) => {
+                for tab in tabs {
+                    each(&mut tab.elements);
+                }
+            }
+            Element::Anchor { elements, .. }
+            | Element::Collapsible { elements, .. }
+            | Element::Quote { elements, .. }
+            | Element::RadioGroup { elements, .. }
+            | Element::Color { elements, .. }
+            | Element::Size { elements, .. }
+            | Element::Include { elements, .. } => each(elements),
+            Element::List { items, .. } => {
+                for item in items {
+                    match item {
+                        ListItem::Elements { elements, .. } => each(elements),
+                        ListItem::SubList { element } => {
+                            each(std::slice::from_mut(element))
+                        }
+                    }
+                }
+            }
+            Element::DefinitionList(items) => {
+                for DefinitionListItem {
+                    key_elements,
+                    value_elements,
+                    ..
+                } in items
+                {
+                    each(key_elements);
+                    each(value_elements);
+                }
+            }
+            Element::Partial(partial) => match partial {
+                PartialElement::ListItem(ListItem::Elements { elements, .. }) => {
+                    each(elements)
+                }
+                PartialElement::ListItem(ListItem::SubList { element }) => {
+                    each(std::slice::from_mut(element))
+                }
+                PartialElement::TableRow(row) => {
+                    for cell in &mut row.cells {
+                        each(&mut cell.elements);
+                    }
+                }
+                PartialElement::TableCell(cell) => each(&mut cell.elements),
+                PartialElement::Tab(tab) => each(&mut tab.elements),
+                PartialElement::RubyText(ruby) => each(&mut ruby.elements),
+            },
+            // No child elements
+            Element::Module(_)
+            | Element::Text(_)
+            | Element::Raw(_)
+            | Element::HtmlEntity(_)
+            | Element::Variable(_)
+            | Element::Email(_)
+            | Element::AnchorName(_)
+            | Element::Link { .. }
+            | Element::Image { .. }
+            | Element::Media { .. }
+            | Element::Gallery { .. }
+            | Element::RadioButton { .. }
+            | Element::CheckBox { .. }
+            | Element::TableOfContents { .. }
+            | Element::Footnote
+            | Element::FootnoteRepeat(_)
+            | Element::FootnoteBlock { .. }
+            | Element::BibliographyCite { .. }
+            | Element::BibliographyBlock { .. }
+            | Element::User { .. }
+            | Element::Date { .. }
+            | Element::Code { .. }
+            | Element::CodeBlob { .. }
+            | Element::Math { .. }
+            | Element::MathInline { .. }
+            | Element::Calc { .. }
+            | Element::EquationReference(_)
+            | Element::Embed(_)
+            | Element::Html { .. }
+            | Element::Iframe { .. }
+            | Element::Style(_)
+            | Element::Redirect { .. }
+            | Element::LineBreak
+            | Element::LineBreaks(_)
+            | Element::ClearFloat(_)
+            | Element::HorizontalRule => (),
+        }
+    }
+}
+
+/// Calls the given function on each element, in document order.
+///
+/// Each element is visited before its children.
+pub fn walk_elements<'e, 't, F>(elements: &'e [Element<'t>], f: &mut F)
+where
+    F: FnMut(&'e Element<'t>),
+{
+    for element in elements {
+        walk_element(element, f);
+    }
+}
+
+fn walk_element<'e, 't, F>(element: &'e Element<'t>, f: &mut F)
+where
+    F: FnMut(&'e Element<'t>),
+{
+    f(element);
+    element.for_each_child(|child| walk_element(child, f));
+}
+
+impl<'t> SyntaxTree<'t> {
+    /// Calls the given function on each element in this tree.
+    ///
+    /// This visits the body, then the contents of each footnote,
+    /// then the contents of each bibliography entry.
+    /// The generated table of contents is not included.
+    pub fn walk<'e, F>(&'e self, mut f: F)
+    where
+        F: FnMut(&'e Element<'t>),
+    {
+        walk_elements(&self.elements, &mut f);
+
+        for footnote in &self.footnotes {
+            walk_elements(footnote, &mut f);
+        }
+
+        for bibliography in self.bibliographies.iter() {
+            for (_, elements) in bibliography.slice() {
+                walk_elements(elements, &mut f);
+            }
+        }
+    }
+}
+
+#[test]
+fn walk() {
+    use crate::tree::{Container, ContainerType};
+
+    let elements = vec![
+        text!("a"),
+        Element::Container(Container::new(
+            ContainerType::Bold,
+            vec![text!("b"), Element::LineBreak],
+            Default::default(),
+        )),
+        text!("c"),
+    ];
+
+    let mut names = Vec::new();
+    walk_elements(&elements, &mut |element| names.push(element.name()));
+    assert_eq!(names, vec!["Text", "Bold", "Text", "LineBreak", "Text"]);
+}
@@ -19,7 +19,9 @@
  */
 
 use super::clone::string_to_owned;
+use crate::settings::EmbedProviders;
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case", tag = "embed", content = "data")]
@@ -37,6 +39,15 @@ pub enum Embed<'t> {
 
     #[serde(rename_all = "kebab-case")]
     GitlabSnippet { snippet_id: Cow<'t, str> },
+
+    /// An embed from a provider registered in the settings.
+    ///
+    /// See [`EmbedProviders`].
+    Provider {
+        provider: Cow<'t, str>,
+        id: Cow<'t, str>,
+        params: BTreeMap<Cow<'t, str>, Cow<'t, str>>,
+    },
 }
 
 impl Embed<'_> {
@@ -46,11 +57,16 @@ impl Embed<'_> {
             Embed::Vimeo { .. } => "Vimeo",
             Embed::GithubGist { .. } => "GithubGist",
             Embed::GitlabSnippet { .. } => "GitlabSnippet",
+            Embed::Provider { .. } => "Provider",
         }
     }
 
-    pub fn direct_url(&self) -> String {
-        match self {
+    /// Returns a URL to view the embedded content directly.
+    ///
+    /// This is only `None` for provider embeds whose provider
+    /// is not in the given registry.
+    pub fn direct_url(&self, providers: &EmbedProviders) -> Option<String> {
+        let url = match self {
             Embed::Youtube { video_id } => format!("https://youtu.be/{video_id}"),
             Embed::Vimeo { video_id } => format!("https://vimeo.com/{video_id}"),
             Embed::GithubGist { username, hash } => {
@@ -59,7 +75,14 @@ impl Embed<'_> {
             Embed::GitlabSnippet { snippet_id } => {
                 format!("https://gitlab.com/-/snippets/{snippet_id}")
             }
-        }
+            Embed::Provider {
+                provider,
+                id,
+                params,
+            } => providers.get(provider)?.build_url(id, params),
+        };
+
+        Some(url)
     }
 
     pub fn to_owned(&self) -> Embed<'static> {
@@ -80,6 +103,19 @@ impl Embed<'_> {
             Embed::GitlabSnippet { snippet_id } => Embed::GitlabSnippet {
                 snippet_id: string_to_owned(snippet_id),
             },
+
+            Embed::Provider {
+                provider,
+                id,
+                params,
+            } => Embed::Provider {
+                provider: string_to_owned(provider),
+                id: string_to_owned(id),
+                params: params
+                    .iter()
+                    .map(|(key, value)| (string_to_owned(key), string_to_owned(value)))
+                    .collect(),
+            },
         }
     }
 }
@@ -0,0 +1,212 @@
+/*
+ * tree/excerpt.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Shortened versions of a page, for previews such as in page listings.
+//!
+//! Excerpts are produced from the syntax tree rather than by truncating
+//! rendered output, so the result is always well-formed.
+
+use super::visit::transform;
+use super::{ContainerType, Element, Elements, LinkLabel, LinkLocation, SyntaxTree};
+use std::borrow::Cow;
+
+impl<'t> SyntaxTree<'t> {
+    /// Returns the leading elements of this page, for use as a preview.
+    ///
+    /// The excerpt ends after the first paragraph, or once `max_len`
+    /// characters of visible text have been included, whichever comes first.
+    /// Text cut off partway is shortened to the last whole word, and
+    /// followed by an ellipsis.
+    ///
+    /// Elements which only make sense as part of the full page, such as
+    /// the table of contents, footnotes, and modules, are removed.
+    pub fn excerpt(&self, max_len: usize) -> Vec<Element<'t>> {
+        let mut elements = self.elements.clone();
+        let mut excerpt = Excerpt {
+            remaining: max_len,
+            done: max_len == 0,
+        };
+
+        transform(&mut elements, |element| excerpt.process(element));
+        elements
+    }
+}
+
+#[derive(Debug)]
+struct Excerpt {
+    /// How many more characters of visible text may be included.
+    remaining: usize,
+
+    /// Whether the end of the excerpt has been reached.
+    ///
+    /// Since elements are transformed bottom-up, any element with children
+    /// remaining at this point contains the end, and so is kept.
+    done: bool,
+}
+
+impl Excerpt {
+    fn process<'t>(&mut self, element: Element<'t>) -> Elements<'t> {
+        match element {
+            // Elements which don't belong in a preview
+            Element::TableOfContents { .. }
+            | Element::Footnote
+            | Element::FootnoteRepeat(_)
+            | Element::FootnoteBlock { .. }
+            | Element::BibliographyBlock { .. }
+            | Element::Module(_)
+            | Element::Style(_)
+            | Element::Redirect { .. } => Elements::None,
+
+            // Visible text, which may be shortened
+            Element::Text(text) if !self.done => {
+                Elements::Single(Element::Text(self.take_text(text)))
+            }
+            Element::Raw(text) if !self.done => {
+                Elements::Single(Element::Raw(self.take_text(text)))
+            }
+
+            // Visible text, which is kept whole
+            Element::Email(ref text) if !self.done => {
+                self.take_whole(text);
+                Elements::Single(element)
+            }
+            Element::Link {
+                ref link,
+                ref label,
+                ..
+            } if !self.done => {
+                match (label, link) {
+                    (LinkLabel::Text(text), _) | (LinkLabel::Url(Some(text)), _) => {
+                        self.take_whole(text);
+                    }
+                    (LinkLabel::Url(None), LinkLocation::Url(url)) => {
+                        self.take_whole(url)
+                    }
+                    _ => (),
+                }
+
+                Elements::Single(element)
+            }
+
+            // Only the first paragraph is included
+            Element::Container(ref container)
+                if container.ctype() == ContainerType::Paragraph && !self.done =>
+            {
+                self.done = true;
+                Elements::Single(element)
+            }
+
+            // Past the end of the excerpt, keeping only the
+            // elements containing where it was cut off
+            _ if self.done => {
+                let mut has_children = false;
+                element.for_each_child(|_| has_children = true);
+
+                if has_children {
+                    Elements::Single(element)
+                } else {
+                    Elements::None
+                }
+            }
+
+            _ => Elements::Single(element),
+        }
+    }
+
+    fn take_text<'t>(&mut self, text: Cow<'t, str>) -> Cow<'t, str> {
+        let length = text.chars().count();
+
+        // Whitespace reaching the limit ends the excerpt,
+        // so it doesn't end with a space and a lone ellipsis.
+        if length >= self.remaining && text.trim().is_empty() {
+            self.remaining = 0;
+            self.done = true;
+            return cow!("…");
+        }
+
+        if length <= self.remaining {
+            self.remaining -= length;
+            return text;
+        }
+
+        // Cut at the limit, then back to the last whole word, if there is one
+        let index = text
+            .char_indices()
+            .nth(self.remaining)
+            .map_or(text.len(), |(index, _)| index);
+
+        let cut = &text[..index];
+        let shortened = match cut.rfind(char::is_whitespace) {
+            Some(space) if !cut[..space].trim_end().is_empty() => &cut[..space],
+            _ => cut,
+        };
+
+        self.remaining = 0;
+        self.done = true;
+        Cow::Owned(format!("{}…", shortened.trim_end()))
+    }
+
+    fn take_whole(&mut self, text: &str) {
+        self.remaining = self.remaining.saturating_sub(text.chars().count());
+        if self.remaining == 0 {
+            self.done = true;
+        }
+    }
+}
+
+#[test]
+fn excerpt() {
+    use crate::data::PageInfo;
+    use crate::render::html::HtmlRender;
+    use crate::settings::{WikitextMode, WikitextSettings};
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let excerpt = |text: &str, max_len| {
+        let tokens = crate::tokenize(text);
+        let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+        HtmlRender
+            .render_excerpt(&tree, &page_info, &settings, max_len)
+            .body
+    };
+
+    // Stops after the first paragraph, skipping the table of contents
+    assert_eq!(
+        excerpt("[[toc]]\n\n+ Apple\n\nBanana **cherry**\n\nDurian", 100),
+        "<h1 id=\"toc0\">Apple</h1><p>Banana <strong>cherry</strong></p>",
+    );
+
+    // Shortens text within formatting, closing its container
+    assert_eq!(
+        excerpt("Apple **banana cherry durian**\n\nEggplant", 20),
+        "<p>Apple <strong>banana cherry…</strong></p>",
+    );
+
+    // Footnotes and modules are removed
+    assert_eq!(
+        excerpt(
+            "[[module Rate]]\n\nApple[[footnote]]Banana[[/footnote]]",
+            100
+        ),
+        "<p>Apple</p>",
+    );
+
+    assert_eq!(excerpt("Apple", 0), "");
+}
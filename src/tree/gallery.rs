@@ -0,0 +1,159 @@
+/*
+ * tree/gallery.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::clone::option_string_to_owned;
+use crate::tree::{AttributeMap, ImageSource, LinkLocation};
+use ref_map::*;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+
+/// A single image within a `[[gallery]]`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct GalleryImage<'t> {
+    pub source: ImageSource<'t>,
+    pub link: Option<LinkLocation<'t>>,
+    pub title: Option<Cow<'t, str>>,
+    pub attributes: AttributeMap<'t>,
+}
+
+impl GalleryImage<'_> {
+    /// The file name of this image, or its full URL if not an attachment.
+    pub fn file_name(&self) -> &str {
+        match &self.source {
+            ImageSource::Url(url) => url,
+            ImageSource::File1 { file }
+            | ImageSource::File2 { file, .. }
+            | ImageSource::File3 { file, .. } => file,
+        }
+    }
+
+    pub fn to_owned(&self) -> GalleryImage<'static> {
+        GalleryImage {
+            source: self.source.to_owned(),
+            link: self.link.ref_map(|link| link.to_owned()),
+            title: option_string_to_owned(&self.title),
+            attributes: self.attributes.to_owned(),
+        }
+    }
+}
+
+/// Returns the images of a gallery in the order they should be displayed.
+pub fn sort_gallery_images<'a, 't>(
+    images: &'a [GalleryImage<'t>],
+    order: Option<GalleryOrder>,
+) -> Vec<&'a GalleryImage<'t>> {
+    let mut images: Vec<_> = images.iter().collect();
+    if let Some(order) = order {
+        images.sort_by(|first, second| order.compare(first, second));
+    }
+
+    images
+}
+
+/// How large each image in a gallery is displayed.
+#[derive(Serialize, Deserialize, Debug, Default, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum GallerySize {
+    Square,
+    #[default]
+    Thumbnail,
+    Small,
+    Medium,
+}
+
+impl GallerySize {
+    pub fn parse(value: &str) -> Option<Self> {
+        const SIZES: [(&str, GallerySize); 4] = [
+            ("square", GallerySize::Square),
+            ("thumbnail", GallerySize::Thumbnail),
+            ("small", GallerySize::Small),
+            ("medium", GallerySize::Medium),
+        ];
+
+        SIZES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(value))
+            .map(|(_, size)| *size)
+    }
+
+    pub fn html_class(self) -> &'static str {
+        match self {
+            GallerySize::Square => "wj-gallery-square",
+            GallerySize::Thumbnail => "wj-gallery-thumbnail",
+            GallerySize::Small => "wj-gallery-small",
+            GallerySize::Medium => "wj-gallery-medium",
+        }
+    }
+}
+
+/// The field to sort gallery images by, and in which direction.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct GalleryOrder {
+    pub field: GalleryOrderField,
+    pub descending: bool,
+}
+
+impl GalleryOrder {
+    /// Compares two images according to this ordering.
+    ///
+    /// Fields which depend on file metadata, such as the upload date,
+    /// are not available to the renderer, so such images compare equal
+    /// and keep the order they were listed in.
+    pub fn compare(self, first: &GalleryImage, second: &GalleryImage) -> Ordering {
+        let ordering = match self.field {
+            GalleryOrderField::Name => first.file_name().cmp(second.file_name()),
+            GalleryOrderField::Title => first.title.cmp(&second.title),
+            GalleryOrderField::Created | GalleryOrderField::Size => Ordering::Equal,
+        };
+
+        if self.descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum GalleryOrderField {
+    Name,
+    Title,
+    Created,
+    Size,
+}
+
+impl GalleryOrderField {
+    pub fn parse(value: &str) -> Option<Self> {
+        const FIELDS: [(&str, GalleryOrderField); 4] = [
+            ("name", GalleryOrderField::Name),
+            ("title", GalleryOrderField::Title),
+            ("created_at", GalleryOrderField::Created),
+            ("size", GalleryOrderField::Size),
+        ];
+
+        FIELDS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(value))
+            .map(|(_, field)| *field)
+    }
+}
@@ -19,7 +19,7 @@
  */
 
 use super::clone::string_to_owned;
-use crate::url::is_url;
+use crate::url::{is_url, split_local_file};
 use std::borrow::Cow;
 use strum_macros::IntoStaticStr;
 
@@ -52,6 +52,14 @@ impl<'t> ImageSource<'t> {
             return Some(ImageSource::Url(cow!(source)));
         }
 
+        // Paths to attached files, as Wikidot links to them
+        if let Some((page, file)) = split_local_file(source) {
+            return Some(ImageSource::File2 {
+                page: cow!(page),
+                file: cow!(file),
+            });
+        }
+
         // Strip leading / if present
         let source = source.strip_prefix('/').unwrap_or(source);
 
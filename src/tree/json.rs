@@ -0,0 +1,187 @@
+/*
+ * tree/json.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Versioned JSON storage of syntax trees.
+//!
+//! Trees are stored as `{"version": 1, "tree": {...}}`, so that consumers
+//! which cache parsed trees can tell whether they can still be loaded after
+//! upgrading ftml, and otherwise reparse the source.
+
+use super::{Element, SyntaxTree};
+use crate::error::FtmlError;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// The current version of the serialized syntax tree format.
+///
+/// This is increased whenever a change to the tree would cause previously
+/// stored trees to fail to load, or to load with a different meaning.
+pub const SYNTAX_TREE_VERSION: u64 = 1;
+
+#[derive(Serialize, Debug)]
+struct VersionedTree<'a, 't> {
+    version: u64,
+    tree: &'a SyntaxTree<'t>,
+}
+
+impl SyntaxTree<'_> {
+    /// Serializes this tree as JSON, tagged with [`SYNTAX_TREE_VERSION`].
+    pub fn to_json(&self) -> Result<String, FtmlError> {
+        let versioned = VersionedTree {
+            version: SYNTAX_TREE_VERSION,
+            tree: self,
+        };
+
+        let json = serde_json::to_string(&versioned)?;
+        Ok(json)
+    }
+
+    /// Loads a tree previously serialized with [`SyntaxTree::to_json`].
+    ///
+    /// This fails with [`FtmlError::UnsupportedTreeVersion`] if the tree was
+    /// stored by an incompatible version of ftml, or [`FtmlError::InvalidTree`]
+    /// naming the path to the first node which is malformed.
+    pub fn from_json(json: &str) -> Result<SyntaxTree<'static>, FtmlError> {
+        let mut value = serde_json::from_str::<Value>(json)?;
+
+        let version = match value.get("version") {
+            Some(Value::Number(number)) => number.as_u64(),
+            _ => None,
+        };
+
+        match version {
+            Some(SYNTAX_TREE_VERSION) => (),
+            Some(version) => return Err(FtmlError::UnsupportedTreeVersion(version)),
+            None => return Err(invalid("version", "missing or not an integer")),
+        }
+
+        let tree = match value.get_mut("tree") {
+            Some(tree) => tree.take(),
+            None => return Err(invalid("tree", "missing field")),
+        };
+
+        match SyntaxTree::deserialize(&tree) {
+            Ok(tree) => Ok(tree),
+            Err(error) => {
+                // Find the innermost element which is malformed, if any,
+                // since serde errors do not say where they occurred.
+                let error = match find_invalid_element(&tree, &mut str!("tree")) {
+                    Some((path, error)) => invalid(&path, error),
+                    None => invalid("tree", error),
+                };
+
+                Err(error)
+            }
+        }
+    }
+}
+
+/// Searches depth-first for the innermost element which fails to deserialize.
+///
+/// Returns its path, such as `tree.elements[2].data.elements[0]`, and the error.
+fn find_invalid_element(value: &Value, path: &mut String) -> Option<(String, String)> {
+    let length = path.len();
+    let mut found = None;
+
+    match value {
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                str_write!(path, "[{index}]");
+                found = find_invalid_element(item, path);
+                path.truncate(length);
+
+                if found.is_some() {
+                    break;
+                }
+            }
+        }
+        Value::Object(fields) => {
+            for (key, field) in fields {
+                str_write!(path, ".{key}");
+                found = find_invalid_element(field, path);
+                path.truncate(length);
+
+                if found.is_some() {
+                    break;
+                }
+            }
+        }
+        _ => (),
+    }
+
+    if found.is_none() && value.get("element").is_some() {
+        if let Err(error) = Element::deserialize(value) {
+            found = Some((path.clone(), error.to_string()));
+        }
+    }
+
+    found
+}
+
+fn invalid<M: ToString>(path: &str, message: M) -> FtmlError {
+    FtmlError::InvalidTree {
+        path: str!(path),
+        message: message.to_string(),
+    }
+}
+
+#[test]
+fn json() {
+    use crate::data::PageInfo;
+    use crate::settings::{WikitextMode, WikitextSettings};
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let tokens = crate::tokenize("**Apple** [[span]]banana[[/span]]\n\n* cherry");
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+
+    // Round trip
+    let json = tree.to_json().unwrap();
+    assert!(json.starts_with(r#"{"version":1,"tree":{"#));
+    assert_eq!(SyntaxTree::from_json(&json).unwrap(), tree);
+
+    // Other versions are rejected
+    let error = SyntaxTree::from_json(r#"{"version":0,"tree":{}}"#).unwrap_err();
+    assert!(matches!(error, FtmlError::UnsupportedTreeVersion(0)));
+
+    let error = SyntaxTree::from_json(r#"{"tree":{}}"#).unwrap_err();
+    assert!(
+        matches!(error, FtmlError::InvalidTree { ref path, .. } if path == "version")
+    );
+
+    // Malformed elements are pinpointed
+    let json = json.replace(
+        r#""element":"text","data":"banana""#,
+        r#""element":"text","data":5"#,
+    );
+    match SyntaxTree::from_json(&json).unwrap_err() {
+        FtmlError::InvalidTree { path, message } => {
+            assert_eq!(path, "tree.elements[0].data.elements[2].data.elements[0]");
+            assert!(message.contains("expected a string"), "{message}");
+        }
+        error => panic!("Unexpected error: {error}"),
+    }
+
+    // Otherwise the whole tree is named
+    let json = json.replace(r#""footnotes":[]"#, r#""footnotes":{}"#);
+    let json = json.replace(r#""data":5"#, r#""data":"banana""#);
+    let error = SyntaxTree::from_json(&json).unwrap_err();
+    assert!(matches!(error, FtmlError::InvalidTree { ref path, .. } if path == "tree"));
+}
@@ -0,0 +1,268 @@
+/*
+ * tree/list_pages.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Structured representation of `[[module ListPages]]` requests.
+//!
+//! Rather than passing along an opaque argument map, the ListPages module
+//! is parsed into typed selectors, ordering, and pagination information,
+//! alongside a template describing how each listed page is rendered.
+
+use super::clone::{option_string_to_owned, string_to_owned};
+use std::borrow::Cow;
+use std::num::NonZeroU32;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ModuleListPages<'t> {
+    /// Which pages should be included in the listing.
+    pub selector: ListPagesSelector<'t>,
+
+    /// How the selected pages are sorted, if specified.
+    pub order: Option<ListPagesOrder<'t>>,
+
+    /// The maximum number of pages to list in total.
+    pub limit: Option<NonZeroU32>,
+
+    /// The number of pages to list on each page of results.
+    pub per_page: Option<NonZeroU32>,
+
+    /// How many pages at the beginning of the results to skip.
+    pub offset: Option<u32>,
+
+    /// Whether each page should be rendered in its own container.
+    pub separate: bool,
+
+    /// Whether the listing should be wrapped in a container.
+    pub wrapper: bool,
+
+    /// Wikitext to add before the listing.
+    pub prepend_line: Option<Cow<'t, str>>,
+
+    /// Wikitext to add after the listing.
+    pub append_line: Option<Cow<'t, str>>,
+
+    /// The body template, rendered once per listed page.
+    pub template: Vec<ListPagesTemplatePart<'t>>,
+}
+
+impl ModuleListPages<'_> {
+    pub fn to_owned(&self) -> ModuleListPages<'static> {
+        ModuleListPages {
+            selector: self.selector.to_owned(),
+            order: self.order.as_ref().map(|order| order.to_owned()),
+            limit: self.limit,
+            per_page: self.per_page,
+            offset: self.offset,
+            separate: self.separate,
+            wrapper: self.wrapper,
+            prepend_line: option_string_to_owned(&self.prepend_line),
+            append_line: option_string_to_owned(&self.append_line),
+            template: self.template.iter().map(|part| part.to_owned()).collect(),
+        }
+    }
+}
+
+/// Criteria for choosing which pages are listed.
+///
+/// Fields which are range or comparison expressions (such as `rating` or
+/// `created_at`) are kept as written, since their interpretation is left
+/// up to the `Handle` implementation.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ListPagesSelector<'t> {
+    pub page_type: Option<Cow<'t, str>>,
+    pub categories: ListPagesFilter<'t>,
+    pub tags: ListPagesFilter<'t>,
+    pub parent: Option<Cow<'t, str>>,
+    pub link_to: Option<Cow<'t, str>>,
+    pub name: Option<Cow<'t, str>>,
+    pub created_by: Option<Cow<'t, str>>,
+    pub created_at: Option<Cow<'t, str>>,
+    pub updated_at: Option<Cow<'t, str>>,
+    pub rating: Option<Cow<'t, str>>,
+    pub votes: Option<Cow<'t, str>>,
+}
+
+impl ListPagesSelector<'_> {
+    pub fn to_owned(&self) -> ListPagesSelector<'static> {
+        ListPagesSelector {
+            page_type: option_string_to_owned(&self.page_type),
+            categories: self.categories.to_owned(),
+            tags: self.tags.to_owned(),
+            parent: option_string_to_owned(&self.parent),
+            link_to: option_string_to_owned(&self.link_to),
+            name: option_string_to_owned(&self.name),
+            created_by: option_string_to_owned(&self.created_by),
+            created_at: option_string_to_owned(&self.created_at),
+            updated_at: option_string_to_owned(&self.updated_at),
+            rating: option_string_to_owned(&self.rating),
+            votes: option_string_to_owned(&self.votes),
+        }
+    }
+}
+
+/// A list of values to filter on, such as categories or tags.
+///
+/// Plain values are alternatives (at least one must match), values
+/// prefixed with `+` must all be present, and values prefixed with `-`
+/// must all be absent.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ListPagesFilter<'t> {
+    pub any: Vec<Cow<'t, str>>,
+    pub required: Vec<Cow<'t, str>>,
+    pub excluded: Vec<Cow<'t, str>>,
+}
+
+impl ListPagesFilter<'_> {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.any.is_empty() && self.required.is_empty() && self.excluded.is_empty()
+    }
+
+    pub fn to_owned(&self) -> ListPagesFilter<'static> {
+        fn strings_to_owned(strings: &[Cow<'_, str>]) -> Vec<Cow<'static, str>> {
+            strings.iter().map(|s| string_to_owned(s)).collect()
+        }
+
+        ListPagesFilter {
+            any: strings_to_owned(&self.any),
+            required: strings_to_owned(&self.required),
+            excluded: strings_to_owned(&self.excluded),
+        }
+    }
+}
+
+/// The field to sort listed pages by, and in which direction.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ListPagesOrder<'t> {
+    pub field: Cow<'t, str>,
+    pub descending: bool,
+}
+
+impl ListPagesOrder<'_> {
+    pub fn to_owned(&self) -> ListPagesOrder<'static> {
+        ListPagesOrder {
+            field: string_to_owned(&self.field),
+            descending: self.descending,
+        }
+    }
+}
+
+/// A piece of the ListPages body template.
+///
+/// Fields are written as `%%field%%` in the module body, and are
+/// substituted with the corresponding value for each listed page.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", tag = "type", content = "value")]
+pub enum ListPagesTemplatePart<'t> {
+    Text(Cow<'t, str>),
+    Field(Cow<'t, str>),
+}
+
+impl ListPagesTemplatePart<'_> {
+    /// Splits a template into text and `%%field%%` placeholders.
+    ///
+    /// A pair of `%%` markers only counts as a field if the name between them
+    /// is non-empty and contains no whitespace, otherwise it is left as text.
+    pub fn parse(body: &str) -> Vec<ListPagesTemplatePart<'_>> {
+        let mut parts = Vec::new();
+        let mut text_start = 0;
+        let mut search_start = 0;
+
+        while let Some(open) = body[search_start..].find("%%") {
+            let field_start = search_start + open + 2;
+            let field_end = match body[field_start..].find("%%") {
+                Some(length) => field_start + length,
+                None => break,
+            };
+
+            let field = &body[field_start..field_end];
+            if field.is_empty() || field.contains(char::is_whitespace) {
+                search_start = field_start;
+                continue;
+            }
+
+            let text = &body[text_start..field_start - 2];
+            if !text.is_empty() {
+                parts.push(ListPagesTemplatePart::Text(cow!(text)));
+            }
+
+            parts.push(ListPagesTemplatePart::Field(cow!(field)));
+            text_start = field_end + 2;
+            search_start = text_start;
+        }
+
+        if text_start < body.len() {
+            parts.push(ListPagesTemplatePart::Text(cow!(&body[text_start..])));
+        }
+
+        parts
+    }
+
+    pub fn to_owned(&self) -> ListPagesTemplatePart<'static> {
+        match self {
+            ListPagesTemplatePart::Text(text) => {
+                ListPagesTemplatePart::Text(string_to_owned(text))
+            }
+            ListPagesTemplatePart::Field(field) => {
+                ListPagesTemplatePart::Field(string_to_owned(field))
+            }
+        }
+    }
+}
+
+#[test]
+fn template() {
+    use ListPagesTemplatePart::{Field, Text};
+
+    macro_rules! check {
+        ($body:expr, $expected:expr $(,)?) => {{
+            let expected: Vec<ListPagesTemplatePart> = $expected;
+            assert_eq!(
+                ListPagesTemplatePart::parse($body),
+                expected,
+                "Template parts mismatch"
+            );
+        }};
+    }
+
+    check!("", vec![]);
+    check!("plain text", vec![Text(cow!("plain text"))]);
+    check!(
+        "* %%title_linked%% by %%created_by%%",
+        vec![
+            Text(cow!("* ")),
+            Field(cow!("title_linked")),
+            Text(cow!(" by ")),
+            Field(cow!("created_by")),
+        ],
+    );
+    check!(
+        "100%% %%rating%%%%",
+        vec![
+            Text(cow!("100%% ")),
+            Field(cow!("rating")),
+            Text(cow!("%%"))
+        ],
+    );
+    check!("%%content{1}%%", vec![Field(cow!("content{1}"))]);
+}
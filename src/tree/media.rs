@@ -0,0 +1,67 @@
+/*
+ * tree/media.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+/// What kind of media file is being embedded.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum MediaType {
+    Audio,
+    Video,
+}
+
+impl MediaType {
+    /// The name of this media type, which is also its HTML tag.
+    pub fn name(self) -> &'static str {
+        match self {
+            MediaType::Audio => "audio",
+            MediaType::Video => "video",
+        }
+    }
+}
+
+/// Playback options for embedded media.
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct MediaFlags {
+    /// Whether the browser's playback controls are shown.
+    pub controls: bool,
+
+    /// Whether playback starts as soon as the page loads.
+    ///
+    /// Most browsers only permit this for muted media.
+    pub autoplay: bool,
+
+    /// Whether playback restarts after reaching the end.
+    pub looping: bool,
+
+    /// Whether the audio is initially muted.
+    pub muted: bool,
+}
+
+impl Default for MediaFlags {
+    fn default() -> Self {
+        MediaFlags {
+            controls: true,
+            autoplay: false,
+            looping: false,
+            muted: false,
+        }
+    }
+}
@@ -19,26 +19,37 @@
  */
 
 pub mod attribute;
+pub mod diff;
+pub mod query;
+pub mod visit;
 
 mod align;
 mod anchor;
 mod bibliography;
 mod clear_float;
 mod clone;
+mod code_blob;
 mod container;
 mod date;
 mod definition_list;
 mod element;
 mod embed;
+mod excerpt;
+mod gallery;
 mod heading;
 mod image;
+mod json;
 mod link;
 mod list;
+mod list_pages;
+mod media;
 mod module;
 mod partial;
 mod ruby;
+mod statistics;
 mod tab;
 mod table;
+mod table_of_contents;
 mod tag;
 mod variables;
 
@@ -47,25 +58,36 @@ pub use self::anchor::*;
 pub use self::attribute::AttributeMap;
 pub use self::bibliography::*;
 pub use self::clear_float::*;
+pub use self::code_blob::CodeBlob;
 pub use self::container::*;
 pub use self::date::DateItem;
 pub use self::definition_list::*;
 pub use self::element::*;
 pub use self::embed::*;
+pub use self::gallery::*;
 pub use self::heading::*;
 pub use self::image::*;
+pub use self::json::SYNTAX_TREE_VERSION;
 pub use self::link::*;
 pub use self::list::*;
+pub use self::list_pages::*;
+pub use self::media::*;
 pub use self::module::*;
 pub use self::partial::*;
 pub use self::ruby::*;
+pub use self::statistics::{TextStatistics, WORDS_PER_MINUTE};
 pub use self::tab::*;
 pub use self::table::*;
+pub use self::table_of_contents::*;
 pub use self::tag::*;
 pub use self::variables::*;
+pub use self::visit::{Visitor, VisitorMut};
 
-use self::clone::{elements_lists_to_owned, elements_to_owned};
+use self::clone::{
+    elements_lists_to_owned, elements_to_owned, option_string_to_owned, string_to_owned,
+};
 use crate::parsing::{ParseError, ParseOutcome};
+use std::borrow::Cow;
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
@@ -83,12 +105,41 @@ pub struct SyntaxTree<'t> {
     /// match the heading level.
     pub table_of_contents: Vec<Element<'t>>,
 
+    /// The anchor, level, and source location of each heading
+    /// in the table of contents, in page order.
+    #[serde(default)]
+    pub table_of_contents_entries: Vec<TableOfContentsEntry<'t>>,
+
     /// The full footnote list for this page.
     pub footnotes: Vec<Vec<Element<'t>>>,
 
     /// The full list of bibliographies for this page.
     pub bibliographies: BibliographyList<'t>,
 
+    /// The name of each numbered equation on this page, in order.
+    ///
+    /// Equations are numbered from one, and `[[eref]]` references
+    /// are resolved against these names. Unnamed equations are `None`.
+    #[serde(default)]
+    pub equations: Vec<Option<Cow<'t, str>>>,
+
+    /// The CSS stylesheets on this page, in order.
+    ///
+    /// These come from `[[css]]` blocks and `[[module CSS]]`, and allow
+    /// consumers to place page styling separately from the body.
+    /// The `Element::Style` elements remain in the tree as well.
+    #[serde(default)]
+    pub styles: Vec<Cow<'t, str>>,
+
+    /// The bodies of code blocks stored out-of-line, in page order.
+    ///
+    /// Each `Element::CodeBlob` refers to one of these by its identifier.
+    /// The host submits these to its blob store, from which renderers read
+    /// them back. Hosts which store the tree as well may take these out of
+    /// it first, so that the stored tree stays small.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub code_blobs: Vec<CodeBlob<'t>>,
+
     /// Hint for the size of the wikitext input.
     ///
     /// This is an optimization to make rendering large parges slightly faster.
@@ -103,24 +154,54 @@ impl<'t> SyntaxTree<'t> {
         table_of_contents: Vec<Element<'t>>,
         footnotes: Vec<Vec<Element<'t>>>,
         bibliographies: BibliographyList<'t>,
+        styles: Vec<Cow<'t, str>>,
         wikitext_len: usize,
     ) -> ParseOutcome<Self> {
         let tree = SyntaxTree {
             elements,
             table_of_contents,
+            table_of_contents_entries: vec![],
             footnotes,
             bibliographies,
+            equations: vec![],
+            styles,
+            code_blobs: vec![],
             wikitext_len,
         };
         ParseOutcome::new(tree, errors)
     }
 
+    /// Returns the destination of each `[[module Redirect]]` on this page, in order.
+    ///
+    /// Hosts generally only follow the first of these.
+    pub fn redirects(&self) -> Vec<&LinkLocation<'t>> {
+        let mut redirects = Vec::new();
+        self.walk(|element| {
+            if let Element::Redirect { destination } = element {
+                redirects.push(destination);
+            }
+        });
+        redirects
+    }
+
     pub fn to_owned(&self) -> SyntaxTree<'static> {
         SyntaxTree {
             elements: elements_to_owned(&self.elements),
             table_of_contents: elements_to_owned(&self.table_of_contents),
+            table_of_contents_entries: self
+                .table_of_contents_entries
+                .iter()
+                .map(|entry| entry.to_owned())
+                .collect(),
             footnotes: elements_lists_to_owned(&self.footnotes),
             bibliographies: self.bibliographies.to_owned(),
+            equations: self
+                .equations
+                .iter()
+                .map(|name| option_string_to_owned(name))
+                .collect(),
+            styles: self.styles.iter().map(|css| string_to_owned(css)).collect(),
+            code_blobs: self.code_blobs.iter().map(|blob| blob.to_owned()).collect(),
             wikitext_len: self.wikitext_len,
         }
     }
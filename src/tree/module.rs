@@ -21,7 +21,7 @@
 //! Representation of Wikidot modules, along with their context.
 
 use super::clone::option_string_to_owned;
-use super::AttributeMap;
+use super::{AttributeMap, ModuleListPages};
 use std::borrow::Cow;
 use std::num::NonZeroU32;
 use strum_macros::IntoStaticStr;
@@ -45,6 +45,9 @@ pub enum Module<'t> {
         attributes: AttributeMap<'t>,
     },
 
+    /// Lists pages matching the given criteria, rendered using a template.
+    ListPages(Box<ModuleListPages<'t>>),
+
     /// Lists the structure of pages as connected by parenthood.
     ///
     /// Shows the hierarchy of parent relationships present on the given page.
@@ -81,6 +84,9 @@ impl Module<'_> {
                 button_text: option_string_to_owned(button_text),
                 attributes: attributes.to_owned(),
             },
+            Module::ListPages(list_pages) => {
+                Module::ListPages(Box::new(ModuleListPages::to_owned(list_pages)))
+            }
             Module::PageTree {
                 root,
                 show_root,
@@ -0,0 +1,611 @@
+/*
+ * tree/query.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Selector queries over syntax trees.
+//!
+//! Selectors resemble a small subset of CSS. Each step names an element,
+//! as in [`Element::name()`], or is `*` to match any element. Names are
+//! case-insensitive, and dashes and underscores are ignored, so both
+//! `table-of-contents` and `TableOfContents` are accepted.
+//!
+//! A step may be followed by any number of attribute conditions:
+//!
+//! * `[key]` — the attribute is present.
+//! * `[key=value]` — the attribute has exactly this value.
+//! * `[key^=value]`, `[key$=value]`, `[key*=value]` — the attribute
+//!   starts with, ends with, or contains this value.
+//!
+//! Values may be quoted with `"` or `'`. Besides the element's own
+//! attributes, some elements have properties which can be queried the
+//! same way:
+//!
+//! * Links: `type`, `url` or `page`, `target`, and `external` if the link
+//!   is to an absolute URL.
+//! * Images: `url`, if sourced from one.
+//! * Iframes: `url`.
+//! * Includes: `page`.
+//! * Code blocks: `language`.
+//!
+//! Steps separated by whitespace match descendants, and those separated
+//! by `>` match direct children. Several selectors may be separated by
+//! commas, matching elements which satisfy any of them.
+//!
+//! For instance, `collapsible link[external], iframe` finds every link to
+//! another site within a collapsible, as well as every iframe.
+
+use super::{Element, ImageSource, LinkLocation, SyntaxTree};
+use crate::url::is_url;
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// A parsed selector, for use with [`SyntaxTree::query`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selector {
+    alternatives: Vec<Vec<Step>>,
+}
+
+impl Selector {
+    pub fn parse(selector: &str) -> Result<Self, SelectorParseError> {
+        info!("Parsing selector '{selector}'");
+
+        let mut parser = SelectorParser {
+            input: selector,
+            position: 0,
+        };
+
+        let mut alternatives = vec![parser.parse_steps()?];
+        while parser.eat(',') {
+            alternatives.push(parser.parse_steps()?);
+        }
+
+        Ok(Selector { alternatives })
+    }
+
+    /// Whether this element matches, given its ancestors from outermost to innermost.
+    pub fn matches(&self, element: &Element, ancestors: &[&Element]) -> bool {
+        self.alternatives
+            .iter()
+            .any(|steps| matches_steps(steps, element, ancestors))
+    }
+}
+
+impl FromStr for Selector {
+    type Err = SelectorParseError;
+
+    #[inline]
+    fn from_str(selector: &str) -> Result<Self, Self::Err> {
+        Selector::parse(selector)
+    }
+}
+
+/// An element matched by a selector.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryMatch<'e, 't> {
+    /// The indices to follow to reach this element from the body.
+    ///
+    /// Each index is the position of an element among the children of its
+    /// parent, in the order of [`Element::for_each_child()`].
+    /// See [`SyntaxTree::element_at()`].
+    pub path: Vec<usize>,
+
+    pub element: &'e Element<'t>,
+}
+
+impl<'t> SyntaxTree<'t> {
+    /// Finds each element in the body matching this selector, in page order.
+    ///
+    /// Footnotes and bibliographies are not part of the body,
+    /// they may be searched using [`query_elements()`].
+    pub fn query<'e>(&'e self, selector: &Selector) -> Vec<QueryMatch<'e, 't>> {
+        query_elements(&self.elements, selector)
+    }
+
+    /// Gets the element in the body at this path, as returned by [`SyntaxTree::query`].
+    pub fn element_at(&self, path: &[usize]) -> Option<&Element<'t>> {
+        let (&first, rest) = path.split_first()?;
+        let mut element = self.elements.get(first)?;
+
+        for &index in rest {
+            let mut child = None;
+            let mut current = 0;
+            element.for_each_child(|element| {
+                if current == index {
+                    child = Some(element);
+                }
+
+                current += 1;
+            });
+
+            element = child?;
+        }
+
+        Some(element)
+    }
+}
+
+/// Finds each element in this list matching the selector, including their children.
+pub fn query_elements<'e, 't>(
+    elements: &'e [Element<'t>],
+    selector: &Selector,
+) -> Vec<QueryMatch<'e, 't>> {
+    let mut query = Query {
+        selector,
+        matches: Vec::new(),
+        ancestors: Vec::new(),
+        path: Vec::new(),
+    };
+
+    for (index, element) in elements.iter().enumerate() {
+        query.visit(index, element);
+    }
+
+    debug!("Found {} elements matching selector", query.matches.len());
+    query.matches
+}
+
+#[derive(Debug)]
+struct Query<'s, 'e, 't> {
+    selector: &'s Selector,
+    matches: Vec<QueryMatch<'e, 't>>,
+    ancestors: Vec<&'e Element<'t>>,
+    path: Vec<usize>,
+}
+
+impl<'e, 't> Query<'_, 'e, 't> {
+    fn visit(&mut self, index: usize, element: &'e Element<'t>) {
+        self.path.push(index);
+
+        if self.selector.matches(element, &self.ancestors) {
+            self.matches.push(QueryMatch {
+                path: self.path.clone(),
+                element,
+            });
+        }
+
+        let mut children = Vec::new();
+        element.for_each_child(|child| children.push(child));
+
+        self.ancestors.push(element);
+        for (index, child) in children.into_iter().enumerate() {
+            self.visit(index, child);
+        }
+        self.ancestors.pop();
+
+        self.path.pop();
+    }
+}
+
+/// Matches the last step against the element, and the rest against its ancestors.
+fn matches_steps(steps: &[Step], element: &Element, ancestors: &[&Element]) -> bool {
+    let (step, rest) = match steps.split_last() {
+        Some(split) => split,
+        None => return true,
+    };
+
+    if !step.compound.matches(element) {
+        return false;
+    }
+
+    if rest.is_empty() {
+        return true;
+    }
+
+    match step.combinator {
+        Combinator::Child => match ancestors.split_last() {
+            Some((parent, ancestors)) => matches_steps(rest, parent, ancestors),
+            None => false,
+        },
+        Combinator::Descendant => (0..ancestors.len())
+            .rev()
+            .any(|index| matches_steps(rest, ancestors[index], &ancestors[..index])),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Step {
+    /// How this step relates to the previous one.
+    ///
+    /// This is ignored for the first step.
+    combinator: Combinator,
+    compound: Compound,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Compound {
+    /// The normalized element name, or `None` for any element.
+    name: Option<String>,
+    conditions: Vec<Condition>,
+}
+
+impl Compound {
+    fn matches(&self, element: &Element) -> bool {
+        if let Some(name) = &self.name {
+            if normalize_name(element.name()) != *name {
+                return false;
+            }
+        }
+
+        self.conditions.iter().all(|condition| {
+            let value = get_attribute(element, &condition.key);
+            condition.matches(value.as_deref())
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Condition {
+    key: String,
+    operator: Operator,
+    value: String,
+}
+
+impl Condition {
+    fn matches(&self, value: Option<&str>) -> bool {
+        let value = match value {
+            Some(value) => value,
+            None => return false,
+        };
+
+        match self.operator {
+            Operator::Present => true,
+            Operator::Equals => value == self.value,
+            Operator::StartsWith => value.starts_with(&self.value),
+            Operator::EndsWith => value.ends_with(&self.value),
+            Operator::Contains => value.contains(&self.value),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Operator {
+    Present,
+    Equals,
+    StartsWith,
+    EndsWith,
+    Contains,
+}
+
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|&c| c != '-' && c != '_')
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Gets an attribute of this element, or one of its queryable properties.
+fn get_attribute<'e>(element: &'e Element, key: &str) -> Option<Cow<'e, str>> {
+    if let Some(attributes) = element.attributes() {
+        if let Some(value) = attributes.get().get(key) {
+            return Some(Cow::Borrowed(value));
+        }
+    }
+
+    let value = match (element, key) {
+        (Element::Link { ltype, .. }, "type") => Cow::Borrowed(ltype.name()),
+        (
+            Element::Link {
+                link: LinkLocation::Url(url),
+                ..
+            },
+            "url",
+        ) => Cow::Borrowed(url.as_ref()),
+        (
+            Element::Link {
+                link: LinkLocation::Url(url),
+                ..
+            },
+            "external",
+        ) if is_url(url) => Cow::Borrowed(""),
+        (
+            Element::Link {
+                link: LinkLocation::Page(page),
+                ..
+            },
+            "page",
+        ) => Cow::Owned(page.to_string()),
+        (
+            Element::Link {
+                target: Some(target),
+                ..
+            },
+            "target",
+        ) => Cow::Borrowed(target.name()),
+        (
+            Element::Image {
+                source: ImageSource::Url(url),
+                ..
+            },
+            "url",
+        ) => Cow::Borrowed(url.as_ref()),
+        (Element::Iframe { url, .. }, "url") => Cow::Borrowed(url.as_ref()),
+        (Element::Include { location, .. }, "page") => Cow::Owned(location.to_string()),
+        (
+            Element::Code {
+                language: Some(language),
+                ..
+            },
+            "language",
+        ) => Cow::Borrowed(language.as_ref()),
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+#[derive(Debug)]
+struct SelectorParser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> SelectorParser<'a> {
+    fn parse_steps(&mut self) -> Result<Vec<Step>, SelectorParseError> {
+        let mut steps = Vec::new();
+        let mut combinator = Combinator::Descendant;
+
+        loop {
+            self.skip_whitespace();
+            steps.push(Step {
+                combinator,
+                compound: self.parse_compound()?,
+            });
+
+            let had_space = self.skip_whitespace();
+            combinator = match self.peek() {
+                None | Some(',') => return Ok(steps),
+                Some('>') => {
+                    self.position += 1;
+                    Combinator::Child
+                }
+                Some(_) if had_space => Combinator::Descendant,
+                Some(_) => return Err(self.error("unexpected character")),
+            };
+        }
+    }
+
+    fn parse_compound(&mut self) -> Result<Compound, SelectorParseError> {
+        let name = if self.eat('*') {
+            None
+        } else {
+            let name = self.parse_identifier();
+            if name.is_empty() && self.peek() != Some('[') {
+                return Err(self.error("expected element name"));
+            }
+
+            (!name.is_empty()).then(|| normalize_name(name))
+        };
+
+        let mut conditions = Vec::new();
+        while self.eat('[') {
+            conditions.push(self.parse_condition()?);
+        }
+
+        Ok(Compound { name, conditions })
+    }
+
+    fn parse_condition(&mut self) -> Result<Condition, SelectorParseError> {
+        self.skip_whitespace();
+        let key = self.parse_identifier();
+        if key.is_empty() {
+            return Err(self.error("expected attribute name"));
+        }
+        let key = str!(key);
+        self.skip_whitespace();
+
+        let operator = match self.peek() {
+            Some(']') => Operator::Present,
+            Some('=') => Operator::Equals,
+            Some('^') => Operator::StartsWith,
+            Some('$') => Operator::EndsWith,
+            Some('*') => Operator::Contains,
+            _ => return Err(self.error("expected attribute operator")),
+        };
+
+        if operator == Operator::Present {
+            self.position += 1;
+            return Ok(Condition {
+                key,
+                operator,
+                value: String::new(),
+            });
+        }
+
+        if operator != Operator::Equals {
+            self.position += 1;
+        }
+
+        if !self.eat('=') {
+            return Err(self.error("expected '='"));
+        }
+
+        self.skip_whitespace();
+        let value = self.parse_value()?;
+        self.skip_whitespace();
+
+        if !self.eat(']') {
+            return Err(self.error("expected ']'"));
+        }
+
+        Ok(Condition {
+            key,
+            operator,
+            value,
+        })
+    }
+
+    fn parse_value(&mut self) -> Result<String, SelectorParseError> {
+        let quote = match self.peek() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => {
+                let value = self.take_while(|c| c != ']' && !c.is_whitespace());
+                return Ok(str!(value));
+            }
+        };
+
+        self.position += 1;
+        let value = self.take_while(|c| c != quote);
+        if !self.eat(quote) {
+            return Err(self.error("unterminated string"));
+        }
+
+        Ok(str!(value))
+    }
+
+    fn parse_identifier(&mut self) -> &'a str {
+        self.take_while(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    }
+
+    fn take_while<F>(&mut self, f: F) -> &'a str
+    where
+        F: Fn(char) -> bool,
+    {
+        let rest = &self.input[self.position..];
+        let length = rest.find(|c| !f(c)).unwrap_or(rest.len());
+        self.position += length;
+        &rest[..length]
+    }
+
+    /// Skips whitespace, returning whether there was any.
+    fn skip_whitespace(&mut self) -> bool {
+        !self.take_while(char::is_whitespace).is_empty()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    fn eat(&mut self, ch: char) -> bool {
+        if self.peek() == Some(ch) {
+            self.position += ch.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self, message: &'static str) -> SelectorParseError {
+        SelectorParseError {
+            position: self.position,
+            message,
+        }
+    }
+}
+
+/// A selector could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelectorParseError {
+    /// The byte offset in the selector where the error occurred.
+    pub position: usize,
+    pub message: &'static str,
+}
+
+impl Display for SelectorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+#[test]
+fn query() {
+    use crate::data::PageInfo;
+    use crate::settings::{WikitextMode, WikitextSettings};
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let text = "[https://example.com/ Apple] [/banana Banana]\n\n\
+                [[collapsible]]\n\
+                [https://example.org/ Cherry] [[[durian]]]\n\n\
+                [[div class=\"fruit\"]]\n\
+                [https://example.net/ Eggplant]\n\
+                [[/div]]\n\
+                [[/collapsible]]\n\n\
+                [[iframe https://example.com/frame]]";
+
+    let tokens = crate::tokenize(text);
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+
+    let query = |selector: &str| {
+        let selector = Selector::parse(selector).expect("Unable to parse selector");
+
+        tree.query(&selector)
+            .into_iter()
+            .map(|result| {
+                let element = tree
+                    .element_at(&result.path)
+                    .expect("Path does not lead to an element");
+
+                assert_eq!(element, result.element, "Path leads to a different element");
+                get_attribute(element, "url")
+                    .or_else(|| get_attribute(element, "page"))
+                    .map(|value| value.into_owned())
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        query("link[external]"),
+        [
+            "https://example.com/",
+            "https://example.org/",
+            "https://example.net/",
+        ],
+    );
+    assert_eq!(
+        query("collapsible link[external]"),
+        ["https://example.org/", "https://example.net/"],
+    );
+    assert_eq!(
+        query("collapsible > * > link[external]"),
+        ["https://example.org/"]
+    );
+    assert_eq!(query("div[class=fruit] link"), ["https://example.net/"]);
+    assert_eq!(query("link[type=page]"), ["durian"]);
+    assert_eq!(query("link[url^='/']"), ["/banana"]);
+    assert_eq!(
+        query("IFRAME, link[url*=\".net\"]"),
+        ["https://example.net/", "https://example.com/frame"],
+    );
+    assert!(query("collapsible > link").is_empty());
+    assert!(query("table-of-contents").is_empty());
+
+    // Malformed selectors
+    for selector in [
+        "",
+        "link[",
+        "link[url~=a]",
+        "link[url='a]",
+        "a,",
+        "a >",
+        "a]",
+    ] {
+        assert!(
+            Selector::parse(selector).is_err(),
+            "Selector '{selector}' should not parse",
+        );
+    }
+}
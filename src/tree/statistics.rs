@@ -0,0 +1,188 @@
+/*
+ * tree/statistics.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Summary statistics about the text of a syntax tree.
+//!
+//! These are computed from the tree directly, without rendering, so they
+//! are approximate: page titles for links are not looked up, and the
+//! contents of includes are only counted if they have been expanded.
+
+use super::visit::{walk_element, Visitor};
+use super::{ContainerType, Element, LinkLabel, LinkLocation, SyntaxTree};
+use crate::entity::find_entity;
+
+/// Average adult silent reading speed, used to estimate reading time.
+pub const WORDS_PER_MINUTE: usize = 200;
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct TextStatistics {
+    /// The number of words of visible text.
+    pub words: usize,
+
+    /// The number of characters of visible text.
+    pub characters: usize,
+
+    /// The size of the source wikitext, including markup.
+    ///
+    /// This is taken from [`SyntaxTree::wikitext_len`], and so is
+    /// measured in bytes rather than characters. It is zero for
+    /// trees which were deserialized rather than parsed.
+    pub characters_with_markup: usize,
+
+    /// The number of headings.
+    pub headings: usize,
+
+    /// The number of links, including anchors.
+    pub links: usize,
+
+    /// The number of images.
+    pub images: usize,
+
+    /// Estimated time to read the text, in seconds.
+    ///
+    /// See [`WORDS_PER_MINUTE`].
+    pub reading_time_seconds: u64,
+}
+
+impl SyntaxTree<'_> {
+    /// Counts words, characters, and other features of this page.
+    ///
+    /// This covers the same elements as [`SyntaxTree::walk`].
+    pub fn statistics(&self) -> TextStatistics {
+        let mut counter = Counter::default();
+        counter.visit_tree(self);
+
+        let Counter { mut stats, .. } = counter;
+        let reading_time = (stats.words * 60).div_ceil(WORDS_PER_MINUTE);
+        stats.characters_with_markup = self.wikitext_len;
+        stats.reading_time_seconds = reading_time as u64;
+        stats
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counter {
+    stats: TextStatistics,
+
+    /// Whether the last character counted was part of a word.
+    ///
+    /// This lets words split across inline formatting,
+    /// such as `**A**pple`, be counted once.
+    in_word: bool,
+}
+
+impl Counter {
+    fn add_text(&mut self, text: &str) {
+        for ch in text.chars() {
+            self.stats.characters += 1;
+
+            if ch.is_whitespace() {
+                self.in_word = false;
+            } else if !self.in_word {
+                self.stats.words += 1;
+                self.in_word = true;
+            }
+        }
+    }
+}
+
+impl<'t> Visitor<'t> for Counter {
+    fn visit_element(&mut self, element: &Element<'t>) {
+        // Block-level elements separate words
+        if !element.paragraph_safe() {
+            self.in_word = false;
+        }
+
+        match element {
+            Element::Text(text) | Element::Raw(text) | Element::Email(text) => {
+                self.add_text(text);
+            }
+            Element::HtmlEntity(name) => {
+                if let Some(value) = find_entity(name) {
+                    self.add_text(&value);
+                }
+            }
+            Element::Code { contents, .. } => self.add_text(contents),
+            Element::Container(container)
+                if matches!(container.ctype(), ContainerType::Header(_)) =>
+            {
+                self.stats.headings += 1;
+            }
+            Element::Link { link, label, .. } => {
+                self.stats.links += 1;
+
+                match (label, link) {
+                    (LinkLabel::Text(text), _) | (LinkLabel::Url(Some(text)), _) => {
+                        self.add_text(text);
+                    }
+                    (LinkLabel::Url(None), LinkLocation::Url(url)) => self.add_text(url),
+                    _ => (),
+                }
+            }
+            Element::Anchor { .. } => self.stats.links += 1,
+            Element::Image { .. } => self.stats.images += 1,
+            Element::Gallery { images, .. } => self.stats.images += images.len(),
+            Element::LineBreak | Element::LineBreaks(_) => self.in_word = false,
+            _ => (),
+        }
+
+        walk_element(self, element);
+
+        if !element.paragraph_safe() {
+            self.in_word = false;
+        }
+    }
+}
+
+#[test]
+fn statistics() {
+    use crate::data::PageInfo;
+    use crate::settings::{WikitextMode, WikitextSettings};
+
+    let page_info = PageInfo::dummy();
+    let settings = WikitextSettings::from_mode(WikitextMode::Page);
+    let text = "+ Fruit\n\n\
+                **App**le banana [[[cherry|Cherry pie]]] [https://example.com/]\n\
+                [[image durian.png]] [[a href=\"/eggplant\"]]Eggplant[[/a]]\n\n\
+                ++ More\n\nFig";
+
+    let tokens = crate::tokenize(text);
+    let (tree, _errors) = crate::parse(&tokens, &page_info, &settings).into();
+    let stats = tree.statistics();
+
+    assert_eq!(
+        stats,
+        TextStatistics {
+            words: 9,
+            characters: 67,
+            characters_with_markup: text.len(),
+            headings: 2,
+            links: 3,
+            images: 1,
+            reading_time_seconds: 3,
+        },
+    );
+
+    assert_eq!(
+        SyntaxTree::default().statistics(),
+        TextStatistics::default()
+    );
+}
@@ -0,0 +1,61 @@
+/*
+ * tree/table_of_contents.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+use super::clone::string_to_owned;
+use super::HeadingLevel;
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// Information about a heading listed in the table of contents.
+///
+/// These are alongside the table of contents elements in the syntax tree,
+/// so that consumers building their own navigation can link to the same
+/// anchors as the rendered HTML.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct TableOfContentsEntry<'t> {
+    /// The anchor ID given to this heading.
+    ///
+    /// This is the same ID the HTML renderer emits, without the `#`.
+    pub id: Cow<'t, str>,
+
+    /// The level of the heading.
+    pub level: HeadingLevel,
+
+    /// The text of the heading, without formatting.
+    pub name: Cow<'t, str>,
+
+    /// Where the heading is in the source wikitext.
+    ///
+    /// For headings from included pages, this refers
+    /// to the source of the included page.
+    pub span: Range<usize>,
+}
+
+impl TableOfContentsEntry<'_> {
+    pub fn to_owned(&self) -> TableOfContentsEntry<'static> {
+        TableOfContentsEntry {
+            id: string_to_owned(&self.id),
+            level: self.level,
+            name: string_to_owned(&self.name),
+            span: self.span.clone(),
+        }
+    }
+}
@@ -20,7 +20,7 @@
 
 use super::clone::string_map_to_owned;
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 pub type VariableMap<'t> = HashMap<Cow<'t, str>, Cow<'t, str>>;
 
@@ -55,4 +55,19 @@ impl VariableScopes {
     pub fn pop_scope(&mut self) {
         self.scopes.pop().expect("Scope stack was empty");
     }
+
+    /// Returns the value of every variable currently visible, sorted by name.
+    ///
+    /// Shadowed values are excluded, and the result does not depend on
+    /// the iteration order of the underlying maps.
+    pub fn visible_values(&self) -> BTreeMap<&str, &str> {
+        let mut values = BTreeMap::new();
+        for scope in &self.scopes {
+            for (name, value) in scope {
+                values.insert(name.as_ref(), value.as_ref());
+            }
+        }
+
+        values
+    }
 }
@@ -0,0 +1,436 @@
+/*
+ * tree/visit.rs
+ *
+ * ftml - Library to parse Wikidot text
+ * Copyright (C) 2019-2024 Wikijump Team
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU Affero General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+ * GNU Affero General Public License for more details.
+ *
+ * You should have received a copy of the GNU Affero General Public License
+ * along with this program. If not, see <http://www.gnu.org/licenses/>.
+ */
+
+//! Visitor and transformer API for syntax trees.
+//!
+//! Rather than matching over every [`Element`] variant by hand, implement
+//! [`Visitor`] or [`VisitorMut`] and override only the methods of interest.
+//! The default implementations descend into every child, so new element
+//! variants are walked without any changes to existing visitors.
+//!
+//! To replace or remove nodes, use [`transform()`], or [`transform_lists()`]
+//! for changes spanning several sibling elements.
+
+use super::{Element, Elements, ListItem, SyntaxTree};
+use std::mem;
+
+/// Read-only traversal of a syntax tree.
+///
+/// Overridden methods should call the corresponding `walk_*` function
+/// if they wish to continue visiting the children of a node.
+pub trait Visitor<'t> {
+    /// Visits a single element, then its children.
+    fn visit_element(&mut self, element: &Element<'t>) {
+        walk_element(self, element);
+    }
+
+    /// Visits each element in a list, in order.
+    fn visit_elements(&mut self, elements: &[Element<'t>]) {
+        for element in elements {
+            self.visit_element(element);
+        }
+    }
+
+    /// Visits the body, footnotes, and bibliographies of a tree.
+    fn visit_tree(&mut self, tree: &SyntaxTree<'t>) {
+        walk_tree(self, tree);
+    }
+}
+
+/// Mutable traversal of a syntax tree.
+///
+/// This permits editing elements in place. To insert or
+/// remove elements, see [`transform()`] instead.
+pub trait VisitorMut<'t> {
+    /// Visits a single element, then its children.
+    fn visit_element_mut(&mut self, element: &mut Element<'t>) {
+        walk_element_mut(self, element);
+    }
+
+    /// Visits each element in a list, in order.
+    fn visit_elements_mut(&mut self, elements: &mut [Element<'t>]) {
+        for element in elements {
+            self.visit_element_mut(element);
+        }
+    }
+
+    /// Visits the body, footnotes, and bibliographies of a tree.
+    fn visit_tree_mut(&mut self, tree: &mut SyntaxTree<'t>) {
+        walk_tree_mut(self, tree);
+    }
+}
+
+/// Visits each direct child of the given element.
+pub fn walk_element<'t, V>(visitor: &mut V, element: &Element<'t>)
+where
+    V: Visitor<'t> + ?Sized,
+{
+    element.for_each_child(|child| visitor.visit_element(child));
+}
+
+/// Visits each element of the tree.
+///
+/// Like [`SyntaxTree::walk`], the generated table of contents is not included.
+pub fn walk_tree<'t, V>(visitor: &mut V, tree: &SyntaxTree<'t>)
+where
+    V: Visitor<'t> + ?Sized,
+{
+    visitor.visit_elements(&tree.elements);
+
+    for footnote in &tree.footnotes {
+        visitor.visit_elements(footnote);
+    }
+
+    for bibliography in tree.bibliographies.iter() {
+        for (_, elements) in bibliography.slice() {
+            visitor.visit_elements(elements);
+        }
+    }
+}
+
+/// Mutably visits each direct child of the given element.
+pub fn walk_element_mut<'t, V>(visitor: &mut V, element: &mut Element<'t>)
+where
+    V: VisitorMut<'t> + ?Sized,
+{
+    element.for_each_child_mut(|child| visitor.visit_element_mut(child));
+}
+
+/// Mutably visits each element of the tree.
+pub fn walk_tree_mut<'t, V>(visitor: &mut V, tree: &mut SyntaxTree<'t>)
+where
+    V: VisitorMut<'t> + ?Sized,
+{
+    visitor.visit_elements_mut(&mut tree.elements);
+
+    for footnote in &mut tree.footnotes {
+        visitor.visit_elements_mut(footnote);
+    }
+
+    for bibliography in tree.bibliographies.iter_mut() {
+        for elements in bibliography.elements_mut() {
+            visitor.visit_elements_mut(elements);
+        }
+    }
+}
+
+/// Replaces each element with the output of the given function.
+///
+/// This runs bottom-up: the children of an element are transformed
+/// before the element itself is passed to the function. Returning
+/// `Elements::None` removes the element, and `Elements::Multiple`
+/// splices the new elements in its place.
+///
+/// Partial elements are passed to the function but not descended into,
+/// since they do not appear in finished syntax trees.
+pub fn transform<'t, F>(elements: &mut Vec<Element<'t>>, mut f: F)
+where
+    F: FnMut(Element<'t>) -> Elements<'t>,
+{
+    transform_elements(elements, &mut f);
+}
+
+fn transform_elements<'t, F>(elements: &mut Vec<Element<'t>>, f: &mut F)
+where
+    F: FnMut(Element<'t>) -> Elements<'t>,
+{
+    for mut element in mem::take(elements) {
+        for_each_list_mut(&mut element, &mut |list| transform_elements(list, f));
+        elements.extend(f(element));
+    }
+}
+
+/// Replaces each list of sibling elements using the given function.
+///
+/// This runs bottom-up like [`transform()`], but the function is given
+/// a whole list at once. This allows changes spanning several adjacent
+/// elements, such as joining runs of text.
+pub fn transform_lists<'t, F>(elements: &mut Vec<Element<'t>>, mut f: F)
+where
+    F: FnMut(&mut Vec<Element<'t>>),
+{
+    transform_lists_inner(elements, &mut f);
+}
+
+fn transform_lists_inner<'t, F>(elements: &mut Vec<Element<'t>>, f: &mut F)
+where
+    F: FnMut(&mut Vec<Element<'t>>),
+{
+    for element in elements.iter_mut() {
+        for_each_list_mut(element, &mut |list| transform_lists_inner(list, f));
+    }
+
+    f(elements);
+}
+
+/// Calls the given function on each list of child elements.
+fn for_each_list_mut<'t, F>(element: &mut Element<'t>, f: &mut F)
+where
+    F: FnMut(&mut Vec<Element<'t>>),
+{
+    match element {
+        Element::Container(container) => f(container.elements_mut()),
+        Element::Table(table) => {
+            for row in &mut table.rows {
+                for cell in &mut row.cells {
+                    f(&mut cell.elements);
+                }
+            }
+        }
+        Element::TabView(tabs) => {
+            for tab in tabs {
+                f(&mut tab.elements);
+            }
+        }
+        Element::Anchor { elements, .. }
+        | Element::Collapsible { elements, .. }
+        | Element::Quote { elements, .. }
+        | Element::RadioGroup { elements, .. }
+        | Element::Color { elements, .. }
+        | Element::Size { elements, .. }
+        | Element::Include { elements, .. } => f(elements),
+        Element::List { items, .. } => {
+            // Sub-lists occupy a single slot, so each
+            // replacement element becomes its own list item.
+            for item in mem::take(items) {
+                match item {
+                    ListItem::Elements {
+                        attributes,
+                        mut elements,
+                    } => {
+                        f(&mut elements);
+                        items.push(ListItem::Elements {
+                            attributes,
+                            elements,
+                        });
+                    }
+                    ListItem::SubList { element } => {
+                        let mut elements = vec![*element];
+                        f(&mut elements);
+                        items.extend(elements.into_iter().map(|element| {
+                            ListItem::SubList {
+                                element: Box::new(element),
+                            }
+                        }));
+                    }
+                }
+            }
+        }
+        Element::DefinitionList(items) => {
+            for item in items {
+                f(&mut item.key_elements);
+                f(&mut item.value_elements);
+            }
+        }
+        // No child elements, or partials, which are not descended into
+        Element::Module(_)
+        | Element::Text(_)
+        | Element::Raw(_)
+        | Element::HtmlEntity(_)
+        | Element::Variable(_)
+        | Element::Email(_)
+        | Element::AnchorName(_)
+        | Element::Link { .. }
+        | Element::Image { .. }
+        | Element::Media { .. }
+        | Element::Gallery { .. }
+        | Element::RadioButton { .. }
+        | Element::CheckBox { .. }
+        | Element::TableOfContents { .. }
+        | Element::Footnote
+        | Element::FootnoteRepeat(_)
+        | Element::FootnoteBlock { .. }
+        | Element::BibliographyCite { .. }
+        | Element::BibliographyBlock { .. }
+        | Element::User { .. }
+        | Element::Date { .. }
+        | Element::Code { .. }
+        | Element::CodeBlob { .. }
+        | Element::Math { .. }
+        | Element::MathInline { .. }
+        | Element::Calc { .. }
+        | Element::EquationReference(_)
+        | Element::Embed(_)
+        | Element::Html { .. }
+        | Element::Iframe { .. }
+        | Element::Style(_)
+        | Element::Redirect { .. }
+        | Element::LineBreak
+        | Element::LineBreaks(_)
+        | Element::ClearFloat(_)
+        | Element::HorizontalRule
+        | Element::Partial(_) => (),
+    }
+}
+
+impl<'t> SyntaxTree<'t> {
+    /// Replaces each element in this tree using [`transform()`].
+    ///
+    /// This covers the same elements as [`SyntaxTree::walk`].
+    pub fn transform<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Element<'t>) -> Elements<'t>,
+    {
+        transform_elements(&mut self.elements, &mut f);
+
+        for footnote in &mut self.footnotes {
+            transform_elements(footnote, &mut f);
+        }
+
+        for bibliography in self.bibliographies.iter_mut() {
+            for elements in bibliography.elements_mut() {
+                transform_elements(elements, &mut f);
+            }
+        }
+    }
+
+    /// Replaces each list of sibling elements in this tree using [`transform_lists()`].
+    ///
+    /// This covers the same elements as [`SyntaxTree::walk`].
+    pub fn transform_lists<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut Vec<Element<'t>>),
+    {
+        transform_lists_inner(&mut self.elements, &mut f);
+
+        for footnote in &mut self.footnotes {
+            transform_lists_inner(footnote, &mut f);
+        }
+
+        for bibliography in self.bibliographies.iter_mut() {
+            for elements in bibliography.elements_mut() {
+                transform_lists_inner(elements, &mut f);
+            }
+        }
+    }
+}
+
+#[test]
+fn visitor() {
+    use crate::tree::{Container, ContainerType, LinkLabel, LinkLocation, LinkType};
+    use std::borrow::Cow;
+
+    fn tree() -> Vec<Element<'static>> {
+        vec![
+            Element::Container(Container::new(
+                ContainerType::Paragraph,
+                vec![
+                    text!("apple banana"),
+                    Element::Link {
+                        ltype: LinkType::Direct,
+                        link: LinkLocation::Url(cow!("http://example.com/")),
+                        label: LinkLabel::Url(None),
+                        target: None,
+                    },
+                    Element::LineBreak,
+                ],
+                Default::default(),
+            )),
+            Element::Color {
+                color: cow!("blue"),
+                elements: vec![text!("cherry")],
+            },
+        ]
+    }
+
+    // Word counter
+    struct Words(usize);
+
+    impl<'t> Visitor<'t> for Words {
+        fn visit_element(&mut self, element: &Element<'t>) {
+            if let Element::Text(text) = element {
+                self.0 += text.split_whitespace().count();
+            }
+
+            walk_element(self, element);
+        }
+    }
+
+    let mut words = Words(0);
+    words.visit_elements(&tree());
+    assert_eq!(words.0, 3);
+
+    // Link rewriter
+    struct Secure;
+
+    impl<'t> VisitorMut<'t> for Secure {
+        fn visit_element_mut(&mut self, element: &mut Element<'t>) {
+            if let Element::Link {
+                link: LinkLocation::Url(url),
+                ..
+            } = element
+            {
+                if let Some(rest) = url.strip_prefix("http://") {
+                    *url = Cow::Owned(format!("https://{rest}"));
+                }
+            }
+
+            walk_element_mut(self, element);
+        }
+    }
+
+    let mut elements = tree();
+    Secure.visit_elements_mut(&mut elements);
+    assert!(matches!(
+        elements[0],
+        Element::Container(ref container)
+            if matches!(
+                container.elements()[1],
+                Element::Link { link: LinkLocation::Url(ref url), .. }
+                    if url == "https://example.com/",
+            ),
+    ));
+
+    // Transformer
+    let mut elements = tree();
+    transform(&mut elements, |element| match element {
+        Element::LineBreak => Elements::None,
+        Element::Color { elements, .. } => Elements::Multiple(elements),
+        Element::Text(text) => {
+            Elements::Single(Element::Text(Cow::Owned(text.to_uppercase())))
+        }
+        element => Elements::Single(element),
+    });
+    assert_eq!(elements.len(), 2);
+    assert_eq!(elements[1], text!("CHERRY"));
+    match elements[0] {
+        Element::Container(ref container) => {
+            assert_eq!(container.elements().len(), 2);
+            assert_eq!(container.elements()[0], text!("APPLE BANANA"));
+        }
+        _ => panic!("Transformed element is not a container"),
+    }
+
+    // List transformer
+    let mut elements = tree();
+    transform_lists(&mut elements, |elements| {
+        if elements.len() > 1 {
+            elements.reverse();
+        }
+    });
+    assert!(matches!(elements[0], Element::Color { .. }));
+    match elements[1] {
+        Element::Container(ref container) => {
+            assert_eq!(container.elements()[0], Element::LineBreak);
+            assert_eq!(container.elements()[2], text!("apple banana"));
+        }
+        _ => panic!("Transformed element is not a container"),
+    }
+}
@@ -21,7 +21,6 @@
 use std::borrow::Cow;
 use wikidot_normalize::normalize;
 
-#[cfg(feature = "html")]
 use crate::tree::LinkLocation;
 
 pub const URL_SCHEMES: [&str; 19] = [
@@ -69,7 +68,6 @@ pub fn dangerous_scheme(url: &str) -> bool {
         .unwrap_or(false)
 }
 
-#[cfg(feature = "html")]
 pub fn normalize_link<'a>(
     link: &'a LinkLocation<'a>,
     helper: &dyn BuildSiteUrl,
@@ -101,6 +99,75 @@ pub fn normalize_href(url: &str) -> Cow<str> {
     }
 }
 
+/// Splits trailing punctuation off of a bare URL.
+///
+/// This follows the usual linkifier rules, where sentence punctuation
+/// directly after a URL (such as `.`, `,`, or `;`) is not part of it.
+/// A closing parenthesis or angle bracket is only removed if it has no
+/// matching opening one within the URL, so links like
+/// `https://en.wikipedia.org/wiki/Foo_(bar)` remain intact.
+///
+/// Returns the URL and the removed suffix, which may be empty.
+/// The scheme itself is never trimmed.
+pub fn split_url_suffix(url: &str) -> (&str, &str) {
+    let minimum = url.find("://").map(|idx| idx + 3).unwrap_or(0);
+    let mut end = url.len();
+
+    // Count brackets once, then adjust as characters are removed,
+    // so long runs of trailing brackets take linear time.
+    let count = |ch| url.bytes().filter(|&b| b == ch).count();
+    let mut parens = (count(b'('), count(b')'));
+    let mut angles = (count(b'<'), count(b'>'));
+
+    while end > minimum {
+        let Some(ch) = url[..end].chars().next_back() else {
+            break;
+        };
+
+        let strip = match ch {
+            '.' | ',' | ';' | ':' | '!' | '?' | '\'' | '"' => true,
+            ')' => parens.1 > parens.0,
+            '>' => angles.1 > angles.0,
+            _ => false,
+        };
+
+        if !strip {
+            break;
+        }
+
+        match ch {
+            ')' => parens.1 -= 1,
+            '>' => angles.1 -= 1,
+            _ => (),
+        }
+
+        end -= ch.len_utf8();
+    }
+
+    // Don't produce a URL which is only the scheme
+    if end == minimum {
+        return (url, "");
+    }
+
+    url.split_at(end)
+}
+
+/// Splits a path to a file attached to a page, such as
+/// `/local--files/page/file.png`, into its page and filename.
+///
+/// Returns `None` if this is not a path to an attached file.
+pub fn split_local_file(url: &str) -> Option<(&str, &str)> {
+    let path = url.strip_prefix('/').unwrap_or(url);
+    let path = path.strip_prefix("local--files/")?;
+    let (page, file) = path.split_once('/')?;
+
+    if page.is_empty() || file.is_empty() || file.contains('/') {
+        return None;
+    }
+
+    Some((page, file))
+}
+
 pub trait BuildSiteUrl {
     fn build_url(&self, site: &str, path: &str) -> String;
 }
@@ -129,3 +196,128 @@ fn detect_dangerous_schemes() {
     check!("data:text/html,<script>alert('XSS');</script>", true);
     check!("DATA:text/html,<script>alert('XSS');</script>", true);
 }
+
+#[test]
+fn url_suffixes() {
+    macro_rules! check {
+        ($input:expr, $url:expr, $suffix:expr $(,)?) => {
+            assert_eq!(
+                split_url_suffix($input),
+                ($url, $suffix),
+                "For input {:?}, URL suffix splitting failed",
+                $input,
+            )
+        };
+    }
+
+    // No suffix
+    check!("https://example.com", "https://example.com", "");
+    check!("https://example.com/", "https://example.com/", "");
+    check!(
+        "https://example.com/a?b=c&d",
+        "https://example.com/a?b=c&d",
+        ""
+    );
+    check!("https://example.com/#top", "https://example.com/#top", "");
+
+    // Sentence punctuation
+    check!("https://example.com.", "https://example.com", ".");
+    check!("https://example.com,", "https://example.com", ",");
+    check!("https://example.com;", "https://example.com", ";");
+    check!("https://example.com:", "https://example.com", ":");
+    check!("https://example.com!", "https://example.com", "!");
+    check!("https://example.com?", "https://example.com", "?");
+    check!("https://example.com/page'", "https://example.com/page", "'");
+    check!(
+        "https://example.com/page\"",
+        "https://example.com/page",
+        "\""
+    );
+    check!("https://example.com/...", "https://example.com/", "...");
+    check!("https://example.com/?!", "https://example.com/", "?!");
+
+    // Internal punctuation is kept
+    check!("https://example.com/a.b", "https://example.com/a.b", "");
+    check!("https://example.com:8080/", "https://example.com:8080/", "");
+    check!("https://example.com/a,b;c", "https://example.com/a,b;c", "");
+
+    // Parentheses
+    check!("https://example.com)", "https://example.com", ")");
+    check!("https://example.com).", "https://example.com", ").");
+    check!(
+        "https://en.wikipedia.org/wiki/Foo_(bar)",
+        "https://en.wikipedia.org/wiki/Foo_(bar)",
+        "",
+    );
+    check!(
+        "https://en.wikipedia.org/wiki/Foo_(bar).",
+        "https://en.wikipedia.org/wiki/Foo_(bar)",
+        ".",
+    );
+    check!(
+        "https://en.wikipedia.org/wiki/Foo_(bar)),",
+        "https://en.wikipedia.org/wiki/Foo_(bar)",
+        "),",
+    );
+    check!(
+        "https://example.com/(a)(b))",
+        "https://example.com/(a)(b)",
+        ")"
+    );
+
+    // Angle brackets
+    check!("https://example.com/>", "https://example.com/", ">");
+    check!("https://example.com/<a>", "https://example.com/<a>", "");
+    check!("https://example.com/<a>>.", "https://example.com/<a>", ">.");
+
+    // Long runs of brackets are trimmed in linear time
+    let suffix = ")".repeat(100_000);
+    let url = format!("https://example.com/(a){suffix}");
+    assert_eq!(
+        split_url_suffix(&url),
+        ("https://example.com/(a)", suffix.as_str()),
+    );
+
+    let suffix = ">".repeat(100_000);
+    let url = format!("https://example.com/{suffix}");
+    assert_eq!(
+        split_url_suffix(&url),
+        ("https://example.com/", suffix.as_str())
+    );
+
+    // Scheme is never trimmed
+    check!("http://.", "http://.", "");
+    check!("ftp://...", "ftp://...", "");
+    check!("https://).", "https://).", "");
+
+    // Non-ASCII
+    check!("https://例え.jp/ページ。", "https://例え.jp/ページ。", "");
+    check!("https://例え.jp/ページ.", "https://例え.jp/ページ", ".");
+}
+
+#[test]
+fn local_files() {
+    macro_rules! check {
+        ($input:expr, $result:expr $(,)?) => {
+            assert_eq!(
+                split_local_file($input),
+                $result,
+                "For input {:?}, local file splitting failed",
+                $input,
+            )
+        };
+    }
+
+    check!("/local--files/page/file.png", Some(("page", "file.png")));
+    check!("local--files/page/file.png", Some(("page", "file.png")));
+    check!(
+        "/local--files/fragment:a/b.pdf",
+        Some(("fragment:a", "b.pdf"))
+    );
+    check!("/local--files/page/", None);
+    check!("/local--files//file.png", None);
+    check!("/local--files/page/dir/file.png", None);
+    check!("/local--files/page", None);
+    check!("/page/file.png", None);
+    check!("https://example.com/local--files/page/file.png", None);
+}
@@ -21,13 +21,17 @@
 macro_rules! js_to_rust {
     ($js:expr) => {{
         use crate::wasm::error::error_to_js;
-        serde_wasm_bindgen::from_value($js).map_err(error_to_js)
+        use crate::FtmlError;
+        serde_wasm_bindgen::from_value($js)
+            .map_err(|error| error_to_js(FtmlError::Ffi(error.to_string())))
     }};
 }
 
 macro_rules! rust_to_js {
     ($object:expr) => {{
         use crate::wasm::error::error_to_js;
-        serde_wasm_bindgen::to_value(&$object).map_err(error_to_js)
+        use crate::FtmlError;
+        serde_wasm_bindgen::to_value(&$object)
+            .map_err(|error| error_to_js(FtmlError::Ffi(error.to_string())))
     }};
 }
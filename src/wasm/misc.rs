@@ -27,3 +27,18 @@ use crate::info;
 pub fn version() -> String {
     info::VERSION.clone()
 }
+
+#[wasm_bindgen]
+pub fn token_registry() -> Result<JsValue, JsValue> {
+    rust_to_js!(crate::parsing::token_registry())
+}
+
+#[wasm_bindgen]
+pub fn rule_registry() -> Result<JsValue, JsValue> {
+    rust_to_js!(crate::parsing::rule_registry())
+}
+
+#[wasm_bindgen]
+pub fn block_registry() -> Result<JsValue, JsValue> {
+    rust_to_js!(crate::parsing::block_registry())
+}
@@ -36,12 +36,12 @@ mod prelude {
     pub use wasm_bindgen::JsCast;
 }
 
-pub use self::misc::version;
+pub use self::misc::{block_registry, rule_registry, token_registry, version};
 pub use self::parsing::{parse, ParseOutcome, SyntaxTree};
-pub use self::preproc::preprocess;
-pub use self::render::render_text;
+pub use self::preproc::{preprocess, preprocess_with_settings};
+pub use self::render::{render_markdown, render_text, render_text_partial};
 pub use self::settings::WikitextSettings;
 pub use self::tokenizer::{tokenize, Tokenization};
 
 #[cfg(feature = "html")]
-pub use self::render::render_html;
+pub use self::render::{render_html, render_html_partial};
@@ -22,16 +22,55 @@ use super::page_info::PageInfo;
 use super::prelude::*;
 use super::settings::WikitextSettings;
 use super::tokenizer::Tokenization;
-use crate::parsing::{ParseError as RustParseError, ParseOutcome as RustParseOutcome};
+use crate::parsing::{
+    ParseError as RustParseError, ParseErrorKind, ParseOutcome as RustParseOutcome,
+    ParseReport as RustParseReport, ParseSeverity, Token,
+};
 use crate::tree::SyntaxTree as RustSyntaxTree;
 use crate::utf16::Utf16IndexMap;
 use crate::Tokenization as RustTokenization;
+use std::ops::Range;
 use std::sync::Arc;
 
+#[wasm_bindgen(typescript_custom_section)]
+const PARSE_WARNING_TYPE: &str = r#"
+/**
+ * A problem with the wikitext found while parsing.
+ *
+ * The span is in UTF-16 code units, so it can be used
+ * to index the original JavaScript string directly.
+ */
+export interface ParseWarning {
+    rule: string;
+    kind: string;
+    severity: "warning" | "error";
+    token: string;
+    span: { start: number; end: number };
+    slice: string;
+}
+"#;
+
+/// A parse warning, as exposed to Javascript.
+///
+/// Only issues of at least [`ParseSeverity::Warning`] are included,
+/// since rule fallbacks are expected in normal wikitext.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+struct ParseWarning {
+    rule: String,
+    kind: ParseErrorKind,
+    severity: ParseSeverity,
+    token: Token,
+    span: Range<usize>,
+    slice: String,
+}
+
 #[wasm_bindgen]
 #[derive(Debug, Clone)]
 pub struct ParseOutcome {
     inner: Arc<RustParseOutcome<RustSyntaxTree<'static>>>,
+    report: Arc<RustParseReport>,
+    warnings: Arc<Vec<ParseWarning>>,
 }
 
 #[wasm_bindgen]
@@ -40,6 +79,8 @@ impl ParseOutcome {
     pub fn copy(&self) -> ParseOutcome {
         ParseOutcome {
             inner: Arc::clone(&self.inner),
+            report: Arc::clone(&self.report),
+            warnings: Arc::clone(&self.warnings),
         }
     }
 
@@ -56,6 +97,21 @@ impl ParseOutcome {
     pub fn errors(&self) -> Result<JsValue, JsValue> {
         rust_to_js!(self.inner.errors())
     }
+
+    #[wasm_bindgen]
+    pub fn rejected(&self) -> bool {
+        self.inner.is_rejected()
+    }
+
+    #[wasm_bindgen]
+    pub fn report(&self) -> Result<JsValue, JsValue> {
+        rust_to_js!(*self.report)
+    }
+
+    #[wasm_bindgen(unchecked_return_type = "ParseWarning[]")]
+    pub fn warnings(&self) -> Result<JsValue, JsValue> {
+        rust_to_js!(*self.warnings)
+    }
 }
 
 #[wasm_bindgen]
@@ -82,6 +138,11 @@ impl SyntaxTree {
     pub fn data(&self) -> Result<JsValue, JsValue> {
         rust_to_js!(*self.inner)
     }
+
+    #[wasm_bindgen]
+    pub fn statistics(&self) -> Result<JsValue, JsValue> {
+        rust_to_js!(self.inner.statistics())
+    }
 }
 
 // Exported functions
@@ -96,19 +157,35 @@ pub fn parse(
     let tokenization = tokens.get();
     let page_info = page_info.get();
     let settings = settings.get();
-    let (syntax_tree, errors) = crate::parse(tokenization, page_info, settings).into();
+    let outcome = crate::parse(tokenization, page_info, settings);
+    let rejected = outcome.is_rejected();
+    let (syntax_tree, errors) = outcome.into();
 
     // Deep-clone AST to make it owned, so it can be
     // safely passed to JS, where it will live for an unknown time.
     let syntax_tree = syntax_tree.to_owned();
 
-    // Convert errors to use UTF-16 indices
-    let errors = convert_errors_utf16(tokenization, errors);
-
-    // Create inner wrapper
-    let inner = Arc::new(RustParseOutcome::new(syntax_tree, errors));
-
-    Ok(ParseOutcome { inner })
+    // Summarize errors, while the spans still match the text
+    let report = RustParseReport::new(&errors, tokenization.full_text().inner());
+
+    // Convert errors and report to use UTF-16 indices,
+    // and gather warnings with their source text
+    let (errors, report, warnings) = convert_errors_utf16(tokenization, errors, report);
+
+    // Create inner wrappers
+    let inner = Arc::new(if rejected {
+        RustParseOutcome::rejected(syntax_tree, errors)
+    } else {
+        RustParseOutcome::new(syntax_tree, errors)
+    });
+    let report = Arc::new(report);
+    let warnings = Arc::new(warnings);
+
+    Ok(ParseOutcome {
+        inner,
+        report,
+        warnings,
+    })
 }
 
 // Utility functions
@@ -116,18 +193,41 @@ pub fn parse(
 fn convert_errors_utf16(
     tokenization: &RustTokenization,
     errors: Vec<RustParseError>,
-) -> Vec<RustParseError> {
+    report: RustParseReport,
+) -> (Vec<RustParseError>, RustParseReport, Vec<ParseWarning>) {
     // As an optimization, we can avoid the (relatively expensive) Utf16IndexMap creation
     // if we know there are no errors to map indices of.
     if errors.is_empty() {
-        return errors;
+        return (errors, report, vec![]);
     }
 
     let full_text = tokenization.full_text().inner();
     let utf16_map = Utf16IndexMap::new(full_text);
 
-    errors
+    // The slices must be taken before the spans are converted
+    let warnings = errors
+        .iter()
+        .filter(|err| err.severity() >= ParseSeverity::Warning)
+        .map(|err| {
+            let span = err.span();
+
+            ParseWarning {
+                rule: str!(err.rule()),
+                kind: err.kind(),
+                severity: err.severity(),
+                token: err.token(),
+                slice: str!(&full_text[span.clone()]),
+                span: utf16_map.get_index(span.start)..utf16_map.get_index(span.end),
+            }
+        })
+        .collect();
+
+    let errors = errors
         .into_iter()
         .map(|err| err.to_utf16_indices(&utf16_map))
-        .collect()
+        .collect();
+
+    let report = report.to_utf16_indices(&utf16_map);
+
+    (errors, report, warnings)
 }
@@ -19,9 +19,16 @@
  */
 
 use super::prelude::*;
+use super::settings::WikitextSettings;
 
 #[wasm_bindgen]
 pub fn preprocess(mut text: String) -> String {
     crate::preprocess(&mut text);
     text
 }
+
+#[wasm_bindgen]
+pub fn preprocess_with_settings(mut text: String, settings: WikitextSettings) -> String {
+    crate::preprocess_with_settings(&mut text, settings.get());
+    text
+}
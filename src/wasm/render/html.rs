@@ -29,6 +29,7 @@ use super::super::prelude::*;
 use super::super::settings::WikitextSettings;
 use crate::render::html::{HtmlOutput as RustHtmlOutput, HtmlRender};
 use crate::render::Render;
+use crate::tree::Element;
 use std::sync::Arc;
 
 #[wasm_bindgen]
@@ -60,6 +61,21 @@ impl HtmlOutput {
     pub fn backlinks(&self) -> Result<JsValue, JsValue> {
         rust_to_js!(self.inner.backlinks)
     }
+
+    #[wasm_bindgen]
+    pub fn table_of_contents(&self) -> Option<String> {
+        self.inner.table_of_contents.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn footnotes(&self) -> Option<String> {
+        self.inner.footnotes.clone()
+    }
+
+    #[wasm_bindgen]
+    pub fn accessibility(&self) -> Result<JsValue, JsValue> {
+        rust_to_js!(self.inner.accessibility)
+    }
 }
 
 // Function exports
@@ -79,3 +95,19 @@ pub fn render_html(
         inner: Arc::new(html),
     }
 }
+
+#[wasm_bindgen]
+pub fn render_html_partial(
+    elements: JsValue,
+    page_info: PageInfo,
+    settings: WikitextSettings,
+) -> Result<HtmlOutput, JsValue> {
+    let elements: Vec<Element> = js_to_rust!(elements)?;
+    let page_info = page_info.get();
+    let settings = settings.get();
+    let html = HtmlRender.render_partial(&elements, page_info, settings, 0);
+
+    Ok(HtmlOutput {
+        inner: Arc::new(html),
+    })
+}
@@ -28,8 +28,10 @@ use super::page_info::PageInfo;
 use super::parsing::SyntaxTree;
 use super::prelude::*;
 use super::settings::WikitextSettings;
+use crate::render::markdown::MarkdownRender;
 use crate::render::text::TextRender;
 use crate::render::Render;
+use crate::tree::Element;
 
 // Function exports
 
@@ -46,3 +48,30 @@ pub fn render_text(
 
     text
 }
+
+#[wasm_bindgen]
+pub fn render_text_partial(
+    elements: JsValue,
+    page_info: PageInfo,
+    settings: WikitextSettings,
+) -> Result<String, JsValue> {
+    let elements: Vec<Element> = js_to_rust!(elements)?;
+    let page_info = page_info.get();
+    let settings = settings.get();
+    let text = TextRender.render_partial(&elements, page_info, settings, 0);
+
+    Ok(text)
+}
+
+#[wasm_bindgen]
+pub fn render_markdown(
+    syntax_tree: SyntaxTree,
+    page_info: PageInfo,
+    settings: WikitextSettings,
+) -> String {
+    let tree = syntax_tree.get();
+    let page_info = page_info.get();
+    let settings = settings.get();
+
+    MarkdownRender.render(tree, page_info, settings)
+}
@@ -18,9 +18,11 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+use super::error::error_to_js;
 use super::prelude::*;
 use crate::settings::{
-    WikitextMode as RustWikitextMode, WikitextSettings as RustWikitextSettings,
+    SettingsPresets, WikitextMode as RustWikitextMode,
+    WikitextSettings as RustWikitextSettings,
 };
 use std::sync::Arc;
 
@@ -53,17 +55,27 @@ impl WikitextSettings {
 
     #[wasm_bindgen]
     pub fn from_mode(mode: String) -> Result<WikitextSettings, JsValue> {
-        let rust_mode = match mode.as_str() {
-            "page" => RustWikitextMode::Page,
-            "draft" => RustWikitextMode::Draft,
-            "forum-post" => RustWikitextMode::ForumPost,
-            "direct-message" => RustWikitextMode::DirectMessage,
-            "list" => RustWikitextMode::List,
-            _ => return Err(JsValue::from_str("Unknown mode")),
+        let rust_mode = match RustWikitextMode::from_name(&mode) {
+            Some(mode) => mode,
+            None => return Err(JsValue::from_str("Unknown mode")),
         };
 
         Ok(WikitextSettings {
             inner: Arc::new(RustWikitextSettings::from_mode(rust_mode)),
         })
     }
+
+    #[wasm_bindgen]
+    pub fn from_named_preset(
+        presets: JsValue,
+        name: String,
+    ) -> Result<WikitextSettings, JsValue> {
+        let presets: SettingsPresets = js_to_rust!(presets)?;
+        let settings = RustWikitextSettings::from_named_preset(&presets, &name)
+            .map_err(error_to_js)?;
+
+        Ok(WikitextSettings {
+            inner: Arc::new(settings),
+        })
+    }
 }